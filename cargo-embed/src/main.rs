@@ -392,14 +392,9 @@ fn main_try(metadata: Arc<Mutex<Metadata>>, offset: UtcOffset) -> Result<()> {
             None
         };
 
-        let rtt_header_address = if let Ok(mut file) = File::open(path) {
-            if let Some(address) = rttui::app::App::get_rtt_symbol(&mut file) {
-                ScanRegion::Exact(address as u32)
-            } else {
-                ScanRegion::Ram
-            }
-        } else {
-            ScanRegion::Ram
+        let rtt_header_address = match fs::read(path) {
+            Ok(elf) => ScanRegion::from_elf(&elf)?,
+            Err(_) => ScanRegion::Ram,
         };
 
         let mut rtt = rtt_attach(session.clone(), config.rtt.timeout, &rtt_header_address)
@@ -704,6 +699,7 @@ fn flash(
                         fp.finish()
                     };
                 }
+                StartedVerifying | DataVerified { .. } | FailedVerifying | FinishedVerifying => {}
                 DiagnosticMessage { .. } => todo!(),
             }
         });
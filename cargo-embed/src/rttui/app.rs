@@ -7,10 +7,7 @@ use crossterm::{
 use probe_rs::rtt::RttChannel;
 use probe_rs::Core;
 use std::{fmt::write, path::PathBuf, sync::mpsc::RecvTimeoutError};
-use std::{
-    io::{Read, Seek, Write},
-    time::Duration,
-};
+use std::{io::Write, time::Duration};
 use tui::{
     backend::CrosstermBackend,
     layout::{Constraint, Direction, Layout},
@@ -138,24 +135,6 @@ impl App {
         })
     }
 
-    pub fn get_rtt_symbol<T: Read + Seek>(file: &mut T) -> Option<u64> {
-        let mut buffer = Vec::new();
-        if file.read_to_end(&mut buffer).is_ok() {
-            if let Ok(binary) = goblin::elf::Elf::parse(buffer.as_slice()) {
-                for sym in &binary.syms {
-                    if let Some(name) = binary.strtab.get_at(sym.st_name) {
-                        if name == "_SEGGER_RTT" {
-                            return Some(sym.st_value);
-                        }
-                    }
-                }
-            }
-        }
-
-        log::warn!("No RTT header info was present in the ELF file. Does your firmware run RTT?");
-        None
-    }
-
     pub fn render(&mut self, defmt_state: Option<&DefmtInformation>) {
         let input = self.current_tab().input().to_owned();
         let has_down_channel = self.current_tab().has_down_channel();
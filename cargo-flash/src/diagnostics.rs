@@ -172,6 +172,12 @@ pub(crate) fn render_diagnostics(error: OperationError) {
                 format!("Try specifying a speed lower than {speed} kHz")
             ],
         ),
+        OperationError::FailedToEnableCjtag(_e) => (
+            error.to_string(),
+            vec![
+                "cJTAG is only supported by FTDI based probes. Make sure the probe is connected with the two-wire TMSC/TCKC interface, or run without `--cjtag`.".into()
+            ],
+        ),
         OperationError::AttachingFailed { source, connect_under_reset } => match source {
             ProbeRsError::ChipNotFound(RegistryError::ChipAutodetectFailed) => (
                 error.to_string(),
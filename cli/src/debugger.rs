@@ -9,7 +9,8 @@ use num_traits::Num;
 use probe_rs::{
     architecture::arm::Dump,
     debug::{
-        debug_info::DebugInfo, registers::DebugRegisters, stack_frame::StackFrame, VariableName,
+        debug_info::DebugInfo, registers::DebugRegisters, stack_frame::StackFrame, CrashReport,
+        VariableName,
     },
     Core, CoreType, InstructionSet, MemoryInterface, RegisterDescription, RegisterId,
     RegisterValue,
@@ -102,6 +103,9 @@ impl DebugCli {
                             capstone::arch::riscv::ArchExtraMode::RiscVC,
                         ))
                         .build(),
+                    InstructionSet::Xtensa => {
+                        return Err(anyhow!("Disassembly is not supported for Xtensa").into())
+                    }
                 }
                 .map_err(|err| anyhow!("Error creating capstone: {:?}", err))?;
 
@@ -150,7 +154,7 @@ impl DebugCli {
 
                     if cli_data.core.architecture() == probe_rs::Architecture::Arm {
                         match cli_data.core.core_type() {
-                            CoreType::Armv6m | CoreType::Armv7em | CoreType::Armv7m | CoreType::Armv8m | CoreType::Armv7a | CoreType::Armv8a => {
+                            CoreType::Armv6m | CoreType::Armv7em | CoreType::Armv7m | CoreType::Armv8m | CoreType::Armv7a | CoreType::Armv7r | CoreType::Armv8a => {
                                 // Unwrap is safe here because ARM always defines this register
                                 let psr_desc = cli_data.core.registers().psr().unwrap();
 
@@ -471,6 +475,32 @@ impl DebugCli {
             },
         });
 
+        cli.add_command(Command {
+            name: "crash",
+            help_text: "Show the location of a panic or HardFault",
+
+            function: |cli_data, _args| {
+                match cli_data.state {
+                    DebugState::Halted(_) => {
+                        if let Some(di) = &cli_data.debug_info {
+                            match CrashReport::from_core(&mut cli_data.core, di)
+                                .map_err(anyhow::Error::from)?
+                            {
+                                Some(report) => print!("{report}"),
+                                None => println!("The core did not crash."),
+                            }
+                        } else {
+                            println!("No debug information present!");
+                        }
+                    }
+                    DebugState::Running => {
+                        println!("Core must be halted for this command.");
+                    }
+                }
+                Ok(CliState::Continue)
+            },
+        });
+
         cli.add_command(Command {
             name: "regs",
             help_text: "Show CPU register values",
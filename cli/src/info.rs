@@ -10,7 +10,7 @@ use probe_rs::{
             dp::{DPIDR, TARGETID},
             memory::{Component, CoresightComponent, PeripheralType},
             sequences::DefaultArmSequence,
-            ApAddress, ApInformation, ArmProbeInterface, DpAddress, MemoryApInformation, Register,
+            ApInformation, ArmProbeInterface, DpAddress, MemoryApInformation, Register,
         },
         riscv::communication_interface::RiscvCommunicationInterface,
     },
@@ -181,16 +181,10 @@ fn show_arm_info(interface: &mut dyn ArmProbeInterface) -> Result<()> {
     let mut tree = Tree::new(dp_node);
 
     let dp = DpAddress::Default;
-    let num_access_ports = interface.num_access_ports(dp)?;
+    let access_ports = interface.access_ports(dp)?;
 
-    for ap_index in 0..num_access_ports {
-        let ap = ApAddress {
-            ap: ap_index as u8,
-            dp,
-        };
-        let access_port = GenericAp::new(ap);
-
-        let ap_information = interface.ap_information(access_port)?;
+    for ap_information in access_ports {
+        let access_port = GenericAp::new(ap_information.address());
 
         match ap_information {
             ApInformation::MemoryAp(MemoryApInformation {
@@ -201,8 +195,8 @@ fn show_arm_info(interface: &mut dyn ArmProbeInterface) -> Result<()> {
             }) => {
                 let mut ap_nodes = Tree::new(format!("{} MemoryAP", address.ap));
 
-                if *device_enabled {
-                    match handle_memory_ap(access_port.into(), *debug_base_address, interface) {
+                if device_enabled {
+                    match handle_memory_ap(access_port.into(), debug_base_address, interface) {
                         Ok(component_tree) => ap_nodes.push(component_tree),
                         Err(e) => ap_nodes.push(format!("Error during access: {e}")),
                     };
@@ -214,14 +208,9 @@ fn show_arm_info(interface: &mut dyn ArmProbeInterface) -> Result<()> {
             }
 
             ApInformation::Other { address, idr } => {
-                let designer = idr.DESIGNER;
-
-                let cc = (designer >> 7) as u8;
-                let id = (designer & 0x7f) as u8;
+                let jep = idr.designer();
 
-                let jep = jep106::JEP106Code::new(cc, id);
-
-                let ap_type = if designer == 0x43b {
+                let ap_type = if idr.DESIGNER == 0x43b {
                     format!("{:?}", idr.TYPE)
                 } else {
                     format!("{:#x}", idr.TYPE as u8)
@@ -270,8 +259,12 @@ fn coresight_component_tree(
 ) -> Result<Tree<String>> {
     let tree = match &component {
         Component::GenericVerificationComponent(_) => Tree::new("Generic".to_string()),
-        Component::Class1RomTable(_, table) => {
-            let mut rom_table = Tree::new("ROM Table (Class 1)".to_string());
+        Component::Class1RomTable(_, table) | Component::Class9RomTable(_, table) => {
+            let class = match component {
+                Component::Class1RomTable(..) => 1,
+                _ => 9,
+            };
+            let mut rom_table = Tree::new(format!("ROM Table (Class {class})"));
 
             for entry in table.entries() {
                 let component = entry.component().clone();
@@ -15,7 +15,7 @@ use probe_rs::{
     architecture::arm::{component::TraceSink, swo::SwoConfig},
     debug::debug_info::DebugInfo,
     flashing::{erase_all, BinOptions, FileDownloadError, Format},
-    MemoryInterface, Probe,
+    MemoryInterface, Permissions, Probe, ResetType,
 };
 
 use probe_rs_cli_util::{
@@ -76,6 +76,10 @@ enum Subcommand {
 
         /// Whether the reset pin should be asserted or deasserted. If left open, just pulse it
         assert: Option<bool>,
+
+        /// How the target is reset: sequence, hardware, system or core. If left open, the default of the target is used
+        #[clap(long, value_parser = parse_reset_type)]
+        reset_type: Option<ResetType>,
     },
     /// Run a GDB server
     Gdb {
@@ -157,6 +161,11 @@ enum Subcommand {
         #[clap(flatten)]
         common: ProbeOptions,
     },
+    /// Recover a locked target by erasing it, without attaching to the cores
+    Recover {
+        #[clap(flatten)]
+        common: ProbeOptions,
+    },
     /// Flash and run an ELF program
     #[clap(name = "run")]
     Run {
@@ -327,7 +336,8 @@ fn main() -> Result<()> {
             shared,
             common,
             assert,
-        } => reset_target_of_device(&shared, &common, assert),
+            reset_type,
+        } => reset_target_of_device(&shared, &common, assert, reset_type),
         Subcommand::Debug {
             shared,
             common,
@@ -350,7 +360,7 @@ fn main() -> Result<()> {
             disable_double_buffering,
         } => download_program_fast(
             common,
-            format.into(base_address, skip_bytes),
+            format.into(base_address, skip_bytes, &path)?,
             &path,
             chip_erase,
             disable_progressbars,
@@ -369,6 +379,7 @@ fn main() -> Result<()> {
             utc_offset,
         ),
         Subcommand::Erase { common } => erase(&common),
+        Subcommand::Recover { common } => recover(&common),
         Subcommand::Trace {
             shared,
             common,
@@ -469,11 +480,7 @@ fn download_program_fast(
 
     let mut loader = session.target().flash_loader();
 
-    match format {
-        Format::Bin(options) => loader.load_bin_data(&mut file, options),
-        Format::Elf => loader.load_elf_data(&mut file),
-        Format::Hex => loader.load_hex_data(&mut file),
-    }?;
+    loader.load_image(&mut file, format)?;
 
     run_flash_download(
         &mut session,
@@ -486,6 +493,7 @@ fn download_program_fast(
             reset_halt: false,
             log: None,
             restore_unwritten: false,
+            skip_unchanged: false,
             flash_layout_output_path: None,
             elf: None,
             work_dir: None,
@@ -507,14 +515,30 @@ fn erase(common: &ProbeOptions) -> Result<()> {
     Ok(())
 }
 
+fn recover(common: &ProbeOptions) -> Result<()> {
+    let target = common.get_target_selector()?;
+    let probe = common.attach_probe()?;
+
+    probe.recover(target, Permissions::new().allow_erase_all())?;
+
+    println!("Target recovered, all nonvolatile memory was erased.");
+
+    Ok(())
+}
+
 fn reset_target_of_device(
     shared_options: &CoreOptions,
     common: &ProbeOptions,
     _assert: Option<bool>,
+    reset_type: Option<ResetType>,
 ) -> Result<()> {
     let mut session = common.simple_attach()?;
 
-    session.core(shared_options.core)?.reset()?;
+    let mut core = session.core(shared_options.core)?;
+    match reset_type {
+        Some(reset_type) => core.reset_with(reset_type)?,
+        None => core.reset()?,
+    }
 
     Ok(())
 }
@@ -621,18 +645,33 @@ enum DownloadFileType {
     Elf,
     Hex,
     Bin,
+    Srec,
+    Uf2,
+    /// Detect the format from the file contents, files of unknown format are treated as binary.
+    Auto,
 }
 
 impl DownloadFileType {
-    fn into(self, base_address: Option<u64>, skip: Option<u32>) -> Format {
-        match self {
+    fn into(self, base_address: Option<u64>, skip: Option<u32>, path: &str) -> Result<Format> {
+        let bin = Format::Bin(BinOptions {
+            base_address,
+            skip: skip.unwrap_or(0),
+        });
+
+        Ok(match self {
             DownloadFileType::Elf => Format::Elf,
             DownloadFileType::Hex => Format::Hex,
-            DownloadFileType::Bin => Format::Bin(BinOptions {
-                base_address,
-                skip: skip.unwrap_or(0),
-            }),
-        }
+            DownloadFileType::Bin => bin,
+            DownloadFileType::Srec => Format::Srec,
+            DownloadFileType::Uf2 => Format::Uf2,
+            DownloadFileType::Auto => {
+                let contents = std::fs::read(path)
+                    .with_context(|| format!("Failed to read {path} to detect its format."))?;
+                let format = Format::detect(&contents).unwrap_or(bin);
+                tracing::info!("Detected file format: {:?}", format);
+                format
+            }
+        })
     }
 }
 
@@ -643,3 +682,15 @@ fn parse_u32(input: &str) -> Result<u32, ParseIntError> {
 fn parse_u64(input: &str) -> Result<u64, ParseIntError> {
     parse_int::parse(input)
 }
+
+fn parse_reset_type(input: &str) -> Result<ResetType, String> {
+    match &input.to_lowercase()[..] {
+        "sequence" => Ok(ResetType::Sequence),
+        "hardware" => Ok(ResetType::Hardware),
+        "system" => Ok(ResetType::System),
+        "core" => Ok(ResetType::Core),
+        _ => Err(format!(
+            "Reset type '{input}' is unknown, use sequence, hardware, system or core"
+        )),
+    }
+}
@@ -1,5 +1,7 @@
 use anyhow::{Context, Result};
 use probe_rs::flashing::FileDownloadError;
+use probe_rs::semihosting::{Semihosting, SemihostingOptions, SemihostingOutcome};
+use probe_rs::CoreStatus;
 use probe_rs_cli_util::common_options::{CargoOptions, FlashOptions, ProbeOptions};
 use probe_rs_cli_util::flash::run_flash_download;
 use probe_rs_cli_util::rtt;
@@ -37,6 +39,7 @@ pub fn run(
             reset_halt: false,
             log: None,
             restore_unwritten: false,
+            skip_unchanged: false,
             flash_layout_output_path: None,
             elf: None,
             work_dir: None,
@@ -52,7 +55,10 @@ pub fn run(
     let memory_map = session.target().memory_map.clone();
 
     let mut core = session.core(0)?;
-    core.reset()?;
+    core.reset_and_halt(Duration::from_millis(100))?;
+    // Semihosting requests only halt RISC-V cores if this is enabled.
+    core.debug_on_sw_breakpoint(true)?;
+    core.run()?;
 
     let mut rtta = match rtt::attach_to_rtt(
         &mut core,
@@ -68,20 +74,32 @@ pub fn run(
         }
     };
 
-    if let Some(rtta) = &mut rtta {
-        let mut stdout = std::io::stdout();
-        loop {
+    // Files opened by the target with semihosting are resolved relative to the working directory.
+    let mut semihosting = Semihosting::new(SemihostingOptions {
+        root: Some(std::env::current_dir()?),
+        ..Default::default()
+    });
+
+    let mut stdout = std::io::stdout();
+    loop {
+        if let Some(rtta) = &mut rtta {
             for (_ch, data) in rtta.poll_rtt_fallible(&mut core)? {
                 stdout.write_all(data.as_bytes())?;
             }
+        }
 
-            // Poll RTT with a frequency of 10 Hz
-            //
-            // If the polling frequency is too high,
-            // the USB connection to the probe can become unstable.
-            std::thread::sleep(Duration::from_millis(100));
+        if let CoreStatus::Halted(reason) = core.status()? {
+            match semihosting.handle(&mut core)? {
+                Some(SemihostingOutcome::Resumed) => continue,
+                Some(SemihostingOutcome::Exit(code)) => std::process::exit(code),
+                None => anyhow::bail!("The core halted unexpectedly: {:?}", reason),
+            }
         }
-    }
 
-    Ok(())
+        // Poll RTT with a frequency of 10 Hz
+        //
+        // If the polling frequency is too high,
+        // the USB connection to the probe can become unstable.
+        std::thread::sleep(Duration::from_millis(100));
+    }
 }
@@ -480,7 +480,30 @@ impl<P: ProtocolAdapter> DebugAdapter<P> {
                             response_body.type_ = Some(format!("{:?}", variable.type_name));
                             response_body.variables_reference = variables_reference;
                         } else {
-                            // If we made it to here, no register or variable matched the expression.
+                            // No register or variable matched the expression, so evaluate it as an expression over the variables, e.g. `device.uart.tx_buf[3]`.
+                            match expression.parse::<probe_rs::debug::Expression>().and_then(
+                                |parsed| {
+                                    parsed.evaluate(
+                                        &mut target_core.core,
+                                        &target_core.core_data.debug_info,
+                                        stack_frame,
+                                    )
+                                },
+                            ) {
+                                Ok(value) => {
+                                    response_body.memory_reference =
+                                        Some(format!("{}", value.memory_location));
+                                    response_body.result = value.value;
+                                    response_body.type_ = Some(value.type_name);
+                                }
+                                Err(error) => {
+                                    tracing::debug!(
+                                        "Could not evaluate expression '{}': {}",
+                                        expression,
+                                        error
+                                    );
+                                }
+                            }
                         }
                     }
                 }
@@ -1876,9 +1899,9 @@ pub fn get_arguments<T: DeserializeOwned, P: ProtocolAdapter>(
     let Some(raw_arguments) = &req.arguments else {
         debug_adapter.send_response::<()>(req, Err(DebuggerError::InvalidRequest))?;
         return Err(DebuggerError::Other(anyhow!(
-            "Failed to get {} arguments", req.command
+            "Failed to get {} arguments",
+            req.command
         )));
-
     };
 
     match serde_json::from_value(raw_arguments.to_owned()) {
@@ -1,4 +1,4 @@
-use std::{fs::File, path::Path};
+use std::path::Path;
 
 use super::session_data::{self, ActiveBreakpoint, BreakpointType};
 use crate::{
@@ -17,7 +17,7 @@ use crate::{
 use anyhow::{anyhow, Result};
 use probe_rs::{
     debug::{debug_info::DebugInfo, ColumnType, VerifiedBreakpoint},
-    rtt::{Rtt, ScanRegion},
+    rtt::{find_rtt_control_block_in_raw_file, Rtt, ScanRegion},
     Core, CoreStatus, Error, HaltReason,
 };
 use probe_rs_cli_util::rtt::{self, ChannelMode, DataFormat, RttActiveTarget};
@@ -180,13 +180,13 @@ impl<'p> CoreHandle<'p> {
     ) -> Result<()> {
         let mut debugger_rtt_channels: Vec<debug_rtt::DebuggerRttChannel> = vec![];
         // Attach to RTT by using the RTT control block address from the ELF file. Do not scan the memory for the control block.
-        match File::open(program_binary)
+        match std::fs::read(program_binary)
             .map_err(|error| anyhow!("Error attempting to attach to RTT: {}", error))
-            .and_then(|mut open_file| {
-                RttActiveTarget::get_rtt_symbol(&mut open_file).map_or_else(
-                    || Err(anyhow!("No RTT control block found in ELF file")),
-                    |rtt_header_address| Ok(ScanRegion::Exact(rtt_header_address as u32)),
-                )
+            .and_then(|elf| {
+                find_rtt_control_block_in_raw_file(&elf)
+                    .map_err(|error| anyhow!("Error attempting to attach to RTT: {}", error))?
+                    .map(ScanRegion::Exact)
+                    .ok_or_else(|| anyhow!("No RTT control block found in ELF file"))
             })
             .and_then(|scan_region| {
                 Rtt::attach_region(&mut self.core, target_memory_map, &scan_region)
@@ -723,6 +723,32 @@ impl Debugger {
                             .update_progress(Some(1.0), Some("Flashing Pages Complete!"), id)
                             .ok();
                     }
+                    probe_rs::flashing::ProgressEvent::StartedVerifying => {
+                        debug_adapter
+                            .update_progress(Some(0.0), Some("Verifying ..."), id)
+                            .ok();
+                    }
+                    probe_rs::flashing::ProgressEvent::DataVerified { progress, .. } => {
+                        let progress =
+                            progress.completed_bytes as f64 / progress.total_bytes as f64;
+                        debug_adapter
+                            .update_progress(
+                                Some(progress),
+                                Some(format!("Verifying ({:02.0}%)", progress.mul(100_f64))),
+                                id,
+                            )
+                            .ok();
+                    }
+                    probe_rs::flashing::ProgressEvent::FailedVerifying => {
+                        debug_adapter
+                            .update_progress(Some(1.0), Some("Verifying Failed!"), id)
+                            .ok();
+                    }
+                    probe_rs::flashing::ProgressEvent::FinishedVerifying => {
+                        debug_adapter
+                            .update_progress(Some(1.0), Some("Verifying Complete!"), id)
+                            .ok();
+                    }
                     probe_rs::flashing::ProgressEvent::DiagnosticMessage { .. } => (),
                 }
             })
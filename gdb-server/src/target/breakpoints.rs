@@ -0,0 +1,192 @@
+use super::{GdbErrorExt, RuntimeTarget};
+
+use gdbstub::common::{Signal, Tid};
+use gdbstub::stub::MultiThreadStopReason;
+use gdbstub::target::ext::breakpoints::{
+    Breakpoints, HwBreakpoint, HwBreakpointOps, HwWatchpoint, HwWatchpointOps, SwBreakpointOps,
+    WatchKind,
+};
+use probe_rs::{WatchpointCause, WatchpointKind};
+use std::collections::BTreeMap;
+
+impl Breakpoints for RuntimeTarget<'_> {
+    fn support_sw_breakpoint(&mut self) -> Option<SwBreakpointOps<'_, Self>> {
+        None
+    }
+
+    fn support_hw_breakpoint(&mut self) -> Option<HwBreakpointOps<'_, Self>> {
+        Some(self)
+    }
+
+    fn support_hw_watchpoint(&mut self) -> Option<HwWatchpointOps<'_, Self>> {
+        Some(self)
+    }
+}
+
+impl HwBreakpoint for RuntimeTarget<'_> {
+    fn add_hw_breakpoint(
+        &mut self,
+        addr: u64,
+        _kind: <Self::Arch as gdbstub::arch::Arch>::BreakpointKind,
+    ) -> gdbstub::target::TargetResult<bool, Self> {
+        let mut session = self.session.lock().unwrap();
+
+        for core_id in &self.cores {
+            let mut core = session.core(*core_id).into_target_result()?;
+
+            core.set_hw_breakpoint(addr).into_target_result()?;
+        }
+
+        Ok(true)
+    }
+
+    fn remove_hw_breakpoint(
+        &mut self,
+        addr: u64,
+        _kind: <Self::Arch as gdbstub::arch::Arch>::BreakpointKind,
+    ) -> gdbstub::target::TargetResult<bool, Self> {
+        let mut session = self.session.lock().unwrap();
+
+        for core_id in &self.cores {
+            let mut core = session.core(*core_id).into_target_result()?;
+
+            core.clear_hw_breakpoint(addr).into_target_result()?;
+        }
+
+        Ok(true)
+    }
+}
+
+impl HwWatchpoint for RuntimeTarget<'_> {
+    fn add_hw_watchpoint(
+        &mut self,
+        addr: u64,
+        len: u64,
+        kind: WatchKind,
+    ) -> gdbstub::target::TargetResult<bool, Self> {
+        let mut session = self.session.lock().unwrap();
+
+        let probe_rs_kind = match kind {
+            WatchKind::Write => WatchpointKind::Write,
+            WatchKind::Read => WatchpointKind::Read,
+            WatchKind::ReadWrite => WatchpointKind::ReadWrite,
+        };
+
+        for core_id in &self.cores {
+            let mut core = session.core(*core_id).into_target_result()?;
+
+            if let Err(e) = core.set_hw_watchpoint(addr, len as u32, probe_rs_kind) {
+                // Let GDB report that the watchpoint could not be inserted
+                log::warn!("Unable to set watchpoint at {:#010x}: {}", addr, e);
+                return Ok(false);
+            }
+        }
+
+        self.watchpoints.insert(addr, (len, kind));
+
+        Ok(true)
+    }
+
+    fn remove_hw_watchpoint(
+        &mut self,
+        addr: u64,
+        _len: u64,
+        _kind: WatchKind,
+    ) -> gdbstub::target::TargetResult<bool, Self> {
+        let mut session = self.session.lock().unwrap();
+
+        for core_id in &self.cores {
+            let mut core = session.core(*core_id).into_target_result()?;
+
+            core.clear_hw_watchpoint(addr).into_target_result()?;
+        }
+
+        self.watchpoints.remove(&addr);
+
+        Ok(true)
+    }
+}
+
+impl RuntimeTarget<'_> {
+    /// Compute the stop reason for a core halted by a watchpoint
+    pub(crate) fn watchpoint_stop_reason(
+        &self,
+        tid: Tid,
+        cause: WatchpointCause,
+    ) -> MultiThreadStopReason<u64> {
+        match find_watchpoint(&self.watchpoints, cause) {
+            Some((addr, kind)) => MultiThreadStopReason::Watch { tid, kind, addr },
+            None => MultiThreadStopReason::SignalWithThread {
+                tid,
+                signal: Signal::SIGTRAP,
+            },
+        }
+    }
+}
+
+/// Find the address and kind of the watchpoint which was hit
+fn find_watchpoint(
+    watchpoints: &BTreeMap<u64, (u64, WatchKind)>,
+    cause: WatchpointCause,
+) -> Option<(u64, WatchKind)> {
+    match cause {
+        // The unit can report an aligned address, so also check the watched ranges
+        WatchpointCause::Unit { address, .. } => watchpoints
+            .get_key_value(&address)
+            .or_else(|| {
+                watchpoints
+                    .iter()
+                    .find(|(addr, (len, _))| (**addr..**addr + *len).contains(&address))
+            })
+            .map(|(addr, (_, kind))| (*addr, *kind))
+            .or(Some((address, WatchKind::Write))),
+        // Without more information, the watchpoint is only known if there is a single one
+        WatchpointCause::Unknown if watchpoints.len() == 1 => watchpoints
+            .iter()
+            .next()
+            .map(|(addr, (_, kind))| (*addr, *kind)),
+        WatchpointCause::Unknown => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::find_watchpoint;
+
+    use gdbstub::target::ext::breakpoints::WatchKind;
+    use probe_rs::WatchpointCause;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn watchpoint_from_unit() {
+        let watchpoints = BTreeMap::from([
+            (0x2000_0000, (4, WatchKind::Read)),
+            (0x2000_0102, (2, WatchKind::ReadWrite)),
+        ]);
+
+        let hit =
+            |address| find_watchpoint(&watchpoints, WatchpointCause::Unit { unit: 0, address });
+
+        assert_eq!(hit(0x2000_0000), Some((0x2000_0000, WatchKind::Read)));
+        assert_eq!(hit(0x2000_0103), Some((0x2000_0102, WatchKind::ReadWrite)));
+        // Watchpoints which were not set by GDB are reported as written
+        assert_eq!(hit(0x2000_0200), Some((0x2000_0200, WatchKind::Write)));
+    }
+
+    #[test]
+    fn unknown_watchpoint() {
+        let mut watchpoints = BTreeMap::from([(0x2000_0000, (4, WatchKind::Write))]);
+
+        assert_eq!(
+            find_watchpoint(&watchpoints, WatchpointCause::Unknown),
+            Some((0x2000_0000, WatchKind::Write))
+        );
+
+        watchpoints.insert(0x2000_0010, (4, WatchKind::Read));
+
+        assert_eq!(
+            find_watchpoint(&watchpoints, WatchpointCause::Unknown),
+            None
+        );
+    }
+}
@@ -0,0 +1,211 @@
+use super::{GdbErrorExt, RuntimeTarget};
+use crate::target::utils::copy_range_to_buf;
+
+mod data;
+
+use anyhow::anyhow;
+
+use data::build_target_description;
+
+use gdbstub::target::ext::memory_map::MemoryMap;
+use gdbstub::target::ext::target_description_xml_override::TargetDescriptionXmlOverride;
+use gdbstub::target::TargetError;
+
+use probe_rs::config::{MemoryRegion, RawFlashAlgorithm};
+use probe_rs::{CoreType, Session};
+
+use std::fmt::Write;
+use std::ops::Range;
+
+pub(crate) use data::{GdbRegisterSource, TargetDescription};
+
+impl TargetDescriptionXmlOverride for RuntimeTarget<'_> {
+    fn target_description_xml(
+        &self,
+        annex: &[u8],
+        offset: u64,
+        length: usize,
+        buf: &mut [u8],
+    ) -> gdbstub::target::TargetResult<usize, Self> {
+        let annex = String::from_utf8_lossy(annex);
+        if annex != "target.xml" {
+            return Err(TargetError::Fatal(
+                anyhow!("Unsupported annex: '{}'", annex).into(),
+            ));
+        }
+
+        let xml = self.target_desc.get_target_xml();
+        let xml_data = xml.as_bytes();
+
+        Ok(copy_range_to_buf(xml_data, offset, length, buf))
+    }
+}
+
+impl RuntimeTarget<'_> {
+    pub(crate) fn load_target_desc(&mut self) -> Result<(), probe_rs::Error> {
+        let mut session = self.session.lock().unwrap();
+        let mut core = session.core(self.cores[0])?;
+
+        self.target_desc =
+            build_target_description(core.registers(), core.core_type(), core.instruction_set()?);
+
+        Ok(())
+    }
+}
+
+impl MemoryMap for RuntimeTarget<'_> {
+    fn memory_map_xml(
+        &self,
+        offset: u64,
+        length: usize,
+        buf: &mut [u8],
+    ) -> gdbstub::target::TargetResult<usize, Self> {
+        let mut session = self.session.lock().unwrap();
+        let xml = gdb_memory_map(&mut session, self.cores[0]).into_target_result()?;
+        let xml_data = xml.as_bytes();
+
+        Ok(copy_range_to_buf(xml_data, offset, length, buf))
+    }
+}
+
+/// Compute GDB memory map for a session and primary core
+fn gdb_memory_map(
+    session: &mut Session,
+    primary_core_id: usize,
+) -> Result<String, probe_rs::Error> {
+    let (virtual_addressing, address_size) = {
+        let core = session.core(primary_core_id)?;
+        let address_size = core.registers().program_counter().size_in_bits();
+
+        (
+            // Cortex-A cores use virtual addressing
+            matches!(core.core_type(), CoreType::Armv7a | CoreType::Armv8a),
+            address_size,
+        )
+    };
+
+    let target = session.target();
+
+    Ok(memory_map_xml(
+        &target.memory_map,
+        &target.flash_algorithms,
+        virtual_addressing,
+        address_size,
+    ))
+}
+
+/// Build the GDB memory map XML for the memory regions of a target
+///
+/// GDB uses the memory type to decide which kind of breakpoint to set. In regions
+/// which are not writable, i.e. `rom` and `flash`, it uses hardware breakpoints.
+/// NVM regions are reported as `flash` if a flash algorithm covers them, so they
+/// can be programmed with the GDB `load` command.
+fn memory_map_xml(
+    regions: &[MemoryRegion],
+    flash_algorithms: &[RawFlashAlgorithm],
+    virtual_addressing: bool,
+    address_size: usize,
+) -> String {
+    let mut xml_map = r#"<?xml version="1.0"?>
+<!DOCTYPE memory-map PUBLIC "+//IDN gnu.org//DTD GDB Memory Map V1.0//EN" "http://sourceware.org/gdb/gdb-memory-map.dtd">
+<memory-map>
+"#
+    .to_owned();
+
+    if virtual_addressing {
+        // GDB will not attempt to read / write anything outside the address map.
+        // However, with virtual addressing any address could be valid.  As a result
+        // we mark the entire address space as RAM since that's the best assumption
+        // we can make.
+        let _ = writeln!(
+            xml_map,
+            r#"<memory type="ram" start="0x0" length="{:#x}"/>"#,
+            match address_size {
+                32 => 0xFFFF_FFFFu64,
+                64 => 0xFFFF_FFFF_FFFF_FFFF,
+                _ => 0x0,
+            }
+        );
+    } else {
+        for region in regions {
+            let (memory_type, range) = match region {
+                MemoryRegion::Ram(ram) => ("ram", &ram.range),
+                MemoryRegion::Generic(region) => ("rom", &region.range),
+                MemoryRegion::Nvm(region) => {
+                    let blocks = flash_blocks(&region.range, flash_algorithms);
+
+                    if !blocks.is_empty() {
+                        for (range, block_size) in blocks {
+                            let _ = writeln!(
+                                xml_map,
+                                r#"<memory type="flash" start="{:#x}" length="{:#x}"><property name="blocksize">{:#x}</property></memory>"#,
+                                range.start,
+                                range.end - range.start,
+                                block_size
+                            );
+                        }
+
+                        continue;
+                    }
+
+                    // Without a flash algorithm the region can't be written
+                    ("rom", &region.range)
+                }
+            };
+
+            let _ = writeln!(
+                xml_map,
+                r#"<memory type="{}" start="{:#x}" length="{:#x}"/>"#,
+                memory_type,
+                range.start,
+                range.end - range.start
+            );
+        }
+    }
+
+    xml_map.push_str(r#"</memory-map>"#);
+
+    xml_map
+}
+
+/// Split an NVM region into ranges of equally sized sectors, using the sector
+/// layout of the flash algorithm covering it
+///
+/// GDB only supports a single block size per memory map entry, so flash with sectors
+/// of different sizes is reported as multiple entries.
+fn flash_blocks(
+    region: &Range<u64>,
+    flash_algorithms: &[RawFlashAlgorithm],
+) -> Vec<(Range<u64>, u64)> {
+    let Some(properties) = flash_algorithms
+        .iter()
+        .map(|algorithm| &algorithm.flash_properties)
+        .find(|properties| properties.address_range.contains(&region.start))
+    else {
+        return Vec::new();
+    };
+
+    let flash = &properties.address_range;
+    let mut blocks = Vec::new();
+
+    for (i, sectors) in properties.sectors.iter().enumerate() {
+        let start = flash.start + sectors.address;
+        let end = properties
+            .sectors
+            .get(i + 1)
+            .map(|next| flash.start + next.address)
+            .unwrap_or(flash.end);
+
+        let start = start.max(region.start);
+        let end = end.min(region.end);
+
+        if start < end && sectors.size > 0 {
+            blocks.push((start..end, sectors.size));
+        }
+    }
+
+    blocks
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,120 @@
+use probe_rs::config::{
+    FlashProperties, MemoryRegion, NvmRegion, RamRegion, RawFlashAlgorithm, SectorDescription,
+};
+use probe_rs::{CoreType, InstructionSet, RegisterId};
+
+use super::{memory_map_xml, GdbRegisterSource, TargetDescription};
+
+#[test]
+fn test_target_description_microbit() {
+    let target_desc = TargetDescription::new(CoreType::Armv6m, InstructionSet::Thumb2);
+    let description = target_desc.get_target_xml();
+
+    insta::assert_snapshot!(description);
+}
+
+#[test]
+fn test_target_with_features() {
+    let mut target_desc = TargetDescription::new(CoreType::Armv6m, InstructionSet::Thumb2);
+    target_desc.add_gdb_feature("org.probe-rs.feature1");
+    target_desc.add_register_from_details("r0", 32, 0.into());
+    target_desc.add_register_from_details("x1", 64, 1.into());
+    target_desc.add_register_from_details("t2", 64, 2.into());
+
+    target_desc.update_register_name("t2", "at2");
+    target_desc.update_register_type("at2", "special_reg");
+
+    target_desc.add_gdb_feature("org.probe-rs.feature2");
+    target_desc.add_register_from_details("v4", 128, 4.into());
+
+    let description = target_desc.get_target_xml();
+
+    insta::assert_snapshot!(description);
+}
+
+#[test]
+fn test_target_with_bit_field_registers() {
+    let mut target_desc = TargetDescription::new(CoreType::Armv7em, InstructionSet::Thumb2);
+    target_desc.add_gdb_feature("org.gnu.gdb.arm.m-system");
+
+    target_desc.add_bit_field_register("primask", RegisterId(0b10100), 0, 8);
+    target_desc.add_bit_field_register("control", RegisterId(0b10100), 24, 8);
+
+    assert!(matches!(
+        target_desc.get_register(1).source(),
+        GdbRegisterSource::BitField {
+            register: RegisterId(0b10100),
+            offset: 24,
+            size: 8
+        }
+    ));
+
+    insta::assert_snapshot!(target_desc.get_target_xml());
+}
+
+#[test]
+fn test_memory_map() {
+    let regions = [
+        MemoryRegion::Nvm(NvmRegion {
+            name: Some("Flash".to_owned()),
+            range: 0x0800_0000..0x0810_0000,
+            is_boot_memory: true,
+            cores: vec!["main".to_owned()],
+            min_write_size: None,
+            no_overwrite: false,
+            access: None,
+        }),
+        MemoryRegion::Ram(RamRegion {
+            name: Some("SRAM".to_owned()),
+            range: 0x2000_0000..0x2002_0000,
+            is_boot_memory: false,
+            cores: vec!["main".to_owned()],
+            access: None,
+        }),
+    ];
+
+    insta::assert_snapshot!(memory_map_xml(&regions, &[], false, 32));
+}
+
+#[test]
+fn test_memory_map_virtual_addressing() {
+    insta::assert_snapshot!(memory_map_xml(&[], &[], true, 64));
+}
+
+#[test]
+fn test_memory_map_with_flash_algorithm() {
+    let regions = [MemoryRegion::Nvm(NvmRegion {
+        name: Some("Flash".to_owned()),
+        range: 0x0800_0000..0x0810_0000,
+        is_boot_memory: true,
+        cores: vec!["main".to_owned()],
+        min_write_size: None,
+        no_overwrite: false,
+        access: None,
+    })];
+
+    // Sectors of different sizes, as used by the STM32F4
+    let algorithm = RawFlashAlgorithm {
+        flash_properties: FlashProperties {
+            address_range: 0x0800_0000..0x0810_0000,
+            sectors: vec![
+                SectorDescription {
+                    size: 0x4000,
+                    address: 0x0,
+                },
+                SectorDescription {
+                    size: 0x10000,
+                    address: 0x10000,
+                },
+                SectorDescription {
+                    size: 0x20000,
+                    address: 0x20000,
+                },
+            ],
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    insta::assert_snapshot!(memory_map_xml(&regions, &[algorithm], false, 32));
+}
@@ -0,0 +1,43 @@
+use super::{GdbErrorExt, RuntimeTarget};
+
+use gdbstub::target::ext::flash::Flash;
+use gdbstub::target::TargetResult;
+use probe_rs::flashing::DownloadOptions;
+use probe_rs::Error;
+
+impl Flash for RuntimeTarget<'_> {
+    fn flash_erase(&mut self, start_addr: u64, length: u64) -> TargetResult<(), Self> {
+        // The flash loader erases all sectors which are written, so there
+        // is nothing to do until the data is committed
+        log::debug!("Erasing flash {:#x}-{:#x}", start_addr, start_addr + length);
+
+        self.flash_loader
+            .get_or_insert_with(|| self.session.lock().unwrap().target().flash_loader());
+
+        Ok(())
+    }
+
+    fn flash_write(&mut self, start_addr: u64, data: &[u8]) -> TargetResult<(), Self> {
+        let loader = self
+            .flash_loader
+            .get_or_insert_with(|| self.session.lock().unwrap().target().flash_loader());
+
+        loader
+            .add_data(start_addr, data)
+            .map_err(|e| Error::Other(e.into()))
+            .into_target_result()
+    }
+
+    fn flash_done(&mut self) -> TargetResult<(), Self> {
+        let Some(loader) = self.flash_loader.take() else {
+            return Ok(());
+        };
+
+        let mut session = self.session.lock().unwrap();
+
+        loader
+            .commit(&mut session, DownloadOptions::default())
+            .map_err(|e| Error::Other(e.into()))
+            .into_target_result()
+    }
+}
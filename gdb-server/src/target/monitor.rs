@@ -0,0 +1,281 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use super::RuntimeTarget;
+
+use gdbstub::target::ext::monitor_cmd::outputln;
+use gdbstub::target::ext::monitor_cmd::MonitorCmd;
+use probe_rs::architecture::arm::{component::TraceSink, swo::SwoConfig};
+use probe_rs::flashing::erase_all;
+use probe_rs::rtt::routing::TcpEndpoint;
+use probe_rs::rtt::{Rtt, RttRouter, ScanRegion, Sink, Source};
+
+const HELP_TEXT: &str = r#"Supported Commands:
+
+    info - print session information
+    reset - reset target
+    reset halt - reset target and halt afterwards
+    erase - erase the complete flash
+    rtt start [<address>] - attach to RTT, optionally with the control block at <address>,
+                            and route each channel to a TCP socket
+    rtt stop - stop routing the RTT channels
+    swo enable <baud> <tpiu clock> - enable SWO, and send the trace data to a TCP socket
+    swo disable - disable SWO
+"#;
+
+impl MonitorCmd for RuntimeTarget<'_> {
+    fn handle_monitor_cmd(
+        &mut self,
+        cmd: &[u8],
+        mut out: gdbstub::target::ext::monitor_cmd::ConsoleOutput<'_>,
+    ) -> Result<(), Self::Error> {
+        let cmd = String::from_utf8_lossy(cmd);
+        let args = cmd.split_whitespace().collect::<Vec<_>>();
+
+        match args.as_slice() {
+            ["info"] => {
+                outputln!(
+                    out,
+                    "Target info:\n\n{:#?}",
+                    self.session.lock().unwrap().target()
+                );
+            }
+            ["reset"] => {
+                outputln!(out, "Resetting target");
+                match self.session.lock().unwrap().core(self.cores[0])?.reset() {
+                    Ok(_) => {
+                        outputln!(out, "Done")
+                    }
+                    Err(e) => {
+                        outputln!(out, "Error while resetting target:\n\t{}", e)
+                    }
+                }
+            }
+            ["reset", "halt"] => {
+                let timeout: Duration = Duration::new(1, 0);
+                outputln!(out, "Resetting and halting target");
+                match self
+                    .session
+                    .lock()
+                    .unwrap()
+                    .core(self.cores[0])?
+                    .reset_and_halt(timeout)
+                {
+                    Ok(_) => {
+                        outputln!(out, "Target halted")
+                    }
+                    Err(e) => {
+                        outputln!(out, "Error while halting target:\n\t{}", e)
+                    }
+                }
+            }
+            ["erase"] => {
+                outputln!(out, "Erasing flash");
+                match erase_all(&mut self.session.lock().unwrap(), None) {
+                    Ok(_) => {
+                        outputln!(out, "Done")
+                    }
+                    Err(e) => {
+                        outputln!(out, "Error while erasing flash:\n\t{}", e)
+                    }
+                }
+            }
+            ["rtt", "start", address @ ..] => {
+                let region = match address {
+                    [] => ScanRegion::Ram,
+                    [address] => match parse_u32(address) {
+                        Some(address) => ScanRegion::Exact(address),
+                        None => {
+                            outputln!(out, "Invalid address: {}", address);
+                            return Ok(());
+                        }
+                    },
+                    _ => {
+                        outputln!(out, "{}", HELP_TEXT);
+                        return Ok(());
+                    }
+                };
+
+                match self.start_rtt(&region) {
+                    Ok(channels) => {
+                        for (channel, addr) in channels {
+                            outputln!(out, "{}: {}", channel, addr)
+                        }
+                    }
+                    Err(e) => {
+                        outputln!(out, "Error while attaching to RTT:\n\t{}", e)
+                    }
+                }
+            }
+            ["rtt", "stop"] => {
+                self.rtt = None;
+                outputln!(out, "RTT stopped");
+            }
+            ["swo", "enable", baud, tpiu_clk] => {
+                let (Some(baud), Some(tpiu_clk)) = (parse_u32(baud), parse_u32(tpiu_clk)) else {
+                    outputln!(out, "Invalid baud rate or TPIU clock");
+                    return Ok(());
+                };
+
+                match self.enable_swo(SwoConfig::new(tpiu_clk).set_baud(baud)) {
+                    Ok(addr) => {
+                        outputln!(out, "SWO trace data: {}", addr)
+                    }
+                    Err(e) => {
+                        outputln!(out, "Error while enabling SWO:\n\t{}", e)
+                    }
+                }
+            }
+            ["swo", "disable"] => {
+                self.swo = None;
+                match self.session.lock().unwrap().disable_swv(self.cores[0]) {
+                    Ok(_) => {
+                        outputln!(out, "SWO disabled")
+                    }
+                    Err(e) => {
+                        outputln!(out, "Error while disabling SWO:\n\t{}", e)
+                    }
+                }
+            }
+            _ => {
+                outputln!(out, "{}", HELP_TEXT);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl RuntimeTarget<'_> {
+    /// Attach to RTT, and route every channel to a TCP socket
+    ///
+    /// Returns: The channel names with the address of their socket
+    fn start_rtt(&mut self, region: &ScanRegion) -> anyhow::Result<Vec<(String, SocketAddr)>> {
+        let mut session = self.session.lock().unwrap();
+        let memory_map = session.target().memory_map.clone();
+        let mut core = session.core(self.cores[0])?;
+
+        let mut rtt = Rtt::attach_region(&mut core, &memory_map, region)?;
+        let mut router = RttRouter::new();
+        let mut channels = Vec::new();
+
+        let numbers = rtt
+            .up_channels()
+            .iter()
+            .map(|channel| channel.number())
+            .collect::<Vec<_>>();
+        for number in numbers {
+            let Some(channel) = rtt.up_channels().take(number) else {
+                continue;
+            };
+
+            let endpoint = self.bind_endpoint()?;
+            channels.push((
+                format!("Up channel {} ({})", number, channel.name().unwrap_or("")),
+                endpoint.local_addr()?,
+            ));
+            router.route_up(channel, Sink::Tcp(endpoint));
+        }
+
+        let numbers = rtt
+            .down_channels()
+            .iter()
+            .map(|channel| channel.number())
+            .collect::<Vec<_>>();
+        for number in numbers {
+            let Some(channel) = rtt.down_channels().take(number) else {
+                continue;
+            };
+
+            let endpoint = self.bind_endpoint()?;
+            channels.push((
+                format!("Down channel {} ({})", number, channel.name().unwrap_or("")),
+                endpoint.local_addr()?,
+            ));
+            router.route_down(channel, Source::Tcp(endpoint));
+        }
+
+        self.rtt = Some(router);
+
+        Ok(channels)
+    }
+
+    /// Enable SWO, and send the trace data to a TCP socket
+    ///
+    /// Returns: The address of the socket
+    fn enable_swo(&mut self, config: SwoConfig) -> anyhow::Result<SocketAddr> {
+        let endpoint = self.bind_endpoint()?;
+        let addr = endpoint.local_addr()?;
+
+        self.session
+            .lock()
+            .unwrap()
+            .setup_tracing(self.cores[0], TraceSink::Swo(config))?;
+
+        self.swo = Some(Sink::Tcp(endpoint));
+
+        Ok(addr)
+    }
+
+    /// Bind a socket on the same interface as the GDB connection, using any free port
+    fn bind_endpoint(&self) -> std::io::Result<TcpEndpoint> {
+        let mut addr = self.listener.local_addr()?;
+        addr.set_port(0);
+
+        TcpEndpoint::bind(addr)
+    }
+
+    /// Transfer the RTT and SWO data of the target
+    pub(crate) fn poll_channels(&mut self) {
+        if self.rtt.is_none() && self.swo.is_none() {
+            return;
+        }
+
+        let mut session = self.session.lock().unwrap();
+
+        if let Some(router) = &mut self.rtt {
+            let result = session
+                .core(self.cores[0])
+                .map_err(anyhow::Error::from)
+                .and_then(|mut core| Ok(router.poll(&mut core)?));
+
+            if let Err(e) = result {
+                log::warn!("Stopping RTT: {:#}", e);
+                self.rtt = None;
+            }
+        }
+
+        if let Some(swo) = &mut self.swo {
+            let result = session
+                .read_trace_data()
+                .map_err(anyhow::Error::from)
+                .and_then(|data| Ok(swo.write(&data)?));
+
+            if let Err(e) = result {
+                log::warn!("Stopping SWO: {:#}", e);
+                self.swo = None;
+            }
+        }
+    }
+}
+
+/// Parse a decimal or `0x` prefixed hexadecimal number
+fn parse_u32(value: &str) -> Option<u32> {
+    match value.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_u32;
+
+    #[test]
+    fn parse_numbers() {
+        assert_eq!(parse_u32("115200"), Some(115200));
+        assert_eq!(parse_u32("0x2000004c"), Some(0x2000_004c));
+        assert_eq!(parse_u32("0xZZ"), None);
+        assert_eq!(parse_u32("-1"), None);
+    }
+}
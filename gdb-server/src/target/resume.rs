@@ -0,0 +1,152 @@
+use super::{ResumeAction, RuntimeTarget};
+
+use gdbstub::target::ext::base::multithread::{
+    MultiThreadResume, MultiThreadSchedulerLocking, MultiThreadSingleStep,
+};
+use gdbstub::target::ext::base::multithread::{
+    MultiThreadSchedulerLockingOps, MultiThreadSingleStepOps,
+};
+use std::collections::BTreeMap;
+
+/// Resume actions of the cores, to be used upon a continue request
+#[derive(Debug)]
+pub(crate) struct ResumeActions {
+    /// Actions of the cores which GDB set explicitly
+    actions: BTreeMap<usize, ResumeAction>,
+    /// Action of the cores without an explicit action
+    default: ResumeAction,
+}
+
+impl ResumeActions {
+    pub(crate) fn new() -> Self {
+        Self {
+            actions: BTreeMap::new(),
+            default: ResumeAction::Unchanged,
+        }
+    }
+
+    /// Clear all actions, so all cores are resumed unless scheduler locking is enabled
+    fn clear(&mut self) {
+        self.actions.clear();
+        self.default = ResumeAction::Resume;
+    }
+
+    fn set(&mut self, core_id: usize, action: ResumeAction) {
+        self.actions.insert(core_id, action);
+    }
+
+    /// Only resume the cores with an explicit action
+    fn lock_scheduler(&mut self) {
+        self.default = ResumeAction::Unchanged;
+    }
+
+    fn get(&self, core_id: usize) -> ResumeAction {
+        self.actions.get(&core_id).copied().unwrap_or(self.default)
+    }
+}
+
+impl MultiThreadResume for RuntimeTarget<'_> {
+    fn resume(&mut self) -> Result<(), Self::Error> {
+        let mut session = self.session.lock().unwrap();
+
+        self.running_cores.clear();
+
+        for core_id in self.cores.iter() {
+            let action = self.resume_actions.get(*core_id);
+
+            let mut core = session.core(*core_id)?;
+
+            match action {
+                ResumeAction::Resume => core.run()?,
+                ResumeAction::Step => {
+                    core.step()?;
+                }
+                ResumeAction::Unchanged => continue,
+            }
+
+            self.running_cores.push(*core_id);
+        }
+
+        Ok(())
+    }
+
+    fn clear_resume_actions(&mut self) -> Result<(), Self::Error> {
+        self.resume_actions.clear();
+
+        Ok(())
+    }
+
+    fn set_resume_action_continue(
+        &mut self,
+        tid: gdbstub::common::Tid,
+        _signal: Option<gdbstub::common::Signal>,
+    ) -> Result<(), Self::Error> {
+        let core_id = tid.get() - 1;
+        self.resume_actions.set(core_id, ResumeAction::Resume);
+
+        Ok(())
+    }
+
+    fn support_single_step(&mut self) -> Option<MultiThreadSingleStepOps<'_, Self>> {
+        Some(self)
+    }
+
+    fn support_scheduler_locking(&mut self) -> Option<MultiThreadSchedulerLockingOps<'_, Self>> {
+        Some(self)
+    }
+}
+
+impl MultiThreadSingleStep for RuntimeTarget<'_> {
+    fn set_resume_action_step(
+        &mut self,
+        tid: gdbstub::common::Tid,
+        _signal: Option<gdbstub::common::Signal>,
+    ) -> Result<(), Self::Error> {
+        let core_id = tid.get() - 1;
+        self.resume_actions.set(core_id, ResumeAction::Step);
+
+        Ok(())
+    }
+}
+
+impl MultiThreadSchedulerLocking for RuntimeTarget<'_> {
+    fn set_resume_action_scheduler_lock(&mut self) -> Result<(), Self::Error> {
+        self.resume_actions.lock_scheduler();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ResumeAction, ResumeActions};
+
+    #[test]
+    fn all_cores_are_resumed_by_default() {
+        let mut actions = ResumeActions::new();
+        // Nothing is resumed before GDB sent any actions
+        assert_eq!(actions.get(0), ResumeAction::Unchanged);
+
+        actions.clear();
+        actions.set(1, ResumeAction::Step);
+
+        assert_eq!(actions.get(0), ResumeAction::Resume);
+        assert_eq!(actions.get(1), ResumeAction::Step);
+    }
+
+    #[test]
+    fn scheduler_locking_resumes_single_core() {
+        let mut actions = ResumeActions::new();
+        actions.clear();
+        actions.set(1, ResumeAction::Step);
+        actions.lock_scheduler();
+
+        assert_eq!(actions.get(0), ResumeAction::Unchanged);
+        assert_eq!(actions.get(1), ResumeAction::Step);
+
+        // The next continue request resumes all cores again
+        actions.clear();
+        assert_eq!(actions.get(0), ResumeAction::Resume);
+        assert_eq!(actions.get(1), ResumeAction::Resume);
+    }
+}
@@ -83,6 +83,12 @@ pub struct FlashOptions {
         help = "Enable this flag to restore all bytes erased in the sector erase but not overwritten by any page."
     )]
     pub restore_unwritten: bool,
+    #[clap(
+        name = "skip-unchanged",
+        long = "skip-unchanged",
+        help = "Enable this flag to only erase and program the sectors whose contents differ from the new image."
+    )]
+    pub skip_unchanged: bool,
     #[clap(
         name = "filename",
         long = "flash-layout",
@@ -154,6 +160,12 @@ pub struct ProbeOptions {
         help_heading = "PROBE CONFIGURATION"
     )]
     pub speed: Option<u32>,
+    #[clap(
+        long,
+        help = "Use compact JTAG (cJTAG, IEEE 1149.7) in the OScan1 format to connect to the chip.",
+        help_heading = "PROBE CONFIGURATION"
+    )]
+    pub cjtag: bool,
     #[structopt(
         long = "connect-under-reset",
         help = "Use this flag to assert the nreset & ntrst pins during attaching the probe to the chip."
@@ -255,6 +267,12 @@ impl ProbeOptions {
             })?;
         }
 
+        if self.cjtag {
+            probe
+                .set_cjtag(true)
+                .map_err(OperationError::FailedToEnableCjtag)?;
+        }
+
         Ok(probe)
     }
 
@@ -499,6 +517,8 @@ pub enum OperationError {
         source: DebugProbeError,
         speed: u32,
     },
+    #[error("cJTAG could not be enabled.")]
+    FailedToEnableCjtag(#[source] DebugProbeError),
     #[error("Connecting to the chip was unsuccessful.")]
     AttachingFailed {
         #[source]
@@ -25,6 +25,7 @@ pub fn run_flash_download(
 
     let mut download_option = DownloadOptions::default();
     download_option.keep_unwritten_bytes = opt.restore_unwritten;
+    download_option.skip_unchanged = opt.skip_unchanged;
     download_option.dry_run = opt.probe_options.dry_run;
     download_option.do_chip_erase = do_chip_erase;
     download_option.disable_double_buffering = opt.disable_double_buffering;
@@ -133,6 +134,7 @@ pub fn run_flash_download(
                         fp.finish()
                     };
                 }
+                StartedVerifying | DataVerified { .. } | FailedVerifying | FinishedVerifying => {}
                 DiagnosticMessage { .. } => (),
             }
         });
@@ -8,14 +8,7 @@ use probe_rs::rtt::{DownChannel, Rtt, ScanRegion, UpChannel};
 use probe_rs::Core;
 use serde::Deserialize;
 use std::collections::HashMap;
-use std::fs::File;
-use std::{
-    fmt,
-    fmt::Write,
-    fs,
-    io::{Read, Seek},
-    str::FromStr,
-};
+use std::{fmt, fmt::Write, fs, str::FromStr};
 use time::{OffsetDateTime, UtcOffset};
 
 pub fn attach_to_rtt(
@@ -26,14 +19,9 @@ pub fn attach_to_rtt(
     timestamp_offset: UtcOffset,
 ) -> Result<crate::rtt::RttActiveTarget, anyhow::Error> {
     log::info!("Initializing RTT");
-    let rtt_header_address = if let Ok(mut file) = File::open(elf_file) {
-        if let Some(address) = RttActiveTarget::get_rtt_symbol(&mut file) {
-            ScanRegion::Exact(address as u32)
-        } else {
-            ScanRegion::Ram
-        }
-    } else {
-        ScanRegion::Ram
+    let rtt_header_address = match fs::read(elf_file) {
+        Ok(elf) => ScanRegion::from_elf(&elf)?,
+        Err(_) => ScanRegion::Ram,
     };
 
     match Rtt::attach_region(core, memory_map, &rtt_header_address) {
@@ -426,24 +414,6 @@ impl RttActiveTarget {
         })
     }
 
-    pub fn get_rtt_symbol<T: Read + Seek>(file: &mut T) -> Option<u64> {
-        let mut buffer = Vec::new();
-        if file.read_to_end(&mut buffer).is_ok() {
-            if let Ok(binary) = goblin::elf::Elf::parse(buffer.as_slice()) {
-                for sym in &binary.syms {
-                    if let Some(name) = binary.strtab.get_at(sym.st_name) {
-                        if name == "_SEGGER_RTT" {
-                            return Some(sym.st_value);
-                        }
-                    }
-                }
-            }
-        }
-
-        log::warn!("No RTT header info was present in the ELF file. Does your firmware run RTT?");
-        None
-    }
-
     /// Polls the RTT target on all channels and returns available data.
     /// Errors on any channel will be ignored and the data (even if incomplete) from the other channels will be returned.
     #[deprecated(
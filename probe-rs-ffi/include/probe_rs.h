@@ -0,0 +1,103 @@
+/*
+ * C bindings for probe-rs.
+ *
+ * All objects are opaque handles, which have to be released with the matching
+ * `*_free` function. Functions which can fail return a `probe_rs_status`, and
+ * `probe_rs_last_error()` returns a description of the last error on the thread.
+ *
+ * A handle must not be used by multiple threads at the same time.
+ */
+
+#ifndef PROBE_RS_H
+#define PROBE_RS_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum probe_rs_status {
+    PROBE_RS_OK = 0,
+    PROBE_RS_INVALID_ARGUMENT = -1,
+    PROBE_RS_NOT_FOUND = -2,
+    PROBE_RS_PROBE = -3,
+    PROBE_RS_TARGET = -4,
+    PROBE_RS_TIMEOUT = -5,
+    PROBE_RS_FLASHING = -6,
+    PROBE_RS_PANIC = -7,
+    PROBE_RS_OTHER = -8,
+} probe_rs_status;
+
+#define PROBE_RS_FORMAT_BIN 0u
+#define PROBE_RS_FORMAT_HEX 1u
+#define PROBE_RS_FORMAT_ELF 2u
+#define PROBE_RS_FORMAT_SREC 3u
+#define PROBE_RS_FORMAT_UF2 4u
+
+typedef struct ProbeRsProbeList probe_rs_probe_list_t;
+typedef struct ProbeRsSession probe_rs_session_t;
+
+/* The strings are owned by the list, and are valid until it is freed. */
+typedef struct probe_rs_probe_info {
+    const char *identifier;
+    uint16_t vendor_id;
+    uint16_t product_id;
+    /* NULL if the probe has no serial number. */
+    const char *serial_number;
+} probe_rs_probe_info;
+
+/* Valid until the next call of a probe-rs function on this thread. */
+const char *probe_rs_last_error(void);
+
+probe_rs_status probe_rs_probe_list(probe_rs_probe_list_t **list);
+size_t probe_rs_probe_list_len(const probe_rs_probe_list_t *list);
+probe_rs_status probe_rs_probe_list_get(probe_rs_probe_list_t *list, size_t index,
+                                        probe_rs_probe_info *info);
+void probe_rs_probe_list_free(probe_rs_probe_list_t *list);
+
+/* Uses the first connected probe if `list` is NULL. */
+probe_rs_status probe_rs_session_open(probe_rs_probe_list_t *list, size_t index,
+                                      const char *target, probe_rs_session_t **session);
+void probe_rs_session_free(probe_rs_session_t *session);
+size_t probe_rs_session_core_count(probe_rs_session_t *session);
+
+/* `pc` may be NULL. */
+probe_rs_status probe_rs_core_halt(probe_rs_session_t *session, size_t core_index,
+                                   uint32_t timeout_ms, uint64_t *pc);
+probe_rs_status probe_rs_core_run(probe_rs_session_t *session, size_t core_index);
+probe_rs_status probe_rs_core_step(probe_rs_session_t *session, size_t core_index,
+                                   uint64_t *pc);
+probe_rs_status probe_rs_core_reset(probe_rs_session_t *session, size_t core_index,
+                                    bool halt, uint32_t timeout_ms);
+probe_rs_status probe_rs_core_is_halted(probe_rs_session_t *session, size_t core_index,
+                                        bool *halted);
+
+/* Accepts the register names of the core, and `pc`, `sp`, `fp` and `ra`. */
+probe_rs_status probe_rs_core_register_id(probe_rs_session_t *session, size_t core_index,
+                                          const char *name, uint16_t *reg);
+probe_rs_status probe_rs_core_read_register(probe_rs_session_t *session, size_t core_index,
+                                            uint16_t reg, uint64_t *value);
+probe_rs_status probe_rs_core_write_register(probe_rs_session_t *session, size_t core_index,
+                                             uint16_t reg, uint64_t value);
+
+probe_rs_status probe_rs_core_read_memory(probe_rs_session_t *session, size_t core_index,
+                                          uint64_t address, uint8_t *data, size_t len);
+probe_rs_status probe_rs_core_write_memory(probe_rs_session_t *session, size_t core_index,
+                                           uint64_t address, const uint8_t *data, size_t len);
+probe_rs_status probe_rs_core_read_word_32(probe_rs_session_t *session, size_t core_index,
+                                           uint64_t address, uint32_t *value);
+probe_rs_status probe_rs_core_write_word_32(probe_rs_session_t *session, size_t core_index,
+                                            uint64_t address, uint32_t value);
+
+/* `base_address` is only used for PROBE_RS_FORMAT_BIN, 0 means the start of the flash. */
+probe_rs_status probe_rs_download_file(probe_rs_session_t *session, const char *path,
+                                       uint32_t format, uint64_t base_address);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* PROBE_RS_H */
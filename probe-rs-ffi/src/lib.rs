@@ -0,0 +1,869 @@
+//! C bindings for probe-rs.
+//!
+//! This crate exposes the core API of probe-rs through a C ABI, so it can be used from
+//! C, C++ and any other language which can call C functions. The matching header is
+//! `include/probe_rs.h`.
+//!
+//! All objects are passed as opaque handles, which are created by a `*_open` or
+//! `*_list` function and have to be released with the matching `*_free` function.
+//! Every function which can fail returns a [`ProbeRsStatus`], and stores a description
+//! of the error, which can be retrieved with [`probe_rs_last_error`].
+//!
+//! A handle must not be used by multiple threads at the same time.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::Path;
+use std::ptr;
+use std::time::Duration;
+
+use probe_rs::flashing::{self, BinOptions, FileDownloadError, FlashError, Format};
+use probe_rs::{
+    Core, DebugProbeError, DebugProbeInfo, Error, MemoryInterface, Permissions, Probe, RegisterId,
+    RegisterValue, Session,
+};
+
+/// The result of a function of the C API.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProbeRsStatus {
+    /// The function was successful.
+    Ok = 0,
+    /// An argument was invalid, e.g. a null pointer or a string which isn't valid UTF-8.
+    InvalidArgument = -1,
+    /// The probe, core or register doesn't exist.
+    NotFound = -2,
+    /// The debug probe reported an error.
+    Probe = -3,
+    /// The target chip is unknown, or the communication with it failed.
+    Target = -4,
+    /// The operation didn't finish in time.
+    Timeout = -5,
+    /// Flashing failed.
+    Flashing = -6,
+    /// A panic occurred in probe-rs. The handles used in the call should be freed.
+    Panic = -7,
+    /// Any other error.
+    Other = -8,
+}
+
+/// The file formats, which can be passed to [`probe_rs_download_file`].
+pub const PROBE_RS_FORMAT_BIN: u32 = 0;
+/// See [`PROBE_RS_FORMAT_BIN`].
+pub const PROBE_RS_FORMAT_HEX: u32 = 1;
+/// See [`PROBE_RS_FORMAT_BIN`].
+pub const PROBE_RS_FORMAT_ELF: u32 = 2;
+/// See [`PROBE_RS_FORMAT_BIN`].
+pub const PROBE_RS_FORMAT_SREC: u32 = 3;
+/// See [`PROBE_RS_FORMAT_BIN`].
+pub const PROBE_RS_FORMAT_UF2: u32 = 4;
+
+/// The list of the connected debug probes.
+pub struct ProbeRsProbeList {
+    probes: Vec<DebugProbeInfo>,
+    /// The identifiers and serial numbers of the probes, owned by the list
+    /// so they can be handed out as pointers.
+    strings: Vec<(CString, Option<CString>)>,
+}
+
+/// The description of a debug probe, see [`probe_rs_probe_list_get`].
+///
+/// The strings are owned by the list, and are valid until it is freed.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ProbeRsProbeInfo {
+    /// The name of the probe.
+    pub identifier: *const c_char,
+    /// The USB vendor ID of the probe.
+    pub vendor_id: u16,
+    /// The USB product ID of the probe.
+    pub product_id: u16,
+    /// The serial number of the probe, or null if it has none.
+    pub serial_number: *const c_char,
+}
+
+/// A session with a target.
+pub struct ProbeRsSession {
+    session: Session,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+/// An error of a function of the C API, with its status and description.
+struct FfiError {
+    status: ProbeRsStatus,
+    message: String,
+}
+
+impl FfiError {
+    fn invalid_argument(message: impl Into<String>) -> Self {
+        Self {
+            status: ProbeRsStatus::InvalidArgument,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<Error> for FfiError {
+    fn from(error: Error) -> Self {
+        let status = match &error {
+            Error::Probe(DebugProbeError::ProbeCouldNotBeCreated(_)) => ProbeRsStatus::NotFound,
+            Error::Probe(_) => ProbeRsStatus::Probe,
+            Error::CoreNotFound(_) => ProbeRsStatus::NotFound,
+            Error::Timeout => ProbeRsStatus::Timeout,
+            Error::ChipNotFound(_)
+            | Error::Arm(_)
+            | Error::Riscv(_)
+            | Error::Xtensa(_)
+            | Error::MemoryNotAligned { .. } => ProbeRsStatus::Target,
+            _ => ProbeRsStatus::Other,
+        };
+
+        Self {
+            status,
+            message: format!("{error:#}"),
+        }
+    }
+}
+
+impl From<DebugProbeError> for FfiError {
+    fn from(error: DebugProbeError) -> Self {
+        Error::Probe(error).into()
+    }
+}
+
+impl From<FlashError> for FfiError {
+    fn from(error: FlashError) -> Self {
+        Self {
+            status: ProbeRsStatus::Flashing,
+            message: format!("{error:#}"),
+        }
+    }
+}
+
+impl From<FileDownloadError> for FfiError {
+    fn from(error: FileDownloadError) -> Self {
+        Self {
+            status: ProbeRsStatus::Flashing,
+            message: format!("{error:#}"),
+        }
+    }
+}
+
+/// Runs `function`, and turns its result into a status.
+///
+/// Panics are caught, because unwinding into C is undefined behaviour.
+fn ffi(function: impl FnOnce() -> Result<(), FfiError>) -> ProbeRsStatus {
+    let error = match catch_unwind(AssertUnwindSafe(function)) {
+        Ok(Ok(())) => {
+            set_last_error(String::new());
+            return ProbeRsStatus::Ok;
+        }
+        Ok(Err(error)) => error,
+        Err(panic) => FfiError {
+            status: ProbeRsStatus::Panic,
+            message: panic
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "probe-rs panicked".to_owned()),
+        },
+    };
+
+    set_last_error(error.message);
+    error.status
+}
+
+fn set_last_error(message: String) {
+    // Interior null bytes can't be represented, so the message is cut off at the first one.
+    let message = match CString::new(message) {
+        Ok(message) => message,
+        Err(error) => {
+            let end = error.nul_position();
+            let mut bytes = error.into_vec();
+            bytes.truncate(end);
+            CString::new(bytes).unwrap_or_default()
+        }
+    };
+
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = message);
+}
+
+/// Converts a pointer to a reference, checking that it isn't null.
+///
+/// # Safety
+///
+/// The pointer has to be null, or point to a valid `T`.
+unsafe fn get<'a, T>(pointer: *mut T, name: &str) -> Result<&'a mut T, FfiError> {
+    pointer
+        .as_mut()
+        .ok_or_else(|| FfiError::invalid_argument(format!("`{name}` is null")))
+}
+
+/// Converts a pointer to a string, checking that it isn't null and is valid UTF-8.
+///
+/// # Safety
+///
+/// The pointer has to be null, or point to a null terminated string.
+unsafe fn get_str<'a>(pointer: *const c_char, name: &str) -> Result<&'a str, FfiError> {
+    if pointer.is_null() {
+        return Err(FfiError::invalid_argument(format!("`{name}` is null")));
+    }
+
+    CStr::from_ptr(pointer)
+        .to_str()
+        .map_err(|_| FfiError::invalid_argument(format!("`{name}` is not valid UTF-8")))
+}
+
+/// Converts a pointer and a length to a slice. A null pointer is allowed if the length is 0.
+///
+/// # Safety
+///
+/// The pointer has to point to `len` valid bytes, unless `len` is 0.
+unsafe fn get_slice<'a>(data: *const u8, len: usize) -> Result<&'a [u8], FfiError> {
+    match (data.is_null(), len) {
+        (_, 0) => Ok(&[]),
+        (true, _) => Err(FfiError::invalid_argument("`data` is null")),
+        (false, _) => Ok(std::slice::from_raw_parts(data, len)),
+    }
+}
+
+/// Converts a pointer and a length to a mutable slice. A null pointer is allowed if the length is 0.
+///
+/// # Safety
+///
+/// The pointer has to point to `len` valid, writable bytes, unless `len` is 0.
+unsafe fn get_slice_mut<'a>(data: *mut u8, len: usize) -> Result<&'a mut [u8], FfiError> {
+    match (data.is_null(), len) {
+        (_, 0) => Ok(&mut []),
+        (true, _) => Err(FfiError::invalid_argument("`data` is null")),
+        (false, _) => Ok(std::slice::from_raw_parts_mut(data, len)),
+    }
+}
+
+/// Writes `value` to the output argument `out`, if it isn't null.
+///
+/// # Safety
+///
+/// The pointer has to be null, or point to a writable `T`.
+unsafe fn set<T>(out: *mut T, value: T) {
+    if !out.is_null() {
+        out.write(value);
+    }
+}
+
+/// Executes `function` with the core `core_index` of the session.
+///
+/// # Safety
+///
+/// The session has to be null, or a handle returned by [`probe_rs_session_open`].
+unsafe fn with_core(
+    session: *mut ProbeRsSession,
+    core_index: usize,
+    function: impl FnOnce(&mut Core) -> Result<(), FfiError>,
+) -> ProbeRsStatus {
+    ffi(|| {
+        let session = get(session, "session")?;
+        function(&mut session.session.core(core_index)?)
+    })
+}
+
+/// Returns the description of the last error of a function called on this thread,
+/// or an empty string if the last call was successful.
+///
+/// The string is valid until the next function of probe-rs is called on this thread.
+#[no_mangle]
+pub extern "C" fn probe_rs_last_error() -> *const c_char {
+    LAST_ERROR.with(|last_error| last_error.borrow().as_ptr())
+}
+
+/// Lists the connected debug probes.
+///
+/// The list has to be freed with [`probe_rs_probe_list_free`].
+///
+/// # Safety
+///
+/// `list` has to point to a writable pointer.
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_probe_list(list: *mut *mut ProbeRsProbeList) -> ProbeRsStatus {
+    ffi(|| {
+        let list = get(list, "list")?;
+
+        let probes = Probe::list_all();
+        let strings = probes
+            .iter()
+            .map(|probe| {
+                (
+                    CString::new(probe.identifier.as_str()).unwrap_or_default(),
+                    probe
+                        .serial_number
+                        .as_deref()
+                        .and_then(|serial_number| CString::new(serial_number).ok()),
+                )
+            })
+            .collect();
+
+        *list = Box::into_raw(Box::new(ProbeRsProbeList { probes, strings }));
+
+        Ok(())
+    })
+}
+
+/// Returns the number of probes in the list.
+///
+/// # Safety
+///
+/// `list` has to be null, or a list returned by [`probe_rs_probe_list`].
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_probe_list_len(list: *const ProbeRsProbeList) -> usize {
+    list.as_ref().map_or(0, |list| list.probes.len())
+}
+
+/// Gets the description of the probe at `index` in the list.
+///
+/// # Safety
+///
+/// `list` has to be a list returned by [`probe_rs_probe_list`], and `info` has
+/// to point to a writable [`ProbeRsProbeInfo`].
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_probe_list_get(
+    list: *mut ProbeRsProbeList,
+    index: usize,
+    info: *mut ProbeRsProbeInfo,
+) -> ProbeRsStatus {
+    ffi(|| {
+        let list = get(list, "list")?;
+        let info = get(info, "info")?;
+
+        let (probe, (identifier, serial_number)) = list
+            .probes
+            .iter()
+            .zip(&list.strings)
+            .nth(index)
+            .ok_or_else(|| FfiError {
+                status: ProbeRsStatus::NotFound,
+                message: format!("There is no probe with the index {index}"),
+            })?;
+
+        *info = ProbeRsProbeInfo {
+            identifier: identifier.as_ptr(),
+            vendor_id: probe.vendor_id,
+            product_id: probe.product_id,
+            serial_number: serial_number
+                .as_ref()
+                .map_or(ptr::null(), |serial_number| serial_number.as_ptr()),
+        };
+
+        Ok(())
+    })
+}
+
+/// Frees a list returned by [`probe_rs_probe_list`]. Passing null is allowed.
+///
+/// # Safety
+///
+/// `list` has to be null, or a list returned by [`probe_rs_probe_list`] which wasn't freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_probe_list_free(list: *mut ProbeRsProbeList) {
+    if !list.is_null() {
+        drop(Box::from_raw(list));
+    }
+}
+
+/// Opens the probe at `index` in the list, and attaches to the target chip `target`.
+///
+/// If `list` is null, the first connected probe is used. The session has to be
+/// closed with [`probe_rs_session_free`].
+///
+/// # Safety
+///
+/// `list` has to be null, or a list returned by [`probe_rs_probe_list`], `target`
+/// has to be a null terminated string, and `session` has to point to a writable pointer.
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_session_open(
+    list: *mut ProbeRsProbeList,
+    index: usize,
+    target: *const c_char,
+    session: *mut *mut ProbeRsSession,
+) -> ProbeRsStatus {
+    ffi(|| {
+        let target = get_str(target, "target")?;
+        let out = get(session, "session")?;
+
+        let session = match list.as_ref() {
+            Some(list) => {
+                let probe = list.probes.get(index).ok_or_else(|| FfiError {
+                    status: ProbeRsStatus::NotFound,
+                    message: format!("There is no probe with the index {index}"),
+                })?;
+
+                probe.open()?.attach(target, Permissions::default())?
+            }
+            None => Session::auto_attach(target, Permissions::default())?,
+        };
+
+        *out = Box::into_raw(Box::new(ProbeRsSession { session }));
+
+        Ok(())
+    })
+}
+
+/// Closes a session returned by [`probe_rs_session_open`]. Passing null is allowed.
+///
+/// # Safety
+///
+/// `session` has to be null, or a session which wasn't freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_session_free(session: *mut ProbeRsSession) {
+    if !session.is_null() {
+        // Closing the session can fail in the drop implementations of the probes.
+        let _ = catch_unwind(AssertUnwindSafe(|| drop(Box::from_raw(session))));
+    }
+}
+
+/// Returns the number of cores of the target.
+///
+/// # Safety
+///
+/// `session` has to be null, or a session returned by [`probe_rs_session_open`].
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_session_core_count(session: *mut ProbeRsSession) -> usize {
+    session
+        .as_ref()
+        .map_or(0, |session| session.session.list_cores().len())
+}
+
+/// Halts the core, and waits up to `timeout_ms` milliseconds until it is halted.
+///
+/// The program counter of the halted core is written to `pc`, if it isn't null.
+///
+/// # Safety
+///
+/// `session` has to be a session returned by [`probe_rs_session_open`], and `pc`
+/// has to be null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_core_halt(
+    session: *mut ProbeRsSession,
+    core_index: usize,
+    timeout_ms: u32,
+    pc: *mut u64,
+) -> ProbeRsStatus {
+    with_core(session, core_index, |core| {
+        let info = core.halt(Duration::from_millis(timeout_ms.into()))?;
+        set(pc, info.pc);
+        Ok(())
+    })
+}
+
+/// Resumes the core.
+///
+/// # Safety
+///
+/// `session` has to be a session returned by [`probe_rs_session_open`].
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_core_run(
+    session: *mut ProbeRsSession,
+    core_index: usize,
+) -> ProbeRsStatus {
+    with_core(session, core_index, |core| Ok(core.run()?))
+}
+
+/// Executes a single instruction on the halted core.
+///
+/// The program counter after the step is written to `pc`, if it isn't null.
+///
+/// # Safety
+///
+/// `session` has to be a session returned by [`probe_rs_session_open`], and `pc`
+/// has to be null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_core_step(
+    session: *mut ProbeRsSession,
+    core_index: usize,
+    pc: *mut u64,
+) -> ProbeRsStatus {
+    with_core(session, core_index, |core| {
+        let info = core.step()?;
+        set(pc, info.pc);
+        Ok(())
+    })
+}
+
+/// Resets the core. If `halt` is true, the core is halted after the reset, waiting
+/// up to `timeout_ms` milliseconds.
+///
+/// # Safety
+///
+/// `session` has to be a session returned by [`probe_rs_session_open`].
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_core_reset(
+    session: *mut ProbeRsSession,
+    core_index: usize,
+    halt: bool,
+    timeout_ms: u32,
+) -> ProbeRsStatus {
+    with_core(session, core_index, |core| {
+        if halt {
+            core.reset_and_halt(Duration::from_millis(timeout_ms.into()))?;
+        } else {
+            core.reset()?;
+        }
+        Ok(())
+    })
+}
+
+/// Writes whether the core is halted to `halted`.
+///
+/// # Safety
+///
+/// `session` has to be a session returned by [`probe_rs_session_open`], and `halted`
+/// has to be writable.
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_core_is_halted(
+    session: *mut ProbeRsSession,
+    core_index: usize,
+    halted: *mut bool,
+) -> ProbeRsStatus {
+    with_core(session, core_index, |core| {
+        let halted = get(halted, "halted")?;
+        *halted = core.core_halted()?;
+        Ok(())
+    })
+}
+
+/// Looks up the ID of the register `name` of the core, for use with [`probe_rs_core_read_register`].
+///
+/// Besides the names of the core, e.g. `R0` or `x10`, the names `pc`, `sp`, `fp` and
+/// `ra` can be used for the program counter, stack pointer, frame pointer and
+/// return address. The names are compared case-insensitively.
+///
+/// # Safety
+///
+/// `session` has to be a session returned by [`probe_rs_session_open`], `name` has to
+/// be a null terminated string, and `register` has to be writable.
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_core_register_id(
+    session: *mut ProbeRsSession,
+    core_index: usize,
+    name: *const c_char,
+    register: *mut u16,
+) -> ProbeRsStatus {
+    with_core(session, core_index, |core| {
+        let name = get_str(name, "name")?;
+        let register = get(register, "register")?;
+
+        let registers = core.registers();
+        let description = match name.to_ascii_lowercase().as_str() {
+            "pc" => Some(registers.program_counter()),
+            "sp" => Some(registers.stack_pointer()),
+            "fp" => Some(registers.frame_pointer()),
+            "ra" => Some(registers.return_address()),
+            _ => registers
+                .platform_registers()
+                .chain(registers.other())
+                .chain(registers.fpu_registers().into_iter().flatten())
+                .find(|description| description.name().eq_ignore_ascii_case(name)),
+        }
+        .ok_or_else(|| FfiError {
+            status: ProbeRsStatus::NotFound,
+            message: format!("The core has no register `{name}`"),
+        })?;
+
+        *register = RegisterId::from(description).0;
+
+        Ok(())
+    })
+}
+
+/// Reads the register with the ID `register` of the halted core.
+///
+/// # Safety
+///
+/// `session` has to be a session returned by [`probe_rs_session_open`], and `value`
+/// has to be writable.
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_core_read_register(
+    session: *mut ProbeRsSession,
+    core_index: usize,
+    register: u16,
+    value: *mut u64,
+) -> ProbeRsStatus {
+    with_core(session, core_index, |core| {
+        let value = get(value, "value")?;
+        *value = core.read_core_reg(RegisterId(register))?;
+        Ok(())
+    })
+}
+
+/// Writes `value` to the register with the ID `register` of the halted core.
+///
+/// # Safety
+///
+/// `session` has to be a session returned by [`probe_rs_session_open`].
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_core_write_register(
+    session: *mut ProbeRsSession,
+    core_index: usize,
+    register: u16,
+    value: u64,
+) -> ProbeRsStatus {
+    with_core(session, core_index, |core| {
+        Ok(core.write_core_reg(RegisterId(register), RegisterValue::from(value))?)
+    })
+}
+
+/// Reads `len` bytes at `address` into `data`.
+///
+/// # Safety
+///
+/// `session` has to be a session returned by [`probe_rs_session_open`], and `data`
+/// has to point to `len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_core_read_memory(
+    session: *mut ProbeRsSession,
+    core_index: usize,
+    address: u64,
+    data: *mut u8,
+    len: usize,
+) -> ProbeRsStatus {
+    with_core(session, core_index, |core| {
+        Ok(core.read(address, get_slice_mut(data, len)?)?)
+    })
+}
+
+/// Writes `len` bytes from `data` to `address`.
+///
+/// # Safety
+///
+/// `session` has to be a session returned by [`probe_rs_session_open`], and `data`
+/// has to point to `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_core_write_memory(
+    session: *mut ProbeRsSession,
+    core_index: usize,
+    address: u64,
+    data: *const u8,
+    len: usize,
+) -> ProbeRsStatus {
+    with_core(session, core_index, |core| {
+        Ok(core.write(address, get_slice(data, len)?)?)
+    })
+}
+
+/// Reads a 32-bit word at `address`, which has to be 4-byte aligned.
+///
+/// # Safety
+///
+/// `session` has to be a session returned by [`probe_rs_session_open`], and `value`
+/// has to be writable.
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_core_read_word_32(
+    session: *mut ProbeRsSession,
+    core_index: usize,
+    address: u64,
+    value: *mut u32,
+) -> ProbeRsStatus {
+    with_core(session, core_index, |core| {
+        let value = get(value, "value")?;
+        *value = core.read_word_32(address)?;
+        Ok(())
+    })
+}
+
+/// Writes a 32-bit word to `address`, which has to be 4-byte aligned.
+///
+/// # Safety
+///
+/// `session` has to be a session returned by [`probe_rs_session_open`].
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_core_write_word_32(
+    session: *mut ProbeRsSession,
+    core_index: usize,
+    address: u64,
+    value: u32,
+) -> ProbeRsStatus {
+    with_core(session, core_index, |core| {
+        Ok(core.write_word_32(address, value)?)
+    })
+}
+
+/// Downloads the file at `path` to the flash of the target.
+///
+/// `format` is one of the `PROBE_RS_FORMAT_*` constants. `base_address` is only
+/// used for binary files, which are written to the start of the flash if it is 0.
+///
+/// # Safety
+///
+/// `session` has to be a session returned by [`probe_rs_session_open`], and `path`
+/// has to be a null terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_download_file(
+    session: *mut ProbeRsSession,
+    path: *const c_char,
+    format: u32,
+    base_address: u64,
+) -> ProbeRsStatus {
+    ffi(|| {
+        let session = get(session, "session")?;
+        let path = get_str(path, "path")?;
+
+        let format = match format {
+            PROBE_RS_FORMAT_BIN => Format::Bin(BinOptions {
+                base_address: (base_address != 0).then_some(base_address),
+                skip: 0,
+            }),
+            PROBE_RS_FORMAT_HEX => Format::Hex,
+            PROBE_RS_FORMAT_ELF => Format::Elf,
+            PROBE_RS_FORMAT_SREC => Format::Srec,
+            PROBE_RS_FORMAT_UF2 => Format::Uf2,
+            _ => {
+                return Err(FfiError::invalid_argument(format!(
+                    "Unknown file format {format}"
+                )))
+            }
+        };
+
+        Ok(flashing::download_file(
+            &mut session.session,
+            Path::new(path),
+            format,
+        )?)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use probe_rs::FakeProbe;
+
+    fn session() -> *mut ProbeRsSession {
+        let mut fake_probe = FakeProbe::new();
+        fake_probe.add_memory_region(0x2000_0000, vec![0; 0x100]);
+
+        let session = fake_probe
+            .into_probe()
+            .attach("nrf51822_xxAC", Permissions::default())
+            .unwrap();
+
+        Box::into_raw(Box::new(ProbeRsSession { session }))
+    }
+
+    fn last_error() -> String {
+        unsafe { CStr::from_ptr(probe_rs_last_error()) }
+            .to_str()
+            .unwrap()
+            .to_owned()
+    }
+
+    #[test]
+    fn memory_access() {
+        let session = session();
+
+        unsafe {
+            assert_eq!(probe_rs_session_core_count(session), 1);
+
+            let data = [1, 2, 3, 4, 5, 6];
+            assert_eq!(
+                probe_rs_core_write_memory(session, 0, 0x2000_0001, data.as_ptr(), data.len()),
+                ProbeRsStatus::Ok
+            );
+
+            let mut read = [0; 8];
+            assert_eq!(
+                probe_rs_core_read_memory(session, 0, 0x2000_0000, read.as_mut_ptr(), read.len()),
+                ProbeRsStatus::Ok
+            );
+            assert_eq!(read, [0, 1, 2, 3, 4, 5, 6, 0]);
+
+            let mut word = 0;
+            assert_eq!(
+                probe_rs_core_read_word_32(session, 0, 0x2000_0004, &mut word),
+                ProbeRsStatus::Ok
+            );
+            assert_eq!(word, 0x0006_0504);
+            assert_eq!(last_error(), "");
+
+            probe_rs_session_free(session);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let session = session();
+
+        unsafe {
+            assert_eq!(
+                probe_rs_core_run(session, 1),
+                ProbeRsStatus::NotFound,
+                "{}",
+                last_error()
+            );
+            assert!(!last_error().is_empty());
+
+            assert_eq!(
+                probe_rs_core_read_memory(session, 0, 0x2000_0000, ptr::null_mut(), 4),
+                ProbeRsStatus::InvalidArgument
+            );
+            assert_eq!(last_error(), "`data` is null");
+
+            assert_eq!(
+                probe_rs_core_run(ptr::null_mut(), 0),
+                ProbeRsStatus::InvalidArgument
+            );
+
+            let path = CString::new("firmware.bin").unwrap();
+            assert_eq!(
+                probe_rs_download_file(session, path.as_ptr(), 42, 0),
+                ProbeRsStatus::InvalidArgument
+            );
+
+            let name = CString::new("R42").unwrap();
+            let mut register = 0;
+            assert_eq!(
+                probe_rs_core_register_id(session, 0, name.as_ptr(), &mut register),
+                ProbeRsStatus::NotFound
+            );
+
+            probe_rs_session_free(session);
+        }
+    }
+
+    #[test]
+    fn register_ids() {
+        let session = session();
+
+        unsafe {
+            let name = CString::new("pc").unwrap();
+            let mut pc = 0;
+            assert_eq!(
+                probe_rs_core_register_id(session, 0, name.as_ptr(), &mut pc),
+                ProbeRsStatus::Ok
+            );
+
+            let name = CString::new("r0").unwrap();
+            let mut r0 = 0;
+            assert_eq!(
+                probe_rs_core_register_id(session, 0, name.as_ptr(), &mut r0),
+                ProbeRsStatus::Ok
+            );
+            assert_ne!(pc, r0);
+
+            probe_rs_session_free(session);
+        }
+    }
+
+    #[test]
+    fn empty_probe_list() {
+        unsafe {
+            assert_eq!(probe_rs_probe_list_len(ptr::null()), 0);
+            assert_eq!(
+                probe_rs_probe_list(ptr::null_mut()),
+                ProbeRsStatus::InvalidArgument
+            );
+            probe_rs_probe_list_free(ptr::null_mut());
+        }
+    }
+}
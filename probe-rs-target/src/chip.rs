@@ -1,4 +1,5 @@
-use super::memory::MemoryRegion;
+use super::memory::{BankSwap, MemoryAlias, MemoryRegion};
+use super::sequence::SequenceDescription;
 use crate::{serialize::hex_option, CoreType};
 use serde::{Deserialize, Serialize};
 /// A single chip variant.
@@ -27,6 +28,17 @@ pub struct Chip {
     /// [`ChipFamily::flash_algorithms`]: crate::ChipFamily::flash_algorithms
     #[serde(default)]
     pub flash_algorithms: Vec<String>,
+    /// Debug sequences from the debug description of a CMSIS-Pack.
+    ///
+    /// These replace the built-in debug sequences of the same name.
+    #[serde(default)]
+    pub sequences: Vec<SequenceDescription>,
+    /// The flash banks which can be swapped, if the chip has a dual-bank flash.
+    #[serde(default)]
+    pub bank_swap: Option<BankSwap>,
+    /// The method used to reset the chip, unless a different one is requested.
+    #[serde(default)]
+    pub default_reset_type: ResetType,
 }
 
 impl Chip {
@@ -41,13 +53,35 @@ impl Chip {
                 name: "main".to_string(),
                 core_type,
                 core_access_options: CoreAccessOptions::Arm(ArmCoreAccessOptions::default()),
+                memory_aliases: vec![],
             }],
             memory_map: vec![],
             flash_algorithms: vec![],
+            sequences: vec![],
+            bank_swap: None,
+            default_reset_type: ResetType::default(),
         }
     }
 }
 
+/// The method used to reset a chip or one of its cores.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ResetType {
+    /// Use the reset sequence of the chip.
+    ///
+    /// Unless the chip has a custom sequence, this is the same as [`ResetType::System`].
+    #[default]
+    Sequence,
+    /// Assert the reset pin of the probe.
+    Hardware,
+    /// Request a reset of the whole system by software, e.g. with `AIRCR.SYSRESETREQ`
+    /// on Cortex-M cores, or `dmcontrol.ndmreset` on RISC-V cores.
+    System,
+    /// Reset only the core, and not the peripherals, e.g. with `AIRCR.VECTRESET` on ARMv7-M cores,
+    /// or `dmcontrol.hartreset` on RISC-V cores.
+    Core,
+}
+
 /// An individual core inside a chip
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Core {
@@ -61,6 +95,13 @@ pub struct Core {
 
     /// The AP number to access the core
     pub core_access_options: CoreAccessOptions,
+
+    /// Address ranges which the core sees at a different address than the debugger,
+    /// e.g. if the core is accessed through the access port of another core.
+    ///
+    /// The memory map contains the addresses as seen by the core.
+    #[serde(default)]
+    pub memory_aliases: Vec<MemoryAlias>,
 }
 
 /// The data required to access a core
@@ -70,6 +111,8 @@ pub enum CoreAccessOptions {
     Arm(ArmCoreAccessOptions),
     /// Riscv specific options
     Riscv(RiscvCoreAccessOptions),
+    /// Xtensa specific options
+    Xtensa(XtensaCoreAccessOptions),
 }
 
 /// The data required to access an ARM core
@@ -87,8 +130,30 @@ pub struct ArmCoreAccessOptions {
     /// Required in ARMv8-A
     #[serde(serialize_with = "hex_option")]
     pub cti_base: Option<u64>,
+    /// Set for the redundant core of a lockstep pair, e.g. a Cortex-R5 checker core.
+    ///
+    /// A redundant core executes the same instructions as the lead core,
+    /// so it is never accessed. Only the lead core is debugged.
+    #[serde(default)]
+    pub lockstep_redundant: bool,
+    /// Set for cores with caches, e.g. the Cortex-M7, to keep the caches coherent with
+    /// the memory accesses of the debugger.
+    ///
+    /// The data cache is cleaned before the memory is accessed, and the caches are
+    /// invalidated after the memory was written, e.g. when a software breakpoint is set.
+    #[serde(default)]
+    pub cache_maintenance: bool,
 }
 
 /// The data required to access a Risc-V core
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RiscvCoreAccessOptions {
+    /// The index of the hart in the debug module.
+    /// If not set, the index of the core in the target description is used.
+    #[serde(default)]
+    pub hart_id: Option<u32>,
+}
+
+/// The data required to access an Xtensa core
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RiscvCoreAccessOptions {}
+pub struct XtensaCoreAccessOptions {}
@@ -1,4 +1,4 @@
-use crate::CoreAccessOptions;
+use crate::{CoreAccessOptions, MemoryRegion};
 
 use super::chip::Chip;
 use super::flash_algorithm::RawFlashAlgorithm;
@@ -10,7 +10,8 @@ use serde::{Deserialize, Serialize};
 ///
 /// This is used for diagnostics, when
 /// an error related to a target description occurs.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum TargetDescriptionSource {
     /// The target description is a generic target description,
     /// which just describes a core type (e.g. M4), without any
@@ -34,6 +35,8 @@ pub enum CoreType {
     Armv7a,
     /// ARMv7-M: Cortex M3
     Armv7m,
+    /// ARMv7-R: Cortex R4, R5
+    Armv7r,
     /// ARMv7e-M: Cortex M4, M7
     Armv7em,
     /// ARMv7-A: Cortex A35, A55, A72
@@ -42,6 +45,8 @@ pub enum CoreType {
     Armv8m,
     /// RISC-V
     Riscv,
+    /// Xtensa: ESP32, ESP32-S3
+    Xtensa,
 }
 
 impl CoreType {
@@ -55,12 +60,15 @@ impl CoreType {
 }
 
 /// The architecture family of a specific [`CoreType`].
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Architecture {
     /// An ARM core of one of the specific types [`CoreType::Armv6m`], [`CoreType::Armv7m`], [`CoreType::Armv7em`] or [`CoreType::Armv8m`]
     Arm,
     /// A RISC-V core.
     Riscv,
+    /// An Xtensa core.
+    Xtensa,
 }
 
 impl CoreType {
@@ -68,13 +76,14 @@ impl CoreType {
     pub fn architecture(&self) -> Architecture {
         match self {
             CoreType::Riscv => Architecture::Riscv,
+            CoreType::Xtensa => Architecture::Xtensa,
             _ => Architecture::Arm,
         }
     }
 }
 
 /// Instruction set used by a core
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum InstructionSet {
     /// ARM Thumb 2 instruction set
     Thumb2,
@@ -86,6 +95,8 @@ pub enum InstructionSet {
     RV32,
     /// RISC-V 32-bit compressed instruction sets (RV32C) - covers all ISA variants that allow compressed 16-bit instructions.
     RV32C,
+    /// Xtensa instruction set, with 24-bit instructions and the 16-bit code density option.
+    Xtensa,
 }
 
 impl InstructionSet {
@@ -100,11 +111,15 @@ impl InstructionSet {
             InstructionSet::A64 => 4,
             InstructionSet::RV32 => 4,
             InstructionSet::RV32C => 2,
+            InstructionSet::Xtensa => 2,
         }
     }
-    /// Get the maximum instruction size in bytes. All supported architectures have a maximum instruction size of 4 bytes.
+    /// Get the maximum instruction size in bytes.
     pub fn get_maximum_instruction_size(&self) -> u8 {
-        4
+        match self {
+            InstructionSet::Xtensa => 3,
+            _ => 4,
+        }
     }
 }
 
@@ -133,6 +148,12 @@ pub struct ChipFamily {
     pub variants: Vec<Chip>,
     /// This vector holds all available algorithms.
     pub flash_algorithms: Vec<RawFlashAlgorithm>,
+    /// The name of the debug sequence used for all variants of the family.
+    ///
+    /// This selects one of the sequences implemented in probe-rs, or a sequence registered at
+    /// runtime. If it is not set, the sequence is chosen based on the name of the variant.
+    #[serde(default)]
+    pub debug_sequence: Option<String>,
     #[serde(skip, default = "default_source")]
     /// Source of the target description, used for diagnostics
     pub source: TargetDescriptionSource,
@@ -164,6 +185,40 @@ impl ChipFamily {
                 }
             }
 
+            // Make sure the swapped banks exist and have the same size.
+            if let Some(bank_swap) = &variant.bank_swap {
+                let sizes = bank_swap
+                    .banks
+                    .iter()
+                    .map(|name| {
+                        variant
+                            .memory_map
+                            .iter()
+                            .find_map(|region| match region {
+                                MemoryRegion::Nvm(region)
+                                    if region.name.as_deref() == Some(name.as_str()) =>
+                                {
+                                    Some(region.range.end - region.range.start)
+                                }
+                                _ => None,
+                            })
+                            .ok_or_else(|| {
+                                format!(
+                                    "unknown flash bank `{}` for variant `{}`",
+                                    name, variant.name
+                                )
+                            })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                if sizes[0] != sizes[1] {
+                    return Err(format!(
+                        "swapped flash banks of variant `{}` have different sizes",
+                        variant.name
+                    ));
+                }
+            }
+
             // Check that there is at least one core.
             if let Some(core) = variant.cores.get(0) {
                 // Make sure that the core types (architectures) are not mixed.
@@ -185,6 +240,31 @@ impl ChipFamily {
                 ));
             }
 
+            // Cores are selected by name, so the names have to be unique.
+            for (index, core) in variant.cores.iter().enumerate() {
+                if variant.cores[..index]
+                    .iter()
+                    .any(|other| other.name == core.name)
+                {
+                    return Err(format!(
+                        "duplicate core name `{}` for variant `{}`",
+                        core.name, variant.name
+                    ));
+                }
+            }
+
+            // Make sure the memory regions are accessed by cores of the variant.
+            for region in &variant.memory_map {
+                for core_name in region.cores() {
+                    if !variant.cores.iter().any(|core| &core.name == core_name) {
+                        return Err(format!(
+                            "unknown core `{}` in the memory map of variant `{}`",
+                            core_name, variant.name
+                        ));
+                    }
+                }
+            }
+
             // Core specific validation logic based on type
             for core in variant.cores.iter() {
                 // The core access options must match the core type specified
@@ -196,6 +276,7 @@ impl ChipFamily {
                                 | CoreType::Armv7a
                                 | CoreType::Armv7em
                                 | CoreType::Armv7m
+                                | CoreType::Armv7r
                                 | CoreType::Armv8a
                                 | CoreType::Armv8m
                         ) {
@@ -205,8 +286,10 @@ impl ChipFamily {
                             ));
                         }
 
-                        if matches!(core.core_type, CoreType::Armv7a | CoreType::Armv8a)
-                            && options.debug_base.is_none()
+                        if matches!(
+                            core.core_type,
+                            CoreType::Armv7a | CoreType::Armv7r | CoreType::Armv8a
+                        ) && options.debug_base.is_none()
                         {
                             return Err(format!("Core {} requires setting debug_base", core.name));
                         }
@@ -223,6 +306,14 @@ impl ChipFamily {
                             ));
                         }
                     }
+                    CoreAccessOptions::Xtensa(_) => {
+                        if core.core_type != CoreType::Xtensa {
+                            return Err(format!(
+                                "Xtensa options don't match core type {:?} on core {}",
+                                core.core_type, core.name
+                            ));
+                        }
+                    }
                 }
             }
         }
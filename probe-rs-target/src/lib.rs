@@ -16,15 +16,20 @@ mod chip_family;
 mod flash_algorithm;
 mod flash_properties;
 mod memory;
+mod sequence;
 pub(crate) mod serialize;
 
-pub use chip::{ArmCoreAccessOptions, Chip, Core, CoreAccessOptions, RiscvCoreAccessOptions};
+pub use chip::{
+    ArmCoreAccessOptions, Chip, Core, CoreAccessOptions, ResetType, RiscvCoreAccessOptions,
+    XtensaCoreAccessOptions,
+};
 pub use chip_family::{
     Architecture, ChipFamily, CoreType, InstructionSet, TargetDescriptionSource,
 };
 pub use flash_algorithm::RawFlashAlgorithm;
 pub use flash_properties::FlashProperties;
 pub use memory::{
-    GenericRegion, MemoryRange, MemoryRegion, NvmRegion, PageInfo, RamRegion, SectorDescription,
-    SectorInfo,
+    BankSwap, GenericRegion, MemoryAccess, MemoryAlias, MemoryRange, MemoryRegion, NvmRegion,
+    PageInfo, RamRegion, SectorDescription, SectorInfo,
 };
+pub use sequence::{SequenceBlock, SequenceControl, SequenceDescription, SequenceElement};
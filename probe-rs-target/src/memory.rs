@@ -15,6 +15,19 @@ pub struct NvmRegion {
     pub is_boot_memory: bool,
     /// List of cores that can access this region
     pub cores: Vec<String>,
+    /// The size of the smallest unit which can be written, e.g. the ECC word of the flash.
+    ///
+    /// Writes are aligned to this size, and padded with the erased byte value.
+    #[serde(default)]
+    pub min_write_size: Option<u32>,
+    /// True if a written unit can't be written again before it is erased, e.g. because of ECC.
+    ///
+    /// This also applies to units which are written with the erased byte value.
+    #[serde(default)]
+    pub no_overwrite: bool,
+    /// Restrictions for accesses to the region by the debugger.
+    #[serde(default)]
+    pub access: Option<MemoryAccess>,
 }
 
 impl NvmRegion {
@@ -26,6 +39,22 @@ impl NvmRegion {
     }
 }
 
+/// Describes a dual-bank flash, whose banks can be swapped in the memory map.
+///
+/// When the banks are swapped, the bank which is normally mapped at the address of the
+/// second bank is mapped at the address of the first bank, and vice versa.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BankSwap {
+    /// The names of the two [`NvmRegion`]s which are swapped. Both banks have the same size.
+    pub banks: [String; 2],
+    /// The address of the register which contains the bank swap option bits.
+    #[serde(serialize_with = "hex_u_int")]
+    pub register: u64,
+    /// The banks are swapped if any of these bits is set in the register.
+    #[serde(serialize_with = "hex_u_int")]
+    pub mask: u32,
+}
+
 /// Represents a region in RAM.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct RamRegion {
@@ -39,6 +68,9 @@ pub struct RamRegion {
     pub is_boot_memory: bool,
     /// List of cores that can access this region
     pub cores: Vec<String>,
+    /// Restrictions for accesses to the region by the debugger.
+    #[serde(default)]
+    pub access: Option<MemoryAccess>,
 }
 
 /// Represents a generic region.
@@ -51,6 +83,65 @@ pub struct GenericRegion {
     pub range: Range<u64>,
     /// List of cores that can access this region
     pub cores: Vec<String>,
+    /// Restrictions for accesses to the region by the debugger, e.g. for
+    /// peripherals where a read has side effects.
+    #[serde(default)]
+    pub access: Option<MemoryAccess>,
+}
+
+/// Restrictions for the accesses to a memory region by the debugger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MemoryAccess {
+    /// True if the region can be read.
+    ///
+    /// This should be false for regions where a read has side effects,
+    /// e.g. FIFO registers which drop the data which is read.
+    #[serde(default = "default_true")]
+    pub read: bool,
+    /// True if the region can be written, false if the region is write-protected.
+    #[serde(default = "default_true")]
+    pub write: bool,
+    /// True if the region can only be accessed in the secure state.
+    #[serde(default)]
+    pub secure_only: bool,
+}
+
+impl Default for MemoryAccess {
+    fn default() -> Self {
+        Self {
+            read: true,
+            write: true,
+            secure_only: false,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// An address range which a core sees at a different address than the debugger.
+///
+/// E.g. a secondary core which is debugged through the access port of the main
+/// core, and sees its own RAM at an alias address.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MemoryAlias {
+    /// Address range, as seen by the core
+    #[serde(serialize_with = "hex_range")]
+    pub range: Range<u64>,
+    /// Address of the start of the range, as seen by the debugger
+    #[serde(serialize_with = "hex_u_int")]
+    pub target: u64,
+}
+
+impl MemoryAlias {
+    /// Translates an address seen by the core to the address seen by the debugger,
+    /// if it is contained in the alias.
+    pub fn translate(&self, address: u64) -> Option<u64> {
+        self.range
+            .contains(&address)
+            .then(|| self.target + (address - self.range.start))
+    }
 }
 
 /// Holds information about a specific, individual flash
@@ -142,10 +233,75 @@ pub enum MemoryRegion {
     Nvm(NvmRegion),
 }
 
+impl MemoryRegion {
+    /// Returns the address range of the region.
+    pub fn address_range(&self) -> Range<u64> {
+        match self {
+            MemoryRegion::Ram(region) => region.range.clone(),
+            MemoryRegion::Generic(region) => region.range.clone(),
+            MemoryRegion::Nvm(region) => region.range.clone(),
+        }
+    }
+
+    /// Returns the restrictions for accesses to the region by the debugger.
+    pub fn access(&self) -> MemoryAccess {
+        match self {
+            MemoryRegion::Ram(region) => region.access,
+            MemoryRegion::Generic(region) => region.access,
+            MemoryRegion::Nvm(region) => region.access,
+        }
+        .unwrap_or_default()
+    }
+
+    /// Returns the names of the cores which can access the region.
+    pub fn cores(&self) -> &[String] {
+        match self {
+            MemoryRegion::Ram(region) => &region.cores,
+            MemoryRegion::Generic(region) => &region.cores,
+            MemoryRegion::Nvm(region) => &region.cores,
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    fn memory_alias_translation() {
+        let alias = MemoryAlias {
+            range: 0x0..0x1_0000,
+            target: 0x2004_0000,
+        };
+
+        assert_eq!(alias.translate(0x0), Some(0x2004_0000));
+        assert_eq!(alias.translate(0xFFFC), Some(0x2004_FFFC));
+        assert_eq!(alias.translate(0x1_0000), None);
+    }
+
+    #[test]
+    fn memory_access_defaults() {
+        let mut region = GenericRegion {
+            name: Some("FIFO".to_owned()),
+            range: 0x4000_0000..0x4000_0100,
+            cores: vec!["main".to_owned()],
+            access: None,
+        };
+
+        // Regions without restrictions can be read and written
+        assert_eq!(
+            MemoryRegion::Generic(region.clone()).access(),
+            MemoryAccess::default()
+        );
+        assert!(MemoryAccess::default().read && MemoryAccess::default().write);
+
+        region.access = Some(MemoryAccess {
+            read: false,
+            ..Default::default()
+        });
+        assert!(!MemoryRegion::Generic(region).access().read);
+    }
+
     #[test]
     fn contains_range1() {
         let range1 = 0..1;
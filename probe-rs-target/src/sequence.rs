@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+/// A debug sequence, as found in the `<sequences>` element of the debug description of a
+/// CMSIS-Pack.
+///
+/// Sequences with the names of the standard debug sequences, e.g. `ResetSystem` or
+/// `DebugPortStart`, replace the built-in implementation of the respective sequence.
+/// See the [CMSIS-Pack documentation] for details.
+///
+/// [CMSIS-Pack documentation]: https://open-cmsis-pack.github.io/Open-CMSIS-Pack-Spec/main/html/debug_description.html#sequences
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SequenceDescription {
+    /// The name of the sequence, e.g. `ResetSystem`.
+    pub name: String,
+    /// A disabled sequence does nothing, instead of running the built-in implementation.
+    #[serde(default)]
+    pub disable: bool,
+    /// The blocks and control elements of the sequence, executed in order.
+    #[serde(default)]
+    pub body: Vec<SequenceElement>,
+}
+
+/// An element of a debug sequence.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SequenceElement {
+    /// A block of statements.
+    Block(SequenceBlock),
+    /// Conditional or repeated execution of other elements.
+    Control(SequenceControl),
+}
+
+/// A block of statements of a debug sequence, e.g. `__var value = Read32(0x40000000);`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SequenceBlock {
+    /// The statements of the block are executed without interruption.
+    #[serde(default)]
+    pub atomic: bool,
+    /// The statements of the block.
+    pub code: String,
+}
+
+/// The `<control>` element of a debug sequence.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SequenceControl {
+    /// The body is only executed if this expression is not zero.
+    #[serde(rename = "if", default)]
+    pub condition: Option<String>,
+    /// The body is executed repeatedly, as long as this expression is not zero.
+    #[serde(rename = "while", default)]
+    pub while_condition: Option<String>,
+    /// The timeout of the `while` loop in microseconds. `0` disables the timeout.
+    #[serde(default)]
+    pub timeout: u64,
+    /// The nested elements.
+    #[serde(default)]
+    pub body: Vec<SequenceElement>,
+}
@@ -0,0 +1,247 @@
+//! Discovery of APv2 access ports on ADIv6 debug ports.
+//!
+//! On an ADIv6 debug port, the access ports are not numbered. Instead, each access port
+//! occupies a 4 kB block in the address space of the debug port, and the access ports are
+//! described by a (possibly nested) ROM table, which is pointed to by the `BASEPTR` registers
+//! of the debug port.
+
+use crate::architecture::arm::ArmError;
+
+/// Offset of the APv1 compatible register block in an APv2 access port.
+///
+/// The registers of an APv2 access port are located at the same offsets as in an APv1
+/// access port, with this offset added. For example, the IDR register is at `0xDFC`
+/// instead of `0xFC`.
+pub(crate) const APV2_REGISTER_OFFSET: u64 = 0xD00;
+
+/// Offset of the DEVARCH register in a CoreSight component.
+const DEVARCH_OFFSET: u64 = 0xFBC;
+/// Offset of the DEVID register in a CoreSight component.
+const DEVID_OFFSET: u64 = 0xFC8;
+/// Offset of the CIDR1 register, which contains the component class.
+const CIDR1_OFFSET: u64 = 0xFF4;
+
+/// Class 0x1 ROM table, as used in ADIv5.
+const CLASS_ROM_TABLE: u32 = 0x1;
+/// CoreSight component, the type is determined by the DEVARCH register.
+const CLASS_CORESIGHT: u32 = 0x9;
+
+/// The JEP106 code of Arm, as used in the DEVARCH.ARCHITECT field.
+const ARCHITECT_ARM: u32 = 0x23B;
+/// DEVARCH.ARCHPART value of a Class 0x9 ROM table.
+const ARCHPART_ROM_TABLE: u32 = 0xAF7;
+
+/// Maximum nesting depth of ROM tables, to avoid loops in broken ROM tables.
+const MAX_ROM_TABLE_DEPTH: usize = 8;
+
+/// Maximum number of access ports which can be addressed.
+const MAX_ACCESS_PORTS: usize = 256;
+
+/// A component found in the address space of the debug port.
+#[derive(Debug, PartialEq, Eq)]
+enum ComponentKind {
+    /// A ROM table, with 32 or 64 bit wide entries.
+    RomTable { wide_entries: bool },
+    /// An access port.
+    AccessPort,
+    /// Any other component, which is ignored.
+    Other,
+}
+
+/// Finds the base addresses of all access ports, by walking the ROM table at `root`.
+///
+/// `read` has to read a 32 bit word from the address space of the debug port.
+pub(crate) fn find_access_ports(
+    root: u64,
+    mut read: impl FnMut(u64) -> Result<u32, ArmError>,
+) -> Result<Vec<u64>, ArmError> {
+    let mut access_ports = Vec::new();
+
+    visit_component(root, 0, &mut read, &mut access_ports)?;
+
+    Ok(access_ports)
+}
+
+fn visit_component(
+    base: u64,
+    depth: usize,
+    read: &mut impl FnMut(u64) -> Result<u32, ArmError>,
+    access_ports: &mut Vec<u64>,
+) -> Result<(), ArmError> {
+    match component_kind(base, read)? {
+        ComponentKind::AccessPort => {
+            tracing::debug!("Found APv2 access port at {:#x}", base);
+
+            if access_ports.len() < MAX_ACCESS_PORTS {
+                access_ports.push(base);
+            } else {
+                tracing::warn!("Ignoring access port at {:#x}, too many access ports", base);
+            }
+        }
+        ComponentKind::RomTable { wide_entries } => {
+            if depth >= MAX_ROM_TABLE_DEPTH {
+                tracing::warn!("Ignoring ROM table at {:#x}, nested too deeply", base);
+                return Ok(());
+            }
+
+            for child in rom_table_entries(base, wide_entries, read)? {
+                visit_component(child, depth + 1, read, access_ports)?;
+            }
+        }
+        ComponentKind::Other => {
+            tracing::debug!("Ignoring component at {:#x}", base);
+        }
+    }
+
+    Ok(())
+}
+
+fn component_kind(
+    base: u64,
+    read: &mut impl FnMut(u64) -> Result<u32, ArmError>,
+) -> Result<ComponentKind, ArmError> {
+    let class = (read(base + CIDR1_OFFSET)? >> 4) & 0xF;
+
+    match class {
+        CLASS_ROM_TABLE => Ok(ComponentKind::RomTable {
+            wide_entries: false,
+        }),
+        CLASS_CORESIGHT => {
+            let devarch = read(base + DEVARCH_OFFSET)?;
+
+            let architect = devarch >> 21;
+            let present = devarch & (1 << 20) != 0;
+            let archpart = devarch & 0xFFF;
+
+            if !present || architect != ARCHITECT_ARM {
+                return Ok(ComponentKind::Other);
+            }
+
+            if archpart == ARCHPART_ROM_TABLE {
+                // DEVID.FORMAT indicates if the entries are 32 or 64 bit wide.
+                let devid = read(base + DEVID_OFFSET)?;
+
+                Ok(ComponentKind::RomTable {
+                    wide_entries: devid & 0xF == 1,
+                })
+            } else if archpart & 0xF0F == 0xA07 {
+                // All AP architectures (MEM-AP, JTAG-AP, ...) use ARCHPART 0xAx7.
+                Ok(ComponentKind::AccessPort)
+            } else {
+                Ok(ComponentKind::Other)
+            }
+        }
+        _ => Ok(ComponentKind::Other),
+    }
+}
+
+/// Reads the addresses of all present components from a ROM table.
+fn rom_table_entries(
+    base: u64,
+    wide_entries: bool,
+    read: &mut impl FnMut(u64) -> Result<u32, ArmError>,
+) -> Result<Vec<u64>, ArmError> {
+    // The entries are located in the first 2 kB of the ROM table for Class 0x9 ROM tables,
+    // and in the first 3.75 kB for Class 0x1 ROM tables. Using the smaller limit is fine,
+    // as the table is terminated by an empty entry.
+    let entry_size = if wide_entries { 8 } else { 4 };
+    let max_entries = 0x800 / entry_size;
+
+    let mut children = Vec::new();
+
+    for index in 0..max_entries {
+        let address = base + index * entry_size;
+
+        let entry = if wide_entries {
+            u64::from(read(address)?) | (u64::from(read(address + 4)?) << 32)
+        } else {
+            // The offset is a signed value, so the entry has to be sign extended.
+            read(address)? as i32 as i64 as u64
+        };
+
+        if entry == 0 {
+            break;
+        }
+
+        // Bit 0 indicates if the entry is present.
+        if entry & 1 == 0 {
+            continue;
+        }
+
+        children.push(base.wrapping_add(entry & !0xFFF));
+    }
+
+    Ok(children)
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::find_access_ports;
+
+    const CIDR1_ROM_TABLE: u32 = 0x10;
+    const CIDR1_CORESIGHT: u32 = 0x90;
+
+    const DEVARCH_ROM_TABLE: u32 = (0x23B << 21) | (1 << 20) | 0x0AF7;
+    const DEVARCH_MEM_AP: u32 = (0x23B << 21) | (1 << 20) | 0x0A17;
+    const DEVARCH_OTHER: u32 = (0x23B << 21) | (1 << 20) | 0x4A13;
+
+    struct AddressSpace(HashMap<u64, u32>);
+
+    impl AddressSpace {
+        fn component(&mut self, base: u64, cidr1: u32, devarch: u32) {
+            self.0.insert(base + 0xFF4, cidr1);
+            self.0.insert(base + 0xFBC, devarch);
+        }
+
+        fn read(&self, address: u64) -> u32 {
+            self.0.get(&address).copied().unwrap_or(0)
+        }
+    }
+
+    #[test]
+    fn nested_rom_tables() {
+        let mut space = AddressSpace(HashMap::new());
+
+        // Root ROM table, with two APs and a nested ROM table.
+        space.component(0x0, CIDR1_CORESIGHT, DEVARCH_ROM_TABLE);
+        space.0.insert(0x0, 0x0000_2003);
+        space.0.insert(0x4, 0x0001_0003);
+        // Not present, but the table continues.
+        space.0.insert(0x8, 0x0000_5002);
+        space.0.insert(0xC, 0x0000_3003);
+
+        space.component(0x2000, CIDR1_CORESIGHT, DEVARCH_MEM_AP);
+        space.component(0x3000, CIDR1_CORESIGHT, DEVARCH_OTHER);
+
+        // Nested class 0x1 ROM table, with a negative offset to an AP.
+        space.component(0x10000, CIDR1_ROM_TABLE, 0);
+        space.0.insert(0x10000, 0x0000_1003);
+        space.0.insert(0x10004, 0xFFFF_F003);
+
+        space.component(0x11000, CIDR1_CORESIGHT, DEVARCH_MEM_AP);
+        space.component(0xF000, CIDR1_CORESIGHT, DEVARCH_MEM_AP);
+
+        let access_ports = find_access_ports(0x0, |address| Ok(space.read(address))).unwrap();
+
+        assert_eq!(access_ports, vec![0x2000, 0x11000, 0xF000]);
+    }
+
+    #[test]
+    fn wide_rom_table_entries() {
+        let mut space = AddressSpace(HashMap::new());
+
+        space.component(0x1_0000_0000, CIDR1_CORESIGHT, DEVARCH_ROM_TABLE);
+        space.0.insert(0x1_0000_0FC8, 1);
+        space.0.insert(0x1_0000_0000, 0x0000_4003);
+        space.0.insert(0x1_0000_0004, 0x0000_0001);
+
+        space.component(0x2_0000_4000, CIDR1_CORESIGHT, DEVARCH_MEM_AP);
+
+        let access_ports =
+            find_access_ports(0x1_0000_0000, |address| Ok(space.read(address))).unwrap();
+
+        assert_eq!(access_ports, vec![0x2_0000_4000]);
+    }
+}
@@ -85,3 +85,30 @@ define_ap_register!(
         | (u32::from(value.VARIANT) << 4)
         | (value.TYPE.to_u32().unwrap())
 );
+
+impl IDR {
+    /// The JEP106 code of the designer of this access port.
+    pub fn designer(&self) -> jep106::JEP106Code {
+        let continuation_code = (self.DESIGNER >> 7) as u8;
+        let identity_code = (self.DESIGNER & 0x7F) as u8;
+
+        jep106::JEP106Code::new(continuation_code, identity_code)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ApClass, ApType, IDR};
+
+    #[test]
+    fn decode_idr() {
+        // AHB-AP of a Cortex-M4
+        let idr = IDR::try_from(0x2477_0011).unwrap();
+
+        assert_eq!(idr.CLASS, ApClass::MemAp);
+        assert_eq!(idr.TYPE, ApType::AmbaAhb3);
+        assert_eq!(idr.VARIANT, 1);
+        assert_eq!(idr.REVISION, 2);
+        assert_eq!(idr.designer().get(), Some("ARM Ltd"));
+    }
+}
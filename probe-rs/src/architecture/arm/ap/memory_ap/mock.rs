@@ -12,7 +12,12 @@ use std::convert::TryInto;
 
 #[derive(Debug)]
 pub struct MockMemoryAp {
+    /// Memory starting at address 0.
     pub memory: Vec<u8>,
+    /// Additional memory regions, as pairs of start address and content.
+    ///
+    /// These take precedence over `memory`.
+    pub regions: Vec<(u32, Vec<u8>)>,
     store: HashMap<u8, u32>,
 }
 
@@ -28,9 +33,54 @@ impl MockMemoryAp {
         store.insert(DRW::ADDRESS, 0);
         Self {
             memory: std::iter::repeat(1..=255).flatten().take(1 << 15).collect(),
+            regions: Vec::new(),
             store,
         }
     }
+
+    /// Returns the `len` bytes of memory at `address`, if they are all backed by memory.
+    fn memory_slice(&self, address: u32, len: usize) -> Option<&[u8]> {
+        for (start, data) in &self.regions {
+            if let Some(offset) = address.checked_sub(*start) {
+                if let Some(slice) = data.get(offset as usize..offset as usize + len) {
+                    return Some(slice);
+                }
+            }
+        }
+
+        self.memory.get(address as usize..address as usize + len)
+    }
+
+    /// Mutable version of [`MockMemoryAp::memory_slice`].
+    fn memory_slice_mut(&mut self, address: u32, len: usize) -> Option<&mut [u8]> {
+        let region = self.regions.iter().position(|(start, data)| {
+            address
+                .checked_sub(*start)
+                .map(|offset| offset as usize + len <= data.len())
+                .unwrap_or(false)
+        });
+
+        match region {
+            Some(index) => {
+                let (start, data) = &mut self.regions[index];
+                let offset = (address - *start) as usize;
+                data.get_mut(offset..offset + len)
+            }
+            None => self
+                .memory
+                .get_mut(address as usize..address as usize + len),
+        }
+    }
+}
+
+/// Packed transfers access a full word of memory with each DRW access,
+/// so they are handled like 32 bit transfers.
+fn packed_size(csw: &CSW) -> DataSize {
+    if csw.AddrInc == AddressIncrement::Packed {
+        DataSize::U32
+    } else {
+        csw.SIZE
+    }
 }
 
 impl FlushableArmAccess for MockMemoryAp {
@@ -68,14 +118,12 @@ impl ApAccess for MockMemoryAp {
             DRW::ADDRESS => {
                 let drw = self.store[&DRW::ADDRESS];
                 let bit_offset = (address % 4) * 8;
-                let offset = address as usize;
                 let csw = CSW::try_from(csw).unwrap();
 
-                let (new_drw, offset) = match csw.SIZE {
+                let (new_drw, offset) = match packed_size(&csw) {
                     DataSize::U32 => {
                         let bytes: [u8; 4] = self
-                            .memory
-                            .get(offset..offset + 4)
+                            .memory_slice(address, 4)
                             .map(|v| v.try_into().unwrap())
                             .unwrap_or([0u8; 4]);
 
@@ -83,8 +131,7 @@ impl ApAccess for MockMemoryAp {
                     }
                     DataSize::U16 => {
                         let bytes = self
-                            .memory
-                            .get(offset..offset + 2)
+                            .memory_slice(address, 2)
                             .map(|v| v.try_into().unwrap())
                             .unwrap_or([0u8; 2]);
                         let value = u16::from_le_bytes(bytes);
@@ -94,7 +141,7 @@ impl ApAccess for MockMemoryAp {
                         )
                     }
                     DataSize::U8 => {
-                        let value = *self.memory.get(offset).unwrap_or(&0u8);
+                        let value = self.memory_slice(address, 1).map_or(0, |v| v[0]);
                         (
                             drw & !(0xff << bit_offset) | (u32::from(value) << bit_offset),
                             1,
@@ -106,13 +153,10 @@ impl ApAccess for MockMemoryAp {
                 self.store.insert(DRW::ADDRESS, new_drw);
 
                 match csw.AddrInc {
-                    AddressIncrement::Single => {
+                    AddressIncrement::Single | AddressIncrement::Packed => {
                         self.store.insert(TAR::ADDRESS, address + offset);
                     }
                     AddressIncrement::Off => (),
-                    AddressIncrement::Packed => {
-                        unimplemented!();
-                    }
                 }
 
                 Ok(R::try_from(new_drw).unwrap())
@@ -145,8 +189,9 @@ impl ApAccess for MockMemoryAp {
         match R::ADDRESS {
             DRW::ADDRESS => {
                 let csw = CSW::try_from(csw).unwrap();
+                let size = packed_size(&csw);
 
-                let access_width = match csw.SIZE {
+                let access_width = match size {
                     DataSize::U256 => 32,
                     DataSize::U128 => 16,
                     DataSize::U64 => 8,
@@ -155,39 +200,36 @@ impl ApAccess for MockMemoryAp {
                     DataSize::U8 => 1,
                 };
 
-                if (address + access_width) as usize > self.memory.len() {
+                let bit_offset = (address % 4) * 8;
+
+                let Some(memory) = self.memory_slice_mut(address, access_width as usize) else {
                     // Ignore out-of-bounds write
                     return Ok(());
-                }
+                };
 
-                let bit_offset = (address % 4) * 8;
-                match csw.SIZE {
+                match size {
                     DataSize::U32 => {
-                        self.memory[address as usize..address as usize + 4]
-                            .copy_from_slice(&value.to_le_bytes());
+                        memory.copy_from_slice(&value.to_le_bytes());
                         Ok(4)
                     }
                     DataSize::U16 => {
                         let value = value >> bit_offset;
-                        self.memory[address as usize] = value as u8;
-                        self.memory[address as usize + 1] = (value >> 8) as u8;
+                        memory[0] = value as u8;
+                        memory[1] = (value >> 8) as u8;
                         Ok(2)
                     }
                     DataSize::U8 => {
                         let value = value >> bit_offset;
-                        self.memory[address as usize] = value as u8;
+                        memory[0] = value as u8;
                         Ok(1)
                     }
                     _ => panic!("MockMemoryAp: unknown width"),
                 }
                 .map(|offset| match csw.AddrInc {
-                    AddressIncrement::Single => {
+                    AddressIncrement::Single | AddressIncrement::Packed => {
                         self.store.insert(TAR::ADDRESS, address + offset);
                     }
                     AddressIncrement::Off => (),
-                    AddressIncrement::Packed => {
-                        unimplemented!();
-                    }
                 })
             }
             CSW::ADDRESS => {
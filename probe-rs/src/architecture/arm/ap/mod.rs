@@ -2,6 +2,7 @@
 
 #[macro_use]
 pub mod register_generation;
+pub(crate) mod apv2;
 pub(crate) mod generic_ap;
 pub(crate) mod memory_ap;
 
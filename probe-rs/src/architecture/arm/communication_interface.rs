@@ -1,9 +1,12 @@
 use super::{
     ap::{
-        valid_access_ports, AccessPort, ApAccess, ApClass, BaseaddrFormat, GenericAp, MemoryAp,
-        BASE, BASE2, CFG, CSW, IDR,
+        apv2::{self, APV2_REGISTER_OFFSET},
+        valid_access_ports, AccessPort, AddressIncrement, ApAccess, ApClass, BaseaddrFormat,
+        GenericAp, MemoryAp, BASE, BASE2, CFG, CSW, IDR,
+    },
+    dp::{
+        Abort, Ctrl, DebugPortVersion, DpAccess, Select, BASEPTR0, BASEPTR1, DPIDR, DPIDR1, SELECT1,
     },
-    dp::{Abort, Ctrl, DebugPortVersion, DpAccess, Select, DPIDR},
     memory::{
         adi_v5_memory_interface::{ADIMemoryInterface, ArmProbe},
         Component,
@@ -85,6 +88,19 @@ pub trait ArmProbeInterface: DapAccess + SwdSequence + SwoAccess + Send {
     /// if necessary. This will also  
     fn num_access_ports(&mut self, dp: DpAddress) -> Result<usize, ArmError>;
 
+    /// Returns information about all access ports of the debug port.
+    ///
+    /// This includes the decoded [`IDR`] of every AP, and for memory APs
+    /// the debug base address and the supported memory accesses.
+    fn access_ports(&mut self, dp: DpAddress) -> Result<Vec<ApInformation>, ArmError> {
+        (0..self.num_access_ports(dp)?)
+            .map(|ap| {
+                let address = ApAddress { dp, ap: ap as u8 };
+                self.ap_information(GenericAp::new(address)).cloned()
+            })
+            .collect()
+    }
+
     /// Reads the chip info from the romtable of given debug port.
     fn read_chip_info_from_rom_table(
         &mut self,
@@ -159,13 +175,23 @@ impl ArmDebugState for Initialized {}
 
 #[derive(Debug)]
 pub(crate) struct DpState {
-    pub _debug_port_version: DebugPortVersion,
+    pub debug_port_version: DebugPortVersion,
 
     pub current_dpbanksel: u8,
 
     pub current_apsel: u8,
     pub current_apbanksel: u8,
 
+    /// The address selected for AP accesses, only used on ADIv6 debug ports.
+    ///
+    /// Bits `[31:4]` are stored in SELECT, bits `[63:32]` in SELECT1.
+    pub current_ap_bank_address: u64,
+
+    /// Base addresses of the APs, only used on ADIv6 debug ports.
+    ///
+    /// The index into this list is used as the AP number.
+    pub ap_base_addresses: Vec<u64>,
+
     /// Information about the APs of the target.
     /// APs are identified by a number, starting from zero.
     pub ap_information: Vec<ApInformation>,
@@ -174,13 +200,31 @@ pub(crate) struct DpState {
 impl DpState {
     pub fn new() -> Self {
         Self {
-            _debug_port_version: DebugPortVersion::Unsupported(0xFF),
+            debug_port_version: DebugPortVersion::Unsupported(0xFF),
             current_dpbanksel: 0,
             current_apsel: 0,
             current_apbanksel: 0,
+            current_ap_bank_address: 0,
+            ap_base_addresses: Vec::new(),
             ap_information: Vec::new(),
         }
     }
+
+    /// The value of the SELECT register, based on the cached selection.
+    fn select_register(&self) -> Select {
+        let mut select = Select(0);
+
+        if self.debug_port_version == DebugPortVersion::DPv3 {
+            select.set_addr((self.current_ap_bank_address as u32) >> 4);
+        } else {
+            select.set_ap_sel(self.current_apsel);
+            select.set_ap_bank_sel(self.current_apbanksel);
+        }
+
+        select.set_dp_bank_sel(self.current_dpbanksel);
+
+        select
+    }
 }
 
 /// Information about an access port. Can be used for target discovery.
@@ -200,6 +244,22 @@ pub enum ApInformation {
 }
 
 impl ApInformation {
+    /// The address of the access port.
+    pub fn address(&self) -> ApAddress {
+        match self {
+            ApInformation::MemoryAp(info) => info.address,
+            ApInformation::Other { address, .. } => *address,
+        }
+    }
+
+    /// The decoded [`IDR`] register of the access port.
+    pub fn idr(&self) -> IDR {
+        match self {
+            ApInformation::MemoryAp(info) => info.idr,
+            ApInformation::Other { idr, .. } => *idr,
+        }
+    }
+
     /// Read information about an AP from its registers.
     ///
     /// This reads the IDR register of the AP, and parses
@@ -239,10 +299,26 @@ impl ApInformation {
             probe.write_ap_register(access_port, csw)?;
             let csw: CSW = probe.read_ap_register(access_port)?;
 
-            probe.write_ap_register(access_port, old_csw)?;
-
             let only_32bit_data_size = csw.SIZE != DataSize::U8;
 
+            // Packed transfers are only possible with sub-word accesses. If they are not
+            // supported, the AddrInc field does not read back as packed.
+            let supports_packed_transfers = if only_32bit_data_size {
+                false
+            } else {
+                let mut packed_csw = CSW::new(DataSize::U8);
+                packed_csw.AddrInc = AddressIncrement::Packed;
+
+                probe.write_ap_register(access_port, packed_csw)?;
+                let packed_csw: CSW = probe.read_ap_register(access_port)?;
+
+                packed_csw.AddrInc == AddressIncrement::Packed
+            };
+
+            tracing::debug!("Packed transfers supported: {}", supports_packed_transfers);
+
+            probe.write_ap_register(access_port, old_csw)?;
+
             let supports_hnonsec = csw.HNONSEC == 1;
 
             tracing::debug!("HNONSEC supported: {}", supports_hnonsec);
@@ -258,7 +334,9 @@ impl ApInformation {
 
             Ok(ApInformation::MemoryAp(MemoryApInformation {
                 address: access_port.ap_address(),
+                idr,
                 supports_only_32bit_data_size: only_32bit_data_size,
+                supports_packed_transfers,
                 debug_base_address: base_address,
                 supports_hnonsec,
                 has_large_address_extension,
@@ -281,11 +359,19 @@ pub struct MemoryApInformation {
     /// Zero-based port number of the access port. This is used in the debug port to select an AP.
     pub address: ApAddress,
 
+    /// Content of the [`IDR`] register describing this AP.
+    pub idr: IDR,
+
     /// Some Memory APs only support 32 bit wide access to data, while others
     /// also support other widths. Based on this, 8 bit data access can either
     /// be performed directly, or has to be done as a 32 bit access.
     pub supports_only_32bit_data_size: bool,
 
+    /// Packed transfers (see C2.2.7) combine multiple 8 or 16 bit accesses into
+    /// a single access to the DRW register, which makes sub-word block transfers
+    /// considerably faster.
+    pub supports_packed_transfers: bool,
+
     /// The Debug Base Address points to either the start of a set of debug register,
     /// or a ROM table which describes the connected debug components.
     ///
@@ -372,7 +458,7 @@ impl<S: ArmDebugState> SwdSequence for ArmCommunicationInterface<S> {
         pin_select: u32,
         pin_wait: u32,
     ) -> Result<u32, DebugProbeError> {
-        self.probe.swj_pins(pin_out, pin_select, pin_wait)
+        RawDapAccess::swj_pins(&mut *self.probe, pin_out, pin_select, pin_wait)
     }
 }
 
@@ -509,11 +595,39 @@ impl<'interface> ArmCommunicationInterface<Initialized> {
             ctrl_reg.set_orun_detect(self.state.use_overrun_detect);
             self.write_dp_register(dp, ctrl_reg)?;
 
+            let debug_port_version = match self.read_dp_register::<DPIDR>(dp) {
+                Ok(dpidr) => DebugPortVersion::from(dpidr.version()),
+                Err(e) => {
+                    tracing::debug!("Failed to read DPIDR: {}", e);
+                    DebugPortVersion::Unsupported(0xFF)
+                }
+            };
+
+            tracing::debug!("Debug port version: {}", debug_port_version);
+
+            // note(unwrap): we have inserted the state above, it must exist.
+            self.state.dps.get_mut(&dp).unwrap().debug_port_version = debug_port_version;
+
             /* determine the number and type of available APs */
             tracing::trace!("Searching valid APs");
 
             let ap_span = tracing::debug_span!("AP discovery").entered();
-            for ap in valid_access_ports(self, dp) {
+
+            let access_ports = if debug_port_version == DebugPortVersion::DPv3 {
+                let base_addresses = self.discover_apv2_access_ports(dp)?;
+                let num_access_ports = base_addresses.len();
+
+                // note(unwrap): we have inserted the state above, it must exist.
+                self.state.dps.get_mut(&dp).unwrap().ap_base_addresses = base_addresses;
+
+                (0..num_access_ports)
+                    .map(|ap| GenericAp::new(ApAddress { dp, ap: ap as u8 }))
+                    .collect()
+            } else {
+                valid_access_ports(self, dp)
+            };
+
+            for ap in access_ports {
                 let ap_state = ApInformation::read_from_target(self, ap)?;
                 tracing::debug!("AP {:x?}: {:?}", ap, ap_state);
 
@@ -537,25 +651,25 @@ impl<'interface> ArmCommunicationInterface<Initialized> {
 
         // DP register addresses are 4 bank bits, 4 address bits. Lowest 2 address bits are
         // always 0, so this leaves only 4 possible addresses: 0x0, 0x4, 0x8, 0xC.
-        // Only address 0x4 is banked, the rest are don't care.
+        // Only address 0x4 is banked, the rest are don't care. On DPv3 debug ports,
+        // address 0x0 is banked as well.
 
         let bank = dp_register_address >> 4;
         let addr = dp_register_address & 0xF;
 
-        if addr != 4 {
+        let banked =
+            addr == 4 || (addr == 0 && dp_state.debug_port_version == DebugPortVersion::DPv3);
+
+        if !banked {
             return Ok(());
         }
 
         if bank != dp_state.current_dpbanksel {
             dp_state.current_dpbanksel = bank;
 
-            let mut select = Select(0);
-
             tracing::debug!("Changing DP_BANK_SEL to {}", dp_state.current_dpbanksel);
 
-            select.set_ap_sel(dp_state.current_apsel);
-            select.set_ap_bank_sel(dp_state.current_apbanksel);
-            select.set_dp_bank_sel(dp_state.current_dpbanksel);
+            let select = dp_state.select_register();
 
             self.write_dp_register(dp, select)?;
         }
@@ -570,6 +684,18 @@ impl<'interface> ArmCommunicationInterface<Initialized> {
     ) -> Result<(), ArmError> {
         let dp_state = self.select_dp(ap.dp)?;
 
+        if dp_state.debug_port_version == DebugPortVersion::DPv3 {
+            let base_address = *dp_state
+                .ap_base_addresses
+                .get(ap.ap as usize)
+                .ok_or(ArmError::ApDoesNotExist(ap))?;
+
+            return self.select_apv2_address(
+                ap.dp,
+                base_address + APV2_REGISTER_OFFSET + u64::from(ap_register_address),
+            );
+        }
+
         let port = ap.ap;
         let ap_bank = ap_register_address >> 4;
 
@@ -586,17 +712,13 @@ impl<'interface> ArmCommunicationInterface<Initialized> {
         }
 
         if cache_changed {
-            let mut select = Select(0);
-
             tracing::debug!(
                 "Changing AP to {}, AP_BANK_SEL to {}",
                 dp_state.current_apsel,
                 dp_state.current_apbanksel
             );
 
-            select.set_ap_sel(dp_state.current_apsel);
-            select.set_ap_bank_sel(dp_state.current_apbanksel);
-            select.set_dp_bank_sel(dp_state.current_dpbanksel);
+            let select = dp_state.select_register();
 
             self.write_dp_register(ap.dp, select)?;
         }
@@ -604,6 +726,69 @@ impl<'interface> ArmCommunicationInterface<Initialized> {
         Ok(())
     }
 
+    /// Selects `address` in the address space of an ADIv6 debug port for AP accesses.
+    ///
+    /// Afterwards, the word at `address` can be accessed using the AP register
+    /// address `address & 0xF`.
+    fn select_apv2_address(&mut self, dp: DpAddress, address: u64) -> Result<(), ArmError> {
+        let bank_address = address & !0xF;
+
+        let dp_state = self.select_dp(dp)?;
+
+        let previous_address = dp_state.current_ap_bank_address;
+
+        if previous_address == bank_address {
+            return Ok(());
+        }
+
+        dp_state.current_ap_bank_address = bank_address;
+
+        tracing::debug!("Changing AP address to {:#x}", bank_address);
+
+        if previous_address >> 32 != bank_address >> 32 {
+            self.write_dp_register(dp, SELECT1((bank_address >> 32) as u32))?;
+        }
+
+        // The state was created when selecting the DP, so it must exist.
+        let select = self.state.dps[&dp].select_register();
+
+        self.write_dp_register(dp, select)
+    }
+
+    /// Reads a word from the address space of an ADIv6 debug port.
+    fn read_apv2_address(&mut self, dp: DpAddress, address: u64) -> Result<u32, ArmError> {
+        self.select_apv2_address(dp, address)?;
+
+        let value = self
+            .probe
+            .raw_read_register(PortType::AccessPort, (address & 0xF) as u8)?;
+
+        Ok(value)
+    }
+
+    /// Finds the base addresses of the APs of an ADIv6 debug port.
+    fn discover_apv2_access_ports(&mut self, dp: DpAddress) -> Result<Vec<u64>, ArmError> {
+        let baseptr0: BASEPTR0 = self.read_dp_register(dp)?;
+
+        if !baseptr0.valid() {
+            tracing::warn!("The debug port does not have a valid base pointer, no APs found.");
+            return Ok(Vec::new());
+        }
+
+        let mut base_address = u64::from(baseptr0.ptr()) << 12;
+
+        let dpidr1: DPIDR1 = self.read_dp_register(dp)?;
+
+        if dpidr1.asize() > 32 {
+            let baseptr1: BASEPTR1 = self.read_dp_register(dp)?;
+            base_address |= u64::from(baseptr1.0) << 32;
+        }
+
+        tracing::debug!("Searching APs, starting at {:#x}", base_address);
+
+        apv2::find_access_ports(base_address, |address| self.read_apv2_address(dp, address))
+    }
+
     /// Determine the type and additional information about an AP.
     ///
     /// If the AP doesn't exist, None is returned.
@@ -755,7 +940,13 @@ impl ArmCommunicationInterface<Initialized> {
         dp: DpAddress,
     ) -> Result<Option<ArmChipInfo>, ArmError> {
         // faults on some chips need to be cleaned up.
-        let aps = valid_access_ports(self, dp);
+        let aps = if self.select_dp(dp)?.debug_port_version == DebugPortVersion::DPv3 {
+            (0..self.num_access_ports(dp)?)
+                .map(|ap| GenericAp::new(ApAddress { dp, ap: ap as u8 }))
+                .collect()
+        } else {
+            valid_access_ports(self, dp)
+        };
 
         // Check sticky error and cleanup if necessary
         let ctrl_reg: crate::architecture::arm::dp::Ctrl = self.read_dp_register(dp)?;
@@ -779,7 +970,9 @@ impl ArmCommunicationInterface<Initialized> {
 
                 let component = Component::try_parse(&mut *memory, baseaddr)?;
 
-                if let Component::Class1RomTable(component_id, _) = component {
+                if let Component::Class1RomTable(component_id, _)
+                | Component::Class9RomTable(component_id, _) = component
+                {
                     if let Some(jep106) = component_id.peripheral_id().jep106() {
                         return Ok(Some(ArmChipInfo {
                             manufacturer: jep106,
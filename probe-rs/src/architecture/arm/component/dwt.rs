@@ -8,7 +8,7 @@
 
 use super::super::memory::romtable::CoresightComponent;
 use super::DebugComponentInterface;
-use crate::architecture::arm::{ArmError, ArmProbeInterface};
+use crate::architecture::arm::{swo::PcSamplingInterval, ArmError, ArmProbeInterface};
 use crate::{memory_mapped_bitfield_register, Error};
 
 /// A struct representing a DWT unit on target.
@@ -78,6 +78,30 @@ impl<'a> Dwt<'a> {
         function.store_unit(self.component, self.interface, unit)
     }
 
+    /// Enables periodic PC sampling, with the given interval between two samples.
+    ///
+    /// The samples are derived from the cycle counter, which is enabled as well.
+    pub fn enable_pc_sampling(&mut self, interval: PcSamplingInterval) -> Result<(), ArmError> {
+        let mut ctrl = Ctrl::load(self.component, self.interface)?;
+
+        // The reload value may only be changed while PC sampling is disabled.
+        ctrl.set_pcsamplena(false);
+        ctrl.store(self.component, self.interface)?;
+
+        ctrl.set_cyctap(interval.cyctap);
+        ctrl.set_postpreset(interval.postpreset);
+        ctrl.set_cyccntena(true);
+        ctrl.set_pcsamplena(true);
+        ctrl.store(self.component, self.interface)
+    }
+
+    /// Disables periodic PC sampling.
+    pub fn disable_pc_sampling(&mut self) -> Result<(), ArmError> {
+        let mut ctrl = Ctrl::load(self.component, self.interface)?;
+        ctrl.set_pcsamplena(false);
+        ctrl.store(self.component, self.interface)
+    }
+
     /// Enable exception tracing.
     pub fn enable_exception_trace(&mut self) -> Result<(), ArmError> {
         let mut ctrl = Ctrl::load(self.component, self.interface)?;
@@ -117,7 +141,7 @@ memory_mapped_bitfield_register! {
     pub u8, synctap, set_synctap: 11, 10;
     pub cyctap, set_cyctap: 9;
     pub u8, postinit, set_postinit: 8, 5;
-    pub postpreset, set_postpreset: 4, 1;
+    pub u8, postpreset, set_postpreset: 4, 1;
     pub cyccntena, set_cyccntena: 0;
 
 }
@@ -0,0 +1,128 @@
+//! Arm Embedded Trace Buffer (ETB) CoreSight Component
+//!
+//! # Description
+//! The ETB stores formatted trace data in on-chip RAM, from which it can be read through the debug
+//! interface. See "DDI0314H CoreSight Components Technical Reference Manual", chapter 5.
+use super::super::memory::romtable::CoresightComponent;
+use crate::architecture::arm::{ArmError, ArmProbeInterface};
+
+const REGISTER_OFFSET_RDP: u32 = 0x004;
+const REGISTER_OFFSET_STS: u32 = 0x00C;
+const REGISTER_OFFSET_RRD: u32 = 0x010;
+const REGISTER_OFFSET_RRP: u32 = 0x014;
+const REGISTER_OFFSET_RWP: u32 = 0x018;
+const REGISTER_OFFSET_CTL: u32 = 0x020;
+const REGISTER_OFFSET_FFSR: u32 = 0x300;
+const REGISTER_OFFSET_FFCR: u32 = 0x304;
+const REGISTER_OFFSET_LAR: u32 = 0xFB0;
+
+/// STS.Full, the write pointer has wrapped around.
+const STS_FULL: u32 = 1 << 0;
+/// FFSR.FtStopped, the formatter has stopped.
+const FFSR_STOPPED: u32 = 1 << 1;
+/// FFCR.EnFTC and FFCR.EnFCont, enable continuous formatting.
+const FFCR_CONTINUOUS_FORMATTING: u32 = (1 << 1) | (1 << 0);
+/// FFCR.StopFl and FFCR.FOnMan, flush the formatter and stop the capture.
+const FFCR_STOP: u32 = (1 << 12) | (1 << 6);
+
+/// Number of status polls before giving up on stopping the capture.
+const MAX_STATUS_POLLS: usize = 100;
+
+/// Embedded Trace Buffer
+pub struct Etb<'a> {
+    component: &'a CoresightComponent,
+    interface: &'a mut dyn ArmProbeInterface,
+}
+
+impl<'a> Etb<'a> {
+    /// Construct a new ETB component.
+    pub fn new(
+        interface: &'a mut dyn ArmProbeInterface,
+        component: &'a CoresightComponent,
+    ) -> Self {
+        Etb {
+            component,
+            interface,
+        }
+    }
+
+    /// Unlock the ETB for programming.
+    pub fn unlock(&mut self) -> Result<(), ArmError> {
+        self.component
+            .write_reg(self.interface, REGISTER_OFFSET_LAR, 0xC5AC_CE55)
+    }
+
+    /// Start capturing formatted trace data, starting at the beginning of the buffer.
+    pub fn enable_capture(&mut self) -> Result<(), ArmError> {
+        self.component
+            .write_reg(self.interface, REGISTER_OFFSET_CTL, 0)?;
+        self.component
+            .write_reg(self.interface, REGISTER_OFFSET_RWP, 0)?;
+        self.component.write_reg(
+            self.interface,
+            REGISTER_OFFSET_FFCR,
+            FFCR_CONTINUOUS_FORMATTING,
+        )?;
+        self.component
+            .write_reg(self.interface, REGISTER_OFFSET_CTL, 1)
+    }
+
+    /// Flush the formatter, and stop capturing trace data.
+    pub fn disable_capture(&mut self) -> Result<(), ArmError> {
+        self.component.write_reg(
+            self.interface,
+            REGISTER_OFFSET_FFCR,
+            FFCR_CONTINUOUS_FORMATTING | FFCR_STOP,
+        )?;
+
+        for _ in 0..MAX_STATUS_POLLS {
+            let status = self
+                .component
+                .read_reg(self.interface, REGISTER_OFFSET_FFSR)?;
+
+            if status & FFSR_STOPPED != 0 {
+                return self
+                    .component
+                    .write_reg(self.interface, REGISTER_OFFSET_CTL, 0);
+            }
+        }
+
+        Err(ArmError::Timeout)
+    }
+
+    /// Read the formatted trace data from the buffer, oldest data first.
+    ///
+    /// The capture has to be stopped with [`Etb::disable_capture`] before.
+    pub fn read(&mut self) -> Result<Vec<u8>, ArmError> {
+        let depth = self
+            .component
+            .read_reg(self.interface, REGISTER_OFFSET_RDP)?;
+        let write_pointer = self
+            .component
+            .read_reg(self.interface, REGISTER_OFFSET_RWP)?;
+        let status = self
+            .component
+            .read_reg(self.interface, REGISTER_OFFSET_STS)?;
+
+        // Once the buffer is full, the oldest data is at the write pointer.
+        let (start, words) = if status & STS_FULL != 0 {
+            (write_pointer, depth)
+        } else {
+            (0, write_pointer)
+        };
+
+        self.component
+            .write_reg(self.interface, REGISTER_OFFSET_RRP, start)?;
+
+        // The read pointer is incremented on each read, and wraps around at the end of the buffer.
+        let mut data = Vec::with_capacity(words as usize * 4);
+        for _ in 0..words {
+            let word = self
+                .component
+                .read_reg(self.interface, REGISTER_OFFSET_RRD)?;
+            data.extend_from_slice(&word.to_le_bytes());
+        }
+
+        Ok(data)
+    }
+}
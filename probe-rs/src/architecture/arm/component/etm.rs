@@ -0,0 +1,211 @@
+//! Embedded Trace Macrocell (ETM) and Program Trace Macrocell (PTM) CoreSight components
+//!
+//! # Description
+//! The ETM generates a trace of the instructions executed by a core. This module configures the
+//! ETM to trace all instructions, without decoding the generated trace.
+//!
+//! ETMv3 and the PTM (which uses the ETMv3 programmers model) are described in "ARM IHI 0014Q
+//! Embedded Trace Macrocell Architecture Specification ETMv1.0 to ETMv3.5", ETMv4 in
+//! "ARM IHI 0064 ETM Architecture Specification ETMv4".
+use super::super::memory::romtable::CoresightComponent;
+use crate::architecture::arm::{ArmError, ArmProbeInterface};
+
+const REGISTER_OFFSET_LAR: u32 = 0xFB0;
+const REGISTER_OFFSET_OSLAR: u32 = 0x300;
+/// ETMIDR on ETMv3, TRCIDR1 on ETMv4.
+const REGISTER_OFFSET_IDR: u32 = 0x1E4;
+
+const ETMV3_CR: u32 = 0x000;
+const ETMV3_SR: u32 = 0x010;
+const ETMV3_TEEVR: u32 = 0x020;
+const ETMV3_TECR1: u32 = 0x024;
+const ETMV3_TRACEIDR: u32 = 0x200;
+
+/// ETMCR.PowerDown
+const ETMV3_CR_POWER_DOWN: u32 = 1 << 0;
+/// ETMCR.ProgBit
+const ETMV3_CR_PROGRAMMING: u32 = 1 << 10;
+/// ETMSR.ProgBit
+const ETMV3_SR_PROGRAMMING: u32 = 1 << 1;
+/// The resource which is always active, used as "always true" event.
+const ETMV3_EVENT_ALWAYS: u32 = 0x6F;
+/// TECR1.Exclude, with no excluded address ranges everything is traced.
+const ETMV3_TECR1_EXCLUDE: u32 = 1 << 24;
+
+const ETMV4_PRGCTLR: u32 = 0x004;
+const ETMV4_STATR: u32 = 0x00C;
+const ETMV4_CONFIGR: u32 = 0x010;
+const ETMV4_EVENTCTL0R: u32 = 0x020;
+const ETMV4_EVENTCTL1R: u32 = 0x024;
+const ETMV4_STALLCTLR: u32 = 0x02C;
+const ETMV4_TSCTLR: u32 = 0x030;
+const ETMV4_SYNCPR: u32 = 0x034;
+const ETMV4_TRACEIDR: u32 = 0x040;
+const ETMV4_VICTLR: u32 = 0x080;
+const ETMV4_VIIECTLR: u32 = 0x084;
+const ETMV4_VISSCTLR: u32 = 0x088;
+
+/// TRCSTATR.IDLE
+const ETMV4_STATR_IDLE: u32 = 1 << 0;
+/// TRCVICTLR with the always true event and the start/stop logic in the started state.
+const ETMV4_VICTLR_TRACE_ALL: u32 = (1 << 9) | 0x01;
+/// Generate a synchronization packet every 2^12 bytes.
+const ETMV4_SYNC_PERIOD: u32 = 12;
+
+/// Number of status polls before giving up on a state change.
+const MAX_STATUS_POLLS: usize = 100;
+
+/// The architecture version of an ETM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EtmVersion {
+    /// ETMv3, or a PTM, which uses the same programmers model.
+    V3,
+    /// ETMv4
+    V4,
+}
+
+/// Embedded Trace Macrocell
+pub struct Etm<'a> {
+    component: &'a CoresightComponent,
+    interface: &'a mut dyn ArmProbeInterface,
+}
+
+impl<'a> Etm<'a> {
+    /// Construct a new ETM component.
+    pub fn new(
+        interface: &'a mut dyn ArmProbeInterface,
+        component: &'a CoresightComponent,
+    ) -> Self {
+        Etm {
+            component,
+            interface,
+        }
+    }
+
+    /// Read the architecture version of the ETM.
+    pub fn version(&mut self) -> Result<EtmVersion, ArmError> {
+        let idr = self
+            .component
+            .read_reg(self.interface, REGISTER_OFFSET_IDR)?;
+
+        // ETMIDR and TRCIDR1 both contain the major architecture version in bits [11:8].
+        match (idr >> 8) & 0xF {
+            4 => Ok(EtmVersion::V4),
+            _ => Ok(EtmVersion::V3),
+        }
+    }
+
+    /// Unlock the ETM for programming, by clearing the software and the OS lock.
+    pub fn unlock(&mut self) -> Result<(), ArmError> {
+        self.component
+            .write_reg(self.interface, REGISTER_OFFSET_LAR, 0xC5AC_CE55)?;
+        self.component
+            .write_reg(self.interface, REGISTER_OFFSET_OSLAR, 0)?;
+
+        Ok(())
+    }
+
+    /// Configure the ETM to trace all executed instructions, using `trace_id` as its trace source ID.
+    pub fn enable(&mut self, trace_id: u8) -> Result<(), ArmError> {
+        self.unlock()?;
+
+        match self.version()? {
+            EtmVersion::V3 => self.enable_v3(trace_id),
+            EtmVersion::V4 => self.enable_v4(trace_id),
+        }
+    }
+
+    /// Stop generating trace.
+    pub fn disable(&mut self) -> Result<(), ArmError> {
+        self.unlock()?;
+
+        match self.version()? {
+            EtmVersion::V3 => {
+                self.set_v3_programming(true)?;
+                self.component.write_reg(
+                    self.interface,
+                    ETMV3_CR,
+                    ETMV3_CR_PROGRAMMING | ETMV3_CR_POWER_DOWN,
+                )
+            }
+            EtmVersion::V4 => self.set_v4_enabled(false),
+        }
+    }
+
+    fn enable_v3(&mut self, trace_id: u8) -> Result<(), ArmError> {
+        // Power up the ETM, and enter the programming state.
+        self.component
+            .write_reg(self.interface, ETMV3_CR, ETMV3_CR_PROGRAMMING)?;
+        self.set_v3_programming(true)?;
+
+        self.component
+            .write_reg(self.interface, ETMV3_TEEVR, ETMV3_EVENT_ALWAYS)?;
+        self.component
+            .write_reg(self.interface, ETMV3_TECR1, ETMV3_TECR1_EXCLUDE)?;
+        self.component
+            .write_reg(self.interface, ETMV3_TRACEIDR, trace_id as u32)?;
+
+        self.set_v3_programming(false)
+    }
+
+    /// Sets or clears ETMCR.ProgBit, and waits until ETMSR reflects the new state.
+    fn set_v3_programming(&mut self, programming: bool) -> Result<(), ArmError> {
+        let mut control = self.component.read_reg(self.interface, ETMV3_CR)?;
+
+        if programming {
+            control |= ETMV3_CR_PROGRAMMING;
+        } else {
+            control &= !ETMV3_CR_PROGRAMMING;
+        }
+
+        self.component
+            .write_reg(self.interface, ETMV3_CR, control)?;
+
+        for _ in 0..MAX_STATUS_POLLS {
+            let status = self.component.read_reg(self.interface, ETMV3_SR)?;
+
+            if (status & ETMV3_SR_PROGRAMMING != 0) == programming {
+                return Ok(());
+            }
+        }
+
+        Err(ArmError::Timeout)
+    }
+
+    fn enable_v4(&mut self, trace_id: u8) -> Result<(), ArmError> {
+        self.set_v4_enabled(false)?;
+
+        for (offset, value) in [
+            (ETMV4_CONFIGR, 0),
+            (ETMV4_EVENTCTL0R, 0),
+            (ETMV4_EVENTCTL1R, 0),
+            (ETMV4_STALLCTLR, 0),
+            (ETMV4_TSCTLR, 0),
+            (ETMV4_SYNCPR, ETMV4_SYNC_PERIOD),
+            (ETMV4_TRACEIDR, trace_id as u32),
+            (ETMV4_VICTLR, ETMV4_VICTLR_TRACE_ALL),
+            (ETMV4_VIIECTLR, 0),
+            (ETMV4_VISSCTLR, 0),
+        ] {
+            self.component.write_reg(self.interface, offset, value)?;
+        }
+
+        self.set_v4_enabled(true)
+    }
+
+    /// Enables or disables the trace unit, and waits until it is idle or running.
+    fn set_v4_enabled(&mut self, enabled: bool) -> Result<(), ArmError> {
+        self.component
+            .write_reg(self.interface, ETMV4_PRGCTLR, enabled as u32)?;
+
+        for _ in 0..MAX_STATUS_POLLS {
+            let status = self.component.read_reg(self.interface, ETMV4_STATR)?;
+
+            if (status & ETMV4_STATR_IDLE == 0) == enabled {
+                return Ok(());
+            }
+        }
+
+        Err(ArmError::Timeout)
+    }
+}
@@ -1,6 +1,8 @@
 //! Types and functions for interacting with CoreSight Components
 
 mod dwt;
+mod etb;
+mod etm;
 mod itm;
 mod scs;
 mod swo;
@@ -14,11 +16,13 @@ use super::memory::Component;
 use super::ArmError;
 use super::{ApAddress, ApInformation, DpAddress, MemoryApInformation};
 use crate::architecture::arm::core::armv6m::Demcr;
-use crate::architecture::arm::{ArmProbeInterface, SwoConfig, SwoMode};
+use crate::architecture::arm::{swo::PcSamplingInterval, ArmProbeInterface, SwoConfig, SwoMode};
 use crate::{Core, Error, MemoryInterface, MemoryMappedRegister};
 
 pub use self::itm::Itm;
 pub use dwt::Dwt;
+pub use etb::Etb;
+pub use etm::{Etm, EtmVersion};
 pub use scs::Scs;
 pub use swo::Swo;
 pub use tmc::TraceMemoryController;
@@ -252,20 +256,24 @@ pub(crate) fn setup_tracing(
         }
 
         TraceSink::TraceMemory => {
-            let mut tmc = TraceMemoryController::new(
-                interface,
-                find_component(components, PeripheralType::Tmc)?,
-            );
+            if let Ok(peripheral) = find_component(components, PeripheralType::Tmc) {
+                let mut tmc = TraceMemoryController::new(interface, peripheral);
 
-            // Clear out the TMC FIFO before initiating the capture.
-            tmc.disable_capture()?;
-            while !tmc.ready()? {}
+                // Clear out the TMC FIFO before initiating the capture.
+                tmc.disable_capture()?;
+                while !tmc.ready()? {}
 
-            // Configure the TMC for software-polled mode, as we will read out data using the debug
-            // interface.
-            tmc.set_mode(tmc::Mode::Software)?;
+                // Configure the TMC for software-polled mode, as we will read out data using the
+                // debug interface.
+                tmc.set_mode(tmc::Mode::Software)?;
 
-            tmc.enable_capture()?;
+                tmc.enable_capture()?;
+            } else {
+                // Older CoreSight systems only have an ETB as on-chip trace memory.
+                let mut etb = Etb::new(interface, find_component(components, PeripheralType::Etb)?);
+                etb.unlock()?;
+                etb.enable_capture()?;
+            }
         }
     }
 
@@ -290,8 +298,31 @@ pub(crate) fn read_trace_memory(
     interface: &mut dyn ArmProbeInterface,
     components: &[CoresightComponent],
 ) -> Result<Vec<u8>, ArmError> {
-    let mut tmc =
-        TraceMemoryController::new(interface, find_component(components, PeripheralType::Tmc)?);
+    let formatted = read_formatted_trace_memory(interface, components)?;
+
+    // ITM ATID, see Itm::tx_enable()
+    Ok(demultiplex(&formatted, 13))
+}
+
+/// Read the formatted trace data from internal trace memory, which is either a TMC or an ETB.
+fn read_formatted_trace_memory(
+    interface: &mut dyn ArmProbeInterface,
+    components: &[CoresightComponent],
+) -> Result<Vec<u8>, ArmError> {
+    let peripheral = match find_component(components, PeripheralType::Tmc) {
+        Ok(peripheral) => peripheral,
+        Err(_) => {
+            // The ETB can only be read while the capture is stopped.
+            let mut etb = Etb::new(interface, find_component(components, PeripheralType::Etb)?);
+            etb.disable_capture()?;
+            let data = etb.read()?;
+            etb.enable_capture()?;
+
+            return Ok(data);
+        }
+    };
+
+    let mut tmc = TraceMemoryController::new(interface, peripheral);
 
     let fifo_size = tmc.fifo_size()?;
 
@@ -322,28 +353,80 @@ pub(crate) fn read_trace_memory(
         }
     }
 
-    // The TMC formats data into frames, as it contains trace data from multiple data sources. We
-    // need to deserialize the frames and pull out only the data source of interest. For now, all
-    // we care about is the ITM data.
+    Ok(etf_trace)
+}
 
+/// Extracts the data of the trace source with ATID `trace_id` from formatted trace data.
+///
+/// The trace memory formats data into frames, as it contains trace data from multiple data
+/// sources. Incomplete frames at the end of `formatted` are ignored.
+fn demultiplex(formatted: &[u8], trace_id: u8) -> Vec<u8> {
     let mut id = 0.into();
-    let mut itm_trace = Vec::new();
+    let mut trace = Vec::new();
 
     // Process each formatted frame and extract the multiplexed trace data.
-    for frame_buffer in etf_trace.chunks_exact(16) {
+    for frame_buffer in formatted.chunks_exact(16) {
         let mut frame = tmc::Frame::new(frame_buffer, id);
         for (id, data) in &mut frame {
             match id.into() {
-                // ITM ATID, see Itm::tx_enable()
-                13 => itm_trace.push(data),
+                id if id == trace_id => trace.push(data),
                 0 => (),
-                id => tracing::warn!("Unexpected trace source ATID {id}: {data}, ignoring"),
+                id => tracing::trace!("Ignoring data of trace source ATID {id}: {data}"),
             }
         }
         id = frame.id();
     }
 
-    Ok(itm_trace)
+    trace
+}
+
+/// Configures the first ETM to trace all executed instructions, and routes its trace through the
+/// trace funnels.
+///
+/// The trace sink has to be configured with [`setup_tracing`] before. Expects to be given a list
+/// of all ROM table `components` as the second argument.
+pub(crate) fn enable_etm_trace(
+    interface: &mut dyn ArmProbeInterface,
+    components: &[CoresightComponent],
+    trace_id: u8,
+) -> Result<(), ArmError> {
+    let funnels = components
+        .iter()
+        .flat_map(|component| component.iter())
+        .filter(|component| component.peripheral_type() == Some(PeripheralType::TraceFunnel));
+
+    for peripheral in funnels {
+        let mut funnel = TraceFunnel::new(interface, peripheral);
+        funnel.unlock()?;
+        funnel.enable_port(0xFF)?;
+    }
+
+    let mut etm = Etm::new(interface, find_component(components, PeripheralType::Etm)?);
+    etm.enable(trace_id)
+}
+
+/// Stops the trace generation of the first ETM.
+///
+/// Expects to be given a list of all ROM table `components` as the second argument.
+pub(crate) fn disable_etm_trace(
+    interface: &mut dyn ArmProbeInterface,
+    components: &[CoresightComponent],
+) -> Result<(), ArmError> {
+    let mut etm = Etm::new(interface, find_component(components, PeripheralType::Etm)?);
+    etm.disable()
+}
+
+/// Read the raw ETM trace data of the trace source `trace_id` from internal trace memory.
+///
+/// Expects to be given a list of all ROM table `components` as the second argument.
+pub(crate) fn read_etm_trace_memory(
+    interface: &mut dyn ArmProbeInterface,
+    components: &[CoresightComponent],
+    trace_id: u8,
+) -> Result<Vec<u8>, ArmError> {
+    let formatted = read_formatted_trace_memory(interface, components)?;
+
+    Ok(demultiplex(&formatted, trace_id))
 }
 
 /// Configures DWT trace unit `unit` to begin tracing `address`.
@@ -388,3 +471,51 @@ pub fn disable_swv(core: &mut Core) -> Result<(), Error> {
     core.write_word_32(Demcr::get_mmio_address(), demcr.into())?;
     Ok(())
 }
+
+/// Enables periodic PC sampling in the DWT.
+///
+/// Expects to be given a list of all ROM table `components` as the second argument.
+pub(crate) fn enable_pc_sampling(
+    interface: &mut dyn ArmProbeInterface,
+    components: &[CoresightComponent],
+    interval: PcSamplingInterval,
+) -> Result<(), ArmError> {
+    let mut dwt = Dwt::new(interface, find_component(components, PeripheralType::Dwt)?);
+    dwt.enable_pc_sampling(interval)
+}
+
+/// Disables periodic PC sampling in the DWT.
+///
+/// Expects to be given a list of all ROM table `components` as the second argument.
+pub(crate) fn disable_pc_sampling(
+    interface: &mut dyn ArmProbeInterface,
+    components: &[CoresightComponent],
+) -> Result<(), ArmError> {
+    let mut dwt = Dwt::new(interface, find_component(components, PeripheralType::Dwt)?);
+    dwt.disable_pc_sampling()
+}
+
+#[cfg(test)]
+mod test {
+    use super::demultiplex;
+
+    #[test]
+    fn demultiplex_trace_sources() {
+        let mut frame = [0u8; 16];
+        // Switch to ATID 5, the following byte belongs to the new ID.
+        frame[0] = (5 << 1) | 1;
+        frame[1] = 0xAA;
+        // Data byte with its LSB stored in the auxiliary byte.
+        frame[2] = 0x44;
+        // Switch to ATID 13.
+        frame[4] = (13 << 1) | 1;
+        frame[5] = 0x55;
+        frame[15] = 0b0000_0010;
+
+        assert_eq!(&demultiplex(&frame, 5)[..3], &[0xAA, 0x45, 0x00]);
+        assert_eq!(demultiplex(&frame, 13)[0], 0x55);
+        assert!(demultiplex(&frame, 7).is_empty());
+        // Incomplete frames are ignored.
+        assert!(demultiplex(&frame[..8], 5).is_empty());
+    }
+}
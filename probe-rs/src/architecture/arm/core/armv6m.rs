@@ -3,16 +3,18 @@
 use super::{CortexMState, Dfsr, CORTEX_M_COMMON_REGS};
 use crate::{
     architecture::arm::{
-        memory::adi_v5_memory_interface::ArmProbe, sequences::ArmDebugSequence, ArmError,
+        memory::adi_v5_memory_interface::ArmProbe,
+        sequences::{reset_with_type, ArmDebugSequence},
+        ArmError,
     },
     core::{
         RegisterDataType, RegisterDescription, RegisterFile, RegisterId, RegisterKind,
-        RegisterValue,
+        RegisterValue, WatchpointKind,
     },
     error::Error,
     memory::valid_32bit_address,
     Architecture, CoreInformation, CoreInterface, CoreStatus, CoreType, DebugProbeError,
-    HaltReason, InstructionSet, MemoryInterface, MemoryMappedRegister,
+    HaltReason, InstructionSet, MemoryInterface, MemoryMappedRegister, ResetType,
 };
 use anyhow::Result;
 use bitfield::bitfield;
@@ -463,6 +465,7 @@ impl<'probe> Armv6m<'probe> {
         mut memory: Box<dyn ArmProbe + 'probe>,
         state: &'probe mut CortexMState,
         sequence: Arc<dyn ArmDebugSequence>,
+        non_intrusive: bool,
     ) -> Result<Self, ArmError> {
         if !state.initialized() {
             // determine current state
@@ -484,9 +487,12 @@ impl<'probe> Armv6m<'probe> {
 
             // Clear DFSR register. The bits in the register are sticky,
             // so we clear them here to ensure that that none are set.
-            let dfsr_clear = Dfsr::clear_all();
+            // A non-intrusive attach leaves them to the firmware or another debugger.
+            if !non_intrusive {
+                let dfsr_clear = Dfsr::clear_all();
 
-            memory.write_word_32(Dfsr::get_mmio_address(), dfsr_clear.into())?;
+                memory.write_word_32(Dfsr::get_mmio_address(), dfsr_clear.into())?;
+            }
 
             state.current_state = core_state;
             state.initialize();
@@ -620,17 +626,38 @@ impl<'probe> CoreInterface for Armv6m<'probe> {
         })
     }
 
-    fn reset(&mut self) -> Result<(), Error> {
-        self.sequence
-            .reset_system(&mut *self.memory, crate::CoreType::Armv6m, None)?;
+    fn reset(&mut self, reset_type: ResetType) -> Result<(), Error> {
+        reset_with_type(
+            &*self.sequence,
+            &mut *self.memory,
+            crate::CoreType::Armv6m,
+            None,
+            reset_type,
+        )?;
         Ok(())
     }
 
-    fn reset_and_halt(&mut self, _timeout: Duration) -> Result<CoreInformation, Error> {
+    fn reset_and_halt(
+        &mut self,
+        reset_type: ResetType,
+        timeout: Duration,
+    ) -> Result<CoreInformation, Error> {
         self.sequence
             .reset_catch_set(&mut *self.memory, crate::CoreType::Armv6m, None)?;
+        let result = reset_with_type(
+            &*self.sequence,
+            &mut *self.memory,
+            crate::CoreType::Armv6m,
+            None,
+            reset_type,
+        )
+        .map_err(Error::from)
+        .and_then(|()| self.wait_for_core_halted(timeout));
+
+        // The reset catch is cleared even if the core didn't halt, so later resets aren't caught.
         self.sequence
-            .reset_system(&mut *self.memory, crate::CoreType::Armv6m, None)?;
+            .reset_catch_clear(&mut *self.memory, crate::CoreType::Armv6m, None)?;
+        result?;
 
         // Update core status
         let _ = self.status()?;
@@ -641,9 +668,6 @@ impl<'probe> CoreInterface for Armv6m<'probe> {
             self.write_core_reg(XPSR.id, (xpsr_value | XPSR_THUMB).into())?;
         }
 
-        self.sequence
-            .reset_catch_clear(&mut *self.memory, crate::CoreType::Armv6m, None)?;
-
         // try to read the program counter
         let pc_value = self.read_core_reg(PC.id)?;
 
@@ -721,6 +745,35 @@ impl<'probe> CoreInterface for Armv6m<'probe> {
         Ok(())
     }
 
+    fn available_watchpoint_units(&mut self) -> Result<u32, Error> {
+        super::cortex_m::available_watchpoint_units(&mut *self.memory)
+    }
+
+    fn hw_watchpoints(&mut self) -> Result<Vec<Option<u64>>, Error> {
+        super::cortex_m::hw_watchpoints(&mut *self.memory)
+    }
+
+    fn set_hw_watchpoint(
+        &mut self,
+        unit_index: usize,
+        address: u64,
+        size: u32,
+        kind: WatchpointKind,
+    ) -> Result<(), Error> {
+        super::cortex_m::set_hw_watchpoint(
+            &mut *self.memory,
+            unit_index,
+            address,
+            size,
+            kind,
+            false,
+        )
+    }
+
+    fn clear_hw_watchpoint(&mut self, unit_index: usize) -> Result<(), Error> {
+        super::cortex_m::clear_hw_watchpoint(&mut *self.memory, unit_index)
+    }
+
     fn hw_breakpoints_enabled(&self) -> bool {
         self.state.hw_breakpoints_enabled
     }
@@ -764,7 +817,11 @@ impl<'probe> CoreInterface for Armv6m<'probe> {
         if dhcsr.s_halt() {
             let dfsr = Dfsr(self.memory.read_word_32(Dfsr::get_mmio_address())?);
 
-            let reason = dfsr.halt_reason();
+            let reason = super::cortex_m::identify_halt_reason(
+                &mut *self.memory,
+                dfsr.halt_reason(),
+                false,
+            )?;
 
             // Clear bits from Dfsr register
             self.memory
@@ -842,6 +899,10 @@ impl<'probe> CoreInterface for Armv6m<'probe> {
     fn fpu_support(&mut self) -> Result<bool, crate::error::Error> {
         Ok(false)
     }
+
+    fn reset_detected(&mut self) -> Result<bool, Error> {
+        super::cortex_m::reset_detected(&mut *self.memory)
+    }
 }
 
 impl<'probe> MemoryInterface for Armv6m<'probe> {
@@ -856,6 +917,11 @@ impl<'probe> MemoryInterface for Armv6m<'probe> {
         let value = self.memory.read_word_32(address)?;
         Ok(value)
     }
+
+    fn read_word_16(&mut self, address: u64) -> Result<u16, Error> {
+        let value = self.memory.read_word_16(address)?;
+        Ok(value)
+    }
     fn read_word_8(&mut self, address: u64) -> Result<u8, Error> {
         let value = self.memory.read_word_8(address)?;
         Ok(value)
@@ -871,6 +937,11 @@ impl<'probe> MemoryInterface for Armv6m<'probe> {
         Ok(())
     }
 
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), Error> {
+        self.memory.read_16(address, data)?;
+        Ok(())
+    }
+
     fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error> {
         self.memory.read_8(address, data)?;
         Ok(())
@@ -886,6 +957,11 @@ impl<'probe> MemoryInterface for Armv6m<'probe> {
         Ok(())
     }
 
+    fn write_word_16(&mut self, address: u64, data: u16) -> Result<(), Error> {
+        self.memory.write_word_16(address, data)?;
+        Ok(())
+    }
+
     fn write_word_8(&mut self, address: u64, data: u8) -> Result<(), Error> {
         self.memory.write_word_8(address, data)?;
         Ok(())
@@ -901,6 +977,11 @@ impl<'probe> MemoryInterface for Armv6m<'probe> {
         Ok(())
     }
 
+    fn write_16(&mut self, address: u64, data: &[u16]) -> Result<(), Error> {
+        self.memory.write_16(address, data)?;
+        Ok(())
+    }
+
     fn write_8(&mut self, address: u64, data: &[u8]) -> Result<(), Error> {
         self.memory.write_8(address, data)?;
         Ok(())
@@ -11,22 +11,35 @@ use crate::{
     architecture::arm::{
         core::{armv7a_debug_regs::*, register},
         memory::adi_v5_memory_interface::ArmProbe,
-        sequences::ArmDebugSequence,
+        sequences::{reset_with_type, ArmDebugSequence},
         ArmError,
     },
+    config::RamRegion,
     core::{MemoryMappedRegister, RegisterFile, RegisterId, RegisterValue},
     error::Error,
     memory::valid_32bit_address,
     Architecture, CoreInformation, CoreInterface, CoreStatus, CoreType, InstructionSet,
-    MemoryInterface,
+    MemoryInterface, ResetType,
 };
 use anyhow::Result;
 use std::{
     mem::size_of,
+    ops::Range,
     sync::Arc,
     time::{Duration, Instant},
 };
 
+/// Timeout for operations on the debug registers, which should complete immediately.
+const OPERATION_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// DBGDSCR.ExtDCCmode value for non-blocking mode.
+const DCC_MODE_NON_BLOCKING: u32 = 0b00;
+/// DBGDSCR.ExtDCCmode value for fast mode.
+///
+/// In fast mode, the instruction in DBGITR is issued on every read of DBGDTRTX
+/// and every write of DBGDTRRX, which is used for block memory transfers.
+const DCC_MODE_FAST: u32 = 0b10;
+
 /// Errors for the ARMv7-A state machine
 #[derive(thiserror::Error, Debug)]
 pub enum Armv7aError {
@@ -44,6 +57,9 @@ pub enum Armv7aError {
 }
 
 /// Interface for interacting with an ARMv7-A core
+///
+/// ARMv7-R cores, such as the Cortex-R4 and Cortex-R5, use the same debug architecture
+/// and are handled by this interface as well.
 pub struct Armv7a<'probe> {
     memory: Box<dyn ArmProbe + 'probe>,
 
@@ -56,14 +72,36 @@ pub struct Armv7a<'probe> {
     num_breakpoints: Option<u32>,
 
     itr_enabled: bool,
+
+    core_type: CoreType,
 }
 
 impl<'probe> Armv7a<'probe> {
     pub(crate) fn new(
+        memory: Box<dyn ArmProbe + 'probe>,
+        state: &'probe mut CortexAState,
+        base_address: u64,
+        sequence: Arc<dyn ArmDebugSequence>,
+    ) -> Result<Self, Error> {
+        Self::with_core_type(memory, state, base_address, sequence, CoreType::Armv7a)
+    }
+
+    /// Create an interface for an ARMv7-R core.
+    pub(crate) fn new_armv7r(
+        memory: Box<dyn ArmProbe + 'probe>,
+        state: &'probe mut CortexAState,
+        base_address: u64,
+        sequence: Arc<dyn ArmDebugSequence>,
+    ) -> Result<Self, Error> {
+        Self::with_core_type(memory, state, base_address, sequence, CoreType::Armv7r)
+    }
+
+    fn with_core_type(
         mut memory: Box<dyn ArmProbe + 'probe>,
         state: &'probe mut CortexAState,
         base_address: u64,
         sequence: Arc<dyn ArmDebugSequence>,
+        core_type: CoreType,
     ) -> Result<Self, Error> {
         if !state.initialized() {
             // determine current state
@@ -92,6 +130,7 @@ impl<'probe> Armv7a<'probe> {
             sequence,
             num_breakpoints: None,
             itr_enabled: false,
+            core_type,
         };
 
         if !core.state.initialized() {
@@ -149,14 +188,43 @@ impl<'probe> Armv7a<'probe> {
         self.memory.write_word_32(address, instruction)?;
 
         // Wait for completion
+        let dbgdscr = self.wait_for_instruction_complete()?;
+
+        self.check_and_clear_aborts(dbgdscr)?;
+
+        Ok(dbgdscr)
+    }
+
+    /// Reads DBGDSCR until all issued instructions have completed.
+    fn wait_for_instruction_complete(&mut self) -> Result<Dbgdscr, Error> {
         let address = Dbgdscr::get_mmio_address_from_base(self.base_address)?;
-        let mut dbgdscr = Dbgdscr(self.memory.read_word_32(address)?);
+        let dbgdscr = Dbgdscr(self.memory.read_word_32(address)?);
+
+        self.poll_dbgdscr(dbgdscr, Dbgdscr::instrcoml_l)
+    }
+
+    /// Reads DBGDSCR until `condition` is true, starting with the already read value `dbgdscr`.
+    fn poll_dbgdscr(
+        &mut self,
+        mut dbgdscr: Dbgdscr,
+        condition: impl Fn(&Dbgdscr) -> bool,
+    ) -> Result<Dbgdscr, Error> {
+        let address = Dbgdscr::get_mmio_address_from_base(self.base_address)?;
+        let start = Instant::now();
+
+        while !condition(&dbgdscr) {
+            if start.elapsed() > OPERATION_TIMEOUT {
+                return Err(Error::Arm(ArmError::Timeout));
+            }
 
-        while !dbgdscr.instrcoml_l() {
             dbgdscr = Dbgdscr(self.memory.read_word_32(address)?);
         }
 
-        // Check if we had any aborts, if so clear them and fail
+        Ok(dbgdscr)
+    }
+
+    /// Check if we had any aborts, if so clear them and fail
+    fn check_and_clear_aborts(&mut self, dbgdscr: Dbgdscr) -> Result<(), Error> {
         if dbgdscr.adabort_l() || dbgdscr.sdabort_l() {
             let address = Dbgdrcr::get_mmio_address_from_base(self.base_address)?;
             let mut dbgdrcr = Dbgdrcr(0);
@@ -167,19 +235,107 @@ impl<'probe> Armv7a<'probe> {
             return Err(Error::Arm(Armv7aError::DataAbort.into()));
         }
 
-        Ok(dbgdscr)
+        Ok(())
+    }
+
+    /// Sets the access mode of the DCC registers.
+    fn set_dcc_mode(&mut self, mode: u32) -> Result<(), Error> {
+        let address = Dbgdscr::get_mmio_address_from_base(self.base_address)?;
+        let mut dbgdscr = Dbgdscr(self.memory.read_word_32(address)?);
+        dbgdscr.set_extdccmode(mode);
+
+        self.memory.write_word_32(address, dbgdscr.into())?;
+
+        Ok(())
+    }
+
+    /// Reads a block of words from memory, using the DCC in fast mode.
+    fn read_32_fast(&mut self, address: u32, data: &mut [u32]) -> Result<(), Error> {
+        let Some((last, words)) = data.split_last_mut() else {
+            return Ok(());
+        };
+
+        // LDC p14, c5, [r0], #4
+        let instr = build_ldc(14, 5, 0, 4);
+
+        self.prepare_r0_for_clobber()?;
+        self.set_r0(address)?;
+
+        // Load the first word into DBGDTRTX
+        self.execute_instruction(instr)?;
+
+        if !words.is_empty() {
+            self.set_dcc_mode(DCC_MODE_FAST)?;
+
+            let result = (|| {
+                // Latch the instruction, it is issued again on each read of DBGDTRTX
+                let address = Dbgitr::get_mmio_address_from_base(self.base_address)?;
+                self.memory.write_word_32(address, instr)?;
+
+                let address = Dbgdtrtx::get_mmio_address_from_base(self.base_address)?;
+                for word in words.iter_mut() {
+                    *word = self.memory.read_word_32(address)?;
+                }
+
+                Ok(())
+            })();
+
+            // The mode is restored even if the transfer failed, as the core can't be debugged
+            // in fast mode. The first error is returned.
+            let restored = self.set_dcc_mode(DCC_MODE_NON_BLOCKING);
+            result.and(restored)?;
+        }
+
+        let dbgdscr = self.wait_for_instruction_complete()?;
+        self.check_and_clear_aborts(dbgdscr)?;
+
+        self.poll_dbgdscr(dbgdscr, Dbgdscr::txfull_l)?;
+
+        // The last word is not followed by another instruction
+        let address = Dbgdtrtx::get_mmio_address_from_base(self.base_address)?;
+        *last = self.memory.read_word_32(address)?;
+
+        Ok(())
+    }
+
+    /// Writes a block of words to memory, using the DCC in fast mode.
+    fn write_32_fast(&mut self, address: u32, data: &[u32]) -> Result<(), Error> {
+        // STC p14, c5, [r0], #4
+        let instr = build_stc(14, 5, 0, 4);
+
+        self.prepare_r0_for_clobber()?;
+        self.set_r0(address)?;
+
+        self.set_dcc_mode(DCC_MODE_FAST)?;
+
+        let result = (|| {
+            // Latch the instruction, it is issued on each write of DBGDTRRX
+            let address = Dbgitr::get_mmio_address_from_base(self.base_address)?;
+            self.memory.write_word_32(address, instr)?;
+
+            let address = Dbgdtrrx::get_mmio_address_from_base(self.base_address)?;
+            for word in data {
+                self.memory.write_word_32(address, *word)?;
+            }
+
+            Ok(())
+        })();
+
+        // Like for reads, the mode is restored even if the transfer failed.
+        let restored = self.set_dcc_mode(DCC_MODE_NON_BLOCKING);
+        result.and(restored)?;
+
+        let dbgdscr = self.wait_for_instruction_complete()?;
+        self.check_and_clear_aborts(dbgdscr)
     }
 
     /// Execute an instruction on the CPU and return the result
     fn execute_instruction_with_result(&mut self, instruction: u32) -> Result<u32, Error> {
         // Run instruction
-        let mut dbgdscr = self.execute_instruction(instruction)?;
+        let dbgdscr = self.execute_instruction(instruction)?;
 
         // Wait for TXfull
-        while !dbgdscr.txfull_l() {
-            let address = Dbgdscr::get_mmio_address_from_base(self.base_address)?;
-            dbgdscr = Dbgdscr(self.memory.read_word_32(address)?);
-        }
+        self.poll_dbgdscr(dbgdscr, Dbgdscr::txfull_l)?;
 
         // Read result
         let address = Dbgdtrtx::get_mmio_address_from_base(self.base_address)?;
@@ -199,11 +355,9 @@ impl<'probe> Armv7a<'probe> {
 
         // Wait for RXfull
         let address = Dbgdscr::get_mmio_address_from_base(self.base_address)?;
-        let mut dbgdscr = Dbgdscr(self.memory.read_word_32(address)?);
+        let dbgdscr = Dbgdscr(self.memory.read_word_32(address)?);
 
-        while !dbgdscr.rxfull_l() {
-            dbgdscr = Dbgdscr(self.memory.read_word_32(address)?);
-        }
+        self.poll_dbgdscr(dbgdscr, Dbgdscr::rxfull_l)?;
 
         // Run instruction
         self.execute_instruction(instruction)?;
@@ -285,6 +439,19 @@ impl<'probe> Armv7a<'probe> {
         Ok(())
     }
 
+    /// Reads the CP15 register selected by `crn`, `crm` and `opcode2`.
+    fn read_cp15(&mut self, crn: u8, crm: u8, opcode2: u8) -> Result<u32, Error> {
+        self.prepare_r0_for_clobber()?;
+
+        // MRC p15, 0, r0, <crn>, <crm>, <opcode2>
+        let instruction = build_mrc(15, 0, 0, crn, crm, opcode2);
+        self.execute_instruction(instruction)?;
+
+        // Read from r0
+        let instruction = build_mcr(14, 0, 0, 0, 5, 0);
+        self.execute_instruction_with_result(instruction)
+    }
+
     fn set_r0(&mut self, value: u32) -> Result<(), Error> {
         let instruction = build_mrc(14, 0, 0, 0, 5, 0);
 
@@ -361,13 +528,9 @@ impl<'probe> CoreInterface for Armv7a<'probe> {
 
         // Wait for ack
         let address = Dbgdscr::get_mmio_address_from_base(self.base_address)?;
+        let dbgdscr = Dbgdscr(self.memory.read_word_32(address)?);
 
-        loop {
-            let dbgdscr = Dbgdscr(self.memory.read_word_32(address)?);
-            if dbgdscr.restarted() {
-                break;
-            }
-        }
+        self.poll_dbgdscr(dbgdscr, Dbgdscr::restarted)?;
 
         // Recompute / verify current state
         self.set_core_status(CoreStatus::Running);
@@ -376,11 +539,13 @@ impl<'probe> CoreInterface for Armv7a<'probe> {
         Ok(())
     }
 
-    fn reset(&mut self) -> Result<(), Error> {
-        self.sequence.reset_system(
+    fn reset(&mut self, reset_type: ResetType) -> Result<(), Error> {
+        reset_with_type(
+            &*self.sequence,
             &mut *self.memory,
-            crate::CoreType::Armv7a,
+            self.core_type,
             Some(self.base_address),
+            reset_type,
         )?;
 
         // Reset our cached values
@@ -389,16 +554,22 @@ impl<'probe> CoreInterface for Armv7a<'probe> {
         Ok(())
     }
 
-    fn reset_and_halt(&mut self, timeout: Duration) -> Result<CoreInformation, Error> {
+    fn reset_and_halt(
+        &mut self,
+        reset_type: ResetType,
+        timeout: Duration,
+    ) -> Result<CoreInformation, Error> {
         self.sequence.reset_catch_set(
             &mut *self.memory,
-            crate::CoreType::Armv7a,
+            self.core_type,
             Some(self.base_address),
         )?;
-        self.sequence.reset_system(
+        reset_with_type(
+            &*self.sequence,
             &mut *self.memory,
-            crate::CoreType::Armv7a,
+            self.core_type,
             Some(self.base_address),
+            reset_type,
         )?;
 
         // Request halt
@@ -411,7 +582,7 @@ impl<'probe> CoreInterface for Armv7a<'probe> {
         // Release from reset
         self.sequence.reset_catch_clear(
             &mut *self.memory,
-            crate::CoreType::Armv7a,
+            self.core_type,
             Some(self.base_address),
         )?;
 
@@ -688,7 +859,7 @@ impl<'probe> CoreInterface for Armv7a<'probe> {
     }
 
     fn core_type(&self) -> CoreType {
-        CoreType::Armv7a
+        self.core_type
     }
 
     fn instruction_set(&mut self) -> Result<InstructionSet, Error> {
@@ -749,9 +920,47 @@ impl<'probe> CoreInterface for Armv7a<'probe> {
     }
 
     fn fpu_support(&mut self) -> Result<bool, crate::error::Error> {
-        Err(crate::error::Error::Other(anyhow::anyhow!(
-            "Fpu detection not yet implemented"
-        )))
+        // The number of FP registers is only known after the core was halted once.
+        self.read_fp_reg_count()?;
+
+        match self.state.fp_reg_count {
+            Some(count) => Ok(count > 0),
+            None => Err(Error::Arm(Armv7aError::NotHalted.into())),
+        }
+    }
+
+    fn tcm_regions(&mut self) -> Result<Vec<RamRegion>, Error> {
+        if self.core_type != CoreType::Armv7r {
+            return Ok(vec![]);
+        }
+
+        // The TCM type register (TCMTR) contains the number of ATCMs and BTCMs.
+        let tcmtr = self.read_cp15(0, 0, 2)?;
+
+        let mut regions = vec![];
+        for (name, count, opcode2) in [
+            ("ATCM", tcmtr & 0b111, 1),
+            ("BTCM", (tcmtr >> 16) & 0b111, 0),
+        ] {
+            if count == 0 {
+                continue;
+            }
+
+            let region = self.read_cp15(9, 1, opcode2)?;
+            tracing::debug!("{} region register: {:#010x}", name, region);
+
+            if let Some(range) = tcm_region_range(region) {
+                regions.push(RamRegion {
+                    name: Some(name.to_string()),
+                    range,
+                    is_boot_memory: false,
+                    cores: vec![],
+                    access: None,
+                });
+            }
+        }
+
+        Ok(regions)
     }
 
     fn on_session_stop(&mut self) -> Result<(), Error> {
@@ -765,6 +974,22 @@ impl<'probe> CoreInterface for Armv7a<'probe> {
     }
 }
 
+/// Decodes a TCM region register of a Cortex-R4 or Cortex-R5.
+///
+/// Returns `None` if the TCM is disabled or its size is 0.
+fn tcm_region_range(region: u32) -> Option<Range<u64>> {
+    let enabled = region & 0b1 != 0;
+    let size = (region >> 2) & 0b1_1111;
+
+    // The smallest size is 4 KiB, encoded as 0b00011. Smaller values mean that there is no TCM.
+    if !enabled || size < 0b00011 {
+        return None;
+    }
+
+    let base = (region & 0xffff_f000) as u64;
+    Some(base..base + (1 << (size + 9)))
+}
+
 impl<'probe> MemoryInterface for Armv7a<'probe> {
     fn supports_native_64bit_access(&mut self) -> bool {
         false
@@ -805,6 +1030,28 @@ impl<'probe> MemoryInterface for Armv7a<'probe> {
         Ok(data.to_le_bytes()[byte_offset as usize])
     }
 
+    fn read_word_16(&mut self, address: u64) -> Result<u16, Error> {
+        if address & 0x1 != 0 {
+            return Err(Error::MemoryNotAligned {
+                address,
+                alignment: 2,
+            });
+        }
+
+        // Find the word this is in and its byte offset
+        let byte_offset = (address % 4) as usize;
+        let word_start = address - byte_offset as u64;
+
+        // Read the word
+        let data = self.read_word_32(word_start)?.to_le_bytes();
+
+        // Return the half word
+        Ok(u16::from_le_bytes([
+            data[byte_offset],
+            data[byte_offset + 1],
+        ]))
+    }
+
     fn read_64(&mut self, address: u64, data: &mut [u64]) -> Result<(), crate::error::Error> {
         for (i, word) in data.iter_mut().enumerate() {
             *word = self.read_word_64(address + ((i as u64) * 8))?;
@@ -814,6 +1061,11 @@ impl<'probe> MemoryInterface for Armv7a<'probe> {
     }
 
     fn read_32(&mut self, address: u64, data: &mut [u32]) -> Result<(), Error> {
+        if data.len() > 1 {
+            let address = valid_32bit_address(address)?;
+            return self.read_32_fast(address, data);
+        }
+
         for (i, word) in data.iter_mut().enumerate() {
             *word = self.read_word_32(address + ((i as u64) * 4))?;
         }
@@ -829,6 +1081,14 @@ impl<'probe> MemoryInterface for Armv7a<'probe> {
         Ok(())
     }
 
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), Error> {
+        for (i, half_word) in data.iter_mut().enumerate() {
+            *half_word = self.read_word_16(address + ((i as u64) * 2))?;
+        }
+
+        Ok(())
+    }
+
     fn write_word_64(&mut self, address: u64, data: u64) -> Result<(), crate::error::Error> {
         let data_low = data as u32;
         let data_high = (data >> 32) as u32;
@@ -866,6 +1126,26 @@ impl<'probe> MemoryInterface for Armv7a<'probe> {
         self.write_word_32(word_start, u32::from_le_bytes(word_bytes))
     }
 
+    fn write_word_16(&mut self, address: u64, data: u16) -> Result<(), Error> {
+        if address & 0x1 != 0 {
+            return Err(Error::MemoryNotAligned {
+                address,
+                alignment: 2,
+            });
+        }
+
+        // Find the word this is in and its byte offset
+        let byte_offset = (address % 4) as usize;
+        let word_start = address - byte_offset as u64;
+
+        // Get the current word value
+        let current_word = self.read_word_32(word_start)?;
+        let mut word_bytes = current_word.to_le_bytes();
+        word_bytes[byte_offset..byte_offset + 2].copy_from_slice(&data.to_le_bytes());
+
+        self.write_word_32(word_start, u32::from_le_bytes(word_bytes))
+    }
+
     fn write_64(&mut self, address: u64, data: &[u64]) -> Result<(), crate::error::Error> {
         for (i, word) in data.iter().enumerate() {
             self.write_word_64(address + ((i as u64) * 8), *word)?;
@@ -875,6 +1155,11 @@ impl<'probe> MemoryInterface for Armv7a<'probe> {
     }
 
     fn write_32(&mut self, address: u64, data: &[u32]) -> Result<(), Error> {
+        if data.len() > 1 {
+            let address = valid_32bit_address(address)?;
+            return self.write_32_fast(address, data);
+        }
+
         for (i, word) in data.iter().enumerate() {
             self.write_word_32(address + ((i as u64) * 4), *word)?;
         }
@@ -884,7 +1169,15 @@ impl<'probe> MemoryInterface for Armv7a<'probe> {
 
     fn write_8(&mut self, address: u64, data: &[u8]) -> Result<(), Error> {
         for (i, byte) in data.iter().enumerate() {
-            self.write_word_8(address + ((i as u64) * 4), *byte)?;
+            self.write_word_8(address + (i as u64), *byte)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_16(&mut self, address: u64, data: &[u16]) -> Result<(), Error> {
+        for (i, half_word) in data.iter().enumerate() {
+            self.write_word_16(address + ((i as u64) * 2), *half_word)?;
         }
 
         Ok(())
@@ -922,24 +1215,43 @@ mod test {
         read: bool,
         address: u64,
         value: u32,
+        /// The access fails with a timeout.
+        fails: bool,
     }
 
     pub struct MockProbe {
         expected_ops: Vec<ExpectedMemoryOp>,
+        /// Checks that all expected operations were executed when the probe is dropped.
+        check_all_executed: bool,
     }
 
     impl MockProbe {
         pub fn new() -> Self {
             MockProbe {
                 expected_ops: vec![],
+                check_all_executed: false,
             }
         }
 
+        pub fn check_all_executed(&mut self) {
+            self.check_all_executed = true;
+        }
+
         pub fn expected_read(&mut self, addr: u64, value: u32) {
             self.expected_ops.push(ExpectedMemoryOp {
                 read: true,
                 address: addr,
                 value,
+                fails: false,
+            });
+        }
+
+        pub fn expected_failed_read(&mut self, addr: u64) {
+            self.expected_ops.push(ExpectedMemoryOp {
+                read: true,
+                address: addr,
+                value: 0,
+                fails: true,
             });
         }
 
@@ -948,10 +1260,32 @@ mod test {
                 read: false,
                 address: addr,
                 value,
+                fails: false,
+            });
+        }
+
+        pub fn expected_failed_write(&mut self, addr: u64, value: u32) {
+            self.expected_ops.push(ExpectedMemoryOp {
+                read: false,
+                address: addr,
+                value,
+                fails: true,
             });
         }
     }
 
+    impl Drop for MockProbe {
+        fn drop(&mut self) {
+            if self.check_all_executed && !std::thread::panicking() {
+                assert!(
+                    self.expected_ops.is_empty(),
+                    "{} expected operations were not executed",
+                    self.expected_ops.len()
+                );
+            }
+        }
+    }
+
     impl ArmProbe for MockProbe {
         fn update_core_status(&mut self, _: CoreStatus) {}
 
@@ -959,6 +1293,10 @@ mod test {
             todo!()
         }
 
+        fn read_16(&mut self, _address: u64, _data: &mut [u16]) -> Result<(), ArmError> {
+            todo!()
+        }
+
         fn read_32(&mut self, address: u64, data: &mut [u32]) -> Result<(), ArmError> {
             if self.expected_ops.is_empty() {
                 panic!(
@@ -985,6 +1323,10 @@ mod test {
                 address_to_reg_num(address)
             );
 
+            if expected_op.fails {
+                return Err(ArmError::Timeout);
+            }
+
             data[0] = expected_op.value;
 
             Ok(())
@@ -998,6 +1340,10 @@ mod test {
             todo!()
         }
 
+        fn write_16(&mut self, _address: u64, _data: &[u16]) -> Result<(), ArmError> {
+            todo!()
+        }
+
         fn write_32(&mut self, address: u64, data: &[u32]) -> Result<(), ArmError> {
             if self.expected_ops.is_empty() {
                 panic!(
@@ -1029,6 +1375,10 @@ mod test {
                 expected_op.value, data[0]
             );
 
+            if expected_op.fails {
+                return Err(ArmError::Timeout);
+            }
+
             Ok(())
         }
 
@@ -1165,6 +1515,28 @@ mod test {
         add_read_reg_expectations(probe, 0, 0b010);
     }
 
+    fn add_read_cp15_expectations(
+        probe: &mut MockProbe,
+        crn: u8,
+        crm: u8,
+        opcode2: u8,
+        value: u32,
+    ) {
+        let mut dbgdscr = Dbgdscr(0);
+        dbgdscr.set_instrcoml_l(true);
+        dbgdscr.set_txfull_l(true);
+
+        probe.expected_write(
+            Dbgitr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            build_mrc(15, 0, 0, crn, crm, opcode2),
+        );
+        probe.expected_read(
+            Dbgdscr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            dbgdscr.into(),
+        );
+        add_read_reg_expectations(probe, 0, value);
+    }
+
     fn add_read_cpsr_expectations(probe: &mut MockProbe, value: u32) {
         let mut dbgdscr = Dbgdscr(0);
         dbgdscr.set_instrcoml_l(true);
@@ -1256,6 +1628,82 @@ mod test {
         .unwrap();
     }
 
+    #[test]
+    fn armv7r_new() {
+        let mut probe = MockProbe::new();
+
+        // Add expectations
+        add_status_expectations(&mut probe, true);
+        add_enable_itr_expectations(&mut probe);
+        add_read_reg_expectations(&mut probe, 0, 0);
+        add_read_fp_count_expectations(&mut probe);
+
+        let mock_mem = Box::new(probe) as _;
+
+        let mut state = CortexAState::new();
+        let armv7r = Armv7a::new_armv7r(
+            mock_mem,
+            &mut state,
+            TEST_BASE_ADDRESS,
+            DefaultArmSequence::create(),
+        )
+        .unwrap();
+
+        assert_eq!(armv7r.core_type(), CoreType::Armv7r);
+    }
+
+    #[test]
+    fn armv7r_tcm_regions() {
+        let mut probe = MockProbe::new();
+
+        // Add expectations
+        add_status_expectations(&mut probe, true);
+        add_enable_itr_expectations(&mut probe);
+        add_read_reg_expectations(&mut probe, 0, 0);
+        add_read_fp_count_expectations(&mut probe);
+
+        // One ATCM and one BTCM
+        add_read_cp15_expectations(&mut probe, 0, 0, 2, 0x8001_0001);
+        // 64 KiB ATCM at 0x0
+        add_read_cp15_expectations(&mut probe, 9, 1, 1, 0x0000_001d);
+        // Disabled BTCM
+        add_read_cp15_expectations(&mut probe, 9, 1, 0, 0x0800_001c);
+
+        let mock_mem = Box::new(probe) as _;
+
+        let mut state = CortexAState::new();
+        let mut armv7r = Armv7a::new_armv7r(
+            mock_mem,
+            &mut state,
+            TEST_BASE_ADDRESS,
+            DefaultArmSequence::create(),
+        )
+        .unwrap();
+
+        let regions = armv7r.tcm_regions().unwrap();
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].name.as_deref(), Some("ATCM"));
+        assert_eq!(regions[0].range, 0x0..0x1_0000);
+    }
+
+    #[test]
+    fn decode_tcm_region() {
+        // 4 KiB
+        assert_eq!(
+            tcm_region_range(0x0800_000d),
+            Some(0x0800_0000..0x0800_1000)
+        );
+        // 8 MiB
+        assert_eq!(
+            tcm_region_range(0x0080_0039),
+            Some(0x0080_0000..0x0100_0000)
+        );
+        // Disabled
+        assert_eq!(tcm_region_range(0x0800_000c), None);
+        // No TCM
+        assert_eq!(tcm_region_range(0x0800_0001), None);
+    }
+
     #[test]
     fn armv7a_core_halted() {
         let mut probe = MockProbe::new();
@@ -1845,4 +2293,187 @@ mod test {
 
         assert_eq!(0xBA, armv7a.read_word_8(MEMORY_ADDRESS).unwrap());
     }
+
+    fn add_set_dcc_mode_expectations(probe: &mut MockProbe, mode: u32) {
+        let mut dbgdscr = Dbgdscr(0);
+        dbgdscr.set_halted(true);
+        dbgdscr.set_itren(true);
+        probe.expected_read(
+            Dbgdscr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            dbgdscr.into(),
+        );
+        dbgdscr.set_extdccmode(mode);
+        probe.expected_write(
+            Dbgdscr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            dbgdscr.into(),
+        );
+    }
+
+    #[test]
+    fn armv7a_read_32_fast() {
+        const MEMORY_VALUES: [u32; 3] = [0xBA5EBA11, 0xDEADBEEF, 0xC0FFEE00];
+        const MEMORY_ADDRESS: u64 = 0x12345678;
+
+        let mut probe = MockProbe::new();
+        let mut state = CortexAState::new();
+
+        // Add expectations
+        add_status_expectations(&mut probe, true);
+        add_enable_itr_expectations(&mut probe);
+        add_read_reg_expectations(&mut probe, 0, 0);
+        add_read_fp_count_expectations(&mut probe);
+
+        // The first word is loaded into DBGDTRTX using a normal LDC
+        add_set_r0_expectation(&mut probe, MEMORY_ADDRESS as u32);
+
+        let mut dbgdscr = Dbgdscr(0);
+        dbgdscr.set_instrcoml_l(true);
+        dbgdscr.set_txfull_l(true);
+
+        probe.expected_write(
+            Dbgitr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            build_ldc(14, 5, 0, 4),
+        );
+        probe.expected_read(
+            Dbgdscr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            dbgdscr.into(),
+        );
+
+        // Each read in fast mode returns a word and loads the next one
+        add_set_dcc_mode_expectations(&mut probe, DCC_MODE_FAST);
+        probe.expected_write(
+            Dbgitr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            build_ldc(14, 5, 0, 4),
+        );
+        for value in &MEMORY_VALUES[..2] {
+            probe.expected_read(
+                Dbgdtrtx::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+                *value,
+            );
+        }
+        add_set_dcc_mode_expectations(&mut probe, DCC_MODE_NON_BLOCKING);
+
+        probe.expected_read(
+            Dbgdscr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            dbgdscr.into(),
+        );
+        probe.expected_read(
+            Dbgdtrtx::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            MEMORY_VALUES[2],
+        );
+
+        let mock_mem = Box::new(probe) as _;
+
+        let mut armv7a = Armv7a::new(
+            mock_mem,
+            &mut state,
+            TEST_BASE_ADDRESS,
+            DefaultArmSequence::create(),
+        )
+        .unwrap();
+
+        let mut data = [0u32; 3];
+        armv7a.read_32(MEMORY_ADDRESS, &mut data).unwrap();
+
+        assert_eq!(data, MEMORY_VALUES);
+    }
+
+    #[test]
+    fn armv7a_read_32_fast_restores_dcc_mode_on_error() {
+        const MEMORY_ADDRESS: u64 = 0x12345678;
+
+        let mut probe = MockProbe::new();
+        probe.check_all_executed();
+        let mut state = CortexAState::new();
+
+        // Add expectations
+        add_status_expectations(&mut probe, true);
+        add_enable_itr_expectations(&mut probe);
+        add_read_reg_expectations(&mut probe, 0, 0);
+        add_read_fp_count_expectations(&mut probe);
+
+        add_set_r0_expectation(&mut probe, MEMORY_ADDRESS as u32);
+
+        let mut dbgdscr = Dbgdscr(0);
+        dbgdscr.set_instrcoml_l(true);
+        dbgdscr.set_txfull_l(true);
+
+        probe.expected_write(
+            Dbgitr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            build_ldc(14, 5, 0, 4),
+        );
+        probe.expected_read(
+            Dbgdscr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            dbgdscr.into(),
+        );
+
+        // The first read in fast mode fails
+        add_set_dcc_mode_expectations(&mut probe, DCC_MODE_FAST);
+        probe.expected_write(
+            Dbgitr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            build_ldc(14, 5, 0, 4),
+        );
+        probe
+            .expected_failed_read(Dbgdtrtx::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap());
+
+        // The DCC is switched back to the non-blocking mode anyway
+        add_set_dcc_mode_expectations(&mut probe, DCC_MODE_NON_BLOCKING);
+
+        let mock_mem = Box::new(probe) as _;
+
+        let mut armv7a = Armv7a::new(
+            mock_mem,
+            &mut state,
+            TEST_BASE_ADDRESS,
+            DefaultArmSequence::create(),
+        )
+        .unwrap();
+
+        let mut data = [0u32; 3];
+        assert!(armv7a.read_32(MEMORY_ADDRESS, &mut data).is_err());
+    }
+
+    #[test]
+    fn armv7a_write_32_fast_restores_dcc_mode_on_error() {
+        const MEMORY_VALUES: [u32; 2] = [0xBA5EBA11, 0xDEADBEEF];
+        const MEMORY_ADDRESS: u64 = 0x12345678;
+
+        let mut probe = MockProbe::new();
+        probe.check_all_executed();
+        let mut state = CortexAState::new();
+
+        // Add expectations
+        add_status_expectations(&mut probe, true);
+        add_enable_itr_expectations(&mut probe);
+        add_read_reg_expectations(&mut probe, 0, 0);
+        add_read_fp_count_expectations(&mut probe);
+
+        add_set_r0_expectation(&mut probe, MEMORY_ADDRESS as u32);
+
+        // The first write in fast mode fails
+        add_set_dcc_mode_expectations(&mut probe, DCC_MODE_FAST);
+        probe.expected_write(
+            Dbgitr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            build_stc(14, 5, 0, 4),
+        );
+        probe.expected_failed_write(
+            Dbgdtrrx::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            MEMORY_VALUES[0],
+        );
+
+        // The DCC is switched back to the non-blocking mode anyway
+        add_set_dcc_mode_expectations(&mut probe, DCC_MODE_NON_BLOCKING);
+
+        let mock_mem = Box::new(probe) as _;
+
+        let mut armv7a = Armv7a::new(
+            mock_mem,
+            &mut state,
+            TEST_BASE_ADDRESS,
+            DefaultArmSequence::create(),
+        )
+        .unwrap();
+
+        assert!(armv7a.write_32(MEMORY_ADDRESS, &MEMORY_VALUES).is_err());
+    }
 }
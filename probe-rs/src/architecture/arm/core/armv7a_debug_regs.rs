@@ -1,6 +1,9 @@
 //! Debug register definitions
 
-use crate::{core::BreakpointCause, memory_mapped_bitfield_register, HaltReason};
+use crate::{
+    core::{BreakpointCause, WatchpointCause},
+    memory_mapped_bitfield_register, HaltReason,
+};
 
 memory_mapped_bitfield_register! {
     /// DBGDSCR - Debug Status and Control Registers
@@ -57,7 +60,7 @@ memory_mapped_bitfield_register! {
     /// Fast mode.
     ///
     /// The value 0b11 is reserved.
-    pub extdccmode, _: 21, 20;
+    pub extdccmode, set_extdccmode: 21, 20;
 
     /// Asynchronous Aborts Discarded. The possible values of this bit are:
     ///
@@ -221,7 +224,7 @@ impl Dbgdscr {
                 // Breakpoint debug event
                 0b0001 => HaltReason::Breakpoint(BreakpointCause::Hardware),
                 // Async watchpoint debug event
-                0b0010 => HaltReason::Watchpoint,
+                0b0010 => HaltReason::Watchpoint(WatchpointCause::Unknown),
                 // BKPT instruction
                 0b0011 => HaltReason::Breakpoint(BreakpointCause::Software),
                 // External halt request
@@ -231,7 +234,7 @@ impl Dbgdscr {
                 // OS Unlock vector catch
                 0b1000 => HaltReason::Exception,
                 // Sync watchpoint debug event
-                0b1010 => HaltReason::Watchpoint,
+                0b1010 => HaltReason::Watchpoint(WatchpointCause::Unknown),
                 // All other values are reserved
                 _ => HaltReason::Unknown,
             }
@@ -1,14 +1,15 @@
 //! Register types and the core interface for armv7-M
 
 use crate::architecture::arm::memory::adi_v5_memory_interface::ArmProbe;
-use crate::architecture::arm::sequences::ArmDebugSequence;
+use crate::architecture::arm::sequences::{reset_with_type, ArmDebugSequence};
 use crate::architecture::arm::ArmError;
 use crate::core::{
-    CoreInformation, CoreInterface, MemoryMappedRegister, RegisterFile, RegisterId, RegisterValue,
+    CoreInformation, CoreInterface, FaultStatus, MemoryMappedRegister, RegisterFile, RegisterId,
+    RegisterValue, WatchpointKind,
 };
 use crate::error::Error;
 use crate::memory::valid_32bit_address;
-use crate::{CoreType, DebugProbeError, InstructionSet};
+use crate::{CoreType, DebugProbeError, InstructionSet, ResetType};
 
 use super::cortex_m::Mvfr0;
 use super::{register, CortexMState, Dfsr, CORTEX_M_COMMON_REGS, CORTEX_M_WITH_FP_REGS};
@@ -593,6 +594,9 @@ pub struct Armv7m<'probe> {
     state: &'probe mut CortexMState,
 
     sequence: Arc<dyn ArmDebugSequence>,
+
+    /// Keep the caches coherent with the memory accesses of the debugger.
+    cache_maintenance: bool,
 }
 
 impl<'probe> Armv7m<'probe> {
@@ -600,6 +604,8 @@ impl<'probe> Armv7m<'probe> {
         mut memory: Box<dyn ArmProbe + 'probe>,
         state: &'probe mut CortexMState,
         sequence: Arc<dyn ArmDebugSequence>,
+        cache_maintenance: bool,
+        non_intrusive: bool,
     ) -> Result<Self, Error> {
         if !state.initialized() {
             // determine current state
@@ -621,9 +627,12 @@ impl<'probe> Armv7m<'probe> {
 
             // Clear DFSR register. The bits in the register are sticky,
             // so we clear them here to ensure that that none are set.
-            let dfsr_clear = Dfsr::clear_all();
+            // A non-intrusive attach leaves them to the firmware or another debugger.
+            if !non_intrusive {
+                let dfsr_clear = Dfsr::clear_all();
 
-            memory.write_word_32(Dfsr::get_mmio_address(), dfsr_clear.into())?;
+                memory.write_word_32(Dfsr::get_mmio_address(), dfsr_clear.into())?;
+            }
 
             state.current_state = core_state;
             state.fp_present = Mvfr0(memory.read_word_32(Mvfr0::get_mmio_address())?).fp_present();
@@ -635,12 +644,97 @@ impl<'probe> Armv7m<'probe> {
             memory,
             state,
             sequence,
+            cache_maintenance,
         })
     }
 
     fn set_core_status(&mut self, new_status: CoreStatus) {
         super::update_core_status(&mut self.memory, &mut self.state.current_state, new_status);
     }
+
+    /// Returns which of the data and the instruction cache need maintenance for an access to `address`.
+    fn enabled_caches(&mut self, address: u64) -> Result<(bool, bool), ArmError> {
+        // The system region is never cached.
+        if !self.cache_maintenance || address >= SYSTEM_REGION_START {
+            return Ok((false, false));
+        }
+
+        let ccr = self.memory.read_word_32(CCR)?;
+
+        Ok((ccr & CCR_DC != 0, ccr & CCR_IC != 0))
+    }
+
+    /// Cleans the data cache for the `len` bytes at `address`, so the memory contains
+    /// the data written by the core.
+    fn clean_data_cache(&mut self, address: u64, len: usize) -> Result<(), ArmError> {
+        if let (true, _) = self.enabled_caches(address)? {
+            for line in cache_lines(address, len) {
+                self.memory.write_word_32(DCCMVAC, line as u32)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Invalidates the caches for the `len` bytes at `address`, so the core sees the data
+    /// written by the debugger.
+    fn invalidate_caches(&mut self, address: u64, len: usize) -> Result<(), ArmError> {
+        let (data, instruction) = self.enabled_caches(address)?;
+
+        if data {
+            for line in cache_lines(address, len) {
+                self.memory.write_word_32(DCIMVAC, line as u32)?;
+            }
+        }
+
+        if instruction {
+            self.memory.write_word_32(ICIALLU, 0)?;
+        }
+
+        Ok(())
+    }
+
+    /// Runs the memory `write`, keeping the caches for the `len` bytes at `address` coherent.
+    fn write_coherent(
+        &mut self,
+        address: u64,
+        len: usize,
+        write: impl FnOnce(&mut dyn ArmProbe) -> Result<(), ArmError>,
+    ) -> Result<(), Error> {
+        // Dirty cache lines are cleaned first, otherwise they would overwrite the written data
+        // when they are evicted.
+        self.clean_data_cache(address, len)?;
+        write(&mut *self.memory)?;
+        self.invalidate_caches(address, len)?;
+
+        Ok(())
+    }
+}
+
+/// Start of the system region, which contains the PPB and is never cached.
+const SYSTEM_REGION_START: u64 = 0xE000_0000;
+
+/// The Configuration and Control Register, which contains the cache enable bits.
+const CCR: u64 = 0xE000_ED14;
+const CCR_DC: u32 = 1 << 16;
+const CCR_IC: u32 = 1 << 17;
+
+/// Invalidate all instruction cache lines to the point of unification.
+const ICIALLU: u64 = 0xE000_EF50;
+/// Invalidate data cache line by address to the point of coherency.
+const DCIMVAC: u64 = 0xE000_EF5C;
+/// Clean data cache line by address to the point of coherency.
+const DCCMVAC: u64 = 0xE000_EF68;
+
+/// The size of a cache line of the Cortex-M7.
+const CACHE_LINE_SIZE: u64 = 32;
+
+/// The addresses of the cache lines which contain the `len` bytes at `address`.
+fn cache_lines(address: u64, len: usize) -> impl Iterator<Item = u64> {
+    let start = address & !(CACHE_LINE_SIZE - 1);
+    let end = address + len as u64;
+
+    (start..end).step_by(CACHE_LINE_SIZE as usize)
 }
 
 impl<'probe> CoreInterface for Armv7m<'probe> {
@@ -690,7 +784,8 @@ impl<'probe> CoreInterface for Armv7m<'probe> {
         if dhcsr.s_halt() {
             let dfsr = Dfsr(self.memory.read_word_32(Dfsr::get_mmio_address())?);
 
-            let reason = dfsr.halt_reason();
+            let reason =
+                super::cortex_m::identify_halt_reason(&mut *self.memory, dfsr.halt_reason(), true)?;
 
             // Clear bits from Dfsr register
             self.memory
@@ -731,8 +826,17 @@ impl<'probe> CoreInterface for Armv7m<'probe> {
 
     fn read_core_reg(&mut self, address: RegisterId) -> Result<RegisterValue, Error> {
         if self.state.current_state.is_halted() {
-            let val = super::cortex_m::read_core_reg(&mut *self.memory, address)?;
-            Ok(val.into())
+            super::cortex_m::read_core_reg_value(&mut *self.memory, address, self.state.fp_present)
+        } else {
+            Err(Error::Arm(ArmError::CoreNotHalted))
+        }
+    }
+
+    fn read_core_reg_nonstop(&mut self, address: RegisterId) -> Result<RegisterValue, Error> {
+        if self.state.current_state.is_halted() {
+            self.read_core_reg(address)
+        } else if address == register::PC.id {
+            Ok(super::cortex_m::sample_pc(&mut *self.memory)?.into())
         } else {
             Err(Error::Arm(ArmError::CoreNotHalted))
         }
@@ -740,8 +844,12 @@ impl<'probe> CoreInterface for Armv7m<'probe> {
 
     fn write_core_reg(&mut self, address: RegisterId, value: RegisterValue) -> Result<(), Error> {
         if self.state.current_state.is_halted() {
-            super::cortex_m::write_core_reg(&mut *self.memory, address, value.try_into()?)?;
-            Ok(())
+            super::cortex_m::write_core_reg_value(
+                &mut *self.memory,
+                address,
+                value,
+                self.state.fp_present,
+            )
         } else {
             Err(Error::Arm(ArmError::CoreNotHalted))
         }
@@ -860,20 +968,41 @@ impl<'probe> CoreInterface for Armv7m<'probe> {
         })
     }
 
-    fn reset(&mut self) -> Result<(), Error> {
-        self.sequence
-            .reset_system(&mut *self.memory, crate::CoreType::Armv7m, None)?;
+    fn reset(&mut self, reset_type: ResetType) -> Result<(), Error> {
+        reset_with_type(
+            &*self.sequence,
+            &mut *self.memory,
+            crate::CoreType::Armv7m,
+            None,
+            reset_type,
+        )?;
         Ok(())
     }
 
-    fn reset_and_halt(&mut self, _timeout: Duration) -> Result<CoreInformation, Error> {
+    fn reset_and_halt(
+        &mut self,
+        reset_type: ResetType,
+        timeout: Duration,
+    ) -> Result<CoreInformation, Error> {
         // Set the vc_corereset bit in the DEMCR register.
         // This will halt the core after reset.
 
         self.sequence
             .reset_catch_set(&mut *self.memory, crate::CoreType::Armv7m, None)?;
+        let result = reset_with_type(
+            &*self.sequence,
+            &mut *self.memory,
+            crate::CoreType::Armv7m,
+            None,
+            reset_type,
+        )
+        .map_err(Error::from)
+        .and_then(|()| self.wait_for_core_halted(timeout));
+
+        // The reset catch is cleared even if the core didn't halt, so later resets aren't caught.
         self.sequence
-            .reset_system(&mut *self.memory, crate::CoreType::Armv7m, None)?;
+            .reset_catch_clear(&mut *self.memory, crate::CoreType::Armv7m, None)?;
+        result?;
 
         // Update core status
         let _ = self.status()?;
@@ -884,9 +1013,6 @@ impl<'probe> CoreInterface for Armv7m<'probe> {
             self.write_core_reg(register::XPSR.id, (xpsr_value | XPSR_THUMB).into())?;
         }
 
-        self.sequence
-            .reset_catch_clear(&mut *self.memory, crate::CoreType::Armv7m, None)?;
-
         // try to read the program counter
         let pc_value = self.read_core_reg(register::PC.id)?;
 
@@ -978,6 +1104,35 @@ impl<'probe> CoreInterface for Armv7m<'probe> {
         Ok(())
     }
 
+    fn available_watchpoint_units(&mut self) -> Result<u32, Error> {
+        super::cortex_m::available_watchpoint_units(&mut *self.memory)
+    }
+
+    fn hw_watchpoints(&mut self) -> Result<Vec<Option<u64>>, Error> {
+        super::cortex_m::hw_watchpoints(&mut *self.memory)
+    }
+
+    fn set_hw_watchpoint(
+        &mut self,
+        unit_index: usize,
+        address: u64,
+        size: u32,
+        kind: WatchpointKind,
+    ) -> Result<(), Error> {
+        super::cortex_m::set_hw_watchpoint(
+            &mut *self.memory,
+            unit_index,
+            address,
+            size,
+            kind,
+            false,
+        )
+    }
+
+    fn clear_hw_watchpoint(&mut self, unit_index: usize) -> Result<(), Error> {
+        super::cortex_m::clear_hw_watchpoint(&mut *self.memory, unit_index)
+    }
+
     fn hw_breakpoints_enabled(&self) -> bool {
         self.state.hw_breakpoints_enabled
     }
@@ -1029,6 +1184,22 @@ impl<'probe> CoreInterface for Armv7m<'probe> {
     fn fpu_support(&mut self) -> Result<bool, crate::error::Error> {
         Ok(self.state.fp_present)
     }
+
+    fn fault_status(&mut self) -> Result<FaultStatus, Error> {
+        super::cortex_m::read_fault_status(&mut *self.memory)
+    }
+
+    fn reset_detected(&mut self) -> Result<bool, Error> {
+        super::cortex_m::reset_detected(&mut *self.memory)
+    }
+
+    fn enable_cycle_counter(&mut self) -> Result<(), Error> {
+        super::cortex_m::enable_cycle_counter(&mut *self.memory)
+    }
+
+    fn read_cycle_counter(&mut self) -> Result<u32, Error> {
+        super::cortex_m::read_cycle_counter(&mut *self.memory)
+    }
 }
 
 impl<'probe> MemoryInterface for Armv7m<'probe> {
@@ -1037,81 +1208,109 @@ impl<'probe> MemoryInterface for Armv7m<'probe> {
     }
 
     fn read_word_64(&mut self, address: u64) -> Result<u64, crate::error::Error> {
+        self.clean_data_cache(address, 8)?;
+
         self.memory
             .read_word_64(address)
             .map_err(From::<ArmError>::from)
     }
 
     fn read_word_32(&mut self, address: u64) -> Result<u32, Error> {
+        self.clean_data_cache(address, 4)?;
+
         self.memory
             .read_word_32(address)
             .map_err(From::<ArmError>::from)
     }
 
+    fn read_word_16(&mut self, address: u64) -> Result<u16, Error> {
+        self.clean_data_cache(address, 2)?;
+
+        self.memory
+            .read_word_16(address)
+            .map_err(From::<ArmError>::from)
+    }
+
     fn read_word_8(&mut self, address: u64) -> Result<u8, Error> {
+        self.clean_data_cache(address, 1)?;
+
         self.memory
             .read_word_8(address)
             .map_err(From::<ArmError>::from)
     }
 
     fn read_64(&mut self, address: u64, data: &mut [u64]) -> Result<(), crate::error::Error> {
+        self.clean_data_cache(address, data.len() * 8)?;
+
         self.memory
             .read_64(address, data)
             .map_err(From::<ArmError>::from)
     }
 
     fn read_32(&mut self, address: u64, data: &mut [u32]) -> Result<(), Error> {
+        self.clean_data_cache(address, data.len() * 4)?;
+
         self.memory
             .read_32(address, data)
             .map_err(From::<ArmError>::from)
     }
 
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), Error> {
+        self.clean_data_cache(address, data.len() * 2)?;
+
+        self.memory
+            .read_16(address, data)
+            .map_err(From::<ArmError>::from)
+    }
+
     fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error> {
+        self.clean_data_cache(address, data.len())?;
+
         self.memory
             .read_8(address, data)
             .map_err(From::<ArmError>::from)
     }
 
     fn write_word_64(&mut self, address: u64, data: u64) -> Result<(), crate::error::Error> {
-        self.memory
-            .write_word_64(address, data)
-            .map_err(From::<ArmError>::from)
+        self.write_coherent(address, 8, |memory| memory.write_word_64(address, data))
     }
 
     fn write_word_32(&mut self, address: u64, data: u32) -> Result<(), Error> {
-        self.memory
-            .write_word_32(address, data)
-            .map_err(From::<ArmError>::from)
+        self.write_coherent(address, 4, |memory| memory.write_word_32(address, data))
+    }
+
+    fn write_word_16(&mut self, address: u64, data: u16) -> Result<(), Error> {
+        self.write_coherent(address, 2, |memory| memory.write_word_16(address, data))
     }
 
     fn write_word_8(&mut self, address: u64, data: u8) -> Result<(), Error> {
-        self.memory
-            .write_word_8(address, data)
-            .map_err(From::<ArmError>::from)
+        self.write_coherent(address, 1, |memory| memory.write_word_8(address, data))
     }
 
     fn write_64(&mut self, address: u64, data: &[u64]) -> Result<(), crate::error::Error> {
-        self.memory
-            .write_64(address, data)
-            .map_err(From::<ArmError>::from)
+        self.write_coherent(address, data.len() * 8, |memory| {
+            memory.write_64(address, data)
+        })
     }
 
     fn write_32(&mut self, address: u64, data: &[u32]) -> Result<(), Error> {
-        self.memory
-            .write_32(address, data)
-            .map_err(From::<ArmError>::from)
+        self.write_coherent(address, data.len() * 4, |memory| {
+            memory.write_32(address, data)
+        })
+    }
+
+    fn write_16(&mut self, address: u64, data: &[u16]) -> Result<(), Error> {
+        self.write_coherent(address, data.len() * 2, |memory| {
+            memory.write_16(address, data)
+        })
     }
 
     fn write_8(&mut self, address: u64, data: &[u8]) -> Result<(), Error> {
-        self.memory
-            .write_8(address, data)
-            .map_err(From::<ArmError>::from)
+        self.write_coherent(address, data.len(), |memory| memory.write_8(address, data))
     }
 
     fn write(&mut self, address: u64, data: &[u8]) -> Result<(), Error> {
-        self.memory
-            .write(address, data)
-            .map_err(From::<ArmError>::from)
+        self.write_coherent(address, data.len(), |memory| memory.write(address, data))
     }
 
     fn supports_8bit_transfers(&self) -> Result<bool, Error> {
@@ -1125,6 +1324,19 @@ impl<'probe> MemoryInterface for Armv7m<'probe> {
     }
 }
 
+#[test]
+fn cache_lines_of_range() {
+    assert_eq!(
+        cache_lines(0x2000_0010, 0x48).collect::<Vec<_>>(),
+        vec![0x2000_0000, 0x2000_0020, 0x2000_0040]
+    );
+    assert_eq!(
+        cache_lines(0x2000_0020, 4).collect::<Vec<_>>(),
+        vec![0x2000_0020]
+    );
+    assert_eq!(cache_lines(0x2000_0020, 0).count(), 0);
+}
+
 #[test]
 fn breakpoint_register_value() {
     // Check that the register configuration for the FPBU is
@@ -11,14 +11,16 @@ use super::{
 use crate::core::memory_mapped_registers::MemoryMappedRegister;
 use crate::{
     architecture::arm::{
-        core::armv8a_debug_regs::*, memory::adi_v5_memory_interface::ArmProbe,
-        sequences::ArmDebugSequence, ArmError,
+        core::armv8a_debug_regs::*,
+        memory::adi_v5_memory_interface::ArmProbe,
+        sequences::{reset_with_type, ArmDebugSequence},
+        ArmError,
     },
     core::{RegisterFile, RegisterId, RegisterValue},
     error::Error,
     memory::valid_32bit_address,
     Architecture, CoreInformation, CoreInterface, CoreStatus, CoreType, InstructionSet,
-    MemoryInterface,
+    MemoryInterface, ResetType,
 };
 use anyhow::Result;
 use std::{
@@ -42,6 +44,9 @@ pub enum Armv8aError {
     DataAbort,
 }
 
+/// Timeout for operations on the debug registers, which should complete immediately.
+const OPERATION_TIMEOUT: Duration = Duration::from_millis(250);
+
 /// When in 32-bit mode the two words have to be placed in swapped
 fn prep_instr_for_itr_32(instruction: u32) -> u32 {
     ((instruction & 0xFFFF) << 16) | ((instruction & 0xFFFF_0000) >> 16)
@@ -129,13 +134,37 @@ impl<'probe> Armv8a<'probe> {
 
         // Wait for completion
         let address = Edscr::get_mmio_address_from_base(self.base_address)?;
-        let mut edscr = Edscr(self.memory.read_word_32(address)?);
+        let edscr = Edscr(self.memory.read_word_32(address)?);
+
+        let edscr = self.poll_edscr(edscr, Edscr::ite)?;
+
+        self.check_and_clear_aborts(edscr)?;
+
+        Ok(edscr)
+    }
+
+    /// Reads EDSCR until `condition` is true, starting with the already read value `edscr`.
+    fn poll_edscr(
+        &mut self,
+        mut edscr: Edscr,
+        condition: impl Fn(&Edscr) -> bool,
+    ) -> Result<Edscr, Error> {
+        let address = Edscr::get_mmio_address_from_base(self.base_address)?;
+        let start = Instant::now();
+
+        while !condition(&edscr) {
+            if start.elapsed() > OPERATION_TIMEOUT {
+                return Err(Error::Arm(ArmError::Timeout));
+            }
 
-        while !edscr.ite() {
             edscr = Edscr(self.memory.read_word_32(address)?);
         }
 
-        // Check if we had any aborts, if so clear them and fail
+        Ok(edscr)
+    }
+
+    /// Check if we had any aborts, if so clear them and fail
+    fn check_and_clear_aborts(&mut self, edscr: Edscr) -> Result<(), Error> {
         if edscr.err() || edscr.a() {
             let address = Edrcr::get_mmio_address_from_base(self.base_address)?;
             let mut edrcr = Edrcr(0);
@@ -146,19 +175,96 @@ impl<'probe> Armv8a<'probe> {
             return Err(Error::Arm(Armv8aError::DataAbort.into()));
         }
 
+        Ok(())
+    }
+
+    /// Enables or disables the memory access mode of the DCC.
+    ///
+    /// In memory access mode, each read of DBGDTRTX executes `LDR W1, [X0], #4`,
+    /// and each write of DBGDTRRX executes `STR W1, [X0], #4`. This is only
+    /// available in AArch64 state.
+    fn set_memory_access_mode(&mut self, enabled: bool) -> Result<Edscr, Error> {
+        let address = Edscr::get_mmio_address_from_base(self.base_address)?;
+        let mut edscr = Edscr(self.memory.read_word_32(address)?);
+        edscr.set_ma(enabled);
+
+        self.memory.write_word_32(address, edscr.into())?;
+
         Ok(edscr)
     }
 
+    /// Reads a block of words from memory, using the memory access mode.
+    fn read_cpu_memory_aarch64_32_fast(
+        &mut self,
+        address: u64,
+        data: &mut [u32],
+    ) -> Result<(), Error> {
+        let Some((last, words)) = data.split_last_mut() else {
+            return Ok(());
+        };
+
+        // Save x0, x1
+        self.prepare_for_clobber(0)?;
+        self.prepare_for_clobber(1)?;
+
+        // Load x0 with the address to read from
+        self.set_reg_value(0, address)?;
+
+        // MSR DBGDTR_EL0, X0 - Dummy operation so that DBGDTRTX is full
+        let instruction = aarch64::build_msr(2, 3, 0, 4, 0, 0);
+        self.execute_instruction(instruction)?;
+
+        self.set_memory_access_mode(true)?;
+
+        let address = Dbgdtrtx::get_mmio_address_from_base(self.base_address)?;
+
+        // The first read returns the dummy value and loads the first word
+        let _ = self.memory.read_word_32(address)?;
+
+        for word in words.iter_mut() {
+            *word = self.memory.read_word_32(address)?;
+        }
+
+        let edscr = self.set_memory_access_mode(false)?;
+        self.check_and_clear_aborts(edscr)?;
+
+        // The last word is read in normal mode, so no further load is executed
+        *last = self.memory.read_word_32(address)?;
+
+        Ok(())
+    }
+
+    /// Writes a block of words to memory, using the memory access mode.
+    fn write_cpu_memory_aarch64_32_fast(
+        &mut self,
+        address: u64,
+        data: &[u32],
+    ) -> Result<(), Error> {
+        // Save x0, x1
+        self.prepare_for_clobber(0)?;
+        self.prepare_for_clobber(1)?;
+
+        // Load x0 with the address to write to
+        self.set_reg_value(0, address)?;
+
+        self.set_memory_access_mode(true)?;
+
+        let address = Dbgdtrrx::get_mmio_address_from_base(self.base_address)?;
+        for word in data {
+            self.memory.write_word_32(address, *word)?;
+        }
+
+        let edscr = self.set_memory_access_mode(false)?;
+        self.check_and_clear_aborts(edscr)
+    }
+
     /// Execute an instruction on the CPU and return the result
     fn execute_instruction_with_result_32(&mut self, instruction: u32) -> Result<u32, Error> {
         // Run instruction
-        let mut edscr = self.execute_instruction(instruction)?;
+        let edscr = self.execute_instruction(instruction)?;
 
         // Wait for TXfull
-        while !edscr.txfull() {
-            let address = Edscr::get_mmio_address_from_base(self.base_address)?;
-            edscr = Edscr(self.memory.read_word_32(address)?);
-        }
+        self.poll_edscr(edscr, Edscr::txfull)?;
 
         // Read result
         let address = Dbgdtrtx::get_mmio_address_from_base(self.base_address)?;
@@ -170,13 +276,10 @@ impl<'probe> Armv8a<'probe> {
     /// Execute an instruction on the CPU and return the result
     fn execute_instruction_with_result_64(&mut self, instruction: u32) -> Result<u64, Error> {
         // Run instruction
-        let mut edscr = self.execute_instruction(instruction)?;
+        let edscr = self.execute_instruction(instruction)?;
 
         // Wait for TXfull
-        while !edscr.txfull() {
-            let address = Edscr::get_mmio_address_from_base(self.base_address)?;
-            edscr = Edscr(self.memory.read_word_32(address)?);
-        }
+        self.poll_edscr(edscr, Edscr::txfull)?;
 
         // Read result
         let address = Dbgdtrrx::get_mmio_address_from_base(self.base_address)?;
@@ -199,11 +302,9 @@ impl<'probe> Armv8a<'probe> {
 
         // Wait for RXfull
         let address = Edscr::get_mmio_address_from_base(self.base_address)?;
-        let mut edscr = Edscr(self.memory.read_word_32(address)?);
+        let edscr = Edscr(self.memory.read_word_32(address)?);
 
-        while !edscr.rxfull() {
-            edscr = Edscr(self.memory.read_word_32(address)?);
-        }
+        self.poll_edscr(edscr, Edscr::rxfull)?;
 
         // Run instruction
         self.execute_instruction(instruction)?;
@@ -228,11 +329,9 @@ impl<'probe> Armv8a<'probe> {
 
         // Wait for RXfull
         let address = Edscr::get_mmio_address_from_base(self.base_address)?;
-        let mut edscr = Edscr(self.memory.read_word_32(address)?);
+        let edscr = Edscr(self.memory.read_word_32(address)?);
 
-        while !edscr.rxfull() {
-            edscr = Edscr(self.memory.read_word_32(address)?);
-        }
+        self.poll_edscr(edscr, Edscr::rxfull)?;
 
         // Run instruction
         self.execute_instruction(instruction)?;
@@ -810,11 +909,11 @@ impl<'probe> CoreInterface for Armv8a<'probe> {
 
         // Wait for ack
         let address = Edprsr::get_mmio_address_from_base(self.base_address)?;
+        let start = Instant::now();
 
-        loop {
-            let edprsr = Edprsr(self.memory.read_word_32(address)?);
-            if edprsr.sdr() {
-                break;
+        while !Edprsr(self.memory.read_word_32(address)?).sdr() {
+            if start.elapsed() > OPERATION_TIMEOUT {
+                return Err(Error::Arm(ArmError::Timeout));
             }
         }
 
@@ -831,11 +930,13 @@ impl<'probe> CoreInterface for Armv8a<'probe> {
         Ok(())
     }
 
-    fn reset(&mut self) -> Result<(), Error> {
-        self.sequence.reset_system(
+    fn reset(&mut self, reset_type: ResetType) -> Result<(), Error> {
+        reset_with_type(
+            &*self.sequence,
             &mut *self.memory,
             crate::CoreType::Armv8a,
             Some(self.base_address),
+            reset_type,
         )?;
 
         // Reset our cached values
@@ -844,16 +945,22 @@ impl<'probe> CoreInterface for Armv8a<'probe> {
         Ok(())
     }
 
-    fn reset_and_halt(&mut self, timeout: Duration) -> Result<CoreInformation, Error> {
+    fn reset_and_halt(
+        &mut self,
+        reset_type: ResetType,
+        timeout: Duration,
+    ) -> Result<CoreInformation, Error> {
         self.sequence.reset_catch_set(
             &mut *self.memory,
             crate::CoreType::Armv8a,
             Some(self.base_address),
         )?;
-        self.sequence.reset_system(
+        reset_with_type(
+            &*self.sequence,
             &mut *self.memory,
             crate::CoreType::Armv8a,
             Some(self.base_address),
+            reset_type,
         )?;
 
         // Release from reset
@@ -1135,6 +1242,28 @@ impl<'probe> MemoryInterface for Armv8a<'probe> {
         Ok(data.to_le_bytes()[byte_offset as usize])
     }
 
+    fn read_word_16(&mut self, address: u64) -> Result<u16, Error> {
+        if address & 0x1 != 0 {
+            return Err(Error::MemoryNotAligned {
+                address,
+                alignment: 2,
+            });
+        }
+
+        // Find the word this is in and its byte offset
+        let byte_offset = (address % 4) as usize;
+        let word_start = address - byte_offset as u64;
+
+        // Read the word
+        let data = self.read_word_32(word_start)?.to_le_bytes();
+
+        // Return the half word
+        Ok(u16::from_le_bytes([
+            data[byte_offset],
+            data[byte_offset + 1],
+        ]))
+    }
+
     fn read_64(&mut self, address: u64, data: &mut [u64]) -> Result<(), Error> {
         for (i, word) in data.iter_mut().enumerate() {
             *word = self.read_word_64(address + ((i as u64) * 8))?;
@@ -1144,6 +1273,10 @@ impl<'probe> MemoryInterface for Armv8a<'probe> {
     }
 
     fn read_32(&mut self, address: u64, data: &mut [u32]) -> Result<(), Error> {
+        if self.state.is_64_bit && data.len() > 1 {
+            return self.read_cpu_memory_aarch64_32_fast(address, data);
+        }
+
         for (i, word) in data.iter_mut().enumerate() {
             *word = self.read_word_32(address + ((i as u64) * 4))?;
         }
@@ -1159,6 +1292,14 @@ impl<'probe> MemoryInterface for Armv8a<'probe> {
         Ok(())
     }
 
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), Error> {
+        for (i, half_word) in data.iter_mut().enumerate() {
+            *half_word = self.read_word_16(address + ((i as u64) * 2))?;
+        }
+
+        Ok(())
+    }
+
     fn write_word_64(&mut self, address: u64, data: u64) -> Result<(), Error> {
         if self.state.is_64_bit {
             self.write_cpu_memory_aarch64_64(address, data)
@@ -1192,6 +1333,26 @@ impl<'probe> MemoryInterface for Armv8a<'probe> {
         self.write_word_32(word_start, u32::from_le_bytes(word_bytes))
     }
 
+    fn write_word_16(&mut self, address: u64, data: u16) -> Result<(), Error> {
+        if address & 0x1 != 0 {
+            return Err(Error::MemoryNotAligned {
+                address,
+                alignment: 2,
+            });
+        }
+
+        // Find the word this is in and its byte offset
+        let byte_offset = (address % 4) as usize;
+        let word_start = address - byte_offset as u64;
+
+        // Get the current word value
+        let current_word = self.read_word_32(word_start)?;
+        let mut word_bytes = current_word.to_le_bytes();
+        word_bytes[byte_offset..byte_offset + 2].copy_from_slice(&data.to_le_bytes());
+
+        self.write_word_32(word_start, u32::from_le_bytes(word_bytes))
+    }
+
     fn write_64(&mut self, address: u64, data: &[u64]) -> Result<(), Error> {
         for (i, word) in data.iter().enumerate() {
             self.write_word_64(address + ((i as u64) * 8), *word)?;
@@ -1201,6 +1362,10 @@ impl<'probe> MemoryInterface for Armv8a<'probe> {
     }
 
     fn write_32(&mut self, address: u64, data: &[u32]) -> Result<(), Error> {
+        if self.state.is_64_bit && data.len() > 1 {
+            return self.write_cpu_memory_aarch64_32_fast(address, data);
+        }
+
         for (i, word) in data.iter().enumerate() {
             self.write_word_32(address + ((i as u64) * 4), *word)?;
         }
@@ -1210,7 +1375,15 @@ impl<'probe> MemoryInterface for Armv8a<'probe> {
 
     fn write_8(&mut self, address: u64, data: &[u8]) -> Result<(), Error> {
         for (i, byte) in data.iter().enumerate() {
-            self.write_word_8(address + ((i as u64) * 4), *byte)?;
+            self.write_word_8(address + (i as u64), *byte)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_16(&mut self, address: u64, data: &[u16]) -> Result<(), Error> {
+        for (i, half_word) in data.iter().enumerate() {
+            self.write_word_16(address + ((i as u64) * 2), *half_word)?;
         }
 
         Ok(())
@@ -1288,6 +1461,10 @@ mod test {
             todo!()
         }
 
+        fn read_16(&mut self, _address: u64, _data: &mut [u16]) -> Result<(), ArmError> {
+            todo!()
+        }
+
         fn read_32(&mut self, address: u64, data: &mut [u32]) -> Result<(), ArmError> {
             if self.expected_ops.is_empty() {
                 panic!(
@@ -1323,6 +1500,10 @@ mod test {
             todo!()
         }
 
+        fn write_16(&mut self, _address: u64, _data: &[u16]) -> Result<(), ArmError> {
+            todo!()
+        }
+
         fn write_32(&mut self, address: u64, data: &[u32]) -> Result<(), ArmError> {
             if self.expected_ops.is_empty() {
                 panic!(
@@ -2429,6 +2610,89 @@ mod test {
         assert_eq!(MEMORY_VALUE, armv8a.read_word_32(MEMORY_ADDRESS).unwrap());
     }
 
+    #[test]
+    fn armv8a_read_32_aarch64_fast() {
+        const MEMORY_VALUES: [u32; 3] = [0xBA5EBA11, 0xDEADBEEF, 0xC0FFEE00];
+        const MEMORY_ADDRESS: u64 = 0x1_2345_6780;
+
+        let mut probe = MockProbe::new(true);
+        let mut state = CortexAState::new();
+
+        // Add expectations
+        add_status_expectations(&mut probe, true);
+
+        add_read_reg_64_expectations(&mut probe, 0, 0);
+        add_read_reg_64_expectations(&mut probe, 1, 0);
+
+        add_set_x0_expectation(&mut probe, MEMORY_ADDRESS);
+
+        let mut edscr = Edscr(0);
+        edscr.set_ite(true);
+        probe.expected_write(
+            Editr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            aarch64::build_msr(2, 3, 0, 4, 0, 0),
+        );
+        probe.expected_read(
+            Edscr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            edscr.into(),
+        );
+
+        // Enable memory access mode
+        probe.expected_read(
+            Edscr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            edscr.into(),
+        );
+        edscr.set_ma(true);
+        probe.expected_write(
+            Edscr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            edscr.into(),
+        );
+
+        // Dummy read, followed by all words except the last one
+        probe.expected_read(
+            Dbgdtrtx::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            0,
+        );
+        for value in &MEMORY_VALUES[..2] {
+            probe.expected_read(
+                Dbgdtrtx::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+                *value,
+            );
+        }
+
+        // Disable memory access mode
+        probe.expected_read(
+            Edscr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            edscr.into(),
+        );
+        edscr.set_ma(false);
+        probe.expected_write(
+            Edscr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            edscr.into(),
+        );
+
+        probe.expected_read(
+            Dbgdtrtx::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            MEMORY_VALUES[2],
+        );
+
+        let mock_mem = Box::new(probe) as _;
+
+        let mut armv8a = Armv8a::new(
+            mock_mem,
+            &mut state,
+            TEST_BASE_ADDRESS,
+            TEST_CTI_ADDRESS,
+            DefaultArmSequence::create(),
+        )
+        .unwrap();
+
+        let mut data = [0u32; 3];
+        armv8a.read_32(MEMORY_ADDRESS, &mut data).unwrap();
+
+        assert_eq!(data, MEMORY_VALUES);
+    }
+
     #[test]
     fn armv8a_read_word_8() {
         const MEMORY_VALUE: u32 = 0xBA5EBA11;
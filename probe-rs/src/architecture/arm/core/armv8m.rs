@@ -3,14 +3,16 @@
 use super::{cortex_m::Mvfr0, CortexMState, Dfsr, CORTEX_M_COMMON_REGS, CORTEX_M_WITH_FP_REGS};
 use crate::{
     architecture::arm::{
-        core::register, memory::adi_v5_memory_interface::ArmProbe, sequences::ArmDebugSequence,
+        core::register,
+        memory::adi_v5_memory_interface::ArmProbe,
+        sequences::{reset_with_type, ArmDebugSequence},
         ArmError,
     },
-    core::{RegisterFile, RegisterId, RegisterValue},
+    core::{FaultStatus, RegisterFile, RegisterId, RegisterValue, WatchpointKind},
     error::Error,
     memory::valid_32bit_address,
     Architecture, CoreInformation, CoreInterface, CoreStatus, CoreType, HaltReason, InstructionSet,
-    MemoryInterface, MemoryMappedRegister,
+    MemoryInterface, MemoryMappedRegister, ResetType,
 };
 use anyhow::Result;
 use bitfield::bitfield;
@@ -34,6 +36,7 @@ impl<'probe> Armv8m<'probe> {
         mut memory: Box<dyn ArmProbe + 'probe>,
         state: &'probe mut CortexMState,
         sequence: Arc<dyn ArmDebugSequence>,
+        non_intrusive: bool,
     ) -> Result<Self, Error> {
         if !state.initialized() {
             // determine current state
@@ -57,9 +60,12 @@ impl<'probe> Armv8m<'probe> {
 
             // Clear DFSR register. The bits in the register are sticky,
             // so we clear them here to ensure that that none are set.
-            let dfsr_clear = Dfsr::clear_all();
+            // A non-intrusive attach leaves them to the firmware or another debugger.
+            if !non_intrusive {
+                let dfsr_clear = Dfsr::clear_all();
 
-            memory.write_word_32(Dfsr::get_mmio_address(), dfsr_clear.into())?;
+                memory.write_word_32(Dfsr::get_mmio_address(), dfsr_clear.into())?;
+            }
 
             state.current_state = core_state;
             state.fp_present = Mvfr0(memory.read_word_32(Mvfr0::get_mmio_address())?).fp_present();
@@ -141,20 +147,41 @@ impl<'probe> CoreInterface for Armv8m<'probe> {
         Ok(())
     }
 
-    fn reset(&mut self) -> Result<(), Error> {
-        self.sequence
-            .reset_system(&mut *self.memory, crate::CoreType::Armv8m, None)?;
+    fn reset(&mut self, reset_type: ResetType) -> Result<(), Error> {
+        reset_with_type(
+            &*self.sequence,
+            &mut *self.memory,
+            crate::CoreType::Armv8m,
+            None,
+            reset_type,
+        )?;
         Ok(())
     }
 
-    fn reset_and_halt(&mut self, _timeout: Duration) -> Result<CoreInformation, Error> {
+    fn reset_and_halt(
+        &mut self,
+        reset_type: ResetType,
+        timeout: Duration,
+    ) -> Result<CoreInformation, Error> {
         // Set the vc_corereset bit in the DEMCR register.
         // This will halt the core after reset.
 
         self.sequence
             .reset_catch_set(&mut *self.memory, crate::CoreType::Armv8m, None)?;
+        let result = reset_with_type(
+            &*self.sequence,
+            &mut *self.memory,
+            crate::CoreType::Armv8m,
+            None,
+            reset_type,
+        )
+        .map_err(Error::from)
+        .and_then(|()| self.wait_for_core_halted(timeout));
+
+        // The reset catch is cleared even if the core didn't halt, so later resets aren't caught.
         self.sequence
-            .reset_system(&mut *self.memory, crate::CoreType::Armv8m, None)?;
+            .reset_catch_clear(&mut *self.memory, crate::CoreType::Armv8m, None)?;
+        result?;
 
         // Update core status
         let _ = self.status()?;
@@ -165,9 +192,6 @@ impl<'probe> CoreInterface for Armv8m<'probe> {
             self.write_core_reg(register::XPSR.id, (xpsr_value | XPSR_THUMB).into())?;
         }
 
-        self.sequence
-            .reset_catch_clear(&mut *self.memory, crate::CoreType::Armv8m, None)?;
-
         // try to read the program counter
         let pc_value = self.read_core_reg(register::PC.id)?;
 
@@ -231,8 +255,17 @@ impl<'probe> CoreInterface for Armv8m<'probe> {
 
     fn read_core_reg(&mut self, address: RegisterId) -> Result<RegisterValue, Error> {
         if self.state.current_state.is_halted() {
-            let value = super::cortex_m::read_core_reg(&mut *self.memory, address)?;
-            Ok(value.into())
+            super::cortex_m::read_core_reg_value(&mut *self.memory, address, self.state.fp_present)
+        } else {
+            Err(Error::Arm(ArmError::CoreNotHalted))
+        }
+    }
+
+    fn read_core_reg_nonstop(&mut self, address: RegisterId) -> Result<RegisterValue, Error> {
+        if self.state.current_state.is_halted() {
+            self.read_core_reg(address)
+        } else if address == register::PC.id {
+            Ok(super::cortex_m::sample_pc(&mut *self.memory)?.into())
         } else {
             Err(Error::Arm(ArmError::CoreNotHalted))
         }
@@ -240,8 +273,12 @@ impl<'probe> CoreInterface for Armv8m<'probe> {
 
     fn write_core_reg(&mut self, address: RegisterId, value: RegisterValue) -> Result<(), Error> {
         if self.state.current_state.is_halted() {
-            super::cortex_m::write_core_reg(&mut *self.memory, address, value.try_into()?)?;
-            Ok(())
+            super::cortex_m::write_core_reg_value(
+                &mut *self.memory,
+                address,
+                value,
+                self.state.fp_present,
+            )
         } else {
             Err(Error::Arm(ArmError::CoreNotHalted))
         }
@@ -307,6 +344,28 @@ impl<'probe> CoreInterface for Armv8m<'probe> {
         Ok(())
     }
 
+    fn available_watchpoint_units(&mut self) -> Result<u32, Error> {
+        super::cortex_m::available_watchpoint_units(&mut *self.memory)
+    }
+
+    fn hw_watchpoints(&mut self) -> Result<Vec<Option<u64>>, Error> {
+        super::cortex_m::hw_watchpoints(&mut *self.memory)
+    }
+
+    fn set_hw_watchpoint(
+        &mut self,
+        unit_index: usize,
+        address: u64,
+        size: u32,
+        kind: WatchpointKind,
+    ) -> Result<(), Error> {
+        super::cortex_m::set_hw_watchpoint(&mut *self.memory, unit_index, address, size, kind, true)
+    }
+
+    fn clear_hw_watchpoint(&mut self, unit_index: usize) -> Result<(), Error> {
+        super::cortex_m::clear_hw_watchpoint(&mut *self.memory, unit_index)
+    }
+
     fn hw_breakpoints_enabled(&self) -> bool {
         self.state.hw_breakpoints_enabled
     }
@@ -352,7 +411,8 @@ impl<'probe> CoreInterface for Armv8m<'probe> {
         if dhcsr.s_halt() {
             let dfsr = Dfsr(self.memory.read_word_32(Dfsr::get_mmio_address())?);
 
-            let reason = dfsr.halt_reason();
+            let reason =
+                super::cortex_m::identify_halt_reason(&mut *self.memory, dfsr.halt_reason(), true)?;
 
             // Clear bits from Dfsr register
             self.memory
@@ -413,6 +473,22 @@ impl<'probe> CoreInterface for Armv8m<'probe> {
     fn fpu_support(&mut self) -> Result<bool, crate::error::Error> {
         Ok(self.state.fp_present)
     }
+
+    fn fault_status(&mut self) -> Result<FaultStatus, Error> {
+        super::cortex_m::read_fault_status(&mut *self.memory)
+    }
+
+    fn reset_detected(&mut self) -> Result<bool, Error> {
+        super::cortex_m::reset_detected(&mut *self.memory)
+    }
+
+    fn enable_cycle_counter(&mut self) -> Result<(), Error> {
+        super::cortex_m::enable_cycle_counter(&mut *self.memory)
+    }
+
+    fn read_cycle_counter(&mut self) -> Result<u32, Error> {
+        super::cortex_m::read_cycle_counter(&mut *self.memory)
+    }
 }
 
 impl<'probe> MemoryInterface for Armv8m<'probe> {
@@ -424,6 +500,12 @@ impl<'probe> MemoryInterface for Armv8m<'probe> {
             .read_word_32(address)
             .map_err(From::<ArmError>::from)
     }
+
+    fn read_word_16(&mut self, address: u64) -> Result<u16, Error> {
+        self.memory
+            .read_word_16(address)
+            .map_err(From::<ArmError>::from)
+    }
     fn read_word_8(&mut self, address: u64) -> Result<u8, Error> {
         self.memory
             .read_word_8(address)
@@ -442,6 +524,12 @@ impl<'probe> MemoryInterface for Armv8m<'probe> {
             .map_err(From::<ArmError>::from)
     }
 
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), Error> {
+        self.memory
+            .read_16(address, data)
+            .map_err(From::<ArmError>::from)
+    }
+
     fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error> {
         self.memory
             .read_8(address, data)
@@ -466,6 +554,12 @@ impl<'probe> MemoryInterface for Armv8m<'probe> {
             .map_err(From::<ArmError>::from)
     }
 
+    fn write_word_16(&mut self, address: u64, data: u16) -> Result<(), Error> {
+        self.memory
+            .write_word_16(address, data)
+            .map_err(From::<ArmError>::from)
+    }
+
     fn write_word_8(&mut self, address: u64, data: u8) -> Result<(), Error> {
         self.memory
             .write_word_8(address, data)
@@ -484,6 +578,12 @@ impl<'probe> MemoryInterface for Armv8m<'probe> {
             .map_err(From::<ArmError>::from)
     }
 
+    fn write_16(&mut self, address: u64, data: &[u16]) -> Result<(), Error> {
+        self.memory
+            .write_16(address, data)
+            .map_err(From::<ArmError>::from)
+    }
+
     fn write_8(&mut self, address: u64, data: &[u8]) -> Result<(), Error> {
         self.memory
             .write_8(address, data)
@@ -1,10 +1,16 @@
 //! Common functions and data types for Cortex-M core variants
 
+use super::armv6m::Demcr;
 use crate::{
     architecture::arm::{memory::adi_v5_memory_interface::ArmProbe, ArmError},
-    core::RegisterId,
-    memory_mapped_bitfield_register, Error, MemoryMappedRegister,
+    core::{
+        BreakpointCause, Fault, FaultKind, FaultStatus, RegisterId, RegisterValue, WatchpointCause,
+        WatchpointKind,
+    },
+    memory::valid_32bit_address,
+    memory_mapped_bitfield_register, Error, HaltReason, MemoryMappedRegister,
 };
+use anyhow::anyhow;
 use std::time::{Duration, Instant};
 
 memory_mapped_bitfield_register! {
@@ -57,12 +63,24 @@ memory_mapped_bitfield_register! {
     0xE000_ED88, "CPACR",
     impl From;
     pub fpu_privilige, _: 21,20;
+    /// Access rights of coprocessor 10, the single precision FP unit.
+    pub u8, cp10, set_cp10: 21, 20;
+    /// Access rights of coprocessor 11, the double precision FP unit.
+    pub u8, cp11, set_cp11: 23, 22;
 }
 
 impl Cpacr {
+    /// Value of the `CP10` and `CP11` fields which grants full access.
+    const FULL_ACCESS: u8 = 0b11;
+
     pub fn fpu_present(&self) -> bool {
         self.fpu_privilige() != 0
     }
+
+    /// Returns true if the FP unit can be accessed by both privileged and unprivileged code.
+    pub fn fpu_full_access(&self) -> bool {
+        self.cp10() == Self::FULL_ACCESS && self.cp11() == Self::FULL_ACCESS
+    }
 }
 
 memory_mapped_bitfield_register! {
@@ -114,6 +132,123 @@ pub(crate) fn write_core_reg(
     Ok(())
 }
 
+/// ID of the `FPSCR` register.
+const FPSCR: RegisterId = RegisterId(33);
+/// ID of the `S0` register, the IDs of `S1` to `S31` follow.
+const S0: u16 = 64;
+/// ID of the `D0` register, the IDs of `D1` to `D15` follow. These IDs are not used
+/// by the hardware, the double precision registers are accessed as two single precision registers.
+const D0: u16 = 0x100;
+
+/// A core register, decoded from its [`RegisterId`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CoreRegister {
+    /// A register which is not part of the FP extension.
+    Integer(RegisterId),
+    /// `FPSCR` or one of `S0` to `S31`.
+    FloatingPoint(RegisterId),
+    /// One of `D0` to `D15`, consisting of a low and a high single precision register.
+    Double { low: RegisterId, high: RegisterId },
+}
+
+impl CoreRegister {
+    fn from_id(id: RegisterId) -> Self {
+        match id {
+            FPSCR | RegisterId(S0..=95) => CoreRegister::FloatingPoint(id),
+            RegisterId(n @ D0..=0x10F) => {
+                let low = S0 + 2 * (n - D0);
+                CoreRegister::Double {
+                    low: RegisterId(low),
+                    high: RegisterId(low + 1),
+                }
+            }
+            _ => CoreRegister::Integer(id),
+        }
+    }
+}
+
+/// Runs `f` with full access to the FP unit granted in `CPACR`.
+///
+/// Accessing the FP registers through `DCRSR` fails if the FP unit is disabled. If access has
+/// to be granted, the previous value of `CPACR` is restored afterwards, even if `f` fails.
+fn with_fpu_access<T>(
+    memory: &mut dyn ArmProbe,
+    f: impl FnOnce(&mut dyn ArmProbe) -> Result<T, Error>,
+) -> Result<T, Error> {
+    let cpacr = Cpacr(memory.read_word_32(Cpacr::get_mmio_address())?);
+
+    if cpacr.fpu_full_access() {
+        return f(memory);
+    }
+
+    let mut enabled = cpacr;
+    enabled.set_cp10(Cpacr::FULL_ACCESS);
+    enabled.set_cp11(Cpacr::FULL_ACCESS);
+    memory.write_word_32(Cpacr::get_mmio_address(), enabled.into())?;
+
+    let result = f(memory);
+
+    memory.write_word_32(Cpacr::get_mmio_address(), cpacr.into())?;
+
+    result
+}
+
+fn fpu_not_present(id: RegisterId) -> Error {
+    Error::Other(anyhow!(
+        "Register {:?} is not available, the core has no FP unit",
+        id
+    ))
+}
+
+/// Reads a core register, including the registers of the FP extension.
+///
+/// The double precision registers are read as two single precision registers.
+pub(crate) fn read_core_reg_value(
+    memory: &mut dyn ArmProbe,
+    id: RegisterId,
+    fp_present: bool,
+) -> Result<RegisterValue, Error> {
+    match CoreRegister::from_id(id) {
+        CoreRegister::Integer(id) => Ok(read_core_reg(memory, id)?.into()),
+        _ if !fp_present => Err(fpu_not_present(id)),
+        CoreRegister::FloatingPoint(id) => {
+            with_fpu_access(memory, |memory| Ok(read_core_reg(memory, id)?.into()))
+        }
+        CoreRegister::Double { low, high } => with_fpu_access(memory, |memory| {
+            let low = read_core_reg(memory, low)?;
+            let high = read_core_reg(memory, high)?;
+
+            Ok(RegisterValue::U64(((high as u64) << 32) | low as u64))
+        }),
+    }
+}
+
+/// Writes a core register, including the registers of the FP extension.
+///
+/// The double precision registers are written as two single precision registers.
+pub(crate) fn write_core_reg_value(
+    memory: &mut dyn ArmProbe,
+    id: RegisterId,
+    value: RegisterValue,
+    fp_present: bool,
+) -> Result<(), Error> {
+    match CoreRegister::from_id(id) {
+        CoreRegister::Integer(id) => write_core_reg(memory, id, value.try_into()?),
+        _ if !fp_present => Err(fpu_not_present(id)),
+        CoreRegister::FloatingPoint(id) => {
+            let value: u32 = value.try_into()?;
+            with_fpu_access(memory, |memory| write_core_reg(memory, id, value))
+        }
+        CoreRegister::Double { low, high } => {
+            let value: u64 = value.try_into()?;
+            with_fpu_access(memory, |memory| {
+                write_core_reg(memory, low, value as u32)?;
+                write_core_reg(memory, high, (value >> 32) as u32)
+            })
+        }
+    }
+}
+
 fn wait_for_core_register_transfer(
     memory: &mut dyn ArmProbe,
     timeout: Duration,
@@ -131,3 +266,836 @@ fn wait_for_core_register_transfer(
     }
     Err(ArmError::Timeout)
 }
+
+memory_mapped_bitfield_register! {
+    /// DWT Control Register, DWT_CTRL
+    pub struct DwtCtrl(u32);
+    0xE000_1000, "DWT_CTRL",
+    impl From;
+    /// The number of implemented comparators.
+    pub u8, numcomp, _: 31, 28;
+    /// The cycle counter is not implemented.
+    pub nocyccnt, _: 25;
+    /// Enables the cycle counter.
+    pub cyccntena, set_cyccntena: 0;
+}
+
+memory_mapped_bitfield_register! {
+    /// DWT Cycle Count Register, DWT_CYCCNT
+    pub struct DwtCyccnt(u32);
+    0xE000_1004, "DWT_CYCCNT",
+    impl From;
+}
+
+memory_mapped_bitfield_register! {
+    /// DWT Program Counter Sample Register, DWT_PCSR
+    pub struct DwtPcsr(u32);
+    0xE000_101C, "DWT_PCSR",
+    impl From;
+}
+
+memory_mapped_bitfield_register! {
+    /// DWT Comparator Register, DWT_COMP0. The registers of the other comparators follow
+    /// with a stride of [`DWT_COMPARATOR_STRIDE`].
+    pub struct DwtComp(u32);
+    0xE000_1020, "DWT_COMP0",
+    impl From;
+}
+
+memory_mapped_bitfield_register! {
+    /// DWT Comparator Mask Register, DWT_MASK0. Only present in ARMv6-M and ARMv7-M.
+    pub struct DwtMask(u32);
+    0xE000_1024, "DWT_MASK0",
+    impl From;
+    /// The number of least significant address bits which are ignored by the comparator.
+    pub u8, mask, set_mask: 4, 0;
+}
+
+memory_mapped_bitfield_register! {
+    /// DWT Comparator Function Register, DWT_FUNCTION0
+    pub struct DwtFunction(u32);
+    0xE000_1028, "DWT_FUNCTION0",
+    impl From;
+    /// The comparator matched since the register was last read. Reading the register clears this bit.
+    pub matched, _: 24;
+    /// The size of the watched data, as log2 of the size in bytes. Only used on ARMv8-M.
+    pub u8, datavsize, set_datavsize: 11, 10;
+    /// The action on a match. `0b01` generates a debug event. Only present on ARMv8-M.
+    pub u8, action, set_action: 5, 4;
+    /// The function of the comparator. Called `MATCH` on ARMv8-M. `0` disables the comparator.
+    pub u8, function, set_function: 3, 0;
+}
+
+/// The address offset between the registers of two DWT comparators.
+const DWT_COMPARATOR_STRIDE: u64 = 0x10;
+
+/// Returns the number of DWT comparators, which can be used as watchpoints.
+pub(crate) fn available_watchpoint_units(memory: &mut dyn ArmProbe) -> Result<u32, Error> {
+    let ctrl = DwtCtrl(memory.read_word_32(DwtCtrl::get_mmio_address())?);
+
+    Ok(ctrl.numcomp() as u32)
+}
+
+/// Samples the program counter of the running core, using the DWT_PCSR register.
+///
+/// The core registers cannot be accessed through DCRSR while the core is running,
+/// so this is the only register which can be read without halting the core.
+pub(crate) fn sample_pc(memory: &mut dyn ArmProbe) -> Result<u32, Error> {
+    // The DWT only works if it is enabled in DEMCR.
+    let mut demcr = Demcr(memory.read_word_32(Demcr::get_mmio_address())?);
+    if !demcr.dwtena() {
+        demcr.set_dwtena(true);
+        memory.write_word_32(Demcr::get_mmio_address(), demcr.into())?;
+    }
+
+    let pc = DwtPcsr(memory.read_word_32(DwtPcsr::get_mmio_address())?).0;
+
+    // The sample reads as all ones if the core is halted, or if sampling is not
+    // implemented or prohibited.
+    if pc == 0xFFFF_FFFF {
+        return Err(Error::Other(anyhow!(
+            "The program counter could not be sampled"
+        )));
+    }
+
+    Ok(pc)
+}
+
+/// Enables the DWT cycle counter, without resetting it.
+pub(crate) fn enable_cycle_counter(memory: &mut dyn ArmProbe) -> Result<(), Error> {
+    // The DWT only works if it is enabled in DEMCR.
+    let mut demcr = Demcr(memory.read_word_32(Demcr::get_mmio_address())?);
+    if !demcr.dwtena() {
+        demcr.set_dwtena(true);
+        memory.write_word_32(Demcr::get_mmio_address(), demcr.into())?;
+    }
+
+    let mut ctrl = DwtCtrl(memory.read_word_32(DwtCtrl::get_mmio_address())?);
+    if ctrl.nocyccnt() {
+        return Err(Error::Other(anyhow!(
+            "The cycle counter is not implemented by this core"
+        )));
+    }
+
+    if !ctrl.cyccntena() {
+        ctrl.set_cyccntena(true);
+        memory.write_word_32(DwtCtrl::get_mmio_address(), ctrl.into())?;
+    }
+
+    Ok(())
+}
+
+/// Reads the DWT cycle counter.
+pub(crate) fn read_cycle_counter(memory: &mut dyn ArmProbe) -> Result<u32, Error> {
+    Ok(memory.read_word_32(DwtCyccnt::get_mmio_address())?)
+}
+
+/// Returns the address of each DWT comparator which is in use.
+pub(crate) fn hw_watchpoints(memory: &mut dyn ArmProbe) -> Result<Vec<Option<u64>>, Error> {
+    let num_units = available_watchpoint_units(memory)? as u64;
+
+    let mut watchpoints = Vec::with_capacity(num_units as usize);
+
+    for unit in 0..num_units {
+        let offset = unit * DWT_COMPARATOR_STRIDE;
+        let function = DwtFunction(memory.read_word_32(DwtFunction::get_mmio_address() + offset)?);
+
+        // Comparators used for data tracing are reported as well, so they don't get overwritten.
+        if function.function() == 0 {
+            watchpoints.push(None);
+        } else {
+            let address = memory.read_word_32(DwtComp::get_mmio_address() + offset)?;
+            watchpoints.push(Some(address as u64));
+        }
+    }
+
+    Ok(watchpoints)
+}
+
+/// Configures DWT comparator `unit` to halt the core on accesses to the `size` bytes at `address`.
+///
+/// ARMv6-M and ARMv7-M use the mask register to watch naturally aligned blocks of any power of
+/// two size, ARMv8-M supports sizes of up to 4 bytes.
+pub(crate) fn set_hw_watchpoint(
+    memory: &mut dyn ArmProbe,
+    unit: usize,
+    address: u64,
+    size: u32,
+    kind: WatchpointKind,
+    armv8m: bool,
+) -> Result<(), Error> {
+    let address = valid_32bit_address(address)?;
+
+    if !size.is_power_of_two() || (armv8m && size > 4) {
+        return Err(Error::Other(anyhow!(
+            "A watchpoint on {} bytes is not supported by this core",
+            size
+        )));
+    }
+
+    if address & (size - 1) != 0 {
+        return Err(Error::MemoryNotAligned {
+            address: address.into(),
+            alignment: size as usize,
+        });
+    }
+
+    // The DWT only works if it is enabled in DEMCR.
+    let mut demcr = Demcr(memory.read_word_32(Demcr::get_mmio_address())?);
+    if !demcr.dwtena() {
+        demcr.set_dwtena(true);
+        memory.write_word_32(Demcr::get_mmio_address(), demcr.into())?;
+    }
+
+    let offset = unit as u64 * DWT_COMPARATOR_STRIDE;
+
+    // Disable the comparator while it is reconfigured.
+    memory.write_word_32(DwtFunction::get_mmio_address() + offset, 0)?;
+    memory.write_word_32(DwtComp::get_mmio_address() + offset, address)?;
+
+    let mut function = DwtFunction(0);
+
+    if armv8m {
+        function.set_function(match kind {
+            WatchpointKind::ReadWrite => 0b0100,
+            WatchpointKind::Write => 0b0101,
+            WatchpointKind::Read => 0b0110,
+        });
+        function.set_action(0b01);
+        function.set_datavsize(size.trailing_zeros() as u8);
+    } else {
+        let mut mask = DwtMask(0);
+        mask.set_mask(size.trailing_zeros() as u8);
+        memory.write_word_32(DwtMask::get_mmio_address() + offset, mask.into())?;
+
+        function.set_function(match kind {
+            WatchpointKind::Read => 0b0101,
+            WatchpointKind::Write => 0b0110,
+            WatchpointKind::ReadWrite => 0b0111,
+        });
+    }
+
+    memory.write_word_32(DwtFunction::get_mmio_address() + offset, function.into())?;
+
+    Ok(())
+}
+
+/// Disables DWT comparator `unit`.
+pub(crate) fn clear_hw_watchpoint(memory: &mut dyn ArmProbe, unit: usize) -> Result<(), Error> {
+    let offset = unit as u64 * DWT_COMPARATOR_STRIDE;
+
+    memory.write_word_32(DwtFunction::get_mmio_address() + offset, 0)?;
+    memory.write_word_32(DwtComp::get_mmio_address() + offset, 0)?;
+
+    Ok(())
+}
+
+/// Returns `true` if the core was reset since DHCSR was last read.
+///
+/// The `S_RESET_ST` bit is cleared when DHCSR is read, so resets are missed if DHCSR
+/// was read for another reason in the meantime.
+pub(crate) fn reset_detected(memory: &mut dyn ArmProbe) -> Result<bool, Error> {
+    let dhcsr = Dhcsr(memory.read_word_32(Dhcsr::get_mmio_address())?);
+
+    Ok(dhcsr.s_reset_st())
+}
+
+/// The `BKPT 0xAB` instruction, which requests a semihosting operation.
+const BKPT_SEMIHOSTING: u16 = 0xBEAB;
+/// Mask and value of the `BKPT` instruction, without its immediate.
+const BKPT_MASK: u16 = 0xFF00;
+const BKPT: u16 = 0xBE00;
+
+/// Refines the halt `reason` decoded from the DFSR, which can't tell apart some causes.
+///
+/// - For watchpoints, the DWT comparator which matched is identified.
+/// - For breakpoints, the instruction at the PC tells apart `BKPT` instructions, semihosting
+///   requests and hardware breakpoints.
+/// - For exceptions, the fault status registers are checked for a fault, if `has_fault_status`
+///   is set. ARMv6-M doesn't have these registers.
+///
+/// Reading the function registers clears their `MATCHED` bit, so this has to be called only once
+/// per halt.
+pub(crate) fn identify_halt_reason(
+    memory: &mut dyn ArmProbe,
+    reason: HaltReason,
+    has_fault_status: bool,
+) -> Result<HaltReason, Error> {
+    match reason {
+        HaltReason::Watchpoint(WatchpointCause::Unknown) => identify_watchpoint(memory),
+        HaltReason::Breakpoint(BreakpointCause::Unknown) => identify_breakpoint(memory),
+        HaltReason::Exception if has_fault_status => {
+            let status = read_fault_status(memory)?;
+
+            Ok(match status.faults.first() {
+                _ if status.escalated => HaltReason::Fault(FaultKind::HardFault),
+                Some(fault) => HaltReason::Fault(fault.kind),
+                None => reason,
+            })
+        }
+        _ => Ok(reason),
+    }
+}
+
+/// Identifies the DWT comparator which caused a watchpoint halt.
+fn identify_watchpoint(memory: &mut dyn ArmProbe) -> Result<HaltReason, Error> {
+    let num_units = available_watchpoint_units(memory)? as usize;
+
+    for unit in 0..num_units {
+        let offset = unit as u64 * DWT_COMPARATOR_STRIDE;
+        let function = DwtFunction(memory.read_word_32(DwtFunction::get_mmio_address() + offset)?);
+
+        if function.matched() && function.function() != 0 {
+            let address = memory.read_word_32(DwtComp::get_mmio_address() + offset)?;
+
+            return Ok(HaltReason::Watchpoint(WatchpointCause::Unit {
+                unit,
+                address: address as u64,
+            }));
+        }
+    }
+
+    Ok(HaltReason::Watchpoint(WatchpointCause::Unknown))
+}
+
+/// Identifies the cause of a breakpoint halt from the instruction at the PC.
+fn identify_breakpoint(memory: &mut dyn ArmProbe) -> Result<HaltReason, Error> {
+    let pc = read_core_reg(memory, RegisterId(15))?;
+
+    let word = memory.read_word_32((pc & !0b11) as u64)?;
+    let instruction = (word >> ((pc & 0b10) * 8)) as u16;
+
+    let cause = if instruction == BKPT_SEMIHOSTING {
+        BreakpointCause::Semihosting
+    } else if instruction & BKPT_MASK == BKPT {
+        BreakpointCause::Software
+    } else {
+        BreakpointCause::Hardware
+    };
+
+    Ok(HaltReason::Breakpoint(cause))
+}
+
+memory_mapped_bitfield_register! {
+    /// Configurable Fault Status Register, CFSR. Combines the MemManage, BusFault
+    /// and UsageFault status registers. Not present in ARMv6-M.
+    pub struct Cfsr(u32);
+    0xE000_ED28, "CFSR",
+    impl From;
+    /// The MMFAR register holds the address of the MemManage fault.
+    pub mmarvalid, _: 7;
+    /// The BFAR register holds the address of the BusFault.
+    pub bfarvalid, _: 15;
+}
+
+memory_mapped_bitfield_register! {
+    /// HardFault Status Register, HFSR. Not present in ARMv6-M.
+    pub struct Hfsr(u32);
+    0xE000_ED2C, "HFSR",
+    impl From;
+    /// A configurable fault was escalated to a HardFault.
+    pub forced, _: 30;
+    /// A BusFault occurred while reading the vector table.
+    pub vecttbl, _: 1;
+}
+
+memory_mapped_bitfield_register! {
+    /// MemManage Fault Address Register, MMFAR
+    pub struct Mmfar(u32);
+    0xE000_ED34, "MMFAR",
+    impl From;
+}
+
+memory_mapped_bitfield_register! {
+    /// BusFault Address Register, BFAR
+    pub struct Bfar(u32);
+    0xE000_ED38, "BFAR",
+    impl From;
+}
+
+/// The fault causes flagged in the CFSR, as bit index, fault kind and description.
+const CFSR_FAULTS: &[(u32, FaultKind, &str)] = &[
+    (0, FaultKind::MemManage, "Instruction access violation"),
+    (1, FaultKind::MemManage, "Data access violation"),
+    (
+        3,
+        FaultKind::MemManage,
+        "Fault on unstacking for a return from exception",
+    ),
+    (
+        4,
+        FaultKind::MemManage,
+        "Fault on stacking for exception entry",
+    ),
+    (
+        5,
+        FaultKind::MemManage,
+        "Fault during floating-point lazy state preservation",
+    ),
+    (8, FaultKind::BusFault, "Instruction bus error"),
+    (9, FaultKind::BusFault, "Precise data bus error"),
+    (10, FaultKind::BusFault, "Imprecise data bus error"),
+    (
+        11,
+        FaultKind::BusFault,
+        "Fault on unstacking for a return from exception",
+    ),
+    (
+        12,
+        FaultKind::BusFault,
+        "Fault on stacking for exception entry",
+    ),
+    (
+        13,
+        FaultKind::BusFault,
+        "Fault during floating-point lazy state preservation",
+    ),
+    (16, FaultKind::UsageFault, "Undefined instruction"),
+    (17, FaultKind::UsageFault, "Invalid EPSR state"),
+    (
+        18,
+        FaultKind::UsageFault,
+        "Invalid EXC_RETURN value loaded into the PC",
+    ),
+    (
+        19,
+        FaultKind::UsageFault,
+        "Access to a disabled or absent coprocessor",
+    ),
+    (20, FaultKind::UsageFault, "Stack overflow"),
+    (24, FaultKind::UsageFault, "Unaligned access"),
+    (25, FaultKind::UsageFault, "Division by zero"),
+];
+
+/// Decodes the fault status registers. The fault address registers are only used if
+/// they are flagged as valid in the CFSR.
+fn decode_fault_status(cfsr: Cfsr, hfsr: Hfsr, mmfar: Mmfar, bfar: Bfar) -> FaultStatus {
+    let mut faults: Vec<Fault> = CFSR_FAULTS
+        .iter()
+        .filter(|(bit, _, _)| cfsr.0 & (1 << bit) != 0)
+        .map(|&(bit, kind, cause)| {
+            let address = match bit {
+                // Only data access violations report the faulting address.
+                1 if cfsr.mmarvalid() => Some(mmfar.0 as u64),
+                9 if cfsr.bfarvalid() => Some(bfar.0 as u64),
+                _ => None,
+            };
+
+            Fault {
+                kind,
+                cause,
+                address,
+            }
+        })
+        .collect();
+
+    if hfsr.vecttbl() {
+        faults.push(Fault {
+            kind: FaultKind::HardFault,
+            cause: "BusFault on a vector table read",
+            address: None,
+        });
+    }
+
+    FaultStatus {
+        faults,
+        escalated: hfsr.forced(),
+    }
+}
+
+/// Reads and decodes the fault status registers of an ARMv7-M or ARMv8-M core.
+pub(crate) fn read_fault_status(memory: &mut dyn ArmProbe) -> Result<FaultStatus, Error> {
+    let cfsr = Cfsr(memory.read_word_32(Cfsr::get_mmio_address())?);
+    let hfsr = Hfsr(memory.read_word_32(Hfsr::get_mmio_address())?);
+    let mmfar = Mmfar(memory.read_word_32(Mmfar::get_mmio_address())?);
+    let bfar = Bfar(memory.read_word_32(Bfar::get_mmio_address())?);
+
+    tracing::debug!(
+        "CFSR: {:#010x}, HFSR: {:#010x}, MMFAR: {:#010x}, BFAR: {:#010x}",
+        cfsr.0,
+        hfsr.0,
+        mmfar.0,
+        bfar.0
+    );
+
+    Ok(decode_fault_status(cfsr, hfsr, mmfar, bfar))
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::{DwtComp, DwtFunction, DWT_COMPARATOR_STRIDE};
+    use crate::{
+        architecture::arm::{
+            ap::MemoryAp,
+            communication_interface::{Initialized, SwdSequence},
+            memory::adi_v5_memory_interface::ArmProbe,
+            ApAddress, ArmCommunicationInterface, ArmError, DpAddress,
+        },
+        core::{
+            BreakpointCause, Fault, FaultKind, RegisterId, RegisterValue, WatchpointCause,
+            WatchpointKind,
+        },
+        probe::DebugProbeError,
+        CoreStatus, HaltReason, MemoryMappedRegister,
+    };
+
+    /// Memory which returns zero for all addresses which were not written.
+    #[derive(Default)]
+    struct MockMemory(HashMap<u64, u32>);
+
+    impl SwdSequence for MockMemory {
+        fn swj_sequence(&mut self, _bit_len: u8, _bits: u64) -> Result<(), DebugProbeError> {
+            unimplemented!()
+        }
+
+        fn swj_pins(
+            &mut self,
+            _pin_out: u32,
+            _pin_select: u32,
+            _pin_wait: u32,
+        ) -> Result<u32, DebugProbeError> {
+            unimplemented!()
+        }
+    }
+
+    impl ArmProbe for MockMemory {
+        fn read_8(&mut self, _address: u64, _data: &mut [u8]) -> Result<(), ArmError> {
+            unimplemented!()
+        }
+
+        fn read_16(&mut self, _address: u64, _data: &mut [u16]) -> Result<(), ArmError> {
+            unimplemented!()
+        }
+
+        fn read_32(&mut self, address: u64, data: &mut [u32]) -> Result<(), ArmError> {
+            for (i, word) in data.iter_mut().enumerate() {
+                *word = self.0.get(&(address + 4 * i as u64)).copied().unwrap_or(0);
+            }
+
+            Ok(())
+        }
+
+        fn read_64(&mut self, _address: u64, _data: &mut [u64]) -> Result<(), ArmError> {
+            unimplemented!()
+        }
+
+        fn write_8(&mut self, _address: u64, _data: &[u8]) -> Result<(), ArmError> {
+            unimplemented!()
+        }
+
+        fn write_16(&mut self, _address: u64, _data: &[u16]) -> Result<(), ArmError> {
+            unimplemented!()
+        }
+
+        fn write_32(&mut self, address: u64, data: &[u32]) -> Result<(), ArmError> {
+            for (i, word) in data.iter().enumerate() {
+                self.0.insert(address + 4 * i as u64, *word);
+            }
+
+            Ok(())
+        }
+
+        fn write_64(&mut self, _address: u64, _data: &[u64]) -> Result<(), ArmError> {
+            unimplemented!()
+        }
+
+        fn flush(&mut self) -> Result<(), ArmError> {
+            Ok(())
+        }
+
+        fn supports_native_64bit_access(&mut self) -> bool {
+            false
+        }
+
+        fn supports_8bit_transfers(&self) -> Result<bool, ArmError> {
+            Ok(false)
+        }
+
+        fn ap(&mut self) -> MemoryAp {
+            MemoryAp::new(ApAddress {
+                dp: DpAddress::Default,
+                ap: 0,
+            })
+        }
+
+        fn get_arm_communication_interface(
+            &mut self,
+        ) -> Result<&mut ArmCommunicationInterface<Initialized>, DebugProbeError> {
+            Err(DebugProbeError::NotImplemented(
+                "get_arm_communication_interface",
+            ))
+        }
+
+        fn update_core_status(&mut self, _state: CoreStatus) {}
+    }
+
+    /// A DWT with four comparators.
+    fn memory_with_dwt() -> MockMemory {
+        let mut memory = MockMemory::default();
+        memory.0.insert(0xE000_1000, 0x4000_0000);
+        memory
+    }
+
+    #[test]
+    fn armv7m_watchpoint() {
+        let mut memory = memory_with_dwt();
+
+        super::set_hw_watchpoint(&mut memory, 1, 0x2000_0100, 4, WatchpointKind::Write, false)
+            .unwrap();
+
+        // DEMCR.DWTENA, COMP1, MASK1 and FUNCTION1
+        assert_eq!(memory.0[&0xE000_EDFC], 1 << 24);
+        assert_eq!(memory.0[&0xE000_1030], 0x2000_0100);
+        assert_eq!(memory.0[&0xE000_1034], 2);
+        assert_eq!(memory.0[&0xE000_1038], 0b0110);
+
+        assert_eq!(
+            super::hw_watchpoints(&mut memory).unwrap(),
+            vec![None, Some(0x2000_0100), None, None]
+        );
+
+        super::clear_hw_watchpoint(&mut memory, 1).unwrap();
+
+        assert_eq!(super::hw_watchpoints(&mut memory).unwrap(), vec![None; 4]);
+    }
+
+    #[test]
+    fn armv8m_watchpoint() {
+        let mut memory = memory_with_dwt();
+
+        super::set_hw_watchpoint(
+            &mut memory,
+            0,
+            0x2000_0102,
+            2,
+            WatchpointKind::ReadWrite,
+            true,
+        )
+        .unwrap();
+
+        // DATAVSIZE = halfword, ACTION = debug event, MATCH = data address
+        assert_eq!(memory.0[&0xE000_1028], (1 << 10) | (0b01 << 4) | 0b0100);
+
+        // Larger sizes need linked comparators, which are not supported.
+        assert!(super::set_hw_watchpoint(
+            &mut memory,
+            0,
+            0x2000_0100,
+            8,
+            WatchpointKind::Write,
+            true
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn unaligned_watchpoint() {
+        let mut memory = memory_with_dwt();
+
+        assert!(super::set_hw_watchpoint(
+            &mut memory,
+            0,
+            0x2000_0102,
+            4,
+            WatchpointKind::Read,
+            false
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn identify_matched_watchpoint() {
+        let mut memory = memory_with_dwt();
+
+        memory.0.insert(
+            DwtFunction::get_mmio_address() + 2 * DWT_COMPARATOR_STRIDE,
+            (1 << 24) | 0b0111,
+        );
+        memory.0.insert(
+            DwtComp::get_mmio_address() + 2 * DWT_COMPARATOR_STRIDE,
+            0x2000_0100,
+        );
+
+        assert_eq!(
+            super::identify_halt_reason(
+                &mut memory,
+                HaltReason::Watchpoint(WatchpointCause::Unknown),
+                true
+            )
+            .unwrap(),
+            HaltReason::Watchpoint(WatchpointCause::Unit {
+                unit: 2,
+                address: 0x2000_0100
+            })
+        );
+        assert_eq!(
+            super::identify_halt_reason(&mut memory, HaltReason::Request, true).unwrap(),
+            HaltReason::Request
+        );
+    }
+
+    /// A halted core with the PC at `pc`, and `instruction` at the PC.
+    fn memory_with_instruction(pc: u32, instruction: u16) -> MockMemory {
+        let mut memory = memory_with_fpu_disabled();
+        // DCRDR, the core register transfer returns the PC.
+        memory.0.insert(0xE000_EDF8, pc);
+
+        let shift = (pc & 0b10) * 8;
+        memory.0.insert(
+            (pc & !0b11) as u64,
+            0xBF00_BF00 & !(0xFFFF << shift) | (instruction as u32) << shift,
+        );
+        memory
+    }
+
+    #[test]
+    fn identify_breakpoint_instruction() {
+        for (instruction, cause) in [
+            (0xBEAB, BreakpointCause::Semihosting),
+            (0xBE00, BreakpointCause::Software),
+            (0xBF00, BreakpointCause::Hardware),
+        ] {
+            for pc in [0x0800_0100, 0x0800_0102] {
+                let mut memory = memory_with_instruction(pc, instruction);
+
+                assert_eq!(
+                    super::identify_halt_reason(
+                        &mut memory,
+                        HaltReason::Breakpoint(BreakpointCause::Unknown),
+                        true
+                    )
+                    .unwrap(),
+                    HaltReason::Breakpoint(cause),
+                    "instruction {instruction:#06x} at {pc:#010x}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn identify_fault_exception() {
+        let mut memory = MockMemory::default();
+        // CFSR.DIVBYZERO
+        memory.0.insert(0xE000_ED28, 1 << 25);
+
+        assert_eq!(
+            super::identify_halt_reason(&mut memory, HaltReason::Exception, true).unwrap(),
+            HaltReason::Fault(FaultKind::UsageFault)
+        );
+
+        // Without fault status registers, the exception can't be identified.
+        assert_eq!(
+            super::identify_halt_reason(&mut memory, HaltReason::Exception, false).unwrap(),
+            HaltReason::Exception
+        );
+
+        // No fault is flagged, e.g. for a vector catch on reset.
+        let mut memory = MockMemory::default();
+        assert_eq!(
+            super::identify_halt_reason(&mut memory, HaltReason::Exception, true).unwrap(),
+            HaltReason::Exception
+        );
+    }
+
+    /// A core with the FP unit disabled, which completes core register transfers immediately.
+    fn memory_with_fpu_disabled() -> MockMemory {
+        let mut memory = MockMemory::default();
+        // DHCSR.S_REGRDY
+        memory.0.insert(0xE000_EDF0, 1 << 16);
+        memory
+    }
+
+    #[test]
+    fn read_double_precision_register() {
+        let mut memory = memory_with_fpu_disabled();
+        memory.0.insert(0xE000_EDF8, 0x1234_5678);
+
+        let value = super::read_core_reg_value(&mut memory, RegisterId(0x101), true).unwrap();
+
+        assert_eq!(value, RegisterValue::U64(0x1234_5678_1234_5678));
+        // DCRSR selects S3, the upper half of D1.
+        assert_eq!(memory.0[&0xE000_EDF4], 67);
+        // CPACR is restored.
+        assert_eq!(memory.0[&0xE000_ED88], 0);
+    }
+
+    #[test]
+    fn write_double_precision_register() {
+        let mut memory = memory_with_fpu_disabled();
+        memory.0.insert(0xE000_ED88, 0x0010_0000);
+
+        super::write_core_reg_value(
+            &mut memory,
+            RegisterId(0x101),
+            RegisterValue::U64(0x1234_5678_9ABC_DEF0),
+            true,
+        )
+        .unwrap();
+
+        // The upper half is written last, to S3.
+        assert_eq!(memory.0[&0xE000_EDF8], 0x1234_5678);
+        assert_eq!(memory.0[&0xE000_EDF4], (1 << 16) | 67);
+        assert_eq!(memory.0[&0xE000_ED88], 0x0010_0000);
+    }
+
+    #[test]
+    fn fp_register_without_fpu() {
+        let mut memory = memory_with_fpu_disabled();
+
+        assert!(super::read_core_reg_value(&mut memory, RegisterId(64), false).is_err());
+        assert!(super::read_core_reg_value(&mut memory, RegisterId(15), false).is_ok());
+    }
+
+    #[test]
+    fn escalated_bus_fault() {
+        let mut memory = MockMemory::default();
+        // CFSR.PRECISERR and BFARVALID, HFSR.FORCED
+        memory.0.insert(0xE000_ED28, (1 << 9) | (1 << 15));
+        memory.0.insert(0xE000_ED2C, 1 << 30);
+        memory.0.insert(0xE000_ED38, 0x6000_0000);
+
+        let status = super::read_fault_status(&mut memory).unwrap();
+
+        assert_eq!(
+            status.faults,
+            vec![Fault {
+                kind: FaultKind::BusFault,
+                cause: "Precise data bus error",
+                address: Some(0x6000_0000),
+            }]
+        );
+        assert!(status.escalated);
+    }
+
+    #[test]
+    fn fault_address_is_ignored_if_invalid() {
+        let mut memory = MockMemory::default();
+        // CFSR.DACCVIOL and DIVBYZERO
+        memory.0.insert(0xE000_ED28, (1 << 1) | (1 << 25));
+        memory.0.insert(0xE000_ED34, 0x2000_0000);
+
+        let status = super::read_fault_status(&mut memory).unwrap();
+
+        assert_eq!(status.faults.len(), 2);
+        assert_eq!(status.faults[0].kind, FaultKind::MemManage);
+        assert_eq!(status.faults[0].address, None);
+        assert_eq!(status.faults[1].kind, FaultKind::UsageFault);
+        assert!(!status.escalated);
+    }
+
+    #[test]
+    fn sample_pc_of_running_core() {
+        let mut memory = MockMemory::default();
+        memory.0.insert(0xE000_101C, 0x0800_1234);
+
+        assert_eq!(super::sample_pc(&mut memory).unwrap(), 0x0800_1234);
+        // DEMCR.DWTENA
+        assert_eq!(memory.0[&0xE000_EDFC], 1 << 24);
+
+        memory.0.insert(0xE000_101C, 0xFFFF_FFFF);
+
+        assert!(super::sample_pc(&mut memory).is_err());
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{
     core::{
         BreakpointCause, RegisterDataType, RegisterDescription, RegisterFile, RegisterId,
-        RegisterKind, RegisterValue,
+        RegisterKind, RegisterValue, WatchpointCause,
     },
     memory_mapped_bitfield_register, CoreStatus, HaltReason,
 };
@@ -310,13 +310,13 @@ static ARM32_COMMON_REGS: RegisterFile = RegisterFile {
     fp_registers: None,
 };
 
-static AARCH32_COMMON_REGS: RegisterFile = RegisterFile {
+pub(crate) static AARCH32_COMMON_REGS: RegisterFile = RegisterFile {
     psr: Some(&register::CPSR),
 
     ..ARM32_COMMON_REGS
 };
 
-static AARCH32_FP_16_REGS: RegisterFile = RegisterFile {
+pub(crate) static AARCH32_FP_16_REGS: RegisterFile = RegisterFile {
     fp_status: Some(&register::AARCH32_FPSCR),
     fp_registers: Some(&[
         RegisterDescription {
@@ -436,7 +436,7 @@ static AARCH32_FP_16_REGS: RegisterFile = RegisterFile {
     ..AARCH32_COMMON_REGS
 };
 
-static AARCH32_FP_32_REGS: RegisterFile = RegisterFile {
+pub(crate) static AARCH32_FP_32_REGS: RegisterFile = RegisterFile {
     fp_status: Some(&register::AARCH32_FPSCR),
     fp_registers: Some(&[
         RegisterDescription {
@@ -668,7 +668,7 @@ static AARCH32_FP_32_REGS: RegisterFile = RegisterFile {
     ..AARCH32_COMMON_REGS
 };
 
-static CORTEX_M_COMMON_REGS: RegisterFile = RegisterFile {
+pub(crate) static CORTEX_M_COMMON_REGS: RegisterFile = RegisterFile {
     msp: Some(&register::MSP),
     psp: Some(&register::PSP),
     other: &[register::EXTRA],
@@ -677,7 +677,7 @@ static CORTEX_M_COMMON_REGS: RegisterFile = RegisterFile {
     ..ARM32_COMMON_REGS
 };
 
-static CORTEX_M_WITH_FP_REGS: RegisterFile = RegisterFile {
+pub(crate) static CORTEX_M_WITH_FP_REGS: RegisterFile = RegisterFile {
     fp_status: Some(&register::FPSCR),
     fp_registers: Some(&[
         RegisterDescription {
@@ -906,6 +906,124 @@ static CORTEX_M_WITH_FP_REGS: RegisterFile = RegisterFile {
         },
     ]),
 
+    // The double precision registers are not directly accessible, they are
+    // read and written as pairs of single precision registers.
+    other: &[
+        register::EXTRA,
+        RegisterDescription {
+            name: "D0",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x100),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "D1",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x101),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "D2",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x102),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "D3",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x103),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "D4",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x104),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "D5",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x105),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "D6",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x106),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "D7",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x107),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "D8",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x108),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "D9",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x109),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "D10",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x10a),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "D11",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x10b),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "D12",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x10c),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "D13",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x10d),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "D14",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x10e),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "D15",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x10f),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+    ],
+
     ..CORTEX_M_COMMON_REGS
 };
 
@@ -950,7 +1068,7 @@ impl Dfsr {
         } else if self.external() {
             HaltReason::External
         } else if self.dwttrap() {
-            HaltReason::Watchpoint
+            HaltReason::Watchpoint(WatchpointCause::Unknown)
         } else if self.halted() {
             HaltReason::Request
         } else if self.vcatch() {
@@ -315,6 +315,9 @@ bitfield! {
     /// Note
     /// Every Arm Debug Interface implementation must include at least one AP.
     pub u8, ap_sel, set_ap_sel: 31, 24;
+    /// Bits `[31:4]` of the address selected for AP accesses. Only used on DPv3 (ADIv6)
+    /// debug ports, where this field replaces the APSEL and APBANKSEL fields.
+    pub u32, addr, set_addr: 31, 4;
     /// Selects the active four-word register bank on the current AP. See Using the AP to access debug resources on page A1-31.
     /// After a powerup reset, the value of this field is UNKNOWN.
     pub u8, ap_bank_sel, set_ap_bank_sel: 7, 4;
@@ -482,6 +485,138 @@ impl Register for TARGETID {
     const NAME: &'static str = "TARGETID";
 }
 
+bitfield! {
+    /// DPIDR1, Debug Port Identification register 1 (see ADI v6.0 B2.2.6)
+    ///
+    /// DPIDR1 is only implemented in DPv3 (ADIv6) and provides additional information about the Debug Port.
+    #[derive(Clone)]
+    pub struct DPIDR1(u32);
+    impl Debug;
+    /// Error mode. Indicates the error response behavior of the DP.
+    pub errmode, _: 7;
+    /// The size of the address space in bits. Valid values are 32, 40, 44, 48, 52 and 56.
+    pub u8, asize, _: 6, 0;
+}
+
+impl TryFrom<u32> for DPIDR1 {
+    type Error = RegisterParseError;
+
+    fn try_from(raw: u32) -> Result<Self, Self::Error> {
+        Ok(Self(raw))
+    }
+}
+
+impl From<DPIDR1> for u32 {
+    fn from(raw: DPIDR1) -> Self {
+        raw.0
+    }
+}
+
+impl DpRegister for DPIDR1 {
+    const VERSION: DebugPortVersion = DebugPortVersion::DPv3;
+}
+
+impl Register for DPIDR1 {
+    const ADDRESS: u8 = 0x10;
+    const NAME: &'static str = "DPIDR1";
+}
+
+bitfield! {
+    /// BASEPTR0, Base Pointer register 0 (see ADI v6.0 B2.2.2)
+    ///
+    /// Points to the top-level component in the address space of a DPv3 (ADIv6) debug port,
+    /// which is typically a ROM table describing the access ports.
+    #[derive(Clone)]
+    pub struct BASEPTR0(u32);
+    impl Debug;
+    /// Bits `[31:12]` of the address of the top-level component.
+    pub u32, ptr, _: 31, 12;
+    /// Indicates if [`BASEPTR0::ptr`] contains a valid address.
+    pub valid, _: 0;
+}
+
+impl TryFrom<u32> for BASEPTR0 {
+    type Error = RegisterParseError;
+
+    fn try_from(raw: u32) -> Result<Self, Self::Error> {
+        Ok(Self(raw))
+    }
+}
+
+impl From<BASEPTR0> for u32 {
+    fn from(raw: BASEPTR0) -> Self {
+        raw.0
+    }
+}
+
+impl DpRegister for BASEPTR0 {
+    const VERSION: DebugPortVersion = DebugPortVersion::DPv3;
+}
+
+impl Register for BASEPTR0 {
+    const ADDRESS: u8 = 0x20;
+    const NAME: &'static str = "BASEPTR0";
+}
+
+/// BASEPTR1, Base Pointer register 1 (see ADI v6.0 B2.2.3)
+///
+/// Contains bits `[63:32]` of the address of the top-level component, if the address
+/// space of the debug port is larger than 32 bits.
+#[derive(Debug, Clone)]
+pub struct BASEPTR1(pub u32);
+
+impl TryFrom<u32> for BASEPTR1 {
+    type Error = RegisterParseError;
+
+    fn try_from(raw: u32) -> Result<Self, Self::Error> {
+        Ok(Self(raw))
+    }
+}
+
+impl From<BASEPTR1> for u32 {
+    fn from(raw: BASEPTR1) -> Self {
+        raw.0
+    }
+}
+
+impl DpRegister for BASEPTR1 {
+    const VERSION: DebugPortVersion = DebugPortVersion::DPv3;
+}
+
+impl Register for BASEPTR1 {
+    const ADDRESS: u8 = 0x30;
+    const NAME: &'static str = "BASEPTR1";
+}
+
+/// SELECT1, AP Select register 1 (see ADI v6.0 B2.2.12)
+///
+/// Contains bits `[63:32]` of the address selected for AP accesses on a DPv3 (ADIv6) debug port.
+#[derive(Debug, Clone)]
+pub struct SELECT1(pub u32);
+
+impl TryFrom<u32> for SELECT1 {
+    type Error = RegisterParseError;
+
+    fn try_from(raw: u32) -> Result<Self, Self::Error> {
+        Ok(Self(raw))
+    }
+}
+
+impl From<SELECT1> for u32 {
+    fn from(raw: SELECT1) -> Self {
+        raw.0
+    }
+}
+
+impl DpRegister for SELECT1 {
+    const VERSION: DebugPortVersion = DebugPortVersion::DPv3;
+}
+
+impl Register for SELECT1 {
+    const ADDRESS: u8 = 0x54;
+    const NAME: &'static str = "SELECT1";
+}
+
 /// The ID of a debug port. Can be used to detect and select devices in a multidrop setup.
 #[derive(Debug)]
 pub struct DebugPortId {
@@ -581,6 +716,8 @@ pub enum DebugPortVersion {
     DPv1,
     /// Version 2 (**very** rare (only known example is the RP2040))
     DPv2,
+    /// Version 3, part of ADIv6 (used by e.g. Cortex-M55/M85 and Armv9 devices)
+    DPv3,
     /// Some unsupported value was encountered!
     Unsupported(u8),
 }
@@ -593,6 +730,7 @@ impl From<DebugPortVersion> for u8 {
             DPv0 => 0,
             DPv1 => 1,
             DPv2 => 2,
+            DPv3 => 3,
             Unsupported(val) => val,
         }
     }
@@ -615,6 +753,7 @@ impl Display for DebugPortVersion {
             DPv0 => write!(f, "DPv0"),
             DPv1 => write!(f, "DPv1"),
             DPv2 => write!(f, "DPv2"),
+            DPv3 => write!(f, "DPv3"),
             Unsupported(version) => write!(f, "<unsupported Debugport Version {version}>"),
         }
     }
@@ -626,6 +765,7 @@ impl From<u8> for DebugPortVersion {
             0 => DebugPortVersion::DPv0,
             1 => DebugPortVersion::DPv1,
             2 => DebugPortVersion::DPv2,
+            3 => DebugPortVersion::DPv3,
             value => DebugPortVersion::Unsupported(value),
         }
     }
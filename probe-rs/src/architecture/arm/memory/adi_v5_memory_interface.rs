@@ -7,6 +7,7 @@ use crate::architecture::arm::{
     communication_interface::Initialized, dp::DpAccess, MemoryApInformation,
 };
 use crate::architecture::arm::{ArmCommunicationInterface, ArmError};
+use crate::memory::split_into_chunks;
 use crate::{CoreStatus, DebugProbeError};
 use std::convert::TryInto;
 use std::ops::Range;
@@ -16,6 +17,8 @@ pub trait ArmProbe: SwdSequence {
 
     fn read_32(&mut self, address: u64, data: &mut [u32]) -> Result<(), ArmError>;
 
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), ArmError>;
+
     fn read_64(&mut self, address: u64, data: &mut [u64]) -> Result<(), ArmError>;
 
     /// Reads a 64 bit word from `address`.
@@ -34,6 +37,14 @@ pub trait ArmProbe: SwdSequence {
         Ok(buff[0])
     }
 
+    /// Reads a 16 bit word from `address`.
+    fn read_word_16(&mut self, address: u64) -> Result<u16, ArmError> {
+        let mut buff = [0];
+        self.read_16(address, &mut buff)?;
+
+        Ok(buff[0])
+    }
+
     /// Reads an 8 bit word from `address`.
     fn read_word_8(&mut self, address: u64) -> Result<u8, ArmError> {
         let mut buff = [0];
@@ -45,26 +56,29 @@ pub trait ArmProbe: SwdSequence {
     /// Read a block of 8bit words at `address`. May use 32 bit memory access,
     /// so should only be used if reading memory locations that don't have side
     /// effects. Generally faster than [`MemoryInterface::read_8`].
+    ///
+    /// If `address` or the length of `data` are not word aligned, the surrounding
+    /// words are read.
     fn read(&mut self, address: u64, data: &mut [u8]) -> Result<(), ArmError> {
-        let len = data.len();
-        if address % 4 == 0 && len % 4 == 0 {
-            let mut buffer = vec![0u32; len / 4];
-            self.read_32(address, &mut buffer)?;
-            for (bytes, value) in data.chunks_exact_mut(4).zip(buffer.iter()) {
-                bytes.copy_from_slice(&u32::to_le_bytes(*value));
-            }
-        } else {
-            let start_extra_count = (address % 4) as usize;
-            let mut buffer = vec![0u32; (start_extra_count + len + 3) / 4];
-            let read_address = address - start_extra_count as u64;
-            self.read_32(read_address, &mut buffer)?;
-            for (bytes, value) in data
-                .chunks_exact_mut(4)
-                .zip(buffer[start_extra_count..start_extra_count + len].iter())
-            {
-                bytes.copy_from_slice(&u32::to_le_bytes(*value));
-            }
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        let range = aligned_range(address, data.len())?;
+
+        let mut buffer = vec![0u32; ((range.end - range.start) / 4) as usize];
+        self.read_32(range.start, &mut buffer)?;
+
+        let offset = (address - range.start) as usize;
+        for (byte, value) in data.iter_mut().zip(
+            buffer
+                .iter()
+                .flat_map(|word| word.to_le_bytes())
+                .skip(offset),
+        ) {
+            *byte = value;
         }
+
         Ok(())
     }
 
@@ -72,6 +86,8 @@ pub trait ArmProbe: SwdSequence {
 
     fn write_32(&mut self, address: u64, data: &[u32]) -> Result<(), ArmError>;
 
+    fn write_16(&mut self, address: u64, data: &[u16]) -> Result<(), ArmError>;
+
     fn write_64(&mut self, address: u64, data: &[u64]) -> Result<(), ArmError>;
 
     /// Writes a 64 bit word to `address`.
@@ -84,6 +100,11 @@ pub trait ArmProbe: SwdSequence {
         self.write_32(address, &[data])
     }
 
+    /// Writes a 16 bit word to `address`.
+    fn write_word_16(&mut self, address: u64, data: u16) -> Result<(), ArmError> {
+        self.write_16(address, &[data])
+    }
+
     /// Writes a 8 bit word to `address`.
     fn write_word_8(&mut self, address: u64, data: u8) -> Result<(), ArmError> {
         self.write_8(address, &[data])
@@ -92,37 +113,39 @@ pub trait ArmProbe: SwdSequence {
     /// Write a block of 8bit words to `address`. May use 32 bit memory access,
     /// so it should only be used if writing memory locations that don't have side
     /// effects. Generally faster than [`MemoryInterface::write_8`].
+    ///
+    /// The block is split into chunks, which are each written with the largest
+    /// aligned access size, so an unaligned block starts and ends with byte or
+    /// half word accesses.
     fn write(&mut self, address: u64, data: &[u8]) -> Result<(), ArmError> {
-        let len = data.len();
-        let start_extra_count = 4 - (address % 4) as usize;
-        let end_extra_count = (len - start_extra_count) % 4;
-        let inbetween_count = len - start_extra_count - end_extra_count;
-        assert!(start_extra_count < 4);
-        assert!(end_extra_count < 4);
-        assert!(inbetween_count % 4 == 0);
-
-        // If we do not have 32 bit aligned access we first check that we can do 8 bit aligned access on this platform.
-        // If we cannot we throw an error.
-        // If we can we read the first n < 4 bytes up until the word aligned address that comes next.
-        if address % 4 != 0 || len % 4 != 0 {
-            // If we do not support 8 bit transfers we have to bail because we can only do 32 bit word aligned transers.
-            if !self.supports_8bit_transfers()? {
-                return Err(ArmError::alignment_error(address, 4));
-            }
-
-            // We first do an 8 bit write of the first < 4 bytes up until the 4 byte aligned boundary.
-            self.write_8(address, &data[..start_extra_count])?;
-        }
+        let chunks = split_into_chunks(address, data.len(), 4);
 
-        let mut buffer = vec![0u32; inbetween_count / 4];
-        for (bytes, value) in data.chunks_exact(4).zip(buffer.iter_mut()) {
-            *value = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        // Accesses smaller than a word are only possible if the AP supports 8 bit transfers.
+        if chunks.iter().any(|chunk| chunk.size < 4) && !self.supports_8bit_transfers()? {
+            return Err(ArmError::alignment_error(address, 4));
         }
-        self.write_32(address, &buffer)?;
 
-        // We read the remaining bytes that we did not read yet which is always n < 4.
-        if end_extra_count > 0 {
-            self.write_8(address, &data[..start_extra_count])?;
+        for chunk in chunks {
+            let offset = (chunk.address - address) as usize;
+            let bytes = &data[offset..offset + chunk.len];
+
+            match chunk.size {
+                4 => {
+                    let words = bytes
+                        .chunks_exact(4)
+                        .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+                        .collect::<Vec<_>>();
+                    self.write_32(chunk.address, &words)?
+                }
+                2 => {
+                    let half_words = bytes
+                        .chunks_exact(2)
+                        .map(|half_word| u16::from_le_bytes([half_word[0], half_word[1]]))
+                        .collect::<Vec<_>>();
+                    self.write_16(chunk.address, &half_words)?
+                }
+                _ => self.write_8(chunk.address, bytes)?,
+            }
         }
 
         Ok(())
@@ -323,7 +346,7 @@ where
     /// Returns `ArmError::MemoryNotAligned` if this does not hold true.
     pub fn read_word_64(&mut self, access_port: MemoryAp, address: u64) -> Result<u64, ArmError> {
         if (address % 8) != 0 {
-            return Err(ArmError::alignment_error(address, 4));
+            return Err(ArmError::alignment_error(address, 8));
         }
 
         if !self.ap_information.has_large_data_extension {
@@ -386,6 +409,73 @@ where
         Ok(((result.data >> bit_offset) & 0xFF) as u8)
     }
 
+    /// Read a 16 bit word at `address`.
+    ///
+    /// The address where the read should be performed at has to be half-word aligned.
+    /// Returns `ArmError::MemoryNotAligned` if this does not hold true.
+    pub fn read_word_16(&mut self, access_port: MemoryAp, address: u64) -> Result<u16, ArmError> {
+        if self.ap_information.supports_only_32bit_data_size {
+            return Err(ArmError::UnsupportedTransferWidth(16));
+        }
+
+        if (address & 0x1) != 0 {
+            return Err(ArmError::alignment_error(address, 2));
+        }
+
+        // Offset of the half word in the word (little endian)
+        let bit_offset = (address & 0x2) * 8;
+
+        let csw = self.build_csw_register(DataSize::U16);
+        self.write_csw_register(access_port, csw)?;
+        self.write_tar_register(access_port, address)?;
+        let result: DRW = self.read_ap_register(access_port)?;
+
+        // Extract the correct half word
+        // See "Arm Debug Interface Architecture Specification ADIv5.0 to ADIv5.2", C2.2.6
+        Ok(((result.data >> bit_offset) & 0xFFFF) as u16)
+    }
+
+    /// Read a block of 64 bit words at `address`.
+    ///
+    /// The number of words read is `data.len()`.
+    /// The address where the read should be performed at has to be double-word aligned.
+    /// Returns `ArmError::MemoryNotAligned` if this does not hold true.
+    ///
+    /// Without the large data extension, the block is read with 32 bit accesses.
+    pub fn read_64(
+        &mut self,
+        access_port: MemoryAp,
+        address: u64,
+        data: &mut [u64],
+    ) -> Result<(), ArmError> {
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        if (address & 0x7) != 0 {
+            return Err(ArmError::alignment_error(address, 8));
+        }
+
+        let mut words = vec![0u32; data.len() * 2];
+
+        if self.ap_information.has_large_data_extension {
+            // Each 64 bit access consists of two DRW accesses, the TAR
+            // is incremented after the second one.
+            let csw = self.build_csw_register(DataSize::U64);
+            self.write_csw_register(access_port, csw)?;
+
+            self.read_drw_auto_increment(access_port, address, 4, &mut words)?;
+        } else {
+            self.read_32(access_port, address, &mut words)?;
+        }
+
+        for (value, words) in data.iter_mut().zip(words.chunks_exact(2)) {
+            *value = (words[0] as u64) | ((words[1] as u64) << 32);
+        }
+
+        Ok(())
+    }
+
     /// Read a block of 32 bit words at `address`.
     ///
     /// The number of words read is `data.len()`.
@@ -405,71 +495,10 @@ where
             return Err(ArmError::alignment_error(address, 4));
         }
 
-        // Second we read in 32 bit reads until we have less than 32 bits left to read.
         let csw = self.build_csw_register(DataSize::U32);
         self.write_csw_register(access_port, csw)?;
-        self.write_tar_register(access_port, address)?;
-
-        // The maximum chunk size we can read before data overflows.
-        // This is the size of the internal counter that is used for the address increment in the ARM spec.
-        let max_chunk_size_bytes = 0x400;
-
-        let mut remaining_data_len = data.len();
-
-        let first_chunk_size_bytes = std::cmp::min(
-            max_chunk_size_bytes - (address as usize % max_chunk_size_bytes),
-            data.len() * 4,
-        );
-
-        let mut data_offset = 0;
-
-        tracing::debug!(
-            "Read first block with len {} at address {:#08x}",
-            first_chunk_size_bytes,
-            address
-        );
-
-        let first_chunk_size_transfer_unit = first_chunk_size_bytes / 4;
-
-        self.read_ap_register_repeated(
-            access_port,
-            DRW { data: 0 },
-            &mut data[data_offset..first_chunk_size_transfer_unit],
-        )?;
-
-        remaining_data_len -= first_chunk_size_transfer_unit;
-        let mut address = address
-            .checked_add((4 * first_chunk_size_transfer_unit) as u64)
-            .ok_or(ArmError::OutOfBounds)?;
-        data_offset += first_chunk_size_transfer_unit;
-
-        while remaining_data_len > 0 {
-            // the autoincrement is limited to the 10 lowest bits so we need to write the address
-            // every time it overflows
-            self.write_tar_register(access_port, address)?;
-
-            let next_chunk_size_bytes = std::cmp::min(max_chunk_size_bytes, remaining_data_len * 4);
-
-            tracing::debug!(
-                "Reading chunk with len {} at address {:#08x}",
-                next_chunk_size_bytes,
-                address
-            );
-
-            let next_chunk_size_transfer_unit = next_chunk_size_bytes / 4;
-
-            self.read_ap_register_repeated(
-                access_port,
-                DRW { data: 0 },
-                &mut data[data_offset..(data_offset + next_chunk_size_transfer_unit)],
-            )?;
 
-            remaining_data_len -= next_chunk_size_transfer_unit;
-            address = address
-                .checked_add((4 * next_chunk_size_transfer_unit) as u64)
-                .ok_or(ArmError::OutOfBounds)?;
-            data_offset += next_chunk_size_transfer_unit;
-        }
+        self.read_drw_auto_increment(access_port, address, 4, data)?;
 
         tracing::debug!("Finished reading block");
 
@@ -478,7 +507,8 @@ where
 
     /// Read a block of 8 bit words at `address`.
     ///
-    /// The number of words read is `data.len()`.
+    /// The number of words read is `data.len()`. If the AP supports packed transfers,
+    /// the word aligned part of the block is read with four bytes per DRW access.
     pub fn read_8(
         &mut self,
         access_port: MemoryAp,
@@ -489,85 +519,115 @@ where
             return Err(ArmError::UnsupportedTransferWidth(8));
         }
 
-        if data.is_empty() {
-            return Ok(());
+        if self.ap_information.supports_packed_transfers {
+            if let Some(packed) = packed_range(address, data.len()) {
+                let head = (packed.start - address) as usize;
+                let tail = head + (packed.end - packed.start) as usize;
+
+                self.read_8_unpacked(access_port, address, &mut data[..head])?;
+                self.read_packed(
+                    access_port,
+                    DataSize::U8,
+                    packed.start,
+                    &mut data[head..tail],
+                )?;
+                return self.read_8_unpacked(access_port, packed.end, &mut data[tail..]);
+            }
         }
 
-        let start_address = address;
-        let mut data_u32 = vec![0u32; data.len()];
-
-        let csw = self.build_csw_register(DataSize::U8);
-        self.write_csw_register(access_port, csw)?;
+        self.read_8_unpacked(access_port, address, data)
+    }
 
-        let mut address = address;
-        self.write_tar_register(access_port, address)?;
+    /// Read a block of 16 bit words at `address`.
+    ///
+    /// The number of words read is `data.len()`.
+    /// The address where the read should be performed at has to be half-word aligned.
+    /// Returns `ArmError::MemoryNotAligned` if this does not hold true.
+    pub fn read_16(
+        &mut self,
+        access_port: MemoryAp,
+        address: u64,
+        data: &mut [u16],
+    ) -> Result<(), ArmError> {
+        if self.ap_information.supports_only_32bit_data_size {
+            return Err(ArmError::UnsupportedTransferWidth(16));
+        }
 
-        // The maximum chunk size we can read before data overflows.
-        // This is the size of the internal counter that is used for the address increment in the ARM spec.
-        let max_chunk_size_bytes = 0x400;
+        if (address & 0x1) != 0 {
+            return Err(ArmError::alignment_error(address, 2));
+        }
 
-        let mut remaining_data_len = data.len();
+        if self.ap_information.supports_packed_transfers {
+            if let Some(packed) = packed_range(address, data.len() * 2) {
+                let head = ((packed.start - address) / 2) as usize;
+                let tail = head + ((packed.end - packed.start) / 2) as usize;
 
-        let first_chunk_size_bytes = std::cmp::min(
-            max_chunk_size_bytes - (address as usize % max_chunk_size_bytes),
-            data.len(),
-        );
+                self.read_16_unpacked(access_port, address, &mut data[..head])?;
 
-        let mut data_offset = 0;
+                let mut bytes = vec![0u8; (tail - head) * 2];
+                self.read_packed(access_port, DataSize::U16, packed.start, &mut bytes)?;
+                for (value, bytes) in data[head..tail].iter_mut().zip(bytes.chunks_exact(2)) {
+                    *value = u16::from_le_bytes([bytes[0], bytes[1]]);
+                }
 
-        tracing::debug!(
-            "Read first block with len {} at address {:#08x}",
-            first_chunk_size_bytes,
-            address
-        );
+                return self.read_16_unpacked(access_port, packed.end, &mut data[tail..]);
+            }
+        }
 
-        let first_chunk_size_transfer_unit = first_chunk_size_bytes;
+        self.read_16_unpacked(access_port, address, data)
+    }
 
-        self.read_ap_register_repeated(
-            access_port,
-            DRW { data: 0 },
-            &mut data_u32[data_offset..first_chunk_size_transfer_unit],
-        )?;
+    /// Read a block of 16 bit words at `address`, using one DRW access per half word.
+    fn read_16_unpacked(
+        &mut self,
+        access_port: MemoryAp,
+        address: u64,
+        data: &mut [u16],
+    ) -> Result<(), ArmError> {
+        if data.is_empty() {
+            return Ok(());
+        }
 
-        remaining_data_len -= first_chunk_size_transfer_unit;
-        address = address
-            .checked_add((first_chunk_size_transfer_unit) as u64)
-            .ok_or(ArmError::OutOfBounds)?;
-        data_offset += first_chunk_size_transfer_unit;
+        let mut data_u32 = vec![0u32; data.len()];
 
-        while remaining_data_len > 0 {
-            // The autoincrement is limited to the 10 lowest bits so we need to write the address
-            // every time it overflows.
-            self.write_tar_register(access_port, address)?;
+        let csw = self.build_csw_register(DataSize::U16);
+        self.write_csw_register(access_port, csw)?;
 
-            let next_chunk_size_bytes = std::cmp::min(max_chunk_size_bytes, remaining_data_len);
+        self.read_drw_auto_increment(access_port, address, 2, &mut data_u32)?;
 
-            tracing::debug!(
-                "Reading chunk with len {} at address {:#08x}",
-                next_chunk_size_bytes,
-                address
-            );
+        // Half words are transfered in their byte lanes as well, see C2.2.6.
+        for (target, (i, source)) in data.iter_mut().zip(data_u32.iter().enumerate()) {
+            *target = ((*source >> (((address + 2 * i as u64) & 0x2) * 8)) & 0xFFFF) as u16;
+        }
 
-            let next_chunk_size_transfer_unit = next_chunk_size_bytes;
+        tracing::debug!("Finished reading block");
 
-            self.read_ap_register_repeated(
-                access_port,
-                DRW { data: 0 },
-                &mut data_u32[data_offset..(data_offset + next_chunk_size_transfer_unit)],
-            )?;
+        Ok(())
+    }
 
-            remaining_data_len -= next_chunk_size_transfer_unit;
-            address = address
-                .checked_add((next_chunk_size_transfer_unit) as u64)
-                .ok_or(ArmError::OutOfBounds)?;
-            data_offset += next_chunk_size_transfer_unit;
+    /// Read a block of 8 bit words at `address`, using one DRW access per byte.
+    fn read_8_unpacked(
+        &mut self,
+        access_port: MemoryAp,
+        address: u64,
+        data: &mut [u8],
+    ) -> Result<(), ArmError> {
+        if data.is_empty() {
+            return Ok(());
         }
 
+        let mut data_u32 = vec![0u32; data.len()];
+
+        let csw = self.build_csw_register(DataSize::U8);
+        self.write_csw_register(access_port, csw)?;
+
+        self.read_drw_auto_increment(access_port, address, 1, &mut data_u32)?;
+
         // The required shifting logic here is described in C2.2.6 Byte lanes of the ADI v5.2 specification.
         // All bytes are transfered in their lane, so when we do an access at an address that is not divisible by 4,
         // we have to shift the word (one or two bytes) to it's correct position.
         for (target, (i, source)) in data.iter_mut().zip(data_u32.iter().enumerate()) {
-            *target = ((*source >> (((start_address + i as u64) % 4) * 8)) & 0xFF) as u8;
+            *target = ((*source >> (((address + i as u64) % 4) * 8)) & 0xFF) as u8;
         }
 
         tracing::debug!("Finished reading block");
@@ -575,6 +635,36 @@ where
         Ok(())
     }
 
+    /// Read a word aligned block of bytes at `address` with packed transfers of `data_size`.
+    ///
+    /// Each DRW access performs multiple accesses of `data_size` on the bus, and returns
+    /// the data of all of them in their byte lanes (see C2.2.7).
+    fn read_packed(
+        &mut self,
+        access_port: MemoryAp,
+        data_size: DataSize,
+        address: u64,
+        data: &mut [u8],
+    ) -> Result<(), ArmError> {
+        debug_assert!(address & 0x3 == 0 && data.len() & 0x3 == 0);
+
+        let mut data_u32 = vec![0u32; data.len() / 4];
+
+        let mut csw = self.build_csw_register(data_size);
+        csw.AddrInc = AddressIncrement::Packed;
+        self.write_csw_register(access_port, csw)?;
+
+        self.read_drw_auto_increment(access_port, address, 4, &mut data_u32)?;
+
+        for (bytes, value) in data.chunks_exact_mut(4).zip(data_u32.iter()) {
+            bytes.copy_from_slice(&value.to_le_bytes());
+        }
+
+        tracing::debug!("Finished reading packed block");
+
+        Ok(())
+    }
+
     /// Write a 64bit word at `addr`.
     ///
     /// The address where the write should be performed at has to be word aligned.
@@ -586,7 +676,7 @@ where
         data: u64,
     ) -> Result<(), ArmError> {
         if (address % 8) != 0 {
-            return Err(ArmError::alignment_error(address, 4));
+            return Err(ArmError::alignment_error(address, 8));
         }
 
         let low_word = data as u32;
@@ -663,6 +753,74 @@ where
         Ok(())
     }
 
+    /// Write a 16 bit word at `address`.
+    ///
+    /// The address where the write should be performed at has to be half-word aligned.
+    /// Returns `ArmError::MemoryNotAligned` if this does not hold true.
+    pub fn write_word_16(
+        &mut self,
+        access_port: MemoryAp,
+        address: u64,
+        data: u16,
+    ) -> Result<(), ArmError> {
+        if self.ap_information.supports_only_32bit_data_size {
+            return Err(ArmError::UnsupportedTransferWidth(16));
+        }
+
+        if (address & 0x1) != 0 {
+            return Err(ArmError::alignment_error(address, 2));
+        }
+
+        // Offset of the half word in the word (little endian)
+        let bit_offset = (address & 0x2) * 8;
+
+        let csw = self.build_csw_register(DataSize::U16);
+        let drw = DRW {
+            data: u32::from(data) << bit_offset,
+        };
+        self.write_csw_register(access_port, csw)?;
+        self.write_tar_register(access_port, address)?;
+        self.write_ap_register(access_port, drw)?;
+
+        Ok(())
+    }
+
+    /// Write a block of 64 bit words at `address`.
+    ///
+    /// The number of words written is `data.len()`.
+    /// The address where the write should be performed at has to be double-word aligned.
+    /// Returns `ArmError::MemoryNotAligned` if this does not hold true.
+    ///
+    /// Without the large data extension, the block is written with 32 bit accesses.
+    pub fn write_64(
+        &mut self,
+        access_port: MemoryAp,
+        address: u64,
+        data: &[u64],
+    ) -> Result<(), ArmError> {
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        if (address & 0x7) != 0 {
+            return Err(ArmError::alignment_error(address, 8));
+        }
+
+        let words = data
+            .iter()
+            .flat_map(|value| [*value as u32, (*value >> 32) as u32])
+            .collect::<Vec<_>>();
+
+        if self.ap_information.has_large_data_extension {
+            let csw = self.build_csw_register(DataSize::U64);
+            self.write_csw_register(access_port, csw)?;
+
+            self.write_drw_auto_increment(access_port, address, 4, &words)
+        } else {
+            self.write_32(access_port, address, &words)
+        }
+    }
+
     /// Write a block of 32 bit words at `address`.
     ///
     /// The number of words written is `data.len()`.
@@ -688,91 +846,124 @@ where
             address
         );
 
-        // Second we write in 32 bit reads until we have less than 32 bits left to write.
         let csw = self.build_csw_register(DataSize::U32);
-
         self.write_csw_register(access_port, csw)?;
 
-        self.write_tar_register(access_port, address)?;
+        self.write_drw_auto_increment(access_port, address, 4, data)?;
 
-        // maximum chunk size
-        let max_chunk_size_bytes = 0x400_usize;
+        tracing::debug!("Finished writing block");
 
-        let mut remaining_data_len = data.len();
+        Ok(())
+    }
 
-        let first_chunk_size_bytes = std::cmp::min(
-            max_chunk_size_bytes - (address as usize % max_chunk_size_bytes),
-            data.len() * 4,
-        );
+    /// Write a block of 8 bit words at `address`.
+    ///
+    /// The number of words written is `data.len()`. If the AP supports packed transfers,
+    /// the word aligned part of the block is written with four bytes per DRW access.
+    pub fn write_8(
+        &mut self,
+        access_port: MemoryAp,
+        address: u64,
+        data: &[u8],
+    ) -> Result<(), ArmError> {
+        if self.ap_information.supports_only_32bit_data_size {
+            return Err(ArmError::UnsupportedTransferWidth(8));
+        }
 
-        let mut data_offset = 0;
+        if self.ap_information.supports_packed_transfers {
+            if let Some(packed) = packed_range(address, data.len()) {
+                let head = (packed.start - address) as usize;
+                let tail = head + (packed.end - packed.start) as usize;
 
-        tracing::debug!(
-            "Write first block with len {} at address {:#08x}",
-            first_chunk_size_bytes,
-            address
-        );
+                self.write_8_unpacked(access_port, address, &data[..head])?;
+                self.write_packed(access_port, DataSize::U8, packed.start, &data[head..tail])?;
+                return self.write_8_unpacked(access_port, packed.end, &data[tail..]);
+            }
+        }
 
-        let first_chunk_size_transfer_unit = first_chunk_size_bytes / 4;
+        self.write_8_unpacked(access_port, address, data)
+    }
 
-        self.write_ap_register_repeated(
-            access_port,
-            DRW { data: 0 },
-            &data[data_offset..first_chunk_size_transfer_unit],
-        )?;
+    /// Write a block of 16 bit words at `address`.
+    ///
+    /// The number of words written is `data.len()`.
+    /// The address where the write should be performed at has to be half-word aligned.
+    /// Returns `ArmError::MemoryNotAligned` if this does not hold true.
+    pub fn write_16(
+        &mut self,
+        access_port: MemoryAp,
+        address: u64,
+        data: &[u16],
+    ) -> Result<(), ArmError> {
+        if self.ap_information.supports_only_32bit_data_size {
+            return Err(ArmError::UnsupportedTransferWidth(16));
+        }
 
-        remaining_data_len -= first_chunk_size_transfer_unit;
-        let mut address = address
-            .checked_add((first_chunk_size_transfer_unit * 4) as u64)
-            .ok_or(ArmError::OutOfBounds)?;
-        data_offset += first_chunk_size_transfer_unit;
+        if (address & 0x1) != 0 {
+            return Err(ArmError::alignment_error(address, 2));
+        }
 
-        while remaining_data_len > 0 {
-            // the autoincrement is limited to the 10 lowest bits so we need to write the address
-            // every time it overflows
-            self.write_tar_register(access_port, address)?;
+        if self.ap_information.supports_packed_transfers {
+            if let Some(packed) = packed_range(address, data.len() * 2) {
+                let head = ((packed.start - address) / 2) as usize;
+                let tail = head + ((packed.end - packed.start) / 2) as usize;
 
-            let next_chunk_size_bytes = std::cmp::min(max_chunk_size_bytes, remaining_data_len * 4);
+                self.write_16_unpacked(access_port, address, &data[..head])?;
 
-            tracing::debug!(
-                "Writing chunk with len {} at address {:#08x}",
-                next_chunk_size_bytes,
-                address
-            );
+                let bytes: Vec<u8> = data[head..tail]
+                    .iter()
+                    .flat_map(|value| value.to_le_bytes())
+                    .collect();
+                self.write_packed(access_port, DataSize::U16, packed.start, &bytes)?;
 
-            let next_chunk_size_transfer_unit = next_chunk_size_bytes / 4;
+                return self.write_16_unpacked(access_port, packed.end, &data[tail..]);
+            }
+        }
 
-            self.write_ap_register_repeated(
-                access_port,
-                DRW { data: 0 },
-                &data[data_offset..(data_offset + next_chunk_size_transfer_unit)],
-            )?;
+        self.write_16_unpacked(access_port, address, data)
+    }
 
-            remaining_data_len -= next_chunk_size_transfer_unit;
-            address = address
-                .checked_add((next_chunk_size_transfer_unit * 4) as u64)
-                .ok_or(ArmError::OutOfBounds)?;
-            data_offset += next_chunk_size_transfer_unit;
+    /// Write a block of 16 bit words at `address`, using one DRW access per half word.
+    fn write_16_unpacked(
+        &mut self,
+        access_port: MemoryAp,
+        address: u64,
+        data: &[u16],
+    ) -> Result<(), ArmError> {
+        if data.is_empty() {
+            return Ok(());
         }
 
+        // Half words are transfered in their byte lanes as well, see C2.2.6.
+        let data = data
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (*v as u32) << (((address + 2 * i as u64) & 0x2) * 8))
+            .collect::<Vec<_>>();
+
+        tracing::debug!(
+            "Write block with total size {} bytes to address {:#08x}",
+            data.len() * 2,
+            address
+        );
+
+        let csw = self.build_csw_register(DataSize::U16);
+        self.write_csw_register(access_port, csw)?;
+
+        self.write_drw_auto_increment(access_port, address, 2, &data)?;
+
         tracing::debug!("Finished writing block");
 
         Ok(())
     }
 
-    /// Write a block of 8 bit words at `address`.
-    ///
-    /// The number of words written is `data.len()`.
-    pub fn write_8(
+    /// Write a block of 8 bit words at `address`, using one DRW access per byte.
+    fn write_8_unpacked(
         &mut self,
         access_port: MemoryAp,
         address: u64,
         data: &[u8],
     ) -> Result<(), ArmError> {
-        if self.ap_information.supports_only_32bit_data_size {
-            return Err(ArmError::UnsupportedTransferWidth(8));
-        }
-
         if data.is_empty() {
             return Ok(());
         }
@@ -792,77 +983,123 @@ where
             address
         );
 
-        // Second we write in 8 bit writes until we have less than 8 bits left to write.
         let csw = self.build_csw_register(DataSize::U8);
-
         self.write_csw_register(access_port, csw)?;
-        self.write_tar_register(access_port, address)?;
 
-        // figure out how many words we can write before the
-        // data overflows
+        self.write_drw_auto_increment(access_port, address, 1, &data)?;
 
-        // maximum chunk size
-        let max_chunk_size_bytes = 0x400_usize;
+        tracing::debug!("Finished writing block");
 
-        let mut remaining_data_len = data.len();
+        Ok(())
+    }
 
-        let first_chunk_size_bytes = std::cmp::min(
-            max_chunk_size_bytes - (address as usize % max_chunk_size_bytes),
-            data.len(),
-        );
+    /// Write a word aligned block of bytes to `address` with packed transfers of `data_size`.
+    fn write_packed(
+        &mut self,
+        access_port: MemoryAp,
+        data_size: DataSize,
+        address: u64,
+        data: &[u8],
+    ) -> Result<(), ArmError> {
+        debug_assert!(address & 0x3 == 0 && data.len() & 0x3 == 0);
 
-        let mut data_offset = 0;
+        let data = data
+            .chunks_exact(4)
+            .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect::<Vec<_>>();
 
         tracing::debug!(
-            "Write first block with len {} at address {:#08x}",
-            first_chunk_size_bytes,
+            "Write packed block with total size {} bytes to address {:#08x}",
+            data.len() * 4,
             address
         );
 
-        let first_chunk_size_transfer_unit = first_chunk_size_bytes;
+        let mut csw = self.build_csw_register(data_size);
+        csw.AddrInc = AddressIncrement::Packed;
+        self.write_csw_register(access_port, csw)?;
+
+        self.write_drw_auto_increment(access_port, address, 4, &data)?;
 
-        self.write_ap_register_repeated(
-            access_port,
-            DRW { data: 0 },
-            &data[data_offset..first_chunk_size_transfer_unit],
-        )?;
+        tracing::debug!("Finished writing packed block");
 
-        remaining_data_len -= first_chunk_size_transfer_unit;
-        let mut address = address
-            .checked_add((first_chunk_size_transfer_unit) as u64)
-            .ok_or(ArmError::OutOfBounds)?;
-        data_offset += first_chunk_size_transfer_unit;
+        Ok(())
+    }
+
+    /// Read `data.len()` values from the DRW, starting at `address`.
+    ///
+    /// The TAR is incremented by `bytes_per_transfer` after each access. Because the
+    /// auto-increment is only guaranteed for the lowest 10 bits of the TAR, the transfer
+    /// is split at each 1KB boundary and the TAR is rewritten for every chunk.
+    fn read_drw_auto_increment(
+        &mut self,
+        access_port: MemoryAp,
+        mut address: u64,
+        bytes_per_transfer: usize,
+        data: &mut [u32],
+    ) -> Result<(), ArmError> {
+        let mut data_offset = 0;
+
+        while data_offset < data.len() {
+            let chunk_len =
+                auto_increment_chunk_len(address, bytes_per_transfer, data.len() - data_offset);
+
+            tracing::debug!(
+                "Reading chunk with len {} at address {:#08x}",
+                chunk_len * bytes_per_transfer,
+                address
+            );
 
-        while remaining_data_len > 0 {
-            // the autoincrement is limited to the 10 lowest bits so we need to write the address
-            // every time it overflows
             self.write_tar_register(access_port, address)?;
+            self.read_ap_register_repeated(
+                access_port,
+                DRW { data: 0 },
+                &mut data[data_offset..data_offset + chunk_len],
+            )?;
+
+            address = address
+                .checked_add((chunk_len * bytes_per_transfer) as u64)
+                .ok_or(ArmError::OutOfBounds)?;
+            data_offset += chunk_len;
+        }
+
+        Ok(())
+    }
+
+    /// Write `data` to the DRW, starting at `address`.
+    ///
+    /// See [`Self::read_drw_auto_increment`] for the handling of the TAR.
+    fn write_drw_auto_increment(
+        &mut self,
+        access_port: MemoryAp,
+        mut address: u64,
+        bytes_per_transfer: usize,
+        data: &[u32],
+    ) -> Result<(), ArmError> {
+        let mut data_offset = 0;
 
-            let next_chunk_size_bytes = std::cmp::min(max_chunk_size_bytes, remaining_data_len);
+        while data_offset < data.len() {
+            let chunk_len =
+                auto_increment_chunk_len(address, bytes_per_transfer, data.len() - data_offset);
 
             tracing::debug!(
                 "Writing chunk with len {} at address {:#08x}",
-                next_chunk_size_bytes,
+                chunk_len * bytes_per_transfer,
                 address
             );
 
-            let next_chunk_size_transfer_unit = next_chunk_size_bytes;
-
+            self.write_tar_register(access_port, address)?;
             self.write_ap_register_repeated(
                 access_port,
                 DRW { data: 0 },
-                &data[data_offset..(data_offset + next_chunk_size_transfer_unit)],
+                &data[data_offset..data_offset + chunk_len],
             )?;
 
-            remaining_data_len -= next_chunk_size_transfer_unit;
             address = address
-                .checked_add((next_chunk_size_transfer_unit) as u64)
+                .checked_add((chunk_len * bytes_per_transfer) as u64)
                 .ok_or(ArmError::OutOfBounds)?;
-            data_offset += next_chunk_size_transfer_unit;
+            data_offset += chunk_len;
         }
 
-        tracing::debug!("Finished writing block");
-
         Ok(())
     }
 }
@@ -915,9 +1152,21 @@ where
         Ok(())
     }
 
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), ArmError> {
+        if data.len() == 1 {
+            data[0] = self.read_word_16(self.memory_ap, address)?;
+        } else {
+            self.read_16(self.memory_ap, address, data)?;
+        }
+
+        Ok(())
+    }
+
     fn read_64(&mut self, address: u64, data: &mut [u64]) -> Result<(), ArmError> {
-        for (i, d) in data.iter_mut().enumerate() {
-            *d = self.read_word_64(self.memory_ap, address + (i as u64 * 8))?;
+        if data.len() == 1 {
+            data[0] = self.read_word_64(self.memory_ap, address)?;
+        } else {
+            self.read_64(self.memory_ap, address, data)?;
         }
 
         Ok(())
@@ -943,9 +1192,21 @@ where
         Ok(())
     }
 
+    fn write_16(&mut self, address: u64, data: &[u16]) -> Result<(), ArmError> {
+        if data.len() == 1 {
+            self.write_word_16(self.memory_ap, address, data[0])?;
+        } else {
+            self.write_16(self.memory_ap, address, data)?;
+        }
+
+        Ok(())
+    }
+
     fn write_64(&mut self, address: u64, data: &[u64]) -> Result<(), ArmError> {
-        for (i, d) in data.iter().enumerate() {
-            self.write_word_64(self.memory_ap, address + (i as u64 * 8), *d)?;
+        if data.len() == 1 {
+            self.write_word_64(self.memory_ap, address, data[0])?;
+        } else {
+            self.write_64(self.memory_ap, address, data)?;
         }
 
         Ok(())
@@ -992,6 +1253,30 @@ fn aligned_range(address: u64, len: usize) -> Result<Range<u64>, ArmError> {
     Ok(Range { start, end })
 }
 
+/// The TAR auto-increment is only guaranteed to work for the lowest 10 bits
+/// of the address (see C2.2.2), so sequential accesses must not cross a 1KB boundary.
+const AUTO_INCREMENT_BOUNDARY: u64 = 0x400;
+
+/// Returns the number of transfers of `bytes_per_transfer` that can be performed,
+/// starting at `address`, before the next auto-increment boundary is reached.
+fn auto_increment_chunk_len(address: u64, bytes_per_transfer: usize, remaining: usize) -> usize {
+    let bytes_to_boundary = AUTO_INCREMENT_BOUNDARY - (address & (AUTO_INCREMENT_BOUNDARY - 1));
+
+    std::cmp::min(
+        (bytes_to_boundary as usize / bytes_per_transfer).max(1),
+        remaining,
+    )
+}
+
+/// Returns the word aligned part of the block of `len` bytes starting at `address`,
+/// which can be transferred with packed transfers, or `None` if there is none.
+fn packed_range(address: u64, len: usize) -> Option<Range<u64>> {
+    let start = address.checked_add(3)? & !0x3;
+    let end = address.checked_add(len as u64)? & !0x3;
+
+    (start < end).then_some(start..end)
+}
+
 #[cfg(test)]
 mod tests {
     use scroll::Pread;
@@ -1000,7 +1285,7 @@ mod tests {
 
     use super::super::super::ap::memory_ap::mock::MockMemoryAp;
     use super::super::super::ap::memory_ap::MemoryAp;
-    use super::ADIMemoryInterface;
+    use super::{ADIMemoryInterface, ArmError, ArmProbe};
 
     const DUMMY_AP: MemoryAp = MemoryAp::new(ApAddress {
         dp: DpAddress::Default,
@@ -1014,7 +1299,9 @@ mod tests {
         ) -> ADIMemoryInterface<'interface, MockMemoryAp> {
             let ap_information = MemoryApInformation {
                 address: DUMMY_AP.ap_address(),
+                idr: Default::default(),
                 supports_only_32bit_data_size: false,
+                supports_packed_transfers: false,
                 supports_hnonsec: false,
                 debug_base_address: 0xf000_0000,
                 has_large_address_extension: false,
@@ -1232,7 +1519,229 @@ mod tests {
         }
     }
 
-    use super::aligned_range;
+    #[test]
+    fn read_8_packed() {
+        let mut mock = MockMemoryAp::with_pattern();
+        mock.memory[..DATA8.len()].copy_from_slice(DATA8);
+        let mut mi = ADIMemoryInterface::new_mock(&mut mock);
+        mi.ap_information.supports_packed_transfers = true;
+
+        for address in 0..4 {
+            for len in 0..12 {
+                let mut data = vec![0u8; len];
+                mi.read_8(DUMMY_AP, address, &mut data)
+                    .unwrap_or_else(|_| panic!("read_8 failed, address = {address}, len = {len}"));
+
+                assert_eq!(
+                    data.as_slice(),
+                    &DATA8[address as usize..address as usize + len],
+                    "address = {address}, len = {len}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn write_8_packed() {
+        for address in 0..4 {
+            for len in 0..12 {
+                let mut mock = MockMemoryAp::with_pattern();
+                let mut mi = ADIMemoryInterface::new_mock(&mut mock);
+                mi.ap_information.supports_packed_transfers = true;
+
+                let mut expected = Vec::from(mi.mock_memory());
+                expected[address as usize..(address as usize) + len].copy_from_slice(&DATA8[..len]);
+
+                let data = &DATA8[..len];
+                mi.write_8(DUMMY_AP, address, data)
+                    .unwrap_or_else(|_| panic!("write_8 failed, address = {address}, len = {len}"));
+
+                assert_eq!(
+                    mi.mock_memory(),
+                    expected.as_slice(),
+                    "address = {address}, len = {len}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn read_unaligned_block() {
+        let mut mock = MockMemoryAp::with_pattern();
+        mock.memory[..DATA8.len()].copy_from_slice(DATA8);
+        let mut mi = ADIMemoryInterface::new_mock(&mut mock);
+
+        for address in 0..4 {
+            for len in 0..12 {
+                let mut data = vec![0u8; len];
+                ArmProbe::read(&mut mi, address, &mut data)
+                    .unwrap_or_else(|_| panic!("read failed, address = {address}, len = {len}"));
+
+                assert_eq!(
+                    data.as_slice(),
+                    &DATA8[address as usize..address as usize + len],
+                    "address = {address}, len = {len}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn write_unaligned_block() {
+        for address in 0..4 {
+            for len in 0..12 {
+                let mut mock = MockMemoryAp::with_pattern();
+                let mut mi = ADIMemoryInterface::new_mock(&mut mock);
+
+                let mut expected = Vec::from(mi.mock_memory());
+                expected[address as usize..(address as usize) + len].copy_from_slice(&DATA8[..len]);
+
+                ArmProbe::write(&mut mi, address, &DATA8[..len])
+                    .unwrap_or_else(|_| panic!("write failed, address = {address}, len = {len}"));
+
+                assert_eq!(
+                    mi.mock_memory(),
+                    expected.as_slice(),
+                    "address = {address}, len = {len}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn write_unaligned_block_without_8bit_transfers() {
+        let mut mock = MockMemoryAp::with_pattern();
+        let mut mi = ADIMemoryInterface::new_mock(&mut mock);
+        mi.ap_information.supports_only_32bit_data_size = true;
+
+        ArmProbe::write(&mut mi, 0, &DATA8[..8]).unwrap();
+        assert_eq!(&mi.mock_memory()[..8], &DATA8[..8]);
+
+        assert!(matches!(
+            ArmProbe::write(&mut mi, 1, &DATA8[..8]),
+            Err(ArmError::MemoryNotAligned { .. })
+        ));
+    }
+
+    // DATA8 interpreted as little endian 16-bit words
+    const DATA16: &[u16] = &[
+        0x8180, 0x8382, 0x8584, 0x8786, 0x8988, 0x8b8a, 0x8d8c, 0x8f8e,
+    ];
+
+    #[test]
+    fn read_word_16() {
+        let mut mock = MockMemoryAp::with_pattern();
+        mock.memory[..8].copy_from_slice(&DATA8[..8]);
+        let mut mi = ADIMemoryInterface::new_mock(&mut mock);
+
+        for address in [0, 2, 4, 6] {
+            let value = mi
+                .read_word_16(DUMMY_AP, address)
+                .unwrap_or_else(|_| panic!("read_word_16 failed, address = {address}"));
+            assert_eq!(value, DATA16[address as usize / 2], "address = {address}");
+        }
+
+        assert!(mi.read_word_16(DUMMY_AP, 1).is_err());
+    }
+
+    #[test]
+    fn read_16() {
+        for packed in [false, true] {
+            let mut mock = MockMemoryAp::with_pattern();
+            mock.memory[..DATA8.len()].copy_from_slice(DATA8);
+            let mut mi = ADIMemoryInterface::new_mock(&mut mock);
+            mi.ap_information.supports_packed_transfers = packed;
+
+            for address in [0, 2] {
+                for len in 0..6 {
+                    let mut data = vec![0u16; len];
+                    mi.read_16(DUMMY_AP, address, &mut data)
+                        .unwrap_or_else(|_| {
+                            panic!("read_16 failed, address = {address}, len = {len}")
+                        });
+
+                    assert_eq!(
+                        data.as_slice(),
+                        &DATA16[address as usize / 2..address as usize / 2 + len],
+                        "address = {address}, len = {len}, packed = {packed}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn write_16() {
+        for packed in [false, true] {
+            for address in [0, 2] {
+                for len in 0..6 {
+                    let mut mock = MockMemoryAp::with_pattern();
+                    let mut mi = ADIMemoryInterface::new_mock(&mut mock);
+                    mi.ap_information.supports_packed_transfers = packed;
+
+                    let mut expected = Vec::from(mi.mock_memory());
+                    expected[address as usize..(address as usize) + len * 2]
+                        .copy_from_slice(&DATA8[..len * 2]);
+
+                    mi.write_16(DUMMY_AP, address, &DATA16[..len])
+                        .unwrap_or_else(|_| {
+                            panic!("write_16 failed, address = {address}, len = {len}")
+                        });
+
+                    assert_eq!(
+                        mi.mock_memory(),
+                        expected.as_slice(),
+                        "address = {address}, len = {len}, packed = {packed}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn read_64_without_large_data_extension() {
+        let mut mock = MockMemoryAp::with_pattern();
+        mock.memory[..DATA8.len()].copy_from_slice(DATA8);
+        let mut mi = ADIMemoryInterface::new_mock(&mut mock);
+
+        let mut data = [0u64; 2];
+        mi.read_64(DUMMY_AP, 0, &mut data).expect("read_64 failed");
+
+        assert_eq!(data, [0x8786858483828180, 0x8f8e8d8c8b8a8988]);
+        assert!(mi.read_64(DUMMY_AP, 4, &mut data).is_err());
+    }
+
+    #[test]
+    fn write_64_without_large_data_extension() {
+        let mut mock = MockMemoryAp::with_pattern();
+        let mut mi = ADIMemoryInterface::new_mock(&mut mock);
+
+        let mut expected = Vec::from(mi.mock_memory());
+        expected[8..24].copy_from_slice(DATA8);
+
+        mi.write_64(DUMMY_AP, 8, &[0x8786858483828180, 0x8f8e8d8c8b8a8988])
+            .expect("write_64 failed");
+
+        assert_eq!(mi.mock_memory(), expected.as_slice());
+    }
+
+    use super::{aligned_range, auto_increment_chunk_len, packed_range};
+
+    #[test]
+    fn packed_range_is_word_aligned() {
+        assert_eq!(packed_range(0x1001, 10), Some(0x1004..0x1008));
+        assert_eq!(packed_range(0x1000, 8), Some(0x1000..0x1008));
+        assert_eq!(packed_range(0x1001, 6), None);
+        assert_eq!(packed_range(0x1000, 3), None);
+    }
+
+    #[test]
+    fn auto_increment_chunks_stop_at_1kb_boundary() {
+        assert_eq!(auto_increment_chunk_len(0x2000, 4, 1000), 256);
+        assert_eq!(auto_increment_chunk_len(0x23f8, 4, 1000), 2);
+        assert_eq!(auto_increment_chunk_len(0x23fd, 1, 1000), 3);
+        assert_eq!(auto_increment_chunk_len(0x2000, 1, 10), 10);
+    }
 
     #[test]
     fn aligned_range_at_limit_does_not_panic() {
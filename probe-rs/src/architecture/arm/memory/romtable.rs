@@ -5,6 +5,9 @@ use crate::architecture::arm::{ap::MemoryAp, communication_interface::ArmProbeIn
 use enum_primitive_derive::Primitive;
 use num_traits::cast::FromPrimitive;
 
+/// The DEVARCH.ARCHID of a Class 0x9 ROM table.
+const CLASS9_ROM_TABLE_ARCH_ID: u16 = 0x0AF7;
+
 /// An error to report any errors that are romtable discovery specific.
 #[derive(thiserror::Error, Debug)]
 pub enum RomTableError {
@@ -35,14 +38,20 @@ impl RomTableError {
 /// A lazy romtable reader that is used to create an iterator over all romtable entries.
 struct RomTableReader<'probe: 'memory, 'memory> {
     base_address: u64,
+    format: RomTableFormat,
     memory: &'memory mut (dyn ArmProbe + 'probe),
 }
 
 /// Iterates over a ROM table non recursively.
 impl<'probe: 'memory, 'memory> RomTableReader<'probe, 'memory> {
-    fn new(memory: &'memory mut (dyn ArmProbe + 'probe), base_address: u64) -> Self {
+    fn new(
+        memory: &'memory mut (dyn ArmProbe + 'probe),
+        base_address: u64,
+        format: RomTableFormat,
+    ) -> Self {
         RomTableReader {
             base_address,
+            format,
             memory,
         }
     }
@@ -75,36 +84,81 @@ impl<'probe, 'memory, 'reader> Iterator for RomTableIterator<'probe, 'memory, 'r
     type Item = Result<RomTableEntryRaw, RomTableError>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        let format = self.rom_table_reader.format;
+
+        if self.offset >= format.entries_size() {
+            tracing::info!("Reached the end of the rom table entries, stopping.");
+            return None;
+        }
+
         let component_address = self.rom_table_reader.base_address + self.offset;
         tracing::info!("Reading rom table entry at {:08x}", component_address);
 
-        self.offset += 4;
+        self.offset += format.entry_size();
 
-        let mut entry_data = [0u32; 1];
+        let mut entry_data = [0u32; 2];
+        let entry_data = &mut entry_data[..(format.entry_size() / 4) as usize];
 
         if let Err(e) = self
             .rom_table_reader
             .memory
-            .read_32(component_address, &mut entry_data)
+            .read_32(component_address, entry_data)
         {
             return Some(Err(RomTableError::memory(e)));
         }
 
+        let raw = match entry_data {
+            // 32 bit entries contain a signed offset, so they are sign extended.
+            [low] => *low as i32 as i64 as u64,
+            [low, high] => u64::from(*low) | (u64::from(*high) << 32),
+            _ => unreachable!(),
+        };
+
         // End of entries is marked by an all zero entry
-        if entry_data[0] == 0 {
+        if raw == 0 {
             tracing::info!("Entry consists of all zeroes, stopping.");
             return None;
         }
 
-        let entry_data =
-            RomTableEntryRaw::new(self.rom_table_reader.base_address as u32, entry_data[0]);
+        let entry_data = RomTableEntryRaw::new(self.rom_table_reader.base_address, raw);
 
         tracing::info!("ROM Table Entry: {:#x?}", entry_data);
         Some(Ok(entry_data))
     }
 }
 
-/// Encapsulates information about a CoreSight ROM table (class 1).
+/// The layout of the entries of a ROM table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RomTableFormat {
+    /// Class 0x1 ROM table, with 32 bit entries.
+    Class1,
+    /// Class 0x9 ROM table, with 32 bit entries.
+    Class9,
+    /// Class 0x9 ROM table, with 64 bit entries.
+    Class9Wide,
+}
+
+impl RomTableFormat {
+    /// The size of a single entry, in bytes.
+    fn entry_size(self) -> u64 {
+        match self {
+            RomTableFormat::Class1 | RomTableFormat::Class9 => 4,
+            RomTableFormat::Class9Wide => 8,
+        }
+    }
+
+    /// The size of the region containing the entries, in bytes.
+    ///
+    /// Described in sections D3.4 and D4.4 of the ADIv6.0 specification.
+    fn entries_size(self) -> u64 {
+        match self {
+            RomTableFormat::Class1 => 0xF00,
+            RomTableFormat::Class9 | RomTableFormat::Class9Wide => 0x800,
+        }
+    }
+}
+
+/// Encapsulates information about a CoreSight ROM table (class 0x1 or class 0x9).
 #[derive(Clone, Debug, PartialEq)]
 pub struct RomTable {
     /// ALL the entries in the romtable in flattened fashion.
@@ -117,7 +171,11 @@ impl RomTable {
     ///
     /// This does not check whether the data actually signalizes
     /// to contain a ROM table but assumes this was checked beforehand.
-    fn try_parse(memory: &mut dyn ArmProbe, base_address: u64) -> Result<RomTable, RomTableError> {
+    fn try_parse(
+        memory: &mut dyn ArmProbe,
+        base_address: u64,
+        format: RomTableFormat,
+    ) -> Result<RomTable, RomTableError> {
         // This is required for the collect down below.
         let mut entries = vec![];
 
@@ -125,7 +183,7 @@ impl RomTable {
 
         // Read all the raw romtable entries and flatten them.
 
-        let reader = RomTableReader::new(memory, base_address)
+        let reader = RomTableReader::new(memory, base_address, format)
             .entries()
             .filter_map(Result::ok)
             // This is not a needless collect! It fixes the borrowing issue with &mut Memory that clippy cannot detect!
@@ -138,7 +196,19 @@ impl RomTable {
             tracing::info!("Parsing entry at {:x?}", entry_base_addr);
 
             if raw_entry.entry_present {
-                let component = Component::try_parse(memory, u64::from(entry_base_addr))?;
+                // A single component which cannot be read, e.g. because it is powered down,
+                // should not prevent the discovery of the remaining components.
+                let component = match Component::try_parse(memory, entry_base_addr) {
+                    Ok(component) => component,
+                    Err(e) => {
+                        tracing::warn!(
+                            "Failed to parse component at {:#x}, skipping it: {}",
+                            entry_base_addr,
+                            e
+                        );
+                        continue;
+                    }
+                };
 
                 // Finally remember the entry.
                 entries.push(RomTableEntry {
@@ -169,8 +239,8 @@ impl RomTable {
 #[derive(Debug, PartialEq)]
 struct RomTableEntryRaw {
     /// The offset from the BASEADDR at which the CoreSight component
-    /// behind this ROM table entry is located.
-    address_offset: i32,
+    /// behind this ROM table entry is located, in units of 4 kB.
+    address_offset: i64,
     /// The power domain ID of the CoreSight component behind the ROM table entry.
     power_domain_id: u8,
     /// The power domain is valid if this is true.
@@ -182,15 +252,17 @@ struct RomTableEntryRaw {
     /// Indicates whether the ROM table behind the address offset is present.
     pub entry_present: bool,
     // Base address of the rom table
-    base_address: u32,
+    base_address: u64,
 }
 
 impl RomTableEntryRaw {
     /// Create a new RomTableEntryRaw from raw ROM table entry data in memory.
-    fn new(base_address: u32, raw: u32) -> Self {
+    ///
+    /// 32 bit entries have to be sign extended to 64 bit.
+    fn new(base_address: u64, raw: u64) -> Self {
         tracing::debug!("Parsing raw rom table entry: 0x{:05x}", raw);
 
-        let address_offset = ((raw >> 12) & 0xf_ff_ff) as i32;
+        let address_offset = (raw as i64) >> 12;
         let power_domain_id = ((raw >> 4) & 0xf) as u8;
         let power_domain_valid = (raw & 4) == 4;
         let format = (raw & 2) == 2;
@@ -207,8 +279,9 @@ impl RomTableEntryRaw {
     }
 
     /// Returns the address of the CoreSight component behind a ROM table entry.
-    pub fn component_address(&self) -> u32 {
-        (i64::from(self.base_address) + (i64::from(self.address_offset << 12))) as u32
+    pub fn component_address(&self) -> u64 {
+        self.base_address
+            .wrapping_add((self.address_offset << 12) as u64)
     }
 }
 
@@ -364,6 +437,24 @@ impl<'probe: 'memory, 'memory> ComponentInformationReader<'probe, 'memory> {
         Ok(PeripheralID::from_raw(&data, dev_type, arch_id))
     }
 
+    /// Reads the format of a Class 0x9 ROM table from the DEVID register.
+    ///
+    /// This function does a direct memory access and is meant for internal use only.
+    fn class9_rom_table_format(&mut self) -> Result<RomTableFormat, RomTableError> {
+        const DEVID_OFFSET: u64 = 0xFC8;
+        const DEVID_FORMAT_MASK: u32 = 0xF;
+
+        let devid = self
+            .memory
+            .read_word_32(self.base_address + DEVID_OFFSET)
+            .map_err(RomTableError::memory)?;
+
+        Ok(match devid & DEVID_FORMAT_MASK {
+            1 => RomTableFormat::Class9Wide,
+            _ => RomTableFormat::Class9,
+        })
+    }
+
     /// Reads all component properties from a component info table
     ///
     /// This function does a direct memory access and is meant for internal use only.
@@ -404,8 +495,11 @@ pub enum Component {
     Class1RomTable(ComponentId, RomTable),
     /// CoreSight component. For general information about CoreSight components, see the CoreSight Architecture Specification.
 
-    /// A CoreSight component can be a Class 0x9 ROM Table, which can be identified from the DEVARCH.ARCHID having the value 0x0AF7. See also _ROM Table Types on page D2-237_. For detailed information about Class 0x9 ROM Tables, see _Chapter D4 Class 0x9 ROM Tables_.
+    /// Class 0x9 ROM Tables are returned as [`Component::Class9RomTable`] instead.
     CoresightComponent(ComponentId),
+    /// Class 0x9 ROM Table, which is a CoreSight component identified by DEVARCH.ARCHID having the value 0x0AF7.
+    /// For detailed information about Class 0x9 ROM Tables, see _Chapter D4 Class 0x9 ROM Tables_.
+    Class9RomTable(ComponentId, RomTable),
     /// Peripheral Test Block.
     PeripheralTestBlock(ComponentId),
     /// Generic IP component.
@@ -442,10 +536,24 @@ impl Component {
                 Component::GenericVerificationComponent(component_id)
             }
             RawComponent::RomTable => {
-                let rom_table = RomTable::try_parse(memory, component_id.component_address)?;
+                let rom_table = RomTable::try_parse(
+                    memory,
+                    component_id.component_address,
+                    RomTableFormat::Class1,
+                )?;
 
                 Component::Class1RomTable(component_id, rom_table)
             }
+            RawComponent::CoreSightComponent
+                if component_id.peripheral_id.arch_id == CLASS9_ROM_TABLE_ARCH_ID =>
+            {
+                let format =
+                    ComponentInformationReader::new(baseaddr, memory).class9_rom_table_format()?;
+                let rom_table =
+                    RomTable::try_parse(memory, component_id.component_address, format)?;
+
+                Component::Class9RomTable(component_id, rom_table)
+            }
             RawComponent::CoreSightComponent => Component::CoresightComponent(component_id),
             RawComponent::PeripheralTestBlock => Component::PeripheralTestBlock(component_id),
             RawComponent::GenericIPComponent => Component::GenericIPComponent(component_id),
@@ -463,6 +571,7 @@ impl Component {
             Component::GenericVerificationComponent(component_id) => component_id,
             Component::Class1RomTable(component_id, ..) => component_id,
             Component::CoresightComponent(component_id, ..) => component_id,
+            Component::Class9RomTable(component_id, ..) => component_id,
             Component::PeripheralTestBlock(component_id) => component_id,
             Component::GenericIPComponent(component_id) => component_id,
             Component::CoreLinkOrPrimeCellOrSystemComponent(component_id) => component_id,
@@ -519,6 +628,20 @@ impl CoresightComponent {
         })
     }
 
+    /// Returns the type of the component, if it is a known component.
+    pub fn peripheral_type(&self) -> Option<PeripheralType> {
+        match &self.component {
+            Component::Class1RomTable(..) | Component::Class9RomTable(..) => {
+                Some(PeripheralType::Rom)
+            }
+            component => component
+                .id()
+                .peripheral_id
+                .determine_part()
+                .map(|info| info.peripheral_type()),
+        }
+    }
+
     /// Turns this component into a component iterator which iterates all its children recursively.
     pub fn iter(&self) -> CoresightComponentIter {
         CoresightComponentIter::new(vec![self])
@@ -570,9 +693,11 @@ impl<'a> Iterator for CoresightComponentIter<'a> {
         if let Some(component) = self.components.get(self.current) {
             // If it has children, remember to iterate them next.
             self.children = match &component.component {
-                Component::Class1RomTable(_, v) => Some(Box::new(CoresightComponentIter::new(
-                    v.entries.iter().map(|v| &v.component).collect(),
-                ))),
+                Component::Class1RomTable(_, v) | Component::Class9RomTable(_, v) => {
+                    Some(Box::new(CoresightComponentIter::new(
+                        v.entries.iter().map(|v| &v.component).collect(),
+                    )))
+                }
                 _ => None,
             };
             // Advance the pointer by one.
@@ -727,6 +852,15 @@ impl PeripheralID {
             ("ARM Ltd", 0xD21, 0x11, 0x0000) => Some(PartInfo::new("Cortex-M33 TPIU", PeripheralType::Tpiu)),
             ("ARM Ltd", 0xD21, 0x14, 0x1A14) => Some(PartInfo::new("Cortex-M33 CTI", PeripheralType::Cti)),
             ("ARM Ltd", 0x9A3, 0x13, 0x0000) => Some(PartInfo::new("Cortex-M0 MTB", PeripheralType::Mtb)),
+            // Unknown parts which implement an architecture identified by DEVARCH.
+            (_, _, _, 0x0AF7) => Some(PartInfo::new("CoreSight ROM Table", PeripheralType::Rom)),
+            (_, _, _, 0x1A01) => Some(PartInfo::new("ITM", PeripheralType::Itm)),
+            (_, _, _, 0x1A02) => Some(PartInfo::new("DWT", PeripheralType::Dwt)),
+            (_, _, _, 0x1A03) => Some(PartInfo::new("BPU", PeripheralType::Bpu)),
+            (_, _, _, 0x1A14) => Some(PartInfo::new("CTI", PeripheralType::Cti)),
+            (_, _, _, 0x2A04) => Some(PartInfo::new("SCS", PeripheralType::Scs)),
+            (_, _, _, 0x0A63) => Some(PartInfo::new("STM", PeripheralType::Stm)),
+            (_, _, _, 0x4A13) => Some(PartInfo::new("ETMv4", PeripheralType::Etm)),
             _ => None,
         }
     }
@@ -827,3 +961,192 @@ impl std::fmt::Display for PeripheralType {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::{Component, CoresightComponent, PeripheralType};
+    use crate::{
+        architecture::arm::{
+            ap::MemoryAp,
+            communication_interface::{Initialized, SwdSequence},
+            memory::adi_v5_memory_interface::ArmProbe,
+            ApAddress, ArmCommunicationInterface, ArmError, DpAddress,
+        },
+        probe::DebugProbeError,
+        CoreStatus,
+    };
+
+    const CIDR1_ROM_TABLE: u32 = 0x10;
+    const CIDR1_CORESIGHT: u32 = 0x90;
+
+    const DEVARCH_ROM_TABLE: u32 = 0x4770_0AF7;
+    const DEVARCH_ITM: u32 = 0x4770_1A01;
+    const DEVARCH_CTI: u32 = 0x4770_1A14;
+
+    /// Memory which returns zero for all addresses which were not written.
+    #[derive(Default)]
+    struct MockMemory(HashMap<u64, u32>);
+
+    impl MockMemory {
+        fn component(&mut self, base: u64, cidr1: u32, devarch: u32) {
+            self.0.insert(base + 0xFBC, devarch);
+            self.0.insert(base + 0xFF4, cidr1);
+        }
+    }
+
+    impl SwdSequence for MockMemory {
+        fn swj_sequence(&mut self, _bit_len: u8, _bits: u64) -> Result<(), DebugProbeError> {
+            unimplemented!()
+        }
+
+        fn swj_pins(
+            &mut self,
+            _pin_out: u32,
+            _pin_select: u32,
+            _pin_wait: u32,
+        ) -> Result<u32, DebugProbeError> {
+            unimplemented!()
+        }
+    }
+
+    impl ArmProbe for MockMemory {
+        fn read_8(&mut self, _address: u64, _data: &mut [u8]) -> Result<(), ArmError> {
+            unimplemented!()
+        }
+
+        fn read_16(&mut self, _address: u64, _data: &mut [u16]) -> Result<(), ArmError> {
+            unimplemented!()
+        }
+
+        fn read_32(&mut self, address: u64, data: &mut [u32]) -> Result<(), ArmError> {
+            for (i, word) in data.iter_mut().enumerate() {
+                *word = self.0.get(&(address + 4 * i as u64)).copied().unwrap_or(0);
+            }
+
+            Ok(())
+        }
+
+        fn read_64(&mut self, _address: u64, _data: &mut [u64]) -> Result<(), ArmError> {
+            unimplemented!()
+        }
+
+        fn write_8(&mut self, _address: u64, _data: &[u8]) -> Result<(), ArmError> {
+            unimplemented!()
+        }
+
+        fn write_16(&mut self, _address: u64, _data: &[u16]) -> Result<(), ArmError> {
+            unimplemented!()
+        }
+
+        fn write_32(&mut self, _address: u64, _data: &[u32]) -> Result<(), ArmError> {
+            unimplemented!()
+        }
+
+        fn write_64(&mut self, _address: u64, _data: &[u64]) -> Result<(), ArmError> {
+            unimplemented!()
+        }
+
+        fn flush(&mut self) -> Result<(), ArmError> {
+            Ok(())
+        }
+
+        fn supports_native_64bit_access(&mut self) -> bool {
+            false
+        }
+
+        fn supports_8bit_transfers(&self) -> Result<bool, ArmError> {
+            Ok(false)
+        }
+
+        fn ap(&mut self) -> MemoryAp {
+            MemoryAp::new(ApAddress {
+                dp: DpAddress::Default,
+                ap: 0,
+            })
+        }
+
+        fn get_arm_communication_interface(
+            &mut self,
+        ) -> Result<&mut ArmCommunicationInterface<Initialized>, DebugProbeError> {
+            Err(DebugProbeError::NotImplemented(
+                "get_arm_communication_interface",
+            ))
+        }
+
+        fn update_core_status(&mut self, _state: CoreStatus) {}
+    }
+
+    #[test]
+    fn nested_class9_and_class1_rom_tables() {
+        let mut memory = MockMemory::default();
+
+        // Class 0x9 ROM table, with an ITM and a nested Class 0x1 ROM table.
+        memory.component(0xE00F_F000, CIDR1_CORESIGHT, DEVARCH_ROM_TABLE);
+        memory.0.insert(0xE00F_F000, 0x0000_1003);
+        memory.0.insert(0xE00F_F004, 0xFFFF_F003);
+
+        memory.component(0xE010_0000, CIDR1_CORESIGHT, DEVARCH_ITM);
+
+        // Class 0x1 ROM table, with an entry which is not present, and a CTI.
+        memory.component(0xE00F_E000, CIDR1_ROM_TABLE, 0);
+        memory.0.insert(0xE00F_E000, 0x0000_2002);
+        memory.0.insert(0xE00F_E004, 0x0000_3003);
+
+        memory.component(0xE010_1000, CIDR1_CORESIGHT, DEVARCH_CTI);
+
+        let ap = memory.ap();
+        let component = Component::try_parse(&mut memory, 0xE00F_F000).unwrap();
+        assert!(matches!(component, Component::Class9RomTable(..)));
+
+        let root = CoresightComponent::new(component, ap);
+
+        let found: Vec<_> = root
+            .iter()
+            .map(|c| (c.component.id().component_address(), c.peripheral_type()))
+            .collect();
+
+        assert_eq!(
+            found,
+            vec![
+                (0xE00F_F000, Some(PeripheralType::Rom)),
+                (0xE010_0000, Some(PeripheralType::Itm)),
+                (0xE00F_E000, Some(PeripheralType::Rom)),
+                (0xE010_1000, Some(PeripheralType::Cti)),
+            ]
+        );
+
+        assert_eq!(
+            root.find_component(PeripheralType::Cti)
+                .map(|c| c.component.id().component_address()),
+            Some(0xE010_1000)
+        );
+    }
+
+    #[test]
+    fn wide_class9_rom_table_entries() {
+        let mut memory = MockMemory::default();
+
+        memory.component(0x1000, CIDR1_CORESIGHT, DEVARCH_ROM_TABLE);
+        // DEVID.FORMAT indicates 64 bit entries.
+        memory.0.insert(0x1FC8, 0x1);
+        memory.0.insert(0x1000, 0x0000_0003);
+        memory.0.insert(0x1004, 0x0000_0001);
+
+        memory.component(0x1_0000_1000, CIDR1_CORESIGHT, DEVARCH_ITM);
+
+        let component = Component::try_parse(&mut memory, 0x1000).unwrap();
+
+        let Component::Class9RomTable(_, table) = component else {
+            panic!("Expected a Class 0x9 ROM table, got {component:?}");
+        };
+
+        let addresses: Vec<_> = table
+            .entries()
+            .map(|entry| entry.component().id().component_address())
+            .collect();
+
+        assert_eq!(addresses, vec![0x1_0000_1000]);
+    }
+}
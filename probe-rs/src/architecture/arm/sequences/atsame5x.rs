@@ -6,7 +6,7 @@ use crate::{
         self,
         arm::{
             ap::MemoryAp, memory::adi_v5_memory_interface::ArmProbe, ApAddress, ArmError,
-            ArmProbeInterface, DpAddress,
+            ArmProbeInterface, DpAddress, RawDapAccess,
         },
     },
     session::MissingPermissions,
@@ -183,7 +183,7 @@ impl<'a> architecture::arm::communication_interface::SwdSequence for SwdSequence
         pin_select: u32,
         pin_wait: u32,
     ) -> Result<u32, DebugProbeError> {
-        self.0.swj_pins(pin_out, pin_select, pin_wait)
+        RawDapAccess::swj_pins(self.0, pin_out, pin_select, pin_wait)
     }
 }
 
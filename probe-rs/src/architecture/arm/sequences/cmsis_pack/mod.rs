@@ -0,0 +1,1228 @@
+//! Interpreter for the debug sequences of CMSIS-Packs.
+//!
+//! CMSIS-Packs describe device specific debug behaviour in the `<sequences>` element of their
+//! debug description, using a small C-like language. Sequences contained in a target description
+//! replace the built-in debug sequence with the same name, all other sequences are handled by
+//! the Rust implementation for the target.
+//!
+//! See the [CMSIS-Pack documentation] for the specification.
+//!
+//! [CMSIS-Pack documentation]: https://open-cmsis-pack.github.io/Open-CMSIS-Pack-Spec/main/html/debug_description.html#sequences
+
+mod parser;
+
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use probe_rs_target::{CoreType, SequenceDescription, SequenceElement};
+
+use self::parser::{BinaryOp, Expr, Statement, UnaryOp};
+use super::{ArmDebugSequence, ArmDebugSequenceError, DebugEraseSequence};
+use crate::{
+    architecture::arm::{
+        ap::{AccessPort, MemoryAp},
+        communication_interface::{DapProbe, Initialized},
+        component::TraceSink,
+        dp::{Select, DPIDR},
+        memory::{adi_v5_memory_interface::ArmProbe, romtable::CoresightComponent},
+        ApAddress, ArmCommunicationInterface, ArmError, ArmProbeInterface, DpAddress, PortType,
+        RawDapAccess, Register,
+    },
+    DebugProbeError, WireProtocol,
+};
+
+/// Maximum nesting depth of sequences calling other sequences.
+const MAX_SEQUENCE_DEPTH: usize = 16;
+
+/// `__errorcontrol` bit to ignore errors of the debug access functions.
+const ERROR_CONTROL_IGNORE: u64 = 1 << 0;
+
+/// An error which occurred while executing a CMSIS-Pack debug sequence.
+#[derive(thiserror::Error, Debug)]
+pub enum SequenceError {
+    /// The code of the sequence could not be parsed.
+    #[error("Syntax error: {0}")]
+    Syntax(String),
+    /// A variable was used before it was declared.
+    #[error("The variable '{0}' is not declared")]
+    UnknownVariable(String),
+    /// A function which is not supported was called.
+    #[error("The function '{0}' is not supported")]
+    UnknownFunction(String),
+    /// A function was called with the wrong arguments.
+    #[error("Invalid arguments for the function '{0}'")]
+    InvalidArguments(String),
+    /// A function which is not available in the current debug sequence was called.
+    #[error("The function '{0}' is not available in this debug sequence")]
+    Unavailable(&'static str),
+    /// A sequence which does not exist was called.
+    #[error("The sequence '{0}' does not exist")]
+    UnknownSequence(String),
+    /// Sequences calling each other are nested too deep.
+    #[error("Sequences are nested deeper than {MAX_SEQUENCE_DEPTH} levels")]
+    NestingTooDeep,
+    /// A division by zero occurred.
+    #[error("Division by zero")]
+    DivisionByZero,
+    /// A `while` loop did not finish within its timeout.
+    #[error("A loop did not finish within {0} µs")]
+    Timeout(u64),
+    /// The sequence reported an error with the `Message` function.
+    #[error("The sequence reported an error: {0}")]
+    Message(String),
+    /// A debug access function failed.
+    #[error("A debug access failed")]
+    Access(#[from] ArmError),
+}
+
+impl From<DebugProbeError> for SequenceError {
+    fn from(error: DebugProbeError) -> Self {
+        SequenceError::Access(error.into())
+    }
+}
+
+impl From<SequenceError> for ArmError {
+    fn from(error: SequenceError) -> Self {
+        match error {
+            SequenceError::Access(error) => error,
+            error => ArmDebugSequenceError::custom(error).into(),
+        }
+    }
+}
+
+/// The debug access functions a sequence can use.
+///
+/// Depending on the debug sequence, a different interface to the target is available,
+/// so not every function can be used in every sequence.
+trait DebugAccess {
+    /// The wire protocol which is used to access the target, if known.
+    fn protocol(&self) -> Option<WireProtocol> {
+        None
+    }
+
+    /// The AP used for memory accesses, if `__ap` is not changed by the sequence.
+    fn default_ap(&mut self) -> u8 {
+        0
+    }
+
+    fn read_memory(&mut self, ap: u8, address: u64, size: usize) -> Result<u64, SequenceError>;
+
+    fn write_memory(
+        &mut self,
+        ap: u8,
+        address: u64,
+        size: usize,
+        value: u64,
+    ) -> Result<(), SequenceError>;
+
+    fn read_dp(&mut self, address: u8) -> Result<u32, SequenceError>;
+
+    fn write_dp(&mut self, address: u8, value: u32) -> Result<(), SequenceError>;
+
+    fn read_ap(&mut self, ap: u8, address: u8) -> Result<u32, SequenceError>;
+
+    fn write_ap(&mut self, ap: u8, address: u8, value: u32) -> Result<(), SequenceError>;
+
+    fn swj_sequence(&mut self, bit_len: u8, bits: u64) -> Result<(), SequenceError>;
+
+    fn swj_pins(&mut self, out: u32, select: u32, wait: u32) -> Result<u32, SequenceError>;
+}
+
+fn read_sized(memory: &mut dyn ArmProbe, address: u64, size: usize) -> Result<u64, ArmError> {
+    Ok(match size {
+        1 => memory.read_word_8(address)? as u64,
+        4 => memory.read_word_32(address)? as u64,
+        8 => memory.read_word_64(address)?,
+        _ => {
+            let mut data = [0u8; 8];
+            memory.read_8(address, &mut data[..size])?;
+            u64::from_le_bytes(data)
+        }
+    })
+}
+
+fn write_sized(
+    memory: &mut dyn ArmProbe,
+    address: u64,
+    size: usize,
+    value: u64,
+) -> Result<(), ArmError> {
+    match size {
+        1 => memory.write_word_8(address, value as u8)?,
+        4 => memory.write_word_32(address, value as u32)?,
+        8 => memory.write_word_64(address, value)?,
+        _ => memory.write_8(address, &value.to_le_bytes()[..size])?,
+    }
+
+    memory.flush()
+}
+
+/// Debug access through a fully initialized ARM interface.
+struct InterfaceAccess<'a> {
+    interface: &'a mut dyn ArmProbeInterface,
+    dp: DpAddress,
+    ap: u8,
+}
+
+impl InterfaceAccess<'_> {
+    fn memory_ap(&self, ap: u8) -> MemoryAp {
+        MemoryAp::new(ApAddress { dp: self.dp, ap })
+    }
+}
+
+impl DebugAccess for InterfaceAccess<'_> {
+    fn default_ap(&mut self) -> u8 {
+        self.ap
+    }
+
+    fn read_memory(&mut self, ap: u8, address: u64, size: usize) -> Result<u64, SequenceError> {
+        let mut memory = self.interface.memory_interface(self.memory_ap(ap))?;
+        Ok(read_sized(&mut *memory, address, size)?)
+    }
+
+    fn write_memory(
+        &mut self,
+        ap: u8,
+        address: u64,
+        size: usize,
+        value: u64,
+    ) -> Result<(), SequenceError> {
+        let mut memory = self.interface.memory_interface(self.memory_ap(ap))?;
+        Ok(write_sized(&mut *memory, address, size, value)?)
+    }
+
+    fn read_dp(&mut self, address: u8) -> Result<u32, SequenceError> {
+        Ok(self.interface.read_raw_dp_register(self.dp, address)?)
+    }
+
+    fn write_dp(&mut self, address: u8, value: u32) -> Result<(), SequenceError> {
+        Ok(self
+            .interface
+            .write_raw_dp_register(self.dp, address, value)?)
+    }
+
+    fn read_ap(&mut self, ap: u8, address: u8) -> Result<u32, SequenceError> {
+        let ap = ApAddress { dp: self.dp, ap };
+        Ok(self.interface.read_raw_ap_register(ap, address)?)
+    }
+
+    fn write_ap(&mut self, ap: u8, address: u8, value: u32) -> Result<(), SequenceError> {
+        let ap = ApAddress { dp: self.dp, ap };
+        Ok(self.interface.write_raw_ap_register(ap, address, value)?)
+    }
+
+    fn swj_sequence(&mut self, bit_len: u8, bits: u64) -> Result<(), SequenceError> {
+        Ok(self.interface.swj_sequence(bit_len, bits)?)
+    }
+
+    fn swj_pins(&mut self, out: u32, select: u32, wait: u32) -> Result<u32, SequenceError> {
+        Ok(self.interface.swj_pins(out, select, wait)?)
+    }
+}
+
+/// Debug access through the memory interface of a single AP.
+struct MemoryAccess<'a> {
+    memory: &'a mut dyn ArmProbe,
+}
+
+impl MemoryAccess<'_> {
+    /// Other APs and the DP are accessed through the underlying communication interface.
+    fn interface(&mut self, ap: u8) -> Result<InterfaceAccess<'_>, SequenceError> {
+        let dp = self.memory.ap().ap_address().dp;
+
+        Ok(InterfaceAccess {
+            interface: self.memory.get_arm_communication_interface()?,
+            dp,
+            ap,
+        })
+    }
+
+    fn is_own_ap(&mut self, ap: u8) -> bool {
+        self.memory.ap().ap_address().ap == ap
+    }
+}
+
+impl DebugAccess for MemoryAccess<'_> {
+    fn default_ap(&mut self) -> u8 {
+        self.memory.ap().ap_address().ap
+    }
+
+    fn read_memory(&mut self, ap: u8, address: u64, size: usize) -> Result<u64, SequenceError> {
+        if self.is_own_ap(ap) {
+            Ok(read_sized(self.memory, address, size)?)
+        } else {
+            self.interface(ap)?.read_memory(ap, address, size)
+        }
+    }
+
+    fn write_memory(
+        &mut self,
+        ap: u8,
+        address: u64,
+        size: usize,
+        value: u64,
+    ) -> Result<(), SequenceError> {
+        if self.is_own_ap(ap) {
+            Ok(write_sized(self.memory, address, size, value)?)
+        } else {
+            self.interface(ap)?.write_memory(ap, address, size, value)
+        }
+    }
+
+    fn read_dp(&mut self, address: u8) -> Result<u32, SequenceError> {
+        self.interface(0)?.read_dp(address)
+    }
+
+    fn write_dp(&mut self, address: u8, value: u32) -> Result<(), SequenceError> {
+        self.interface(0)?.write_dp(address, value)
+    }
+
+    fn read_ap(&mut self, ap: u8, address: u8) -> Result<u32, SequenceError> {
+        self.interface(ap)?.read_ap(ap, address)
+    }
+
+    fn write_ap(&mut self, ap: u8, address: u8, value: u32) -> Result<(), SequenceError> {
+        self.interface(ap)?.write_ap(ap, address, value)
+    }
+
+    fn swj_sequence(&mut self, bit_len: u8, bits: u64) -> Result<(), SequenceError> {
+        Ok(self.memory.swj_sequence(bit_len, bits)?)
+    }
+
+    fn swj_pins(&mut self, out: u32, select: u32, wait: u32) -> Result<u32, SequenceError> {
+        Ok(self.memory.swj_pins(out, select, wait)?)
+    }
+}
+
+/// Raw debug access through the probe, before the debug port is initialized.
+struct ProbeAccess<'a> {
+    probe: &'a mut dyn DapProbe,
+}
+
+impl ProbeAccess<'_> {
+    /// Selects the AP and the register bank of `address` for raw AP accesses.
+    fn select_ap(&mut self, ap: u8, address: u8) -> Result<(), SequenceError> {
+        let mut select = Select(0);
+        select.set_ap_sel(ap);
+        select.set_ap_bank_sel(address >> 4);
+
+        Ok(self
+            .probe
+            .raw_write_register(PortType::DebugPort, Select::ADDRESS, select.into())?)
+    }
+}
+
+impl DebugAccess for ProbeAccess<'_> {
+    fn protocol(&self) -> Option<WireProtocol> {
+        self.probe.active_protocol()
+    }
+
+    fn read_memory(&mut self, _ap: u8, _address: u64, _size: usize) -> Result<u64, SequenceError> {
+        Err(SequenceError::Unavailable("Read"))
+    }
+
+    fn write_memory(
+        &mut self,
+        _ap: u8,
+        _address: u64,
+        _size: usize,
+        _value: u64,
+    ) -> Result<(), SequenceError> {
+        Err(SequenceError::Unavailable("Write"))
+    }
+
+    fn read_dp(&mut self, address: u8) -> Result<u32, SequenceError> {
+        Ok(self.probe.raw_read_register(PortType::DebugPort, address)?)
+    }
+
+    fn write_dp(&mut self, address: u8, value: u32) -> Result<(), SequenceError> {
+        Ok(self
+            .probe
+            .raw_write_register(PortType::DebugPort, address, value)?)
+    }
+
+    fn read_ap(&mut self, ap: u8, address: u8) -> Result<u32, SequenceError> {
+        self.select_ap(ap, address)?;
+        Ok(self
+            .probe
+            .raw_read_register(PortType::AccessPort, address & 0xF)?)
+    }
+
+    fn write_ap(&mut self, ap: u8, address: u8, value: u32) -> Result<(), SequenceError> {
+        self.select_ap(ap, address)?;
+        Ok(self
+            .probe
+            .raw_write_register(PortType::AccessPort, address & 0xF, value)?)
+    }
+
+    fn swj_sequence(&mut self, bit_len: u8, bits: u64) -> Result<(), SequenceError> {
+        Ok(RawDapAccess::swj_sequence(self.probe, bit_len, bits)?)
+    }
+
+    fn swj_pins(&mut self, out: u32, select: u32, wait: u32) -> Result<u32, SequenceError> {
+        Ok(RawDapAccess::swj_pins(self.probe, out, select, wait)?)
+    }
+}
+
+/// Executes debug sequences.
+struct Interpreter<'a> {
+    sequences: &'a [SequenceDescription],
+    access: &'a mut dyn DebugAccess,
+    /// The predefined debug access variables, like `__ap`.
+    globals: HashMap<String, u64>,
+    /// The variables declared in the currently executed sequences, innermost last.
+    scopes: Vec<HashMap<String, u64>>,
+}
+
+impl<'a> Interpreter<'a> {
+    fn new(sequences: &'a [SequenceDescription], access: &'a mut dyn DebugAccess) -> Self {
+        // Bits [15:0] contain the protocol, bit 16 is set for an SWJ-DP.
+        let protocol = match access.protocol() {
+            Some(WireProtocol::Jtag) => 0x1_0001,
+            _ => 0x1_0002,
+        };
+        let ap = access.default_ap() as u64;
+
+        let globals = [
+            ("__protocol", protocol),
+            // Connection for debugging.
+            ("__connection", 0x1),
+            ("__dp", 0),
+            ("__ap", ap),
+            ("__traceout", 0),
+            ("__errorcontrol", 0),
+            ("__Result", 0),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect();
+
+        Self {
+            sequences,
+            access,
+            globals,
+            scopes: Vec::new(),
+        }
+    }
+
+    fn run_sequence(&mut self, name: &str) -> Result<(), SequenceError> {
+        let sequences = self.sequences;
+        let sequence = sequences
+            .iter()
+            .find(|sequence| sequence.name == name)
+            .ok_or_else(|| SequenceError::UnknownSequence(name.to_string()))?;
+
+        if sequence.disable {
+            tracing::debug!("Skipping disabled debug sequence {}", name);
+            return Ok(());
+        }
+
+        if self.scopes.len() >= MAX_SEQUENCE_DEPTH {
+            return Err(SequenceError::NestingTooDeep);
+        }
+
+        tracing::debug!("Running debug sequence {}", name);
+
+        self.scopes.push(HashMap::new());
+        let result = self.execute_elements(&sequence.body);
+        self.scopes.pop();
+
+        result
+    }
+
+    fn execute_elements(&mut self, elements: &[SequenceElement]) -> Result<(), SequenceError> {
+        for element in elements {
+            match element {
+                SequenceElement::Block(block) => {
+                    for statement in parser::parse_statements(&block.code)? {
+                        self.execute_statement(&statement)?;
+                    }
+                }
+                SequenceElement::Control(control) => {
+                    if let Some(condition) = &control.condition {
+                        if self.evaluate_str(condition)? == 0 {
+                            continue;
+                        }
+                    }
+
+                    let Some(while_condition) = &control.while_condition else {
+                        self.execute_elements(&control.body)?;
+                        continue;
+                    };
+
+                    let while_condition = parser::parse_expression(while_condition)?;
+                    let start = Instant::now();
+
+                    while self.evaluate(&while_condition)? != 0 {
+                        self.execute_elements(&control.body)?;
+
+                        if control.timeout != 0
+                            && start.elapsed() > Duration::from_micros(control.timeout)
+                        {
+                            return Err(SequenceError::Timeout(control.timeout));
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn execute_statement(&mut self, statement: &Statement) -> Result<(), SequenceError> {
+        match statement {
+            Statement::Declare(name, value) => {
+                let value = self.evaluate(value)?;
+                // Variables are always declared in a sequence, see `run_sequence`.
+                self.scopes
+                    .last_mut()
+                    .unwrap()
+                    .insert(name.to_string(), value);
+            }
+            Statement::Assign(name, operator, value) => {
+                let mut value = self.evaluate(value)?;
+
+                if let Some(operator) = operator {
+                    value = binary(*operator, self.variable(name)?, value)?;
+                }
+
+                *self.variable_mut(name)? = value;
+            }
+            Statement::Expr(expression) => {
+                self.evaluate(expression)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn variable_mut(&mut self, name: &str) -> Result<&mut u64, SequenceError> {
+        let scope = self
+            .scopes
+            .last_mut()
+            .filter(|scope| scope.contains_key(name));
+
+        match scope {
+            Some(scope) => Ok(scope.get_mut(name).unwrap()),
+            None => self
+                .globals
+                .get_mut(name)
+                .ok_or_else(|| SequenceError::UnknownVariable(name.to_string())),
+        }
+    }
+
+    fn variable(&mut self, name: &str) -> Result<u64, SequenceError> {
+        self.variable_mut(name).map(|value| *value)
+    }
+
+    fn evaluate_str(&mut self, expression: &str) -> Result<u64, SequenceError> {
+        self.evaluate(&parser::parse_expression(expression)?)
+    }
+
+    fn evaluate(&mut self, expression: &Expr) -> Result<u64, SequenceError> {
+        Ok(match expression {
+            Expr::Number(value) => *value,
+            Expr::String(_) => {
+                return Err(SequenceError::Syntax(
+                    "strings are only allowed as function arguments".to_string(),
+                ))
+            }
+            Expr::Variable(name) => self.variable(name)?,
+            Expr::Unary(op, value) => {
+                let value = self.evaluate(value)?;
+                match op {
+                    UnaryOp::Not => (value == 0) as u64,
+                    UnaryOp::BitNot => !value,
+                    UnaryOp::Neg => value.wrapping_neg(),
+                }
+            }
+            // Logical operators only evaluate the right hand side when required.
+            Expr::Binary(BinaryOp::And, lhs, rhs) => {
+                (self.evaluate(lhs)? != 0 && self.evaluate(rhs)? != 0) as u64
+            }
+            Expr::Binary(BinaryOp::Or, lhs, rhs) => {
+                (self.evaluate(lhs)? != 0 || self.evaluate(rhs)? != 0) as u64
+            }
+            Expr::Binary(op, lhs, rhs) => {
+                let lhs = self.evaluate(lhs)?;
+                let rhs = self.evaluate(rhs)?;
+                binary(*op, lhs, rhs)?
+            }
+            Expr::Conditional(condition, if_true, if_false) => {
+                if self.evaluate(condition)? != 0 {
+                    self.evaluate(if_true)?
+                } else {
+                    self.evaluate(if_false)?
+                }
+            }
+            Expr::Call(name, arguments) => self.call(name, arguments)?,
+        })
+    }
+
+    fn arguments<const N: usize>(
+        &mut self,
+        name: &str,
+        arguments: &[Expr],
+    ) -> Result<[u64; N], SequenceError> {
+        if arguments.len() != N {
+            return Err(SequenceError::InvalidArguments(name.to_string()));
+        }
+
+        let mut values = [0; N];
+        for (value, argument) in values.iter_mut().zip(arguments) {
+            *value = self.evaluate(argument)?;
+        }
+
+        Ok(values)
+    }
+
+    /// Applies `__errorcontrol` to the result of a debug access function.
+    fn access_result<T: Default>(
+        &self,
+        result: Result<T, SequenceError>,
+    ) -> Result<T, SequenceError> {
+        match result {
+            Err(SequenceError::Access(error))
+                if self.globals["__errorcontrol"] & ERROR_CONTROL_IGNORE != 0 =>
+            {
+                tracing::debug!("Ignoring failed debug access: {}", error);
+                Ok(T::default())
+            }
+            result => result,
+        }
+    }
+
+    fn call(&mut self, name: &str, arguments: &[Expr]) -> Result<u64, SequenceError> {
+        let ap = self.globals["__ap"] as u8;
+
+        let memory_size = |prefix: &str| match name.strip_prefix(prefix) {
+            Some("8") => Some(1),
+            Some("16") => Some(2),
+            Some("32") => Some(4),
+            Some("64") => Some(8),
+            _ => None,
+        };
+
+        if let Some(size) = memory_size("Read") {
+            let [address] = self.arguments(name, arguments)?;
+            let result = self.access.read_memory(ap, address, size);
+            return self.access_result(result);
+        }
+
+        if let Some(size) = memory_size("Write") {
+            let [address, value] = self.arguments(name, arguments)?;
+            let result = self.access.write_memory(ap, address, size, value);
+            return self.access_result(result).map(|_| 0);
+        }
+
+        let result = match name {
+            "Sequence" => {
+                let [sequence] = string_arguments(name, arguments)?;
+                self.run_sequence(sequence)?;
+                0
+            }
+            "ReadDP" => {
+                let [address] = self.arguments(name, arguments)?;
+                let result = self.access.read_dp(address as u8);
+                self.access_result(result)? as u64
+            }
+            "WriteDP" => {
+                let [address, value] = self.arguments(name, arguments)?;
+                let result = self.access.write_dp(address as u8, value as u32);
+                self.access_result(result)?;
+                0
+            }
+            "ReadAP" => {
+                let [address] = self.arguments(name, arguments)?;
+                let result = self.access.read_ap(ap, address as u8);
+                self.access_result(result)? as u64
+            }
+            "WriteAP" => {
+                let [address, value] = self.arguments(name, arguments)?;
+                let result = self.access.write_ap(ap, address as u8, value as u32);
+                self.access_result(result)?;
+                0
+            }
+            "DAP_WriteABORT" => {
+                let [value] = self.arguments(name, arguments)?;
+                let result = self.access.write_dp(0x0, value as u32);
+                self.access_result(result)?;
+                0
+            }
+            "DAP_Delay" => {
+                let [delay] = self.arguments(name, arguments)?;
+                thread::sleep(Duration::from_micros(delay));
+                0
+            }
+            "DAP_SWJ_Pins" => {
+                let [out, select, wait] = self.arguments(name, arguments)?;
+                let result = self.access.swj_pins(out as u32, select as u32, wait as u32);
+                self.access_result(result)? as u64
+            }
+            "DAP_SWJ_Sequence" => {
+                let [bit_len, bits] = self.arguments(name, arguments)?;
+                let result = self.access.swj_sequence(bit_len as u8, bits);
+                self.access_result(result)?;
+                0
+            }
+            "DAP_SWJ_Clock" => {
+                let [frequency] = self.arguments(name, arguments)?;
+                tracing::debug!("Ignoring request to set the SWJ clock to {} Hz", frequency);
+                0
+            }
+            "DAP_JTAG_Sequence" => return Err(SequenceError::Unavailable("DAP_JTAG_Sequence")),
+            // There is no user interaction, so the default answer is used.
+            "Query" | "QueryValue" => {
+                let (message, default) = match arguments {
+                    [_, Expr::String(message), default] | [Expr::String(message), default] => {
+                        (message, default)
+                    }
+                    _ => return Err(SequenceError::InvalidArguments(name.to_string())),
+                };
+                let default = self.evaluate(default)?;
+                tracing::info!("{} (answered with {:#x})", message, default);
+                default
+            }
+            "Message" => {
+                let (kind, format, values) = match arguments {
+                    [kind, Expr::String(format), values @ ..] => (kind, format, values),
+                    _ => return Err(SequenceError::InvalidArguments(name.to_string())),
+                };
+                let kind = self.evaluate(kind)?;
+                let values = values
+                    .iter()
+                    .map(|value| self.evaluate(value))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let message = format_message(format, &values);
+
+                match kind {
+                    0 => tracing::info!("{}", message),
+                    1 => tracing::warn!("{}", message),
+                    _ => return Err(SequenceError::Message(message)),
+                }
+                0
+            }
+            "LoadDebugInfo" => 0,
+            _ => return Err(SequenceError::UnknownFunction(name.to_string())),
+        };
+
+        Ok(result)
+    }
+}
+
+fn string_arguments<'e, const N: usize>(
+    name: &str,
+    arguments: &'e [Expr],
+) -> Result<[&'e str; N], SequenceError> {
+    let invalid = || SequenceError::InvalidArguments(name.to_string());
+
+    if arguments.len() != N {
+        return Err(invalid());
+    }
+
+    let mut values = [""; N];
+    for (value, argument) in values.iter_mut().zip(arguments) {
+        match argument {
+            Expr::String(string) => *value = string,
+            _ => return Err(invalid()),
+        }
+    }
+
+    Ok(values)
+}
+
+fn binary(op: BinaryOp, lhs: u64, rhs: u64) -> Result<u64, SequenceError> {
+    Ok(match op {
+        BinaryOp::Or => (lhs != 0 || rhs != 0) as u64,
+        BinaryOp::And => (lhs != 0 && rhs != 0) as u64,
+        BinaryOp::BitOr => lhs | rhs,
+        BinaryOp::BitXor => lhs ^ rhs,
+        BinaryOp::BitAnd => lhs & rhs,
+        BinaryOp::Eq => (lhs == rhs) as u64,
+        BinaryOp::Ne => (lhs != rhs) as u64,
+        BinaryOp::Lt => (lhs < rhs) as u64,
+        BinaryOp::Le => (lhs <= rhs) as u64,
+        BinaryOp::Gt => (lhs > rhs) as u64,
+        BinaryOp::Ge => (lhs >= rhs) as u64,
+        BinaryOp::Shl => lhs.checked_shl(rhs as u32).unwrap_or(0),
+        BinaryOp::Shr => lhs.checked_shr(rhs as u32).unwrap_or(0),
+        BinaryOp::Add => lhs.wrapping_add(rhs),
+        BinaryOp::Sub => lhs.wrapping_sub(rhs),
+        BinaryOp::Mul => lhs.wrapping_mul(rhs),
+        BinaryOp::Div => lhs.checked_div(rhs).ok_or(SequenceError::DivisionByZero)?,
+        BinaryOp::Rem => lhs.checked_rem(rhs).ok_or(SequenceError::DivisionByZero)?,
+    })
+}
+
+/// Formats the message of the `Message` function, which supports `%d`, `%u`, `%x` and `%X`,
+/// with an optional zero padded width like `%08x`.
+fn format_message(format: &str, values: &[u64]) -> String {
+    let mut message = String::new();
+    let mut values = values.iter();
+    let mut chars = format.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            message.push(c);
+            continue;
+        }
+
+        let mut width = String::new();
+        while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+            width.push(digit);
+        }
+        let width: usize = width.parse().unwrap_or(0);
+
+        match chars.next() {
+            Some('%') => message.push('%'),
+            Some(conversion @ ('d' | 'u' | 'x' | 'X')) => {
+                let value = values.next().copied().unwrap_or(0);
+                let formatted = match conversion {
+                    'x' => format!("{value:0width$x}"),
+                    'X' => format!("{value:0width$X}"),
+                    _ => format!("{value:0width$}"),
+                };
+                message.push_str(&formatted);
+            }
+            Some(other) => {
+                message.push('%');
+                message.push(other);
+            }
+            None => message.push('%'),
+        }
+    }
+
+    message
+}
+
+/// A debug sequence which runs the sequences of a CMSIS-Pack debug description.
+///
+/// Standard sequences which are not part of the debug description are forwarded to the
+/// built-in sequence of the target.
+pub struct CmsisPackSequence {
+    sequences: Vec<SequenceDescription>,
+    fallback: Arc<dyn ArmDebugSequence>,
+}
+
+impl CmsisPackSequence {
+    /// Creates a sequence which runs `sequences`, and uses `fallback` for all other sequences.
+    pub fn create(
+        sequences: Vec<SequenceDescription>,
+        fallback: Arc<dyn ArmDebugSequence>,
+    ) -> Arc<dyn ArmDebugSequence> {
+        Arc::new(Self {
+            sequences,
+            fallback,
+        })
+    }
+
+    fn has_sequence(&self, name: &str) -> bool {
+        self.sequences.iter().any(|sequence| sequence.name == name)
+    }
+
+    fn run(&self, name: &str, access: &mut dyn DebugAccess) -> Result<(), ArmError> {
+        Interpreter::new(&self.sequences, access)
+            .run_sequence(name)
+            .map_err(|error| {
+                tracing::warn!("Debug sequence {} failed: {}", name, error);
+                error.into()
+            })
+    }
+}
+
+impl ArmDebugSequence for CmsisPackSequence {
+    fn reset_hardware_assert(&self, interface: &mut dyn DapProbe) -> Result<(), ArmError> {
+        if !self.has_sequence("ResetHardwareAssert") {
+            return self.fallback.reset_hardware_assert(interface);
+        }
+
+        self.run("ResetHardwareAssert", &mut ProbeAccess { probe: interface })
+    }
+
+    fn reset_hardware_deassert(&self, memory: &mut dyn ArmProbe) -> Result<(), ArmError> {
+        if !self.has_sequence("ResetHardwareDeassert") {
+            return self.fallback.reset_hardware_deassert(memory);
+        }
+
+        self.run("ResetHardwareDeassert", &mut MemoryAccess { memory })
+    }
+
+    fn debug_port_setup(&self, interface: &mut dyn DapProbe) -> Result<(), ArmError> {
+        if !self.has_sequence("DebugPortSetup") {
+            return self.fallback.debug_port_setup(interface);
+        }
+
+        self.run("DebugPortSetup", &mut ProbeAccess { probe: interface })?;
+
+        // Like the built-in sequence, read DPIDR to leave the line reset state.
+        let _ = interface.raw_read_register(PortType::DebugPort, DPIDR::ADDRESS);
+
+        Ok(())
+    }
+
+    fn debug_port_start(
+        &self,
+        interface: &mut ArmCommunicationInterface<Initialized>,
+        dp: DpAddress,
+    ) -> Result<(), ArmError> {
+        if !self.has_sequence("DebugPortStart") {
+            return self.fallback.debug_port_start(interface, dp);
+        }
+
+        self.run(
+            "DebugPortStart",
+            &mut InterfaceAccess {
+                interface,
+                dp,
+                ap: 0,
+            },
+        )
+    }
+
+    fn debug_core_start(
+        &self,
+        interface: &mut dyn ArmProbeInterface,
+        core_ap: MemoryAp,
+        core_type: CoreType,
+        debug_base: Option<u64>,
+        cti_base: Option<u64>,
+    ) -> Result<(), ArmError> {
+        if !self.has_sequence("DebugCoreStart") {
+            return self
+                .fallback
+                .debug_core_start(interface, core_ap, core_type, debug_base, cti_base);
+        }
+
+        let address = core_ap.ap_address();
+        self.run(
+            "DebugCoreStart",
+            &mut InterfaceAccess {
+                interface,
+                dp: address.dp,
+                ap: address.ap,
+            },
+        )
+    }
+
+    fn reset_catch_set(
+        &self,
+        core: &mut dyn ArmProbe,
+        core_type: CoreType,
+        debug_base: Option<u64>,
+    ) -> Result<(), ArmError> {
+        if !self.has_sequence("ResetCatchSet") {
+            return self.fallback.reset_catch_set(core, core_type, debug_base);
+        }
+
+        self.run("ResetCatchSet", &mut MemoryAccess { memory: core })
+    }
+
+    fn reset_catch_clear(
+        &self,
+        core: &mut dyn ArmProbe,
+        core_type: CoreType,
+        debug_base: Option<u64>,
+    ) -> Result<(), ArmError> {
+        if !self.has_sequence("ResetCatchClear") {
+            return self.fallback.reset_catch_clear(core, core_type, debug_base);
+        }
+
+        self.run("ResetCatchClear", &mut MemoryAccess { memory: core })
+    }
+
+    fn trace_start(
+        &self,
+        interface: &mut dyn ArmProbeInterface,
+        components: &[CoresightComponent],
+        sink: &TraceSink,
+    ) -> Result<(), ArmError> {
+        if !self.has_sequence("TraceStart") {
+            return self.fallback.trace_start(interface, components, sink);
+        }
+
+        self.run(
+            "TraceStart",
+            &mut InterfaceAccess {
+                interface,
+                dp: DpAddress::Default,
+                ap: 0,
+            },
+        )
+    }
+
+    fn reset_system(
+        &self,
+        interface: &mut dyn ArmProbe,
+        core_type: CoreType,
+        debug_base: Option<u64>,
+    ) -> Result<(), ArmError> {
+        if !self.has_sequence("ResetSystem") {
+            return self.fallback.reset_system(interface, core_type, debug_base);
+        }
+
+        self.run("ResetSystem", &mut MemoryAccess { memory: interface })
+    }
+
+    fn debug_device_unlock(
+        &self,
+        interface: &mut dyn ArmProbeInterface,
+        default_ap: MemoryAp,
+        permissions: &crate::Permissions,
+    ) -> Result<(), ArmError> {
+        if !self.has_sequence("DebugDeviceUnlock") {
+            return self
+                .fallback
+                .debug_device_unlock(interface, default_ap, permissions);
+        }
+
+        let address = default_ap.ap_address();
+        self.run(
+            "DebugDeviceUnlock",
+            &mut InterfaceAccess {
+                interface,
+                dp: address.dp,
+                ap: address.ap,
+            },
+        )
+    }
+
+    fn recover_support_start(&self, interface: &mut dyn ArmProbe) -> Result<(), ArmError> {
+        if !self.has_sequence("RecoverSupportStart") {
+            return self.fallback.recover_support_start(interface);
+        }
+
+        self.run(
+            "RecoverSupportStart",
+            &mut MemoryAccess { memory: interface },
+        )
+    }
+
+    fn debug_core_stop(
+        &self,
+        interface: &mut dyn ArmProbeInterface,
+        core_ap: MemoryAp,
+        core_type: CoreType,
+    ) -> Result<(), ArmError> {
+        if !self.has_sequence("DebugCoreStop") {
+            return self.fallback.debug_core_stop(interface, core_ap, core_type);
+        }
+
+        let address = core_ap.ap_address();
+        self.run(
+            "DebugCoreStop",
+            &mut InterfaceAccess {
+                interface,
+                dp: address.dp,
+                ap: address.ap,
+            },
+        )
+    }
+
+    fn debug_erase_sequence(&self) -> Option<Arc<dyn DebugEraseSequence>> {
+        self.fallback.debug_erase_sequence()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use probe_rs_target::{SequenceBlock, SequenceControl, SequenceDescription, SequenceElement};
+
+    use super::{format_message, DebugAccess, Interpreter, SequenceError};
+    use crate::architecture::arm::ArmError;
+
+    /// Memory which counts down the value at `0x2000` on every read.
+    #[derive(Default)]
+    struct MockAccess {
+        memory: HashMap<u64, u64>,
+        dp_writes: Vec<(u8, u32)>,
+    }
+
+    impl DebugAccess for MockAccess {
+        fn read_memory(
+            &mut self,
+            _ap: u8,
+            address: u64,
+            _size: usize,
+        ) -> Result<u64, SequenceError> {
+            if address == 0xDEAD {
+                return Err(ArmError::OutOfBounds.into());
+            }
+
+            let value = self.memory.entry(address).or_default();
+            let result = *value;
+            if address == 0x2000 {
+                *value = value.saturating_sub(1);
+            }
+            Ok(result)
+        }
+
+        fn write_memory(
+            &mut self,
+            _ap: u8,
+            address: u64,
+            _size: usize,
+            value: u64,
+        ) -> Result<(), SequenceError> {
+            self.memory.insert(address, value);
+            Ok(())
+        }
+
+        fn read_dp(&mut self, _address: u8) -> Result<u32, SequenceError> {
+            Ok(0)
+        }
+
+        fn write_dp(&mut self, address: u8, value: u32) -> Result<(), SequenceError> {
+            self.dp_writes.push((address, value));
+            Ok(())
+        }
+
+        fn read_ap(&mut self, _ap: u8, _address: u8) -> Result<u32, SequenceError> {
+            Ok(0)
+        }
+
+        fn write_ap(&mut self, _ap: u8, _address: u8, _value: u32) -> Result<(), SequenceError> {
+            Ok(())
+        }
+
+        fn swj_sequence(&mut self, _bit_len: u8, _bits: u64) -> Result<(), SequenceError> {
+            Ok(())
+        }
+
+        fn swj_pins(&mut self, _out: u32, _select: u32, _wait: u32) -> Result<u32, SequenceError> {
+            Ok(0)
+        }
+    }
+
+    fn block(code: &str) -> SequenceElement {
+        SequenceElement::Block(SequenceBlock {
+            atomic: false,
+            code: code.to_string(),
+        })
+    }
+
+    fn sequence(name: &str, body: Vec<SequenceElement>) -> SequenceDescription {
+        SequenceDescription {
+            name: name.to_string(),
+            disable: false,
+            body,
+        }
+    }
+
+    #[test]
+    fn read_modify_write() {
+        let sequences = [sequence(
+            "DebugDeviceUnlock",
+            vec![block(
+                "__var DbgMcu_CR = Read32(0xE0042004);\n\
+                 DbgMcu_CR |= 0x00000007;\n\
+                 Write32(0xE0042004, DbgMcu_CR);\n\
+                 DAP_WriteABORT(0x1E);",
+            )],
+        )];
+
+        let mut access = MockAccess::default();
+        access.memory.insert(0xE004_2004, 0x100);
+
+        Interpreter::new(&sequences, &mut access)
+            .run_sequence("DebugDeviceUnlock")
+            .unwrap();
+
+        assert_eq!(access.memory[&0xE004_2004], 0x107);
+        assert_eq!(access.dp_writes, vec![(0x0, 0x1E)]);
+    }
+
+    #[test]
+    fn control_elements() {
+        let sequences = [
+            sequence(
+                "ResetSystem",
+                vec![
+                    block("__var polls = 0;"),
+                    SequenceElement::Control(SequenceControl {
+                        condition: Some("Read32(0x1000) == 0".to_string()),
+                        while_condition: Some("Read32(0x2000) != 0".to_string()),
+                        timeout: 0,
+                        body: vec![block("polls += 1;")],
+                    }),
+                    SequenceElement::Control(SequenceControl {
+                        condition: Some("polls != 3".to_string()),
+                        while_condition: None,
+                        timeout: 0,
+                        body: vec![block("Write32(0x3000, 1);")],
+                    }),
+                    block("Sequence(\"Nested\");"),
+                ],
+            ),
+            sequence("Nested", vec![block("Write32(0x4000, 0x5 << 4);")]),
+        ];
+
+        let mut access = MockAccess::default();
+        access.memory.insert(0x2000, 3);
+
+        Interpreter::new(&sequences, &mut access)
+            .run_sequence("ResetSystem")
+            .unwrap();
+
+        assert_eq!(access.memory.get(&0x3000), None);
+        assert_eq!(access.memory[&0x4000], 0x50);
+    }
+
+    #[test]
+    fn errors() {
+        let sequences = [
+            sequence("Undeclared", vec![block("value = 1;")]),
+            sequence("Recursive", vec![block("Sequence(\"Recursive\");")]),
+            sequence(
+                "IgnoreErrors",
+                vec![block(
+                    "__errorcontrol = 1; __var value = Read32(0xDEAD); Write32(0x10, value + 1);",
+                )],
+            ),
+            sequence("AccessError", vec![block("Read32(0xDEAD);")]),
+            sequence("Failure", vec![block("Message(2, \"Device is locked\");")]),
+        ];
+
+        let mut access = MockAccess::default();
+        let mut interpreter = Interpreter::new(&sequences, &mut access);
+
+        assert!(matches!(
+            interpreter.run_sequence("Undeclared"),
+            Err(SequenceError::UnknownVariable(_))
+        ));
+        assert!(matches!(
+            interpreter.run_sequence("Recursive"),
+            Err(SequenceError::NestingTooDeep)
+        ));
+        assert!(matches!(
+            interpreter.run_sequence("Missing"),
+            Err(SequenceError::UnknownSequence(_))
+        ));
+        assert!(matches!(
+            interpreter.run_sequence("Failure"),
+            Err(SequenceError::Message(message)) if message == "Device is locked"
+        ));
+
+        interpreter.run_sequence("IgnoreErrors").unwrap();
+        assert_eq!(access.memory[&0x10], 1);
+
+        let mut access = MockAccess::default();
+        assert!(matches!(
+            Interpreter::new(&sequences, &mut access).run_sequence("AccessError"),
+            Err(SequenceError::Access(ArmError::OutOfBounds))
+        ));
+    }
+
+    #[test]
+    fn message_format() {
+        assert_eq!(
+            format_message("IDCODE %08X, rev %d, 100%%", &[0x6BA0_2477, 2]),
+            "IDCODE 6BA02477, rev 2, 100%"
+        );
+    }
+}
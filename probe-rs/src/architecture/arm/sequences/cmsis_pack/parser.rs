@@ -0,0 +1,481 @@
+//! Parser for the statements and expressions of CMSIS-Pack debug sequences.
+//!
+//! The language is a subset of C, operating on 64-bit unsigned integers. See the
+//! [expression documentation] for the syntax.
+//!
+//! [expression documentation]: https://open-cmsis-pack.github.io/Open-CMSIS-Pack-Spec/main/html/debug_description.html#debugExpressions
+
+use super::SequenceError;
+
+/// A binary operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum BinaryOp {
+    Or,
+    And,
+    BitOr,
+    BitXor,
+    BitAnd,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Shl,
+    Shr,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+/// A unary operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum UnaryOp {
+    Not,
+    BitNot,
+    Neg,
+}
+
+/// An expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Expr {
+    Number(u64),
+    /// A string literal, only valid as a function argument.
+    String(String),
+    Variable(String),
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Conditional(Box<Expr>, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+/// A statement of a sequence block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Statement {
+    /// `__var name = value;`
+    Declare(String, Expr),
+    /// `name = value;`, or a compound assignment like `name |= value;`.
+    Assign(String, Option<BinaryOp>, Expr),
+    /// An expression evaluated for its side effects, usually a function call.
+    Expr(Expr),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Number(u64),
+    String(String),
+    Identifier(String),
+    Punct(&'static str),
+}
+
+/// Punctuation, longest first so that the lexer matches greedily.
+const PUNCTUATION: &[&str] = &[
+    "<<=", ">>=", "||", "&&", "==", "!=", "<=", ">=", "<<", ">>", "+=", "-=", "*=", "/=", "%=",
+    "&=", "|=", "^=", "(", ")", ",", ";", "?", ":", "=", "|", "^", "&", "<", ">", "+", "-", "*",
+    "/", "%", "!", "~",
+];
+
+fn tokenize(code: &str) -> Result<Vec<Token>, SequenceError> {
+    let mut tokens = Vec::new();
+    let mut rest = code;
+
+    loop {
+        rest = rest.trim_start();
+
+        if rest.is_empty() {
+            return Ok(tokens);
+        }
+
+        if let Some(comment) = rest.strip_prefix("//") {
+            rest = comment.find('\n').map_or("", |end| &comment[end..]);
+            continue;
+        }
+
+        if let Some(comment) = rest.strip_prefix("/*") {
+            let end = comment
+                .find("*/")
+                .ok_or_else(|| SequenceError::Syntax("unterminated comment".to_string()))?;
+            rest = &comment[end + 2..];
+            continue;
+        }
+
+        let first = rest.chars().next().unwrap();
+
+        if first.is_ascii_digit() {
+            let end = rest
+                .find(|c: char| !c.is_ascii_alphanumeric())
+                .unwrap_or(rest.len());
+            tokens.push(Token::Number(parse_number(&rest[..end])?));
+            rest = &rest[end..];
+        } else if first.is_ascii_alphabetic() || first == '_' {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            tokens.push(Token::Identifier(rest[..end].to_string()));
+            rest = &rest[end..];
+        } else if first == '"' {
+            let end = rest[1..]
+                .find('"')
+                .ok_or_else(|| SequenceError::Syntax("unterminated string".to_string()))?;
+            tokens.push(Token::String(rest[1..end + 1].to_string()));
+            rest = &rest[end + 2..];
+        } else {
+            let punct = PUNCTUATION
+                .iter()
+                .find(|punct| rest.starts_with(**punct))
+                .ok_or_else(|| SequenceError::Syntax(format!("unexpected character '{first}'")))?;
+            tokens.push(Token::Punct(punct));
+            rest = &rest[punct.len()..];
+        }
+    }
+}
+
+fn parse_number(literal: &str) -> Result<u64, SequenceError> {
+    let parsed = if let Some(hex) = literal
+        .strip_prefix("0x")
+        .or_else(|| literal.strip_prefix("0X"))
+    {
+        u64::from_str_radix(hex, 16)
+    } else if let Some(binary) = literal
+        .strip_prefix("0b")
+        .or_else(|| literal.strip_prefix("0B"))
+    {
+        u64::from_str_radix(binary, 2)
+    } else {
+        literal.parse()
+    };
+
+    parsed.map_err(|_| SequenceError::Syntax(format!("invalid number '{literal}'")))
+}
+
+/// Binary operators, from the lowest to the highest precedence.
+const BINARY_PRECEDENCE: &[&[(&str, BinaryOp)]] = &[
+    &[("||", BinaryOp::Or)],
+    &[("&&", BinaryOp::And)],
+    &[("|", BinaryOp::BitOr)],
+    &[("^", BinaryOp::BitXor)],
+    &[("&", BinaryOp::BitAnd)],
+    &[("==", BinaryOp::Eq), ("!=", BinaryOp::Ne)],
+    &[
+        ("<", BinaryOp::Lt),
+        ("<=", BinaryOp::Le),
+        (">", BinaryOp::Gt),
+        (">=", BinaryOp::Ge),
+    ],
+    &[("<<", BinaryOp::Shl), (">>", BinaryOp::Shr)],
+    &[("+", BinaryOp::Add), ("-", BinaryOp::Sub)],
+    &[
+        ("*", BinaryOp::Mul),
+        ("/", BinaryOp::Div),
+        ("%", BinaryOp::Rem),
+    ],
+];
+
+const COMPOUND_ASSIGNMENTS: &[(&str, BinaryOp)] = &[
+    ("+=", BinaryOp::Add),
+    ("-=", BinaryOp::Sub),
+    ("*=", BinaryOp::Mul),
+    ("/=", BinaryOp::Div),
+    ("%=", BinaryOp::Rem),
+    ("&=", BinaryOp::BitAnd),
+    ("|=", BinaryOp::BitOr),
+    ("^=", BinaryOp::BitXor),
+    ("<<=", BinaryOp::Shl),
+    (">>=", BinaryOp::Shr),
+];
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn new(code: &str) -> Result<Self, SequenceError> {
+        Ok(Self {
+            tokens: tokenize(code)?,
+            position: 0,
+        })
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn peek_punct(&self) -> Option<&'static str> {
+        match self.peek() {
+            Some(Token::Punct(punct)) => Some(punct),
+            _ => None,
+        }
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, punct: &str) -> bool {
+        if self.peek_punct() == Some(punct) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), SequenceError> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            Err(SequenceError::Syntax(format!(
+                "expected '{punct}', found {:?}",
+                self.peek()
+            )))
+        }
+    }
+
+    fn statements(&mut self) -> Result<Vec<Statement>, SequenceError> {
+        let mut statements = Vec::new();
+
+        while self.peek().is_some() {
+            if self.eat(";") {
+                continue;
+            }
+
+            statements.push(self.statement()?);
+
+            // The terminating semicolon of the last statement is optional.
+            if self.peek().is_some() {
+                self.expect(";")?;
+            }
+        }
+
+        Ok(statements)
+    }
+
+    fn statement(&mut self) -> Result<Statement, SequenceError> {
+        if let Some(Token::Identifier(identifier)) = self.peek() {
+            let identifier = identifier.clone();
+
+            if identifier == "__var" {
+                self.position += 1;
+
+                let name = match self.next() {
+                    Some(Token::Identifier(name)) => name,
+                    token => {
+                        return Err(SequenceError::Syntax(format!(
+                            "expected a variable name, found {token:?}"
+                        )))
+                    }
+                };
+
+                self.expect("=")?;
+                return Ok(Statement::Declare(name, self.expression()?));
+            }
+
+            if let Some(Token::Punct(punct)) = self.tokens.get(self.position + 1) {
+                let operator = if *punct == "=" {
+                    Some(None)
+                } else {
+                    COMPOUND_ASSIGNMENTS
+                        .iter()
+                        .find(|(symbol, _)| symbol == punct)
+                        .map(|(_, op)| Some(*op))
+                };
+
+                if let Some(operator) = operator {
+                    self.position += 2;
+                    return Ok(Statement::Assign(identifier, operator, self.expression()?));
+                }
+            }
+        }
+
+        Ok(Statement::Expr(self.expression()?))
+    }
+
+    fn expression(&mut self) -> Result<Expr, SequenceError> {
+        let condition = self.binary(0)?;
+
+        if self.eat("?") {
+            let if_true = self.expression()?;
+            self.expect(":")?;
+            let if_false = self.expression()?;
+
+            return Ok(Expr::Conditional(
+                Box::new(condition),
+                Box::new(if_true),
+                Box::new(if_false),
+            ));
+        }
+
+        Ok(condition)
+    }
+
+    fn binary(&mut self, level: usize) -> Result<Expr, SequenceError> {
+        let Some(operators) = BINARY_PRECEDENCE.get(level) else {
+            return self.unary();
+        };
+
+        let mut lhs = self.binary(level + 1)?;
+
+        while let Some(op) = self.peek_punct().and_then(|punct| {
+            operators
+                .iter()
+                .find(|(symbol, _)| *symbol == punct)
+                .map(|(_, op)| *op)
+        }) {
+            self.position += 1;
+            let rhs = self.binary(level + 1)?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr, SequenceError> {
+        let op = match self.peek_punct() {
+            Some("!") => UnaryOp::Not,
+            Some("~") => UnaryOp::BitNot,
+            Some("-") => UnaryOp::Neg,
+            Some("+") => {
+                self.position += 1;
+                return self.unary();
+            }
+            _ => return self.primary(),
+        };
+
+        self.position += 1;
+        Ok(Expr::Unary(op, Box::new(self.unary()?)))
+    }
+
+    fn primary(&mut self) -> Result<Expr, SequenceError> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(Expr::Number(value)),
+            Some(Token::String(value)) => Ok(Expr::String(value)),
+            Some(Token::Identifier(name)) => {
+                if !self.eat("(") {
+                    return Ok(Expr::Variable(name));
+                }
+
+                let mut arguments = Vec::new();
+                if !self.eat(")") {
+                    loop {
+                        arguments.push(self.expression()?);
+
+                        if self.eat(")") {
+                            break;
+                        }
+                        self.expect(",")?;
+                    }
+                }
+
+                Ok(Expr::Call(name, arguments))
+            }
+            Some(Token::Punct("(")) => {
+                let expression = self.expression()?;
+                self.expect(")")?;
+                Ok(expression)
+            }
+            token => Err(SequenceError::Syntax(format!(
+                "expected an expression, found {token:?}"
+            ))),
+        }
+    }
+}
+
+/// Parses the statements of a sequence block.
+pub(super) fn parse_statements(code: &str) -> Result<Vec<Statement>, SequenceError> {
+    Parser::new(code)?.statements()
+}
+
+/// Parses a single expression, e.g. the condition of a control element.
+pub(super) fn parse_expression(code: &str) -> Result<Expr, SequenceError> {
+    let mut parser = Parser::new(code)?;
+    let expression = parser.expression()?;
+
+    match parser.peek() {
+        None => Ok(expression),
+        Some(token) => Err(SequenceError::Syntax(format!(
+            "unexpected {token:?} after the expression"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_expression, parse_statements, BinaryOp, Expr, Statement};
+
+    #[test]
+    fn operator_precedence() {
+        assert_eq!(
+            parse_expression("1 + 2 * 3").unwrap(),
+            Expr::Binary(
+                BinaryOp::Add,
+                Box::new(Expr::Number(1)),
+                Box::new(Expr::Binary(
+                    BinaryOp::Mul,
+                    Box::new(Expr::Number(2)),
+                    Box::new(Expr::Number(3))
+                ))
+            )
+        );
+        assert_eq!(
+            parse_expression("a & 0x1 == 0").unwrap(),
+            Expr::Binary(
+                BinaryOp::BitAnd,
+                Box::new(Expr::Variable("a".to_string())),
+                Box::new(Expr::Binary(
+                    BinaryOp::Eq,
+                    Box::new(Expr::Number(1)),
+                    Box::new(Expr::Number(0))
+                ))
+            )
+        );
+    }
+
+    #[test]
+    fn statements() {
+        let statements = parse_statements(
+            r#"
+            // Enable the debug clock.
+            __var value = Read32(0xE0042004);
+            value |= 0x7; /* DBG_SLEEP | DBG_STOP | DBG_STANDBY */
+            Write32(0xE0042004, value);
+            Message(0, "done")
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            statements,
+            vec![
+                Statement::Declare(
+                    "value".to_string(),
+                    Expr::Call("Read32".to_string(), vec![Expr::Number(0xE004_2004)])
+                ),
+                Statement::Assign("value".to_string(), Some(BinaryOp::BitOr), Expr::Number(7)),
+                Statement::Expr(Expr::Call(
+                    "Write32".to_string(),
+                    vec![
+                        Expr::Number(0xE004_2004),
+                        Expr::Variable("value".to_string())
+                    ]
+                )),
+                Statement::Expr(Expr::Call(
+                    "Message".to_string(),
+                    vec![Expr::Number(0), Expr::String("done".to_string())]
+                )),
+            ]
+        );
+    }
+
+    #[test]
+    fn syntax_errors() {
+        assert!(parse_statements("__var = 1;").is_err());
+        assert!(parse_statements("Write32(0x1000 0x2);").is_err());
+        assert!(parse_expression("(1 + 2").is_err());
+        assert!(parse_expression("1 2").is_err());
+    }
+}
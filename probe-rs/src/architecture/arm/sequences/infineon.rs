@@ -3,7 +3,7 @@
 use crate::architecture::arm::armv7m::{Aircr, Dhcsr, FpCtrl, FpRev1CompX, FpRev2CompX};
 use crate::architecture::arm::memory::adi_v5_memory_interface::ArmProbe;
 use crate::architecture::arm::sequences::ArmDebugSequenceError;
-use crate::architecture::arm::ArmError;
+use crate::architecture::arm::{ArmError, RawDapAccess};
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::thread;
@@ -368,7 +368,7 @@ impl ArmDebugSequence for XMC4000 {
         pin_output.set_swdio_tms(true);
 
         loop {
-            match interface.swj_pins(pin_output.0 as u32, pin_select.0 as u32, 0) {
+            match RawDapAccess::swj_pins(interface, pin_output.0 as u32, pin_select.0 as u32, 0) {
                 Err(DebugProbeError::CommandNotSupportedByProbe("swj_pins"))
                     if pin_select.swdio_tms() =>
                 {
@@ -406,7 +406,7 @@ impl ArmDebugSequence for XMC4000 {
 
         // Deassert nRST
         pin_output.set_nreset(true);
-        interface.swj_pins(pin_output.0 as u32, pin_select.0 as u32, 0)?;
+        RawDapAccess::swj_pins(interface, pin_output.0 as u32, pin_select.0 as u32, 0)?;
 
         // Race! :(
 
@@ -0,0 +1,198 @@
+//! Sequences for NXP Kinetis devices.
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use super::{ArmDebugSequence, ArmDebugSequenceError};
+use crate::architecture::arm::{ap::MemoryAp, ApAddress, ArmError, ArmProbeInterface, DpAddress};
+
+/// The MDM-AP, which is used to mass erase the device even if it is secured.
+const MDM_AP: ApAddress = ApAddress {
+    dp: DpAddress::Default,
+    ap: 1,
+};
+
+/// The status register of the MDM-AP.
+const MDM_STATUS: u8 = 0x00;
+/// The control register of the MDM-AP.
+const MDM_CONTROL: u8 = 0x04;
+/// The identification register of the MDM-AP.
+const MDM_IDR: u8 = 0xFC;
+
+/// The value of the IDR of the MDM-AP.
+const MDM_IDR_VALUE: u32 = 0x001C_0000;
+/// Mask for the IDR, the lower bits differ between the Kinetis families.
+const MDM_IDR_MASK: u32 = 0xFFFF_FF00;
+
+const STATUS_FLASH_READY: u32 = 1 << 1;
+const STATUS_SYSTEM_SECURITY: u32 = 1 << 2;
+const STATUS_MASS_ERASE_ENABLE: u32 = 1 << 5;
+
+const CONTROL_MASS_ERASE_IN_PROGRESS: u32 = 1 << 0;
+const CONTROL_SYSTEM_RESET_REQUEST: u32 = 1 << 3;
+
+/// The maximum time a mass erase can take.
+const MASS_ERASE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The sequence handle for the Kinetis family.
+pub struct Kinetis(());
+
+impl Kinetis {
+    /// Create a sequence handle for Kinetis devices.
+    pub fn create() -> Arc<dyn ArmDebugSequence> {
+        Arc::new(Self(()))
+    }
+
+    /// Waits until `condition` is true for the MDM-AP register at `address`.
+    fn wait_for(
+        interface: &mut dyn ArmProbeInterface,
+        address: u8,
+        condition: impl Fn(u32) -> bool,
+    ) -> Result<(), ArmError> {
+        let start = Instant::now();
+        while start.elapsed() < MASS_ERASE_TIMEOUT {
+            if condition(interface.read_raw_ap_register(MDM_AP, address)?) {
+                return Ok(());
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        Err(ArmError::Timeout)
+    }
+}
+
+impl ArmDebugSequence for Kinetis {
+    fn debug_device_recover(
+        &self,
+        interface: &mut dyn ArmProbeInterface,
+        _default_ap: MemoryAp,
+    ) -> Result<(), ArmError> {
+        let idr = interface.read_raw_ap_register(MDM_AP, MDM_IDR)?;
+        if idr & MDM_IDR_MASK != MDM_IDR_VALUE {
+            return Err(ArmDebugSequenceError::custom(format!(
+                "No MDM-AP found, the IDR of AP 1 is {idr:#010x}"
+            ))
+            .into());
+        }
+
+        // Keep the core in reset, so the firmware can't interfere with the mass erase.
+        interface.write_raw_ap_register(MDM_AP, MDM_CONTROL, CONTROL_SYSTEM_RESET_REQUEST)?;
+
+        Self::wait_for(interface, MDM_STATUS, |status| {
+            status & STATUS_FLASH_READY != 0
+        })?;
+
+        let status = interface.read_raw_ap_register(MDM_AP, MDM_STATUS)?;
+        if status & STATUS_MASS_ERASE_ENABLE == 0 {
+            return Err(ArmDebugSequenceError::custom(
+                "Mass erase is disabled by the flash security settings",
+            )
+            .into());
+        }
+
+        tracing::info!("Mass erasing device through the MDM-AP");
+        interface.write_raw_ap_register(
+            MDM_AP,
+            MDM_CONTROL,
+            CONTROL_SYSTEM_RESET_REQUEST | CONTROL_MASS_ERASE_IN_PROGRESS,
+        )?;
+
+        // The bit is cleared by the device, once the mass erase is done.
+        Self::wait_for(interface, MDM_CONTROL, |control| {
+            control & CONTROL_MASS_ERASE_IN_PROGRESS == 0
+        })?;
+
+        // Release the reset.
+        interface.write_raw_ap_register(MDM_AP, MDM_CONTROL, 0)?;
+
+        let status = interface.read_raw_ap_register(MDM_AP, MDM_STATUS)?;
+        if status & STATUS_SYSTEM_SECURITY != 0 {
+            return Err(
+                ArmDebugSequenceError::custom("Device is still secured after mass erase").into(),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use super::{Kinetis, MDM_AP};
+    use crate::{
+        architecture::arm::{ap::MemoryAp, ApAddress, ArmError, ArmProbeInterface, DpAddress},
+        probe::fake_probe::FakeProbe,
+    };
+
+    /// Runs the recovery against an MDM-AP, which reports `status` and logs the writes to the
+    /// control register.
+    fn recover(status: u32) -> (Result<(), ArmError>, Vec<u32>) {
+        let control_writes = Arc::new(Mutex::new(Vec::new()));
+
+        let mut probe = FakeProbe::new();
+        probe.set_ap_register_read_handler(Box::new(move |ap, address| {
+            assert_eq!(ap, MDM_AP);
+            match address {
+                0x00 => Ok(status),
+                // The mass erase finishes immediately.
+                0x04 => Ok(0),
+                0xFC => Ok(0x001C_0003),
+                _ => panic!("Unexpected read of MDM-AP register {address:#04x}"),
+            }
+        }));
+
+        let writes = control_writes.clone();
+        probe.set_ap_register_write_handler(Box::new(move |ap, address, value| {
+            assert_eq!((ap, address), (MDM_AP, 0x04));
+            writes.lock().unwrap().push(value);
+            Ok(())
+        }));
+
+        let mut probe = probe.into_probe();
+        probe.attach_to_unspecified().unwrap();
+        let sequence = Kinetis::create();
+        let mut interface: Box<dyn ArmProbeInterface> = probe
+            .try_into_arm_interface()
+            .unwrap()
+            .initialize(sequence.clone())
+            .map_err(|(_, e)| e)
+            .unwrap();
+
+        let default_ap = MemoryAp::new(ApAddress {
+            dp: DpAddress::Default,
+            ap: 0,
+        });
+        let result = sequence.debug_device_recover(&mut *interface, default_ap);
+
+        let writes = control_writes.lock().unwrap().clone();
+        (result, writes)
+    }
+
+    #[test]
+    fn recover_mass_erases() {
+        let (result, control_writes) = recover(0x0000_0022);
+
+        result.unwrap();
+        assert_eq!(control_writes, [0x08, 0x09, 0x00]);
+    }
+
+    #[test]
+    fn recover_with_mass_erase_disabled() {
+        let (result, control_writes) = recover(0x0000_0006);
+
+        assert!(result.is_err());
+        // The mass erase is never started.
+        assert_eq!(control_writes, [0x08]);
+    }
+
+    #[test]
+    fn recover_with_device_still_secured() {
+        let (result, _) = recover(0x0000_0026);
+
+        assert!(result.is_err());
+    }
+}
@@ -1,8 +1,10 @@
 //! Debug sequences to operate special requirements ARM targets.
 
 pub mod atsame5x;
+pub mod cmsis_pack;
 pub mod efm32xg2;
 pub mod infineon;
+pub mod kinetis;
 mod nrf;
 pub mod nrf52;
 pub mod nrf53;
@@ -19,7 +21,7 @@ use std::{
     time::{Duration, Instant},
 };
 
-use probe_rs_target::CoreType;
+use probe_rs_target::{CoreType, ResetType};
 
 use crate::{architecture::arm::ArmProbeInterface, DebugProbeError, MemoryMappedRegister};
 
@@ -34,7 +36,7 @@ use super::{
         adi_v5_memory_interface::ArmProbe,
         romtable::{CoresightComponent, PeripheralType},
     },
-    ArmCommunicationInterface, ArmError, DpAddress, Pins, PortType, Register,
+    ArmCommunicationInterface, ArmError, DpAddress, Pins, PortType, RawDapAccess, Register,
 };
 
 /// An error occurred when executing an ARM debug sequence
@@ -366,7 +368,7 @@ fn cortex_m_reset_catch_set(core: &mut dyn ArmProbe) -> Result<(), ArmError> {
 
 /// ResetSystem for Cortex-M devices
 fn cortex_m_reset_system(interface: &mut dyn ArmProbe) -> Result<(), ArmError> {
-    use crate::architecture::arm::core::armv7m::{Aircr, Dhcsr};
+    use crate::architecture::arm::core::armv7m::Aircr;
 
     let mut aircr = Aircr(0);
     aircr.vectkey();
@@ -374,6 +376,51 @@ fn cortex_m_reset_system(interface: &mut dyn ArmProbe) -> Result<(), ArmError> {
 
     interface.write_word_32(Aircr::get_mmio_address(), aircr.into())?;
 
+    cortex_m_wait_for_reset(interface)
+}
+
+/// Resets only the core of a Cortex-M device, using `AIRCR.VECTRESET`.
+///
+/// This is only available on ARMv7-M, and requires the core to be halted, so it is halted first.
+/// After the reset, the core is resumed, unless the reset is caught.
+fn cortex_m_reset_core(interface: &mut dyn ArmProbe) -> Result<(), ArmError> {
+    use crate::architecture::arm::core::armv7m::Aircr;
+
+    let mut dhcsr = Dhcsr(0);
+    dhcsr.set_c_halt(true);
+    dhcsr.set_c_debugen(true);
+    dhcsr.enable_write();
+    interface.write_word_32(Dhcsr::get_mmio_address(), dhcsr.into())?;
+
+    let start = Instant::now();
+    while !Dhcsr(interface.read_word_32(Dhcsr::get_mmio_address())?).s_halt() {
+        if start.elapsed() > Duration::from_millis(100) {
+            return Err(ArmError::Timeout);
+        }
+    }
+
+    let mut aircr = Aircr(0);
+    aircr.vectkey();
+    aircr.set_vectreset(true);
+
+    interface.write_word_32(Aircr::get_mmio_address(), aircr.into())?;
+
+    cortex_m_wait_for_reset(interface)?;
+
+    // C_HALT is not cleared by the reset, so the core has to be resumed.
+    let demcr = Demcr(interface.read_word_32(Demcr::get_mmio_address())?);
+    if !demcr.vc_corereset() {
+        let mut dhcsr = Dhcsr(0);
+        dhcsr.set_c_debugen(true);
+        dhcsr.enable_write();
+        interface.write_word_32(Dhcsr::get_mmio_address(), dhcsr.into())?;
+    }
+
+    Ok(())
+}
+
+/// Waits until a reset of a Cortex-M device is done.
+fn cortex_m_wait_for_reset(interface: &mut dyn ArmProbe) -> Result<(), ArmError> {
     let start = Instant::now();
 
     while start.elapsed() < Duration::from_micros(50_0000) {
@@ -399,6 +446,42 @@ fn cortex_m_reset_system(interface: &mut dyn ArmProbe) -> Result<(), ArmError> {
     Err(ArmError::Timeout)
 }
 
+/// Resets the device or the core with the given `reset_type`.
+///
+/// [`ResetType::Sequence`] uses [`ArmDebugSequence::reset_system`], the other reset types bypass
+/// the debug sequence of the device.
+pub(crate) fn reset_with_type(
+    sequence: &dyn ArmDebugSequence,
+    interface: &mut dyn ArmProbe,
+    core_type: CoreType,
+    debug_base: Option<u64>,
+    reset_type: ResetType,
+) -> Result<(), ArmError> {
+    match reset_type {
+        ResetType::Sequence => sequence.reset_system(interface, core_type, debug_base),
+        ResetType::Hardware => {
+            let mut n_reset = Pins(0);
+            n_reset.set_nreset(true);
+
+            interface.swj_pins(0, n_reset.0 as u32, 0)?;
+            thread::sleep(Duration::from_millis(50));
+
+            sequence.reset_hardware_deassert(interface)
+        }
+        ResetType::System if core_type.is_cortex_m() => cortex_m_reset_system(interface),
+        ResetType::Core if matches!(core_type, CoreType::Armv7m | CoreType::Armv7em) => {
+            cortex_m_reset_core(interface)
+        }
+        ResetType::System => {
+            Err(DebugProbeError::NotImplemented("System reset on this core type").into())
+        }
+        ResetType::Core => Err(DebugProbeError::NotImplemented(
+            "Core reset, it is only available on ARMv7-M cores",
+        )
+        .into()),
+    }
+}
+
 /// A interface to operate debug sequences for ARM targets.
 ///
 /// Should be implemented on a custom handle for chips that require special sequence code.
@@ -412,7 +495,7 @@ pub trait ArmDebugSequence: Send + Sync {
         let mut n_reset = Pins(0);
         n_reset.set_nreset(true);
 
-        let _ = interface.swj_pins(0, n_reset.0 as u32, 0)?;
+        let _ = RawDapAccess::swj_pins(interface, 0, n_reset.0 as u32, 0)?;
 
         Ok(())
     }
@@ -581,7 +664,7 @@ pub trait ArmDebugSequence: Send + Sync {
 
         // Dispatch based on core type (Cortex-A vs M)
         match core_type {
-            CoreType::Armv7a => armv7a_core_start(&mut *core, debug_base),
+            CoreType::Armv7a | CoreType::Armv7r => armv7a_core_start(&mut *core, debug_base),
             CoreType::Armv8a => armv8a_core_start(&mut *core, debug_base, cti_base),
             CoreType::Armv6m | CoreType::Armv7m | CoreType::Armv7em | CoreType::Armv8m => {
                 cortex_m_core_start(&mut *core)
@@ -604,7 +687,7 @@ pub trait ArmDebugSequence: Send + Sync {
     ) -> Result<(), ArmError> {
         // Dispatch based on core type (Cortex-A vs M)
         match core_type {
-            CoreType::Armv7a => armv7a_reset_catch_set(core, debug_base),
+            CoreType::Armv7a | CoreType::Armv7r => armv7a_reset_catch_set(core, debug_base),
             CoreType::Armv8a => armv8a_reset_catch_set(core, debug_base),
             CoreType::Armv6m | CoreType::Armv7m | CoreType::Armv7em | CoreType::Armv8m => {
                 cortex_m_reset_catch_set(core)
@@ -627,7 +710,7 @@ pub trait ArmDebugSequence: Send + Sync {
     ) -> Result<(), ArmError> {
         // Dispatch based on core type (Cortex-A vs M)
         match core_type {
-            CoreType::Armv7a => armv7a_reset_catch_clear(core, debug_base),
+            CoreType::Armv7a | CoreType::Armv7r => armv7a_reset_catch_clear(core, debug_base),
             CoreType::Armv8a => armv8a_reset_catch_clear(core, debug_base),
             CoreType::Armv6m | CoreType::Armv7m | CoreType::Armv7em | CoreType::Armv8m => {
                 cortex_m_reset_catch_clear(core)
@@ -679,7 +762,7 @@ pub trait ArmDebugSequence: Send + Sync {
     ) -> Result<(), ArmError> {
         // Dispatch based on core type (Cortex-A vs M)
         match core_type {
-            CoreType::Armv7a => armv7a_reset_system(interface, debug_base),
+            CoreType::Armv7a | CoreType::Armv7r => armv7a_reset_system(interface, debug_base),
             CoreType::Armv8a => armv8a_reset_system(interface, debug_base),
             CoreType::Armv6m | CoreType::Armv7m | CoreType::Armv7em | CoreType::Armv8m => {
                 cortex_m_reset_system(interface)
@@ -705,6 +788,27 @@ pub trait ArmDebugSequence: Send + Sync {
         Ok(())
     }
 
+    /// Recover a locked device by erasing it, which removes the read-out protection.
+    ///
+    /// This is executed by [`Probe::recover`](crate::Probe::recover) after having powered up the debug
+    /// port, instead of attaching to the cores, because a locked device usually can't be attached.
+    /// The `erase_all` permission has already been checked when this is called.
+    ///
+    /// By default, the [`DebugEraseSequence`] of the device is used, if it has one.
+    fn debug_device_recover(
+        &self,
+        interface: &mut dyn ArmProbeInterface,
+        _default_ap: MemoryAp,
+    ) -> Result<(), ArmError> {
+        match self.debug_erase_sequence() {
+            Some(erase_sequence) => erase_sequence.erase_all(interface),
+            None => Err(DebugProbeError::NotImplemented(
+                "Device recovery is not available on this device",
+            )
+            .into()),
+        }
+    }
+
     /// Executed before step or run command to support recovery from a lost target connection, e.g. after a low power mode.
     /// This is based on the `RecoverSupportStart` function from the [ARM SVD Debug Description].
     ///
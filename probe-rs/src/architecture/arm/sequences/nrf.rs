@@ -114,4 +114,36 @@ impl<T: Nrf> ArmDebugSequence for T {
 
         Ok(())
     }
+
+    fn debug_device_recover(
+        &self,
+        interface: &mut dyn ArmProbeInterface,
+        default_ap: MemoryAp,
+    ) -> Result<(), ArmError> {
+        let mut interface = interface.memory_interface(default_ap)?;
+
+        for (core_index, (core_ahb_ap_address, core_ctrl_ap_address)) in
+            self.core_aps(&mut *interface).iter().copied().enumerate()
+        {
+            tracing::info!("Recovering core {} with ERASEALL", core_index);
+            unlock_core(
+                interface.get_arm_communication_interface()?,
+                core_ctrl_ap_address,
+                &crate::Permissions::new().allow_erase_all(),
+            )?;
+
+            if !self.is_core_unlocked(
+                interface.get_arm_communication_interface()?,
+                core_ahb_ap_address,
+                core_ctrl_ap_address,
+            )? {
+                return Err(ArmDebugSequenceError::custom(format!(
+                    "Could not unlock core {core_index}"
+                ))
+                .into());
+            }
+        }
+
+        Ok(())
+    }
 }
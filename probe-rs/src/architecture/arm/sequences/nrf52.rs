@@ -43,8 +43,42 @@ impl Nrf52 {
         let status = iface.read_raw_ap_register(ctrl_ap, APPROTECTSTATUS)?;
         Ok(status != 0)
     }
+
+    /// Erases the flash, the UICR and the RAM with the ERASEALL register of the CTRL-AP,
+    /// which also removes the access port protection.
+    fn erase_all(
+        &self,
+        iface: &mut dyn ArmProbeInterface,
+        ctrl_ap: ApAddress,
+    ) -> Result<(), ArmError> {
+        // Reset
+        iface.write_raw_ap_register(ctrl_ap, RESET, 1)?;
+        iface.write_raw_ap_register(ctrl_ap, RESET, 0)?;
+
+        // Start erase
+        iface.write_raw_ap_register(ctrl_ap, ERASEALL, 1)?;
+
+        // Wait for erase done
+        while iface.read_raw_ap_register(ctrl_ap, ERASEALLSTATUS)? != 0 {}
+
+        // Reset again
+        iface.write_raw_ap_register(ctrl_ap, RESET, 1)?;
+        iface.write_raw_ap_register(ctrl_ap, RESET, 0)?;
+
+        if !self.is_core_unlocked(iface, ctrl_ap)? {
+            return Err(ArmDebugSequenceError::custom("Could not unlock core").into());
+        }
+
+        Ok(())
+    }
 }
 
+/// The CTRL-AP of the nRF52.
+const CTRL_AP: ApAddress = ApAddress {
+    ap: 1,
+    dp: DpAddress::Default,
+};
+
 mod clock {
     use crate::architecture::arm::{memory::adi_v5_memory_interface::ArmProbe, ArmError};
     use bitfield::bitfield;
@@ -87,10 +121,7 @@ impl ArmDebugSequence for Nrf52 {
         _default_ap: MemoryAp,
         permissions: &crate::Permissions,
     ) -> Result<(), ArmError> {
-        let ctrl_ap = ApAddress {
-            ap: 1,
-            dp: DpAddress::Default,
-        };
+        let ctrl_ap = CTRL_AP;
 
         tracing::info!("Checking if core is unlocked");
         if self.is_core_unlocked(iface, ctrl_ap)? {
@@ -103,27 +134,20 @@ impl ArmDebugSequence for Nrf52 {
             .erase_all()
             .map_err(|MissingPermissions(desc)| ArmError::MissingPermissions(desc))?;
 
-        // Reset
-        iface.write_raw_ap_register(ctrl_ap, RESET, 1)?;
-        iface.write_raw_ap_register(ctrl_ap, RESET, 0)?;
-
-        // Start erase
-        iface.write_raw_ap_register(ctrl_ap, ERASEALL, 1)?;
-
-        // Wait for erase done
-        while iface.read_raw_ap_register(ctrl_ap, ERASEALLSTATUS)? != 0 {}
-
-        // Reset again
-        iface.write_raw_ap_register(ctrl_ap, RESET, 1)?;
-        iface.write_raw_ap_register(ctrl_ap, RESET, 0)?;
-
-        if !self.is_core_unlocked(iface, ctrl_ap)? {
-            return Err(ArmDebugSequenceError::custom("Could not unlock core").into());
-        }
+        self.erase_all(iface, ctrl_ap)?;
 
         Err(ArmError::ReAttachRequired)
     }
 
+    fn debug_device_recover(
+        &self,
+        iface: &mut dyn ArmProbeInterface,
+        _default_ap: MemoryAp,
+    ) -> Result<(), ArmError> {
+        tracing::info!("Recovering device with ERASEALL");
+        self.erase_all(iface, CTRL_AP)
+    }
+
     fn trace_start(
         &self,
         interface: &mut dyn ArmProbeInterface,
@@ -172,3 +196,65 @@ impl From<ComponentError> for ArmError {
         ArmError::DebugSequence(ArmDebugSequenceError::custom(value))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use super::{APPROTECTSTATUS, CTRL_AP, ERASEALL, ERASEALLSTATUS, RESET};
+    use crate::{probe::fake_probe::FakeProbe, Error, Permissions};
+
+    /// Recovers an nRF52840, whose CTRL-AP reports `approtect_status` after the erase, and
+    /// returns the writes to the CTRL-AP.
+    fn recover(approtect_status: u32) -> (Result<(), Error>, Vec<(u8, u32)>) {
+        let ctrl_ap_writes = Arc::new(Mutex::new(Vec::new()));
+
+        let mut probe = FakeProbe::new();
+        probe.set_ap_register_read_handler(Box::new(move |ap, address| {
+            assert_eq!(ap, CTRL_AP);
+            match address {
+                ERASEALLSTATUS => Ok(0),
+                APPROTECTSTATUS => Ok(approtect_status),
+                _ => panic!("Unexpected read of CTRL-AP register {address:#04x}"),
+            }
+        }));
+
+        let writes = ctrl_ap_writes.clone();
+        probe.set_ap_register_write_handler(Box::new(move |ap, address, value| {
+            assert_eq!(ap, CTRL_AP);
+            writes.lock().unwrap().push((address, value));
+            Ok(())
+        }));
+
+        let result = probe
+            .into_probe()
+            .recover("nRF52840_xxAA", Permissions::new().allow_erase_all());
+
+        let writes = ctrl_ap_writes.lock().unwrap().clone();
+        (result, writes)
+    }
+
+    #[test]
+    fn recover_erases_all() {
+        let (result, writes) = recover(1);
+
+        result.unwrap();
+        assert_eq!(
+            writes,
+            [
+                (RESET, 1),
+                (RESET, 0),
+                (ERASEALL, 1),
+                (RESET, 1),
+                (RESET, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn recover_with_core_still_locked() {
+        let (result, _) = recover(0);
+
+        assert!(result.is_err());
+    }
+}
@@ -13,12 +13,12 @@ use crate::{
         core::armv7m::{Aircr, Demcr, Dhcsr},
         dp::{Abort, Ctrl, DpAccess, Select, DPIDR},
         memory::adi_v5_memory_interface::ArmProbe,
-        ApAddress, ArmCommunicationInterface, ArmError, DapAccess, DpAddress,
+        ApAddress, ArmCommunicationInterface, ArmError, ArmProbeInterface, DapAccess, DpAddress,
     },
     core::MemoryMappedRegister,
 };
 
-use super::ArmDebugSequence;
+use super::{ArmDebugSequence, ArmDebugSequenceError};
 
 /// Start the debug port, and return if the device was (true) or wasn't (false)
 /// powered down.
@@ -95,6 +95,174 @@ impl LPC55Sxx {
     pub fn create() -> Arc<dyn ArmDebugSequence> {
         Arc::new(Self(()))
     }
+
+    /// Authenticates the debugger with the debug authentication protocol of the debug mailbox,
+    /// which enables debug access on devices that have it disabled in the CMPA.
+    ///
+    /// The device answers with a debug authentication challenge (DAC), which is passed to
+    /// `respond`. It has to return the debug authentication response (DAR), signed with the
+    /// debug credential of the device, e.g. created with the `nxpdebugmbox` tool of SPSDK.
+    pub fn debug_authenticate(
+        interface: &mut dyn ArmProbeInterface,
+        dp: DpAddress,
+        respond: impl FnOnce(&[u32]) -> Vec<u32>,
+    ) -> Result<(), ArmError> {
+        let ap = debug_mailbox::ap(dp);
+
+        debug_mailbox::start(interface, ap)?;
+
+        let challenge = debug_mailbox::command(
+            interface,
+            ap,
+            debug_mailbox::DEBUG_AUTHENTICATION_START,
+            &[],
+            debug_mailbox::COMMAND_TIMEOUT,
+        )?;
+
+        let response = respond(&challenge);
+
+        debug_mailbox::command(
+            interface,
+            ap,
+            debug_mailbox::DEBUG_AUTHENTICATION_RESPONSE,
+            &response,
+            debug_mailbox::COMMAND_TIMEOUT,
+        )?;
+
+        Ok(())
+    }
+}
+
+/// The debug mailbox of the LPC55Sxx, which is used to communicate with the ROM of the device,
+/// even if debug access to the core is disabled.
+mod debug_mailbox {
+    use std::time::{Duration, Instant};
+
+    use super::ArmDebugSequenceError;
+    use crate::architecture::arm::{ApAddress, ArmError, ArmProbeInterface, DpAddress};
+
+    /// The control and status register of the DM-AP.
+    const CSW: u8 = 0x00;
+    /// The request register of the DM-AP.
+    const REQUEST: u8 = 0x04;
+    /// The return register of the DM-AP.
+    const RETURN: u8 = 0x08;
+    /// The identification register of the DM-AP.
+    const IDR: u8 = 0xFC;
+
+    /// The value of the IDR of the DM-AP.
+    const IDR_VALUE: u32 = 0x002A_0000;
+
+    const CSW_RESYNCH_REQ: u32 = 1 << 0;
+    const CSW_REQ_PENDING: u32 = 1 << 1;
+    const CSW_CHIP_RESET_REQ: u32 = 1 << 5;
+
+    /// Exchanged with the ROM to acknowledge every word of a request or a response.
+    const ACK_TOKEN: u32 = 0xA5A5;
+
+    pub const ERASE_FLASH: u16 = 0x02;
+    pub const DEBUG_AUTHENTICATION_START: u16 = 0x10;
+    pub const DEBUG_AUTHENTICATION_RESPONSE: u16 = 0x11;
+
+    /// The time the ROM takes to answer a command.
+    pub const COMMAND_TIMEOUT: Duration = Duration::from_secs(1);
+    /// The maximum time a mass erase can take.
+    pub const ERASE_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Returns the address of the DM-AP on the debug port `dp`.
+    pub fn ap(dp: DpAddress) -> ApAddress {
+        ApAddress { dp, ap: 2 }
+    }
+
+    /// Resets the device into the ROM, so it accepts commands through the debug mailbox.
+    pub fn start(interface: &mut dyn ArmProbeInterface, ap: ApAddress) -> Result<(), ArmError> {
+        let idr = interface.read_raw_ap_register(ap, IDR)?;
+        if idr != IDR_VALUE {
+            return Err(ArmDebugSequenceError::custom(format!(
+                "No debug mailbox found, the IDR of AP {} is {idr:#010x}",
+                ap.ap
+            ))
+            .into());
+        }
+
+        interface.write_raw_ap_register(ap, CSW, CSW_RESYNCH_REQ | CSW_CHIP_RESET_REQ)?;
+
+        // The bits are cleared by the device, once the ROM is ready.
+        wait_for(interface, ap, COMMAND_TIMEOUT, |csw| csw == 0)
+    }
+
+    /// Executes `command` with the `parameters` and returns the response of the ROM.
+    pub fn command(
+        interface: &mut dyn ArmProbeInterface,
+        ap: ApAddress,
+        command: u16,
+        parameters: &[u32],
+        timeout: Duration,
+    ) -> Result<Vec<u32>, ArmError> {
+        interface.write_raw_ap_register(ap, REQUEST, request(command, parameters.len()))?;
+
+        for parameter in parameters {
+            let ack = read_return(interface, ap, COMMAND_TIMEOUT)?;
+            if ack & 0xFFFF != ACK_TOKEN {
+                return Err(ArmDebugSequenceError::custom(format!(
+                    "The debug mailbox did not acknowledge a parameter of command {command:#04x}: {ack:#010x}"
+                ))
+                .into());
+            }
+            interface.write_raw_ap_register(ap, REQUEST, *parameter)?;
+        }
+
+        let result = read_return(interface, ap, timeout)?;
+        let status = result & 0xFFFF;
+        if status != 0 {
+            return Err(ArmDebugSequenceError::custom(format!(
+                "Debug mailbox command {command:#04x} failed with status {status:#06x}"
+            ))
+            .into());
+        }
+
+        let len = (result >> 16) & 0x7FFF;
+        let mut response = Vec::with_capacity(len as usize);
+        for remaining in (1..=len).rev() {
+            interface.write_raw_ap_register(ap, REQUEST, (remaining << 16) | ACK_TOKEN)?;
+            response.push(read_return(interface, ap, COMMAND_TIMEOUT)?);
+        }
+
+        Ok(response)
+    }
+
+    /// The request word of `command`, which announces the number of parameters in the upper half.
+    fn request(command: u16, parameters: usize) -> u32 {
+        ((parameters as u32) << 16) | command as u32
+    }
+
+    fn read_return(
+        interface: &mut dyn ArmProbeInterface,
+        ap: ApAddress,
+        timeout: Duration,
+    ) -> Result<u32, ArmError> {
+        wait_for(interface, ap, timeout, |csw| csw & CSW_REQ_PENDING == 0)?;
+        interface.read_raw_ap_register(ap, RETURN)
+    }
+
+    /// Waits until `condition` is true for the CSW of the DM-AP.
+    fn wait_for(
+        interface: &mut dyn ArmProbeInterface,
+        ap: ApAddress,
+        timeout: Duration,
+        condition: impl Fn(u32) -> bool,
+    ) -> Result<(), ArmError> {
+        let start = Instant::now();
+        loop {
+            if condition(interface.read_raw_ap_register(ap, CSW)?) {
+                return Ok(());
+            }
+            if start.elapsed() > timeout {
+                return Err(ArmError::Timeout);
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
 }
 
 impl ArmDebugSequence for LPC55Sxx {
@@ -114,6 +282,27 @@ impl ArmDebugSequence for LPC55Sxx {
         Ok(())
     }
 
+    fn debug_device_recover(
+        &self,
+        interface: &mut dyn ArmProbeInterface,
+        default_ap: MemoryAp,
+    ) -> Result<(), ArmError> {
+        let ap = debug_mailbox::ap(default_ap.ap_address().dp);
+
+        debug_mailbox::start(interface, ap)?;
+
+        tracing::info!("Mass erasing device through the debug mailbox");
+        debug_mailbox::command(
+            interface,
+            ap,
+            debug_mailbox::ERASE_FLASH,
+            &[],
+            debug_mailbox::ERASE_TIMEOUT,
+        )?;
+
+        Ok(())
+    }
+
     fn reset_catch_set(
         &self,
         interface: &mut dyn ArmProbe,
@@ -570,3 +759,158 @@ impl ArmDebugSequence for MIMXRT11xx {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::{
+        collections::VecDeque,
+        sync::{Arc, Mutex},
+    };
+
+    use super::LPC55Sxx;
+    use crate::{
+        architecture::arm::{ArmProbeInterface, DpAddress},
+        probe::fake_probe::FakeProbe,
+        Permissions, Probe,
+    };
+
+    /// The state of a debug mailbox, which answers with scripted return values.
+    #[derive(Default)]
+    struct DebugMailbox {
+        csw_writes: Vec<u32>,
+        requests: Vec<u32>,
+        returns: VecDeque<u32>,
+    }
+
+    fn probe_with_mailbox(mailbox: &Arc<Mutex<DebugMailbox>>) -> Probe {
+        let mut probe = FakeProbe::new();
+
+        let state = mailbox.clone();
+        probe.set_ap_register_read_handler(Box::new(move |ap, address| {
+            assert_eq!(ap.ap, 2);
+            match address {
+                0x00 => Ok(0),
+                0x08 => Ok(state.lock().unwrap().returns.pop_front().unwrap()),
+                0xFC => Ok(0x002A_0000),
+                _ => panic!("Unexpected read of DM-AP register {address:#04x}"),
+            }
+        }));
+
+        let state = mailbox.clone();
+        probe.set_ap_register_write_handler(Box::new(move |ap, address, value| {
+            assert_eq!(ap.ap, 2);
+            let mut state = state.lock().unwrap();
+            match address {
+                0x00 => state.csw_writes.push(value),
+                0x04 => state.requests.push(value),
+                _ => panic!("Unexpected write of DM-AP register {address:#04x}"),
+            }
+            Ok(())
+        }));
+
+        probe.into_probe()
+    }
+
+    fn arm_interface(mut probe: Probe) -> Box<dyn ArmProbeInterface> {
+        probe.attach_to_unspecified().unwrap();
+
+        probe
+            .try_into_arm_interface()
+            .unwrap()
+            .initialize(LPC55Sxx::create())
+            .map_err(|(_, e)| e)
+            .unwrap()
+    }
+
+    #[test]
+    fn recover_erases_through_debug_mailbox() {
+        let mailbox = Arc::new(Mutex::new(DebugMailbox {
+            returns: [0].into(),
+            ..Default::default()
+        }));
+
+        probe_with_mailbox(&mailbox)
+            .recover("LPC55S69JBD100", Permissions::new().allow_erase_all())
+            .unwrap();
+
+        let mailbox = mailbox.lock().unwrap();
+        assert_eq!(mailbox.csw_writes, [0x21]);
+        assert_eq!(mailbox.requests, [0x02]);
+    }
+
+    #[test]
+    fn recover_reports_failed_erase() {
+        let mailbox = Arc::new(Mutex::new(DebugMailbox {
+            returns: [0x0005].into(),
+            ..Default::default()
+        }));
+
+        let result = probe_with_mailbox(&mailbox)
+            .recover("LPC55S69JBD100", Permissions::new().allow_erase_all());
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn recover_requires_erase_all_permission() {
+        let mailbox = Arc::new(Mutex::new(DebugMailbox::default()));
+
+        let result = probe_with_mailbox(&mailbox).recover("LPC55S69JBD100", Permissions::new());
+
+        assert!(result.is_err());
+        assert!(mailbox.lock().unwrap().requests.is_empty());
+    }
+
+    #[test]
+    fn debug_authentication() {
+        let mailbox = Arc::new(Mutex::new(DebugMailbox {
+            returns: [
+                // The challenge has two words.
+                2 << 16,
+                0x1111_1111,
+                0x2222_2222,
+                // Both words of the response are acknowledged.
+                0xA5A5,
+                0xA5A5,
+                0,
+            ]
+            .into(),
+            ..Default::default()
+        }));
+
+        let mut interface = arm_interface(probe_with_mailbox(&mailbox));
+
+        LPC55Sxx::debug_authenticate(&mut *interface, DpAddress::Default, |challenge| {
+            assert_eq!(challenge, [0x1111_1111, 0x2222_2222]);
+            vec![0xAAAA_AAAA, 0xBBBB_BBBB]
+        })
+        .unwrap();
+
+        let mailbox = mailbox.lock().unwrap();
+        assert_eq!(
+            mailbox.requests,
+            [
+                0x10,
+                (2 << 16) | 0xA5A5,
+                (1 << 16) | 0xA5A5,
+                (2 << 16) | 0x11,
+                0xAAAA_AAAA,
+                0xBBBB_BBBB,
+            ]
+        );
+        assert!(mailbox.returns.is_empty());
+    }
+
+    #[test]
+    fn debug_authentication_requires_debug_mailbox() {
+        let mut probe = FakeProbe::new();
+        probe.set_ap_register_read_handler(Box::new(|_, _| Ok(0x2477_0011)));
+
+        let mut interface = arm_interface(probe.into_probe());
+
+        let result = LPC55Sxx::debug_authenticate(&mut *interface, DpAddress::Default, |_| {
+            unreachable!("There is no challenge without a debug mailbox")
+        });
+        assert!(result.is_err());
+    }
+}
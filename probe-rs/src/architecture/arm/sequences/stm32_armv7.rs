@@ -7,15 +7,20 @@
 //! component at a different address which requires clock gating, or the STM32L5 or STM32U5 which
 //! are ARMv8, or the STM32H7 which is ARMv7 but has a more complicated DBGMCU at a different
 //! address.
+//!
+//! Devices with the FLASH_OPTCR register (STM32F2/4/7) can be recovered by regressing the read
+//! protection to level 0.
 
 use std::sync::Arc;
 
 use probe_rs_target::CoreType;
 
-use super::ArmDebugSequence;
+use super::{ArmDebugSequence, ArmDebugSequenceError};
 use crate::architecture::arm::{
-    ap::MemoryAp, component::TraceSink, memory::CoresightComponent, ArmError, ArmProbeInterface,
+    ap::MemoryAp, component::TraceSink, core::armv7m::Dhcsr, memory::CoresightComponent, ArmError,
+    ArmProbeInterface,
 };
+use crate::core::MemoryMappedRegister;
 
 /// Marker structure for most ARMv7 STM32 devices.
 pub struct Stm32Armv7 {}
@@ -62,6 +67,75 @@ mod dbgmcu {
             memory.write_word_32(DBGMCU + Self::ADDRESS, self.0)
         }
     }
+
+    /// Reads the device identifier from the IDCODE register.
+    pub fn device_id(memory: &mut dyn ArmProbe) -> Result<u16, ArmError> {
+        let idcode = memory.read_word_32(DBGMCU)?;
+        Ok((idcode & 0xFFF) as u16)
+    }
+}
+
+/// The option bytes of the STM32F2, STM32F4 and STM32F7, which are programmed through the FLASH_OPTCR register.
+mod option_bytes {
+    use std::time::{Duration, Instant};
+
+    use crate::architecture::arm::{memory::adi_v5_memory_interface::ArmProbe, ArmError};
+
+    /// Device identifiers of the STM32F2, STM32F4 and STM32F7 devices.
+    pub const DEVICE_IDS: &[u16] = &[
+        0x411, 0x413, 0x419, 0x421, 0x423, 0x431, 0x433, 0x434, 0x441, 0x449, 0x451, 0x452, 0x458,
+        0x463,
+    ];
+
+    const FLASH: u64 = 0x4002_3C00;
+    const OPTKEYR: u64 = FLASH + 0x08;
+    const SR: u64 = FLASH + 0x0C;
+    const OPTCR: u64 = FLASH + 0x14;
+
+    const OPTKEY1: u32 = 0x0819_2A3B;
+    const OPTKEY2: u32 = 0x4C5D_6E7F;
+
+    const SR_BSY: u32 = 1 << 16;
+    const OPTCR_OPTLOCK: u32 = 1 << 0;
+    const OPTCR_OPTSTRT: u32 = 1 << 1;
+    const OPTCR_RDP_MASK: u32 = 0xFF << 8;
+    const OPTCR_RDP_LEVEL_0: u32 = 0xAA << 8;
+
+    /// Regressing the read protection mass erases the flash, which can take a while for the large devices.
+    const TIMEOUT: Duration = Duration::from_secs(40);
+
+    /// Sets the read protection to level 0, which mass erases the flash.
+    pub fn regress_read_protection(memory: &mut dyn ArmProbe) -> Result<(), ArmError> {
+        let optcr = memory.read_word_32(OPTCR)?;
+        if optcr & OPTCR_RDP_MASK == OPTCR_RDP_LEVEL_0 {
+            tracing::info!("Read protection is already at level 0");
+            return Ok(());
+        }
+
+        if optcr & OPTCR_OPTLOCK != 0 {
+            memory.write_word_32(OPTKEYR, OPTKEY1)?;
+            memory.write_word_32(OPTKEYR, OPTKEY2)?;
+        }
+
+        tracing::info!("Regressing read protection to level 0");
+        let optcr = (memory.read_word_32(OPTCR)? & !OPTCR_RDP_MASK) | OPTCR_RDP_LEVEL_0;
+        memory.write_word_32(OPTCR, optcr)?;
+        memory.write_word_32(OPTCR, optcr | OPTCR_OPTSTRT)?;
+
+        let start = Instant::now();
+        while memory.read_word_32(SR)? & SR_BSY != 0 {
+            if start.elapsed() > TIMEOUT {
+                return Err(ArmError::Timeout);
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+
+        // Lock the option bytes again.
+        let optcr = memory.read_word_32(OPTCR)?;
+        memory.write_word_32(OPTCR, optcr | OPTCR_OPTLOCK)?;
+
+        Ok(())
+    }
 }
 
 impl ArmDebugSequence for Stm32Armv7 {
@@ -82,6 +156,31 @@ impl ArmDebugSequence for Stm32Armv7 {
         Ok(())
     }
 
+    fn debug_device_recover(
+        &self,
+        interface: &mut dyn ArmProbeInterface,
+        default_ap: MemoryAp,
+    ) -> Result<(), ArmError> {
+        let mut memory = interface.memory_interface(default_ap)?;
+
+        let device_id = dbgmcu::device_id(&mut *memory)?;
+        if !option_bytes::DEVICE_IDS.contains(&device_id) {
+            return Err(ArmDebugSequenceError::custom(format!(
+                "Device recovery is not supported for the STM32 with device id {device_id:#05x}"
+            ))
+            .into());
+        }
+
+        // Halt the core, so the firmware can't interfere with the option byte programming.
+        let mut dhcsr = Dhcsr(0);
+        dhcsr.set_c_halt(true);
+        dhcsr.set_c_debugen(true);
+        dhcsr.enable_write();
+        memory.write_word_32(Dhcsr::get_mmio_address(), dhcsr.0)?;
+
+        option_bytes::regress_read_protection(&mut *memory)
+    }
+
     fn debug_core_stop(
         &self,
         interface: &mut dyn ArmProbeInterface,
@@ -120,3 +219,79 @@ impl ArmDebugSequence for Stm32Armv7 {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::Stm32Armv7;
+    use crate::{
+        architecture::arm::{
+            ap::MemoryAp, sequences::ArmDebugSequence, ApAddress, ArmError, ArmProbeInterface,
+            DpAddress,
+        },
+        probe::fake_probe::FakeProbe,
+    };
+
+    const OPTCR: u64 = 0x4002_3C14;
+    const OPTKEYR: u64 = 0x4002_3C08;
+
+    /// Runs the recovery of a device with the id `device_id` and the option control register
+    /// set to `optcr`, and returns the option key register and option control register afterwards.
+    fn recover(device_id: u32, optcr: u32) -> (Result<(), ArmError>, u32, u32) {
+        let mut probe = FakeProbe::new();
+        probe.add_memory_region(0xE004_2000, device_id.to_le_bytes().to_vec());
+        probe.add_memory_region(0xE000_EDF0, vec![0; 4]);
+
+        let mut flash = vec![0; 0x18];
+        flash[0x14..].copy_from_slice(&optcr.to_le_bytes());
+        probe.add_memory_region(0x4002_3C00, flash);
+
+        let mut probe = probe.into_probe();
+        probe.attach_to_unspecified().unwrap();
+        let sequence = Stm32Armv7::create();
+        let mut interface: Box<dyn ArmProbeInterface> = probe
+            .try_into_arm_interface()
+            .unwrap()
+            .initialize(sequence.clone())
+            .map_err(|(_, e)| e)
+            .unwrap();
+
+        let default_ap = MemoryAp::new(ApAddress {
+            dp: DpAddress::Default,
+            ap: 0,
+        });
+        let result = sequence.debug_device_recover(&mut *interface, default_ap);
+
+        let mut memory = interface.memory_interface(default_ap).unwrap();
+        let optkeyr = memory.read_word_32(OPTKEYR).unwrap();
+        let optcr = memory.read_word_32(OPTCR).unwrap();
+
+        (result, optkeyr, optcr)
+    }
+
+    #[test]
+    fn recover_regresses_read_protection() {
+        let (result, optkeyr, optcr) = recover(0x1000_6413, 0x0FFF_55ED);
+
+        result.unwrap();
+        assert_eq!(optkeyr, 0x4C5D_6E7F);
+        // RDP level 0, with the option bytes locked again.
+        assert_eq!(optcr, 0x0FFF_AAEF);
+    }
+
+    #[test]
+    fn recover_at_read_protection_level_0() {
+        let (result, optkeyr, optcr) = recover(0x1000_6413, 0x0FFF_AAED);
+
+        result.unwrap();
+        assert_eq!(optkeyr, 0);
+        assert_eq!(optcr, 0x0FFF_AAED);
+    }
+
+    #[test]
+    fn recover_unsupported_device() {
+        let (result, _, optcr) = recover(0x1000_6415, 0x0FFF_55ED);
+
+        assert!(result.is_err());
+        assert_eq!(optcr, 0x0FFF_55ED);
+    }
+}
@@ -0,0 +1,222 @@
+//! A timeline of exception entries and exits, decoded from the exception trace of the DWT.
+//!
+//! Exception tracing is enabled by [`Session::setup_tracing`](crate::Session::setup_tracing).
+
+use std::collections::HashMap;
+use std::fmt;
+
+use super::itm::{ExceptionAction, ItmDecoder, ItmPacket};
+
+/// An exception of a Cortex-M core, as identified by its exception number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Exception {
+    /// Thread mode, reported when returning from the last active exception.
+    ThreadMode,
+    /// The reset exception.
+    Reset,
+    /// The non-maskable interrupt.
+    Nmi,
+    /// The hard fault exception.
+    HardFault,
+    /// The memory management fault exception.
+    MemManage,
+    /// The bus fault exception.
+    BusFault,
+    /// The usage fault exception.
+    UsageFault,
+    /// The secure fault exception of ARMv8-M.
+    SecureFault,
+    /// The supervisor call exception.
+    SvCall,
+    /// The debug monitor exception.
+    DebugMonitor,
+    /// The pendable service call exception.
+    PendSv,
+    /// The system tick timer exception.
+    SysTick,
+    /// An external interrupt, with its IRQ number.
+    Interrupt(u16),
+    /// A reserved exception number.
+    Reserved(u16),
+}
+
+impl Exception {
+    /// The exception number of the exception.
+    pub fn number(&self) -> u16 {
+        match self {
+            Exception::ThreadMode => 0,
+            Exception::Reset => 1,
+            Exception::Nmi => 2,
+            Exception::HardFault => 3,
+            Exception::MemManage => 4,
+            Exception::BusFault => 5,
+            Exception::UsageFault => 6,
+            Exception::SecureFault => 7,
+            Exception::SvCall => 11,
+            Exception::DebugMonitor => 12,
+            Exception::PendSv => 14,
+            Exception::SysTick => 15,
+            Exception::Interrupt(irq) => irq + 16,
+            Exception::Reserved(number) => *number,
+        }
+    }
+
+    /// The name of the exception.
+    ///
+    /// External interrupts are named using `interrupt_names`, which maps IRQ numbers to the
+    /// names of the device specific interrupts, e.g. from the SVD file of the device.
+    /// Interrupts which are not found are named `IRQ<n>`.
+    pub fn name(&self, interrupt_names: &HashMap<u16, String>) -> String {
+        match self {
+            Exception::Interrupt(irq) => match interrupt_names.get(irq) {
+                Some(name) => name.clone(),
+                None => self.to_string(),
+            },
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl From<u16> for Exception {
+    fn from(number: u16) -> Self {
+        match number {
+            0 => Exception::ThreadMode,
+            1 => Exception::Reset,
+            2 => Exception::Nmi,
+            3 => Exception::HardFault,
+            4 => Exception::MemManage,
+            5 => Exception::BusFault,
+            6 => Exception::UsageFault,
+            7 => Exception::SecureFault,
+            11 => Exception::SvCall,
+            12 => Exception::DebugMonitor,
+            14 => Exception::PendSv,
+            15 => Exception::SysTick,
+            16.. => Exception::Interrupt(number - 16),
+            _ => Exception::Reserved(number),
+        }
+    }
+}
+
+impl fmt::Display for Exception {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Exception::ThreadMode => write!(f, "Thread mode"),
+            Exception::Reset => write!(f, "Reset"),
+            Exception::Nmi => write!(f, "NMI"),
+            Exception::HardFault => write!(f, "HardFault"),
+            Exception::MemManage => write!(f, "MemManage"),
+            Exception::BusFault => write!(f, "BusFault"),
+            Exception::UsageFault => write!(f, "UsageFault"),
+            Exception::SecureFault => write!(f, "SecureFault"),
+            Exception::SvCall => write!(f, "SVCall"),
+            Exception::DebugMonitor => write!(f, "DebugMonitor"),
+            Exception::PendSv => write!(f, "PendSV"),
+            Exception::SysTick => write!(f, "SysTick"),
+            Exception::Interrupt(irq) => write!(f, "IRQ{irq}"),
+            Exception::Reserved(number) => write!(f, "Reserved exception {number}"),
+        }
+    }
+}
+
+/// An entry into, exit from or return to an exception.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExceptionEvent {
+    /// The sum of all local timestamps received before the event.
+    ///
+    /// The ITM sends local timestamps after the packets they belong to, so this is the time
+    /// of the previous packets which had a timestamp. Always `0` if timestamps are not enabled.
+    pub timestamp: u64,
+    /// The exception.
+    pub exception: Exception,
+    /// What happened to the exception.
+    pub action: ExceptionAction,
+}
+
+/// Decodes the exception trace from the SWO trace data.
+#[derive(Debug, Default)]
+pub struct ExceptionTraceDecoder {
+    decoder: ItmDecoder,
+    timestamp: u64,
+}
+
+impl ExceptionTraceDecoder {
+    /// Creates a new decoder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decodes the exception events in `data`, which continues the data passed to previous calls.
+    ///
+    /// All other packets are ignored.
+    pub fn decode(&mut self, data: &[u8]) -> Vec<ExceptionEvent> {
+        let mut events = Vec::new();
+
+        for packet in self.decoder.decode(data) {
+            match packet {
+                ItmPacket::LocalTimestamp { delta } => self.timestamp += delta as u64,
+                ItmPacket::ExceptionTrace { exception, action } => events.push(ExceptionEvent {
+                    timestamp: self.timestamp,
+                    exception: exception.into(),
+                    action,
+                }),
+                ItmPacket::Overflow => tracing::warn!("ITM overflow, exception events were lost"),
+                _ => (),
+            }
+        }
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::{Exception, ExceptionAction, ExceptionEvent, ExceptionTraceDecoder};
+
+    #[test]
+    fn exception_numbers() {
+        for number in 0..64 {
+            assert_eq!(Exception::from(number).number(), number);
+        }
+
+        assert_eq!(Exception::from(3), Exception::HardFault);
+        assert_eq!(Exception::from(21), Exception::Interrupt(5));
+    }
+
+    #[test]
+    fn exception_names() {
+        let names = HashMap::from([(5, "UART0".to_string())]);
+
+        assert_eq!(Exception::Interrupt(5).name(&names), "UART0");
+        assert_eq!(Exception::Interrupt(6).name(&names), "IRQ6");
+        assert_eq!(Exception::SysTick.name(&names), "SysTick");
+    }
+
+    #[test]
+    fn decode_timeline() {
+        let mut decoder = ExceptionTraceDecoder::new();
+
+        assert_eq!(
+            decoder.decode(&[0x0E, 0x15, 0x10, 0xC0, 0x20, 0x0E, 0x15, 0x20, 0x0E, 0x00, 0x30]),
+            vec![
+                ExceptionEvent {
+                    timestamp: 0,
+                    exception: Exception::Interrupt(5),
+                    action: ExceptionAction::Entered,
+                },
+                ExceptionEvent {
+                    timestamp: 32,
+                    exception: Exception::Interrupt(5),
+                    action: ExceptionAction::Exited,
+                },
+                ExceptionEvent {
+                    timestamp: 32,
+                    exception: Exception::ThreadMode,
+                    action: ExceptionAction::Returned,
+                },
+            ]
+        );
+    }
+}
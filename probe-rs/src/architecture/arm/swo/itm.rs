@@ -0,0 +1,297 @@
+//! Decoding of the ITM and DWT packets which are sent over SWO.
+//!
+//! See the ARMv7-M Architecture Reference Manual, Appendix D4 "Debug ITM and DWT Packet Protocol".
+
+/// A packet sent by the ITM, including the packets of the DWT which are forwarded by the ITM.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ItmPacket {
+    /// A synchronization packet.
+    Sync,
+    /// The ITM FIFO overflowed, and packets were lost.
+    Overflow,
+    /// A local timestamp, with the number of timestamp clock cycles since the previous local timestamp.
+    LocalTimestamp {
+        /// The number of timestamp clock cycles since the previous local timestamp.
+        delta: u32,
+    },
+    /// A software write to an ITM stimulus port.
+    Instrumentation {
+        /// The stimulus port which was written.
+        port: u8,
+        /// The written data, which is 1, 2 or 4 bytes long.
+        payload: Vec<u8>,
+    },
+    /// A periodic PC sample of the DWT.
+    PcSample {
+        /// The sampled PC, or `None` if the core was sleeping.
+        pc: Option<u32>,
+    },
+    /// An exception trace packet of the DWT.
+    ExceptionTrace {
+        /// The exception number. Numbers from 16 upwards are external interrupts.
+        exception: u16,
+        /// What happened to the exception.
+        action: ExceptionAction,
+    },
+    /// Any other packet of the DWT, e.g. a data trace packet.
+    Hardware {
+        /// The discriminator, which identifies the type of the packet.
+        discriminator: u8,
+        /// The data of the packet, which is 1, 2 or 4 bytes long.
+        payload: Vec<u8>,
+    },
+}
+
+/// The event reported by an exception trace packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExceptionAction {
+    /// The exception handler was entered.
+    Entered,
+    /// The exception handler was exited.
+    Exited,
+    /// Execution returned to the exception, e.g. after a higher priority exception was handled.
+    Returned,
+}
+
+/// Discriminator of exception trace packets.
+const DISCRIMINATOR_EXCEPTION_TRACE: u8 = 1;
+/// Discriminator of periodic PC sample packets.
+const DISCRIMINATOR_PC_SAMPLE: u8 = 2;
+
+/// A decoder for the ITM packet stream.
+///
+/// The decoder keeps the bytes of incomplete packets, so the stream can be decoded in chunks,
+/// as it is read from the probe.
+#[derive(Debug, Default)]
+pub struct ItmDecoder {
+    buffer: Vec<u8>,
+}
+
+impl ItmDecoder {
+    /// Creates a new decoder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decodes all complete packets in `data`, which continues the data passed to previous calls.
+    pub fn decode(&mut self, data: &[u8]) -> Vec<ItmPacket> {
+        self.buffer.extend_from_slice(data);
+
+        let mut packets = Vec::new();
+        let mut offset = 0;
+
+        while let Some((packet, len)) = parse_packet(&self.buffer[offset..]) {
+            offset += len;
+            packets.extend(packet);
+        }
+
+        self.buffer.drain(..offset);
+
+        packets
+    }
+}
+
+/// Parses the packet at the start of `data`.
+///
+/// Returns `None` if `data` does not contain a complete packet. Otherwise, returns the packet and
+/// the number of bytes it used. Packets which are ignored, like global timestamps, are returned as
+/// `None` as well.
+fn parse_packet(data: &[u8]) -> Option<(Option<ItmPacket>, usize)> {
+    let header = *data.first()?;
+
+    match header {
+        0x00 => {
+            // A synchronization packet is at least 47 zero bits, followed by a one.
+            let zeros = data.iter().position(|&byte| byte != 0)?;
+
+            if data[zeros] == 0x80 {
+                Some((Some(ItmPacket::Sync), zeros + 1))
+            } else {
+                tracing::debug!("Invalid ITM synchronization packet");
+                Some((None, zeros))
+            }
+        }
+        0x70 => Some((Some(ItmPacket::Overflow), 1)),
+        // Global timestamps
+        0x94 | 0xB4 => {
+            let (_, len) = continuation_payload(&data[1..])?;
+            Some((None, len + 1))
+        }
+        _ if header & 0x0F == 0 => {
+            let delta = if header & 0x80 == 0 {
+                // Format 2, the timestamp is contained in the header.
+                ((header >> 4) & 0x7) as u32
+            } else {
+                let (delta, len) = continuation_payload(&data[1..])?;
+                return Some((Some(ItmPacket::LocalTimestamp { delta }), len + 1));
+            };
+
+            Some((Some(ItmPacket::LocalTimestamp { delta }), 1))
+        }
+        // Extension packets
+        _ if header & 0x0B == 0x08 => {
+            if header & 0x80 == 0 {
+                Some((None, 1))
+            } else {
+                let (_, len) = continuation_payload(&data[1..])?;
+                Some((None, len + 1))
+            }
+        }
+        _ if header & 0x03 != 0 => {
+            let size = match header & 0x03 {
+                0b01 => 1,
+                0b10 => 2,
+                _ => 4,
+            };
+
+            let payload = data.get(1..=size)?.to_vec();
+            let address = header >> 3;
+
+            let packet = if header & 0x04 == 0 {
+                ItmPacket::Instrumentation {
+                    port: address,
+                    payload,
+                }
+            } else if address == DISCRIMINATOR_PC_SAMPLE {
+                // A sleeping core is reported with a single byte payload.
+                ItmPacket::PcSample {
+                    pc: (size == 4).then(|| u32::from_le_bytes(payload.try_into().unwrap())),
+                }
+            } else if address == DISCRIMINATOR_EXCEPTION_TRACE && size == 2 {
+                let exception = u16::from_le_bytes([payload[0], payload[1] & 0x01]);
+
+                match (payload[1] >> 4) & 0x3 {
+                    0b01 => ItmPacket::ExceptionTrace {
+                        exception,
+                        action: ExceptionAction::Entered,
+                    },
+                    0b10 => ItmPacket::ExceptionTrace {
+                        exception,
+                        action: ExceptionAction::Exited,
+                    },
+                    0b11 => ItmPacket::ExceptionTrace {
+                        exception,
+                        action: ExceptionAction::Returned,
+                    },
+                    _ => ItmPacket::Hardware {
+                        discriminator: address,
+                        payload,
+                    },
+                }
+            } else {
+                ItmPacket::Hardware {
+                    discriminator: address,
+                    payload,
+                }
+            };
+
+            Some((Some(packet), size + 1))
+        }
+        _ => {
+            tracing::debug!("Reserved ITM packet header {:#04x}", header);
+            Some((None, 1))
+        }
+    }
+}
+
+/// Parses a payload where each byte contains 7 bits, and the top bit indicates that another byte follows.
+///
+/// Returns the value and the number of bytes used, or `None` if the payload is incomplete.
+fn continuation_payload(data: &[u8]) -> Option<(u32, usize)> {
+    let mut value = 0;
+
+    for (i, byte) in data.iter().enumerate() {
+        // Timestamps have at most 28 bits, any further bits of global timestamps are ignored.
+        if i < 4 {
+            value |= ((byte & 0x7F) as u32) << (7 * i);
+        }
+
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ExceptionAction, ItmDecoder, ItmPacket};
+
+    #[test]
+    fn decode_packets() {
+        let mut decoder = ItmDecoder::new();
+
+        let data = [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x80, // Sync
+            0x01, 0x41, // 'A' on stimulus port 0
+            0x17, 0x34, 0x12, 0x00, 0x08, // PC sample
+            0x15, 0x00, // PC sample while sleeping
+            0xC0, 0x81, 0x01, // Local timestamp 129
+            0x20, // Local timestamp 2
+            0x70, // Overflow
+        ];
+
+        assert_eq!(
+            decoder.decode(&data),
+            vec![
+                ItmPacket::Sync,
+                ItmPacket::Instrumentation {
+                    port: 0,
+                    payload: vec![0x41]
+                },
+                ItmPacket::PcSample {
+                    pc: Some(0x0800_1234)
+                },
+                ItmPacket::PcSample { pc: None },
+                ItmPacket::LocalTimestamp { delta: 129 },
+                ItmPacket::LocalTimestamp { delta: 2 },
+                ItmPacket::Overflow,
+            ]
+        );
+    }
+
+    #[test]
+    fn decode_split_packets() {
+        let mut decoder = ItmDecoder::new();
+
+        assert_eq!(decoder.decode(&[0x17, 0x34]), vec![]);
+        assert_eq!(
+            decoder.decode(&[0x12, 0x00, 0x08, 0x94, 0x81]),
+            vec![ItmPacket::PcSample {
+                pc: Some(0x0800_1234)
+            }]
+        );
+        // The global timestamp is skipped.
+        assert_eq!(
+            decoder.decode(&[0x01, 0x46, 0x02, 0x01]),
+            vec![ItmPacket::Hardware {
+                discriminator: 8,
+                payload: vec![0x02, 0x01]
+            }]
+        );
+    }
+
+    #[test]
+    fn decode_exception_trace() {
+        let mut decoder = ItmDecoder::new();
+
+        assert_eq!(
+            decoder.decode(&[0x0E, 0x1F, 0x10, 0x0E, 0x1F, 0x20, 0x0E, 0x00, 0x30]),
+            vec![
+                ItmPacket::ExceptionTrace {
+                    exception: 31,
+                    action: ExceptionAction::Entered
+                },
+                ItmPacket::ExceptionTrace {
+                    exception: 31,
+                    action: ExceptionAction::Exited
+                },
+                ItmPacket::ExceptionTrace {
+                    exception: 0,
+                    action: ExceptionAction::Returned
+                },
+            ]
+        );
+    }
+}
@@ -1,5 +1,15 @@
 //! SWO tracing related functions.
 
+mod exception_trace;
+mod itm;
+mod pc_sampling;
+
+pub use exception_trace::{Exception, ExceptionEvent, ExceptionTraceDecoder};
+pub use itm::{ExceptionAction, ItmDecoder, ItmPacket};
+pub use pc_sampling::{
+    FunctionSamples, PcHistogram, PcSample, PcSampleDecoder, PcSamplingInterval,
+};
+
 use crate::architecture::arm::communication_interface::ArmProbeInterface;
 
 use super::ArmError;
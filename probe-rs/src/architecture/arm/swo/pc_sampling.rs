@@ -0,0 +1,229 @@
+//! Statistical profiling, using the periodic PC samples of the DWT.
+//!
+//! Once tracing is set up with [`Session::setup_tracing`](crate::Session::setup_tracing), PC sampling
+//! is enabled with [`Session::enable_pc_sampling`](crate::Session::enable_pc_sampling). The trace data
+//! can then be decoded into [`PcSample`]s with a [`PcSampleDecoder`], and aggregated with a
+//! [`PcHistogram`].
+
+use std::collections::HashMap;
+
+use super::itm::{ItmDecoder, ItmPacket};
+use crate::debug::DebugInfo;
+
+/// The interval between two PC samples.
+///
+/// The DWT takes a sample after a configurable number of taps of the cycle counter,
+/// which happen every 64 or every 1024 cycles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PcSamplingInterval {
+    /// Use a tap every 1024 cycles instead of every 64 cycles.
+    pub(crate) cyctap: bool,
+    /// The number of taps between two samples, minus one.
+    pub(crate) postpreset: u8,
+}
+
+impl PcSamplingInterval {
+    /// Returns the shortest supported interval which is at least `cycles` long.
+    ///
+    /// Supported intervals are multiples of 64 cycles up to 1024 cycles, and multiples of
+    /// 1024 cycles up to 16384 cycles. Longer intervals are limited to 16384 cycles.
+    pub fn from_cycles(cycles: u32) -> Self {
+        // The number of taps is rounded up, so the interval is not shorter than requested.
+        let cycles = cycles.clamp(1, 1024 * 16);
+
+        if cycles <= 64 * 16 {
+            Self {
+                cyctap: false,
+                postpreset: ((cycles - 1) / 64) as u8,
+            }
+        } else {
+            Self {
+                cyctap: true,
+                postpreset: ((cycles - 1) / 1024) as u8,
+            }
+        }
+    }
+
+    /// The number of CPU cycles between two samples.
+    pub fn cycles(&self) -> u32 {
+        let tap = if self.cyctap { 1024 } else { 64 };
+
+        tap * (self.postpreset as u32 + 1)
+    }
+}
+
+/// A periodic sample of the program counter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PcSample {
+    /// The sum of all local timestamps received before the sample.
+    ///
+    /// The ITM sends local timestamps after the packets they belong to, so this is the time
+    /// of the previous packets which had a timestamp. Always `0` if timestamps are not enabled.
+    pub timestamp: u64,
+    /// The sampled PC, or `None` if the core was sleeping.
+    pub pc: Option<u32>,
+}
+
+/// Decodes the PC samples from the SWO trace data.
+#[derive(Debug, Default)]
+pub struct PcSampleDecoder {
+    decoder: ItmDecoder,
+    timestamp: u64,
+}
+
+impl PcSampleDecoder {
+    /// Creates a new decoder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decodes the PC samples in `data`, which continues the data passed to previous calls.
+    ///
+    /// All other packets are ignored.
+    pub fn decode(&mut self, data: &[u8]) -> Vec<PcSample> {
+        let mut samples = Vec::new();
+
+        for packet in self.decoder.decode(data) {
+            match packet {
+                ItmPacket::LocalTimestamp { delta } => self.timestamp += delta as u64,
+                ItmPacket::PcSample { pc } => samples.push(PcSample {
+                    timestamp: self.timestamp,
+                    pc,
+                }),
+                ItmPacket::Overflow => tracing::warn!("ITM overflow, PC samples were lost"),
+                _ => (),
+            }
+        }
+
+        samples
+    }
+}
+
+/// The number of samples which were taken in a function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionSamples {
+    /// The name of the function, or `None` if no function was found for the sampled PCs.
+    pub function: Option<String>,
+    /// The number of samples.
+    pub samples: u64,
+}
+
+/// A histogram of PC samples.
+#[derive(Debug, Default, Clone)]
+pub struct PcHistogram {
+    samples: HashMap<u32, u64>,
+    sleeping: u64,
+}
+
+impl PcHistogram {
+    /// Creates an empty histogram.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a sample to the histogram.
+    pub fn add(&mut self, sample: &PcSample) {
+        match sample.pc {
+            Some(pc) => *self.samples.entry(pc).or_default() += 1,
+            None => self.sleeping += 1,
+        }
+    }
+
+    /// The total number of samples, including the samples where the core was sleeping.
+    pub fn total_samples(&self) -> u64 {
+        self.samples.values().sum::<u64>() + self.sleeping
+    }
+
+    /// The number of samples where the core was sleeping.
+    pub fn sleep_samples(&self) -> u64 {
+        self.sleeping
+    }
+
+    /// The number of samples for each sampled PC.
+    pub fn pc_samples(&self) -> &HashMap<u32, u64> {
+        &self.samples
+    }
+
+    /// Aggregates the samples by function, using `debug_info` to find the function of each PC.
+    ///
+    /// Inlined functions are attributed to the function they are inlined into. The result is
+    /// sorted by the number of samples, starting with the most sampled function.
+    pub fn by_function(&self, debug_info: &DebugInfo) -> Vec<FunctionSamples> {
+        let mut functions: HashMap<Option<String>, u64> = HashMap::new();
+
+        for (&pc, &count) in &self.samples {
+            let function = debug_info
+                .function_name(pc as u64, false)
+                .unwrap_or_else(|error| {
+                    tracing::debug!("Failed to find function for PC {:#010x}: {}", pc, error);
+                    None
+                });
+
+            *functions.entry(function).or_default() += count;
+        }
+
+        let mut functions: Vec<_> = functions
+            .into_iter()
+            .map(|(function, samples)| FunctionSamples { function, samples })
+            .collect();
+
+        functions.sort_by(|a, b| {
+            b.samples
+                .cmp(&a.samples)
+                .then_with(|| a.function.cmp(&b.function))
+        });
+
+        functions
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PcHistogram, PcSample, PcSampleDecoder, PcSamplingInterval};
+
+    #[test]
+    fn sampling_intervals() {
+        assert_eq!(PcSamplingInterval::from_cycles(0).cycles(), 64);
+        assert_eq!(PcSamplingInterval::from_cycles(100).cycles(), 128);
+        assert_eq!(PcSamplingInterval::from_cycles(1024).cycles(), 1024);
+        assert_eq!(PcSamplingInterval::from_cycles(1025).cycles(), 2048);
+        assert_eq!(PcSamplingInterval::from_cycles(1_000_000).cycles(), 16384);
+    }
+
+    #[test]
+    fn decode_samples() {
+        let mut decoder = PcSampleDecoder::new();
+
+        let samples = decoder.decode(&[
+            0x17, 0x00, 0x10, 0x00, 0x08, // PC sample
+            0xC0, 0x40, // Local timestamp 64
+            0x15, 0x00, // PC sample while sleeping
+            0x17, 0x00, 0x10, 0x00, 0x08, // PC sample
+        ]);
+
+        assert_eq!(
+            samples,
+            vec![
+                PcSample {
+                    timestamp: 0,
+                    pc: Some(0x0800_1000)
+                },
+                PcSample {
+                    timestamp: 64,
+                    pc: None
+                },
+                PcSample {
+                    timestamp: 64,
+                    pc: Some(0x0800_1000)
+                },
+            ]
+        );
+
+        let mut histogram = PcHistogram::new();
+        samples.iter().for_each(|sample| histogram.add(sample));
+
+        assert_eq!(histogram.total_samples(), 3);
+        assert_eq!(histogram.sleep_samples(), 1);
+        assert_eq!(histogram.pc_samples()[&0x0800_1000], 2);
+    }
+}
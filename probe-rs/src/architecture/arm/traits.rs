@@ -3,7 +3,7 @@ use crate::{CoreStatus, DebugProbe, DebugProbeError};
 use super::ArmError;
 
 /// The type of port we are using.
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 pub enum PortType {
     /// Debug Port (e.g. SWD or JTAG)
     DebugPort,
@@ -31,7 +31,7 @@ bitfield::bitfield! {
 }
 
 /// Debug port address.
-#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash, Serialize, Deserialize)]
 pub enum DpAddress {
     /// Access the single DP on the bus, assuming there is only one.
     /// Will cause corruption if multiple are present.
@@ -42,7 +42,7 @@ pub enum DpAddress {
 }
 
 /// Access port address.
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 pub struct ApAddress {
     /// The address of the debug port this access port belongs to.
     pub dp: DpAddress,
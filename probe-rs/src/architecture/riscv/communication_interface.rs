@@ -6,7 +6,7 @@
 
 use super::{
     dtm::{DmiOperation, DmiOperationStatus, Dtm},
-    register, Dmcontrol, Dmstatus,
+    register, Dmcontrol, Dmstatus, Hawindow, Hawindowsel,
 };
 use crate::{
     architecture::riscv::*,
@@ -66,6 +66,9 @@ pub enum RiscvError {
     /// The connected target is not a RISCV device.
     #[error("Connected target is not a RISCV device.")]
     NoRiscvTarget,
+    /// The requested hart does not exist.
+    #[error("Hart {0} does not exist.")]
+    HartNotFound(u32),
 }
 
 impl From<RiscvError> for ProbeRsError {
@@ -203,6 +206,12 @@ pub struct RiscvCommunicationInterfaceState {
     /// Number of harts
     num_harts: u32,
 
+    /// The hart which is selected by `hartsel`.
+    selected_hart: u32,
+
+    /// The hart array mask can be used to select multiple harts at once.
+    supports_hart_array_mask: bool,
+
     memory_access_info: HashMap<RiscvBusAccess, MemoryAccessMethod>,
 
     /// describes, if the given register can be read / written with an
@@ -215,7 +224,7 @@ const RISCV_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// RiscV only supports 12bit CSRs. See
 /// [Zicsr](https://riscv.org/wp-content/uploads/2019/06/riscv-spec.pdf#chapter.9) extension
-const RISCV_MAX_CSR_ADDR: u16 = 0xFFF;
+pub(crate) const RISCV_MAX_CSR_ADDR: u16 = 0xFFF;
 
 impl RiscvCommunicationInterfaceState {
     /// Create a new interface state.
@@ -245,6 +254,10 @@ impl RiscvCommunicationInterfaceState {
             // We assume only a singe hart exisits initially
             num_harts: 1,
 
+            selected_hart: 0,
+
+            supports_hart_array_mask: false,
+
             memory_access_info: HashMap::new(),
 
             abstract_cmd_register_info: HashMap::new(),
@@ -301,6 +314,15 @@ impl RiscvCommunicationInterface {
         self.dtm.read_idcode()
     }
 
+    /// Returns the number of JTAG idle cycles which are inserted between accesses to the
+    /// debug module.
+    ///
+    /// This starts at the value recommended by the debug transport module, and is
+    /// increased automatically whenever the debug module reports that it is busy.
+    pub fn jtag_idle_cycles(&self) -> u8 {
+        self.dtm.idle_cycles()
+    }
+
     fn enter_debug_mode(&mut self) -> Result<(), RiscvError> {
         // We need a jtag interface
 
@@ -385,10 +407,26 @@ impl RiscvCommunicationInterface {
 
         self.state.num_harts = num_harts;
 
-        // Select hart 0 again
+        // Check if the hart array mask is implemented, which allows
+        // selecting multiple harts at once.
         let mut control = Dmcontrol(0);
-        control.set_hartsel(0);
         control.set_dmactive(true);
+        control.set_hasel(true);
+
+        self.write_dm_register(control)?;
+
+        let control: Dmcontrol = self.read_dm_register()?;
+
+        self.state.supports_hart_array_mask = control.hasel();
+
+        tracing::debug!(
+            "Support for hart array mask: {}",
+            self.state.supports_hart_array_mask
+        );
+
+        // Select hart 0 again
+        self.state.selected_hart = 0;
+        let control = self.dmcontrol();
 
         self.write_dm_register(control)?;
 
@@ -475,6 +513,188 @@ impl RiscvCommunicationInterface {
         Ok(())
     }
 
+    /// Returns the number of harts connected to the debug module.
+    pub fn num_harts(&self) -> u32 {
+        self.state.num_harts
+    }
+
+    /// Returns the hart which is accessed through the interface.
+    pub fn selected_hart(&self) -> u32 {
+        self.state.selected_hart
+    }
+
+    /// Selects the hart which is accessed through the interface.
+    ///
+    /// Run control requests, abstract commands and the program buffer
+    /// only apply to the selected hart.
+    pub fn select_hart(&mut self, hart: u32) -> Result<(), RiscvError> {
+        if hart >= self.state.num_harts {
+            return Err(RiscvError::HartNotFound(hart));
+        }
+
+        if hart == self.state.selected_hart {
+            return Ok(());
+        }
+
+        tracing::debug!("Selecting hart {}", hart);
+
+        self.state.selected_hart = hart;
+
+        let control = self.dmcontrol();
+        self.write_dm_register(control)
+    }
+
+    /// Returns a `dmcontrol` value which keeps the debug module active and
+    /// selects the current hart. All other bits are cleared.
+    ///
+    /// Every write to `dmcontrol` changes the selected hart, so this
+    /// should be used for all writes.
+    pub(super) fn dmcontrol(&self) -> Dmcontrol {
+        let mut control = Dmcontrol(0);
+        control.set_dmactive(true);
+        control.set_hartsel(self.state.selected_hart);
+        control
+    }
+
+    /// Selects the given harts with the hart array mask, and returns a
+    /// `dmcontrol` value with the hart array mask enabled.
+    fn select_hart_group(&mut self, harts: &[u32]) -> Result<Dmcontrol, RiscvError> {
+        if let Some(&hart) = harts.iter().find(|&&hart| hart >= self.state.num_harts) {
+            return Err(RiscvError::HartNotFound(hart));
+        }
+
+        let windows = hart_array_windows(harts, self.state.num_harts);
+
+        for (window, mask) in windows.into_iter().enumerate() {
+            self.write_dm_register(Hawindowsel(window as u32))?;
+            self.write_dm_register(Hawindow(mask))?;
+        }
+
+        let mut control = self.dmcontrol();
+        control.set_hasel(true);
+
+        Ok(control)
+    }
+
+    /// Waits until `condition` is true for the status of the selected harts.
+    fn wait_for_harts(
+        &mut self,
+        timeout: Duration,
+        condition: impl Fn(&Dmstatus) -> bool,
+    ) -> Result<(), RiscvError> {
+        let start = Instant::now();
+
+        loop {
+            let status: Dmstatus = self.read_dm_register()?;
+
+            if condition(&status) {
+                return Ok(());
+            }
+
+            if start.elapsed() > timeout {
+                return Err(RiscvError::Timeout);
+            }
+        }
+    }
+
+    /// Halts multiple harts at the same time.
+    ///
+    /// If the hart array mask is supported, all harts are halted with a single
+    /// request. Otherwise, the halt is requested for one hart after another.
+    /// The selected hart is not changed.
+    pub fn halt_harts(&mut self, harts: &[u32], timeout: Duration) -> Result<(), RiscvError> {
+        if self.state.supports_hart_array_mask {
+            let mut control = self.select_hart_group(harts)?;
+            control.set_haltreq(true);
+            self.write_dm_register(control)?;
+
+            let result = self.wait_for_harts(timeout, Dmstatus::allhalted);
+
+            // Clear the halt request, and deselect the hart group.
+            let control = self.dmcontrol();
+            self.write_dm_register(control)?;
+
+            return result;
+        }
+
+        let selected_hart = self.state.selected_hart;
+
+        for &hart in harts {
+            self.select_hart(hart)?;
+
+            let mut control = self.dmcontrol();
+            control.set_haltreq(true);
+            self.write_dm_register(control)?;
+        }
+
+        let mut result = Ok(());
+
+        for &hart in harts {
+            self.select_hart(hart)?;
+
+            if result.is_ok() {
+                result = self.wait_for_harts(timeout, Dmstatus::allhalted);
+            }
+
+            // Clear the halt request
+            let control = self.dmcontrol();
+            self.write_dm_register(control)?;
+        }
+
+        self.select_hart(selected_hart)?;
+
+        result
+    }
+
+    /// Resumes multiple harts at the same time.
+    ///
+    /// If the hart array mask is supported, all harts are resumed with a single
+    /// request. Otherwise, the harts are resumed one after another.
+    /// The selected hart is not changed.
+    pub fn resume_harts(&mut self, harts: &[u32]) -> Result<(), RiscvError> {
+        if self.state.supports_hart_array_mask {
+            let mut control = self.select_hart_group(harts)?;
+            control.set_resumereq(true);
+            self.write_dm_register(control)?;
+
+            let status: Dmstatus = self.read_dm_register()?;
+
+            // Clear the resume request, and deselect the hart group.
+            let control = self.dmcontrol();
+            self.write_dm_register(control)?;
+
+            if !status.allresumeack() {
+                return Err(RiscvError::RequestNotAcknowledged);
+            }
+
+            return Ok(());
+        }
+
+        let selected_hart = self.state.selected_hart;
+        let mut result = Ok(());
+
+        for &hart in harts {
+            self.select_hart(hart)?;
+
+            let mut control = self.dmcontrol();
+            control.set_resumereq(true);
+            self.write_dm_register(control)?;
+
+            let status: Dmstatus = self.read_dm_register()?;
+            if !status.allresumeack() {
+                result = Err(RiscvError::RequestNotAcknowledged);
+            }
+
+            // Clear the resume request
+            let control = self.dmcontrol();
+            self.write_dm_register(control)?;
+        }
+
+        self.select_hart(selected_hart)?;
+
+        result
+    }
+
     pub(super) fn read_dm_register<R: MemoryMappedRegister<u32>>(
         &mut self,
     ) -> Result<R, RiscvError> {
@@ -980,11 +1200,10 @@ impl RiscvCommunicationInterface {
         // resumereq    = 0
         // ackhavereset = 0
 
-        let mut dmcontrol = Dmcontrol(0);
+        let mut dmcontrol = self.dmcontrol();
         dmcontrol.set_haltreq(false);
         dmcontrol.set_resumereq(false);
         dmcontrol.set_ackhavereset(false);
-        dmcontrol.set_dmactive(true);
         self.write_dm_register(dmcontrol)?;
 
         // read abstractcs to see its state
@@ -1066,6 +1285,17 @@ impl RiscvCommunicationInterface {
         &mut self,
         regno: impl Into<RegisterId>,
     ) -> Result<u32, RiscvError> {
+        self.abstract_cmd_register_read_sized(regno)
+    }
+
+    /// Read a register with an abstract command, using an access of the size of `V`.
+    ///
+    /// This is required for registers which are larger than 32 bits, e.g. the floating
+    /// point registers of a core with the D extension.
+    pub(crate) fn abstract_cmd_register_read_sized<V: RiscvValue>(
+        &mut self,
+        regno: impl Into<RegisterId>,
+    ) -> Result<V, RiscvError> {
         let regno = regno.into();
 
         // Check if the register was already tried via abstract cmd
@@ -1079,7 +1309,7 @@ impl RiscvCommunicationInterface {
         let mut command = AccessRegisterCommand(0);
         command.set_cmd_type(0);
         command.set_transfer(true);
-        command.set_aarsize(RiscvBusAccess::A32);
+        command.set_aarsize(V::WIDTH);
 
         command.set_regno(regno.0 as u32);
 
@@ -1096,9 +1326,7 @@ impl RiscvCommunicationInterface {
             Err(e) => return Err(e),
         }
 
-        let register_value: Data0 = self.read_dm_register()?;
-
-        Ok(register_value.into())
+        self.read_large_dtm_register::<V, Arg0>()
     }
 
     pub(crate) fn abstract_cmd_register_write<V: RiscvValue>(
@@ -1716,11 +1944,20 @@ impl RiscvValue for u128 {
 
 impl MemoryInterface for RiscvCommunicationInterface {
     fn supports_native_64bit_access(&mut self) -> bool {
-        false
+        // Only the system bus supports accesses wider than 32 bits.
+        matches!(
+            self.state.memory_access_method(RiscvBusAccess::A64),
+            MemoryAccessMethod::SystemBus
+        )
     }
 
     fn read_word_64(&mut self, address: u64) -> Result<u64, crate::error::Error> {
         let address = valid_32bit_address(address)?;
+
+        if self.supports_native_64bit_access() {
+            return Ok(self.perform_memory_read_sysbus(address)?);
+        }
+
         let mut ret = self.read_word::<u32>(address)? as u64;
         ret |= (self.read_word::<u32>(address + 4)? as u64) << 32;
 
@@ -1732,6 +1969,12 @@ impl MemoryInterface for RiscvCommunicationInterface {
         self.read_word(address)
     }
 
+    fn read_word_16(&mut self, address: u64) -> Result<u16, crate::Error> {
+        let address = valid_32bit_address(address)?;
+        tracing::debug!("read_word_16 from {:#08x}", address);
+        self.read_word(address)
+    }
+
     fn read_word_8(&mut self, address: u64) -> Result<u8, crate::Error> {
         let address = valid_32bit_address(address)?;
         tracing::debug!("read_word_8 from {:#08x}", address);
@@ -1755,6 +1998,13 @@ impl MemoryInterface for RiscvCommunicationInterface {
         self.read_multiple(address, data)
     }
 
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), crate::Error> {
+        let address = valid_32bit_address(address)?;
+        tracing::debug!("read_16 from {:#08x}", address);
+
+        self.read_multiple(address, data)
+    }
+
     fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), crate::Error> {
         let address = valid_32bit_address(address)?;
         tracing::debug!("read_8 from {:#08x}", address);
@@ -1769,6 +2019,11 @@ impl MemoryInterface for RiscvCommunicationInterface {
 
     fn write_word_64(&mut self, address: u64, data: u64) -> Result<(), crate::error::Error> {
         let address = valid_32bit_address(address)?;
+
+        if self.supports_native_64bit_access() {
+            return Ok(self.perform_memory_write_sysbus(address, &[data])?);
+        }
+
         let low_word = data as u32;
         let high_word = (data >> 32) as u32;
 
@@ -1781,6 +2036,11 @@ impl MemoryInterface for RiscvCommunicationInterface {
         self.write_word(address, data)
     }
 
+    fn write_word_16(&mut self, address: u64, data: u16) -> Result<(), crate::Error> {
+        let address = valid_32bit_address(address)?;
+        self.write_word(address, data)
+    }
+
     fn write_word_8(&mut self, address: u64, data: u8) -> Result<(), crate::Error> {
         let address = valid_32bit_address(address)?;
         self.write_word(address, data)
@@ -1790,6 +2050,10 @@ impl MemoryInterface for RiscvCommunicationInterface {
         let address = valid_32bit_address(address)?;
         tracing::debug!("write_64 to {:#08x}", address);
 
+        if self.supports_native_64bit_access() {
+            return Ok(self.perform_memory_write_sysbus(address, data)?);
+        }
+
         for (i, d) in data.iter().enumerate() {
             self.write_word_64((address + (i as u32 * 8)).into(), *d)?;
         }
@@ -1804,6 +2068,13 @@ impl MemoryInterface for RiscvCommunicationInterface {
         self.write_multiple(address, data)
     }
 
+    fn write_16(&mut self, address: u64, data: &[u16]) -> Result<(), crate::Error> {
+        let address = valid_32bit_address(address)?;
+        tracing::debug!("write_16 to {:#08x}", address);
+
+        self.write_multiple(address, data)
+    }
+
     fn write_8(&mut self, address: u64, data: &[u8]) -> Result<(), crate::Error> {
         let address = valid_32bit_address(address)?;
         tracing::debug!("write_8 to {:#08x}", address);
@@ -2071,3 +2342,28 @@ memory_mapped_bitfield_register! { struct Confstrptr0(u32); 0x19, "confstrptr0",
 memory_mapped_bitfield_register! { struct Confstrptr1(u32); 0x1a, "confstrptr1", impl From; }
 memory_mapped_bitfield_register! { struct Confstrptr2(u32); 0x1b, "confstrptr2", impl From; }
 memory_mapped_bitfield_register! { struct Confstrptr3(u32); 0x1c, "confstrptr3", impl From; }
+
+/// Returns the value of the hart array window registers which select `harts`, for
+/// every window of 32 harts.
+fn hart_array_windows(harts: &[u32], num_harts: u32) -> Vec<u32> {
+    // There is always at least hart 0.
+    let mut windows = vec![0; (num_harts.saturating_sub(1) / 32 + 1) as usize];
+
+    for &hart in harts {
+        windows[(hart / 32) as usize] |= 1 << (hart % 32);
+    }
+
+    windows
+}
+
+#[cfg(test)]
+mod test {
+    use super::hart_array_windows;
+
+    #[test]
+    fn hart_array_window_masks() {
+        assert_eq!(hart_array_windows(&[0, 1], 2), vec![0b11]);
+        assert_eq!(hart_array_windows(&[1, 33], 40), vec![0b10, 0b10]);
+        assert_eq!(hart_array_windows(&[], 1), vec![0]);
+    }
+}
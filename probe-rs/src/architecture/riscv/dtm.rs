@@ -58,7 +58,9 @@ impl Dtm {
             ));
         }
 
-        // Setup the number of idle cycles between JTAG accesses
+        // Setup the number of idle cycles between JTAG accesses. The value from
+        // the DTM is only a hint, it is increased when the debug module reports
+        // that it is busy.
         probe.set_idle_cycles(idle_cycles as u8);
 
         Ok(Self {
@@ -78,6 +80,28 @@ impl Dtm {
         Ok(u32::from_le_bytes((&value[..]).try_into().unwrap()))
     }
 
+    /// Returns the number of idle cycles which are currently inserted between DMI accesses.
+    pub fn idle_cycles(&self) -> u8 {
+        self.probe.get_idle_cycles()
+    }
+
+    /// Increase the number of idle cycles between DMI accesses, after the debug module
+    /// reported that it was still busy with the previous access.
+    ///
+    /// The idle cycles are increased one at a time, so they converge to the minimal
+    /// number of cycles which the debug module requires.
+    fn increase_idle_cycles(&mut self) {
+        let idle_cycles = self.probe.get_idle_cycles();
+
+        if idle_cycles < MAX_IDLE_CYCLES {
+            tracing::debug!(
+                "DMI access busy, increasing idle cycles to {}",
+                idle_cycles + 1
+            );
+            self.probe.set_idle_cycles(idle_cycles + 1);
+        }
+    }
+
     /// Clear the sticky error state (field *op* in the DMI register)
     pub fn reset(&mut self) -> Result<(), RiscvError> {
         let mut dtmcs = Dtmcs(0);
@@ -110,9 +134,14 @@ impl Dtm {
                             self.queued_commands
                                 .extend_from_slice(&cmds[e.results.len()..]);
 
-                            self.probe.set_idle_cycles(self.probe.get_idle_cycles() + 1);
+                            self.increase_idle_cycles();
+
+                            // Keep the results of the successful commands, so the
+                            // indices of the deferred results stay valid.
+                            let mut results = e.results;
+                            results.extend(self.execute()?);
 
-                            self.execute()
+                            Ok(results)
                         }
                         _ => Err(ae),
                     }
@@ -219,8 +248,7 @@ impl Dtm {
                 Err(DmiOperationStatus::RequestInProgress) => {
                     // Operation still in progress, reset dmi status and try again.
                     self.reset()?;
-                    self.probe
-                        .set_idle_cycles(self.probe.get_idle_cycles().saturating_add(1));
+                    self.increase_idle_cycles();
                 }
                 Err(e) => return Err(RiscvError::DmiTransfer(e)),
             }
@@ -245,6 +273,10 @@ bitfield! {
     version, _: 3,0;
 }
 
+/// Upper limit for the idle cycles between DMI accesses. If the debug module is still
+/// busy with this many idle cycles, accesses are only retried.
+const MAX_IDLE_CYCLES: u8 = 100;
+
 /// Address of the `dtmcs` JTAG register.
 const DTMCS_ADDRESS: u32 = 0x10;
 
@@ -295,3 +327,69 @@ impl DmiOperationStatus {
         Some(status)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{DmiOperation, Dtm};
+    use crate::{
+        architecture::riscv::{
+            communication_interface::RiscvCommunicationInterface, mock::MockDebugModule,
+        },
+        probe::CommandResult,
+    };
+
+    /// Address of the `data0` register of the debug module.
+    const DATA0: u64 = 0x04;
+
+    #[test]
+    fn idle_cycles_are_increased_while_busy() {
+        let mut dtm = Dtm::new(Box::new(MockDebugModule::with_idle_cycles(1, 4))).unwrap();
+        assert_eq!(dtm.idle_cycles(), 1);
+
+        dtm.dmi_register_access_with_timeout(
+            DATA0,
+            0x1234,
+            DmiOperation::Write,
+            Duration::from_secs(1),
+        )
+        .unwrap();
+
+        assert_eq!(dtm.idle_cycles(), 4);
+
+        // No more busy responses with enough idle cycles.
+        dtm.dmi_register_access_with_timeout(DATA0, 0, DmiOperation::Read, Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(dtm.idle_cycles(), 4);
+    }
+
+    #[test]
+    fn busy_batches_are_retried() {
+        let mut dtm = Dtm::new(Box::new(MockDebugModule::with_idle_cycles(0, 2))).unwrap();
+
+        dtm.schedule_dmi_register_access(DATA0, 0x1234, DmiOperation::Write)
+            .unwrap();
+        dtm.schedule_dmi_register_access(DATA0, 0, DmiOperation::Read)
+            .unwrap();
+        let read = dtm
+            .schedule_dmi_register_access(0, 0, DmiOperation::NoOp)
+            .unwrap();
+
+        let results = dtm.execute().unwrap();
+
+        // The results of all commands are kept, so the deferred indices stay valid.
+        assert_eq!(results.len(), 3);
+        assert!(matches!(results[read], CommandResult::U32(0x1234)));
+        assert_eq!(dtm.idle_cycles(), 2);
+    }
+
+    #[test]
+    fn idle_cycles_are_tuned_on_connect() {
+        let interface =
+            RiscvCommunicationInterface::new(Box::new(MockDebugModule::with_idle_cycles(1, 3)))
+                .unwrap();
+
+        assert_eq!(interface.jtag_idle_cycles(), 3);
+    }
+}
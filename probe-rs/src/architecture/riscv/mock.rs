@@ -0,0 +1,314 @@
+//! A simulated RISC-V debug module, for testing without hardware.
+
+use std::collections::HashMap;
+
+use crate::{probe::JTAGAccess, DebugProbe, DebugProbeError, DebugProbeSelector, WireProtocol};
+
+/// Address of the `dtmcs` JTAG register.
+const DTMCS_ADDRESS: u32 = 0x10;
+/// Address of the `dmi` JTAG register.
+const DMI_ADDRESS: u32 = 0x11;
+/// Number of address bits in the `dmi` register.
+const ABITS: u32 = 7;
+
+const DATA0: u32 = 0x04;
+const DMCONTROL: u32 = 0x10;
+const DMSTATUS: u32 = 0x11;
+const ABSTRACTCS: u32 = 0x16;
+const COMMAND: u32 = 0x17;
+
+/// `cmderr` value for an unsupported abstract command.
+const CMDERR_NOT_SUPPORTED: u32 = 2;
+
+// Bits of `dmcontrol`
+const HALTREQ: u32 = 1 << 31;
+const RESUMEREQ: u32 = 1 << 30;
+const ACKHAVERESET: u32 = 1 << 28;
+const SETRESETHALTREQ: u32 = 1 << 3;
+const CLRRESETHALTREQ: u32 = 1 << 2;
+const NDMRESET: u32 = 1 << 1;
+
+/// A JTAG probe connected to a debug module (version 0.13) with a single hart, which is
+/// initially halted.
+///
+/// The debug module supports the access register command for the CSRs, and has no program
+/// buffer and no system bus access. The hart can be reset with `dmcontrol.ndmreset`.
+#[derive(Debug)]
+pub struct MockDebugModule {
+    /// The idle cycles recommended in `dtmcs`.
+    recommended_idle_cycles: u8,
+    /// DMI accesses report that the debug module is busy with fewer idle cycles.
+    required_idle_cycles: u8,
+    idle_cycles: u8,
+    /// The result of the last DMI access, which is shifted out by the next access.
+    pending_result: u32,
+    dmcontrol: u32,
+    abstractcs: u32,
+    data0: u32,
+    halted: bool,
+    resume_ack: bool,
+    have_reset: bool,
+    reset_halt_request: bool,
+    /// Whether the debug module supports halting the hart on a reset.
+    pub has_reset_halt_request: bool,
+    /// The hart doesn't react to halt requests, e.g. because it is in a low power mode.
+    pub halt_requests_ignored: bool,
+    /// The content of the CSRs.
+    pub csrs: HashMap<u16, u32>,
+}
+
+impl MockDebugModule {
+    pub fn new() -> Self {
+        Self {
+            recommended_idle_cycles: 0,
+            required_idle_cycles: 0,
+            idle_cycles: 0,
+            pending_result: 0,
+            dmcontrol: 0,
+            abstractcs: 0,
+            data0: 0,
+            halted: true,
+            resume_ack: false,
+            have_reset: false,
+            reset_halt_request: false,
+            has_reset_halt_request: false,
+            halt_requests_ignored: false,
+            csrs: HashMap::new(),
+        }
+    }
+
+    /// The debug module is busy, unless `required` idle cycles are inserted between
+    /// DMI accesses. The DTM only recommends `recommended` idle cycles.
+    pub fn with_idle_cycles(recommended: u8, required: u8) -> Self {
+        Self {
+            recommended_idle_cycles: recommended,
+            required_idle_cycles: required,
+            ..Self::new()
+        }
+    }
+
+    fn read_dm_register(&self, address: u32) -> u32 {
+        match address {
+            DATA0 => self.data0,
+            // Only hart 0 exists, so hartsel has no writable bits, and there is no
+            // hart array mask.
+            DMCONTROL => self.dmcontrol & 0x3,
+            DMSTATUS => self.dmstatus(),
+            // One data register, busy is never set.
+            ABSTRACTCS => self.abstractcs | 0x1,
+            _ => 0,
+        }
+    }
+
+    fn write_dm_register(&mut self, address: u32, value: u32) {
+        match address {
+            DATA0 => self.data0 = value,
+            DMCONTROL => self.write_dmcontrol(value),
+            // cmderr is cleared by writing ones.
+            ABSTRACTCS => self.abstractcs &= !(value & 0x700),
+            COMMAND => self.execute_command(value),
+            _ => {}
+        }
+    }
+
+    fn dmstatus(&self) -> u32 {
+        // Version 0.13, authenticated
+        let mut dmstatus = 0x2 | 1 << 7;
+
+        if self.has_reset_halt_request {
+            dmstatus |= 1 << 5;
+        }
+        if self.halted {
+            // anyhalted, allhalted
+            dmstatus |= 1 << 8 | 1 << 9;
+        } else {
+            // anyrunning, allrunning
+            dmstatus |= 1 << 10 | 1 << 11;
+        }
+        if self.resume_ack {
+            // anyresumeack, allresumeack
+            dmstatus |= 1 << 16 | 1 << 17;
+        }
+        if self.have_reset {
+            // anyhavereset, allhavereset
+            dmstatus |= 1 << 18 | 1 << 19;
+        }
+
+        dmstatus
+    }
+
+    fn write_dmcontrol(&mut self, value: u32) {
+        if value & SETRESETHALTREQ != 0 && self.has_reset_halt_request {
+            self.reset_halt_request = true;
+        }
+        if value & CLRRESETHALTREQ != 0 {
+            self.reset_halt_request = false;
+        }
+        if value & ACKHAVERESET != 0 {
+            self.have_reset = false;
+        }
+        if value & NDMRESET != 0 {
+            // The hart only stays halted after the reset, if the reset is caught.
+            self.have_reset = true;
+            self.halted = self.reset_halt_request;
+            self.resume_ack = false;
+        }
+        if value & HALTREQ != 0 && !self.halt_requests_ignored {
+            self.halted = true;
+        }
+        if value & RESUMEREQ != 0 && self.halted {
+            self.halted = false;
+            self.resume_ack = true;
+        }
+
+        self.dmcontrol = value;
+    }
+
+    fn execute_command(&mut self, command: u32) {
+        let cmd_type = command >> 24;
+        let aarsize = (command >> 20) & 0x7;
+        let transfer = command & (1 << 17) != 0;
+        let write = command & (1 << 16) != 0;
+        let regno = (command & 0xffff) as u16;
+
+        // Only 32 bit accesses to CSRs are supported.
+        if cmd_type != 0 || aarsize != 2 || regno > 0xfff {
+            self.abstractcs |= CMDERR_NOT_SUPPORTED << 8;
+            return;
+        }
+
+        if transfer {
+            if write {
+                self.csrs.insert(regno, self.data0);
+            } else {
+                self.data0 = self.csrs.get(&regno).copied().unwrap_or(0);
+            }
+        }
+    }
+
+    fn dmi_access(&mut self, request: u64) -> u64 {
+        if self.idle_cycles < self.required_idle_cycles {
+            // The request is ignored, and the busy status is returned.
+            return 0x3;
+        }
+
+        let op = request & 0x3;
+        let value = (request >> 2) as u32;
+        let address = (request >> 34) as u32;
+
+        let previous_result = self.pending_result;
+
+        match op {
+            1 => self.pending_result = self.read_dm_register(address),
+            2 => {
+                self.write_dm_register(address, value);
+                self.pending_result = 0;
+            }
+            _ => {}
+        }
+
+        (previous_result as u64) << 2
+    }
+}
+
+impl DebugProbe for MockDebugModule {
+    fn new_from_selector(
+        _selector: impl Into<DebugProbeSelector>,
+    ) -> Result<Box<Self>, DebugProbeError>
+    where
+        Self: Sized,
+    {
+        unimplemented!()
+    }
+
+    fn get_name(&self) -> &str {
+        "Mock RISC-V debug module"
+    }
+
+    fn speed_khz(&self) -> u32 {
+        1000
+    }
+
+    fn set_speed(&mut self, speed_khz: u32) -> Result<u32, DebugProbeError> {
+        Ok(speed_khz)
+    }
+
+    fn attach(&mut self) -> Result<(), DebugProbeError> {
+        Ok(())
+    }
+
+    fn detach(&mut self) -> Result<(), crate::Error> {
+        Ok(())
+    }
+
+    fn target_reset(&mut self) -> Result<(), DebugProbeError> {
+        Err(DebugProbeError::NotImplemented("target_reset"))
+    }
+
+    fn target_reset_assert(&mut self) -> Result<(), DebugProbeError> {
+        Err(DebugProbeError::NotImplemented("target_reset_assert"))
+    }
+
+    fn target_reset_deassert(&mut self) -> Result<(), DebugProbeError> {
+        Err(DebugProbeError::NotImplemented("target_reset_deassert"))
+    }
+
+    fn select_protocol(&mut self, _protocol: WireProtocol) -> Result<(), DebugProbeError> {
+        Ok(())
+    }
+
+    fn active_protocol(&self) -> Option<WireProtocol> {
+        Some(WireProtocol::Jtag)
+    }
+
+    fn into_probe(self: Box<Self>) -> Box<dyn DebugProbe> {
+        self
+    }
+}
+
+impl JTAGAccess for MockDebugModule {
+    fn read_register(&mut self, address: u32, len: u32) -> Result<Vec<u8>, DebugProbeError> {
+        assert_eq!(address, DTMCS_ADDRESS, "Unexpected read of JTAG register");
+        assert_eq!(len, 32);
+
+        // Version 1 of the DTM
+        let dtmcs = 1 | ABITS << 4 | (self.recommended_idle_cycles as u32) << 12;
+
+        Ok(dtmcs.to_le_bytes().to_vec())
+    }
+
+    fn set_idle_cycles(&mut self, idle_cycles: u8) {
+        self.idle_cycles = idle_cycles;
+    }
+
+    fn get_idle_cycles(&self) -> u8 {
+        self.idle_cycles
+    }
+
+    fn set_ir_len(&mut self, len: u32) {
+        assert_eq!(len, 5);
+    }
+
+    fn write_register(
+        &mut self,
+        address: u32,
+        data: &[u8],
+        len: u32,
+    ) -> Result<Vec<u8>, DebugProbeError> {
+        match address {
+            // Resetting the DMI state is always possible.
+            DTMCS_ADDRESS => Ok(vec![0; 4]),
+            DMI_ADDRESS => {
+                assert_eq!(len, ABITS + 34);
+
+                let mut request = [0u8; 8];
+                request.copy_from_slice(&data[..8]);
+
+                let response = self.dmi_access(u64::from_le_bytes(request));
+
+                Ok(response.to_le_bytes()[..6].to_vec())
+            }
+            _ => panic!("Unexpected write to JTAG register {address:#x}"),
+        }
+    }
+}
@@ -5,25 +5,98 @@
 use crate::{
     core::{
         Architecture, BreakpointCause, CoreInformation, RegisterFile, RegisterId, RegisterValue,
+        WatchpointCause, WatchpointKind,
     },
     memory::valid_32bit_address,
     memory_mapped_bitfield_register, CoreInterface, CoreStatus, CoreType, Error, HaltReason,
-    InstructionSet, MemoryInterface,
+    InstructionSet, MemoryInterface, ResetType,
 };
 use anyhow::{anyhow, Result};
 use bitfield::bitfield;
-use communication_interface::{AbstractCommandErrorKind, RiscvCommunicationInterface, RiscvError};
-use register::RISCV_REGISTERS;
+use communication_interface::{
+    AbstractCommandErrorKind, RiscvCommunicationInterface, RiscvError, RISCV_MAX_CSR_ADDR,
+};
+use register::{FCSR, FP_REGISTER_BASE, RISCV_REGISTERS, RISCV_WITH_DP_REGS, RISCV_WITH_FP_REGS};
 use std::time::{Duration, Instant};
 
 #[macro_use]
-mod register;
+pub(crate) mod register;
 pub(crate) mod assembly;
 mod dtm;
+#[cfg(test)]
+mod mock;
 
 pub mod communication_interface;
 pub mod sequences;
 
+/// Addresses of common control and status registers (CSRs), which can be accessed
+/// with [`Core::read_csr`](crate::Core::read_csr) and [`Core::write_csr`](crate::Core::write_csr).
+///
+/// See the RISC-V Privileged Spec for a description of the registers.
+///
+/// The registers of the F, D and V extensions can only be accessed if the extension is
+/// enabled in `mstatus`. The vector data registers themselves can't be accessed with
+/// abstract commands, only the vector CSRs are available.
+pub mod csr {
+    /// Floating-point control and status register
+    pub const FCSR: u16 = 0x003;
+    /// Vector start position
+    pub const VSTART: u16 = 0x008;
+    /// Fixed-point accrued saturation flag
+    pub const VXSAT: u16 = 0x009;
+    /// Fixed-point rounding mode
+    pub const VXRM: u16 = 0x00a;
+    /// Vector control and status register
+    pub const VCSR: u16 = 0x00f;
+    /// Vector length
+    pub const VL: u16 = 0xc20;
+    /// Vector data type register
+    pub const VTYPE: u16 = 0xc21;
+    /// Vector register length in bytes
+    pub const VLENB: u16 = 0xc22;
+    /// Machine status register
+    pub const MSTATUS: u16 = 0x300;
+    /// Machine ISA register
+    pub const MISA: u16 = 0x301;
+    /// Machine interrupt-enable register
+    pub const MIE: u16 = 0x304;
+    /// Machine trap-handler base address
+    pub const MTVEC: u16 = 0x305;
+    /// Machine scratch register
+    pub const MSCRATCH: u16 = 0x340;
+    /// Machine exception program counter
+    pub const MEPC: u16 = 0x341;
+    /// Machine trap cause
+    pub const MCAUSE: u16 = 0x342;
+    /// Machine bad address or instruction
+    pub const MTVAL: u16 = 0x343;
+    /// Machine interrupt pending
+    pub const MIP: u16 = 0x344;
+    /// Debug control and status register
+    pub const DCSR: u16 = 0x7b0;
+    /// Debug program counter
+    pub const DPC: u16 = 0x7b1;
+}
+
+/// The F extension, single precision floating point, in `misa`.
+const MISA_F: u32 = 1 << 5;
+/// The D extension, double precision floating point, in `misa`.
+const MISA_D: u32 = 1 << 3;
+
+/// The `FS` field of `mstatus`, which enables the floating point unit if not zero.
+const MSTATUS_FS: u32 = 0b11 << 13;
+/// The `Initial` state of the `FS` field.
+const MSTATUS_FS_INITIAL: u32 = 0b01 << 13;
+
+/// Trigger select register, selects the trigger accessed through `tdata1` and `tdata2`.
+const TSELECT: u16 = 0x7a0;
+/// First trigger data register, holds the configuration of the selected trigger.
+const TDATA1: u16 = 0x7a1;
+/// Second trigger data register, holds the address compared by the selected trigger.
+const TDATA2: u16 = 0x7a2;
+/// Trigger info register, lists the trigger types supported by the selected trigger.
+const TINFO: u16 = 0x7a4;
+
 /// A interface to operate RISC-V cores.
 pub struct Riscv32<'probe> {
     interface: &'probe mut RiscvCommunicationInterface,
@@ -73,10 +146,11 @@ impl<'probe> Riscv32<'probe> {
 
     // Resume the core.
     fn resume_core(&mut self) -> Result<(), crate::Error> {
+        self.state.trigger_halt_reason = None;
+
         // set resume request.
-        let mut dmcontrol = Dmcontrol(0);
+        let mut dmcontrol = self.interface.dmcontrol();
         dmcontrol.set_resumereq(true);
-        dmcontrol.set_dmactive(true);
         self.interface.write_dm_register(dmcontrol)?;
 
         // check if request has been acknowleged.
@@ -86,8 +160,250 @@ impl<'probe> Riscv32<'probe> {
         };
 
         // clear resume request.
-        let mut dmcontrol = Dmcontrol(0);
-        dmcontrol.set_dmactive(true);
+        let dmcontrol = self.interface.dmcontrol();
+        self.interface.write_dm_register(dmcontrol)?;
+
+        Ok(())
+    }
+
+    /// Returns the `misa` register, which is cached after it was read successfully.
+    ///
+    /// The register can only be read while the core is halted.
+    fn misa(&mut self) -> Result<Misa, RiscvError> {
+        if let Some(misa) = self.state.misa {
+            return Ok(Misa(misa));
+        }
+
+        let misa = self.read_csr(csr::MISA)?;
+        self.state.misa = Some(misa);
+
+        Ok(Misa(misa))
+    }
+
+    /// Returns the floating point extensions of the core, as bits of `misa`.
+    fn fp_extensions(&mut self, address: RegisterId) -> Result<u32, crate::Error> {
+        let extensions = self.misa()?.extensions() & (MISA_F | MISA_D);
+
+        if extensions == 0 {
+            return Err(anyhow!(
+                "Register {:?} is not available, the core has no floating point unit",
+                address
+            )
+            .into());
+        }
+
+        Ok(extensions)
+    }
+
+    /// Runs `f` with the floating point unit enabled in `mstatus`.
+    ///
+    /// The floating point registers can't be accessed while the `FS` field of `mstatus` is off.
+    /// In this case, the unit is enabled temporarily, and `mstatus` is restored afterwards.
+    fn with_fp_access<T>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<T, crate::Error>,
+    ) -> Result<T, crate::Error> {
+        let mstatus = self.read_csr(csr::MSTATUS)?;
+
+        if mstatus & MSTATUS_FS != 0 {
+            return f(self);
+        }
+
+        self.write_csr(csr::MSTATUS, mstatus | MSTATUS_FS_INITIAL)?;
+
+        let result = f(self);
+
+        self.write_csr(csr::MSTATUS, mstatus)?;
+
+        result
+    }
+
+    fn read_fp_register(&mut self, address: RegisterId) -> Result<RegisterValue, crate::Error> {
+        let extensions = self.fp_extensions(address)?;
+
+        self.with_fp_access(|core| {
+            if address != FCSR.id && extensions & MISA_D != 0 {
+                let value: u64 = core.interface.abstract_cmd_register_read_sized(address)?;
+                Ok(value.into())
+            } else {
+                Ok(core.read_csr(address.0)?.into())
+            }
+        })
+    }
+
+    fn write_fp_register(
+        &mut self,
+        address: RegisterId,
+        value: RegisterValue,
+    ) -> Result<(), crate::Error> {
+        let extensions = self.fp_extensions(address)?;
+
+        self.with_fp_access(|core| {
+            if address != FCSR.id && extensions & MISA_D != 0 {
+                let value: u64 = value.try_into()?;
+                core.interface.abstract_cmd_register_write(address, value)?;
+            } else {
+                let value: u32 = value.try_into()?;
+                core.write_csr(address.0, value)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Returns the size of the `ebreak` or `c.ebreak` instruction at `address`,
+    /// or `None` if there is another instruction.
+    fn ebreak_size(&mut self, address: u64) -> Result<Option<usize>, crate::Error> {
+        // The second half is only read for 32-bit instructions, as a compressed
+        // instruction can be at the end of a memory region.
+        let mut instruction = [0; 4];
+        self.read_8(address, &mut instruction[..2])?;
+
+        let len = if is_32bit_instruction(u16::from_le_bytes([instruction[0], instruction[1]])) {
+            self.read_8(address + 2, &mut instruction[2..])?;
+            4
+        } else {
+            2
+        };
+
+        Ok(ebreak_size(&instruction[..len]))
+    }
+
+    /// Determines if the `ebreak` which halted the core is part of a semihosting request.
+    fn ebreak_halt_reason(&mut self) -> Result<HaltReason, crate::Error> {
+        let dpc: u64 = self.read_core_reg(RegisterId(0x7b1))?.try_into()?;
+
+        // The semihosting sequence surrounds the ebreak with one instruction on each side.
+        if let Some(start) = dpc.checked_sub(4) {
+            let mut sequence = [0; 12];
+            if self.read_8(start, &mut sequence).is_ok() && is_semihosting_sequence(sequence) {
+                return Ok(HaltReason::Breakpoint(BreakpointCause::Semihosting));
+            }
+        }
+
+        Ok(HaltReason::Breakpoint(BreakpointCause::Software))
+    }
+
+    /// Identifies the trigger which halted the core.
+    ///
+    /// Identifying the trigger clears its `hit` bit, so the result is cached until the core is
+    /// resumed. The `hit` bit is optional, if no trigger reports a hit, the halt is reported
+    /// as a hardware breakpoint.
+    fn trigger_halt_reason(&mut self) -> Result<HaltReason, crate::Error> {
+        if let Some(reason) = self.state.trigger_halt_reason {
+            return Ok(reason);
+        }
+
+        let mut reason = HaltReason::Breakpoint(BreakpointCause::Hardware);
+
+        for unit_index in 0..self.available_breakpoint_units()? as usize {
+            self.write_csr(TSELECT, unit_index as u32)?;
+
+            let mut tdata_value = Mcontrol(self.read_csr(TDATA1)?);
+
+            if tdata_value.type_() != 0b10 || !tdata_value.hit() {
+                continue;
+            }
+
+            if tdata_value.load() || tdata_value.store() {
+                let address = self.read_csr(TDATA2)?;
+
+                reason = HaltReason::Watchpoint(WatchpointCause::Unit {
+                    unit: unit_index,
+                    address: address as u64,
+                });
+            }
+
+            tdata_value.set_hit(false);
+            self.write_csr(TDATA1, tdata_value.0)?;
+
+            break;
+        }
+
+        self.state.trigger_halt_reason = Some(reason);
+
+        Ok(reason)
+    }
+
+    /// Returns the `tdata1` and `tdata2` values of every trigger which is in use, or `None`
+    /// for unused triggers.
+    fn configured_triggers(&mut self) -> Result<Vec<Option<(u32, u32)>>, Error> {
+        let mut triggers = vec![];
+        let num_triggers = self.available_breakpoint_units()? as usize;
+        for unit_index in 0..num_triggers {
+            // Select the trigger.
+            self.write_csr(TSELECT, unit_index as u32)?;
+
+            // Read the trigger "configuration" data.
+            let tdata_value = Mcontrol(self.read_csr(TDATA1)?);
+
+            tracing::debug!("Trigger {}: {:?}", unit_index, tdata_value);
+
+            // The trigger must be active in at least a single mode
+            let trigger_any_mode_active = tdata_value.m() || tdata_value.s() || tdata_value.u();
+
+            let trigger_any_action_enabled =
+                tdata_value.execute() || tdata_value.store() || tdata_value.load();
+
+            // Only return the trigger if it is for a debug action on an exact address or range.
+            if tdata_value.type_() == 0b10
+                && tdata_value.action() == 1
+                && tdata_value.match_() <= 1
+                && trigger_any_mode_active
+                && trigger_any_action_enabled
+            {
+                let tdata2 = self.read_csr(TDATA2)?;
+                triggers.push(Some((tdata_value.0, tdata2)));
+            } else {
+                triggers.push(None);
+            }
+        }
+
+        Ok(triggers)
+    }
+
+    /// Resets the hart with `dmcontrol.hartreset`, and requests a halt.
+    ///
+    /// Returns `false` if the debug module doesn't support hartreset.
+    fn hart_reset(&mut self) -> Result<bool, RiscvError> {
+        tracing::debug!("Resetting core, setting hartreset bit");
+
+        let mut dmcontrol = self.interface.dmcontrol();
+        dmcontrol.set_hartreset(true);
+        dmcontrol.set_haltreq(true);
+
+        self.interface.write_dm_register(dmcontrol)?;
+
+        // Read back register to verify reset is supported
+        let readback: Dmcontrol = self.interface.read_dm_register()?;
+
+        if readback.hartreset() {
+            tracing::debug!("Clearing hartreset bit");
+            // Reset is performed by setting the bit high, and then low again
+            let mut dmcontrol = self.interface.dmcontrol();
+            dmcontrol.set_haltreq(true);
+            dmcontrol.set_hartreset(false);
+
+            self.interface.write_dm_register(dmcontrol)?;
+        }
+
+        Ok(readback.hartreset())
+    }
+
+    /// Resets the whole system with `dmcontrol.ndmreset`, and requests a halt.
+    fn ndm_reset(&mut self) -> Result<(), RiscvError> {
+        tracing::debug!("Resetting system, setting ndmreset bit");
+        let mut dmcontrol = self.interface.dmcontrol();
+        dmcontrol.set_ndmreset(true);
+        dmcontrol.set_haltreq(true);
+
+        self.interface.write_dm_register(dmcontrol)?;
+
+        tracing::debug!("Clearing ndmreset bit");
+        let mut dmcontrol = self.interface.dmcontrol();
+        dmcontrol.set_ndmreset(false);
+        dmcontrol.set_haltreq(true);
+
         self.interface.write_dm_register(dmcontrol)?;
 
         Ok(())
@@ -126,19 +442,16 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
             self.interface.read_dm_register::<Dmcontrol>()?
         );
 
-        let mut dmcontrol = Dmcontrol(0);
+        let mut dmcontrol = self.interface.dmcontrol();
 
         dmcontrol.set_haltreq(true);
-        dmcontrol.set_dmactive(true);
 
         self.interface.write_dm_register(dmcontrol)?;
 
         self.wait_for_core_halted(timeout)?;
 
         // clear the halt request
-        let mut dmcontrol = Dmcontrol(0);
-
-        dmcontrol.set_dmactive(true);
+        let dmcontrol = self.interface.dmcontrol();
 
         self.interface.write_dm_register(dmcontrol)?;
 
@@ -157,8 +470,8 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
         Ok(())
     }
 
-    fn reset(&mut self) -> Result<(), crate::Error> {
-        match self.reset_and_halt(Duration::from_millis(500)) {
+    fn reset(&mut self, reset_type: ResetType) -> Result<(), crate::Error> {
+        match self.reset_and_halt(reset_type, Duration::from_millis(500)) {
             Ok(_) => self.resume_core()?,
             Err(error) => {
                 return Err(RiscvError::DebugProbe(crate::DebugProbeError::Other(
@@ -172,64 +485,82 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
 
     fn reset_and_halt(
         &mut self,
-        _timeout: Duration,
+        reset_type: ResetType,
+        timeout: Duration,
     ) -> Result<crate::core::CoreInformation, crate::Error> {
-        tracing::debug!("Resetting core, setting hartreset bit");
+        self.state.trigger_halt_reason = None;
 
-        let mut dmcontrol = Dmcontrol(0);
-        dmcontrol.set_dmactive(true);
-        dmcontrol.set_hartreset(true);
-        dmcontrol.set_haltreq(true);
-
-        self.interface.write_dm_register(dmcontrol)?;
-
-        // Read back register to verify reset is supported
-        let readback: Dmcontrol = self.interface.read_dm_register()?;
+        if reset_type == ResetType::Hardware {
+            return Err(
+                crate::DebugProbeError::NotImplemented("Hardware reset of RISC-V cores").into(),
+            );
+        }
 
-        if readback.hartreset() {
-            tracing::debug!("Clearing hartreset bit");
-            // Reset is performed by setting the bit high, and then low again
-            let mut dmcontrol = Dmcontrol(0);
-            dmcontrol.set_dmactive(true);
-            dmcontrol.set_haltreq(true);
-            dmcontrol.set_hartreset(false);
+        // The reset halt request halts the hart before it executes the first instruction
+        // after the reset. Without it, the hart only halts because of the halt request,
+        // which might take effect after some instructions have been executed.
+        let dmstatus: Dmstatus = self.interface.read_dm_register()?;
+        let has_reset_halt_request = dmstatus.hasresethaltreq();
+        if has_reset_halt_request {
+            let mut dmcontrol = self.interface.dmcontrol();
+            dmcontrol.set_resethaltreq(true);
 
             self.interface.write_dm_register(dmcontrol)?;
-        } else {
-            // Hartreset is not supported, whole core needs to be reset
-            //
-            // TODO: Cache this
-            tracing::debug!("Hartreset bit not supported, using ndmreset");
-            let mut dmcontrol = Dmcontrol(0);
-            dmcontrol.set_dmactive(true);
-            dmcontrol.set_ndmreset(true);
-            dmcontrol.set_haltreq(true);
+        }
 
-            self.interface.write_dm_register(dmcontrol)?;
+        match reset_type {
+            ResetType::Sequence => {
+                if !self.hart_reset()? {
+                    // Hartreset is not supported, whole core needs to be reset
+                    //
+                    // TODO: Cache this
+                    tracing::debug!("Hartreset bit not supported, using ndmreset");
+                    self.ndm_reset()?;
+                }
+            }
+            ResetType::Core => {
+                if !self.hart_reset()? {
+                    let mut dmcontrol = self.interface.dmcontrol();
+                    dmcontrol.set_clrresethaltreq(has_reset_halt_request);
 
-            tracing::debug!("Clearing ndmreset bit");
-            let mut dmcontrol = Dmcontrol(0);
-            dmcontrol.set_dmactive(true);
-            dmcontrol.set_ndmreset(false);
-            dmcontrol.set_haltreq(true);
+                    self.interface.write_dm_register(dmcontrol)?;
 
-            self.interface.write_dm_register(dmcontrol)?;
+                    return Err(crate::DebugProbeError::NotImplemented(
+                        "Core reset, the debug module doesn't support hartreset",
+                    )
+                    .into());
+                }
+            }
+            ResetType::System => self.ndm_reset()?,
+            ResetType::Hardware => unreachable!("Checked above"),
         }
 
         // check that cores have reset
-        let readback: Dmstatus = self.interface.read_dm_register()?;
+        let start = Instant::now();
+        let halted = loop {
+            let readback: Dmstatus = self.interface.read_dm_register()?;
 
-        if !(readback.allhavereset() && readback.allhalted()) {
-            return Err(RiscvError::RequestNotAcknowledged.into());
-        }
+            if readback.allhavereset() && readback.allhalted() {
+                break true;
+            }
+
+            if start.elapsed() > timeout {
+                break false;
+            }
+        };
 
         // acknowledge the reset, clear the halt request
-        let mut dmcontrol = Dmcontrol(0);
-        dmcontrol.set_dmactive(true);
+        let mut dmcontrol = self.interface.dmcontrol();
         dmcontrol.set_ackhavereset(true);
+        // The reset halt request is cleared even if the hart didn't halt, so later resets aren't caught.
+        dmcontrol.set_clrresethaltreq(has_reset_halt_request);
 
         self.interface.write_dm_register(dmcontrol)?;
 
+        if !halted {
+            return Err(RiscvError::RequestNotAcknowledged.into());
+        }
+
         let pc = self.read_core_reg(RegisterId(0x7b1))?;
 
         Ok(CoreInformation { pc: pc.try_into()? })
@@ -242,19 +573,18 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
             CoreStatus::Halted(HaltReason::Breakpoint(BreakpointCause::Software))
         ) && self.state.hw_breakpoints_enabled
         {
-            // If we are halted on a software breakpoint AND we have passed the flashing operation, we can skip the single step and manually advance the dpc.
             let mut debug_pc = self.read_core_reg(RegisterId(0x7b1))?;
-            // Advance the dpc by the size of the EBREAK (ebreak or c.ebreak) instruction.
-            if matches!(self.instruction_set()?, InstructionSet::RV32C) {
-                debug_pc.increment_address(2)?;
-            } else {
-                debug_pc.increment_address(4)?;
-            }
 
-            self.write_core_reg(RegisterId(0x7b1), debug_pc)?;
-            return Ok(CoreInformation {
-                pc: debug_pc.try_into()?,
-            });
+            // If we are halted on a software breakpoint AND we have passed the flashing operation, we can skip the single step and manually advance the dpc.
+            // The EBREAK may have been replaced by the original instruction in the meantime, which has to be executed.
+            if let Some(size) = self.ebreak_size(debug_pc.try_into()?)? {
+                debug_pc.increment_address(size)?;
+
+                self.write_core_reg(RegisterId(0x7b1), debug_pc)?;
+                return Ok(CoreInformation {
+                    pc: debug_pc.try_into()?,
+                });
+            }
         } else if matches!(
             halt_reason,
             CoreStatus::Halted(HaltReason::Breakpoint(BreakpointCause::Hardware))
@@ -263,13 +593,34 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
             self.enable_breakpoints(false)?;
         }
 
+        // A watchpoint triggers before the access is performed, so it has to be disabled to
+        // step over the instruction which accesses the watched memory.
+        let disabled_watchpoint = match halt_reason {
+            CoreStatus::Halted(HaltReason::Watchpoint(WatchpointCause::Unit {
+                unit: unit_index,
+                ..
+            })) => {
+                self.write_csr(TSELECT, unit_index as u32)?;
+                let tdata_value = self.read_csr(TDATA1)?;
+
+                let mut disabled = Mcontrol(tdata_value);
+                disabled.set_m(false);
+                disabled.set_s(false);
+                disabled.set_u(false);
+                self.write_csr(TDATA1, disabled.0)?;
+
+                Some((unit_index, tdata_value))
+            }
+            _ => None,
+        };
+
         let mut dcsr = Dcsr(self.read_core_reg(RegisterId(0x7b0))?.try_into()?);
         // Set it up, so that the next `self.run()` will only do a single step
         dcsr.set_step(true);
         // Disable any interrupts during single step.
         dcsr.set_stepie(false);
         dcsr.set_stopcount(true);
-        self.write_csr(0x7b0, dcsr.0)?;
+        self.write_csr(csr::DCSR, dcsr.0)?;
 
         // Now we can resume the core for the single step.
         self.resume_core()?;
@@ -283,7 +634,7 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
         //Re-enable interrupts for single step.
         dcsr.set_stepie(true);
         dcsr.set_stopcount(false);
-        self.write_csr(0x7b0, dcsr.0)?;
+        self.write_csr(csr::DCSR, dcsr.0)?;
 
         // Re-enable breakpoints before we continue.
         if matches!(
@@ -294,10 +645,19 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
             self.enable_breakpoints(true)?;
         }
 
+        if let Some((unit_index, tdata_value)) = disabled_watchpoint {
+            self.write_csr(TSELECT, unit_index as u32)?;
+            self.write_csr(TDATA1, tdata_value)?;
+        }
+
         Ok(CoreInformation { pc: pc.try_into()? })
     }
 
     fn read_core_reg(&mut self, address: RegisterId) -> Result<RegisterValue, crate::Error> {
+        if is_fp_register(address) {
+            return self.read_fp_register(address);
+        }
+
         self.read_csr(address.0)
             .map(|v| v.into())
             .map_err(|e| e.into())
@@ -308,6 +668,10 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
         address: RegisterId,
         value: RegisterValue,
     ) -> Result<(), crate::Error> {
+        if is_fp_register(address) {
+            return self.write_fp_register(address, value);
+        }
+
         let value: u32 = value.try_into()?;
         self.write_csr(address.0, value).map_err(|e| e.into())
     }
@@ -317,29 +681,25 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
 
         tracing::debug!("Determining number of HW breakpoints supported");
 
-        let tselect = 0x7a0;
-        let tdata1 = 0x7a1;
-        let tinfo = 0x7a4;
-
         let mut tselect_index = 0;
 
         // These steps follow the debug specification 0.13, section 5.1 Enumeration
         loop {
             tracing::debug!("Trying tselect={}", tselect_index);
-            if let Err(e) = self.write_csr(tselect, tselect_index) {
+            if let Err(e) = self.write_csr(TSELECT, tselect_index) {
                 match e {
                     RiscvError::AbstractCommand(AbstractCommandErrorKind::Exception) => break,
                     other_error => return Err(other_error.into()),
                 }
             }
 
-            let readback = self.read_csr(tselect)?;
+            let readback = self.read_csr(TSELECT)?;
 
             if readback != tselect_index {
                 break;
             }
 
-            match self.read_csr(tinfo) {
+            match self.read_csr(TINFO) {
                 Ok(tinfo_val) => {
                     if tinfo_val & 0xffff == 1 {
                         // Trigger doesn't exist, break the loop
@@ -354,10 +714,10 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
                 }
                 Err(RiscvError::AbstractCommand(AbstractCommandErrorKind::Exception)) => {
                     // An exception means we have to read tdata1 to discover the type
-                    let tdata_val = self.read_csr(tdata1)?;
+                    let tdata_val = self.read_csr(TDATA1)?;
 
                     // Read the mxl field from the misa register (see RISC-V Privileged Spec, 3.1.1)
-                    let misa_value = Misa(self.read_csr(0x301)?);
+                    let misa_value = Misa(self.read_csr(csr::MISA)?);
                     let xlen = u32::pow(2, misa_value.mxl() + 4);
 
                     let trigger_type = tdata_val >> (xlen - 4);
@@ -385,15 +745,13 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
 
     fn enable_breakpoints(&mut self, state: bool) -> Result<(), crate::Error> {
         // Loop through all triggers, and enable/disable them.
-        let tselect = 0x7a0;
-        let tdata1 = 0x7a1;
 
         for bp_unit_index in 0..self.available_breakpoint_units()? as usize {
             // Select the trigger.
-            self.write_csr(tselect, bp_unit_index as u32)?;
+            self.write_csr(TSELECT, bp_unit_index as u32)?;
 
             // Read the trigger "configuration" data.
-            let mut tdata_value = Mcontrol(self.read_csr(tdata1)?);
+            let mut tdata_value = Mcontrol(self.read_csr(TDATA1)?);
 
             // Only modify the trigger if it is for an execution debug action in all modes(probe-rs enabled it) or no modes (we previously disabled it).
             if tdata_value.type_() == 0b10
@@ -410,7 +768,7 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
                 );
                 tdata_value.set_m(state);
                 tdata_value.set_u(state);
-                self.write_csr(tdata1, tdata_value.0)?;
+                self.write_csr(TDATA1, tdata_value.0)?;
             }
         }
 
@@ -426,17 +784,14 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
         }
 
         // select requested trigger
-        let tselect = 0x7a0;
-        let tdata1 = 0x7a1;
-        let tdata2 = 0x7a2;
 
         tracing::warn!("Setting breakpoint {}", bp_unit_index);
 
-        self.write_csr(tselect, bp_unit_index as u32)?;
+        self.write_csr(TSELECT, bp_unit_index as u32)?;
 
         // verify the trigger has the correct type
 
-        let tdata_value = Mcontrol(self.read_csr(tdata1)?);
+        let tdata_value = Mcontrol(self.read_csr(TDATA1)?);
 
         // This should not happen
         let trigger_type = tdata_value.type_();
@@ -466,26 +821,39 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
         // Match address
         instruction_breakpoint.set_select(false);
 
-        self.write_csr(tdata1, instruction_breakpoint.0)?;
-        self.write_csr(tdata2, addr)?;
+        self.write_csr(TDATA1, instruction_breakpoint.0)?;
+        self.write_csr(TDATA2, addr)?;
 
         Ok(())
     }
 
     fn clear_hw_breakpoint(&mut self, unit_index: usize) -> Result<(), crate::Error> {
-        let tselect = 0x7a0;
-        let tdata1 = 0x7a1;
-        let tdata2 = 0x7a2;
-
-        self.write_csr(tselect, unit_index as u32)?;
-        self.write_csr(tdata1, 0)?;
-        self.write_csr(tdata2, 0)?;
+        self.write_csr(TSELECT, unit_index as u32)?;
+        self.write_csr(TDATA1, 0)?;
+        self.write_csr(TDATA2, 0)?;
 
         Ok(())
     }
 
+    fn read_csr(&mut self, address: u16) -> Result<u32, Error> {
+        // Abstract register numbers above the CSR range are used for the GPRs.
+        if address > RISCV_MAX_CSR_ADDR {
+            return Err(RiscvError::UnsupportedCsrAddress(address).into());
+        }
+
+        Ok(Riscv32::read_csr(self, address)?)
+    }
+
+    fn write_csr(&mut self, address: u16, value: u32) -> Result<(), Error> {
+        if address > RISCV_MAX_CSR_ADDR {
+            return Err(RiscvError::UnsupportedCsrAddress(address).into());
+        }
+
+        Ok(Riscv32::write_csr(self, address, value)?)
+    }
+
     fn registers(&self) -> &'static RegisterFile {
-        &RISCV_REGISTERS
+        register_file(self.state.misa)
     }
 
     fn hw_breakpoints_enabled(&self) -> bool {
@@ -501,7 +869,7 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
     }
 
     fn instruction_set(&mut self) -> Result<InstructionSet, Error> {
-        let misa_value = Misa(self.read_csr(0x301)?);
+        let misa_value = Misa(self.read_csr(csr::MISA)?);
 
         // Check if the Bit at position 2 (signifies letter C, for compressed) is set.
         if misa_value.extensions() & (1 << 2) != 0 {
@@ -523,9 +891,9 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
 
             let reason = match dcsr.cause() {
                 // An ebreak instruction was hit
-                1 => HaltReason::Breakpoint(BreakpointCause::Software),
+                1 => self.ebreak_halt_reason()?,
                 // Trigger module caused halt
-                2 => HaltReason::Breakpoint(BreakpointCause::Hardware),
+                2 => self.trigger_halt_reason()?,
                 // Debugger requested a halt
                 3 => HaltReason::Request,
                 // Core halted after single step
@@ -536,6 +904,13 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
                 _ => HaltReason::Unknown,
             };
 
+            if self.state.misa.is_none() {
+                // The available registers depend on the ISA, which can only be read while halted.
+                if let Err(error) = self.misa() {
+                    tracing::debug!("Failed to read misa: {}", error);
+                }
+            }
+
             Ok(CoreStatus::Halted(reason))
         } else if status.allrunning() {
             Ok(CoreStatus::Running)
@@ -548,50 +923,74 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
     }
 
     /// See docs on the [`CoreInterface::hw_breakpoints`] trait
-    /// NOTE: For riscv, this assumes that only execution breakpoints are used.
+    /// NOTE: For riscv, breakpoints and watchpoints use the same triggers, so triggers used
+    /// as watchpoints are reported as well.
     fn hw_breakpoints(&mut self) -> Result<Vec<Option<u64>>, Error> {
-        let tselect = 0x7a0;
-        let tdata1 = 0x7a1;
-        let tdata2 = 0x7a2;
+        Ok(self
+            .configured_triggers()?
+            .into_iter()
+            .map(|trigger| trigger.map(|(tdata1, tdata2)| trigger_address(tdata1, tdata2) as u64))
+            .collect())
+    }
 
-        let mut breakpoints = vec![];
-        let num_hw_breakpoints = self.available_breakpoint_units()? as usize;
-        for bp_unit_index in 0..num_hw_breakpoints {
-            // Select the trigger.
-            self.write_csr(tselect, bp_unit_index as u32)?;
+    fn available_watchpoint_units(&mut self) -> Result<u32, Error> {
+        // Breakpoints and watchpoints use the same triggers.
+        self.available_breakpoint_units()
+    }
 
-            // Read the trigger "configuration" data.
-            let tdata_value = Mcontrol(self.read_csr(tdata1)?);
+    /// See docs on the [`CoreInterface::hw_watchpoints`] trait
+    /// NOTE: For riscv, breakpoints and watchpoints use the same triggers, so triggers used
+    /// as breakpoints are reported as well, so they don't get overwritten.
+    fn hw_watchpoints(&mut self) -> Result<Vec<Option<u64>>, Error> {
+        self.hw_breakpoints()
+    }
 
-            tracing::warn!("Breakpoint {}: {:?}", bp_unit_index, tdata_value);
+    fn set_hw_watchpoint(
+        &mut self,
+        unit_index: usize,
+        address: u64,
+        size: u32,
+        kind: WatchpointKind,
+    ) -> Result<(), Error> {
+        let address = valid_32bit_address(address)?;
 
-            // The trigger must be active in at least a single mode
-            let trigger_any_mode_active = tdata_value.m() || tdata_value.s() || tdata_value.u();
+        self.write_csr(TSELECT, unit_index as u32)?;
 
-            let trigger_any_action_enabled =
-                tdata_value.execute() || tdata_value.store() || tdata_value.load();
+        // verify the trigger has the correct type
+        let tdata_value = Mcontrol(self.read_csr(TDATA1)?);
 
-            // Only return if the trigger if it is for an execution debug action in all modes.
-            if tdata_value.type_() == 0b10
-                && tdata_value.action() == 1
-                && tdata_value.match_() == 0
-                && trigger_any_mode_active
-                && trigger_any_action_enabled
-            {
-                let breakpoint = self.read_csr(tdata2)?;
-                breakpoints.push(Some(breakpoint as u64));
-            } else {
-                breakpoints.push(None);
-            }
+        let trigger_type = tdata_value.type_();
+        if trigger_type != 0b10 {
+            return Err(RiscvError::UnexpectedTriggerType(trigger_type).into());
         }
 
-        Ok(breakpoints)
+        let (watchpoint, tdata2) = watchpoint_trigger(address, size, kind, tdata_value.maskmax())?;
+
+        self.write_csr(TDATA1, watchpoint.0)?;
+        self.write_csr(TDATA2, tdata2)?;
+
+        Ok(())
+    }
+
+    fn clear_hw_watchpoint(&mut self, unit_index: usize) -> Result<(), Error> {
+        self.clear_hw_breakpoint(unit_index)
     }
 
     fn fpu_support(&mut self) -> Result<bool, crate::error::Error> {
-        Err(crate::error::Error::Other(anyhow::anyhow!(
-            "Fpu detection not yet implemented"
-        )))
+        Ok(self.misa()?.extensions() & (MISA_F | MISA_D) != 0)
+    }
+
+    fn reset_detected(&mut self) -> Result<bool, crate::error::Error> {
+        let status: Dmstatus = self.interface.read_dm_register()?;
+
+        if status.anyhavereset() {
+            let mut dmcontrol = self.interface.dmcontrol();
+            dmcontrol.set_ackhavereset(true);
+
+            self.interface.write_dm_register(dmcontrol)?;
+        }
+
+        Ok(status.anyhavereset())
     }
 
     fn debug_on_sw_breakpoint(&mut self, enabled: bool) -> Result<(), crate::error::Error> {
@@ -601,7 +1000,7 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
         dcsr.set_ebreaks(enabled);
         dcsr.set_ebreaku(enabled);
 
-        self.write_csr(0x7b0, dcsr.0).map_err(|e| e.into())
+        self.write_csr(csr::DCSR, dcsr.0).map_err(|e| e.into())
     }
 }
 
@@ -618,6 +1017,10 @@ impl<'probe> MemoryInterface for Riscv32<'probe> {
         self.interface.read_word_32(address)
     }
 
+    fn read_word_16(&mut self, address: u64) -> Result<u16, Error> {
+        self.interface.read_word_16(address)
+    }
+
     fn read_word_8(&mut self, address: u64) -> Result<u8, Error> {
         self.interface.read_word_8(address)
     }
@@ -630,6 +1033,10 @@ impl<'probe> MemoryInterface for Riscv32<'probe> {
         self.interface.read_32(address, data)
     }
 
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), Error> {
+        self.interface.read_16(address, data)
+    }
+
     fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error> {
         self.interface.read_8(address, data)
     }
@@ -642,6 +1049,10 @@ impl<'probe> MemoryInterface for Riscv32<'probe> {
         self.interface.write_word_32(address, data)
     }
 
+    fn write_word_16(&mut self, address: u64, data: u16) -> Result<(), Error> {
+        self.interface.write_word_16(address, data)
+    }
+
     fn write_word_8(&mut self, address: u64, data: u8) -> Result<(), Error> {
         self.interface.write_word_8(address, data)
     }
@@ -654,6 +1065,10 @@ impl<'probe> MemoryInterface for Riscv32<'probe> {
         self.interface.write_32(address, data)
     }
 
+    fn write_16(&mut self, address: u64, data: &[u16]) -> Result<(), Error> {
+        self.interface.write_16(address, data)
+    }
+
     fn write_8(&mut self, address: u64, data: &[u8]) -> Result<(), Error> {
         self.interface.write_8(address, data)
     }
@@ -671,17 +1086,146 @@ impl<'probe> MemoryInterface for Riscv32<'probe> {
     }
 }
 
+/// Returns true if `address` is one of the floating point registers, including `fcsr`.
+fn is_fp_register(address: RegisterId) -> bool {
+    address == FCSR.id || (FP_REGISTER_BASE..FP_REGISTER_BASE + 32).contains(&address.0)
+}
+
+/// Returns the register file for the extensions in `misa`. If `misa` wasn't read yet,
+/// only the integer registers are available.
+fn register_file(misa: Option<u32>) -> &'static RegisterFile {
+    let extensions = misa.map_or(0, |misa| Misa(misa).extensions());
+
+    if extensions & MISA_D != 0 {
+        &RISCV_WITH_DP_REGS
+    } else if extensions & MISA_F != 0 {
+        &RISCV_WITH_FP_REGS
+    } else {
+        &RISCV_REGISTERS
+    }
+}
+
+/// The `ebreak` instruction.
+const EBREAK: u32 = 0x0010_0073;
+/// The `c.ebreak` instruction.
+const C_EBREAK: u16 = 0x9002;
+
+/// Returns true if the instruction starting with the 16-bit `parcel` is a 32-bit instruction,
+/// i.e. it isn't a compressed instruction.
+fn is_32bit_instruction(parcel: u16) -> bool {
+    parcel & 0b11 == 0b11
+}
+
+/// Returns the size of the `ebreak` or `c.ebreak` instruction in `instruction`, which
+/// contains either a compressed or a 32-bit instruction.
+fn ebreak_size(instruction: &[u8]) -> Option<usize> {
+    match *instruction {
+        [b0, b1] => (u16::from_le_bytes([b0, b1]) == C_EBREAK).then_some(2),
+        [b0, b1, b2, b3] => (u32::from_le_bytes([b0, b1, b2, b3]) == EBREAK).then_some(4),
+        _ => None,
+    }
+}
+
+/// The instructions of the semihosting sequence: `slli x0, x0, 0x1f`, `ebreak`, `srai x0, x0, 7`.
+const SEMIHOSTING_SEQUENCE: [u32; 3] = [0x01F0_1013, EBREAK, 0x4070_5013];
+
+/// Checks if `instructions` is the semihosting sequence.
+///
+/// All instructions of the sequence are uncompressed, even if compressed instructions are supported.
+fn is_semihosting_sequence(instructions: [u8; 12]) -> bool {
+    instructions
+        .chunks_exact(4)
+        .zip(SEMIHOSTING_SEQUENCE)
+        .all(|(instruction, expected)| instruction == expected.to_le_bytes())
+}
+
+/// Returns the address watched by a trigger. For a naturally aligned power of two (NAPOT)
+/// range, this is the start of the range.
+fn trigger_address(tdata1: u32, tdata2: u32) -> u32 {
+    if Mcontrol(tdata1).match_() == 1 {
+        // The size of the range is encoded in the trailing ones of the address.
+        tdata2 & tdata2.wrapping_add(1)
+    } else {
+        tdata2
+    }
+}
+
+/// Returns the `mcontrol` configuration and the `tdata2` value of a trigger which halts the core
+/// on accesses to the `size` bytes at `address`.
+///
+/// Watching more than a single byte requires a trigger which supports matching a naturally
+/// aligned power of two (NAPOT) range of `size` bytes, as reported by `maskmax`.
+fn watchpoint_trigger(
+    address: u32,
+    size: u32,
+    kind: WatchpointKind,
+    maskmax: u32,
+) -> Result<(Mcontrol, u32), Error> {
+    if !size.is_power_of_two() || (size > 1 && size.trailing_zeros() > maskmax) {
+        return Err(Error::Other(anyhow!(
+            "A watchpoint on {} bytes is not supported by this core",
+            size
+        )));
+    }
+
+    if address & (size - 1) != 0 {
+        return Err(Error::MemoryNotAligned {
+            address: address.into(),
+            alignment: size as usize,
+        });
+    }
+
+    let mut watchpoint = Mcontrol(0);
+
+    watchpoint.set_type(0b10);
+    watchpoint.set_dmode(true);
+
+    // Enter debug mode
+    watchpoint.set_action(1);
+
+    watchpoint.set_m(true);
+    watchpoint.set_u(true);
+
+    watchpoint.set_load(matches!(
+        kind,
+        WatchpointKind::Read | WatchpointKind::ReadWrite
+    ));
+    watchpoint.set_store(matches!(
+        kind,
+        WatchpointKind::Write | WatchpointKind::ReadWrite
+    ));
+
+    let tdata2 = if size == 1 {
+        // Match exactly the value in tdata2
+        watchpoint.set_match(0);
+        address
+    } else {
+        // Match a NAPOT range, the size is encoded in the trailing ones of tdata2
+        watchpoint.set_match(1);
+        address | ((size >> 1) - 1)
+    };
+
+    Ok((watchpoint, tdata2))
+}
+
 #[derive(Debug)]
 /// Flags used to control the [`SpecificCoreState`](crate::core::SpecificCoreState) for RiscV architecture
 pub struct RiscVState {
     /// A flag to remember whether we want to use hw_breakpoints during stepping of the core.
     hw_breakpoints_enabled: bool,
+    /// The halt reason of the trigger which halted the core, which is cached
+    /// because identifying the trigger clears its `hit` bit.
+    trigger_halt_reason: Option<HaltReason>,
+    /// The `misa` register, which determines the available registers.
+    misa: Option<u32>,
 }
 
 impl RiscVState {
     pub(crate) fn new() -> Self {
         Self {
             hw_breakpoints_enabled: false,
+            trigger_halt_reason: None,
+            misa: None,
         }
     }
 }
@@ -792,6 +1336,20 @@ memory_mapped_bitfield_register! {
     dataaddr, _: 11, 0;
 }
 
+memory_mapped_bitfield_register! {
+    /// Hart Array Window Select (see 3.12.5)
+    pub struct Hawindowsel(u32);
+    0x14, "hawindowsel",
+    impl From;
+}
+
+memory_mapped_bitfield_register! {
+    /// Hart Array Window (see 3.12.6)
+    pub struct Hawindow(u32);
+    0x15, "hawindow",
+    impl From;
+}
+
 memory_mapped_bitfield_register! { pub struct Data0(u32); 0x04, "data0", impl From; }
 memory_mapped_bitfield_register! { pub struct Data1(u32); 0x05, "data1", impl From; }
 memory_mapped_bitfield_register! { pub struct Data2(u32); 0x06, "data2", impl From; }
@@ -856,3 +1414,184 @@ bitfield! {
     /// Standard RISC-V extensions
     extensions, _: 25, 0;
 }
+
+#[cfg(test)]
+mod test {
+    use super::{
+        communication_interface::{RiscvCommunicationInterface, RiscvError},
+        csr, ebreak_size, is_32bit_instruction, is_fp_register, is_semihosting_sequence,
+        mock::MockDebugModule,
+        register_file, trigger_address, watchpoint_trigger, RiscVState, Riscv32, C_EBREAK,
+    };
+    use std::time::Duration;
+
+    use crate::{
+        core::{RegisterId, WatchpointKind},
+        CoreInterface, DebugProbeError, Error, ResetType,
+    };
+
+    #[test]
+    fn ebreak_sizes() {
+        assert_eq!(ebreak_size(&0x0010_0073u32.to_le_bytes()), Some(4));
+        assert_eq!(ebreak_size(&[0x02, 0x90]), Some(2));
+        // `addi a0, a0, 1`, e.g. restored after a software breakpoint was removed.
+        assert_eq!(ebreak_size(&0x0015_0513u32.to_le_bytes()), None);
+        // `c.addi a0, 1`
+        assert_eq!(ebreak_size(&[0x05, 0x05]), None);
+    }
+
+    #[test]
+    fn instruction_sizes() {
+        assert!(is_32bit_instruction(0x0073));
+        assert!(!is_32bit_instruction(C_EBREAK));
+    }
+
+    #[test]
+    fn semihosting_sequence() {
+        let mut sequence = [0; 12];
+        sequence[0..4].copy_from_slice(&0x01F0_1013u32.to_le_bytes());
+        sequence[4..8].copy_from_slice(&0x0010_0073u32.to_le_bytes());
+        sequence[8..12].copy_from_slice(&0x4070_5013u32.to_le_bytes());
+
+        assert!(is_semihosting_sequence(sequence));
+
+        // A plain ebreak.
+        sequence[0..4].copy_from_slice(&0x0000_0013u32.to_le_bytes());
+        assert!(!is_semihosting_sequence(sequence));
+    }
+
+    #[test]
+    fn floating_point_register_files() {
+        assert!(register_file(None).fp_registers.is_none());
+        assert!(register_file(Some(0x4000_1104)).fp_registers.is_none());
+
+        // RV32IMAFC
+        let registers = register_file(Some(0x4000_1125)).fp_registers.unwrap();
+        assert_eq!(registers.len(), 32);
+        assert_eq!(registers[0].size_in_bits, 32);
+
+        // RV32IMAFDC
+        let registers = register_file(Some(0x4000_112D)).fp_registers.unwrap();
+        assert_eq!(registers[31].size_in_bits, 64);
+
+        assert!(is_fp_register(RegisterId(0x103F)));
+        assert!(is_fp_register(RegisterId(0x003)));
+        assert!(!is_fp_register(RegisterId(0x1040)));
+    }
+
+    #[test]
+    fn single_byte_watchpoint() {
+        let (mcontrol, tdata2) =
+            watchpoint_trigger(0x2000_0003, 1, WatchpointKind::Write, 0).unwrap();
+
+        assert_eq!(mcontrol.type_(), 0b10);
+        assert_eq!(mcontrol.match_(), 0);
+        assert!(mcontrol.store());
+        assert!(!mcontrol.load());
+        assert_eq!(tdata2, 0x2000_0003);
+    }
+
+    #[test]
+    fn napot_watchpoint() {
+        let (mcontrol, tdata2) =
+            watchpoint_trigger(0x2000_0100, 8, WatchpointKind::ReadWrite, 31).unwrap();
+
+        assert_eq!(mcontrol.match_(), 1);
+        assert!(mcontrol.store() && mcontrol.load());
+        assert_eq!(tdata2, 0x2000_0103);
+        assert_eq!(trigger_address(mcontrol.0, tdata2), 0x2000_0100);
+    }
+
+    #[test]
+    fn csr_access() {
+        let mut debug_module = MockDebugModule::new();
+        debug_module.csrs.insert(csr::MISA, 0x4000_1104);
+
+        let mut interface = RiscvCommunicationInterface::new(Box::new(debug_module)).unwrap();
+        let mut state = RiscVState::new();
+        let mut core = Riscv32::new(&mut interface, &mut state);
+
+        assert_eq!(
+            CoreInterface::read_csr(&mut core, csr::MISA).unwrap(),
+            0x4000_1104
+        );
+
+        CoreInterface::write_csr(&mut core, csr::MSCRATCH, 0x1234_5678).unwrap();
+        assert_eq!(
+            CoreInterface::read_csr(&mut core, csr::MSCRATCH).unwrap(),
+            0x1234_5678
+        );
+    }
+
+    #[test]
+    fn csr_addresses_are_checked() {
+        let mut interface =
+            RiscvCommunicationInterface::new(Box::new(MockDebugModule::new())).unwrap();
+        let mut state = RiscVState::new();
+        let mut core = Riscv32::new(&mut interface, &mut state);
+
+        // The abstract register numbers above the CSRs are used for the GPRs.
+        assert!(matches!(
+            CoreInterface::read_csr(&mut core, 0x1000),
+            Err(Error::Riscv(RiscvError::UnsupportedCsrAddress(0x1000)))
+        ));
+        assert!(matches!(
+            CoreInterface::write_csr(&mut core, 0x1001, 0),
+            Err(Error::Riscv(RiscvError::UnsupportedCsrAddress(0x1001)))
+        ));
+    }
+
+    /// Resets the hart of `debug_module` with a system reset, and halts it.
+    fn reset_and_halt(debug_module: MockDebugModule) -> Result<u64, Error> {
+        let mut interface = RiscvCommunicationInterface::new(Box::new(debug_module)).unwrap();
+        let mut state = RiscVState::new();
+        let mut core = Riscv32::new(&mut interface, &mut state);
+
+        core.reset_and_halt(ResetType::System, Duration::from_millis(10))
+            .map(|info| info.pc)
+    }
+
+    #[test]
+    fn reset_is_caught() {
+        let mut debug_module = MockDebugModule::new();
+        debug_module.csrs.insert(csr::DPC, 0x2000_0000);
+        debug_module.has_reset_halt_request = true;
+        // The hart is only halted by the reset halt request.
+        debug_module.halt_requests_ignored = true;
+
+        assert_eq!(reset_and_halt(debug_module).unwrap(), 0x2000_0000);
+    }
+
+    #[test]
+    fn reset_and_halt_times_out() {
+        let mut debug_module = MockDebugModule::new();
+        debug_module.halt_requests_ignored = true;
+
+        assert!(matches!(
+            reset_and_halt(debug_module),
+            Err(Error::Riscv(RiscvError::RequestNotAcknowledged))
+        ));
+    }
+
+    #[test]
+    fn hardware_reset_is_not_supported() {
+        let mut interface =
+            RiscvCommunicationInterface::new(Box::new(MockDebugModule::new())).unwrap();
+        let mut state = RiscVState::new();
+        let mut core = Riscv32::new(&mut interface, &mut state);
+
+        assert!(matches!(
+            core.reset_and_halt(ResetType::Hardware, Duration::from_millis(10)),
+            Err(Error::Probe(DebugProbeError::NotImplemented(_)))
+        ));
+    }
+
+    #[test]
+    fn unsupported_watchpoints() {
+        // Range larger than supported by the trigger
+        assert!(watchpoint_trigger(0x2000_0100, 8, WatchpointKind::Read, 2).is_err());
+        // Unaligned
+        assert!(watchpoint_trigger(0x2000_0102, 4, WatchpointKind::Read, 31).is_err());
+        assert!(watchpoint_trigger(0x2000_0100, 3, WatchpointKind::Read, 31).is_err());
+    }
+}
@@ -54,7 +54,7 @@ pub const S1: RegisterDescription = RegisterDescription {
     size_in_bits: 32,
 };
 
-pub(super) const RISCV_REGISTERS: RegisterFile = RegisterFile {
+pub(crate) const RISCV_REGISTERS: RegisterFile = RegisterFile {
     platform_registers: &[
         RegisterDescription {
             name: "x0",
@@ -370,7 +370,484 @@ pub(super) const RISCV_REGISTERS: RegisterFile = RegisterFile {
     msp: None,
     other: &[],
     psr: None,
-    // TODO: Add FPU registers
     fp_registers: None,
     fp_status: None,
 };
+
+/// ID of the first floating point register `f0`, the IDs of `f1` to `f31` follow.
+pub(super) const FP_REGISTER_BASE: u16 = 0x1020;
+
+/// The floating-point control and status register, accessed as CSR.
+pub(super) const FCSR: RegisterDescription = RegisterDescription {
+    name: "fcsr",
+    _kind: RegisterKind::Fp,
+    id: RegisterId(0x003),
+    _type: RegisterDataType::UnsignedInteger,
+    size_in_bits: 32,
+};
+
+/// Registers of a core with the F extension, which has single precision floating point registers.
+pub(crate) const RISCV_WITH_FP_REGS: RegisterFile = RegisterFile {
+    fp_registers: Some(&[
+        RegisterDescription {
+            name: "f0",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1020),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f1",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1021),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f2",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1022),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f3",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1023),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f4",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1024),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f5",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1025),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f6",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1026),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f7",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1027),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f8",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1028),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f9",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1029),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f10",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x102A),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f11",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x102B),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f12",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x102C),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f13",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x102D),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f14",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x102E),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f15",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x102F),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f16",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1030),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f17",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1031),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f18",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1032),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f19",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1033),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f20",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1034),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f21",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1035),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f22",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1036),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f23",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1037),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f24",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1038),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f25",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1039),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f26",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x103A),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f27",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x103B),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f28",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x103C),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f29",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x103D),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f30",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x103E),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+        RegisterDescription {
+            name: "f31",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x103F),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 32,
+        },
+    ]),
+    fp_status: Some(&FCSR),
+
+    ..RISCV_REGISTERS
+};
+
+/// Registers of a core with the D extension, which has double precision floating point registers.
+pub(crate) const RISCV_WITH_DP_REGS: RegisterFile = RegisterFile {
+    fp_registers: Some(&[
+        RegisterDescription {
+            name: "f0",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1020),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f1",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1021),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f2",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1022),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f3",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1023),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f4",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1024),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f5",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1025),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f6",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1026),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f7",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1027),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f8",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1028),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f9",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1029),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f10",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x102A),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f11",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x102B),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f12",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x102C),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f13",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x102D),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f14",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x102E),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f15",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x102F),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f16",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1030),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f17",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1031),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f18",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1032),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f19",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1033),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f20",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1034),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f21",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1035),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f22",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1036),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f23",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1037),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f24",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1038),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f25",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x1039),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f26",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x103A),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f27",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x103B),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f28",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x103C),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f29",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x103D),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f30",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x103E),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+        RegisterDescription {
+            name: "f31",
+            _kind: RegisterKind::Fp,
+            id: RegisterId(0x103F),
+            _type: RegisterDataType::FloatingPoint,
+            size_in_bits: 64,
+        },
+    ]),
+    fp_status: Some(&FCSR),
+
+    ..RISCV_REGISTERS
+};
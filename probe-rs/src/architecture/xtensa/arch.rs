@@ -0,0 +1,176 @@
+//! Xtensa registers and instruction encodings used by the debugger.
+
+/// An address register of the current register window, `a0` to `a15`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CpuRegister(u8);
+
+impl CpuRegister {
+    /// The register which is used as scratch register, to transfer values between
+    /// the debug data register and special registers or memory.
+    pub const A3: CpuRegister = CpuRegister(3);
+
+    /// Creates the address register `a<index>`. Returns `None` if `index` is larger than 15.
+    pub fn new(index: u8) -> Option<Self> {
+        (index < 16).then_some(Self(index))
+    }
+
+    /// The index of the register in the current window.
+    pub fn index(self) -> u8 {
+        self.0
+    }
+}
+
+/// A special register, accessed with the `RSR` and `WSR` instructions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpecialRegister(u8);
+
+#[allow(dead_code)]
+impl SpecialRegister {
+    pub const LBEG: SpecialRegister = SpecialRegister(0);
+    pub const LEND: SpecialRegister = SpecialRegister(1);
+    pub const LCOUNT: SpecialRegister = SpecialRegister(2);
+    pub const SAR: SpecialRegister = SpecialRegister(3);
+    pub const BR: SpecialRegister = SpecialRegister(4);
+    pub const SCOMPARE1: SpecialRegister = SpecialRegister(12);
+    pub const WINDOWBASE: SpecialRegister = SpecialRegister(72);
+    pub const WINDOWSTART: SpecialRegister = SpecialRegister(73);
+    pub const IBREAKENABLE: SpecialRegister = SpecialRegister(96);
+    pub const DDR: SpecialRegister = SpecialRegister(104);
+    pub const IBREAKA0: SpecialRegister = SpecialRegister(128);
+    pub const DBREAKA0: SpecialRegister = SpecialRegister(144);
+    pub const DBREAKC0: SpecialRegister = SpecialRegister(160);
+    pub const EPC1: SpecialRegister = SpecialRegister(177);
+    pub const EPS2: SpecialRegister = SpecialRegister(194);
+    pub const EXCSAVE1: SpecialRegister = SpecialRegister(209);
+    pub const CPENABLE: SpecialRegister = SpecialRegister(224);
+    pub const PS: SpecialRegister = SpecialRegister(230);
+    pub const VECBASE: SpecialRegister = SpecialRegister(231);
+    pub const EXCCAUSE: SpecialRegister = SpecialRegister(232);
+    pub const DEBUGCAUSE: SpecialRegister = SpecialRegister(233);
+    pub const CCOUNT: SpecialRegister = SpecialRegister(234);
+    pub const PRID: SpecialRegister = SpecialRegister(235);
+    pub const ICOUNT: SpecialRegister = SpecialRegister(236);
+    pub const ICOUNTLEVEL: SpecialRegister = SpecialRegister(237);
+    pub const EXCVADDR: SpecialRegister = SpecialRegister(238);
+
+    /// Creates a special register from its number.
+    pub const fn new(number: u8) -> Self {
+        Self(number)
+    }
+
+    /// The number of the special register, as used in the `RSR` and `WSR` instructions.
+    pub fn number(self) -> u8 {
+        self.0
+    }
+
+    /// The exception PC of the given interrupt level.
+    pub fn epc(level: u8) -> Self {
+        Self(Self::EPC1.0 + level - 1)
+    }
+
+    /// The exception PS of the given interrupt level. There is no EPS for level 1.
+    pub fn eps(level: u8) -> Self {
+        Self(Self::EPS2.0 + level - 2)
+    }
+
+    /// The address register of instruction breakpoint `unit`.
+    pub fn ibreaka(unit: u8) -> Self {
+        Self(Self::IBREAKA0.0 + unit)
+    }
+}
+
+/// Bits of the `DEBUGCAUSE` register.
+pub mod debug_cause {
+    /// The core halted after an instruction step.
+    pub const ICOUNT: u32 = 1 << 0;
+    /// The core hit an instruction breakpoint.
+    pub const IBREAK: u32 = 1 << 1;
+    /// The core hit a data breakpoint.
+    pub const DBREAK: u32 = 1 << 2;
+    /// The core executed a `BREAK` instruction.
+    pub const BREAK: u32 = 1 << 3;
+    /// The core executed a `BREAK.N` instruction.
+    pub const BREAKN: u32 = 1 << 4;
+    /// The core was halted by a debug interrupt.
+    pub const DEBUGINT: u32 = 1 << 5;
+}
+
+/// Encodings of the instructions executed by the debugger.
+///
+/// Only the little endian encodings are supported.
+pub mod instruction {
+    use super::{CpuRegister, SpecialRegister};
+
+    /// Encodes an instruction in the RRR format.
+    const fn rrr(opcode: u32, r: u8, s: u8, t: u8) -> u32 {
+        opcode | ((r as u32 & 0xF) << 12) | ((s as u32 & 0xF) << 8) | ((t as u32 & 0xF) << 4)
+    }
+
+    /// Encodes an instruction in the RSR format, which is used for special register access.
+    const fn rsr_format(opcode: u32, sr: u8, t: u8) -> u32 {
+        opcode | ((sr as u32) << 8) | ((t as u32 & 0xF) << 4)
+    }
+
+    /// Reads the special register `sr` into `t`.
+    pub fn rsr(sr: SpecialRegister, t: CpuRegister) -> u32 {
+        rsr_format(0x03_0000, sr.number(), t.index())
+    }
+
+    /// Writes `t` into the special register `sr`.
+    pub fn wsr(sr: SpecialRegister, t: CpuRegister) -> u32 {
+        rsr_format(0x13_0000, sr.number(), t.index())
+    }
+
+    /// Loads the word at the address in `s` into DDR, and increments `s` by 4.
+    pub fn lddr32_p(s: CpuRegister) -> u32 {
+        rrr(0x00_70E0, 0, s.index(), 0)
+    }
+
+    /// Stores DDR to the address in `s`, and increments `s` by 4.
+    pub fn sddr32_p(s: CpuRegister) -> u32 {
+        rrr(0x00_70F0, 0, s.index(), 0)
+    }
+
+    /// Returns from the debug exception, and resumes normal execution.
+    pub const RFDO: u32 = 0xF1_E000;
+
+    /// `BREAK 1, 15`, which is used to return from code called by the debugger.
+    pub const BREAK_1_15: u32 = 0x00_41F0;
+}
+
+#[cfg(test)]
+mod test {
+    use super::{instruction, CpuRegister, SpecialRegister};
+
+    #[test]
+    fn special_register_instructions() {
+        // rsr.ddr a3
+        assert_eq!(
+            instruction::rsr(SpecialRegister::DDR, CpuRegister::A3),
+            0x03_6830
+        );
+        // wsr.ddr a3
+        assert_eq!(
+            instruction::wsr(SpecialRegister::DDR, CpuRegister::A3),
+            0x13_6830
+        );
+        // rsr.epc6 a2
+        assert_eq!(
+            instruction::rsr(SpecialRegister::epc(6), CpuRegister::new(2).unwrap()),
+            0x03_B620
+        );
+    }
+
+    #[test]
+    fn debug_data_register_instructions() {
+        assert_eq!(instruction::lddr32_p(CpuRegister::A3), 0x00_73E0);
+        assert_eq!(instruction::sddr32_p(CpuRegister::A3), 0x00_73F0);
+    }
+
+    #[test]
+    fn interrupt_level_registers() {
+        assert_eq!(SpecialRegister::epc(6).number(), 182);
+        assert_eq!(SpecialRegister::eps(6).number(), 198);
+        assert_eq!(SpecialRegister::ibreaka(1).number(), 129);
+    }
+}
@@ -0,0 +1,491 @@
+//! Xtensa Debug Module Communication
+//!
+//! The debugger controls a halted Xtensa core by executing instructions on it,
+//! using the debug instruction register (DIR). Values are transferred between
+//! the debugger and the core using the debug data register (DDR).
+
+use std::time::{Duration, Instant};
+
+use super::{
+    arch::{instruction, CpuRegister, SpecialRegister},
+    xdm::{NexusRegister, Xdm},
+};
+use crate::{
+    core::RegisterId, memory::valid_32bit_address, probe::JTAGAccess, DebugProbeError,
+    Error as ProbeRsError, MemoryInterface, Probe,
+};
+
+/// Some error occurred when working with the Xtensa core.
+#[derive(thiserror::Error, Debug)]
+pub enum XtensaError {
+    /// An error with operating the debug probe occurred.
+    #[error("Debug Probe Error")]
+    DebugProbe(#[from] DebugProbeError),
+    /// A timeout occurred.
+    #[error("Timeout during Xtensa debug module access.")]
+    Timeout,
+    /// The connected target is not an Xtensa device.
+    #[error("Connected target is not an Xtensa device.")]
+    NoXtensaTarget,
+    /// The debug module is not powered on.
+    #[error("The Xtensa debug module is not powered on.")]
+    DebugModulePoweredOff,
+    /// An instruction executed by the debugger raised an exception.
+    #[error("An instruction executed by the debugger raised an exception.")]
+    ExecException,
+    /// An instruction was written while the previous one was still being executed.
+    #[error("An instruction was written while the previous one was still being executed.")]
+    ExecOverrun,
+    /// The operation requires the core to be halted.
+    #[error("The core has to be halted for this operation.")]
+    CoreNotHalted,
+    /// The register is not known.
+    #[error("The register {0:?} is not a known Xtensa register.")]
+    UnknownRegister(RegisterId),
+}
+
+impl From<XtensaError> for ProbeRsError {
+    fn from(err: XtensaError) -> Self {
+        match err {
+            XtensaError::DebugProbe(e) => e.into(),
+            XtensaError::Timeout => ProbeRsError::Timeout,
+            other => ProbeRsError::Xtensa(other),
+        }
+    }
+}
+
+/// The interrupt level of the debug exception on the ESP32 and ESP32-S3.
+const DEFAULT_DEBUG_LEVEL: u8 = 6;
+
+/// A interface that implements controls for Xtensa cores.
+#[derive(Debug)]
+pub struct XtensaCommunicationInterface {
+    xdm: Xdm,
+    /// The interrupt level of the debug exception.
+    debug_level: u8,
+}
+
+impl XtensaCommunicationInterface {
+    /// Creates a new Xtensa communication interface with a given probe driver.
+    pub fn new(probe: Box<dyn JTAGAccess>) -> Result<Self, (Box<dyn JTAGAccess>, XtensaError)> {
+        let xdm = Xdm::new(probe)?;
+
+        Ok(Self {
+            xdm,
+            debug_level: DEFAULT_DEBUG_LEVEL,
+        })
+    }
+
+    /// Read the targets IDCODE.
+    pub fn read_idcode(&mut self) -> Result<u32, DebugProbeError> {
+        self.xdm.read_idcode()
+    }
+
+    /// Returns the probe, closing the interface.
+    pub fn close(self) -> Probe {
+        Probe::from_attached_probe(self.xdm.probe.into_probe())
+    }
+
+    /// The interrupt level of the debug exception.
+    pub fn debug_level(&self) -> u8 {
+        self.debug_level
+    }
+
+    /// Set the interrupt level of the debug exception, which depends on the configuration of the core.
+    pub fn set_debug_level(&mut self, level: u8) {
+        self.debug_level = level;
+    }
+
+    /// Check if the core is halted.
+    pub fn is_halted(&mut self) -> Result<bool, XtensaError> {
+        Ok(self.xdm.status()?.stopped())
+    }
+
+    /// Requests the core to halt, and waits until it is halted.
+    pub fn halt(&mut self, timeout: Duration) -> Result<(), XtensaError> {
+        self.xdm.halt()?;
+
+        self.wait_for_core_halted(timeout)
+    }
+
+    /// Waits until the core is halted.
+    pub fn wait_for_core_halted(&mut self, timeout: Duration) -> Result<(), XtensaError> {
+        let start = Instant::now();
+
+        while !self.is_halted()? {
+            if start.elapsed() > timeout {
+                return Err(XtensaError::Timeout);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Resumes the halted core.
+    pub fn resume(&mut self) -> Result<(), XtensaError> {
+        self.xdm.resume()
+    }
+
+    /// Asserts or deasserts the reset of the core.
+    pub fn set_core_reset(&mut self, reset: bool) -> Result<(), XtensaError> {
+        self.xdm.set_core_reset(reset)
+    }
+
+    /// Requests a halt, which takes effect as soon as the core is released from reset.
+    pub(super) fn halt_on_reset(&mut self) -> Result<(), XtensaError> {
+        self.xdm.halt()
+    }
+
+    fn execute(&mut self, instruction: u32) -> Result<(), XtensaError> {
+        self.xdm.execute_instruction(instruction)
+    }
+
+    /// Read an address register of the current window.
+    pub fn read_cpu_register(&mut self, register: CpuRegister) -> Result<u32, XtensaError> {
+        self.execute(instruction::wsr(SpecialRegister::DDR, register))?;
+
+        self.xdm.read_nexus_register(NexusRegister::Ddr)
+    }
+
+    /// Write an address register of the current window.
+    pub fn write_cpu_register(
+        &mut self,
+        register: CpuRegister,
+        value: u32,
+    ) -> Result<(), XtensaError> {
+        self.xdm.write_nexus_register(NexusRegister::Ddr, value)?;
+
+        self.execute(instruction::rsr(SpecialRegister::DDR, register))
+    }
+
+    /// Runs `f` with the scratch register `a3` available, and restores it afterwards.
+    fn with_scratch_register<T>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<T, XtensaError>,
+    ) -> Result<T, XtensaError> {
+        let saved = self.read_cpu_register(CpuRegister::A3)?;
+
+        let result = f(self);
+
+        self.write_cpu_register(CpuRegister::A3, saved)?;
+
+        result
+    }
+
+    /// Read a special register.
+    pub fn read_special_register(&mut self, register: SpecialRegister) -> Result<u32, XtensaError> {
+        self.with_scratch_register(|this| {
+            this.execute(instruction::rsr(register, CpuRegister::A3))?;
+            this.read_cpu_register(CpuRegister::A3)
+        })
+    }
+
+    /// Write a special register.
+    pub fn write_special_register(
+        &mut self,
+        register: SpecialRegister,
+        value: u32,
+    ) -> Result<(), XtensaError> {
+        self.with_scratch_register(|this| {
+            this.write_cpu_register(CpuRegister::A3, value)?;
+            this.execute(instruction::wsr(register, CpuRegister::A3))
+        })
+    }
+
+    fn ensure_halted(&mut self) -> Result<(), XtensaError> {
+        if self.is_halted()? {
+            Ok(())
+        } else {
+            Err(XtensaError::CoreNotHalted)
+        }
+    }
+
+    /// Reads `data.len()` words, starting at the word aligned `address`.
+    ///
+    /// `a3` is loaded with the address, and `LDDR32.P a3` is executed repeatedly,
+    /// by reading DDREXEC. Each execution loads the next word into DDR.
+    fn read_words(&mut self, address: u32, data: &mut [u32]) -> Result<(), XtensaError> {
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        self.ensure_halted()?;
+
+        self.with_scratch_register(|this| {
+            this.write_cpu_register(CpuRegister::A3, address)?;
+            this.execute(instruction::lddr32_p(CpuRegister::A3))?;
+
+            let (last, words) = data.split_last_mut().unwrap();
+
+            for word in words {
+                *word = this.xdm.read_nexus_register(NexusRegister::DdrExec)?;
+            }
+
+            // Reading DDR instead of DDREXEC doesn't load another word, so no
+            // memory after the requested block is accessed.
+            *last = this.xdm.read_nexus_register(NexusRegister::Ddr)?;
+
+            this.xdm.check_execution()
+        })
+    }
+
+    /// Writes `data` to the word aligned `address`.
+    ///
+    /// `a3` is loaded with the address, and `SDDR32.P a3` is placed in DIR0. Each
+    /// write to DDREXEC then stores the written word, and increments the address.
+    fn write_words(&mut self, address: u32, data: &[u32]) -> Result<(), XtensaError> {
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        self.ensure_halted()?;
+
+        self.with_scratch_register(|this| {
+            this.write_cpu_register(CpuRegister::A3, address)?;
+            this.xdm.write_nexus_register(
+                NexusRegister::Dir0,
+                instruction::sddr32_p(CpuRegister::A3),
+            )?;
+
+            for word in data {
+                this.xdm
+                    .write_nexus_register(NexusRegister::DdrExec, *word)?;
+            }
+
+            this.xdm.check_execution()
+        })
+    }
+
+    /// Reads bytes from any address, using word accesses.
+    fn read_bytes(&mut self, address: u32, data: &mut [u8]) -> Result<(), XtensaError> {
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        let (start, words) = word_span(address, data.len());
+
+        let mut buffer = vec![0u32; words];
+        self.read_words(start, &mut buffer)?;
+
+        let bytes: Vec<u8> = buffer.iter().flat_map(|w| w.to_le_bytes()).collect();
+        let offset = (address - start) as usize;
+        data.copy_from_slice(&bytes[offset..offset + data.len()]);
+
+        Ok(())
+    }
+
+    /// Writes bytes to any address. Partially written words are read first,
+    /// so that the remaining bytes are not modified.
+    fn write_bytes(&mut self, address: u32, data: &[u8]) -> Result<(), XtensaError> {
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        let (start, words) = word_span(address, data.len());
+        let offset = (address - start) as usize;
+
+        let mut bytes = vec![0u8; words * 4];
+
+        if offset != 0 {
+            let first = self.read_word(start)?;
+            bytes[..4].copy_from_slice(&first.to_le_bytes());
+        }
+
+        if (offset + data.len()) & 0x3 != 0 {
+            let last_address = start + (words as u32 - 1) * 4;
+            let last = self.read_word(last_address)?;
+            bytes[(words - 1) * 4..].copy_from_slice(&last.to_le_bytes());
+        }
+
+        bytes[offset..offset + data.len()].copy_from_slice(data);
+
+        let buffer: Vec<u32> = bytes
+            .chunks_exact(4)
+            .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+            .collect();
+
+        self.write_words(start, &buffer)
+    }
+
+    fn read_word(&mut self, address: u32) -> Result<u32, XtensaError> {
+        let mut word = [0];
+        self.read_words(address, &mut word)?;
+
+        Ok(word[0])
+    }
+}
+
+/// Returns the word aligned start address and the number of words covering `len` bytes at `address`.
+fn word_span(address: u32, len: usize) -> (u32, usize) {
+    let start = address & !0x3;
+    let end = (address as usize + len + 3) & !0x3;
+
+    let words = (end - start as usize) / 4;
+
+    (start, words)
+}
+
+fn check_alignment(address: u32, alignment: u32) -> Result<(), ProbeRsError> {
+    if address & (alignment - 1) != 0 {
+        return Err(ProbeRsError::MemoryNotAligned {
+            address: address.into(),
+            alignment: alignment as usize,
+        });
+    }
+
+    Ok(())
+}
+
+impl MemoryInterface for XtensaCommunicationInterface {
+    fn supports_native_64bit_access(&mut self) -> bool {
+        false
+    }
+
+    fn read_word_64(&mut self, address: u64) -> Result<u64, ProbeRsError> {
+        let mut data = [0u64];
+        self.read_64(address, &mut data)?;
+
+        Ok(data[0])
+    }
+
+    fn read_word_32(&mut self, address: u64) -> Result<u32, ProbeRsError> {
+        let address = valid_32bit_address(address)?;
+        check_alignment(address, 4)?;
+
+        Ok(self.read_word(address)?)
+    }
+
+    fn read_word_16(&mut self, address: u64) -> Result<u16, ProbeRsError> {
+        let mut data = [0u16];
+        self.read_16(address, &mut data)?;
+
+        Ok(data[0])
+    }
+
+    fn read_word_8(&mut self, address: u64) -> Result<u8, ProbeRsError> {
+        let mut data = [0u8];
+        self.read_8(address, &mut data)?;
+
+        Ok(data[0])
+    }
+
+    fn read_64(&mut self, address: u64, data: &mut [u64]) -> Result<(), ProbeRsError> {
+        let address = valid_32bit_address(address)?;
+        check_alignment(address, 8)?;
+
+        let mut words = vec![0u32; data.len() * 2];
+        self.read_words(address, &mut words)?;
+
+        for (value, words) in data.iter_mut().zip(words.chunks_exact(2)) {
+            *value = words[0] as u64 | (words[1] as u64) << 32;
+        }
+
+        Ok(())
+    }
+
+    fn read_32(&mut self, address: u64, data: &mut [u32]) -> Result<(), ProbeRsError> {
+        let address = valid_32bit_address(address)?;
+        check_alignment(address, 4)?;
+
+        Ok(self.read_words(address, data)?)
+    }
+
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), ProbeRsError> {
+        let address = valid_32bit_address(address)?;
+        check_alignment(address, 2)?;
+
+        let mut bytes = vec![0u8; data.len() * 2];
+        self.read_bytes(address, &mut bytes)?;
+
+        for (value, bytes) in data.iter_mut().zip(bytes.chunks_exact(2)) {
+            *value = u16::from_le_bytes([bytes[0], bytes[1]]);
+        }
+
+        Ok(())
+    }
+
+    fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), ProbeRsError> {
+        let address = valid_32bit_address(address)?;
+
+        Ok(self.read_bytes(address, data)?)
+    }
+
+    fn read(&mut self, address: u64, data: &mut [u8]) -> Result<(), ProbeRsError> {
+        self.read_8(address, data)
+    }
+
+    fn write_word_64(&mut self, address: u64, data: u64) -> Result<(), ProbeRsError> {
+        self.write_64(address, &[data])
+    }
+
+    fn write_word_32(&mut self, address: u64, data: u32) -> Result<(), ProbeRsError> {
+        self.write_32(address, &[data])
+    }
+
+    fn write_word_16(&mut self, address: u64, data: u16) -> Result<(), ProbeRsError> {
+        self.write_16(address, &[data])
+    }
+
+    fn write_word_8(&mut self, address: u64, data: u8) -> Result<(), ProbeRsError> {
+        self.write_8(address, &[data])
+    }
+
+    fn write_64(&mut self, address: u64, data: &[u64]) -> Result<(), ProbeRsError> {
+        let address = valid_32bit_address(address)?;
+        check_alignment(address, 8)?;
+
+        let words: Vec<u32> = data
+            .iter()
+            .flat_map(|value| [*value as u32, (*value >> 32) as u32])
+            .collect();
+
+        Ok(self.write_words(address, &words)?)
+    }
+
+    fn write_32(&mut self, address: u64, data: &[u32]) -> Result<(), ProbeRsError> {
+        let address = valid_32bit_address(address)?;
+        check_alignment(address, 4)?;
+
+        Ok(self.write_words(address, data)?)
+    }
+
+    fn write_16(&mut self, address: u64, data: &[u16]) -> Result<(), ProbeRsError> {
+        let address = valid_32bit_address(address)?;
+        check_alignment(address, 2)?;
+
+        let bytes: Vec<u8> = data.iter().flat_map(|value| value.to_le_bytes()).collect();
+
+        Ok(self.write_bytes(address, &bytes)?)
+    }
+
+    fn write_8(&mut self, address: u64, data: &[u8]) -> Result<(), ProbeRsError> {
+        let address = valid_32bit_address(address)?;
+
+        Ok(self.write_bytes(address, data)?)
+    }
+
+    fn write(&mut self, address: u64, data: &[u8]) -> Result<(), ProbeRsError> {
+        self.write_8(address, data)
+    }
+
+    fn supports_8bit_transfers(&self) -> Result<bool, ProbeRsError> {
+        Ok(false)
+    }
+
+    fn flush(&mut self) -> Result<(), ProbeRsError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::word_span;
+
+    #[test]
+    fn word_span_covers_unaligned_ranges() {
+        assert_eq!(word_span(0x1000, 8), (0x1000, 2));
+        assert_eq!(word_span(0x1001, 1), (0x1000, 1));
+        assert_eq!(word_span(0x1003, 2), (0x1000, 2));
+        assert_eq!(word_span(0x1002, 8), (0x1000, 3));
+    }
+}
@@ -0,0 +1,478 @@
+//! All the interface bits for Xtensa.
+//!
+//! Xtensa cores, as used in the ESP32 and ESP32-S3, are debugged using the On-Chip Debug (OCD)
+//! module, which is accessed over JTAG. The core is halted using a debug interrupt, and while
+//! halted, the debugger executes instructions on the core to access registers and memory.
+
+use std::time::Duration;
+
+use crate::{
+    core::{
+        Architecture, BreakpointCause, CoreInformation, RegisterFile, RegisterId, RegisterValue,
+        WatchpointCause,
+    },
+    CoreInterface, CoreStatus, CoreType, Error, HaltReason, InstructionSet, MemoryInterface,
+    ResetType,
+};
+
+use arch::{debug_cause, SpecialRegister};
+use communication_interface::{XtensaCommunicationInterface, XtensaError};
+use register::{XtensaRegister, XTENSA_REGISTERS};
+
+pub(crate) mod arch;
+pub(crate) mod register;
+mod xdm;
+
+pub mod communication_interface;
+pub mod sequences;
+
+/// Number of instruction breakpoint units of the ESP32 and ESP32-S3.
+const INSTRUCTION_BREAKPOINT_UNITS: u32 = 2;
+
+/// A interface to operate Xtensa cores.
+pub struct Xtensa<'probe> {
+    interface: &'probe mut XtensaCommunicationInterface,
+    state: &'probe mut XtensaState,
+}
+
+impl<'probe> Xtensa<'probe> {
+    /// Create a new Xtensa interface.
+    pub fn new(
+        interface: &'probe mut XtensaCommunicationInterface,
+        state: &'probe mut XtensaState,
+    ) -> Self {
+        Self { interface, state }
+    }
+
+    fn read_register(&mut self, register: XtensaRegister) -> Result<u32, XtensaError> {
+        let level = self.interface.debug_level();
+
+        match register {
+            XtensaRegister::Cpu(register) => self.interface.read_cpu_register(register),
+            XtensaRegister::Special(register) => self.interface.read_special_register(register),
+            XtensaRegister::Pc => self
+                .interface
+                .read_special_register(SpecialRegister::epc(level)),
+            XtensaRegister::Ps => self
+                .interface
+                .read_special_register(SpecialRegister::eps(level)),
+        }
+    }
+
+    fn write_register(&mut self, register: XtensaRegister, value: u32) -> Result<(), XtensaError> {
+        let level = self.interface.debug_level();
+
+        match register {
+            XtensaRegister::Cpu(register) => self.interface.write_cpu_register(register, value),
+            XtensaRegister::Special(register) => {
+                self.interface.write_special_register(register, value)
+            }
+            XtensaRegister::Pc => self
+                .interface
+                .write_special_register(SpecialRegister::epc(level), value),
+            XtensaRegister::Ps => self
+                .interface
+                .write_special_register(SpecialRegister::eps(level), value),
+        }
+    }
+
+    fn read_pc(&mut self) -> Result<CoreInformation, Error> {
+        let pc = self.read_register(XtensaRegister::Pc)?;
+
+        Ok(CoreInformation { pc: pc.into() })
+    }
+
+    fn debug_cause(&mut self) -> Result<u32, XtensaError> {
+        self.interface
+            .read_special_register(SpecialRegister::DEBUGCAUSE)
+    }
+
+    /// Returns the size of the software breakpoint instruction the core is halted on, if any.
+    ///
+    /// `DEBUGCAUSE` is only updated on the next debug exception, so a breakpoint which
+    /// was already stepped over is not reported again.
+    fn software_breakpoint_size(&mut self) -> Result<Option<u32>, XtensaError> {
+        if self.state.software_breakpoint_skipped {
+            return Ok(None);
+        }
+
+        let debug_cause = self.debug_cause()?;
+
+        Ok(if debug_cause & debug_cause::BREAK != 0 {
+            Some(3)
+        } else if debug_cause & debug_cause::BREAKN != 0 {
+            Some(2)
+        } else {
+            None
+        })
+    }
+
+    fn resume(&mut self) -> Result<(), XtensaError> {
+        self.state.software_breakpoint_skipped = false;
+
+        self.interface.resume()
+    }
+}
+
+/// Determines why the core was halted, from the value of the `DEBUGCAUSE` register.
+fn halt_reason(debug_cause: u32) -> HaltReason {
+    let reasons = [
+        (debug_cause::ICOUNT, HaltReason::Step),
+        (
+            debug_cause::IBREAK,
+            HaltReason::Breakpoint(BreakpointCause::Hardware),
+        ),
+        (
+            debug_cause::DBREAK,
+            HaltReason::Watchpoint(WatchpointCause::Unknown),
+        ),
+        (
+            debug_cause::BREAK | debug_cause::BREAKN,
+            HaltReason::Breakpoint(BreakpointCause::Software),
+        ),
+        (debug_cause::DEBUGINT, HaltReason::Request),
+    ];
+
+    let mut matching = reasons
+        .iter()
+        .filter(|(mask, _)| debug_cause & mask != 0)
+        .map(|(_, reason)| *reason);
+
+    match (matching.next(), matching.next()) {
+        (None, _) => HaltReason::Unknown,
+        (Some(reason), None) => reason,
+        (Some(_), Some(_)) => HaltReason::Multiple,
+    }
+}
+
+/// Only the core can be reset through the debug module.
+fn check_reset_type(reset_type: ResetType) -> Result<(), Error> {
+    match reset_type {
+        ResetType::Sequence | ResetType::Core => Ok(()),
+        ResetType::System | ResetType::Hardware => Err(crate::DebugProbeError::NotImplemented(
+            "System and hardware reset of Xtensa cores",
+        )
+        .into()),
+    }
+}
+
+impl<'probe> CoreInterface for Xtensa<'probe> {
+    fn wait_for_core_halted(&mut self, timeout: Duration) -> Result<(), Error> {
+        Ok(self.interface.wait_for_core_halted(timeout)?)
+    }
+
+    fn core_halted(&mut self) -> Result<bool, Error> {
+        Ok(self.interface.is_halted()?)
+    }
+
+    fn status(&mut self) -> Result<CoreStatus, Error> {
+        if !self.interface.is_halted()? {
+            return Ok(CoreStatus::Running);
+        }
+
+        let debug_cause = self.debug_cause()?;
+
+        Ok(CoreStatus::Halted(halt_reason(debug_cause)))
+    }
+
+    fn halt(&mut self, timeout: Duration) -> Result<CoreInformation, Error> {
+        if !self.interface.is_halted()? {
+            self.interface.halt(timeout)?;
+        }
+
+        self.read_pc()
+    }
+
+    fn run(&mut self) -> Result<(), Error> {
+        if !self.interface.is_halted()? {
+            return Ok(());
+        }
+
+        // Step over a software breakpoint, otherwise the core would halt on it again.
+        if self.software_breakpoint_size()?.is_some() {
+            self.step()?;
+        }
+
+        Ok(self.resume()?)
+    }
+
+    fn reset(&mut self, reset_type: ResetType) -> Result<(), Error> {
+        check_reset_type(reset_type)?;
+
+        self.interface.set_core_reset(true)?;
+        self.interface.set_core_reset(false)?;
+
+        Ok(())
+    }
+
+    fn reset_and_halt(
+        &mut self,
+        reset_type: ResetType,
+        timeout: Duration,
+    ) -> Result<CoreInformation, Error> {
+        check_reset_type(reset_type)?;
+
+        self.interface.set_core_reset(true)?;
+        self.interface.halt_on_reset()?;
+        self.interface.set_core_reset(false)?;
+
+        self.interface.wait_for_core_halted(timeout)?;
+
+        self.read_pc()
+    }
+
+    fn step(&mut self) -> Result<CoreInformation, Error> {
+        // A software breakpoint is not executed again, instead the PC is moved past it.
+        if let Some(size) = self.software_breakpoint_size()? {
+            let pc = self.read_register(XtensaRegister::Pc)?.wrapping_add(size);
+            self.write_register(XtensaRegister::Pc, pc)?;
+
+            self.state.software_breakpoint_skipped = true;
+
+            return Ok(CoreInformation { pc: pc.into() });
+        }
+
+        // The instruction counter raises a debug interrupt when it overflows. It is
+        // incremented for every instruction executed below the debug level.
+        let level = self.interface.debug_level();
+        self.interface
+            .write_special_register(SpecialRegister::ICOUNTLEVEL, level.into())?;
+        self.interface
+            .write_special_register(SpecialRegister::ICOUNT, -2i32 as u32)?;
+
+        self.resume()?;
+        self.interface
+            .wait_for_core_halted(Duration::from_millis(100))?;
+
+        self.interface
+            .write_special_register(SpecialRegister::ICOUNTLEVEL, 0)?;
+
+        self.read_pc()
+    }
+
+    fn read_core_reg(&mut self, address: RegisterId) -> Result<RegisterValue, Error> {
+        let register =
+            XtensaRegister::from_id(address).ok_or(XtensaError::UnknownRegister(address))?;
+
+        Ok(self.read_register(register)?.into())
+    }
+
+    fn write_core_reg(&mut self, address: RegisterId, value: RegisterValue) -> Result<(), Error> {
+        let register =
+            XtensaRegister::from_id(address).ok_or(XtensaError::UnknownRegister(address))?;
+
+        Ok(self.write_register(register, value.try_into()?)?)
+    }
+
+    fn available_breakpoint_units(&mut self) -> Result<u32, Error> {
+        Ok(INSTRUCTION_BREAKPOINT_UNITS)
+    }
+
+    fn hw_breakpoints(&mut self) -> Result<Vec<Option<u64>>, Error> {
+        let enabled = self
+            .interface
+            .read_special_register(SpecialRegister::IBREAKENABLE)?;
+
+        let mut breakpoints = vec![];
+
+        for unit in 0..INSTRUCTION_BREAKPOINT_UNITS as u8 {
+            if enabled & (1 << unit) != 0 {
+                let address = self
+                    .interface
+                    .read_special_register(SpecialRegister::ibreaka(unit))?;
+                breakpoints.push(Some(address.into()));
+            } else {
+                breakpoints.push(None);
+            }
+        }
+
+        Ok(breakpoints)
+    }
+
+    fn enable_breakpoints(&mut self, state: bool) -> Result<(), Error> {
+        // Instruction breakpoints are enabled individually, there is no global enable.
+        self.state.hw_breakpoints_enabled = state;
+
+        Ok(())
+    }
+
+    fn set_hw_breakpoint(&mut self, unit_index: usize, addr: u64) -> Result<(), Error> {
+        let address = crate::memory::valid_32bit_address(addr)?;
+        let unit = unit_index as u8;
+
+        self.interface
+            .write_special_register(SpecialRegister::ibreaka(unit), address)?;
+
+        let enabled = self
+            .interface
+            .read_special_register(SpecialRegister::IBREAKENABLE)?;
+        self.interface
+            .write_special_register(SpecialRegister::IBREAKENABLE, enabled | (1 << unit))?;
+
+        Ok(())
+    }
+
+    fn clear_hw_breakpoint(&mut self, unit_index: usize) -> Result<(), Error> {
+        let enabled = self
+            .interface
+            .read_special_register(SpecialRegister::IBREAKENABLE)?;
+        self.interface
+            .write_special_register(SpecialRegister::IBREAKENABLE, enabled & !(1 << unit_index))?;
+
+        Ok(())
+    }
+
+    fn registers(&self) -> &'static RegisterFile {
+        &XTENSA_REGISTERS
+    }
+
+    fn hw_breakpoints_enabled(&self) -> bool {
+        self.state.hw_breakpoints_enabled
+    }
+
+    fn architecture(&self) -> Architecture {
+        Architecture::Xtensa
+    }
+
+    fn core_type(&self) -> CoreType {
+        CoreType::Xtensa
+    }
+
+    fn instruction_set(&mut self) -> Result<InstructionSet, Error> {
+        Ok(InstructionSet::Xtensa)
+    }
+
+    fn fpu_support(&mut self) -> Result<bool, Error> {
+        // Access to the floating point registers is not implemented yet.
+        Ok(false)
+    }
+}
+
+impl<'probe> MemoryInterface for Xtensa<'probe> {
+    fn supports_native_64bit_access(&mut self) -> bool {
+        self.interface.supports_native_64bit_access()
+    }
+
+    fn read_word_64(&mut self, address: u64) -> Result<u64, Error> {
+        self.interface.read_word_64(address)
+    }
+
+    fn read_word_32(&mut self, address: u64) -> Result<u32, Error> {
+        self.interface.read_word_32(address)
+    }
+
+    fn read_word_16(&mut self, address: u64) -> Result<u16, Error> {
+        self.interface.read_word_16(address)
+    }
+
+    fn read_word_8(&mut self, address: u64) -> Result<u8, Error> {
+        self.interface.read_word_8(address)
+    }
+
+    fn read_64(&mut self, address: u64, data: &mut [u64]) -> Result<(), Error> {
+        self.interface.read_64(address, data)
+    }
+
+    fn read_32(&mut self, address: u64, data: &mut [u32]) -> Result<(), Error> {
+        self.interface.read_32(address, data)
+    }
+
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), Error> {
+        self.interface.read_16(address, data)
+    }
+
+    fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error> {
+        self.interface.read_8(address, data)
+    }
+
+    fn read(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error> {
+        self.interface.read(address, data)
+    }
+
+    fn write_word_64(&mut self, address: u64, data: u64) -> Result<(), Error> {
+        self.interface.write_word_64(address, data)
+    }
+
+    fn write_word_32(&mut self, address: u64, data: u32) -> Result<(), Error> {
+        self.interface.write_word_32(address, data)
+    }
+
+    fn write_word_16(&mut self, address: u64, data: u16) -> Result<(), Error> {
+        self.interface.write_word_16(address, data)
+    }
+
+    fn write_word_8(&mut self, address: u64, data: u8) -> Result<(), Error> {
+        self.interface.write_word_8(address, data)
+    }
+
+    fn write_64(&mut self, address: u64, data: &[u64]) -> Result<(), Error> {
+        self.interface.write_64(address, data)
+    }
+
+    fn write_32(&mut self, address: u64, data: &[u32]) -> Result<(), Error> {
+        self.interface.write_32(address, data)
+    }
+
+    fn write_16(&mut self, address: u64, data: &[u16]) -> Result<(), Error> {
+        self.interface.write_16(address, data)
+    }
+
+    fn write_8(&mut self, address: u64, data: &[u8]) -> Result<(), Error> {
+        self.interface.write_8(address, data)
+    }
+
+    fn write(&mut self, address: u64, data: &[u8]) -> Result<(), Error> {
+        self.interface.write(address, data)
+    }
+
+    fn supports_8bit_transfers(&self) -> Result<bool, Error> {
+        self.interface.supports_8bit_transfers()
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.interface.flush()
+    }
+}
+
+#[derive(Debug)]
+/// Flags used to control the [`SpecificCoreState`](crate::core::SpecificCoreState) for the Xtensa architecture
+pub struct XtensaState {
+    /// A flag to remember whether we want to use hw_breakpoints during stepping of the core.
+    hw_breakpoints_enabled: bool,
+    /// Set when the PC was moved past the software breakpoint the core halted on.
+    software_breakpoint_skipped: bool,
+}
+
+impl XtensaState {
+    pub(crate) fn new() -> Self {
+        Self {
+            hw_breakpoints_enabled: false,
+            software_breakpoint_skipped: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{arch::debug_cause, halt_reason};
+    use crate::{core::BreakpointCause, HaltReason};
+
+    #[test]
+    fn halt_reason_from_debug_cause() {
+        assert_eq!(halt_reason(0), HaltReason::Unknown);
+        assert_eq!(halt_reason(debug_cause::ICOUNT), HaltReason::Step);
+        assert_eq!(
+            halt_reason(debug_cause::IBREAK),
+            HaltReason::Breakpoint(BreakpointCause::Hardware)
+        );
+        assert_eq!(
+            halt_reason(debug_cause::BREAKN),
+            HaltReason::Breakpoint(BreakpointCause::Software)
+        );
+        assert_eq!(halt_reason(debug_cause::DEBUGINT), HaltReason::Request);
+        assert_eq!(
+            halt_reason(debug_cause::ICOUNT | debug_cause::IBREAK),
+            HaltReason::Multiple
+        );
+    }
+}
@@ -0,0 +1,165 @@
+//! Register descriptions of Xtensa cores.
+//!
+//! The address registers `a0` to `a15` of the current window use the IDs `0x00` to `0x0F`,
+//! special registers use `0x100` plus their number. The program counter and the processor
+//! state of the halted program are saved in registers which depend on the debug interrupt
+//! level, so they use separate IDs.
+
+use super::arch::{CpuRegister, SpecialRegister};
+use crate::core::{RegisterDataType, RegisterDescription, RegisterFile, RegisterId, RegisterKind};
+
+/// Offset of the IDs of special registers.
+const SPECIAL_REGISTER_OFFSET: u16 = 0x100;
+
+/// ID of the program counter of the halted program.
+pub(super) const PC_ID: RegisterId = RegisterId(0x200);
+/// ID of the processor state of the halted program.
+pub(super) const PS_ID: RegisterId = RegisterId(0x201);
+
+/// A register, as identified by a [`RegisterId`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum XtensaRegister {
+    /// An address register of the current window.
+    Cpu(CpuRegister),
+    /// A special register.
+    Special(SpecialRegister),
+    /// The program counter of the halted program.
+    Pc,
+    /// The processor state of the halted program.
+    Ps,
+}
+
+impl XtensaRegister {
+    pub(super) fn from_id(id: RegisterId) -> Option<Self> {
+        match id {
+            PC_ID => Some(Self::Pc),
+            PS_ID => Some(Self::Ps),
+            RegisterId(id @ 0..=0xF) => CpuRegister::new(id as u8).map(Self::Cpu),
+            RegisterId(id @ SPECIAL_REGISTER_OFFSET..=0x1FF) => Some(Self::Special(
+                SpecialRegister::new((id - SPECIAL_REGISTER_OFFSET) as u8),
+            )),
+            _ => None,
+        }
+    }
+}
+
+const fn address_register(name: &'static str, index: u16) -> RegisterDescription {
+    RegisterDescription {
+        name,
+        _kind: RegisterKind::General,
+        id: RegisterId(index),
+        _type: RegisterDataType::UnsignedInteger,
+        size_in_bits: 32,
+    }
+}
+
+const fn special_register(name: &'static str, number: u16) -> RegisterDescription {
+    RegisterDescription {
+        name,
+        _kind: RegisterKind::General,
+        id: RegisterId(SPECIAL_REGISTER_OFFSET + number),
+        _type: RegisterDataType::UnsignedInteger,
+        size_in_bits: 32,
+    }
+}
+
+const PC: RegisterDescription = RegisterDescription {
+    name: "pc",
+    _kind: RegisterKind::PC,
+    id: PC_ID,
+    _type: RegisterDataType::UnsignedInteger,
+    size_in_bits: 32,
+};
+
+const PS: RegisterDescription = RegisterDescription {
+    name: "ps",
+    _kind: RegisterKind::General,
+    id: PS_ID,
+    _type: RegisterDataType::UnsignedInteger,
+    size_in_bits: 32,
+};
+
+/// In the windowed ABI, `a0` holds the return address.
+const RA: RegisterDescription = address_register("a0", 0);
+/// In the windowed ABI, `a1` is the stack pointer.
+const SP: RegisterDescription = address_register("a1", 1);
+/// In the windowed ABI, `a7` is used as frame pointer.
+const FP: RegisterDescription = address_register("a7", 7);
+
+pub(crate) const XTENSA_REGISTERS: RegisterFile = RegisterFile {
+    platform_registers: &[
+        address_register("a0", 0),
+        address_register("a1", 1),
+        address_register("a2", 2),
+        address_register("a3", 3),
+        address_register("a4", 4),
+        address_register("a5", 5),
+        address_register("a6", 6),
+        address_register("a7", 7),
+        address_register("a8", 8),
+        address_register("a9", 9),
+        address_register("a10", 10),
+        address_register("a11", 11),
+        address_register("a12", 12),
+        address_register("a13", 13),
+        address_register("a14", 14),
+        address_register("a15", 15),
+        PC,
+    ],
+
+    program_counter: &PC,
+    stack_pointer: &SP,
+    return_address: &RA,
+    frame_pointer: &FP,
+
+    argument_registers: &[
+        address_register("a2", 2),
+        address_register("a3", 3),
+        address_register("a4", 4),
+        address_register("a5", 5),
+        address_register("a6", 6),
+        address_register("a7", 7),
+    ],
+
+    result_registers: &[address_register("a2", 2), address_register("a3", 3)],
+
+    msp: None,
+    psp: None,
+    psr: Some(&PS),
+    fp_registers: None,
+    fp_status: None,
+
+    other: &[
+        special_register("sar", 3),
+        special_register("lbeg", 0),
+        special_register("lend", 1),
+        special_register("lcount", 2),
+        special_register("windowbase", 72),
+        special_register("windowstart", 73),
+        special_register("exccause", 232),
+        special_register("excvaddr", 238),
+    ],
+};
+
+#[cfg(test)]
+mod test {
+    use super::{XtensaRegister, PC_ID};
+    use crate::{
+        architecture::xtensa::arch::{CpuRegister, SpecialRegister},
+        core::RegisterId,
+    };
+
+    #[test]
+    fn register_ids() {
+        assert_eq!(
+            XtensaRegister::from_id(RegisterId(3)),
+            Some(XtensaRegister::Cpu(CpuRegister::A3))
+        );
+        assert_eq!(
+            XtensaRegister::from_id(RegisterId(0x100 + 233)),
+            Some(XtensaRegister::Special(SpecialRegister::DEBUGCAUSE))
+        );
+        assert_eq!(XtensaRegister::from_id(PC_ID), Some(XtensaRegister::Pc));
+        assert_eq!(XtensaRegister::from_id(RegisterId(0x10)), None);
+    }
+}
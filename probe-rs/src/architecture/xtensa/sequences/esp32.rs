@@ -0,0 +1,41 @@
+//! Sequences for the ESP32.
+
+use std::sync::Arc;
+
+use super::XtensaDebugSequence;
+use crate::{
+    architecture::xtensa::communication_interface::XtensaCommunicationInterface, MemoryInterface,
+};
+
+/// The debug sequence implementation for the ESP32.
+pub struct ESP32(());
+
+impl ESP32 {
+    /// Creates a new debug sequence handle for the ESP32.
+    pub fn create() -> Arc<dyn XtensaDebugSequence> {
+        Arc::new(Self(()))
+    }
+}
+
+impl XtensaDebugSequence for ESP32 {
+    fn on_connect(&self, interface: &mut XtensaCommunicationInterface) -> Result<(), crate::Error> {
+        tracing::info!("Disabling esp32 watchdogs...");
+
+        // tg0 wdg
+        interface.write_word_32(0x3ff5f064, 0x50D83AA1u32)?; // write protection off
+        interface.write_word_32(0x3ff5f048, 0x0)?;
+        interface.write_word_32(0x3ff5f064, 0x0)?; // write protection on
+
+        // tg1 wdg
+        interface.write_word_32(0x3ff60064, 0x50D83AA1u32)?; // write protection off
+        interface.write_word_32(0x3ff60048, 0x0)?;
+        interface.write_word_32(0x3ff60064, 0x0)?; // write protection on
+
+        // rtc wdg
+        interface.write_word_32(0x3ff480a4, 0x50D83AA1u32)?; // write protection off
+        interface.write_word_32(0x3ff4808c, 0x0)?;
+        interface.write_word_32(0x3ff480a4, 0x0)?; // write protection on
+
+        Ok(())
+    }
+}
@@ -0,0 +1,32 @@
+//! Debug sequences to operate special requirements Xtensa targets.
+
+use super::communication_interface::XtensaCommunicationInterface;
+use std::sync::Arc;
+
+pub mod esp32;
+pub mod esp32s3;
+
+/// A interface to operate debug sequences for Xtensa targets.
+///
+/// Should be implemented on a custom handle for chips that require special sequence code.
+pub trait XtensaDebugSequence: Send + Sync {
+    /// Executed when the probe establishes a connection to the target.
+    fn on_connect(
+        &self,
+        _interface: &mut XtensaCommunicationInterface,
+    ) -> Result<(), crate::Error> {
+        Ok(())
+    }
+}
+
+/// The default sequences that is used for Xtensa chips that do not specify a specific sequence.
+pub struct DefaultXtensaSequence(pub(crate) ());
+
+impl DefaultXtensaSequence {
+    /// Creates a new default Xtensa debug sequence.
+    pub fn create() -> Arc<dyn XtensaDebugSequence> {
+        Arc::new(Self(()))
+    }
+}
+
+impl XtensaDebugSequence for DefaultXtensaSequence {}
@@ -0,0 +1,306 @@
+//! Xtensa Debug Module (XDM) access over JTAG.
+//!
+//! The On-Chip Debug (OCD) registers of the debug module are accessed through the
+//! Nexus address (NAR) and data (NDR) registers, which are both selected with the
+//! `NARSEL` JTAG instruction. The power control registers have their own JTAG
+//! instructions.
+
+use std::convert::TryInto;
+
+use bitfield::bitfield;
+
+use super::communication_interface::XtensaError;
+use crate::{probe::JTAGAccess, DebugProbeError};
+
+/// Length of the JTAG instruction register of the Xtensa TAP.
+const IR_LEN: u32 = 5;
+
+/// JTAG instruction to access the `PWRCTL` register.
+const TAPINS_PWRCTL: u32 = 0x08;
+/// JTAG instruction to access the `PWRSTAT` register.
+const TAPINS_PWRSTAT: u32 = 0x09;
+/// JTAG instruction to access the Nexus registers.
+const TAPINS_NARSEL: u32 = 0x1C;
+/// JTAG instruction to read the IDCODE.
+const TAPINS_IDCODE: u32 = 0x1E;
+
+/// Length of the power registers.
+const PWR_REG_LEN: u32 = 8;
+/// Length of the Nexus address register, 7 address bits and the write bit.
+const NAR_LEN: u32 = 8;
+/// Length of the Nexus data register.
+const NDR_LEN: u32 = 32;
+
+/// Nexus addresses of the OCD registers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(dead_code)]
+pub(super) enum NexusRegister {
+    /// OCD identification register.
+    OcdId = 0x40,
+    /// Debug control register, clearing the written bits.
+    DcrClr = 0x42,
+    /// Debug control register, setting the written bits.
+    DcrSet = 0x43,
+    /// Debug status register.
+    Dsr = 0x44,
+    /// Debug data register.
+    Ddr = 0x45,
+    /// Debug data register. Accessing it executes the instruction in DIR0.
+    DdrExec = 0x46,
+    /// Debug instruction register 0. Writing it executes the instruction.
+    Dir0Exec = 0x47,
+    /// Debug instruction register 0.
+    Dir0 = 0x48,
+}
+
+/// Bits of the debug control register.
+pub(super) mod dcr {
+    /// Enables the OCD, this is required for all other operations.
+    pub const ENABLE_OCD: u32 = 1 << 0;
+    /// Raises a debug interrupt, which halts the core.
+    pub const DEBUG_INTERRUPT: u32 = 1 << 1;
+}
+
+/// Bits of the power control register.
+mod pwrctl {
+    pub const CORE_WAKEUP: u8 = 1 << 0;
+    pub const MEM_WAKEUP: u8 = 1 << 1;
+    pub const DEBUG_WAKEUP: u8 = 1 << 2;
+    pub const CORE_RESET: u8 = 1 << 4;
+    pub const JTAG_DEBUG_USE: u8 = 1 << 7;
+}
+
+/// Bits of the power status register.
+mod pwrstat {
+    pub const DEBUG_DOMAIN_ON: u8 = 1 << 2;
+    pub const CORE_WAS_RESET: u8 = 1 << 4;
+    pub const DEBUG_WAS_RESET: u8 = 1 << 6;
+}
+
+bitfield! {
+    /// The debug status register (DSR).
+    ///
+    /// Status bits are cleared by writing a 1 to them.
+    #[derive(Copy, Clone)]
+    pub struct DebugStatus(u32);
+    impl Debug;
+
+    /// The last instruction executed by the debugger has finished.
+    pub exec_done, set_exec_done: 0;
+    /// The last instruction executed by the debugger raised an exception.
+    pub exec_exception, set_exec_exception: 1;
+    /// An instruction is still being executed.
+    pub exec_busy, _: 2;
+    /// An instruction was written while the last one was still being executed.
+    pub exec_overrun, set_exec_overrun: 3;
+    /// The core is halted, i.e. in the OCD halt mode.
+    pub stopped, _: 4;
+    /// A debug interrupt caused by a break is pending.
+    pub debug_pend_break, set_debug_pend_break: 16;
+    /// A debug interrupt requested by the host is pending.
+    pub debug_pend_host, set_debug_pend_host: 17;
+    /// The core was halted because of a break.
+    pub debug_int_break, set_debug_int_break: 20;
+    /// The core was halted because of a host request.
+    pub debug_int_host, set_debug_int_host: 21;
+    /// The debug module is powered on.
+    pub dbg_mod_power_on, _: 31;
+}
+
+/// Access to the Xtensa debug module, using a JTAG probe.
+#[derive(Debug)]
+pub(super) struct Xdm {
+    pub probe: Box<dyn JTAGAccess>,
+}
+
+impl Xdm {
+    pub fn new(mut probe: Box<dyn JTAGAccess>) -> Result<Self, (Box<dyn JTAGAccess>, XtensaError)> {
+        probe.set_ir_len(IR_LEN);
+
+        let mut xdm = Self { probe };
+
+        if let Err(e) = xdm.power_on() {
+            return Err((xdm.probe, e));
+        }
+
+        Ok(xdm)
+    }
+
+    /// Powers up the core and the debug domain, and enables the OCD.
+    fn power_on(&mut self) -> Result<(), XtensaError> {
+        let wakeup = pwrctl::DEBUG_WAKEUP | pwrctl::MEM_WAKEUP | pwrctl::CORE_WAKEUP;
+
+        self.write_power_register(TAPINS_PWRCTL, wakeup)?;
+        self.write_power_register(TAPINS_PWRCTL, wakeup | pwrctl::JTAG_DEBUG_USE)?;
+
+        // Clear the sticky reset bits.
+        let status = self.write_power_register(
+            TAPINS_PWRSTAT,
+            pwrstat::CORE_WAS_RESET | pwrstat::DEBUG_WAS_RESET,
+        )?;
+
+        tracing::debug!("PWRSTAT: {:#04x}", status);
+
+        if status & pwrstat::DEBUG_DOMAIN_ON == 0 {
+            tracing::debug!("Debug domain is not powered on yet");
+        }
+
+        self.write_nexus_register(NexusRegister::DcrSet, dcr::ENABLE_OCD)?;
+
+        let ocd_id = self.read_nexus_register(NexusRegister::OcdId)?;
+
+        tracing::debug!("OCDID: {:#010x}", ocd_id);
+
+        if ocd_id == 0 || ocd_id == u32::MAX {
+            return Err(XtensaError::NoXtensaTarget);
+        }
+
+        let status = self.status()?;
+
+        if !status.dbg_mod_power_on() {
+            return Err(XtensaError::DebugModulePoweredOff);
+        }
+
+        Ok(())
+    }
+
+    /// Read the IDCODE of the TAP.
+    pub fn read_idcode(&mut self) -> Result<u32, DebugProbeError> {
+        let value = self.probe.read_register(TAPINS_IDCODE, 32)?;
+
+        Ok(u32::from_le_bytes((&value[..]).try_into().unwrap()))
+    }
+
+    /// Writes a power register, and returns the previous value.
+    fn write_power_register(&mut self, instruction: u32, value: u8) -> Result<u8, XtensaError> {
+        let captured = self
+            .probe
+            .write_register(instruction, &[value], PWR_REG_LEN)?;
+
+        Ok(captured[0])
+    }
+
+    /// Puts the core into reset, or releases it from reset.
+    pub fn set_core_reset(&mut self, reset: bool) -> Result<(), XtensaError> {
+        let mut value = pwrctl::DEBUG_WAKEUP
+            | pwrctl::MEM_WAKEUP
+            | pwrctl::CORE_WAKEUP
+            | pwrctl::JTAG_DEBUG_USE;
+
+        if reset {
+            value |= pwrctl::CORE_RESET;
+        }
+
+        self.write_power_register(TAPINS_PWRCTL, value)?;
+
+        Ok(())
+    }
+
+    fn nexus_access(
+        &mut self,
+        register: NexusRegister,
+        write: bool,
+        value: u32,
+    ) -> Result<u32, XtensaError> {
+        let address = ((register as u8) << 1) | write as u8;
+
+        // The NAR and the NDR are both accessed with the NARSEL instruction. The first
+        // DR scan after selecting the instruction accesses NAR, the second one NDR.
+        self.probe
+            .write_register(TAPINS_NARSEL, &[address], NAR_LEN)?;
+        let data = self
+            .probe
+            .write_register(TAPINS_NARSEL, &value.to_le_bytes(), NDR_LEN)?;
+
+        Ok(u32::from_le_bytes((&data[..]).try_into().unwrap()))
+    }
+
+    /// Read an OCD register.
+    pub fn read_nexus_register(&mut self, register: NexusRegister) -> Result<u32, XtensaError> {
+        let value = self.nexus_access(register, false, 0)?;
+
+        tracing::trace!("Read {:?}: {:#010x}", register, value);
+
+        Ok(value)
+    }
+
+    /// Write an OCD register.
+    pub fn write_nexus_register(
+        &mut self,
+        register: NexusRegister,
+        value: u32,
+    ) -> Result<(), XtensaError> {
+        tracing::trace!("Write {:?}: {:#010x}", register, value);
+
+        self.nexus_access(register, true, value)?;
+
+        Ok(())
+    }
+
+    /// Read the debug status register.
+    pub fn status(&mut self) -> Result<DebugStatus, XtensaError> {
+        self.read_nexus_register(NexusRegister::Dsr)
+            .map(DebugStatus)
+    }
+
+    /// Clear the given bits in the debug status register.
+    pub fn clear_status(&mut self, status: DebugStatus) -> Result<(), XtensaError> {
+        self.write_nexus_register(NexusRegister::Dsr, status.0)
+    }
+
+    /// Requests the core to halt, by raising a debug interrupt.
+    pub fn halt(&mut self) -> Result<(), XtensaError> {
+        self.write_nexus_register(NexusRegister::DcrSet, dcr::DEBUG_INTERRUPT)
+    }
+
+    /// Resumes the core, by returning from the debug exception.
+    pub fn resume(&mut self) -> Result<(), XtensaError> {
+        let mut status = DebugStatus(0);
+        status.set_debug_pend_break(true);
+        status.set_debug_pend_host(true);
+        status.set_debug_int_break(true);
+        status.set_debug_int_host(true);
+        self.clear_status(status)?;
+
+        self.write_nexus_register(NexusRegister::Dir0Exec, super::arch::instruction::RFDO)
+    }
+
+    /// Executes `instruction` on the halted core, and waits for it to finish.
+    pub fn execute_instruction(&mut self, instruction: u32) -> Result<(), XtensaError> {
+        self.write_nexus_register(NexusRegister::Dir0Exec, instruction)?;
+
+        self.check_execution()
+    }
+
+    /// Checks that the instructions executed by the debugger were executed successfully.
+    pub fn check_execution(&mut self) -> Result<(), XtensaError> {
+        let mut status = self.status()?;
+
+        // The debug module is typically faster than the JTAG clock, so this is
+        // usually not needed.
+        let mut retries = 10;
+        while status.exec_busy() && retries > 0 {
+            status = self.status()?;
+            retries -= 1;
+        }
+
+        if status.exec_busy() {
+            return Err(XtensaError::Timeout);
+        }
+
+        if status.exec_exception() || status.exec_overrun() {
+            let mut clear = DebugStatus(0);
+            clear.set_exec_exception(true);
+            clear.set_exec_overrun(true);
+            self.clear_status(clear)?;
+
+            return Err(if status.exec_exception() {
+                XtensaError::ExecException
+            } else {
+                XtensaError::ExecOverrun
+            });
+        }
+
+        Ok(())
+    }
+}
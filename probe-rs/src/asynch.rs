@@ -0,0 +1,235 @@
+//! An asynchronous interface to a [`Session`].
+//!
+//! The [`AsyncSession`] moves the session to a thread of its own, and executes all
+//! operations there, so long operations like flashing or waiting for a core to halt
+//! don't block the executor. The futures don't depend on a specific runtime, so they
+//! can be driven by tokio or any other executor.
+//!
+//! Operations which aren't available as methods of the [`AsyncSession`], e.g. polling
+//! RTT channels, can be executed with [`AsyncSession::with_session`] and
+//! [`AsyncSession::with_core`].
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use probe_rs::{asynch::AsyncSession, Permissions, Session};
+//!
+//! # futures::executor::block_on(async {
+//! let session =
+//!     AsyncSession::open(|| Session::auto_attach("nrf51822", Permissions::default())).await?;
+//!
+//! let data = session.read(0, 0x2000_0000, 0x1000).await?;
+//! # Ok::<(), probe_rs::Error>(())
+//! # });
+//! ```
+
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use futures_channel::oneshot;
+
+use crate::flashing::{self, FileDownloadError, Format};
+use crate::{Core, CoreInformation, Error, MemoryInterface, Session};
+
+/// The message used if the thread of the session is gone.
+const WORKER_PANICKED: &str = "The thread of the session panicked";
+
+/// A request executed by the thread of the session.
+enum Request {
+    /// Execute the function with the session.
+    Run(Box<dyn FnOnce(&mut Session) + Send>),
+    /// Stop the thread, and return the session.
+    Stop(oneshot::Sender<Session>),
+}
+
+/// A [`Session`], which is used through futures.
+///
+/// The requests are executed one after another in the order they were made.
+/// Dropping the `AsyncSession` waits until the current request is finished,
+/// and then closes the session.
+pub struct AsyncSession {
+    requests: Option<mpsc::Sender<Request>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl AsyncSession {
+    /// Moves an existing session to a thread of its own.
+    pub fn new(session: Session) -> Self {
+        Self::spawn(move || Some(session))
+    }
+
+    /// Opens a session with the function `open`, which is executed on the thread of the session.
+    ///
+    /// Opening a session can take a while, e.g. to reset the target, so this avoids
+    /// blocking the executor as well.
+    pub async fn open<F>(open: F) -> Result<Self, Error>
+    where
+        F: FnOnce() -> Result<Session, Error> + Send + 'static,
+    {
+        let (sender, receiver) = oneshot::channel();
+
+        let session = Self::spawn(move || match open() {
+            Ok(session) => {
+                let _ = sender.send(Ok(()));
+                Some(session)
+            }
+            Err(error) => {
+                let _ = sender.send(Err(error));
+                None
+            }
+        });
+
+        receiver.await.expect(WORKER_PANICKED)?;
+
+        Ok(session)
+    }
+
+    /// Spawns the thread of the session, which handles the requests until
+    /// the `AsyncSession` is dropped.
+    fn spawn(open: impl FnOnce() -> Option<Session> + Send + 'static) -> Self {
+        let (requests, receiver) = mpsc::channel();
+
+        let worker = std::thread::Builder::new()
+            .name("probe-rs session".to_owned())
+            .spawn(move || {
+                let Some(mut session) = open() else {
+                    return;
+                };
+
+                for request in receiver {
+                    match request {
+                        Request::Run(function) => function(&mut session),
+                        Request::Stop(sender) => {
+                            let _ = sender.send(session);
+                            return;
+                        }
+                    }
+                }
+            })
+            .expect("Failed to spawn the thread of the session");
+
+        Self {
+            requests: Some(requests),
+            worker: Some(worker),
+        }
+    }
+
+    /// Sends a request to the thread of the session.
+    fn send(&self, request: Request) {
+        self.requests
+            .as_ref()
+            .and_then(|requests| requests.send(request).ok())
+            .expect(WORKER_PANICKED);
+    }
+
+    /// Executes `function` with the session, and returns its result.
+    pub async fn with_session<T, F>(&self, function: F) -> T
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Session) -> T + Send + 'static,
+    {
+        let (sender, receiver) = oneshot::channel();
+
+        self.send(Request::Run(Box::new(move |session| {
+            let _ = sender.send(function(session));
+        })));
+
+        receiver.await.expect(WORKER_PANICKED)
+    }
+
+    /// Executes `function` with the core `core_index`, and returns its result.
+    pub async fn with_core<T, F>(&self, core_index: usize, function: F) -> Result<T, Error>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Core) -> Result<T, Error> + Send + 'static,
+    {
+        self.with_session(move |session| function(&mut session.core(core_index)?))
+            .await
+    }
+
+    /// Reads `len` bytes at `address` with the core `core_index`.
+    ///
+    /// See [`MemoryInterface::read`] for the kind of memory accesses used.
+    pub async fn read(
+        &self,
+        core_index: usize,
+        address: u64,
+        len: usize,
+    ) -> Result<Vec<u8>, Error> {
+        self.with_core(core_index, move |core| {
+            let mut data = vec![0; len];
+            core.read(address, &mut data)?;
+            Ok(data)
+        })
+        .await
+    }
+
+    /// Writes `data` to `address` with the core `core_index`.
+    ///
+    /// See [`MemoryInterface::write`] for the kind of memory accesses used.
+    pub async fn write(&self, core_index: usize, address: u64, data: Vec<u8>) -> Result<(), Error> {
+        self.with_core(core_index, move |core| core.write(address, &data))
+            .await
+    }
+
+    /// Halts the core `core_index`, see [`Core::halt`].
+    pub async fn halt(
+        &self,
+        core_index: usize,
+        timeout: Duration,
+    ) -> Result<CoreInformation, Error> {
+        self.with_core(core_index, move |core| core.halt(timeout))
+            .await
+    }
+
+    /// Resumes the core `core_index`, see [`Core::run`].
+    pub async fn run(&self, core_index: usize) -> Result<(), Error> {
+        self.with_core(core_index, |core| core.run()).await
+    }
+
+    /// Waits until the core `core_index` is halted, see [`Core::wait_for_core_halted`].
+    pub async fn wait_for_core_halted(
+        &self,
+        core_index: usize,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        self.with_core(core_index, move |core| core.wait_for_core_halted(timeout))
+            .await
+    }
+
+    /// Downloads the file at `path` to the target, see [`flashing::download_file`].
+    pub async fn download_file(
+        &self,
+        path: impl Into<PathBuf>,
+        format: Format,
+    ) -> Result<(), FileDownloadError> {
+        let path = path.into();
+
+        self.with_session(move |session| flashing::download_file(session, path, format))
+            .await
+    }
+
+    /// Stops the thread of the session, and returns the session.
+    pub async fn into_session(self) -> Session {
+        let (sender, receiver) = oneshot::channel();
+
+        self.send(Request::Stop(sender));
+
+        receiver.await.expect(WORKER_PANICKED)
+    }
+}
+
+impl Drop for AsyncSession {
+    fn drop(&mut self) {
+        // Closing the channel stops the thread, after the pending requests are handled.
+        drop(self.requests.take());
+
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+static_assertions::assert_impl_all!(AsyncSession: Send, Sync);
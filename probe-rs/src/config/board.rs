@@ -0,0 +1,253 @@
+//! Descriptions of development boards
+//!
+//! A board pairs its on-board debug probe with the target chip, so that a session
+//! can be opened with just the name of the board.
+
+use std::io::Read;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use probe_rs_target::ResetType;
+
+use super::{get_target_by_name, RegistryError};
+use crate::{
+    DebugProbeError, DebugProbeInfo, Error, Permissions, Probe, ProbeCreationError, Session,
+};
+
+static BOARDS: Lazy<Mutex<Vec<Board>>> = Lazy::new(|| {
+    Mutex::new(
+        serde_yaml::from_str(include_str!("boards.yaml"))
+            .expect("The built-in board descriptions are invalid"),
+    )
+});
+
+/// A development board, with an on-board debug probe and a target chip.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Board {
+    /// The name of the board, e.g. `nucleo-h743zi`.
+    pub name: String,
+    /// The on-board debug probe.
+    pub probe: BoardProbe,
+    /// The name of the target chip.
+    pub target: String,
+    /// The reset type to use, if the default reset of the target doesn't work on the board.
+    #[serde(default)]
+    pub reset_type: Option<ResetType>,
+    /// Attach to the target while it is held in reset.
+    #[serde(default)]
+    pub connect_under_reset: bool,
+    /// The speed of the probe in kHz, if the default speed doesn't work on the board.
+    #[serde(default)]
+    pub speed: Option<u32>,
+}
+
+/// The on-board debug probe of a [`Board`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoardProbe {
+    /// The USB vendor ID of the probe.
+    pub vendor_id: u16,
+    /// The USB product ID of the probe.
+    pub product_id: u16,
+    /// A pattern for the serial number of the probe, for boards which use the same
+    /// probe. `*` matches any sequence of characters.
+    #[serde(default)]
+    pub serial_number: Option<String>,
+}
+
+impl Board {
+    /// Returns true if the probe is the on-board probe of this board.
+    pub fn matches(&self, probe: &DebugProbeInfo) -> bool {
+        if probe.vendor_id != self.probe.vendor_id || probe.product_id != self.probe.product_id {
+            return false;
+        }
+
+        match (&self.probe.serial_number, &probe.serial_number) {
+            (None, _) => true,
+            (Some(pattern), Some(serial_number)) => matches_pattern(pattern, serial_number),
+            (Some(_), None) => false,
+        }
+    }
+
+    /// Open the first connected probe of this board, and attach to the target.
+    pub fn open(&self, permissions: Permissions) -> Result<Session, Error> {
+        let probe_info = Probe::list_all()
+            .into_iter()
+            .find(|probe| self.matches(probe))
+            .ok_or(DebugProbeError::ProbeCouldNotBeCreated(
+                ProbeCreationError::NotFound,
+            ))?;
+
+        self.attach(probe_info.open()?, permissions)
+    }
+
+    /// Attach to the target of this board through the given probe, applying the
+    /// settings of the board.
+    pub fn attach(&self, mut probe: Probe, permissions: Permissions) -> Result<Session, Error> {
+        if let Some(speed) = self.speed {
+            probe.set_speed(speed)?;
+        }
+
+        let mut target = get_target_by_name(&self.target)?;
+        if let Some(reset_type) = self.reset_type {
+            target.default_reset_type = reset_type;
+        }
+
+        if self.connect_under_reset {
+            probe.attach_under_reset(target, permissions)
+        } else {
+            probe.attach(target, permissions)
+        }
+    }
+}
+
+/// Matches a string against a pattern, in which `*` matches any sequence of characters.
+fn matches_pattern(pattern: &str, value: &str) -> bool {
+    let mut parts = pattern.split('*');
+
+    // The text before the first `*` has to be at the start, the text after
+    // the last `*` at the end, and the others in between in order.
+    let Some(first) = parts.next() else {
+        return true;
+    };
+    let Some(mut rest) = value.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts = parts.collect::<Vec<_>>();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+
+    rest.ends_with(last)
+}
+
+/// Returns all known boards.
+pub fn boards() -> Vec<Board> {
+    BOARDS.lock().unwrap().clone()
+}
+
+/// Get a board by its name. The name is compared case-insensitively.
+pub fn get_board_by_name(name: impl AsRef<str>) -> Result<Board, RegistryError> {
+    let name = name.as_ref();
+
+    BOARDS
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|board| board.name.eq_ignore_ascii_case(name))
+        .cloned()
+        .ok_or_else(|| RegistryError::BoardNotFound(name.to_owned()))
+}
+
+/// Returns the boards which have the given probe on-board.
+///
+/// Multiple boards can use the same probe, e.g. the ST-Link of different Nucleo boards.
+pub fn boards_for_probe(probe: &DebugProbeInfo) -> Vec<Board> {
+    BOARDS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|board| board.matches(probe))
+        .cloned()
+        .collect()
+}
+
+/// Add the boards from a YAML file, containing a list of board descriptions.
+///
+/// Boards with the name of a known board replace it.
+pub fn add_boards_from_yaml<R: Read>(yaml: R) -> Result<(), RegistryError> {
+    let new_boards: Vec<Board> = serde_yaml::from_reader(yaml)?;
+
+    let mut boards = BOARDS.lock().unwrap();
+    for board in new_boards {
+        boards.retain(|b| !b.name.eq_ignore_ascii_case(&board.name));
+        boards.push(board);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::DebugProbeType;
+
+    fn probe_info(vendor_id: u16, product_id: u16, serial_number: &str) -> DebugProbeInfo {
+        DebugProbeInfo::new(
+            "Test probe",
+            vendor_id,
+            product_id,
+            Some(serial_number.to_owned()),
+            DebugProbeType::CmsisDap,
+            None,
+        )
+    }
+
+    #[test]
+    fn builtin_boards_are_valid() {
+        for board in boards() {
+            get_target_by_name(&board.target).unwrap_or_else(|e| {
+                panic!("The target of the board {} is invalid: {e}", board.name)
+            });
+        }
+    }
+
+    #[test]
+    fn serial_number_patterns() {
+        assert!(matches_pattern("9904*", "99043600000001"));
+        assert!(!matches_pattern("9904*", "99003600000001"));
+        assert!(matches_pattern("*36*01", "99043600000001"));
+        assert!(!matches_pattern("*37*", "99043600000001"));
+        assert!(matches_pattern("ABC", "ABC"));
+        assert!(!matches_pattern("ABC", "ABCD"));
+        assert!(matches_pattern("*", ""));
+    }
+
+    #[test]
+    fn board_of_probe() {
+        let microbit = probe_info(0x0d28, 0x0204, "9904360250594e45003d0018000000");
+        let boards = boards_for_probe(&microbit);
+
+        assert_eq!(boards.len(), 1);
+        assert_eq!(boards[0].name, "microbit-v2");
+        assert_eq!(boards[0].target, "nRF52833_xxAA");
+
+        // The ST-Link V2-1 is used by many Nucleo boards
+        let stlink = probe_info(0x0483, 0x374b, "0669FF505052");
+        assert!(boards_for_probe(&stlink).len() > 1);
+    }
+
+    #[test]
+    fn add_boards() {
+        add_boards_from_yaml(
+            r#"
+- name: custom-board
+  probe:
+    vendor_id: 0x1234
+    product_id: 0x5678
+  target: nRF52840_xxAA
+  reset_type: Hardware
+  speed: 1000
+"#
+            .as_bytes(),
+        )
+        .unwrap();
+
+        let board = get_board_by_name("Custom-Board").unwrap();
+        assert_eq!(board.reset_type, Some(ResetType::Hardware));
+        assert_eq!(board.speed, Some(1000));
+        assert!(board.matches(&probe_info(0x1234, 0x5678, "any")));
+
+        assert!(matches!(
+            get_board_by_name("unknown-board"),
+            Err(RegistryError::BoardNotFound(_))
+        ));
+    }
+}
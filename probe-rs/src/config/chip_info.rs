@@ -1,4 +1,13 @@
-use crate::architecture::arm::ArmChipInfo;
+use crate::architecture::arm::{memory::adi_v5_memory_interface::ArmProbe, ArmChipInfo};
+use jep106::JEP106Code;
+
+const STMICRO: JEP106Code = JEP106Code { id: 0x20, cc: 0x00 };
+const NORDIC: JEP106Code = JEP106Code { id: 0x44, cc: 0x02 };
+const FREESCALE: JEP106Code = JEP106Code { id: 0x0E, cc: 0x00 };
+const NXP: JEP106Code = JEP106Code { id: 0x15, cc: 0x00 };
+
+/// Reads the vendor specific ID registers, and returns the name prefixes of the matching chips.
+type VendorRegisterReader = fn(&mut dyn ArmProbe) -> Option<Vec<String>>;
 
 /// Information about a chip which is used
 /// for automatic detection of the connected chip.
@@ -12,6 +21,10 @@ pub(crate) enum ChipInfo {
     /// ARM specific information for chip
     /// auto-detection. See [ArmChipInfo].
     Arm(ArmChipInfo),
+    /// The chip was identified from vendor specific ID registers, or
+    /// from the JTAG IDCODE. Contains the prefixes of the names of all
+    /// chips matching the identifier.
+    NamePrefixes(Vec<String>),
 }
 
 impl From<ArmChipInfo> for ChipInfo {
@@ -19,3 +32,168 @@ impl From<ArmChipInfo> for ChipInfo {
         ChipInfo::Arm(info)
     }
 }
+
+impl ChipInfo {
+    /// Identify an ARM chip by reading the vendor specific ID registers.
+    ///
+    /// If the manufacturer is known from the ROM table, only the registers of this
+    /// manufacturer are read.
+    pub(crate) fn read_from_vendor_registers(
+        memory: &mut dyn ArmProbe,
+        manufacturer: Option<JEP106Code>,
+    ) -> Option<Self> {
+        let vendors: [(&[JEP106Code], VendorRegisterReader); 3] = [
+            (&[STMICRO], read_stm32_idcode),
+            (&[NORDIC], read_nrf_ficr),
+            (&[FREESCALE, NXP], read_kinetis_sdid),
+        ];
+
+        let names = vendors
+            .iter()
+            .filter(|(codes, _)| manufacturer.map(|m| codes.contains(&m)).unwrap_or(true))
+            .find_map(|(_, read)| read(memory))?;
+
+        tracing::debug!("Identified chip from vendor registers: {:?}", names);
+
+        Some(ChipInfo::NamePrefixes(names))
+    }
+
+    /// Identify a chip by its JTAG IDCODE.
+    ///
+    /// This is used for chips without any other identification, e.g. the RISC-V and
+    /// Xtensa chips from Espressif.
+    pub(crate) fn from_jtag_idcode(idcode: u32) -> Option<Self> {
+        let names: &[&str] = match idcode {
+            0x0000_5c25 => &["esp32c3"],
+            0x0000_dc25 => &["esp32c6"],
+            // The ESP32 and the ESP32-S3 use the same IDCODE
+            0x1200_34e5 => &["esp32", "esp32s3"],
+            _ => return None,
+        };
+
+        Some(ChipInfo::NamePrefixes(
+            names.iter().map(|name| name.to_string()).collect(),
+        ))
+    }
+}
+
+/// Read the device ID from the `DBGMCU_IDCODE` register of an STM32.
+fn read_stm32_idcode(memory: &mut dyn ArmProbe) -> Option<Vec<String>> {
+    // The location of the DBGMCU depends on the core, these are the
+    // addresses for the Cortex-M3/M4/M7, the Cortex-M0(+) and the H7.
+    const DBGMCU_IDCODE: [u64; 3] = [0xE004_2000, 0x4001_5800, 0x5C00_1000];
+
+    DBGMCU_IDCODE.iter().find_map(|&address| {
+        let dev_id = memory.read_word_32(address).ok()? & 0xFFF;
+
+        stm32_names(dev_id as u16).map(|names| names.iter().map(|n| n.to_string()).collect())
+    })
+}
+
+/// The name prefixes of the STM32 chips with the given `DEV_ID`.
+fn stm32_names(dev_id: u16) -> Option<&'static [&'static str]> {
+    Some(match dev_id {
+        0x410 => &["STM32F101", "STM32F102", "STM32F103"],
+        0x413 => &["STM32F405", "STM32F407", "STM32F415", "STM32F417"],
+        0x415 => &["STM32L475", "STM32L476", "STM32L486"],
+        0x419 => &["STM32F427", "STM32F429", "STM32F437", "STM32F439"],
+        0x421 => &["STM32F446"],
+        0x431 => &["STM32F411"],
+        0x435 => &["STM32L43", "STM32L44"],
+        0x440 => &["STM32F03", "STM32F05"],
+        0x441 => &["STM32F412"],
+        0x444 => &["STM32F03"],
+        0x445 => &["STM32F04"],
+        0x448 => &["STM32F07"],
+        0x449 => &["STM32F745", "STM32F746", "STM32F756"],
+        0x450 => &["STM32H742", "STM32H743", "STM32H750", "STM32H753"],
+        0x460 => &["STM32G07", "STM32G08"],
+        0x468 => &["STM32G431", "STM32G441"],
+        0x495 => &["STM32WB5"],
+        _ => return None,
+    })
+}
+
+/// Read the part number from the FICR of a nRF chip.
+fn read_nrf_ficr(memory: &mut dyn ArmProbe) -> Option<Vec<String>> {
+    // INFO.PART of the nRF52, nRF53 and nRF91
+    const INFO_PART: [u64; 3] = [0x1000_0100, 0x00FF_020C, 0x00FF_0140];
+
+    INFO_PART.iter().find_map(|&address| {
+        let part = memory.read_word_32(address).ok()?;
+
+        nrf_name(part).map(|name| vec![name])
+    })
+}
+
+/// The name prefix of the nRF chip with the given part number.
+fn nrf_name(part: u32) -> Option<String> {
+    // The part number is the hexadecimal representation of the name, e.g. 0x52840
+    let name = format!("{part:X}");
+
+    let valid = (name.starts_with('5') || name.starts_with('9'))
+        && name.chars().all(|c| c.is_ascii_digit());
+
+    valid.then(|| format!("nRF{name}"))
+}
+
+/// Read the `SIM_SDID` register of a Kinetis chip.
+fn read_kinetis_sdid(memory: &mut dyn ArmProbe) -> Option<Vec<String>> {
+    const SIM_SDID: u64 = 0x4004_8024;
+
+    let sdid = memory.read_word_32(SIM_SDID).ok()?;
+
+    kinetis_name(sdid).map(|name| vec![name])
+}
+
+/// The name prefix of the Kinetis chip with the given `SIM_SDID`.
+fn kinetis_name(sdid: u32) -> Option<String> {
+    let family = (sdid >> 28) & 0xF;
+    let subfamily = (sdid >> 24) & 0xF;
+    let series = match (sdid >> 20) & 0xF {
+        0 => "MK",
+        1 => "MKL",
+        5 => "MKW",
+        6 => "MKV",
+        _ => return None,
+    };
+
+    Some(format!("{series}{family}{subfamily}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stm32_dev_id() {
+        assert_eq!(stm32_names(0x413).unwrap()[1], "STM32F407");
+        assert!(stm32_names(0x000).is_none());
+    }
+
+    #[test]
+    fn nrf_part() {
+        assert_eq!(nrf_name(0x52840).as_deref(), Some("nRF52840"));
+        assert_eq!(nrf_name(0x9160).as_deref(), Some("nRF9160"));
+        assert_eq!(nrf_name(0xFFFF_FFFF), None);
+        assert_eq!(nrf_name(0), None);
+    }
+
+    #[test]
+    fn kinetis_sdid() {
+        // MK64FN1M0VLL12
+        assert_eq!(kinetis_name(0x6400_0000).as_deref(), Some("MK64"));
+        // MKL25Z128VLK4
+        assert_eq!(kinetis_name(0x2510_0000).as_deref(), Some("MKL25"));
+        assert_eq!(kinetis_name(0x2530_0000), None);
+    }
+
+    #[test]
+    fn esp_idcode() {
+        assert!(matches!(
+            ChipInfo::from_jtag_idcode(0x5c25),
+            Some(ChipInfo::NamePrefixes(names)) if names == ["esp32c3"]
+        ));
+        assert!(ChipInfo::from_jtag_idcode(0x4ba0_0477).is_none());
+    }
+}
@@ -1,10 +1,9 @@
-use goblin::{
-    elf::program_header::PT_LOAD,
-    elf64::section_header::{SHT_NOBITS, SHT_PROGBITS},
-};
-use probe_rs::config::MemoryRange;
+use object::elf::{PT_LOAD, SHT_NOBITS, SHT_PROGBITS};
+use object::read::elf::{ElfFile, FileHeader, ProgramHeader, SectionHeader};
+use object::Endianness;
+use probe_rs_target::MemoryRange;
 
-use anyhow::{anyhow, Result};
+use super::PackError;
 
 const CODE_SECTION_KEY: (&str, u32) = ("PrgCode", SHT_PROGBITS);
 const DATA_SECTION_KEY: (&str, u32) = ("PrgData", SHT_PROGBITS);
@@ -41,7 +40,13 @@ pub(crate) struct AlgorithmBinary {
 
 impl AlgorithmBinary {
     /// Extract a new flash algorithm binary blob from an ELF data blob.
-    pub(crate) fn new(elf: &goblin::elf::Elf<'_>, buffer: &[u8]) -> Result<Self> {
+    pub(crate) fn new<Elf: FileHeader<Endian = Endianness>>(
+        elf: &ElfFile<'_, Elf>,
+    ) -> Result<Self, PackError> {
+        let endian = elf.endian();
+        let buffer = elf.data();
+        let section_table = elf.raw_header().sections(endian, buffer)?;
+
         let mut code_section = None;
         let mut data_section = None;
         let mut bss_section = None;
@@ -49,36 +54,46 @@ impl AlgorithmBinary {
         let mut suspicious_sections = Vec::new();
 
         // Iterate all program headers and get sections.
-        for ph in &elf.program_headers {
+        for ph in elf.raw_segments() {
+            let p_offset: u64 = ph.p_offset(endian).into();
+            let p_filesz: u64 = ph.p_filesz(endian).into();
+            let p_vaddr: u64 = ph.p_vaddr(endian).into();
+
             // Only regard sections that contain at least one byte.
             // And are marked loadable (this filters out debug symbols).
-            if ph.p_type == PT_LOAD && ph.p_filesz > 0 {
-                let sector = ph.p_offset..ph.p_offset + ph.p_filesz;
+            if ph.p_type(endian) == PT_LOAD && p_filesz > 0 {
+                let sector = p_offset..p_offset + p_filesz;
 
-                log::debug!("Program header: LOAD to VMA {:#010x}", ph.p_vaddr);
+                tracing::debug!("Program header: LOAD to VMA {:#010x}", p_vaddr);
 
                 // Scan all sectors if they contain any part of the sections found.
-                for sh in &elf.section_headers {
-                    let range = sh.sh_offset..sh.sh_offset + sh.sh_size;
+                for sh in section_table.iter() {
+                    let sh_offset: u64 = sh.sh_offset(endian).into();
+                    let sh_size: u64 = sh.sh_size(endian).into();
+                    let sh_addr: u64 = sh.sh_addr(endian).into();
+
+                    let range = sh_offset..sh_offset + sh_size;
                     if sector.contains_range(&range) {
                         // If we found a valid section, store its contents.
-                        let data =
-                            Vec::from(&buffer[sh.sh_offset as usize..][..sh.sh_size as usize]);
+                        let data = Vec::from(&buffer[sh_offset as usize..][..sh_size as usize]);
                         let section = Some(Section {
-                            start: sh.sh_addr as u32,
-                            length: sh.sh_size as u32,
+                            start: sh_addr as u32,
+                            length: sh_size as u32,
                             data,
-                            load_address: (ph.p_vaddr + sh.sh_offset - ph.p_offset) as u32,
+                            load_address: (p_vaddr + sh_offset - p_offset) as u32,
                         });
 
+                        let name = section_table.section_name(endian, sh)?;
+                        let name = String::from_utf8_lossy(name);
+
                         // Make sure we store the section contents under the right name.
-                        match (&elf.shdr_strtab[sh.sh_name], sh.sh_type) {
+                        match (name.as_ref(), sh.sh_type(endian)) {
                             CODE_SECTION_KEY => code_section = section,
                             DATA_SECTION_KEY => data_section = section,
                             BSS_SECTION_KEY => bss_section = section,
                             (name, _section_type) => {
                                 if SUSPICIOUS_SECTION_NAMES.contains(&name) {
-                                    suspicious_sections.push(name);
+                                    suspicious_sections.push(name.to_owned());
                                 }
                             }
                         }
@@ -88,22 +103,17 @@ impl AlgorithmBinary {
         }
 
         if !suspicious_sections.is_empty() {
-            log::warn!("The ELF file contains some unexpected sections, which should not be part of a flash loader: ");
+            tracing::warn!("The ELF file contains some unexpected sections, which should not be part of a flash loader: ");
 
             for section in suspicious_sections {
-                log::warn!("\t{}", section);
+                tracing::warn!("\t{}", section);
             }
 
-            log::warn!("Code should be placed in the '{}' section, and data should be placed in the '{}' section.", CODE_SECTION_KEY.0, DATA_SECTION_KEY.0);
+            tracing::warn!("Code should be placed in the '{}' section, and data should be placed in the '{}' section.", CODE_SECTION_KEY.0, DATA_SECTION_KEY.0);
         }
 
         // Check all the sections for validity and return the binary blob if possible.
-        let code_section = code_section.ok_or_else(|| {
-            anyhow!(
-                "Section '{}' not found, which is required to be present.",
-                CODE_SECTION_KEY.0
-            )
-        })?;
+        let code_section = code_section.ok_or(PackError::MissingSection(CODE_SECTION_KEY.0))?;
 
         let data_section = data_section.unwrap_or_else(|| Section {
             start: code_section.start + code_section.length,
@@ -1,6 +1,8 @@
+use object::read::elf::{ElfFile, FileHeader};
+use object::Endianness;
 use scroll::Pread;
 
-use anyhow::{anyhow, Result};
+use super::{parser::read_elf_bin_data, PackError};
 
 /// A struct to describe one sector in Flash.
 #[derive(Clone, Debug)]
@@ -64,13 +66,20 @@ impl FlashDevice {
     const MAX_ID_STRING_LENGTH: usize = 128;
 
     /// Parses the `FlashDevice` struct from ELF binary data.
-    pub(crate) fn new(elf: &goblin::elf::Elf<'_>, buffer: &[u8], address: u32) -> Result<Self> {
+    pub(crate) fn new<Elf: FileHeader<Endian = Endianness>>(
+        elf: &ElfFile<'_, Elf>,
+        address: u32,
+    ) -> Result<Self, PackError> {
         // Extract all the sector data from the ELF blob.
-        let sectors = Self::parse_sectors(elf, buffer, address);
+        let sectors = Self::parse_sectors(elf, address);
 
         // Get the rest of the data stored in the struct.
-        let data = crate::parser::read_elf_bin_data(elf, buffer, address, Self::INFO_SIZE)
-            .ok_or_else(|| anyhow!("Failed to read binary data for flash device. Read address: {:#010x}, size: {} bytes", address, Self::INFO_SIZE))?;
+        let data = read_elf_bin_data(elf, address, Self::INFO_SIZE).ok_or(
+            PackError::InvalidFlashDevice {
+                address,
+                size: Self::INFO_SIZE,
+            },
+        )?;
 
         // Get the string length of the name
         let hypothetical_length = data[2..2 + Self::MAX_ID_STRING_LENGTH]
@@ -96,17 +105,14 @@ impl FlashDevice {
     }
 
     /// Parse the sector infos in the device struct.
-    pub(crate) fn parse_sectors(
-        elf: &goblin::elf::Elf<'_>,
-        buffer: &[u8],
+    pub(crate) fn parse_sectors<Elf: FileHeader<Endian = Endianness>>(
+        elf: &ElfFile<'_, Elf>,
         address: u32,
     ) -> Vec<SectorInfo> {
         let mut sectors = vec![];
         let mut offset = Self::INFO_SIZE;
         // As long as we find new sectors, keep em comming.
-        while let Some(data) =
-            crate::parser::read_elf_bin_data(elf, buffer, address + offset, Self::SECTOR_INFO_SIZE)
-        {
+        while let Some(data) = read_elf_bin_data(elf, address + offset, Self::SECTOR_INFO_SIZE) {
             if let Some(sector) = SectorInfo::new(data) {
                 sectors.push(sector);
                 offset += 8;
@@ -0,0 +1,747 @@
+//! Reading of CMSIS-Packs
+//!
+//! CMSIS-Packs are distributed by the chip vendors, and contain the description
+//! of their devices. probe-rs can extract the memory map, the flash algorithms,
+//! the debug access information and the debug sequences of the devices in a pack,
+//! and use them as targets at runtime.
+//!
+//! The debug sequences of a pack replace the built-in sequences of the same name,
+//! see [`CmsisPackSequence`](crate::architecture::arm::sequences::cmsis_pack::CmsisPackSequence).
+//!
+//! # Examples
+//!
+//! ## Add the targets of a pack to the registry
+//!
+//! ```no_run
+//! probe_rs::config::cmsis_pack::add_targets_from_pack("Vendor.DeviceFamilyPack.1.0.0.pack")?;
+//!
+//! let target = probe_rs::config::get_target_by_name("Device")?;
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+mod algorithm_binary;
+mod flash_device;
+mod parser;
+mod sequences;
+
+use std::fs;
+use std::io::{Read, Seek};
+use std::path::Path;
+
+use cmsis_pack::pdsc::{Core, Device, Package, Processor};
+use cmsis_pack::utils::FromElem;
+use probe_rs_target::{
+    ArmCoreAccessOptions, Chip, ChipFamily, Core as ProbeCore, CoreAccessOptions, GenericRegion,
+    MemoryRegion, NvmRegion, RamRegion, RawFlashAlgorithm, ResetType, RiscvCoreAccessOptions,
+    TargetDescriptionSource, XtensaCoreAccessOptions,
+};
+
+use super::{registry, RegistryError};
+use crate::{Architecture, CoreType};
+
+pub use parser::extract_flash_algo;
+pub use sequences::{read_sequences, DeviceSequences};
+
+/// Error type for all errors which occur when reading a CMSIS-Pack.
+#[derive(Debug, thiserror::Error)]
+pub enum PackError {
+    /// An IO error which occurred when trying to read the pack.
+    #[error("An IO error was encountered")]
+    Io(#[from] std::io::Error),
+    /// The pack is not a valid ZIP archive.
+    #[error("Unable to read the pack archive")]
+    Archive(#[from] zip::result::ZipError),
+    /// The pack doesn't contain a `.pdsc` file.
+    #[error("The pack does not contain a .pdsc file")]
+    MissingPdsc,
+    /// The `.pdsc` file of the pack could not be parsed.
+    #[error("Unable to parse the .pdsc file '{name}': {message}")]
+    InvalidPdsc {
+        /// The name of the `.pdsc` file.
+        name: String,
+        /// The error message of the parser.
+        message: String,
+    },
+    /// A flash algorithm is not a valid ELF file.
+    #[error("Unable to parse the flash algorithm ELF file")]
+    Elf(#[from] object::read::Error),
+    /// A symbol required in a flash algorithm is missing.
+    #[error("The symbol '{0}' is missing in the flash algorithm")]
+    MissingSymbol(&'static str),
+    /// A section required in a flash algorithm is missing.
+    #[error("The section '{0}' is missing in the flash algorithm")]
+    MissingSection(&'static str),
+    /// The flash device description of a flash algorithm could not be read.
+    #[error("Failed to read binary data for flash device. Read address: {address:#010x}, size: {size} bytes")]
+    InvalidFlashDevice {
+        /// The address of the flash device description.
+        address: u32,
+        /// The size of the flash device description.
+        size: u32,
+    },
+    /// A flash algorithm is invalid.
+    #[error("Invalid flash algorithm: {0}")]
+    InvalidFlashAlgorithm(String),
+    /// A core of a device is not supported by probe-rs.
+    #[error("Core '{0}' is not yet supported for target generation.")]
+    UnsupportedCore(String),
+    /// The generated targets could not be added to the registry.
+    #[error("Unable to add the targets to the registry")]
+    Registry(#[from] RegistryError),
+}
+
+/// The location of the files of a pack.
+pub enum Kind<'a, T>
+where
+    T: Seek + Read,
+{
+    /// A packed `.pack` file.
+    Archive(&'a mut zip::ZipArchive<T>),
+    /// An unpacked pack, in the given directory.
+    Directory(&'a Path),
+}
+
+/// Read all devices of a parsed `.pdsc` file, and add them to `families`.
+///
+/// The debug sequences of the devices are taken from `sequences`, see [`read_sequences`].
+///
+/// If `only_supported_families` is set, only devices of families which are already
+/// contained in the target registry are added.
+pub fn handle_package<T>(
+    pdsc: Package,
+    sequences: &DeviceSequences,
+    mut kind: Kind<T>,
+    families: &mut Vec<ChipFamily>,
+    only_supported_families: bool,
+) -> Result<(), PackError>
+where
+    T: Seek + Read,
+{
+    // Forge a definition file for each device in the .pdsc file.
+    let pack_file_release = Some(pdsc.releases.latest_release().version.clone());
+    let mut devices = pdsc.devices.0.into_iter().collect::<Vec<_>>();
+    devices.sort_by(|a, b| a.0.cmp(&b.0));
+
+    for (device_name, device) in devices {
+        // Only process this, if this belongs to a supported family.
+        let currently_supported_chip_families = super::families()?;
+
+        if only_supported_families
+            && !currently_supported_chip_families
+                .iter()
+                .any(|supported_family| supported_family.name == device.family)
+        {
+            // We only want to continue if the chip family is already represented as supported probe_rs target chip family.
+            tracing::debug!("Unsupported chip family {}. Skipping ...", device.family);
+            continue;
+        }
+
+        // Check if this device family is already known.
+        let mut potential_family = families
+            .iter_mut()
+            .find(|family| family.name == device.family);
+
+        let family = if let Some(ref mut family) = potential_family {
+            family
+        } else {
+            families.push(ChipFamily {
+                name: device.family.clone(),
+                manufacturer: None,
+                generated_from_pack: true,
+                pack_file_release: pack_file_release.clone(),
+                variants: Vec::new(),
+                flash_algorithms: Vec::new(),
+                debug_sequence: None,
+                source: TargetDescriptionSource::External,
+            });
+            // This unwrap is always safe as we insert at least one item previously.
+            families.last_mut().unwrap()
+        };
+
+        // Extract the flash algorithm, block & sector size and the erased byte value from the ELF binary.
+        let variant_flash_algorithms = device
+            .algorithms
+            .iter()
+            .map(|flash_algorithm| {
+                let algo = match &mut kind {
+                    Kind::Archive(archive) => extract_flash_algo(
+                        archive.by_name(&flash_algorithm.file_name.as_path().to_string_lossy())?,
+                        &flash_algorithm.file_name,
+                        flash_algorithm.default,
+                        false, // Algorithms from CMSIS-Pack files are position independent
+                    ),
+                    Kind::Directory(path) => extract_flash_algo(
+                        fs::File::open(path.join(&flash_algorithm.file_name))?,
+                        &flash_algorithm.file_name,
+                        flash_algorithm.default,
+                        false, // Algorithms from CMSIS-Pack files are position independent
+                    ),
+                }?;
+
+                // We add this algo directly to the algos of the family if it's not already added.
+                // Make sure we never add an algo twice to save file size.
+                if !family.flash_algorithms.contains(&algo) {
+                    family.flash_algorithms.push(algo.clone());
+                }
+
+                // This algo will still be added to the specific chip algos by name.
+                // We just need to deduplicate the entire flash algorithm and reference to it by name at other places.
+
+                Ok(algo)
+            })
+            .filter_map(|flash_algorithm: Result<RawFlashAlgorithm, PackError>| {
+                match flash_algorithm {
+                    Ok(flash_algorithm) => Some(flash_algorithm),
+                    Err(error) => {
+                        tracing::warn!("Failed to parse flash algorithm.");
+                        tracing::warn!("Reason: {:?}", error);
+                        None
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let flash_algorithm_names: Vec<_> = variant_flash_algorithms
+            .iter()
+            .map(|fa| fa.name.to_string())
+            .collect();
+
+        // Sometimes the algos are referenced twice, for example in the multicore H7s
+        // Deduplicate while keeping order.
+        let flash_algorithm_names: Vec<_> = flash_algorithm_names
+            .iter()
+            .enumerate()
+            .filter(|(i, s)| !flash_algorithm_names[..*i].contains(s))
+            .map(|(_, s)| s.clone())
+            .collect();
+
+        let cores = device
+            .processors
+            .iter()
+            .map(create_core)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        family.variants.push(Chip {
+            name: device_name,
+            part: None,
+            cores,
+            memory_map: get_mem_map(&device),
+            flash_algorithms: flash_algorithm_names,
+            sequences: sequences.for_device(&device),
+            bank_swap: None,
+            default_reset_type: ResetType::default(),
+        });
+    }
+
+    Ok(())
+}
+
+fn create_core(processor: &Processor) -> Result<ProbeCore, PackError> {
+    let core_type = core_to_probe_core(&processor.core)?;
+    Ok(ProbeCore {
+        name: processor
+            .name
+            .as_ref()
+            .map(|s| s.to_ascii_lowercase())
+            .unwrap_or_else(|| "main".to_string()),
+        core_type,
+        core_access_options: match core_type.architecture() {
+            Architecture::Arm => CoreAccessOptions::Arm(ArmCoreAccessOptions {
+                ap: processor.ap,
+                psel: 0,
+                debug_base: None,
+                cti_base: None,
+                lockstep_redundant: false,
+                cache_maintenance: false,
+            }),
+            Architecture::Riscv => CoreAccessOptions::Riscv(RiscvCoreAccessOptions::default()),
+            Architecture::Xtensa => CoreAccessOptions::Xtensa(XtensaCoreAccessOptions {}),
+        },
+        memory_aliases: vec![],
+    })
+}
+
+fn core_to_probe_core(value: &Core) -> Result<CoreType, PackError> {
+    Ok(match value {
+        Core::CortexM0 => CoreType::Armv6m,
+        Core::CortexM0Plus => CoreType::Armv6m,
+        Core::CortexM4 => CoreType::Armv7em,
+        Core::CortexM3 => CoreType::Armv7m,
+        Core::CortexM23 => CoreType::Armv8m,
+        Core::CortexM33 => CoreType::Armv8m,
+        Core::CortexM7 => CoreType::Armv7em,
+        Core::StarMC1 => CoreType::Armv8m,
+        c => return Err(PackError::UnsupportedCore(format!("{c:?}"))),
+    })
+}
+
+/// Read the devices of all `.pdsc` files in the given directory and its subdirectories,
+/// and add them to `families`.
+pub fn read_pack_dir(path: &Path, families: &mut Vec<ChipFamily>) -> Result<(), PackError> {
+    // If we get a dir, look for all .pdsc files.
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let entry_path = entry.path();
+
+        if entry_path.is_dir() {
+            read_pack_dir(&entry_path, families)?;
+        } else if let Some(extension) = entry_path.extension() {
+            if extension == "pdsc" {
+                tracing::info!("Found .pdsc file: {}", path.display());
+
+                let name = entry_path.display().to_string();
+                let pdsc = fs::read_to_string(&entry_path)?;
+
+                let package = Package::from_string(&pdsc).map_err(|e| PackError::InvalidPdsc {
+                    name: name.clone(),
+                    message: e.to_string(),
+                })?;
+                let sequences = read_sequences(&name, &pdsc)?;
+
+                handle_package::<fs::File>(
+                    package,
+                    &sequences,
+                    Kind::Directory(path),
+                    families,
+                    false,
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Read the devices of a `.pack` file, and add them to `families`.
+pub fn read_pack_file(path: &Path, families: &mut Vec<ChipFamily>) -> Result<(), PackError> {
+    tracing::info!("Trying to open pack file: {}.", path.display());
+
+    families.extend(read_pack(fs::File::open(path)?)?);
+
+    Ok(())
+}
+
+/// Read the devices of a pack from a reader, e.g. a `.pack` file
+/// which was downloaded into memory.
+pub fn read_pack<T>(reader: T) -> Result<Vec<ChipFamily>, PackError>
+where
+    T: Seek + Read,
+{
+    let mut archive = zip::ZipArchive::new(reader)?;
+
+    let (package, sequences, _) = read_pdsc_from_archive(&mut archive)?;
+
+    let mut families = vec![];
+    handle_package(
+        package,
+        &sequences,
+        Kind::Archive(&mut archive),
+        &mut families,
+        false,
+    )?;
+
+    Ok(families)
+}
+
+/// Read the SVD file of a device from a pack, e.g. a `.pack` file.
+///
+/// Returns `None` if the pack contains no SVD file for the device.
+pub fn read_svd<T>(reader: T, device_name: &str) -> Result<Option<String>, PackError>
+where
+    T: Seek + Read,
+{
+    let mut archive = zip::ZipArchive::new(reader)?;
+
+    let (package, _, directory) = read_pdsc_from_archive(&mut archive)?;
+
+    let Some(svd) = package
+        .devices
+        .0
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(device_name))
+        .and_then(|(_, device)| device.processors.iter().find_map(|p| p.svd.clone()))
+    else {
+        return Ok(None);
+    };
+
+    // The paths in the pdsc are relative to its location, and may use Windows separators.
+    let svd = svd.replace('\\', "/");
+    let path = if directory.is_empty() {
+        svd
+    } else {
+        format!("{directory}/{svd}")
+    };
+
+    let mut content = String::new();
+    archive.by_name(&path)?.read_to_string(&mut content)?;
+
+    Ok(Some(content))
+}
+
+/// Read and parse the `.pdsc` file of a ZIP archive.
+///
+/// Returns the package, its debug sequences, and the directory of the `.pdsc` file in the archive.
+fn read_pdsc_from_archive<T>(
+    archive: &mut zip::ZipArchive<T>,
+) -> Result<(Package, DeviceSequences, String), PackError>
+where
+    T: Seek + Read,
+{
+    let mut pdsc_file = find_pdsc_in_archive(archive)?.ok_or(PackError::MissingPdsc)?;
+
+    let mut pdsc = String::new();
+    pdsc_file.read_to_string(&mut pdsc)?;
+
+    let package = Package::from_string(&pdsc).map_err(|e| PackError::InvalidPdsc {
+        name: pdsc_file.name().to_owned(),
+        message: e.to_string(),
+    })?;
+    let sequences = read_sequences(pdsc_file.name(), &pdsc)?;
+
+    let directory = Path::new(pdsc_file.name())
+        .parent()
+        .map(|directory| directory.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    Ok((package, sequences, directory))
+}
+
+/// Read the devices of a `.pack` file, or of an unpacked pack in a directory,
+/// and add them to the internal target registry.
+///
+/// Families which are already contained in the registry are replaced.
+pub fn add_targets_from_pack(path: impl AsRef<Path>) -> Result<(), PackError> {
+    let path = path.as_ref();
+    let mut families = vec![];
+
+    if path.is_dir() {
+        read_pack_dir(path, &mut families)?;
+    } else {
+        read_pack_file(path, &mut families)?;
+    }
+
+    for family in families {
+        registry::add_target_family(family)?;
+    }
+
+    Ok(())
+}
+
+/// Extracts the pdsc out of a ZIP archive.
+pub fn find_pdsc_in_archive<T>(
+    archive: &mut zip::ZipArchive<T>,
+) -> Result<Option<zip::read::ZipFile<'_>>, PackError>
+where
+    T: Seek + Read,
+{
+    let mut index = None;
+    for i in 0..archive.len() {
+        let file = archive.by_index(i)?;
+        let Some(outpath) = file.enclosed_name() else {
+            tracing::warn!(
+                "Skipping the ZIP file content with path '{}': Path seems to be malformed",
+                file.name()
+            );
+            continue;
+        };
+
+        if let Some(extension) = outpath.extension() {
+            if extension == "pdsc" {
+                // We cannot return the file directly here,
+                // because this leads to lifetime problems.
+
+                index = Some(i);
+                break;
+            }
+        }
+    }
+
+    if let Some(index) = index {
+        let file = archive.by_index(index)?;
+
+        Ok(Some(file))
+    } else {
+        Ok(None)
+    }
+}
+
+/// A flag to indicate what type of memory this is.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum MemoryType {
+    /// A RAM memory.
+    Ram,
+    /// A Non Volatile memory.
+    Nvm,
+    /// Generic
+    Generic,
+}
+
+/// A struct to combine essential information from [`cmsis_pack::pdsc::Device::memories`].
+/// This is used to apply the necessary sorting and filtering in creating [`MemoryRegion`]s.
+// The sequence of the fields is important for the sorting by derived natural order.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct DeviceMemory {
+    memory_type: MemoryType,
+    p_name: Option<String>,
+    is_boot_memory: bool,
+    memory_start: u64,
+    memory_end: u64,
+    name: String,
+}
+
+// From PR's https://github.com/probe-rs/target-gen/pull/20 and https://github.com/probe-rs/target-gen/pull/25:
+// TODO: What is the logic that justifies PR#20 selecting the largest memory? Shouldn't we match flash algo's with RAM based on load_address?
+// Flash and RAM regions are not guaranteed to be sorted in the PDSC file, so we:
+// - Sort them here.
+// - Merge contiguous regions.
+// Update: For multiple cores, we have to take processor access into account during this merge.
+/// Sorts the memory regions in the package and merges contiguous regions with the same attributes.
+fn get_mem_map(device: &Device) -> Vec<MemoryRegion> {
+    let mut device_memories: Vec<DeviceMemory> = device
+        .memories
+        .0
+        .iter()
+        .map(|(name, memory)| DeviceMemory {
+            name: name.clone(),
+            p_name: memory.p_name.clone(),
+            memory_type: if memory.default && memory.access.read && memory.access.write {
+                MemoryType::Ram
+            } else if memory.default
+                && memory.access.read
+                && memory.access.execute
+                && !memory.access.write
+            {
+                MemoryType::Nvm
+            } else {
+                MemoryType::Generic
+            },
+            memory_start: memory.start,
+            memory_end: memory.start + memory.size,
+            is_boot_memory: memory.startup,
+        })
+        .collect();
+
+    // Merge memory regions with the same attributes.
+    if device_memories.len() > 1 {
+        // Sort by memory type, then by processor name, then by boot memory, then by start address.
+        device_memories.sort();
+
+        let mut merged: Vec<DeviceMemory> = Vec::new();
+        let mut cur = device_memories.first().cloned().unwrap();
+        for region in device_memories.iter().skip(1) {
+            if region.is_boot_memory == cur.is_boot_memory && region.memory_start == cur.memory_end
+            {
+                // Merge with previous region.
+                cur.memory_end = region.memory_end;
+                cur.name = format!("{} + {}", cur.name, region.name);
+            } else {
+                merged.push(cur);
+                cur = region.clone();
+            }
+        }
+        merged.push(cur);
+        device_memories = merged;
+    }
+
+    // Finally, sort so that the LARGEST contiguous region is first for each core.
+    device_memories.sort_by_cached_key(|region| {
+        (
+            region.memory_type.clone(),
+            region.p_name.clone(),
+            (region.memory_start as i128 - region.memory_end as i128),
+        )
+    });
+
+    // Convert DeviceMemory's to MemoryRegion's, and assign cores to shared reqions.
+    let mut mem_map = vec![];
+    for region in &device_memories {
+        let current_core = region
+            .p_name
+            .as_ref()
+            .map(|s| s.to_ascii_lowercase())
+            .unwrap_or_else(|| "main".to_string());
+        match region.memory_type {
+            MemoryType::Ram => if let Some(MemoryRegion::Ram(existing_region)) = mem_map.iter_mut().find(|existing_region|{
+                matches!(existing_region, MemoryRegion::Ram(ram_region) if ram_region.name == Some(region.name.clone()))})
+                {
+                    existing_region.cores.push(current_core);
+                } else {
+                    mem_map.push(MemoryRegion::Ram(RamRegion {
+                    name: Some(region.name.clone()),
+                    range: region.memory_start..region.memory_end,
+                    is_boot_memory: region.is_boot_memory,
+                    cores: vec![current_core],
+                    access: None,
+                    }));
+                },
+            MemoryType::Nvm => if let Some(MemoryRegion::Nvm(existing_region)) = mem_map.iter_mut().find(|existing_region|{
+                matches!(existing_region, MemoryRegion::Nvm(nvm_region) if nvm_region.name == Some(region.name.clone()))})
+                {
+                    existing_region.cores.push(current_core);
+                } else {
+                    mem_map.push(MemoryRegion::Nvm(NvmRegion {
+                    name: Some(region.name.clone()),
+                    range: region.memory_start..region.memory_end,
+                    is_boot_memory: region.is_boot_memory,
+                    cores: vec![current_core],
+                    access: None,
+                    min_write_size: None,
+                    no_overwrite: false,
+                    }));
+                },
+            MemoryType::Generic => if let Some(MemoryRegion::Generic(existing_region)) = mem_map.iter_mut().find(|existing_region|{
+                matches!(existing_region, MemoryRegion::Generic(generic_region) if generic_region.name == Some(region.name.clone()))})
+                {
+                    existing_region.cores.push(current_core);
+                } else {
+                    mem_map.push(MemoryRegion::Generic(GenericRegion {
+                    name: Some(region.name.clone()),
+                    range: region.memory_start..region.memory_end,
+                    cores: vec![current_core],
+                    access: None,
+                    }));
+                },
+        };
+    }
+    mem_map
+}
+
+#[cfg(test)]
+mod test {
+    use cmsis_pack::{pdsc::Package, utils::FromElem};
+    use probe_rs_target::{SequenceBlock, SequenceControl, SequenceElement};
+
+    use super::{handle_package, read_sequences, Kind};
+
+    const PDSC: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<package schemaVersion="1.7.7" xmlns:xs="http://www.w3.org/2001/XMLSchema-instance">
+  <vendor>Vendor</vendor>
+  <name>DeviceFamilyPack</name>
+  <description>Test pack</description>
+  <url>http://www.example.com/</url>
+  <releases>
+    <release version="1.0.0">Initial release</release>
+  </releases>
+  <devices>
+    <family Dfamily="Unknown Series" Dvendor="Vendor:0">
+      <processor Dcore="Cortex-M4" Dfpu="SP_FPU" Dmpu="MPU" Dendian="Little-endian"/>
+      <device Dname="UNKNOWN1">
+        <memory id="IRAM1" start="0x20000000" size="0x10000" init="0" default="1"/>
+      </device>
+    </family>
+    <family Dfamily="nRF52 Series" Dvendor="Vendor:0">
+      <processor Dcore="Cortex-M4" Dfpu="SP_FPU" Dmpu="MPU" Dendian="Little-endian"/>
+      <debugvars configfile="Device.dbgconf">
+        __var Ctrl = 0x1;
+      </debugvars>
+      <sequences>
+        <sequence name="ResetSystem">
+          <block>Write32(0xE000ED0C, 0x05FA0004);</block>
+        </sequence>
+        <sequence name="DebugDeviceUnlock">
+          <block atomic="1">__var Status = Read32(0x40000000);</block>
+          <control if="Status == 0" while="Read32(0x40000004) &amp; Ctrl" timeout="1000">
+            <block><![CDATA[Message(0, "Waiting < %x", Status);]]></block>
+          </control>
+        </sequence>
+      </sequences>
+      <device Dname="TEST1">
+        <memory id="IRAM1" start="0x20000000" size="0x10000" init="0" default="1"/>
+      </device>
+      <device Dname="TEST2">
+        <memory id="IRAM1" start="0x20000000" size="0x10000" init="0" default="1"/>
+        <sequences>
+          <sequence name="ResetSystem" disable="1"/>
+          <sequence name="TraceStart" Pname="other"/>
+        </sequences>
+      </device>
+    </family>
+  </devices>
+</package>
+"#;
+
+    #[test]
+    fn sequences_are_read_from_the_pack() {
+        let package = Package::from_string(PDSC).unwrap();
+        let sequences = read_sequences("test.pdsc", PDSC).unwrap();
+
+        let mut families = vec![];
+        handle_package::<std::fs::File>(
+            package,
+            &sequences,
+            Kind::Directory(std::path::Path::new(".")),
+            &mut families,
+            false,
+        )
+        .unwrap();
+
+        let family = families
+            .iter()
+            .find(|family| family.name == "nRF52 Series")
+            .unwrap();
+        let chip = |name| family.variants.iter().find(|c| c.name == name).unwrap();
+        let debug_vars = SequenceElement::Block(SequenceBlock {
+            atomic: false,
+            code: "\n        __var Ctrl = 0x1;\n      ".to_string(),
+        });
+
+        let test1 = chip("TEST1");
+        assert_eq!(test1.sequences.len(), 2);
+        assert_eq!(test1.sequences[0].name, "ResetSystem");
+        assert!(!test1.sequences[0].disable);
+        assert_eq!(
+            test1.sequences[1].body,
+            [
+                debug_vars.clone(),
+                SequenceElement::Block(SequenceBlock {
+                    atomic: true,
+                    code: "__var Status = Read32(0x40000000);".to_string(),
+                }),
+                SequenceElement::Control(SequenceControl {
+                    condition: Some("Status == 0".to_string()),
+                    while_condition: Some("Read32(0x40000004) & Ctrl".to_string()),
+                    timeout: 1000,
+                    body: vec![SequenceElement::Block(SequenceBlock {
+                        atomic: false,
+                        code: r#"Message(0, "Waiting < %x", Status);"#.to_string(),
+                    })],
+                }),
+            ]
+        );
+
+        // The device disables the inherited sequence, and the sequence for another processor
+        // is not used.
+        let test2 = chip("TEST2");
+        assert_eq!(test2.sequences.len(), 2);
+        assert_eq!(test2.sequences[0].name, "DebugDeviceUnlock");
+        assert_eq!(test2.sequences[1].name, "ResetSystem");
+        assert!(test2.sequences[1].disable);
+        assert_eq!(test2.sequences[1].body, [debug_vars]);
+    }
+
+    #[test]
+    fn unsupported_families_are_skipped() {
+        let package = Package::from_string(PDSC).unwrap();
+        let sequences = read_sequences("test.pdsc", PDSC).unwrap();
+
+        let mut families = vec![];
+        handle_package::<std::fs::File>(
+            package,
+            &sequences,
+            Kind::Directory(std::path::Path::new(".")),
+            &mut families,
+            true,
+        )
+        .unwrap();
+
+        // The devices after the unsupported family are still read.
+        assert_eq!(families.len(), 1);
+        assert_eq!(families[0].name, "nRF52 Series");
+        assert_eq!(families[0].variants.len(), 2);
+    }
+
+    #[test]
+    fn invalid_pdsc() {
+        assert!(read_sequences("test.pdsc", "<package><devices></package>").is_err());
+    }
+}
@@ -0,0 +1,188 @@
+use object::read::elf::{ElfFile, ElfFile32, ElfFile64, FileHeader, ProgramHeader};
+use object::{Endianness, FileKind, Object, ObjectSymbol};
+use probe_rs_target::{FlashProperties, RawFlashAlgorithm, SectorDescription};
+
+use super::{algorithm_binary::AlgorithmBinary, flash_device::FlashDevice, PackError};
+
+/// Extract a chunk of data from an ELF binary.
+///
+/// This does only return the data chunk if it is fully contained in one section.
+/// If it is across two sections, no chunk will be returned.
+pub(crate) fn read_elf_bin_data<'a, Elf: FileHeader<Endian = Endianness>>(
+    elf: &ElfFile<'a, Elf>,
+    address: u32,
+    size: u32,
+) -> Option<&'a [u8]> {
+    let endian = elf.endian();
+
+    // Iterate all segments.
+    for ph in elf.raw_segments() {
+        let p_memsz: u64 = ph.p_memsz(endian).into();
+        let p_filesz: u64 = ph.p_filesz(endian).into();
+        let p_paddr: u64 = ph.p_paddr(endian).into();
+        let p_offset: u64 = ph.p_offset(endian).into();
+
+        let segment_address = p_paddr as u32;
+        let segment_size = p_memsz.min(p_filesz) as u32;
+
+        tracing::debug!("Segment address: {:#010x}", segment_address);
+        tracing::debug!("Segment size:    {} bytes", segment_size);
+
+        // If the requested data is above the current segment, skip the segment.
+        if address > segment_address + segment_size {
+            continue;
+        }
+
+        // If the requested data is below the current segment, skip the segment.
+        if address + size <= segment_address {
+            continue;
+        }
+
+        // If the requested data chunk is fully contained in the segment, extract and return the data segment.
+        if address >= segment_address && address + size <= segment_address + segment_size {
+            let start = p_offset as u32 + address - segment_address;
+            return elf.data().get(start as usize..)?.get(..size as usize);
+        }
+    }
+
+    None
+}
+
+fn extract_flash_device<Elf: FileHeader<Endian = Endianness>>(
+    elf: &ElfFile<'_, Elf>,
+) -> Result<FlashDevice, PackError> {
+    // Extract the flash device info.
+    for sym in elf.symbols() {
+        if let Ok("FlashDevice") = sym.name() {
+            // This struct contains information about the FLM file structure.
+            let address = sym.address() as u32;
+            return FlashDevice::new(elf, address);
+        }
+    }
+
+    // Failed to find flash device
+    Err(PackError::MissingSymbol("FlashDevice"))
+}
+
+/// Extracts a position & memory independent flash algorithm blob from the provided ELF file.
+///
+/// The ELF file has to follow the format of the flash algorithms (`.FLM` files)
+/// which are contained in CMSIS-Packs.
+pub fn extract_flash_algo(
+    mut file: impl std::io::Read,
+    file_name: &std::path::Path,
+    default: bool,
+    fixed_load_address: bool,
+) -> Result<RawFlashAlgorithm, PackError> {
+    let mut buffer = vec![];
+    file.read_to_end(&mut buffer)?;
+
+    match FileKind::parse(buffer.as_slice())? {
+        FileKind::Elf32 => extract_flash_algo_from_elf(
+            &ElfFile32::parse(buffer.as_slice())?,
+            file_name,
+            default,
+            fixed_load_address,
+        ),
+        FileKind::Elf64 => extract_flash_algo_from_elf(
+            &ElfFile64::parse(buffer.as_slice())?,
+            file_name,
+            default,
+            fixed_load_address,
+        ),
+        _ => Err(PackError::InvalidFlashAlgorithm(format!(
+            "'{}' is not an ELF file.",
+            file_name.display()
+        ))),
+    }
+}
+
+fn extract_flash_algo_from_elf<Elf: FileHeader<Endian = Endianness>>(
+    elf: &ElfFile<'_, Elf>,
+    file_name: &std::path::Path,
+    default: bool,
+    fixed_load_address: bool,
+) -> Result<RawFlashAlgorithm, PackError> {
+    let mut algo = RawFlashAlgorithm::default();
+
+    let flash_device = extract_flash_device(elf)?;
+
+    // Extract binary blob.
+    let algorithm_binary = AlgorithmBinary::new(elf)?;
+    algo.instructions = algorithm_binary.blob();
+
+    let code_section_offset = algorithm_binary.code_section.start as u64;
+
+    // Extract the function pointers,
+    // and check if a RTT symbol is present.
+    for sym in elf.symbols() {
+        let address = sym.address();
+
+        match sym.name() {
+            Ok("Init") => algo.pc_init = Some(address - code_section_offset),
+            Ok("UnInit") => algo.pc_uninit = Some(address - code_section_offset),
+            Ok("EraseChip") => algo.pc_erase_all = Some(address - code_section_offset),
+            Ok("EraseSector") => algo.pc_erase_sector = address - code_section_offset,
+            Ok("ProgramPage") => algo.pc_program_page = address - code_section_offset,
+            Ok("Verify") => algo.pc_verify = Some(address - code_section_offset),
+            Ok("_SEGGER_RTT") => {
+                algo.rtt_location = Some(address);
+                tracing::debug!("Found RTT control block at address {:#010x}", address);
+            }
+
+            _ => {}
+        }
+    }
+
+    if fixed_load_address {
+        tracing::debug!(
+            "Flash algorithm will be loaded at fixed address {:#010x}",
+            algorithm_binary.code_section.load_address
+        );
+
+        if !algorithm_binary.is_continuous_in_ram() {
+            return Err(PackError::InvalidFlashAlgorithm(
+                "If the flash algorithm is not position independent, all sections have to follow each other in RAM. \
+                Please check your linkerscript.".to_owned(),
+            ));
+        }
+
+        algo.load_address = Some(algorithm_binary.code_section.load_address as u64);
+    }
+
+    algo.description = flash_device.name;
+
+    algo.name = file_name
+        .file_stem()
+        .and_then(|f| f.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    algo.default = default;
+    algo.data_section_offset = algorithm_binary.data_section.start as u64;
+
+    let sectors = flash_device
+        .sectors
+        .iter()
+        .map(|si| SectorDescription {
+            address: si.address.into(),
+            size: si.size.into(),
+        })
+        .collect();
+
+    let properties = FlashProperties {
+        address_range: flash_device.start_address as u64
+            ..(flash_device.start_address as u64 + flash_device.device_size as u64),
+
+        page_size: flash_device.page_size,
+        erased_byte_value: flash_device.erased_default_value,
+
+        program_page_timeout: flash_device.program_page_timeout,
+        erase_sector_timeout: flash_device.erase_sector_timeout,
+
+        sectors,
+    };
+
+    algo.flash_properties = properties;
+
+    Ok(algo)
+}
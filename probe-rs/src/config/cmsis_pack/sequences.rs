@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+
+use cmsis_pack::pdsc::Device;
+use probe_rs_target::{SequenceBlock, SequenceControl, SequenceDescription, SequenceElement};
+use roxmltree::Node;
+
+use super::PackError;
+
+/// The debug sequences of the devices in a `.pdsc` file.
+///
+/// The `<sequences>` and `<debugvars>` of a family, sub family or device are inherited by all
+/// devices and variants below it. A sequence replaces an inherited sequence with the same name.
+#[derive(Debug, Default)]
+pub struct DeviceSequences {
+    devices: HashMap<String, DeviceDescription>,
+}
+
+impl DeviceSequences {
+    /// Returns the debug sequences of `device`.
+    ///
+    /// Sequences which are restricted to a processor are only used if it is the first processor
+    /// of the device, as the sequences of a target are used for all its cores.
+    pub fn for_device(&self, device: &Device) -> Vec<SequenceDescription> {
+        let Some(description) = self.devices.get(&device.name) else {
+            return vec![];
+        };
+
+        let processor = device.processors.first().and_then(|p| p.name.as_deref());
+        let applies = |restriction: &Option<String>| {
+            restriction.is_none() || restriction.as_deref() == processor
+        };
+
+        // The debug variables are global in the debug description. They are declared at the
+        // start of every sequence instead, as they are usually only read by the sequences.
+        let debug_vars = description
+            .debug_vars
+            .iter()
+            .filter(|(restriction, _)| applies(restriction))
+            .map(|(_, code)| code.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        description
+            .sequences
+            .iter()
+            .filter(|(restriction, _)| applies(restriction))
+            .map(|(_, sequence)| {
+                let mut sequence = sequence.clone();
+                if !debug_vars.trim().is_empty() {
+                    sequence.body.insert(
+                        0,
+                        SequenceElement::Block(SequenceBlock {
+                            atomic: false,
+                            code: debug_vars.clone(),
+                        }),
+                    );
+                }
+                sequence
+            })
+            .collect()
+    }
+}
+
+/// The sequences and debug variables of a device, with the processor they are restricted to.
+#[derive(Debug, Default, Clone)]
+struct DeviceDescription {
+    sequences: Vec<(Option<String>, SequenceDescription)>,
+    debug_vars: Vec<(Option<String>, String)>,
+}
+
+/// Read the debug sequences of all devices from the content of a `.pdsc` file.
+///
+/// `name` is the name of the `.pdsc` file, which is used in errors.
+pub fn read_sequences(name: &str, pdsc: &str) -> Result<DeviceSequences, PackError> {
+    let document = roxmltree::Document::parse(pdsc).map_err(|e| PackError::InvalidPdsc {
+        name: name.to_owned(),
+        message: e.to_string(),
+    })?;
+
+    let mut sequences = DeviceSequences::default();
+
+    for devices in document
+        .root_element()
+        .children()
+        .filter(|node| node.has_tag_name("devices"))
+    {
+        for family in devices
+            .children()
+            .filter(|node| node.has_tag_name("family"))
+        {
+            collect_sequences(family, DeviceDescription::default(), &mut sequences);
+        }
+    }
+
+    Ok(sequences)
+}
+
+/// Adds the sequences of `node` to the `inherited` ones, and records them for the devices at or
+/// below `node`.
+fn collect_sequences(
+    node: Node,
+    mut inherited: DeviceDescription,
+    sequences: &mut DeviceSequences,
+) {
+    for child in node.children().filter(Node::is_element) {
+        match child.tag_name().name() {
+            "sequences" => {
+                for sequence in child
+                    .children()
+                    .filter(|node| node.has_tag_name("sequence"))
+                {
+                    let Some(name) = sequence.attribute("name") else {
+                        tracing::warn!("Skipping a debug sequence without a name");
+                        continue;
+                    };
+
+                    let restriction = sequence.attribute("Pname").map(str::to_owned);
+                    let description = SequenceDescription {
+                        name: name.to_owned(),
+                        disable: parse_bool(sequence.attribute("disable")),
+                        body: parse_elements(sequence),
+                    };
+
+                    inherited
+                        .sequences
+                        .retain(|(r, s)| !(s.name == description.name && *r == restriction));
+                    inherited.sequences.push((restriction, description));
+                }
+            }
+            "debugvars" => {
+                let restriction = child.attribute("Pname").map(str::to_owned);
+                inherited.debug_vars.push((restriction, text(child)));
+            }
+            _ => {}
+        }
+    }
+
+    let name = match node.tag_name().name() {
+        "device" => node.attribute("Dname"),
+        "variant" => node.attribute("Dvariant"),
+        _ => None,
+    };
+    if let Some(name) = name {
+        sequences.devices.insert(name.to_owned(), inherited.clone());
+    }
+
+    for child in node.children().filter(|child| {
+        child.has_tag_name("subFamily")
+            || child.has_tag_name("device")
+            || child.has_tag_name("variant")
+    }) {
+        collect_sequences(child, inherited.clone(), sequences);
+    }
+}
+
+/// Parses the `<block>` and `<control>` elements of a sequence or control element.
+fn parse_elements(node: Node) -> Vec<SequenceElement> {
+    node.children()
+        .filter_map(|child| match child.tag_name().name() {
+            "block" => Some(SequenceElement::Block(SequenceBlock {
+                atomic: parse_bool(child.attribute("atomic")),
+                code: text(child),
+            })),
+            "control" => Some(SequenceElement::Control(SequenceControl {
+                condition: child.attribute("if").map(str::to_owned),
+                while_condition: child.attribute("while").map(str::to_owned),
+                timeout: child
+                    .attribute("timeout")
+                    .and_then(|timeout| timeout.trim().parse().ok())
+                    .unwrap_or(0),
+                body: parse_elements(child),
+            })),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The text content of an element, including CDATA sections.
+fn text(node: Node) -> String {
+    node.children()
+        .filter(Node::is_text)
+        .filter_map(|child| child.text())
+        .collect()
+}
+
+/// Parses an optional boolean attribute, which is false if it is missing.
+fn parse_bool(value: Option<&str>) -> bool {
+    matches!(value.map(str::trim), Some("1" | "true"))
+}
@@ -16,26 +16,65 @@
 //!
 //! The built-in targets can be disabled by not including the `builtin-targets` feature.
 //!
+//! ## Querying the targets
+//!
+//! The targets in the registry can be listed with [family_summaries], and a single
+//! chip can be looked up with [chip_summary]. The summaries contain the memory map,
+//! the cores, the flash algorithms and the supported protocols of the chips, and can be
+//! serialized, e.g. to present them in a GUI.
+//!
 //! ## Adding targets at runtime
 //!
 //! To add a target at runtime, the [add_target_from_yaml] function can
 //! be used to read targets from a YAML file.
 //!
+//! With the `cmsis-pack` feature, targets can also be read directly from a
+//! CMSIS-Pack, using [cmsis_pack::add_targets_from_pack].
+//!
+//! ## Boards
+//!
+//! Development boards pair an on-board debug probe with a target chip. A known board
+//! can be opened by name, which selects both the probe and the target:
+//!
+//! ```no_run
+//! use probe_rs::{config::get_board_by_name, Permissions};
+//!
+//! let session = get_board_by_name("nucleo-h743zi")?.open(Permissions::default())?;
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+//!
+//! Additional boards can be added with [add_boards_from_yaml].
+//!
+
+#[cfg(feature = "cmsis-pack")]
+pub mod cmsis_pack;
 
+mod board;
 mod chip_info;
 mod registry;
+mod summary;
 mod target;
+mod validation;
 
 pub use probe_rs_target::{
-    Chip, ChipFamily, Core, CoreType, FlashProperties, GenericRegion, InstructionSet, MemoryRange,
-    MemoryRegion, NvmRegion, PageInfo, RamRegion, RawFlashAlgorithm, SectorDescription, SectorInfo,
-    TargetDescriptionSource,
+    Chip, ChipFamily, Core, CoreType, FlashProperties, GenericRegion, InstructionSet, MemoryAccess,
+    MemoryRange, MemoryRegion, NvmRegion, PageInfo, RamRegion, RawFlashAlgorithm, ResetType,
+    SectorDescription, SectorInfo, TargetDescriptionSource,
 };
 
+pub use board::{
+    add_boards_from_yaml, boards, boards_for_probe, get_board_by_name, Board, BoardProbe,
+};
 pub use registry::{
-    add_target_from_yaml, families, get_target_by_name, search_chips, RegistryError,
+    add_target_family, add_target_from_yaml, chip_summary, families, family_summaries,
+    get_target_by_name, search_chips, RegistryError,
+};
+pub use summary::{ChipSummary, CoreSummary, FamilySummary, FlashAlgorithmSummary};
+pub use target::{
+    register_debug_sequence, unregister_debug_sequence, DebugSequence, Target, TargetParseError,
+    TargetSelector,
 };
-pub use target::{DebugSequence, Target, TargetParseError, TargetSelector};
+pub use validation::{validate_family, validate_target, Diagnostic, DiagnosticKind, Severity};
 
 // Crate-internal API
 pub(crate) use chip_info::ChipInfo;
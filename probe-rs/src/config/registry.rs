@@ -1,9 +1,10 @@
 //! Internal target registry
 
+use super::summary::{ChipSummary, FamilySummary};
 use super::{Chip, ChipFamily, ChipInfo, Core, Target, TargetDescriptionSource};
 use crate::config::CoreType;
 use once_cell::sync::Lazy;
-use probe_rs_target::{CoreAccessOptions, RiscvCoreAccessOptions};
+use probe_rs_target::{CoreAccessOptions, ResetType, RiscvCoreAccessOptions};
 use std::io::Read;
 use std::sync::{Arc, Mutex};
 
@@ -24,6 +25,10 @@ pub enum RegistryError {
     /// no matching chip was found in the registry.
     #[error("The connected chip could not automatically be determined.")]
     ChipAutodetectFailed,
+    /// The chip information read from the target matches multiple chips.
+    /// Contains the names of the matching chips.
+    #[error("The connected chip could not be determined uniquely, it matches: {}", .0.join(", "))]
+    ChipAutodetectAmbiguous(Vec<String>),
     /// A core type contained in a target description is not supported
     /// in probe-rs.
     #[error("The core type '{0}' is not supported in probe-rs.")]
@@ -34,9 +39,15 @@ pub enum RegistryError {
     /// An error occurred while deserializing a YAML target description file.
     #[error("Deserializing the yaml encountered an error")]
     Yaml(#[from] serde_yaml::Error),
+    /// The debug sequence selected by a target description is neither built-in nor registered.
+    #[error("The debug sequence '{0}' is not known.")]
+    UnknownDebugSequence(String),
     /// An invalid [`ChipFamily`] was encountered.
     #[error("Invalid chip family definition ({})", .0.name)]
     InvalidChipFamilyDefinition(Box<ChipFamily>, String),
+    /// The requested board was not found in the list of known boards.
+    #[error("The requested board '{0}' was not found in the list of known boards.")]
+    BoardNotFound(String),
 }
 
 fn add_generic_targets(vec: &mut Vec<ChipFamily>) {
@@ -53,6 +64,7 @@ fn add_generic_targets(vec: &mut Vec<ChipFamily>) {
             ],
 
             flash_algorithms: vec![],
+            debug_sequence: None,
             source: TargetDescriptionSource::Generic,
         },
         ChipFamily {
@@ -62,6 +74,7 @@ fn add_generic_targets(vec: &mut Vec<ChipFamily>) {
             pack_file_release: None,
             variants: vec![Chip::generic_arm("Cortex-M3", CoreType::Armv7m)],
             flash_algorithms: vec![],
+            debug_sequence: None,
             source: TargetDescriptionSource::Generic,
         },
         ChipFamily {
@@ -74,6 +87,7 @@ fn add_generic_targets(vec: &mut Vec<ChipFamily>) {
                 Chip::generic_arm("Cortex-M7", CoreType::Armv7em),
             ],
             flash_algorithms: vec![],
+            debug_sequence: None,
             source: TargetDescriptionSource::Generic,
         },
         ChipFamily {
@@ -88,6 +102,7 @@ fn add_generic_targets(vec: &mut Vec<ChipFamily>) {
                 Chip::generic_arm("Cortex-M55", CoreType::Armv8m),
             ],
             flash_algorithms: vec![],
+            debug_sequence: None,
             source: TargetDescriptionSource::Generic,
         },
         ChipFamily {
@@ -101,12 +116,17 @@ fn add_generic_targets(vec: &mut Vec<ChipFamily>) {
                 cores: vec![Core {
                     name: "core".to_owned(),
                     core_type: CoreType::Riscv,
-                    core_access_options: CoreAccessOptions::Riscv(RiscvCoreAccessOptions {}),
+                    core_access_options: CoreAccessOptions::Riscv(RiscvCoreAccessOptions::default()),
+                    memory_aliases: vec![],
                 }],
                 memory_map: vec![],
                 flash_algorithms: vec![],
+                sequences: vec![],
+                bank_swap: None,
+                default_reset_type: ResetType::default(),
             }],
             flash_algorithms: vec![],
+            debug_sequence: None,
             source: TargetDescriptionSource::Generic,
         },
     ]);
@@ -154,60 +174,60 @@ impl Registry {
     }
 
     fn get_target_by_name(&self, name: impl AsRef<str>) -> Result<Target, RegistryError> {
-        let name = name.as_ref();
+        let (family, chip) = self.get_chip_by_name(name.as_ref())?;
 
+        self.get_target(family, chip)
+    }
+
+    fn get_chip_by_name(&self, name: &str) -> Result<(&ChipFamily, &Chip), RegistryError> {
         tracing::debug!("Searching registry for chip with name {}", name);
 
-        let (family, chip) = {
-            // Try get the corresponding chip.
-            let mut selected_family_and_chip = None;
-            let mut exact_matches = 0;
-            let mut partial_matches = 0;
-            for family in &self.families {
-                for variant in family.variants.iter() {
-                    if match_name_prefix(&variant.name, name) {
-                        if variant.name.len() == name.len() {
-                            tracing::debug!("Exact match for chip name: {}", variant.name);
-                            exact_matches += 1;
-                        } else {
-                            tracing::debug!("Partial match for chip name: {}", variant.name);
-                            partial_matches += 1;
-                            if exact_matches > 0 {
-                                continue;
-                            }
+        // Try get the corresponding chip.
+        let mut selected_family_and_chip = None;
+        let mut exact_matches = 0;
+        let mut partial_matches = 0;
+        for family in &self.families {
+            for variant in family.variants.iter() {
+                if match_name_prefix(&variant.name, name) {
+                    if variant.name.len() == name.len() {
+                        tracing::debug!("Exact match for chip name: {}", variant.name);
+                        exact_matches += 1;
+                    } else {
+                        tracing::debug!("Partial match for chip name: {}", variant.name);
+                        partial_matches += 1;
+                        if exact_matches > 0 {
+                            continue;
                         }
-                        selected_family_and_chip = Some((family, variant));
                     }
+                    selected_family_and_chip = Some((family, variant));
                 }
             }
-            if exact_matches > 1 || (exact_matches == 0 && partial_matches > 1) {
-                tracing::warn!(
-                    "Ignoring ambiguous matches for specified chip name {}",
-                    name,
-                );
-                return Err(RegistryError::ChipNotUnique(name.to_owned()));
-            }
-            let (family, chip) = selected_family_and_chip
-                .ok_or_else(|| RegistryError::ChipNotFound(name.to_owned()))?;
-            if exact_matches == 0 && partial_matches == 1 {
-                tracing::warn!(
-                    "Found chip {} which matches given partial name {}. Consider specifying its full name.",
-                    chip.name,
-                    name,
-                );
-            }
-            if chip.name.to_ascii_lowercase() != name.to_ascii_lowercase() {
-                tracing::warn!(
-                    "Matching {} based on wildcard. Consider specifying the chip as {} instead.",
-                    name,
-                    chip.name,
-                );
-            }
+        }
+        if exact_matches > 1 || (exact_matches == 0 && partial_matches > 1) {
+            tracing::warn!(
+                "Ignoring ambiguous matches for specified chip name {}",
+                name,
+            );
+            return Err(RegistryError::ChipNotUnique(name.to_owned()));
+        }
+        let (family, chip) =
+            selected_family_and_chip.ok_or_else(|| RegistryError::ChipNotFound(name.to_owned()))?;
+        if exact_matches == 0 && partial_matches == 1 {
+            tracing::warn!(
+                "Found chip {} which matches given partial name {}. Consider specifying its full name.",
+                chip.name,
+                name,
+            );
+        }
+        if chip.name.to_ascii_lowercase() != name.to_ascii_lowercase() {
+            tracing::warn!(
+                "Matching {} based on wildcard. Consider specifying the chip as {} instead.",
+                name,
+                chip.name,
+            );
+        }
 
-            // Try get the correspnding flash algorithm.
-            (family, chip)
-        };
-        self.get_target(family, chip)
+        Ok((family, chip))
     }
 
     fn search_chips(&self, name: &str) -> Vec<String> {
@@ -230,46 +250,98 @@ impl Registry {
         targets
     }
 
-    fn get_target_by_chip_info(&self, chip_info: ChipInfo) -> Result<Target, RegistryError> {
-        let (family, chip) = {
-            match chip_info {
-                ChipInfo::Arm(chip_info) => {
-                    // Try get the corresponding chip.
+    /// Find the target matching the given chip infos.
+    ///
+    /// The chip infos are tried in order, the first one matching a single chip is used.
+    fn get_target_by_chip_info(&self, chip_infos: &[ChipInfo]) -> Result<Target, RegistryError> {
+        let mut candidates = Vec::new();
 
-                    let families = self.families.iter().filter(|f| {
-                        f.manufacturer
-                            .map(|m| m == chip_info.manufacturer)
-                            .unwrap_or(false)
-                    });
+        for chip_info in chip_infos {
+            let mut identified_chips = self.chips_by_chip_info(chip_info);
 
-                    let mut identified_chips = Vec::new();
+            if identified_chips.len() == 1 {
+                let (family, chip) = identified_chips.pop().unwrap();
+                return self.get_target(family, chip);
+            }
 
-                    for family in families {
-                        tracing::debug!("Checking family {}", family.name);
+            tracing::debug!(
+                "Found {} matching chips for information {:?}",
+                identified_chips.len(),
+                chip_info
+            );
 
-                        let chips = family
-                            .variants()
-                            .iter()
-                            .filter(|v| v.part.map(|p| p == chip_info.part).unwrap_or(false))
-                            .map(|c| (family, c));
+            if candidates.is_empty() {
+                candidates = identified_chips;
+            }
+        }
 
-                        identified_chips.extend(chips)
-                    }
+        if candidates.is_empty() {
+            Err(RegistryError::ChipAutodetectFailed)
+        } else {
+            Err(RegistryError::ChipAutodetectAmbiguous(
+                candidates
+                    .into_iter()
+                    .map(|(_, chip)| chip.name.clone())
+                    .collect(),
+            ))
+        }
+    }
 
-                    if identified_chips.len() == 1 {
-                        identified_chips.pop().unwrap()
-                    } else {
-                        tracing::debug!(
-                        "Found {} matching chips for information {:?}, unable to determine chip",
-                        identified_chips.len(),
-                        chip_info
-                    );
-                        return Err(RegistryError::ChipAutodetectFailed);
-                    }
+    fn chips_by_chip_info(&self, chip_info: &ChipInfo) -> Vec<(&ChipFamily, &Chip)> {
+        match chip_info {
+            ChipInfo::Arm(chip_info) => self
+                .families
+                .iter()
+                .filter(|f| {
+                    f.manufacturer
+                        .map(|m| m == chip_info.manufacturer)
+                        .unwrap_or(false)
+                })
+                .flat_map(|family| {
+                    tracing::debug!("Checking family {}", family.name);
+
+                    family
+                        .variants()
+                        .iter()
+                        .filter(|v| v.part.map(|p| p == chip_info.part).unwrap_or(false))
+                        .map(move |c| (family, c))
+                })
+                .collect(),
+            ChipInfo::NamePrefixes(prefixes) => {
+                let chips = self
+                    .families
+                    .iter()
+                    .flat_map(|family| family.variants().iter().map(move |c| (family, c)));
+
+                let matches = |exact: bool| {
+                    chips
+                        .clone()
+                        .filter(|(_, chip)| {
+                            let name = chip.name.to_ascii_lowercase();
+
+                            prefixes.iter().any(|prefix| {
+                                let prefix = prefix.to_ascii_lowercase();
+
+                                if exact {
+                                    name == prefix
+                                } else {
+                                    name.starts_with(&prefix)
+                                }
+                            })
+                        })
+                        .collect::<Vec<_>>()
+                };
+
+                // A prefix can be the complete name of a chip, e.g. `esp32`, which
+                // should not match all other chips starting with that name.
+                let exact_matches = matches(true);
+                if exact_matches.is_empty() {
+                    matches(false)
+                } else {
+                    exact_matches
                 }
             }
-        };
-        self.get_target(family, chip)
+        }
     }
 
     fn get_target(&self, family: &ChipFamily, chip: &Chip) -> Result<Target, RegistryError> {
@@ -283,6 +355,10 @@ impl Registry {
     {
         let family: ChipFamily = serde_yaml::from_reader(yaml_reader)?;
 
+        self.add_target_family(family)
+    }
+
+    fn add_target_family(&mut self, family: ChipFamily) -> Result<(), RegistryError> {
         family
             .validate()
             .map_err(|e| RegistryError::InvalidChipFamilyDefinition(Box::new(family.clone()), e))?;
@@ -311,8 +387,8 @@ pub fn search_chips(name: impl AsRef<str>) -> Result<Vec<String>, RegistryError>
 }
 
 /// Try to retrieve a target based on [ChipInfo] read from a target.
-pub(crate) fn get_target_by_chip_info(chip_info: ChipInfo) -> Result<Target, RegistryError> {
-    REGISTRY.lock().unwrap().get_target_by_chip_info(chip_info)
+pub(crate) fn get_target_by_chip_info(chip_infos: &[ChipInfo]) -> Result<Target, RegistryError> {
+    REGISTRY.lock().unwrap().get_target_by_chip_info(chip_infos)
 }
 
 /// Parse a target description and add the contained targets
@@ -345,12 +421,52 @@ where
     REGISTRY.lock().unwrap().add_target_from_yaml(yaml_reader)
 }
 
+/// Add the targets of a [`ChipFamily`] to the internal target registry.
+///
+/// A family with the same name which is already contained in the registry
+/// is replaced.
+pub fn add_target_family(family: ChipFamily) -> Result<(), RegistryError> {
+    REGISTRY.lock().unwrap().add_target_family(family)
+}
+
 /// Get a list of all families which are contained in the internal
 /// registry.
 pub fn families() -> Result<Vec<ChipFamily>, RegistryError> {
     Ok(REGISTRY.lock().unwrap().families().clone())
 }
 
+/// Get a summary of all families which are contained in the internal
+/// registry.
+///
+/// # Examples
+///
+/// ```
+/// for family in probe_rs::config::family_summaries() {
+///     for chip in family.variants {
+///         println!("{}: {} ({:?})", family.name, chip.name, chip.protocols);
+///     }
+/// }
+/// ```
+pub fn family_summaries() -> Vec<FamilySummary> {
+    REGISTRY
+        .lock()
+        .unwrap()
+        .families()
+        .iter()
+        .map(FamilySummary::new)
+        .collect()
+}
+
+/// Get a summary of a chip from the internal registry based on its name.
+///
+/// The name is matched like in [`get_target_by_name`].
+pub fn chip_summary(name: impl AsRef<str>) -> Result<ChipSummary, RegistryError> {
+    let registry = REGISTRY.lock().unwrap();
+    let (family, chip) = registry.get_chip_by_name(name.as_ref())?;
+
+    Ok(ChipSummary::new(family, chip))
+}
+
 /// See if `name` matches the start of `pattern`, treating any lower-case `x`
 /// character in `pattern` as a wildcard that matches any character in `name`.
 ///
@@ -369,6 +485,7 @@ fn match_name_prefix(pattern: &str, name: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::WireProtocol;
 
     #[test]
     fn try_fetch_not_unique() {
@@ -380,6 +497,53 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn fetch_by_chip_info() {
+        let registry = Registry::from_builtin_families();
+
+        let nrf = ChipInfo::NamePrefixes(vec!["nRF52840".to_owned()]);
+        assert_eq!(
+            registry.get_target_by_chip_info(&[nrf]).unwrap().name,
+            "nRF52840_xxAA"
+        );
+
+        // The ESP32 and the ESP32-S3 can't be distinguished, but the other ESP32 variants
+        // are not candidates.
+        let esp = ChipInfo::from_jtag_idcode(0x1200_34e5).unwrap();
+        let Err(RegistryError::ChipAutodetectAmbiguous(mut chips)) =
+            registry.get_target_by_chip_info(&[esp])
+        else {
+            panic!("The ESP32 should not be identified uniquely");
+        };
+        chips.sort();
+        assert_eq!(chips, ["esp32", "esp32s3"]);
+
+        let unknown = ChipInfo::NamePrefixes(vec!["not_a_real_chip".to_owned()]);
+        assert!(matches!(
+            registry.get_target_by_chip_info(&[unknown]),
+            Err(RegistryError::ChipAutodetectFailed)
+        ));
+    }
+
+    #[test]
+    fn summary_of_chip() {
+        let registry = Registry::from_builtin_families();
+        let (family, chip) = registry.get_chip_by_name("nRF52840_xxAA").unwrap();
+
+        let summary = ChipSummary::new(family, chip);
+
+        assert_eq!(summary.family, "nRF52 Series");
+        assert_eq!(summary.cores[0].core_type, CoreType::Armv7em);
+        assert_eq!(summary.protocols, [WireProtocol::Swd, WireProtocol::Jtag]);
+        assert_eq!(summary.flash_algorithms[0].name, "nrf52");
+
+        let (family, chip) = registry.get_chip_by_name("esp32c3").unwrap();
+        assert_eq!(
+            ChipSummary::new(family, chip).protocols,
+            [WireProtocol::Jtag]
+        );
+    }
+
     #[test]
     fn try_fetch_not_found() {
         let registry = Registry::from_builtin_families();
@@ -0,0 +1,156 @@
+//! Structured summaries of the targets in the registry
+//!
+//! The summaries contain the information about a target which is relevant to
+//! select it, e.g. in a chip picker, without the data required to actually
+//! debug or flash it, like the flash algorithm instructions.
+
+use std::ops::Range;
+
+use probe_rs_target::{
+    Architecture, Chip, ChipFamily, CoreType, MemoryRegion, RawFlashAlgorithm, SectorDescription,
+    TargetDescriptionSource,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::WireProtocol;
+
+/// Summary of a chip family in the target registry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FamilySummary {
+    /// The name of the family.
+    pub name: String,
+    /// The name of the manufacturer of the family, if known.
+    pub manufacturer: Option<String>,
+    /// The version of the CMSIS-Pack the family was generated from, if any.
+    pub pack_file_release: Option<String>,
+    /// Where the target description of the family comes from.
+    pub source: TargetDescriptionSource,
+    /// The chips of the family.
+    pub variants: Vec<ChipSummary>,
+}
+
+impl FamilySummary {
+    pub(crate) fn new(family: &ChipFamily) -> Self {
+        Self {
+            name: family.name.clone(),
+            manufacturer: family
+                .manufacturer
+                .and_then(|code| code.get())
+                .map(str::to_owned),
+            pack_file_release: family.pack_file_release.clone(),
+            source: family.source.clone(),
+            variants: family
+                .variants
+                .iter()
+                .map(|chip| ChipSummary::new(family, chip))
+                .collect(),
+        }
+    }
+}
+
+/// Summary of a single chip in the target registry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChipSummary {
+    /// The name of the chip.
+    pub name: String,
+    /// The name of the family the chip belongs to.
+    pub family: String,
+    /// The cores of the chip.
+    pub cores: Vec<CoreSummary>,
+    /// The memory regions of the chip.
+    pub memory_map: Vec<MemoryRegion>,
+    /// The flash algorithms which can be used with the chip.
+    pub flash_algorithms: Vec<FlashAlgorithmSummary>,
+    /// The protocols which can be used to debug all cores of the chip.
+    pub protocols: Vec<WireProtocol>,
+}
+
+impl ChipSummary {
+    pub(crate) fn new(family: &ChipFamily, chip: &Chip) -> Self {
+        let protocols = [WireProtocol::Swd, WireProtocol::Jtag]
+            .into_iter()
+            .filter(|protocol| {
+                chip.cores
+                    .iter()
+                    .all(|core| supported_protocols(core.core_type).contains(protocol))
+            })
+            .collect();
+
+        Self {
+            name: chip.name.clone(),
+            family: family.name.clone(),
+            cores: chip
+                .cores
+                .iter()
+                .map(|core| CoreSummary {
+                    name: core.name.clone(),
+                    core_type: core.core_type,
+                    architecture: core.core_type.architecture(),
+                })
+                .collect(),
+            memory_map: chip.memory_map.clone(),
+            flash_algorithms: chip
+                .flash_algorithms
+                .iter()
+                .filter_map(|name| family.get_algorithm(name))
+                .map(FlashAlgorithmSummary::new)
+                .collect(),
+            protocols,
+        }
+    }
+}
+
+/// Summary of a core of a chip.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoreSummary {
+    /// The name of the core.
+    pub name: String,
+    /// The type of the core.
+    pub core_type: CoreType,
+    /// The architecture of the core.
+    pub architecture: Architecture,
+}
+
+/// Summary of a flash algorithm, without the algorithm itself.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlashAlgorithmSummary {
+    /// The name of the flash algorithm.
+    pub name: String,
+    /// The description of the flash algorithm.
+    pub description: String,
+    /// Whether this is the default algorithm for its memory range.
+    pub default: bool,
+    /// The address range of the flash, which can be programmed with the algorithm.
+    pub address_range: Range<u64>,
+    /// The page size of the flash, in bytes.
+    pub page_size: u32,
+    /// The value of a byte in erased flash.
+    pub erased_byte_value: u8,
+    /// The sectors of the flash.
+    pub sectors: Vec<SectorDescription>,
+    /// The cores which can use the algorithm. If empty, all cores can use it.
+    pub cores: Vec<String>,
+}
+
+impl FlashAlgorithmSummary {
+    fn new(algorithm: &RawFlashAlgorithm) -> Self {
+        Self {
+            name: algorithm.name.clone(),
+            description: algorithm.description.clone(),
+            default: algorithm.default,
+            address_range: algorithm.flash_properties.address_range.clone(),
+            page_size: algorithm.flash_properties.page_size,
+            erased_byte_value: algorithm.flash_properties.erased_byte_value,
+            sectors: algorithm.flash_properties.sectors.clone(),
+            cores: algorithm.cores.clone(),
+        }
+    }
+}
+
+/// The protocols which can be used to debug a core of the given type.
+fn supported_protocols(core_type: CoreType) -> &'static [WireProtocol] {
+    match core_type.architecture() {
+        Architecture::Arm => &[WireProtocol::Swd, WireProtocol::Jtag],
+        Architecture::Riscv | Architecture::Xtensa => &[WireProtocol::Jtag],
+    }
+}
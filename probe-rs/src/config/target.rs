@@ -1,10 +1,12 @@
-use probe_rs_target::{Architecture, ChipFamily};
+use probe_rs_target::{Architecture, BankSwap, ChipFamily, ResetType};
 
 use super::{Core, MemoryRegion, RawFlashAlgorithm, RegistryError, TargetDescriptionSource};
 use crate::architecture::arm::sequences::{
     atsame5x::AtSAME5x,
+    cmsis_pack::CmsisPackSequence,
     efm32xg2::EFM32xG2,
     infineon::XMC4000,
+    kinetis::Kinetis,
     nrf52::Nrf52,
     nrf53::Nrf5340,
     nrf91::Nrf9160,
@@ -16,8 +18,13 @@ use crate::architecture::arm::sequences::{
 };
 use crate::architecture::riscv::sequences::{esp32c3::ESP32C3, esp32c6::ESP32C6};
 use crate::architecture::riscv::sequences::{DefaultRiscvSequence, RiscvDebugSequence};
+use crate::architecture::xtensa::sequences::{
+    esp32::ESP32, esp32s3::ESP32S3, DefaultXtensaSequence, XtensaDebugSequence,
+};
 use crate::flashing::FlashLoader;
-use std::sync::Arc;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
 use crate::architecture::arm::sequences::DefaultArmSequence;
 
@@ -36,6 +43,10 @@ pub struct Target {
     pub(crate) source: TargetDescriptionSource,
     /// Debug sequences for the given target.
     pub debug_sequence: DebugSequence,
+    /// The flash banks which can be swapped, if the target has a dual-bank flash.
+    pub bank_swap: Option<BankSwap>,
+    /// The method used to reset the target, unless a different one is requested.
+    pub default_reset_type: ResetType,
 }
 
 impl std::fmt::Debug for Target {
@@ -91,81 +102,40 @@ impl Target {
             flash_algorithms.push(algo.clone());
         }
 
-        // We always just take the architecture of the first core which is okay if there is no mixed architectures.
-        let mut debug_sequence = match chip.cores[0].core_type.architecture() {
-            Architecture::Arm => DebugSequence::Arm(DefaultArmSequence::create()),
-            Architecture::Riscv => DebugSequence::Riscv(DefaultRiscvSequence::create()),
+        let sequence_name = family
+            .debug_sequence
+            .as_deref()
+            .or_else(|| sequence_name_for_chip(&chip.name));
+
+        let debug_sequence = match sequence_name {
+            Some(name) => {
+                tracing::warn!("Using custom sequence {} for {}", name, chip.name);
+                debug_sequence_by_name(name)
+                    .ok_or_else(|| RegistryError::UnknownDebugSequence(name.to_string()))?
+            }
+            // We always just take the architecture of the first core which is okay if there is no mixed architectures.
+            None => match chip.cores[0].core_type.architecture() {
+                Architecture::Arm => DebugSequence::Arm(DefaultArmSequence::create()),
+                Architecture::Riscv => DebugSequence::Riscv(DefaultRiscvSequence::create()),
+                Architecture::Xtensa => DebugSequence::Xtensa(DefaultXtensaSequence::create()),
+            },
         };
 
-        if chip.name.starts_with("MIMXRT10") {
-            tracing::warn!("Using custom sequence for MIMXRT10xx");
-            debug_sequence = DebugSequence::Arm(MIMXRT10xx::create());
-        } else if chip.name.starts_with("MIMXRT11") {
-            tracing::warn!("Using custom sequence for MIMXRT11xx");
-            debug_sequence = DebugSequence::Arm(MIMXRT11xx::create());
-        } else if chip.name.starts_with("LPC55S16")
-            || chip.name.starts_with("LPC55S26")
-            || chip.name.starts_with("LPC55S28")
-            || chip.name.starts_with("LPC55S66")
-            || chip.name.starts_with("LPC55S69")
-        {
-            tracing::warn!("Using custom sequence for LPC55S16/26/28/66/69");
-            debug_sequence = DebugSequence::Arm(LPC55Sxx::create());
-        } else if chip.name.starts_with("EFM32PG2")
-            || chip.name.starts_with("EFR32BG2")
-            || chip.name.starts_with("EFR32FG2")
-            || chip.name.starts_with("EFR32MG2")
-            || chip.name.starts_with("EFR32ZG2")
-        {
-            tracing::warn!("Using custom sequence for EFM32 Series 2");
-            debug_sequence = DebugSequence::Arm(EFM32xG2::create());
-        } else if chip.name.starts_with("esp32c3") {
-            tracing::warn!("Using custom sequence for ESP32C3");
-            debug_sequence = DebugSequence::Riscv(ESP32C3::create());
-        } else if chip.name.starts_with("esp32c6") {
-            tracing::warn!("Using custom sequence for ESP32C6");
-            debug_sequence = DebugSequence::Riscv(ESP32C6::create());
-        } else if chip.name.starts_with("nRF5340") {
-            tracing::warn!("Using custom sequence for nRF5340");
-            debug_sequence = DebugSequence::Arm(Nrf5340::create());
-        } else if chip.name.starts_with("nRF52") {
-            tracing::warn!("Using custom sequence for nRF52");
-            debug_sequence = DebugSequence::Arm(Nrf52::create());
-        } else if chip.name.starts_with("nRF9160") {
-            tracing::warn!("Using custom sequence for nRF9160");
-            debug_sequence = DebugSequence::Arm(Nrf9160::create());
-        } else if chip.name.starts_with("STM32F0") {
-            tracing::warn!("Using custom sequence for ARMv6 {}", chip.name);
-            debug_sequence = DebugSequence::Arm(Stm32Armv6::create(Stm32Armv6Family::F0));
-        } else if chip.name.starts_with("STM32L0") {
-            tracing::warn!("Using custom sequence for ARMv6 {}", chip.name);
-            debug_sequence = DebugSequence::Arm(Stm32Armv6::create(Stm32Armv6Family::L0));
-        } else if chip.name.starts_with("STM32G0") {
-            tracing::warn!("Using custom sequence for ARMv6 {}", chip.name);
-            debug_sequence = DebugSequence::Arm(Stm32Armv6::create(Stm32Armv6Family::G0));
-        } else if chip.name.starts_with("STM32F1")
-            || chip.name.starts_with("STM32F2")
-            || chip.name.starts_with("STM32F3")
-            || chip.name.starts_with("STM32F4")
-            || chip.name.starts_with("STM32F7")
-            || chip.name.starts_with("STM32G4")
-            || chip.name.starts_with("STM32L1")
-            || chip.name.starts_with("STM32L4")
-            || chip.name.starts_with("STM32WB")
-            || chip.name.starts_with("STM32WL")
-        {
-            tracing::warn!("Using custom sequence for ARMv7 {}", chip.name);
-            debug_sequence = DebugSequence::Arm(Stm32Armv7::create());
-        } else if chip.name.starts_with("STM32H7") {
-            tracing::warn!("Using custom sequence for STM32H7");
-            debug_sequence = DebugSequence::Arm(Stm32h7::create());
-        } else if chip.name.starts_with("ATSAMD5") || chip.name.starts_with("ATSAME5") {
-            tracing::warn!("Using custom sequence for {}", chip.name);
-            debug_sequence = DebugSequence::Arm(AtSAME5x::create());
-        } else if chip.name.starts_with("XMC4") {
-            tracing::warn!("Using custom sequence for XMC4000");
-            debug_sequence = DebugSequence::Arm(XMC4000::create());
-        }
+        // Sequences from the target description replace the built-in sequences of the same name.
+        let debug_sequence = match debug_sequence {
+            DebugSequence::Arm(sequence) if !chip.sequences.is_empty() => {
+                DebugSequence::Arm(CmsisPackSequence::create(chip.sequences.clone(), sequence))
+            }
+            debug_sequence => {
+                if !chip.sequences.is_empty() {
+                    tracing::warn!(
+                        "Ignoring the debug sequences of {}, they are only supported for ARM targets",
+                        chip.name
+                    );
+                }
+                debug_sequence
+            }
+        };
 
         Ok(Target {
             name: chip.name.clone(),
@@ -174,6 +144,8 @@ impl Target {
             source: family.source.clone(),
             memory_map: chip.memory_map.clone(),
             debug_sequence,
+            bank_swap: chip.bank_swap.clone(),
+            default_reset_type: chip.default_reset_type,
         })
     }
 
@@ -210,10 +182,22 @@ impl Target {
     }
 
     /// Gets the core index from the core name
-    pub(crate) fn core_index_by_name(&self, name: &str) -> Option<usize> {
+    pub fn core_index_by_name(&self, name: &str) -> Option<usize> {
         self.cores.iter().position(|c| c.name == name)
     }
 
+    /// Returns the memory regions which can be accessed by the core with the given name.
+    ///
+    /// On devices with multiple cores, each core can have its own RAM and flash, and can
+    /// see shared memory at different addresses.
+    pub fn memory_map_for_core(&self, core_name: &str) -> Vec<MemoryRegion> {
+        self.memory_map
+            .iter()
+            .filter(|region| region.cores().iter().any(|name| name == core_name))
+            .cloned()
+            .collect()
+    }
+
     /// Gets the first found [MemoryRegion] that contains the given address
     pub(crate) fn get_memory_region_by_address(&self, address: u64) -> Option<&MemoryRegion> {
         self.memory_map.iter().find(|region| match region {
@@ -237,24 +221,30 @@ pub enum TargetSelector {
     /// Try to automatically identify the target,
     /// by reading identifying information from
     /// the probe and / or target.
+    ///
+    /// This is also selected by the target name `auto`.
     Auto,
 }
 
 impl From<&str> for TargetSelector {
     fn from(value: &str) -> Self {
-        TargetSelector::Unspecified(value.into())
+        value.to_owned().into()
     }
 }
 
 impl From<&String> for TargetSelector {
     fn from(value: &String) -> Self {
-        TargetSelector::Unspecified(value.into())
+        value.clone().into()
     }
 }
 
 impl From<String> for TargetSelector {
     fn from(value: String) -> Self {
-        TargetSelector::Unspecified(value)
+        if value.eq_ignore_ascii_case("auto") {
+            TargetSelector::Auto
+        } else {
+            TargetSelector::Unspecified(value)
+        }
     }
 }
 
@@ -271,13 +261,268 @@ impl From<Target> for TargetSelector {
 }
 
 /// This is the type to denote a general debug sequence.  
-/// It can differentiate between ARM and RISC-V for now.  
-/// Currently, only the ARM variant does something sensible;  
-/// RISC-V will be ignored when encountered.
+/// It can differentiate between ARM, RISC-V and Xtensa.  
+/// The RISC-V and Xtensa variants only provide an `on_connect` hook.
 #[derive(Clone)]
 pub enum DebugSequence {
     /// An ARM debug sequence.
     Arm(Arc<dyn ArmDebugSequence>),
     /// A RISC-V debug sequence.
     Riscv(Arc<dyn RiscvDebugSequence>),
+    /// An Xtensa debug sequence.
+    Xtensa(Arc<dyn XtensaDebugSequence>),
+}
+
+static DEBUG_SEQUENCES: Lazy<Mutex<HashMap<String, DebugSequence>>> = Lazy::new(Default::default);
+
+/// Registers a debug sequence, which target descriptions can select by `name`.
+///
+/// Chip families select a sequence with their `debug_sequence` field. This allows implementing
+/// the sequences for a target family outside of probe-rs. A registered sequence replaces a
+/// built-in sequence or a previously registered sequence with the same name.
+pub fn register_debug_sequence(name: impl Into<String>, sequence: DebugSequence) {
+    DEBUG_SEQUENCES
+        .lock()
+        .unwrap()
+        .insert(name.into(), sequence);
+}
+
+/// Removes the debug sequence registered with the given name, if any.
+pub fn unregister_debug_sequence(name: &str) {
+    DEBUG_SEQUENCES.lock().unwrap().remove(name);
+}
+
+/// Returns the registered or built-in debug sequence with the given name.
+fn debug_sequence_by_name(name: &str) -> Option<DebugSequence> {
+    if let Some(sequence) = DEBUG_SEQUENCES.lock().unwrap().get(name) {
+        return Some(sequence.clone());
+    }
+
+    let sequence = match name {
+        "mimxrt10xx" => DebugSequence::Arm(MIMXRT10xx::create()),
+        "mimxrt11xx" => DebugSequence::Arm(MIMXRT11xx::create()),
+        "lpc55sxx" => DebugSequence::Arm(LPC55Sxx::create()),
+        "efm32xg2" => DebugSequence::Arm(EFM32xG2::create()),
+        "esp32c3" => DebugSequence::Riscv(ESP32C3::create()),
+        "esp32c6" => DebugSequence::Riscv(ESP32C6::create()),
+        "esp32s3" => DebugSequence::Xtensa(ESP32S3::create()),
+        "esp32" => DebugSequence::Xtensa(ESP32::create()),
+        "nrf5340" => DebugSequence::Arm(Nrf5340::create()),
+        "nrf52" => DebugSequence::Arm(Nrf52::create()),
+        "nrf9160" => DebugSequence::Arm(Nrf9160::create()),
+        "stm32f0" => DebugSequence::Arm(Stm32Armv6::create(Stm32Armv6Family::F0)),
+        "stm32l0" => DebugSequence::Arm(Stm32Armv6::create(Stm32Armv6Family::L0)),
+        "stm32g0" => DebugSequence::Arm(Stm32Armv6::create(Stm32Armv6Family::G0)),
+        "stm32_armv7" => DebugSequence::Arm(Stm32Armv7::create()),
+        "stm32h7" => DebugSequence::Arm(Stm32h7::create()),
+        "atsame5x" => DebugSequence::Arm(AtSAME5x::create()),
+        "xmc4000" => DebugSequence::Arm(XMC4000::create()),
+        "kinetis" => DebugSequence::Arm(Kinetis::create()),
+        _ => return None,
+    };
+
+    Some(sequence)
+}
+
+/// Selects a built-in debug sequence based on the chip name, for target descriptions which don't
+/// specify a sequence.
+fn sequence_name_for_chip(chip_name: &str) -> Option<&'static str> {
+    let starts_with_any =
+        |prefixes: &[&str]| prefixes.iter().any(|prefix| chip_name.starts_with(prefix));
+
+    let name = if chip_name.starts_with("MIMXRT10") {
+        "mimxrt10xx"
+    } else if chip_name.starts_with("MIMXRT11") {
+        "mimxrt11xx"
+    } else if starts_with_any(&["LPC55S16", "LPC55S26", "LPC55S28", "LPC55S66", "LPC55S69"]) {
+        "lpc55sxx"
+    } else if starts_with_any(&["EFM32PG2", "EFR32BG2", "EFR32FG2", "EFR32MG2", "EFR32ZG2"]) {
+        "efm32xg2"
+    } else if chip_name.starts_with("esp32c3") {
+        "esp32c3"
+    } else if chip_name.starts_with("esp32c6") {
+        "esp32c6"
+    } else if chip_name.starts_with("esp32s3") {
+        "esp32s3"
+    } else if chip_name == "esp32" {
+        "esp32"
+    } else if chip_name.starts_with("nRF5340") {
+        "nrf5340"
+    } else if chip_name.starts_with("nRF52") {
+        "nrf52"
+    } else if chip_name.starts_with("nRF9160") {
+        "nrf9160"
+    } else if chip_name.starts_with("STM32F0") {
+        "stm32f0"
+    } else if chip_name.starts_with("STM32L0") {
+        "stm32l0"
+    } else if chip_name.starts_with("STM32G0") {
+        "stm32g0"
+    } else if starts_with_any(&[
+        "STM32F1", "STM32F2", "STM32F3", "STM32F4", "STM32F7", "STM32G4", "STM32L1", "STM32L4",
+        "STM32WB", "STM32WL",
+    ]) {
+        "stm32_armv7"
+    } else if chip_name.starts_with("STM32H7") {
+        "stm32h7"
+    } else if starts_with_any(&["ATSAMD5", "ATSAME5"]) {
+        "atsame5x"
+    } else if chip_name.starts_with("XMC4") {
+        "xmc4000"
+    } else if chip_name.starts_with("MK") {
+        "kinetis"
+    } else {
+        return None;
+    };
+
+    Some(name)
+}
+
+#[cfg(test)]
+mod test {
+    use probe_rs_target::{ChipFamily, ResetType};
+
+    use super::{
+        debug_sequence_by_name, register_debug_sequence, sequence_name_for_chip,
+        unregister_debug_sequence, DebugSequence, Target, TargetSelector,
+    };
+    use crate::architecture::riscv::sequences::DefaultRiscvSequence;
+    use crate::config::get_target_by_name;
+
+    #[test]
+    fn auto_target_selector() {
+        assert!(matches!(TargetSelector::from("auto"), TargetSelector::Auto));
+        assert!(matches!(TargetSelector::from("Auto"), TargetSelector::Auto));
+        assert!(matches!(
+            TargetSelector::from("nrf52840_xxAA"),
+            TargetSelector::Unspecified(name) if name == "nrf52840_xxAA"
+        ));
+    }
+
+    #[test]
+    fn default_reset_type() {
+        let family: ChipFamily = serde_yaml::from_str(
+            r#"
+name: Test
+variants:
+- name: TestChip
+  cores:
+  - name: main
+    type: armv7em
+    core_access_options: !Arm
+      ap: 0
+      psel: 0
+  memory_map: []
+  default_reset_type: Hardware
+- name: OtherChip
+  cores:
+  - name: main
+    type: armv7em
+    core_access_options: !Arm
+      ap: 0
+      psel: 0
+  memory_map: []
+flash_algorithms: []
+"#,
+        )
+        .unwrap();
+
+        let target = Target::new(&family, "TestChip").unwrap();
+        assert_eq!(target.default_reset_type, ResetType::Hardware);
+
+        let target = Target::new(&family, "OtherChip").unwrap();
+        assert_eq!(target.default_reset_type, ResetType::Sequence);
+    }
+
+    #[test]
+    fn memory_aliases_of_core() {
+        let family: ChipFamily = serde_yaml::from_str(
+            r#"
+name: Test
+variants:
+- name: TestChip
+  cores:
+  - name: main
+    type: armv7em
+    core_access_options: !Arm
+      ap: 0
+      psel: 0
+  - name: secondary
+    type: armv6m
+    core_access_options: !Arm
+      ap: 0
+      psel: 0
+    memory_aliases:
+    - range:
+        start: 0x0
+        end: 0x10000
+      target: 0x20040000
+  memory_map: []
+flash_algorithms: []
+"#,
+        )
+        .unwrap();
+
+        let target = Target::new(&family, "TestChip").unwrap();
+        assert!(target.cores[0].memory_aliases.is_empty());
+        assert_eq!(
+            target.cores[1].memory_aliases[0].translate(0x100),
+            Some(0x2004_0100)
+        );
+    }
+
+    #[test]
+    fn memory_map_of_each_core() {
+        let target = get_target_by_name("nRF5340_xxAA").unwrap();
+
+        assert_eq!(target.core_index_by_name("network"), Some(1));
+        assert_eq!(target.core_index_by_name("unknown"), None);
+
+        let ranges = |core| {
+            target
+                .memory_map_for_core(core)
+                .iter()
+                .map(|region| region.address_range())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            ranges("application"),
+            vec![0x2000_0000..0x2004_0000, 0x0..0x10_0000]
+        );
+        assert_eq!(
+            ranges("network"),
+            vec![0x2100_0000..0x2102_0000, 0x100_0000..0x104_0000]
+        );
+    }
+
+    #[test]
+    fn sequence_names() {
+        assert_eq!(sequence_name_for_chip("nRF52840_xxAA"), Some("nrf52"));
+        assert_eq!(sequence_name_for_chip("STM32L476RGTx"), Some("stm32_armv7"));
+        assert_eq!(sequence_name_for_chip("MK64FN1M0VLL12"), Some("kinetis"));
+        assert_eq!(sequence_name_for_chip("LPC55S69JBD100"), Some("lpc55sxx"));
+        assert_eq!(sequence_name_for_chip("RP2040"), None);
+
+        assert!(matches!(
+            debug_sequence_by_name("nrf5340"),
+            Some(DebugSequence::Arm(_))
+        ));
+        assert!(debug_sequence_by_name("unknown").is_none());
+    }
+
+    #[test]
+    fn registered_sequences() {
+        register_debug_sequence(
+            "test_sequence",
+            DebugSequence::Riscv(DefaultRiscvSequence::create()),
+        );
+        assert!(matches!(
+            debug_sequence_by_name("test_sequence"),
+            Some(DebugSequence::Riscv(_))
+        ));
+
+        unregister_debug_sequence("test_sequence");
+        assert!(debug_sequence_by_name("test_sequence").is_none());
+    }
 }
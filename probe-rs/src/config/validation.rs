@@ -0,0 +1,499 @@
+//! Validation of target descriptions
+//!
+//! [`ChipFamily::validate`] rejects target descriptions which can't be used at all, and stops
+//! at the first problem. The functions in this module check for more mistakes, which otherwise
+//! only show up as failures when using the target, and report all of them.
+
+use std::fmt;
+
+use probe_rs_target::{
+    Chip, ChipFamily, MemoryRange, MemoryRegion, RawFlashAlgorithm, SectorDescription,
+};
+
+/// How serious a problem in a target description is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum Severity {
+    /// The target description can be used, but probably doesn't work as intended.
+    Warning,
+    /// The target description can't be used, or is wrong.
+    Error,
+}
+
+/// A problem found in a target description.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    /// How serious the problem is.
+    pub severity: Severity,
+    /// The name of the chip variant with the problem, if it is specific to a variant.
+    pub variant: Option<String>,
+    /// The problem.
+    pub kind: DiagnosticKind,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+
+        match &self.variant {
+            Some(variant) => write!(f, "{severity} in variant `{variant}`: {}", self.kind),
+            None => write!(f, "{severity}: {}", self.kind),
+        }
+    }
+}
+
+/// The problems which are found in target descriptions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, thiserror::Error)]
+pub enum DiagnosticKind {
+    /// The YAML is not a valid target description.
+    #[error("invalid target description: {0}")]
+    InvalidYaml(String),
+    /// The target description is rejected by [`ChipFamily::validate`].
+    #[error("{0}")]
+    InvalidDefinition(String),
+    /// The family contains no chip variants.
+    #[error("the family does not contain any variants")]
+    NoVariants,
+    /// The variant contains no cores.
+    #[error("the variant does not contain any cores")]
+    NoCores,
+    /// A memory region refers to a core which doesn't exist.
+    #[error("`{region}` refers to the unknown core `{core}`")]
+    UnknownCore {
+        /// The name of the memory region.
+        region: String,
+        /// The name of the core.
+        core: String,
+    },
+    /// None of the cores, which can use a flash algorithm, exist in the variant.
+    #[error("the flash algorithm `{0}` can't be used by any core of the variant")]
+    AlgorithmWithoutCore(String),
+    /// The variant refers to a flash algorithm which doesn't exist.
+    #[error("unknown flash algorithm `{0}`")]
+    UnknownFlashAlgorithm(String),
+    /// A memory region ends before it starts.
+    #[error("the memory region `{0}` is empty")]
+    EmptyRegion(String),
+    /// Two memory regions, which are accessed by the same core, overlap.
+    #[error("the memory regions `{0}` and `{1}` overlap")]
+    OverlappingRegions(String, String),
+    /// The variant has flash algorithms, but no RAM to run them in.
+    #[error("the variant has flash algorithms, but no RAM region")]
+    NoRam,
+    /// The load address of a flash algorithm is not in a RAM region, or the algorithm
+    /// and a page of data don't fit into the region.
+    #[error("the flash algorithm `{name}` loaded at {load_address:#010x} doesn't fit into RAM")]
+    AlgorithmNotInRam {
+        /// The name of the flash algorithm.
+        name: String,
+        /// The load address of the flash algorithm.
+        load_address: u64,
+    },
+    /// The flash range of a flash algorithm is not covered by an NVM region.
+    #[error("the flash range of the algorithm `{0}` is not in a flash region of the memory map")]
+    AlgorithmNotInFlash(String),
+    /// The sectors of a flash algorithm are not consistent with its flash range.
+    #[error("the sectors of the flash algorithm `{name}` are inconsistent: {reason}")]
+    InconsistentSectors {
+        /// The name of the flash algorithm.
+        name: String,
+        /// What is wrong with the sectors.
+        reason: String,
+    },
+}
+
+/// Validate a target description in YAML format, e.g. a custom target file.
+///
+/// Returns all problems which were found, an empty list means that the target description is valid.
+pub fn validate_target(yaml: &str) -> Vec<Diagnostic> {
+    match serde_yaml::from_str::<ChipFamily>(yaml) {
+        Ok(family) => validate_family(&family),
+        Err(error) => vec![Diagnostic {
+            severity: Severity::Error,
+            variant: None,
+            kind: DiagnosticKind::InvalidYaml(error.to_string()),
+        }],
+    }
+}
+
+/// Validate a chip family, see [`validate_target`].
+pub fn validate_family(family: &ChipFamily) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    if family.variants.is_empty() {
+        diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            variant: None,
+            kind: DiagnosticKind::NoVariants,
+        });
+    }
+
+    for variant in &family.variants {
+        let mut report = |severity, kind| {
+            diagnostics.push(Diagnostic {
+                severity,
+                variant: Some(variant.name.clone()),
+                kind,
+            })
+        };
+
+        validate_variant(family, variant, &mut report);
+    }
+
+    // The remaining checks of the family only report the first problem, which is likely
+    // one of the problems found above.
+    if !diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Error)
+    {
+        if let Err(message) = family.validate() {
+            diagnostics.push(Diagnostic {
+                severity: Severity::Error,
+                variant: None,
+                kind: DiagnosticKind::InvalidDefinition(message),
+            });
+        }
+    }
+
+    diagnostics
+}
+
+fn validate_variant(
+    family: &ChipFamily,
+    variant: &Chip,
+    report: &mut dyn FnMut(Severity, DiagnosticKind),
+) {
+    if variant.cores.is_empty() {
+        report(Severity::Error, DiagnosticKind::NoCores);
+    }
+
+    for (index, region) in variant.memory_map.iter().enumerate() {
+        let name = region_name(region, index);
+        check_cores(variant, &name, region.cores(), report);
+
+        let range = region.address_range();
+        if range.start >= range.end {
+            report(Severity::Error, DiagnosticKind::EmptyRegion(name.clone()));
+        }
+
+        for (other_index, other) in variant.memory_map.iter().enumerate().skip(index + 1) {
+            // Regions of different cores can overlap, the cores see different memories.
+            let shared_core = region
+                .cores()
+                .iter()
+                .any(|core| other.cores().contains(core));
+
+            if shared_core && range.intersects_range(&other.address_range()) {
+                report(
+                    Severity::Error,
+                    DiagnosticKind::OverlappingRegions(
+                        name.clone(),
+                        region_name(other, other_index),
+                    ),
+                );
+            }
+        }
+    }
+
+    let algorithms = variant
+        .flash_algorithms
+        .iter()
+        .filter_map(|name| {
+            let algorithm = family.get_algorithm(name);
+            if algorithm.is_none() {
+                report(
+                    Severity::Error,
+                    DiagnosticKind::UnknownFlashAlgorithm(name.clone()),
+                );
+            }
+            algorithm
+        })
+        .collect::<Vec<_>>();
+
+    let ram_regions = variant
+        .memory_map
+        .iter()
+        .filter_map(|region| match region {
+            MemoryRegion::Ram(ram) => Some(ram),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    if !algorithms.is_empty() && ram_regions.is_empty() {
+        report(Severity::Error, DiagnosticKind::NoRam);
+    }
+
+    for algorithm in algorithms {
+        // Algorithms are shared between variants with different core names, so only
+        // one of the cores has to exist.
+        if !algorithm.cores.is_empty()
+            && !variant
+                .cores
+                .iter()
+                .any(|core| algorithm.cores.contains(&core.name))
+        {
+            report(
+                Severity::Error,
+                DiagnosticKind::AlgorithmWithoutCore(algorithm.name.clone()),
+            );
+        }
+
+        if let Some(load_address) = algorithm.load_address {
+            // The algorithm needs space for its code and at least one page of data.
+            let size =
+                algorithm.instructions.len() as u64 + algorithm.flash_properties.page_size as u64;
+
+            if !ram_regions.iter().any(|ram| {
+                ram.range
+                    .contains_range(&(load_address..load_address + size))
+            }) {
+                report(
+                    Severity::Error,
+                    DiagnosticKind::AlgorithmNotInRam {
+                        name: algorithm.name.clone(),
+                        load_address,
+                    },
+                );
+            }
+        }
+
+        let flash_range = &algorithm.flash_properties.address_range;
+        if !variant.memory_map.iter().any(|region| match region {
+            MemoryRegion::Nvm(nvm) => nvm.range.contains_range(flash_range),
+            _ => false,
+        }) {
+            report(
+                Severity::Warning,
+                DiagnosticKind::AlgorithmNotInFlash(algorithm.name.clone()),
+            );
+        }
+
+        if let Err((severity, reason)) = check_sectors(algorithm) {
+            report(
+                severity,
+                DiagnosticKind::InconsistentSectors {
+                    name: algorithm.name.clone(),
+                    reason,
+                },
+            );
+        }
+    }
+}
+
+/// Checks that the cores which access a memory region exist.
+fn check_cores(
+    variant: &Chip,
+    region: &str,
+    cores: &[String],
+    report: &mut dyn FnMut(Severity, DiagnosticKind),
+) {
+    for core in cores {
+        if !variant.cores.iter().any(|c| &c.name == core) {
+            report(
+                Severity::Error,
+                DiagnosticKind::UnknownCore {
+                    region: region.to_owned(),
+                    core: core.clone(),
+                },
+            );
+        }
+    }
+}
+
+/// Checks that the sectors of a flash algorithm cover its flash range.
+fn check_sectors(algorithm: &RawFlashAlgorithm) -> Result<(), (Severity, String)> {
+    let properties = &algorithm.flash_properties;
+    let sectors: &[SectorDescription] = &properties.sectors;
+
+    let (Some(first), Some(last)) = (sectors.first(), sectors.last()) else {
+        return Err((Severity::Error, "no sectors are defined".to_owned()));
+    };
+
+    if first.address != 0 {
+        return Err((
+            Severity::Error,
+            format!(
+                "the first sector starts at offset {:#x} instead of 0",
+                first.address
+            ),
+        ));
+    }
+
+    for sector in sectors {
+        if sector.size == 0 {
+            return Err((Severity::Error, "a sector has a size of 0".to_owned()));
+        }
+    }
+
+    for pair in sectors.windows(2) {
+        if pair[1].address <= pair[0].address {
+            return Err((
+                Severity::Error,
+                "the sectors are not sorted by address".to_owned(),
+            ));
+        }
+        if (pair[1].address - pair[0].address) % pair[0].size != 0 {
+            return Err((
+                Severity::Error,
+                format!(
+                    "the sectors at offset {:#x} don't end at offset {:#x}",
+                    pair[0].address, pair[1].address
+                ),
+            ));
+        }
+    }
+
+    let flash_size = properties
+        .address_range
+        .end
+        .saturating_sub(properties.address_range.start);
+    if flash_size <= last.address || (flash_size - last.address) % last.size != 0 {
+        return Err((
+            Severity::Warning,
+            format!(
+                "the sectors don't end at the end of the flash range at offset {flash_size:#x}"
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+fn region_name(region: &MemoryRegion, index: usize) -> String {
+    let name = match region {
+        MemoryRegion::Ram(ram) => ram.name.as_deref(),
+        MemoryRegion::Nvm(nvm) => nvm.name.as_deref(),
+        MemoryRegion::Generic(generic) => generic.name.as_deref(),
+    };
+
+    match name {
+        Some(name) => name.to_owned(),
+        None => format!("#{index}"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::families;
+
+    const TARGET: &str = r#"
+name: Test
+variants:
+- name: TestChip
+  cores:
+  - name: main
+    type: armv7em
+    core_access_options: !Arm
+      ap: 0
+      psel: 0
+  memory_map:
+  - !Nvm
+    name: Flash
+    range:
+      start: 0x0
+      end: 0x100000
+    cores: [main]
+  - !Ram
+    name: SRAM
+    range:
+      start: 0x20000000
+      end: 0x20010000
+    cores: [main]
+  - !Ram
+    name: Alias
+    range:
+      start: 0x2000F000
+      end: 0x20011000
+    cores: [main, other]
+  flash_algorithms: [algo, missing]
+flash_algorithms:
+- name: algo
+  description: Test algorithm
+  instructions: AAAAAA==
+  load_address: 0x30000000
+  pc_init: null
+  pc_uninit: null
+  pc_program_page: 0x1
+  pc_erase_sector: 0x2
+  pc_erase_all: null
+  data_section_offset: 0x4
+  rtt_location: null
+  flash_properties:
+    address_range:
+      start: 0x0
+      end: 0x100000
+    page_size: 0x400
+    erased_byte_value: 0xff
+    program_page_timeout: 100
+    erase_sector_timeout: 1000
+    sectors:
+    - size: 0x1000
+      address: 0x0
+    - size: 0x3000
+      address: 0x2000
+  stack_size: null
+"#;
+
+    #[test]
+    fn report_all_problems() {
+        let diagnostics = validate_target(TARGET);
+        let kinds = diagnostics
+            .iter()
+            .map(|diagnostic| &diagnostic.kind)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            kinds,
+            [
+                &DiagnosticKind::OverlappingRegions("SRAM".to_owned(), "Alias".to_owned()),
+                &DiagnosticKind::UnknownCore {
+                    region: "Alias".to_owned(),
+                    core: "other".to_owned()
+                },
+                &DiagnosticKind::UnknownFlashAlgorithm("missing".to_owned()),
+                &DiagnosticKind::AlgorithmNotInRam {
+                    name: "algo".to_owned(),
+                    load_address: 0x3000_0000
+                },
+                &DiagnosticKind::InconsistentSectors {
+                    name: "algo".to_owned(),
+                    reason:
+                        "the sectors don't end at the end of the flash range at offset 0x100000"
+                            .to_owned()
+                },
+            ]
+        );
+        assert!(diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.variant.as_deref() == Some("TestChip")));
+        assert_eq!(diagnostics[4].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn invalid_yaml() {
+        assert!(matches!(
+            validate_target("name: Test")[..],
+            [Diagnostic {
+                severity: Severity::Error,
+                kind: DiagnosticKind::InvalidYaml(_),
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn builtin_targets_are_valid() {
+        let errors = families()
+            .unwrap()
+            .iter()
+            .flat_map(validate_family)
+            .filter(|diagnostic| diagnostic.severity == Severity::Error)
+            .map(|diagnostic| diagnostic.to_string())
+            .collect::<Vec<_>>();
+
+        assert!(errors.is_empty(), "{errors:#?}");
+    }
+}
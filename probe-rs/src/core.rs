@@ -1,15 +1,21 @@
-use crate::{error, CoreType, Error, InstructionSet, MemoryInterface};
+use crate::{
+    config::RamRegion, error, CoreType, Error, InstructionSet, MemoryInterface, ResetType,
+};
 use anyhow::{anyhow, Result};
 pub use probe_rs_target::{Architecture, CoreAccessOptions};
-use std::time::Duration;
+use std::{collections::BTreeMap, ops::Range, time::Duration};
 
+pub mod core_events;
 pub mod core_state;
 pub mod core_status;
+pub mod dump;
 pub mod memory_mapped_registers;
 pub mod registers;
 
+pub use core_events::{CoreEvent, CoreWatcher};
 pub use core_state::*;
 pub use core_status::*;
+pub use dump::{CoreDump, CoreDumpError, OfflineCore};
 pub use memory_mapped_registers::MemoryMappedRegister;
 pub use registers::*;
 
@@ -20,6 +26,42 @@ pub struct CoreInformation {
     pub pc: u64,
 }
 
+/// The number of cycles the core needed to execute a piece of code, measured with
+/// [`Core::measure_cycles`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CycleMeasurement {
+    /// The elapsed cycles. The cycle counter is 32 bits wide, so only measurements
+    /// of less than 2^32 cycles are correct.
+    pub cycles: u32,
+    /// The clock frequency of the core in Hz, if it is known.
+    pub clock_frequency: Option<u32>,
+}
+
+impl CycleMeasurement {
+    /// The wall-clock time of the measured cycles, estimated from the clock frequency.
+    ///
+    /// Returns `None` if the clock frequency of the core is not known.
+    pub fn duration(&self) -> Option<Duration> {
+        match self.clock_frequency {
+            Some(frequency) if frequency > 0 => Some(Duration::from_nanos(
+                self.cycles as u64 * 1_000_000_000 / frequency as u64,
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// The kind of memory access which triggers a hardware watchpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchpointKind {
+    /// The watchpoint triggers when the watched memory is read.
+    Read,
+    /// The watchpoint triggers when the watched memory is written.
+    Write,
+    /// The watchpoint triggers on any access to the watched memory.
+    ReadWrite,
+}
+
 /// A generic interface to control a MCU core.
 pub trait CoreInterface: MemoryInterface {
     /// Wait until the core is halted. If the core does not halt on its own,
@@ -40,17 +82,21 @@ pub trait CoreInterface: MemoryInterface {
     /// Continue to execute instructions.
     fn run(&mut self) -> Result<(), error::Error>;
 
-    /// Reset the core, and then continue to execute instructions. If the core
-    /// should be halted after reset, use the [`reset_and_halt`] function.
+    /// Reset the core with the given `reset_type`, and then continue to execute instructions.
+    /// If the core should be halted after reset, use the [`reset_and_halt`] function.
     ///
     /// [`reset_and_halt`]: Core::reset_and_halt
-    fn reset(&mut self) -> Result<(), error::Error>;
+    fn reset(&mut self, reset_type: ResetType) -> Result<(), error::Error>;
 
-    /// Reset the core, and then immediately halt. To continue execution after
-    /// reset, use the [`reset`] function.
+    /// Reset the core with the given `reset_type`, and then immediately halt.
+    /// To continue execution after reset, use the [`reset`] function.
     ///
     /// [`reset`]: Core::reset
-    fn reset_and_halt(&mut self, timeout: Duration) -> Result<CoreInformation, error::Error>;
+    fn reset_and_halt(
+        &mut self,
+        reset_type: ResetType,
+        timeout: Duration,
+    ) -> Result<CoreInformation, error::Error>;
 
     /// Steps one instruction and then enters halted state again.
     fn step(&mut self) -> Result<CoreInformation, error::Error>;
@@ -61,6 +107,18 @@ pub trait CoreInterface: MemoryInterface {
         address: registers::RegisterId,
     ) -> Result<registers::RegisterValue, error::Error>;
 
+    /// Read the value of a core register, without halting the core.
+    ///
+    /// Cores which can only access their registers while halted only support a subset of
+    /// the registers while running, or none at all. The default implementation
+    /// is the same as [`CoreInterface::read_core_reg`].
+    fn read_core_reg_nonstop(
+        &mut self,
+        address: registers::RegisterId,
+    ) -> Result<registers::RegisterValue, error::Error> {
+        self.read_core_reg(address)
+    }
+
     /// Write the value of a core register.
     fn write_core_reg(
         &mut self,
@@ -85,6 +143,89 @@ pub trait CoreInterface: MemoryInterface {
     /// Clears the breakpoint configured in unit `unit_index`.
     fn clear_hw_breakpoint(&mut self, unit_index: usize) -> Result<(), error::Error>;
 
+    /// Returns the number of available hardware watchpoint units of the core.
+    fn available_watchpoint_units(&mut self) -> Result<u32, error::Error> {
+        Ok(0)
+    }
+
+    /// Read the addresses watched by the hardware watchpoint units.
+    ///
+    /// Like [`CoreInterface::hw_breakpoints`], this returns an entry for every unit,
+    /// which is `None` if the unit is not in use.
+    fn hw_watchpoints(&mut self) -> Result<Vec<Option<u64>>, error::Error> {
+        Ok(Vec::new())
+    }
+
+    /// Sets a watchpoint on the `size` bytes at `address`, using unit `unit_index`.
+    fn set_hw_watchpoint(
+        &mut self,
+        _unit_index: usize,
+        _address: u64,
+        _size: u32,
+        _kind: WatchpointKind,
+    ) -> Result<(), error::Error> {
+        Err(error::Error::Other(anyhow!(
+            "Hardware watchpoints are not supported on this core"
+        )))
+    }
+
+    /// Clears the watchpoint configured in unit `unit_index`.
+    fn clear_hw_watchpoint(&mut self, _unit_index: usize) -> Result<(), error::Error> {
+        Err(error::Error::Other(anyhow!(
+            "Hardware watchpoints are not supported on this core"
+        )))
+    }
+
+    /// Reads and decodes the fault status registers of the core.
+    fn fault_status(&mut self) -> Result<FaultStatus, error::Error> {
+        Err(error::Error::Other(anyhow!(
+            "Fault status reporting is not supported on this core"
+        )))
+    }
+
+    /// Reads the tightly coupled memories (TCMs) of the core, which are enabled.
+    ///
+    /// This must be queried while halted. The `cores` of the returned regions are empty.
+    /// Cores without TCMs return an empty list.
+    fn tcm_regions(&mut self) -> Result<Vec<RamRegion>, error::Error> {
+        Ok(vec![])
+    }
+
+    /// Returns `true` if the core was reset since the last call, and clears the reset flag.
+    ///
+    /// Cores which can't detect resets always return `false`.
+    fn reset_detected(&mut self) -> Result<bool, error::Error> {
+        Ok(false)
+    }
+
+    /// Enables the cycle counter of the core. Only supported on ARMv7-M and ARMv8-M cores.
+    fn enable_cycle_counter(&mut self) -> Result<(), error::Error> {
+        Err(error::Error::Other(anyhow!(
+            "A cycle counter is not supported on this core"
+        )))
+    }
+
+    /// Reads the cycle counter of the core. Only supported on ARMv7-M and ARMv8-M cores.
+    fn read_cycle_counter(&mut self) -> Result<u32, error::Error> {
+        Err(error::Error::Other(anyhow!(
+            "A cycle counter is not supported on this core"
+        )))
+    }
+
+    /// Reads a control and status register (CSR). Only supported on RISC-V cores.
+    fn read_csr(&mut self, _address: u16) -> Result<u32, error::Error> {
+        Err(error::Error::Other(anyhow!(
+            "CSR access is only supported on RISC-V cores"
+        )))
+    }
+
+    /// Writes a control and status register (CSR). Only supported on RISC-V cores.
+    fn write_csr(&mut self, _address: u16, _value: u32) -> Result<(), error::Error> {
+        Err(error::Error::Other(anyhow!(
+            "CSR access is only supported on RISC-V cores"
+        )))
+    }
+
     /// Returns a list of all the registers of this core.
     fn registers(&self) -> &'static registers::RegisterFile;
 
@@ -125,59 +266,118 @@ impl<'probe> MemoryInterface for Core<'probe> {
     }
 
     fn read_word_64(&mut self, address: u64) -> Result<u64, Error> {
-        self.inner.read_word_64(address)
+        self.state.check_memory_access(address, 8, false)?;
+        self.inner
+            .read_word_64(self.state.translate_address(address))
     }
 
     fn read_word_32(&mut self, address: u64) -> Result<u32, Error> {
-        self.inner.read_word_32(address)
+        self.state.check_memory_access(address, 4, false)?;
+        self.inner
+            .read_word_32(self.state.translate_address(address))
+    }
+
+    fn read_word_16(&mut self, address: u64) -> Result<u16, Error> {
+        self.state.check_memory_access(address, 2, false)?;
+        self.inner
+            .read_word_16(self.state.translate_address(address))
     }
 
     fn read_word_8(&mut self, address: u64) -> Result<u8, Error> {
-        self.inner.read_word_8(address)
+        self.state.check_memory_access(address, 1, false)?;
+        self.inner
+            .read_word_8(self.state.translate_address(address))
     }
 
     fn read_64(&mut self, address: u64, data: &mut [u64]) -> Result<(), Error> {
-        self.inner.read_64(address, data)
+        self.state
+            .check_memory_access(address, data.len() as u64 * 8, false)?;
+        self.inner
+            .read_64(self.state.translate_address(address), data)
     }
 
     fn read_32(&mut self, address: u64, data: &mut [u32]) -> Result<(), Error> {
-        self.inner.read_32(address, data)
+        self.state
+            .check_memory_access(address, data.len() as u64 * 4, false)?;
+        self.inner
+            .read_32(self.state.translate_address(address), data)
+    }
+
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), Error> {
+        self.state
+            .check_memory_access(address, data.len() as u64 * 2, false)?;
+        self.inner
+            .read_16(self.state.translate_address(address), data)
     }
 
     fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error> {
-        self.inner.read_8(address, data)
+        self.state
+            .check_memory_access(address, data.len() as u64, false)?;
+        self.inner
+            .read_8(self.state.translate_address(address), data)
     }
 
     fn read(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error> {
-        self.inner.read(address, data)
+        self.state
+            .check_memory_access(address, data.len() as u64, false)?;
+        self.inner.read(self.state.translate_address(address), data)
     }
 
     fn write_word_64(&mut self, addr: u64, data: u64) -> Result<(), Error> {
-        self.inner.write_word_64(addr, data)
+        self.state.check_memory_access(addr, 8, true)?;
+        self.inner
+            .write_word_64(self.state.translate_address(addr), data)
     }
 
     fn write_word_32(&mut self, addr: u64, data: u32) -> Result<(), Error> {
-        self.inner.write_word_32(addr, data)
+        self.state.check_memory_access(addr, 4, true)?;
+        self.inner
+            .write_word_32(self.state.translate_address(addr), data)
+    }
+
+    fn write_word_16(&mut self, addr: u64, data: u16) -> Result<(), Error> {
+        self.state.check_memory_access(addr, 2, true)?;
+        self.inner
+            .write_word_16(self.state.translate_address(addr), data)
     }
 
     fn write_word_8(&mut self, addr: u64, data: u8) -> Result<(), Error> {
-        self.inner.write_word_8(addr, data)
+        self.state.check_memory_access(addr, 1, true)?;
+        self.inner
+            .write_word_8(self.state.translate_address(addr), data)
     }
 
     fn write_64(&mut self, addr: u64, data: &[u64]) -> Result<(), Error> {
-        self.inner.write_64(addr, data)
+        self.state
+            .check_memory_access(addr, data.len() as u64 * 8, true)?;
+        self.inner
+            .write_64(self.state.translate_address(addr), data)
     }
 
     fn write_32(&mut self, addr: u64, data: &[u32]) -> Result<(), Error> {
-        self.inner.write_32(addr, data)
+        self.state
+            .check_memory_access(addr, data.len() as u64 * 4, true)?;
+        self.inner
+            .write_32(self.state.translate_address(addr), data)
+    }
+
+    fn write_16(&mut self, addr: u64, data: &[u16]) -> Result<(), Error> {
+        self.state
+            .check_memory_access(addr, data.len() as u64 * 2, true)?;
+        self.inner
+            .write_16(self.state.translate_address(addr), data)
     }
 
     fn write_8(&mut self, addr: u64, data: &[u8]) -> Result<(), Error> {
-        self.inner.write_8(addr, data)
+        self.state
+            .check_memory_access(addr, data.len() as u64, true)?;
+        self.inner.write_8(self.state.translate_address(addr), data)
     }
 
     fn write(&mut self, addr: u64, data: &[u8]) -> Result<(), Error> {
-        self.inner.write(addr, data)
+        self.state
+            .check_memory_access(addr, data.len() as u64, true)?;
+        self.inner.write(self.state.translate_address(addr), data)
     }
 
     fn supports_8bit_transfers(&self) -> Result<bool, error::Error> {
@@ -240,33 +440,194 @@ impl<'probe> Core<'probe> {
     }
 
     /// Continue to execute instructions.
+    ///
+    /// If the core is halted on a software breakpoint, the original instruction is executed first.
     #[tracing::instrument(skip(self))]
     pub fn run(&mut self) -> Result<(), error::Error> {
+        if !self.state.sw_breakpoints.is_empty() && self.core_halted()? {
+            self.step_over_sw_breakpoint()?;
+        }
+
         self.inner.run()
     }
 
     /// Reset the core, and then continue to execute instructions. If the core
     /// should be halted after reset, use the [`reset_and_halt`] function.
     ///
+    /// The reset type given by the target description is used, see [`Core::reset_with`]
+    /// to use a different one.
+    ///
     /// [`reset_and_halt`]: Core::reset_and_halt
     #[tracing::instrument(skip(self))]
     pub fn reset(&mut self) -> Result<(), error::Error> {
-        self.inner.reset()
+        self.reset_with(self.state.default_reset_type)
+    }
+
+    /// Reset the core with the given `reset_type`, and then continue to execute instructions.
+    ///
+    /// Not every reset type is supported by every core. For example, there is no way to reset
+    /// only the core on ARMv6-M and ARMv8-M cores.
+    #[tracing::instrument(skip(self))]
+    pub fn reset_with(&mut self, reset_type: ResetType) -> Result<(), error::Error> {
+        self.inner.reset(reset_type)
     }
 
     /// Reset the core, and then immediately halt. To continue execution after
     /// reset, use the [`reset`] function.
     ///
+    /// The reset is caught, e.g. with `DEMCR.VC_CORERESET` on Cortex-M cores, or the reset halt
+    /// request on RISC-V cores, so the core halts before the first instruction is executed.
+    /// If the core doesn't halt within `timeout`, an error is returned.
+    ///
+    /// The reset type given by the target description is used, see [`Core::reset_and_halt_with`]
+    /// to use a different one.
+    ///
     /// [`reset`]: Core::reset
     #[tracing::instrument(skip(self))]
     pub fn reset_and_halt(&mut self, timeout: Duration) -> Result<CoreInformation, error::Error> {
-        self.inner.reset_and_halt(timeout)
+        self.reset_and_halt_with(self.state.default_reset_type, timeout)
+    }
+
+    /// Reset the core with the given `reset_type`, and then immediately halt.
+    #[tracing::instrument(skip(self))]
+    pub fn reset_and_halt_with(
+        &mut self,
+        reset_type: ResetType,
+        timeout: Duration,
+    ) -> Result<CoreInformation, error::Error> {
+        self.inner.reset_and_halt(reset_type, timeout)
     }
 
     /// Steps one instruction and then enters halted state again.
+    ///
+    /// If the core is halted on a software breakpoint, the original instruction is executed.
     #[tracing::instrument(skip(self))]
     pub fn step(&mut self) -> Result<CoreInformation, error::Error> {
-        self.inner.step()
+        match self.step_over_sw_breakpoint()? {
+            Some(info) => Ok(info),
+            None => self.inner.step(),
+        }
+    }
+
+    /// Steps over the instruction at the program counter, and then enters halted state again.
+    ///
+    /// If the instruction calls a function, e.g. `BL` on ARM or `JAL` on RISC-V, the core runs
+    /// until the function returns. Otherwise, this is the same as [`Core::step`].
+    ///
+    /// The core also halts if another breakpoint is hit in the called function. If the function
+    /// doesn't return within `timeout`, the core is halted and an error is returned.
+    #[tracing::instrument(skip(self))]
+    pub fn step_over(&mut self, timeout: Duration) -> Result<CoreInformation, error::Error> {
+        let pc: u64 = self.read_core_reg(self.registers().program_counter())?;
+        let instruction_set = self.instruction_set()?;
+
+        let mut instruction = [0; 4];
+        self.read_8(pc, &mut instruction)?;
+
+        let Some(size) = call_instruction_size(instruction_set, instruction) else {
+            return self.step();
+        };
+
+        tracing::debug!("Stepping over call, running to {:#010x}", pc + size);
+        self.run_to(pc + size, timeout)
+    }
+
+    /// Runs the core until the current function returns, and then enters halted state again.
+    ///
+    /// The core runs until the return address in the link register (`LR` on ARM, `ra` on RISC-V)
+    /// is reached. This is only correct while the return address has not been pushed to the stack
+    /// yet, or has been restored from it, e.g. at the start of a function, or in a leaf function.
+    ///
+    /// The core also halts if another breakpoint is hit. If the function doesn't return within
+    /// `timeout`, the core is halted and an error is returned.
+    #[tracing::instrument(skip(self))]
+    pub fn step_out(&mut self, timeout: Duration) -> Result<CoreInformation, error::Error> {
+        let pc: u64 = self.read_core_reg(self.registers().program_counter())?;
+        let return_address: u64 = self.read_core_reg(self.registers().return_address())?;
+
+        let return_address = match self.architecture() {
+            // Clear the Thumb bit.
+            Architecture::Arm => return_address & !1,
+            // The upper bits of windowed calls hold the window increment, and are taken from the PC.
+            Architecture::Xtensa => (return_address & 0x3FFF_FFFF) | (pc & 0xC000_0000),
+            Architecture::Riscv => return_address,
+        };
+
+        tracing::debug!("Stepping out, running to {:#010x}", return_address);
+        self.run_to(return_address, timeout)
+    }
+
+    /// Runs the core until it reaches `address`, and then enters halted state again.
+    ///
+    /// A temporary breakpoint is set at `address`, and removed once the core halted. A hardware
+    /// breakpoint is used if one is available, otherwise a software breakpoint. Breakpoints which
+    /// were already set at `address` are kept.
+    ///
+    /// If the core is halted, it first executes the current instruction, so it doesn't stop
+    /// immediately on a breakpoint at the current instruction, and `address` is reached
+    /// again if it is the current instruction.
+    ///
+    /// The core also halts if another breakpoint is hit or a fault occurs before `address` is reached.
+    /// Compare the returned program counter to `address`, and use [`Core::status`] to find out why
+    /// the core halted. If the core doesn't halt within `timeout`, it is halted and an error is returned.
+    /// The temporary breakpoint is removed in all cases.
+    #[tracing::instrument(skip(self))]
+    pub fn run_to(
+        &mut self,
+        address: u64,
+        timeout: Duration,
+    ) -> Result<CoreInformation, error::Error> {
+        if self.core_halted()? {
+            let info = self.step()?;
+
+            if info.pc == address {
+                return Ok(info);
+            }
+        }
+
+        let has_breakpoint = self.inner.hw_breakpoints()?.contains(&Some(address))
+            || self.sw_breakpoints().contains(&address);
+
+        let mut temporary_hw_breakpoint = false;
+        if !has_breakpoint {
+            match self.set_hw_breakpoint(address) {
+                Ok(()) => temporary_hw_breakpoint = true,
+                Err(error) => {
+                    tracing::debug!(
+                        "Using a software breakpoint, no hardware breakpoint available: {}",
+                        error
+                    );
+                    self.set_sw_breakpoint(address)?;
+                }
+            }
+        }
+
+        let result = self.run().and_then(|()| self.wait_for_core_halted(timeout));
+
+        // The temporary breakpoint is removed even if the core can't be halted, and the first
+        // error is returned afterwards.
+        let halted = match result {
+            Ok(()) => Ok(()),
+            Err(_) => match self.core_halted() {
+                Ok(true) => Ok(()),
+                Ok(false) => self.halt(Duration::from_millis(100)).map(|_| ()),
+                Err(error) => Err(error),
+            },
+        };
+
+        let cleared = if temporary_hw_breakpoint {
+            self.clear_hw_breakpoint(address)
+        } else if !has_breakpoint {
+            self.clear_sw_breakpoint(address)
+        } else {
+            Ok(())
+        };
+
+        result.and(halted).and(cleared)?;
+
+        Ok(CoreInformation {
+            pc: self.read_core_reg(self.registers().program_counter())?,
+        })
     }
 
     /// Returns the current status of the core.
@@ -307,6 +668,35 @@ impl<'probe> Core<'probe> {
         value.try_into().into_crate_error()
     }
 
+    /// Read the value of a core register, without halting the core.
+    ///
+    /// This can be used to sample registers like the program counter of a running core,
+    /// e.g. to show the activity of the target. Which registers can be read while the core is
+    /// running depends on the architecture, on Cortex-M only the program counter can be
+    /// sampled. If the core is halted, all registers can be read.
+    ///
+    /// # Errors
+    ///
+    /// If the register cannot be read while the core is running, or if `T` isn't
+    /// large enough to hold the register value, an error will be raised.
+    #[tracing::instrument(skip(self, address), fields(address))]
+    pub fn read_core_reg_nonstop<T>(
+        &mut self,
+        address: impl Into<registers::RegisterId>,
+    ) -> Result<T, error::Error>
+    where
+        registers::RegisterValue: TryInto<T>,
+        Result<T, <registers::RegisterValue as TryInto<T>>::Error>: RegisterValueResultExt<T>,
+    {
+        let address = address.into();
+
+        tracing::Span::current().record("address", format!("{address:?}"));
+
+        let value = self.inner.read_core_reg_nonstop(address)?;
+
+        value.try_into().into_crate_error()
+    }
+
     /// Write the value of a core register.
     ///
     /// # Errors
@@ -324,6 +714,24 @@ impl<'probe> Core<'probe> {
         self.inner.write_core_reg(address, value.into())
     }
 
+    /// Read a control and status register (CSR) of a RISC-V core.
+    ///
+    /// `address` is the CSR number, in the range `0x000` to `0xfff`. The addresses
+    /// of common CSRs are available in [`csr`](crate::architecture::riscv::csr).
+    /// Returns an error on other architectures.
+    pub fn read_csr(&mut self, address: u16) -> Result<u32, error::Error> {
+        self.inner.read_csr(address)
+    }
+
+    /// Write a control and status register (CSR) of a RISC-V core.
+    ///
+    /// `address` is the CSR number, in the range `0x000` to `0xfff`. The addresses
+    /// of common CSRs are available in [`csr`](crate::architecture::riscv::csr).
+    /// Returns an error on other architectures.
+    pub fn write_csr(&mut self, address: u16, value: u32) -> Result<(), error::Error> {
+        self.inner.write_csr(address, value)
+    }
+
     /// Returns all the available breakpoint units of the core.
     pub fn available_breakpoint_units(&mut self) -> Result<u32, error::Error> {
         self.inner.available_breakpoint_units()
@@ -424,6 +832,144 @@ impl<'probe> Core<'probe> {
         }
     }
 
+    /// Set a software breakpoint
+    ///
+    /// This function replaces the instruction at `address` with a breakpoint instruction,
+    /// e.g. `BKPT` on ARM or `EBREAK` on RISC-V. The original instruction is restored when the
+    /// breakpoint is cleared, and executed when the core is stepped or resumed from the breakpoint.
+    ///
+    /// Unlike hardware breakpoints, the number of software breakpoints is not limited, but they
+    /// can only be set in writable memory like RAM. If the breakpoint instruction can not be written,
+    /// an error is returned. The core has to be halted to determine the instruction set.
+    #[tracing::instrument(skip(self))]
+    pub fn set_sw_breakpoint(&mut self, address: u64) -> Result<(), error::Error> {
+        if self
+            .state
+            .sw_breakpoints
+            .iter()
+            .any(|bp| bp.address == address)
+        {
+            return Ok(());
+        }
+
+        let instruction_set = self.instruction_set()?;
+
+        let mut first_halfword = [0; 2];
+        self.read_8(address, &mut first_halfword)?;
+
+        let breakpoint =
+            breakpoint_instruction(instruction_set, u16::from_le_bytes(first_halfword))
+                .ok_or_else(|| {
+                    error::Error::Other(anyhow!(
+                        "Software breakpoints are not supported for the {:?} instruction set",
+                        instruction_set
+                    ))
+                })?;
+
+        let mut original = vec![0; breakpoint.len()];
+        self.read_8(address, &mut original)?;
+
+        self.write_8(address, breakpoint)?;
+
+        // Writes to flash or ROM have no effect, so check that the breakpoint is in place.
+        let mut written = vec![0; breakpoint.len()];
+        self.read_8(address, &mut written)?;
+        if written != breakpoint {
+            return Err(error::Error::Other(anyhow!(
+                "Could not write a software breakpoint to {:#010x}, the memory is not writable",
+                address
+            )));
+        }
+
+        tracing::debug!("Set SW breakpoint at {:#010x}", address);
+
+        if self.state.sw_breakpoints.is_empty() {
+            self.debug_on_sw_breakpoint(true)?;
+        }
+
+        self.state
+            .sw_breakpoints
+            .push(SoftwareBreakpoint { address, original });
+
+        Ok(())
+    }
+
+    /// Clear a software breakpoint
+    ///
+    /// This function restores the original instruction at `address`, if a software breakpoint
+    /// was set there with [`Core::set_sw_breakpoint`].
+    #[tracing::instrument(skip(self))]
+    pub fn clear_sw_breakpoint(&mut self, address: u64) -> Result<(), error::Error> {
+        let position = self
+            .state
+            .sw_breakpoints
+            .iter()
+            .position(|bp| bp.address == address)
+            .ok_or_else(|| {
+                error::Error::Other(anyhow!(
+                    "No software breakpoint found at address {:#010x}",
+                    address
+                ))
+            })?;
+
+        let breakpoint = self.state.sw_breakpoints.remove(position);
+        self.write_8(breakpoint.address, &breakpoint.original)?;
+
+        tracing::debug!("Cleared SW breakpoint at {:#010x}", address);
+
+        Ok(())
+    }
+
+    /// Returns the addresses of all software breakpoints set with [`Core::set_sw_breakpoint`].
+    pub fn sw_breakpoints(&self) -> Vec<u64> {
+        self.state
+            .sw_breakpoints
+            .iter()
+            .map(|bp| bp.address)
+            .collect()
+    }
+
+    /// Clear all software breakpoints, and restore the original instructions.
+    #[tracing::instrument(skip(self))]
+    pub fn clear_all_sw_breakpoints(&mut self) -> Result<(), error::Error> {
+        for address in self.sw_breakpoints() {
+            self.clear_sw_breakpoint(address)?;
+        }
+
+        Ok(())
+    }
+
+    /// Executes the original instruction, if the core is halted on a software breakpoint.
+    ///
+    /// The original instruction is restored for a single step, and replaced by the breakpoint
+    /// instruction again afterwards.
+    fn step_over_sw_breakpoint(&mut self) -> Result<Option<CoreInformation>, error::Error> {
+        if self.state.sw_breakpoints.is_empty() {
+            return Ok(None);
+        }
+
+        let pc: u64 = self.read_core_reg(self.registers().program_counter())?;
+
+        let Some(breakpoint) = self
+            .state
+            .sw_breakpoints
+            .iter()
+            .find(|bp| bp.address == pc)
+            .cloned()
+        else {
+            return Ok(None);
+        };
+
+        let mut instruction = vec![0; breakpoint.original.len()];
+        self.read_8(breakpoint.address, &mut instruction)?;
+
+        self.write_8(breakpoint.address, &breakpoint.original)?;
+        let info = self.inner.step();
+        self.write_8(breakpoint.address, &instruction)?;
+
+        info.map(Some)
+    }
+
     /// Clear all hardware breakpoints
     ///
     /// This function will clear all HW breakpoints which are configured on the target,
@@ -437,6 +983,180 @@ impl<'probe> Core<'probe> {
         Ok(())
     }
 
+    /// Returns the number of available hardware watchpoint units of the core.
+    pub fn available_watchpoint_units(&mut self) -> Result<u32, error::Error> {
+        self.inner.available_watchpoint_units()
+    }
+
+    /// Set a hardware watchpoint
+    ///
+    /// This function will try to set a hardware watchpoint on the `size` bytes at `address`,
+    /// which halts the core on the memory accesses given by `kind`. Most architectures require
+    /// `size` to be a power of two, and `address` to be aligned to `size`.
+    ///
+    /// The amount of hardware watchpoints which are supported is chip specific,
+    /// and can be queried using the `available_watchpoint_units` function.
+    #[tracing::instrument(skip(self))]
+    pub fn set_hw_watchpoint(
+        &mut self,
+        address: u64,
+        size: u32,
+        kind: WatchpointKind,
+    ) -> Result<(), error::Error> {
+        let watchpoints = self.inner.hw_watchpoints()?;
+
+        // Reuse the unit if the address is already watched, else find the next free unit.
+        let unit_index = match watchpoints.iter().position(|&wp| wp == Some(address)) {
+            Some(unit_index) => unit_index,
+            None => watchpoints
+                .iter()
+                .position(|wp| wp.is_none())
+                .ok_or_else(|| error::Error::Other(anyhow!("No available hardware watchpoints")))?,
+        };
+
+        tracing::debug!(
+            "Trying to set HW watchpoint #{} on {} bytes at {:#010x}",
+            unit_index,
+            size,
+            address
+        );
+
+        self.inner
+            .set_hw_watchpoint(unit_index, address, size, kind)
+    }
+
+    /// Clear a hardware watchpoint
+    ///
+    /// This function will try to clear a hardware watchpoint at `address` if there exists a watchpoint at that address.
+    #[tracing::instrument(skip(self))]
+    pub fn clear_hw_watchpoint(&mut self, address: u64) -> Result<(), error::Error> {
+        let unit_index = self
+            .inner
+            .hw_watchpoints()?
+            .iter()
+            .position(|&wp| wp == Some(address));
+
+        match unit_index {
+            Some(unit_index) => self.inner.clear_hw_watchpoint(unit_index),
+            None => Err(error::Error::Other(anyhow!(
+                "No watchpoint found at address {:#010x}",
+                address
+            ))),
+        }
+    }
+
+    /// Clear all hardware watchpoints
+    ///
+    /// This function will clear all HW watchpoints which are configured on the target,
+    /// regardless if they are set by probe-rs.
+    #[tracing::instrument(skip(self))]
+    pub fn clear_all_hw_watchpoints(&mut self) -> Result<(), error::Error> {
+        let watchpoints = self.inner.hw_watchpoints()?;
+
+        for (unit_index, watchpoint) in watchpoints.iter().enumerate() {
+            if watchpoint.is_some() {
+                self.inner.clear_hw_watchpoint(unit_index)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads and decodes the fault status registers of the core.
+    ///
+    /// This reports the type and cause of all faults flagged by the core, the address
+    /// of the faulting access if it is known, and whether a fault was escalated to a hard fault.
+    pub fn fault_status(&mut self) -> Result<FaultStatus, error::Error> {
+        self.inner.fault_status()
+    }
+
+    /// Reads the tightly coupled memories (TCMs) of the core, which are enabled.
+    ///
+    /// This must be queried while halted. Only ARMv7-R cores report their TCMs,
+    /// other cores return an empty list.
+    pub fn tcm_regions(&mut self) -> Result<Vec<RamRegion>, error::Error> {
+        self.inner.tcm_regions()
+    }
+
+    /// Returns the state changes of the core since the last call.
+    ///
+    /// The first call reports the current state. Resets are detected on a best effort basis,
+    /// because some architectures clear the reset flag when the core status is read.
+    /// See [`CoreWatcher`] to poll the cores on a background thread.
+    pub fn poll_events(&mut self) -> Result<Vec<CoreEvent>, error::Error> {
+        let reset = self.inner.reset_detected()?;
+        let status = self.status()?;
+
+        let events = core_events::core_events(self.state.last_status, status, reset);
+        self.state.last_status = Some(status);
+
+        Ok(events)
+    }
+
+    /// Enables the cycle counter of the core, e.g. `DWT_CYCCNT` on Cortex-M cores.
+    ///
+    /// The counter is not reset, and only counts while the core is running.
+    /// Only ARMv7-M and ARMv8-M cores with a cycle counter are supported.
+    pub fn enable_cycle_counter(&mut self) -> Result<(), error::Error> {
+        self.inner.enable_cycle_counter()
+    }
+
+    /// Reads the cycle counter of the core, which has to be enabled with
+    /// [`Core::enable_cycle_counter`]. The counter wraps around after 2^32 cycles.
+    pub fn read_cycle_counter(&mut self) -> Result<u32, error::Error> {
+        self.inner.read_cycle_counter()
+    }
+
+    /// The clock frequency of the core in Hz, as configured with
+    /// [`Session::set_core_clock`](crate::Session::set_core_clock).
+    pub fn clock_frequency(&self) -> Option<u32> {
+        self.state.clock_frequency
+    }
+
+    /// Measures the cycles the core needs to execute from `start` to `end`.
+    ///
+    /// The core is run to `start` and then to `end` with [`Core::run_to`], and the cycle counter
+    /// is read at both addresses. This can be used to benchmark a function or an interrupt handler,
+    /// e.g. by passing its start address and the address of its return instruction.
+    /// The returned measurement contains a wall-clock estimate, if the clock frequency of the
+    /// core was configured with [`Session::set_core_clock`](crate::Session::set_core_clock).
+    ///
+    /// An error is returned if the core halts anywhere else, e.g. on another breakpoint.
+    #[tracing::instrument(skip(self))]
+    pub fn measure_cycles(
+        &mut self,
+        start: u64,
+        end: u64,
+        timeout: Duration,
+    ) -> Result<CycleMeasurement, error::Error> {
+        self.enable_cycle_counter()?;
+
+        self.run_to_exactly(start, timeout)?;
+        let start_cycles = self.read_cycle_counter()?;
+
+        self.run_to_exactly(end, timeout)?;
+        let end_cycles = self.read_cycle_counter()?;
+
+        Ok(CycleMeasurement {
+            cycles: end_cycles.wrapping_sub(start_cycles),
+            clock_frequency: self.clock_frequency(),
+        })
+    }
+
+    /// Runs the core to `address`, and returns an error if it halts somewhere else.
+    fn run_to_exactly(&mut self, address: u64, timeout: Duration) -> Result<(), error::Error> {
+        let info = self.run_to(address, timeout)?;
+
+        if info.pc != address {
+            return Err(error::Error::Other(anyhow!(
+                "The core halted at {:#010x} before reaching {:#010x}",
+                info.pc,
+                address
+            )));
+        }
+
+        Ok(())
+    }
+
     /// Returns the architecture of the core.
     pub fn architecture(&self) -> Architecture {
         self.inner.architecture()
@@ -461,9 +1181,611 @@ impl<'probe> Core<'probe> {
         self.inner.fpu_support()
     }
 
+    /// Captures the registers of the core, and the memory in `ranges`, in a [`CoreDump`].
+    ///
+    /// The core has to be halted. Registers which can't be read, e.g. because they are not
+    /// implemented by this core, are left out of the dump. The same applies to the parts of
+    /// `ranges` which can't be read, see [`MemoryInterface::read_partial`].
+    #[tracing::instrument(skip(self))]
+    pub fn dump(&mut self, ranges: &[Range<u64>]) -> Result<CoreDump, error::Error> {
+        let register_file = self.registers();
+
+        let mut descriptions = register_file
+            .platform_registers()
+            .chain(register_file.msp())
+            .chain(register_file.psp())
+            .chain(register_file.psr())
+            .chain(register_file.other())
+            .collect::<Vec<_>>();
+
+        if self.fpu_support()? {
+            descriptions.extend(register_file.fpscr());
+            descriptions.extend(register_file.fpu_registers().into_iter().flatten());
+        }
+
+        let mut registers = BTreeMap::new();
+        for description in descriptions {
+            match self.inner.read_core_reg(description.id) {
+                Ok(value) => {
+                    registers.insert(description.id, value);
+                }
+                Err(error) => tracing::warn!(
+                    "Register {} is left out of the core dump: {:?}",
+                    description.name(),
+                    error
+                ),
+            }
+        }
+
+        let mut memory = Vec::new();
+        for range in ranges {
+            let mut data = vec![0; range.end.saturating_sub(range.start) as usize];
+            let faults = self.read_partial(range.start, &mut data)?;
+
+            // Only the parts between the faulted ranges are stored.
+            let mut start = range.start;
+            for fault in faults {
+                tracing::warn!(
+                    "Memory {:#010x}..{:#010x} can't be read and is left out of the core dump",
+                    fault.start,
+                    fault.end
+                );
+                if start < fault.start {
+                    let offset =
+                        (start - range.start) as usize..(fault.start - range.start) as usize;
+                    memory.push((start, data[offset].to_vec()));
+                }
+                start = fault.end;
+            }
+            if start < range.end {
+                memory.push((start, data[(start - range.start) as usize..].to_vec()));
+            }
+        }
+
+        Ok(CoreDump {
+            core_type: self.core_type(),
+            instruction_set: self.instruction_set()?,
+            registers,
+            memory,
+        })
+    }
+
     /// Called during session tear down to do any pending cleanup
     #[tracing::instrument(skip(self))]
     pub(crate) fn on_session_stop(&mut self) -> Result<(), Error> {
+        // Don't leave breakpoint instructions behind in the target memory.
+        self.clear_all_sw_breakpoints()?;
+
         self.inner.on_session_stop()
     }
 }
+
+/// The breakpoint instruction for `instruction_set`, in little endian byte order.
+///
+/// `first_halfword` is the start of the instruction which is replaced. It is used to choose
+/// between the compressed and the full size breakpoint instruction on RISC-V.
+fn breakpoint_instruction(
+    instruction_set: InstructionSet,
+    first_halfword: u16,
+) -> Option<&'static [u8]> {
+    /// `BKPT #0`
+    const THUMB_BKPT: [u8; 2] = 0xBE00u16.to_le_bytes();
+    /// `BKPT #0`
+    const A32_BKPT: [u8; 4] = 0xE120_0070u32.to_le_bytes();
+    /// `BRK #0`
+    const A64_BRK: [u8; 4] = 0xD420_0000u32.to_le_bytes();
+    /// `EBREAK`
+    const RISCV_EBREAK: [u8; 4] = 0x0010_0073u32.to_le_bytes();
+    /// `C.EBREAK`
+    const RISCV_C_EBREAK: [u8; 2] = 0x9002u16.to_le_bytes();
+
+    match instruction_set {
+        InstructionSet::Thumb2 => Some(&THUMB_BKPT),
+        InstructionSet::A32 => Some(&A32_BKPT),
+        InstructionSet::A64 => Some(&A64_BRK),
+        InstructionSet::RV32 => Some(&RISCV_EBREAK),
+        // The lowest two bits are set for all 32-bit instructions.
+        InstructionSet::RV32C if first_halfword & 0b11 == 0b11 => Some(&RISCV_EBREAK),
+        InstructionSet::RV32C => Some(&RISCV_C_EBREAK),
+        InstructionSet::Xtensa => None,
+    }
+}
+
+/// The size of the instruction at the start of `instruction` if it calls a function, `None` otherwise.
+///
+/// `instruction` holds the bytes at the program counter, in target byte order.
+fn call_instruction_size(instruction_set: InstructionSet, instruction: [u8; 4]) -> Option<u64> {
+    let halfword = u16::from_le_bytes([instruction[0], instruction[1]]);
+    let second_halfword = u16::from_le_bytes([instruction[2], instruction[3]]);
+    let word = u32::from_le_bytes(instruction);
+
+    match instruction_set {
+        InstructionSet::Thumb2 => {
+            let is_32_bit = matches!(halfword >> 11, 0b11101..=0b11111);
+
+            if is_32_bit {
+                // `BL` and `BLX <label>`
+                let call = halfword & 0xF800 == 0xF000 && second_halfword & 0xC000 == 0xC000;
+                call.then_some(4)
+            } else {
+                // `BLX <Rm>`
+                (halfword & 0xFF87 == 0x4780).then_some(2)
+            }
+        }
+        InstructionSet::A32 => {
+            let bl = word >> 28 != 0b1111 && word & 0x0F00_0000 == 0x0B00_0000;
+            let blx_label = word & 0xFE00_0000 == 0xFA00_0000;
+            let blx_register = word & 0x0FFF_FFF0 == 0x012F_FF30;
+
+            (bl || blx_label || blx_register).then_some(4)
+        }
+        InstructionSet::A64 => {
+            let bl = word & 0xFC00_0000 == 0x9400_0000;
+            let blr = word & 0xFFFF_FC1F == 0xD63F_0000;
+
+            (bl || blr).then_some(4)
+        }
+        InstructionSet::RV32 | InstructionSet::RV32C if halfword & 0b11 == 0b11 => {
+            // `JAL` and `JALR`, which store the return address in `ra` or `t0`.
+            let jump = matches!(word & 0x7F, 0x6F | 0x67);
+            let rd = (word >> 7) & 0x1F;
+
+            (jump && matches!(rd, 1 | 5)).then_some(4)
+        }
+        InstructionSet::RV32 => None,
+        InstructionSet::RV32C => {
+            let c_jal = halfword & 0xE003 == 0x2001;
+            // `C.JALR`, the encoding with `rs1 == 0` is `C.EBREAK`.
+            let c_jalr = halfword & 0xF07F == 0x9002 && (halfword >> 7) & 0x1F != 0;
+
+            (c_jal || c_jalr).then_some(2)
+        }
+        InstructionSet::Xtensa => {
+            let call = instruction[0] & 0x0F == 0b0101;
+            // `CALLX0` to `CALLX12`
+            let callx =
+                instruction[0] & 0xCF == 0xC0 && instruction[1] & 0xF0 == 0 && instruction[2] == 0;
+
+            (call || callx).then_some(3)
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::time::Duration;
+
+    use anyhow::anyhow;
+    use probe_rs_target::{ArmCoreAccessOptions, CoreAccessOptions};
+
+    use super::{
+        breakpoint_instruction, call_instruction_size, registers, BreakpointCause, Core,
+        CoreInformation, CoreInterface, CoreState, CoreStatus, CycleMeasurement, HaltReason,
+    };
+    use crate::{
+        architecture::arm::core::CORTEX_M_COMMON_REGS, error, probe::fake_probe::FakeProbe,
+        Architecture, CoreType, Error, InstructionSet, MemoryInterface, Permissions, ResetType,
+    };
+
+    /// A core with a single hardware breakpoint unit and 16 bytes of RAM at address 0.
+    pub(crate) struct MockCore {
+        pub(crate) halted: bool,
+        pub(crate) pc: u64,
+        /// The content of the link register.
+        pub(crate) lr: u64,
+        /// The core halts at the breakpoint when it is resumed, instead of running forever.
+        pub(crate) reaches_breakpoint: bool,
+        pub(crate) halt_fails: bool,
+        pub(crate) hw_breakpoints: Vec<Option<u64>>,
+        pub(crate) memory: Vec<u8>,
+    }
+
+    impl MockCore {
+        pub(crate) fn new() -> Self {
+            Self {
+                halted: false,
+                pc: 0,
+                lr: 0,
+                reaches_breakpoint: false,
+                halt_fails: false,
+                hw_breakpoints: vec![None],
+                memory: (0..16).collect(),
+            }
+        }
+    }
+
+    impl CoreInterface for MockCore {
+        fn wait_for_core_halted(&mut self, _timeout: Duration) -> Result<(), error::Error> {
+            if !self.reaches_breakpoint {
+                return Err(Error::Timeout);
+            }
+
+            self.halted = true;
+            self.pc = self.hw_breakpoints[0].unwrap_or(0x8);
+            Ok(())
+        }
+
+        fn core_halted(&mut self) -> Result<bool, error::Error> {
+            Ok(self.halted)
+        }
+
+        fn status(&mut self) -> Result<CoreStatus, error::Error> {
+            if self.halted {
+                Ok(CoreStatus::Halted(HaltReason::Breakpoint(
+                    BreakpointCause::Hardware,
+                )))
+            } else {
+                Ok(CoreStatus::Running)
+            }
+        }
+
+        fn halt(&mut self, _timeout: Duration) -> Result<CoreInformation, error::Error> {
+            if self.halt_fails {
+                return Err(Error::Other(anyhow!("The core can't be halted")));
+            }
+
+            self.halted = true;
+            Ok(CoreInformation { pc: self.pc })
+        }
+
+        fn run(&mut self) -> Result<(), error::Error> {
+            self.halted = false;
+            Ok(())
+        }
+
+        fn reset(&mut self, _reset_type: ResetType) -> Result<(), error::Error> {
+            unimplemented!()
+        }
+
+        fn reset_and_halt(
+            &mut self,
+            _reset_type: ResetType,
+            _timeout: Duration,
+        ) -> Result<CoreInformation, error::Error> {
+            unimplemented!()
+        }
+
+        fn step(&mut self) -> Result<CoreInformation, error::Error> {
+            self.pc += 2;
+            Ok(CoreInformation { pc: self.pc })
+        }
+
+        fn read_core_reg(
+            &mut self,
+            address: registers::RegisterId,
+        ) -> Result<registers::RegisterValue, error::Error> {
+            if address == self.registers().return_address().id {
+                return Ok((self.lr as u32).into());
+            }
+
+            assert_eq!(address, self.registers().program_counter().id);
+            Ok((self.pc as u32).into())
+        }
+
+        fn write_core_reg(
+            &mut self,
+            _address: registers::RegisterId,
+            _value: registers::RegisterValue,
+        ) -> Result<(), error::Error> {
+            unimplemented!()
+        }
+
+        fn available_breakpoint_units(&mut self) -> Result<u32, error::Error> {
+            Ok(self.hw_breakpoints.len() as u32)
+        }
+
+        fn hw_breakpoints(&mut self) -> Result<Vec<Option<u64>>, error::Error> {
+            Ok(self.hw_breakpoints.clone())
+        }
+
+        fn enable_breakpoints(&mut self, _state: bool) -> Result<(), error::Error> {
+            Ok(())
+        }
+
+        fn set_hw_breakpoint(&mut self, unit_index: usize, addr: u64) -> Result<(), error::Error> {
+            self.hw_breakpoints[unit_index] = Some(addr);
+            Ok(())
+        }
+
+        fn clear_hw_breakpoint(&mut self, unit_index: usize) -> Result<(), error::Error> {
+            self.hw_breakpoints[unit_index] = None;
+            Ok(())
+        }
+
+        fn registers(&self) -> &'static registers::RegisterFile {
+            &CORTEX_M_COMMON_REGS
+        }
+
+        fn hw_breakpoints_enabled(&self) -> bool {
+            true
+        }
+
+        fn architecture(&self) -> Architecture {
+            Architecture::Arm
+        }
+
+        fn core_type(&self) -> CoreType {
+            CoreType::Armv7m
+        }
+
+        fn instruction_set(&mut self) -> Result<InstructionSet, error::Error> {
+            Ok(InstructionSet::Thumb2)
+        }
+
+        fn fpu_support(&mut self) -> Result<bool, error::Error> {
+            Ok(false)
+        }
+    }
+
+    impl MemoryInterface for MockCore {
+        fn supports_native_64bit_access(&mut self) -> bool {
+            false
+        }
+
+        fn read_word_64(&mut self, _address: u64) -> Result<u64, Error> {
+            unimplemented!()
+        }
+
+        fn read_word_32(&mut self, _address: u64) -> Result<u32, Error> {
+            unimplemented!()
+        }
+
+        fn read_word_16(&mut self, _address: u64) -> Result<u16, Error> {
+            unimplemented!()
+        }
+
+        fn read_word_8(&mut self, _address: u64) -> Result<u8, Error> {
+            unimplemented!()
+        }
+
+        fn read_64(&mut self, _address: u64, _data: &mut [u64]) -> Result<(), Error> {
+            unimplemented!()
+        }
+
+        fn read_32(&mut self, _address: u64, _data: &mut [u32]) -> Result<(), Error> {
+            unimplemented!()
+        }
+
+        fn read_16(&mut self, _address: u64, _data: &mut [u16]) -> Result<(), Error> {
+            unimplemented!()
+        }
+
+        fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error> {
+            let address = address as usize;
+            data.copy_from_slice(&self.memory[address..address + data.len()]);
+            Ok(())
+        }
+
+        fn write_word_64(&mut self, _address: u64, _data: u64) -> Result<(), Error> {
+            unimplemented!()
+        }
+
+        fn write_word_32(&mut self, _address: u64, _data: u32) -> Result<(), Error> {
+            unimplemented!()
+        }
+
+        fn write_word_16(&mut self, _address: u64, _data: u16) -> Result<(), Error> {
+            unimplemented!()
+        }
+
+        fn write_word_8(&mut self, _address: u64, _data: u8) -> Result<(), Error> {
+            unimplemented!()
+        }
+
+        fn write_64(&mut self, _address: u64, _data: &[u64]) -> Result<(), Error> {
+            unimplemented!()
+        }
+
+        fn write_32(&mut self, _address: u64, _data: &[u32]) -> Result<(), Error> {
+            unimplemented!()
+        }
+
+        fn write_16(&mut self, _address: u64, _data: &[u16]) -> Result<(), Error> {
+            unimplemented!()
+        }
+
+        fn write_8(&mut self, address: u64, data: &[u8]) -> Result<(), Error> {
+            let address = address as usize;
+            self.memory[address..address + data.len()].copy_from_slice(data);
+            Ok(())
+        }
+
+        fn supports_8bit_transfers(&self) -> Result<bool, Error> {
+            Ok(true)
+        }
+
+        fn flush(&mut self) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    pub(crate) fn core_state() -> CoreState {
+        CoreState::new(0, CoreAccessOptions::Arm(ArmCoreAccessOptions::default()))
+    }
+
+    #[test]
+    fn run_to_address() {
+        let mut state = core_state();
+        let mut core = Core::new(
+            MockCore {
+                reaches_breakpoint: true,
+                ..MockCore::new()
+            },
+            &mut state,
+        );
+
+        let info = core.run_to(0x8, Duration::from_millis(10)).unwrap();
+
+        assert_eq!(info.pc, 0x8);
+        assert_eq!(core.inner.hw_breakpoints().unwrap(), [None]);
+    }
+
+    #[test]
+    fn csr_access_requires_riscv() {
+        let mut state = core_state();
+        let mut core = Core::new(MockCore::new(), &mut state);
+
+        assert!(core.read_csr(0x300).is_err());
+        assert!(core.write_csr(0x300, 0).is_err());
+    }
+
+    #[test]
+    fn run_to_timeout_removes_hw_breakpoint() {
+        let mut state = core_state();
+        let mut core = Core::new(MockCore::new(), &mut state);
+
+        let result = core.run_to(0x8, Duration::from_millis(10));
+
+        assert!(matches!(result, Err(Error::Timeout)));
+        assert!(core.core_halted().unwrap());
+        assert_eq!(core.inner.hw_breakpoints().unwrap(), [None]);
+    }
+
+    #[test]
+    fn run_to_removes_hw_breakpoint_if_halt_fails() {
+        let mut state = core_state();
+        let mut core = Core::new(
+            MockCore {
+                halt_fails: true,
+                ..MockCore::new()
+            },
+            &mut state,
+        );
+
+        let result = core.run_to(0x8, Duration::from_millis(10));
+
+        // The timeout is reported, not the following error.
+        assert!(matches!(result, Err(Error::Timeout)));
+        assert_eq!(core.inner.hw_breakpoints().unwrap(), [None]);
+    }
+
+    #[test]
+    fn run_to_timeout_removes_sw_breakpoint() {
+        let mut state = core_state();
+        let mut core = Core::new(
+            MockCore {
+                halted: true,
+                halt_fails: true,
+                hw_breakpoints: vec![],
+                ..MockCore::new()
+            },
+            &mut state,
+        );
+
+        let result = core.run_to(0x8, Duration::from_millis(10));
+
+        assert!(matches!(result, Err(Error::Timeout)));
+        assert!(core.sw_breakpoints().is_empty());
+
+        let mut memory = [0; 16];
+        core.read_8(0, &mut memory).unwrap();
+        assert_eq!(memory.to_vec(), (0..16).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn cycle_measurement_duration() {
+        let measurement = CycleMeasurement {
+            cycles: 64_000,
+            clock_frequency: Some(64_000_000),
+        };
+        assert_eq!(measurement.duration(), Some(Duration::from_millis(1)));
+
+        let measurement = CycleMeasurement {
+            cycles: 64_000,
+            clock_frequency: None,
+        };
+        assert_eq!(measurement.duration(), None);
+    }
+
+    #[test]
+    fn breakpoint_instructions() {
+        assert_eq!(
+            breakpoint_instruction(InstructionSet::Thumb2, 0x4770),
+            Some(&[0x00, 0xBE][..])
+        );
+        assert_eq!(
+            breakpoint_instruction(InstructionSet::RV32, 0x0001),
+            Some(&[0x73, 0x00, 0x10, 0x00][..])
+        );
+        assert_eq!(breakpoint_instruction(InstructionSet::Xtensa, 0), None);
+    }
+
+    #[test]
+    fn riscv_compressed_breakpoint() {
+        // `c.nop` is replaced by `c.ebreak`.
+        assert_eq!(
+            breakpoint_instruction(InstructionSet::RV32C, 0x0001),
+            Some(&[0x02, 0x90][..])
+        );
+        // The lower half of `addi a0, a0, 1` is a full size instruction.
+        assert_eq!(
+            breakpoint_instruction(InstructionSet::RV32C, 0x0513),
+            Some(&[0x73, 0x00, 0x10, 0x00][..])
+        );
+    }
+
+    #[test]
+    fn thumb_calls() {
+        // `bl 0x100` is a 32-bit call.
+        assert_eq!(
+            call_instruction_size(InstructionSet::Thumb2, [0x00, 0xF0, 0x7E, 0xF8]),
+            Some(4)
+        );
+        // `blx r3`
+        assert_eq!(
+            call_instruction_size(InstructionSet::Thumb2, [0x98, 0x47, 0x00, 0x00]),
+            Some(2)
+        );
+        // `bx lr` is a return.
+        assert_eq!(
+            call_instruction_size(InstructionSet::Thumb2, [0x70, 0x47, 0x00, 0x00]),
+            None
+        );
+        // `b.w` is a jump.
+        assert_eq!(
+            call_instruction_size(InstructionSet::Thumb2, [0x00, 0xF0, 0x7E, 0xB8]),
+            None
+        );
+    }
+
+    #[test]
+    fn riscv_calls() {
+        // `jal ra, 0x10`
+        assert_eq!(
+            call_instruction_size(InstructionSet::RV32C, 0x0100_00EFu32.to_le_bytes()),
+            Some(4)
+        );
+        // `jal zero, 0x10` is a jump.
+        assert_eq!(
+            call_instruction_size(InstructionSet::RV32C, 0x0100_006Fu32.to_le_bytes()),
+            None
+        );
+        // `c.jalr a0`
+        assert_eq!(
+            call_instruction_size(InstructionSet::RV32C, [0x02, 0x95, 0x00, 0x00]),
+            Some(2)
+        );
+        // `c.ebreak`
+        assert_eq!(
+            call_instruction_size(InstructionSet::RV32C, [0x02, 0x90, 0x00, 0x00]),
+            None
+        );
+    }
+
+    #[test]
+    fn reset_catch_is_cleared_if_the_core_does_not_halt() {
+        // The core never reports that it is halted.
+        let mut probe = FakeProbe::new();
+        probe.add_memory_region(0xe000_e000, vec![0; 0x1000]);
+        let mut session = probe
+            .into_probe()
+            .attach_non_intrusive("nrf51822_xxAC", Permissions::default())
+            .unwrap();
+        let mut core = session.core(0).unwrap();
+
+        assert!(core.reset_and_halt(Duration::from_millis(10)).is_err());
+
+        // DEMCR.VC_CORERESET
+        assert_eq!(core.read_word_32(0xe000_edfc).unwrap() & 0x1, 0);
+    }
+}
@@ -0,0 +1,231 @@
+//! Changes of the core state, e.g. when a core halts or is reset.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use crate::{CoreStatus, Error, HaltReason, Session};
+
+/// A change of the state of a core.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoreEvent {
+    /// The core started running.
+    Running,
+    /// The core entered sleep mode, e.g. waiting for an interrupt.
+    Sleeping,
+    /// The core halted for the given reason.
+    Halted(HaltReason),
+    /// The core is locked up, because of an unrecoverable exception.
+    LockedUp,
+    /// The core was reset.
+    ///
+    /// Resets are only detected on architectures which report them, i.e. ARMv6-M, ARMv7-M,
+    /// ARMv8-M and RISC-V.
+    Reset,
+}
+
+/// Determines the events between the `previous` and the `current` status of a core.
+///
+/// If the core was reset, the [`CoreEvent::Reset`] is reported first.
+pub(crate) fn core_events(
+    previous: Option<CoreStatus>,
+    current: CoreStatus,
+    reset: bool,
+) -> Vec<CoreEvent> {
+    let mut events = Vec::new();
+
+    if reset {
+        events.push(CoreEvent::Reset);
+    }
+
+    if previous != Some(current) {
+        let event = match current {
+            CoreStatus::Running => Some(CoreEvent::Running),
+            CoreStatus::Sleeping => Some(CoreEvent::Sleeping),
+            CoreStatus::Halted(reason) => Some(CoreEvent::Halted(reason)),
+            CoreStatus::LockedUp => Some(CoreEvent::LockedUp),
+            CoreStatus::Unknown => None,
+        };
+
+        events.extend(event);
+    }
+
+    events
+}
+
+/// The default interval in which the cores are polled.
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Watches the cores of a session for state changes.
+///
+/// The watcher polls all cores with [`Session::poll_core_events`] on a background thread,
+/// and reports each change with the index of the core. The session is only locked while
+/// the cores are polled, so it can be used in between.
+///
+/// If polling fails, the error is reported and the watcher stops.
+/// The background thread is stopped when the watcher is dropped.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::sync::{Arc, Mutex};
+/// use probe_rs::{CoreEvent, CoreWatcher, Permissions, Probe};
+///
+/// let probe = Probe::list_all()[0].open()?;
+/// let session = probe.attach("nrf52", Permissions::default())?;
+///
+/// let watcher = CoreWatcher::new(Arc::new(Mutex::new(session)));
+///
+/// for event in watcher.iter() {
+///     match event? {
+///         (core, CoreEvent::Halted(reason)) => println!("Core {core} halted: {reason:?}"),
+///         (core, event) => println!("Core {core}: {event:?}"),
+///     }
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct CoreWatcher {
+    events: Receiver<Result<(usize, CoreEvent), Error>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl CoreWatcher {
+    /// Starts watching the cores of `session`, using the default poll interval.
+    pub fn new(session: Arc<Mutex<Session>>) -> Self {
+        Self::with_interval(session, DEFAULT_POLL_INTERVAL)
+    }
+
+    /// Starts watching the cores of `session`, polling them every `interval`.
+    pub fn with_interval(session: Arc<Mutex<Session>>, interval: Duration) -> Self {
+        let (sender, events) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread = {
+            let stop = stop.clone();
+
+            std::thread::Builder::new()
+                .name("core-watcher".to_string())
+                .spawn(move || {
+                    while !stop.load(Ordering::Relaxed) {
+                        let events = match session.lock() {
+                            Ok(mut session) => session.poll_core_events(),
+                            // Another user of the session panicked.
+                            Err(_) => return,
+                        };
+
+                        match events {
+                            Ok(events) => {
+                                for event in events {
+                                    if sender.send(Ok(event)).is_err() {
+                                        // The watcher was dropped.
+                                        return;
+                                    }
+                                }
+                            }
+                            Err(error) => {
+                                let _ = sender.send(Err(error));
+                                return;
+                            }
+                        }
+
+                        std::thread::sleep(interval);
+                    }
+                })
+                .expect("Failed to spawn core watcher thread")
+        };
+
+        Self {
+            events,
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    /// Returns the next event, if one is available, without blocking.
+    pub fn try_next_event(&self) -> Option<Result<(usize, CoreEvent), Error>> {
+        self.events.try_recv().ok()
+    }
+
+    /// Waits up to `timeout` for the next event.
+    pub fn next_event_timeout(
+        &self,
+        timeout: Duration,
+    ) -> Option<Result<(usize, CoreEvent), Error>> {
+        self.events.recv_timeout(timeout).ok()
+    }
+
+    /// Returns an iterator which blocks waiting for events.
+    ///
+    /// The iterator ends after an error, because the watcher stops.
+    pub fn iter(&self) -> impl Iterator<Item = Result<(usize, CoreEvent), Error>> + '_ {
+        self.events.iter()
+    }
+}
+
+impl Drop for CoreWatcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{core_events, CoreEvent};
+    use crate::{BreakpointCause, CoreStatus, HaltReason};
+
+    #[test]
+    fn initial_status() {
+        assert_eq!(
+            core_events(None, CoreStatus::Running, false),
+            vec![CoreEvent::Running]
+        );
+        assert!(core_events(None, CoreStatus::Unknown, false).is_empty());
+    }
+
+    #[test]
+    fn status_changes() {
+        let breakpoint = HaltReason::Breakpoint(BreakpointCause::Hardware);
+
+        assert_eq!(
+            core_events(
+                Some(CoreStatus::Running),
+                CoreStatus::Halted(breakpoint),
+                false
+            ),
+            vec![CoreEvent::Halted(breakpoint)]
+        );
+        assert_eq!(
+            core_events(
+                Some(CoreStatus::Halted(HaltReason::Step)),
+                CoreStatus::Halted(breakpoint),
+                false
+            ),
+            vec![CoreEvent::Halted(breakpoint)]
+        );
+        assert!(core_events(Some(CoreStatus::Running), CoreStatus::Running, false).is_empty());
+    }
+
+    #[test]
+    fn reset_is_reported_first() {
+        assert_eq!(
+            core_events(Some(CoreStatus::Running), CoreStatus::Running, true),
+            vec![CoreEvent::Reset]
+        );
+        assert_eq!(
+            core_events(Some(CoreStatus::Running), CoreStatus::LockedUp, true),
+            vec![CoreEvent::Reset, CoreEvent::LockedUp]
+        );
+    }
+}
@@ -5,10 +5,13 @@ use crate::{
             memory::adi_v5_memory_interface::ArmProbe,
         },
         riscv::{communication_interface::RiscvCommunicationInterface, RiscVState},
+        xtensa::{communication_interface::XtensaCommunicationInterface, XtensaState},
     },
-    Core, CoreType, Error, Target,
+    Core, CoreStatus, CoreType, Error, ResetType, Target,
 };
 pub use probe_rs_target::{Architecture, CoreAccessOptions};
+use probe_rs_target::{MemoryAccess, MemoryAlias};
+use std::ops::Range;
 
 /// A generic core state which caches the generic parts of the core state.
 #[derive(Debug)]
@@ -17,6 +20,37 @@ pub struct CoreState {
 
     /// Information needed to access the core
     core_access_options: CoreAccessOptions,
+
+    /// The software breakpoints which are set on the core.
+    pub(crate) sw_breakpoints: Vec<SoftwareBreakpoint>,
+
+    /// The status of the core when the events were last polled.
+    pub(crate) last_status: Option<CoreStatus>,
+
+    /// The reset type used by [`Core::reset`](crate::Core::reset) and [`Core::reset_and_halt`](crate::Core::reset_and_halt).
+    pub(crate) default_reset_type: ResetType,
+
+    /// The clock frequency of the core in Hz, used to estimate the duration of cycle measurements.
+    pub(crate) clock_frequency: Option<u32>,
+
+    /// The memory regions of the core with restricted access, see [`MemoryAccess`].
+    pub(crate) restricted_regions: Vec<(Range<u64>, MemoryAccess)>,
+
+    /// The address ranges which the core sees at a different address than the debugger.
+    pub(crate) memory_aliases: Vec<MemoryAlias>,
+
+    /// Whether the session was attached with [`AttachMethod::NonIntrusive`](crate::AttachMethod::NonIntrusive),
+    /// in which case the debug registers of the core are not written when attaching to it.
+    pub(crate) non_intrusive: bool,
+}
+
+/// A software breakpoint, and the instruction it replaced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SoftwareBreakpoint {
+    /// The address of the breakpoint instruction.
+    pub address: u64,
+    /// The original instruction at the address, which is restored when the breakpoint is removed.
+    pub original: Vec<u8>,
 }
 
 impl CoreState {
@@ -25,6 +59,13 @@ impl CoreState {
         Self {
             id,
             core_access_options,
+            sw_breakpoints: Vec::new(),
+            last_status: None,
+            default_reset_type: ResetType::default(),
+            clock_frequency: None,
+            restricted_regions: Vec::new(),
+            memory_aliases: Vec::new(),
+            non_intrusive: false,
         }
     }
 
@@ -33,6 +74,48 @@ impl CoreState {
     pub fn id(&self) -> usize {
         self.id
     }
+
+    /// Translates an address as seen by the core to the address as seen by the debugger.
+    pub(crate) fn translate_address(&self, address: u64) -> u64 {
+        self.memory_aliases
+            .iter()
+            .find_map(|alias| alias.translate(address))
+            .unwrap_or(address)
+    }
+
+    /// Checks an access to the `size` bytes at `address` against the access
+    /// restrictions of the memory map.
+    pub(crate) fn check_memory_access(
+        &self,
+        address: u64,
+        size: u64,
+        write: bool,
+    ) -> Result<(), Error> {
+        let end = address.saturating_add(size);
+
+        for (range, access) in &self.restricted_regions {
+            if range.start >= end || address >= range.end {
+                continue;
+            }
+
+            if (write && !access.write) || (!write && !access.read) {
+                return Err(Error::MemoryAccessDenied {
+                    address,
+                    size,
+                    write,
+                });
+            }
+
+            if access.secure_only {
+                tracing::warn!(
+                    "The memory at {:#010x} can only be accessed in the secure state",
+                    address
+                );
+            }
+        }
+
+        Ok(())
+    }
 }
 
 /// The architecture specific core state.
@@ -46,12 +129,16 @@ pub enum SpecificCoreState {
     Armv7m(CortexMState),
     /// The state of an ARMv7-EM core.
     Armv7em(CortexMState),
+    /// The state of an ARMv7-R core.
+    Armv7r(CortexAState),
     /// The state of an ARMv8-A core.
     Armv8a(CortexAState),
     /// The state of an ARMv8-M core.
     Armv8m(CortexMState),
     /// The state of an RISC-V core.
     Riscv(RiscVState),
+    /// The state of an Xtensa core.
+    Xtensa(XtensaState),
 }
 
 impl SpecificCoreState {
@@ -61,9 +148,11 @@ impl SpecificCoreState {
             CoreType::Armv7a => SpecificCoreState::Armv7a(CortexAState::new()),
             CoreType::Armv7m => SpecificCoreState::Armv7m(CortexMState::new()),
             CoreType::Armv7em => SpecificCoreState::Armv7m(CortexMState::new()),
+            CoreType::Armv7r => SpecificCoreState::Armv7r(CortexAState::new()),
             CoreType::Armv8a => SpecificCoreState::Armv8a(CortexAState::new()),
             CoreType::Armv8m => SpecificCoreState::Armv8m(CortexMState::new()),
             CoreType::Riscv => SpecificCoreState::Riscv(RiscVState::new()),
+            CoreType::Xtensa => SpecificCoreState::Xtensa(XtensaState::new()),
         }
     }
 
@@ -73,9 +162,11 @@ impl SpecificCoreState {
             SpecificCoreState::Armv7a(_) => CoreType::Armv7a,
             SpecificCoreState::Armv7m(_) => CoreType::Armv7m,
             SpecificCoreState::Armv7em(_) => CoreType::Armv7em,
+            SpecificCoreState::Armv7r(_) => CoreType::Armv7r,
             SpecificCoreState::Armv8a(_) => CoreType::Armv8a,
             SpecificCoreState::Armv8m(_) => CoreType::Armv8m,
             SpecificCoreState::Riscv(_) => CoreType::Riscv,
+            SpecificCoreState::Xtensa(_) => CoreType::Xtensa,
         }
     }
 
@@ -87,16 +178,18 @@ impl SpecificCoreState {
     ) -> Result<Core<'probe>, Error> {
         let debug_sequence = match &target.debug_sequence {
             crate::config::DebugSequence::Arm(sequence) => sequence.clone(),
-            crate::config::DebugSequence::Riscv(_) => {
+            crate::config::DebugSequence::Riscv(_) | crate::config::DebugSequence::Xtensa(_) => {
                 return Err(Error::UnableToOpenProbe(
                     "Core architecture and Probe mismatch.",
                 ))
             }
         };
 
+        let non_intrusive = state.non_intrusive;
+
         let options = match &state.core_access_options {
             CoreAccessOptions::Arm(options) => options,
-            CoreAccessOptions::Riscv(_) => {
+            CoreAccessOptions::Riscv(_) | CoreAccessOptions::Xtensa(_) => {
                 return Err(Error::UnableToOpenProbe(
                     "Core architecture and Probe mismatch.",
                 ))
@@ -105,7 +198,12 @@ impl SpecificCoreState {
 
         Ok(match self {
             SpecificCoreState::Armv6m(s) => Core::new(
-                crate::architecture::arm::armv6m::Armv6m::new(memory, s, debug_sequence)?,
+                crate::architecture::arm::armv6m::Armv6m::new(
+                    memory,
+                    s,
+                    debug_sequence,
+                    non_intrusive,
+                )?,
                 state,
             ),
             SpecificCoreState::Armv7a(s) => Core::new(
@@ -117,8 +215,23 @@ impl SpecificCoreState {
                 )?,
                 state,
             ),
+            SpecificCoreState::Armv7r(s) => Core::new(
+                crate::architecture::arm::armv7a::Armv7a::new_armv7r(
+                    memory,
+                    s,
+                    options.debug_base.expect("base_address not specified"),
+                    debug_sequence,
+                )?,
+                state,
+            ),
             SpecificCoreState::Armv7m(s) | SpecificCoreState::Armv7em(s) => Core::new(
-                crate::architecture::arm::armv7m::Armv7m::new(memory, s, debug_sequence)?,
+                crate::architecture::arm::armv7m::Armv7m::new(
+                    memory,
+                    s,
+                    debug_sequence,
+                    options.cache_maintenance,
+                    non_intrusive,
+                )?,
                 state,
             ),
             SpecificCoreState::Armv8a(s) => Core::new(
@@ -132,7 +245,12 @@ impl SpecificCoreState {
                 state,
             ),
             SpecificCoreState::Armv8m(s) => Core::new(
-                crate::architecture::arm::armv8m::Armv8m::new(memory, s, debug_sequence)?,
+                crate::architecture::arm::armv8m::Armv8m::new(
+                    memory,
+                    s,
+                    debug_sequence,
+                    non_intrusive,
+                )?,
                 state,
             ),
             _ => {
@@ -160,4 +278,79 @@ impl SpecificCoreState {
             }
         })
     }
+
+    pub(crate) fn attach_xtensa<'probe>(
+        &'probe mut self,
+        state: &'probe mut CoreState,
+        interface: &'probe mut XtensaCommunicationInterface,
+    ) -> Result<Core<'probe>, Error> {
+        Ok(match self {
+            SpecificCoreState::Xtensa(s) => Core::new(
+                crate::architecture::xtensa::Xtensa::new(interface, s),
+                state,
+            ),
+            _ => {
+                return Err(Error::UnableToOpenProbe(
+                    "Core architecture and Probe mismatch.",
+                ))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use probe_rs_target::RiscvCoreAccessOptions;
+
+    #[test]
+    fn restricted_memory_access() {
+        let mut state = CoreState::new(
+            0,
+            CoreAccessOptions::Riscv(RiscvCoreAccessOptions { hart_id: None }),
+        );
+        state.restricted_regions = vec![
+            (
+                0x4000_0000..0x4000_1000,
+                MemoryAccess {
+                    read: false,
+                    ..Default::default()
+                },
+            ),
+            (
+                0x0800_0000..0x0810_0000,
+                MemoryAccess {
+                    write: false,
+                    ..Default::default()
+                },
+            ),
+        ];
+
+        assert!(state.check_memory_access(0x3FFF_FFFC, 4, false).is_ok());
+        assert!(matches!(
+            state.check_memory_access(0x3FFF_FFFC, 8, false),
+            Err(Error::MemoryAccessDenied { write: false, .. })
+        ));
+        assert!(state.check_memory_access(0x4000_0000, 4, true).is_ok());
+        assert!(state.check_memory_access(0x0800_0000, 4, false).is_ok());
+        assert!(matches!(
+            state.check_memory_access(0x080F_FFFF, 1, true),
+            Err(Error::MemoryAccessDenied { write: true, .. })
+        ));
+    }
+
+    #[test]
+    fn aliased_addresses() {
+        let mut state = CoreState::new(
+            1,
+            CoreAccessOptions::Riscv(RiscvCoreAccessOptions { hart_id: None }),
+        );
+        state.memory_aliases = vec![MemoryAlias {
+            range: 0x0..0x8000,
+            target: 0x2004_0000,
+        }];
+
+        assert_eq!(state.translate_address(0x100), 0x2004_0100);
+        assert_eq!(state.translate_address(0x8000), 0x8000);
+    }
 }
@@ -1,5 +1,5 @@
 /// The status of the core.
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
 pub enum CoreStatus {
     /// The core is currently running.
     Running,
@@ -26,18 +26,35 @@ impl CoreStatus {
 }
 
 /// When the core halts due to a breakpoint request, some architectures will allow us to distinguish between a software and hardware breakpoint.
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
 pub enum BreakpointCause {
     /// We encountered a hardware breakpoint.
     Hardware,
     /// We encountered a software breakpoint instruction.
     Software,
+    /// We encountered a breakpoint instruction which requests a semihosting operation,
+    /// e.g. `BKPT 0xAB` on ARM.
+    Semihosting,
     /// We were not able to distinguish if this was a hardware or software breakpoint.
     Unknown,
 }
 
+/// When the core halts due to a data watchpoint, some architectures will allow us to identify the watchpoint unit which triggered.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
+pub enum WatchpointCause {
+    /// The watchpoint configured in the given unit was hit.
+    Unit {
+        /// The index of the watchpoint unit.
+        unit: usize,
+        /// The data address the watchpoint unit is configured for.
+        address: u64,
+    },
+    /// We were not able to identify which watchpoint was hit.
+    Unknown,
+}
+
 /// The reason why a core was halted.
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
 pub enum HaltReason {
     /// Multiple reasons for a halt.
     ///
@@ -50,8 +67,12 @@ pub enum HaltReason {
     /// Core halted due to an exception, e.g. an
     /// an interrupt.
     Exception,
-    /// Core halted due to a data watchpoint
-    Watchpoint,
+    /// Core halted due to an exception, which was caused by a fault of the given kind.
+    ///
+    /// The details of the fault can be read with [`Core::fault_status`](crate::Core::fault_status).
+    Fault(FaultKind),
+    /// Core halted due to a data watchpoint. The cause is `Unknown` if we cannot identify the watchpoint which was hit.
+    Watchpoint(WatchpointCause),
     /// Core halted after single step
     Step,
     /// Core halted because of a debugger request
@@ -63,3 +84,87 @@ pub enum HaltReason {
     /// This can happen for example when the core is already halted when we connect.
     Unknown,
 }
+
+/// The kind of a fault which was raised by the core.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
+pub enum FaultKind {
+    /// A hard fault, which is raised directly and not escalated from another fault.
+    HardFault,
+    /// A memory management fault, caused by an access which violates the MPU configuration.
+    MemManage,
+    /// A bus fault, caused by an error response to a memory access.
+    BusFault,
+    /// A usage fault, caused by the execution of an instruction.
+    UsageFault,
+}
+
+impl std::fmt::Display for FaultKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FaultKind::HardFault => write!(f, "HardFault"),
+            FaultKind::MemManage => write!(f, "MemManage fault"),
+            FaultKind::BusFault => write!(f, "BusFault"),
+            FaultKind::UsageFault => write!(f, "UsageFault"),
+        }
+    }
+}
+
+/// A single fault cause reported by the core.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Fault {
+    /// The kind of the fault.
+    pub kind: FaultKind,
+    /// A description of the cause of the fault.
+    pub cause: &'static str,
+    /// The address of the access which caused the fault, if it is known.
+    pub address: Option<u64>,
+}
+
+impl std::fmt::Display for Fault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.kind, self.cause)?;
+
+        if let Some(address) = self.address {
+            write!(f, " at address {:#010x}", address)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The decoded fault status registers of a core.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct FaultStatus {
+    /// All fault causes which are currently flagged by the core.
+    pub faults: Vec<Fault>,
+    /// A fault was escalated to a hard fault, because it could not be handled by its own handler.
+    pub escalated: bool,
+}
+
+impl FaultStatus {
+    /// Returns `true` if any fault is flagged.
+    pub fn is_faulted(&self) -> bool {
+        !self.faults.is_empty()
+    }
+}
+
+impl std::fmt::Display for FaultStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.is_faulted() {
+            return write!(f, "No fault");
+        }
+
+        for (i, fault) in self.faults.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", fault)?;
+        }
+
+        if self.escalated {
+            write!(f, "\nThe fault was escalated to a HardFault")?;
+        }
+
+        Ok(())
+    }
+}
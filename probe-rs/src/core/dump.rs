@@ -0,0 +1,665 @@
+//! Snapshots of a core, which can be stored and analyzed offline.
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{Read, Write},
+    ops::Range,
+    path::Path,
+    time::Duration,
+};
+
+use anyhow::anyhow;
+use probe_rs_target::{
+    ArmCoreAccessOptions, CoreAccessOptions, RiscvCoreAccessOptions, XtensaCoreAccessOptions,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    architecture::{
+        arm::core::{
+            armv8a_core_regs::AARCH64_REGISTER_FILE, AARCH32_COMMON_REGS, AARCH32_FP_16_REGS,
+            AARCH32_FP_32_REGS, CORTEX_M_COMMON_REGS, CORTEX_M_WITH_FP_REGS,
+        },
+        riscv::register::{RISCV_REGISTERS, RISCV_WITH_DP_REGS, RISCV_WITH_FP_REGS},
+        xtensa::register::XTENSA_REGISTERS,
+    },
+    core::{RegisterFile, RegisterId, RegisterValue},
+    Architecture, Core, CoreInformation, CoreInterface, CoreState, CoreStatus, CoreType, Error,
+    HaltReason, InstructionSet, MemoryInterface, ResetType,
+};
+
+/// The magic bytes at the start of a stored core dump.
+const MAGIC: &[u8; 4] = b"PRCD";
+
+/// The version of the core dump format.
+const VERSION: u32 = 1;
+
+/// A snapshot of the registers and of selected memory of a core.
+///
+/// A core dump is created with [`Core::dump`](crate::Core::dump), and can be stored with
+/// [`CoreDump::store`] to analyze a failure later, without access to the target.
+/// The memory of a loaded core dump can be read with the [`MemoryInterface`],
+/// reading memory which was not captured results in an error.
+///
+/// ## File format
+///
+/// A stored core dump starts with the magic bytes `PRCD`, followed by the version of the
+/// format as little endian `u32`, currently `1`. The rest of the file is the
+/// [bincode](https://docs.rs/bincode) encoding of the `CoreDump`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CoreDump {
+    /// The type of the core.
+    pub core_type: CoreType,
+    /// The instruction set the core was executing.
+    pub instruction_set: InstructionSet,
+    /// The values of the core registers. Registers which could not be read are missing.
+    pub registers: BTreeMap<RegisterId, RegisterValue>,
+    /// The captured memory, as start address and contents of each region.
+    pub memory: Vec<(u64, Vec<u8>)>,
+}
+
+/// A core which is backed by a [`CoreDump`] instead of a probe.
+///
+/// The [`Core`] returned by [`OfflineCore::core`] can be used with the debug information,
+/// e.g. to unwind the stack and read variables, after the target is gone.
+/// The core is always halted. Its registers and the captured memory can be read,
+/// everything else, like running the core or writing to it, returns an error.
+///
+/// ## Example
+///
+/// ```no_run
+/// use probe_rs::{CoreDump, OfflineCore};
+///
+/// let mut offline = OfflineCore::new(CoreDump::load("crash.dump")?);
+/// let mut core = offline.core();
+///
+/// println!("Crashed at {:#010x}", core.read_core_reg::<u64>(core.registers().program_counter())?);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct OfflineCore {
+    dump: CoreDump,
+    state: CoreState,
+}
+
+impl OfflineCore {
+    /// Creates an offline core for `dump`.
+    pub fn new(dump: CoreDump) -> Self {
+        let options = match dump.core_type.architecture() {
+            Architecture::Arm => CoreAccessOptions::Arm(ArmCoreAccessOptions::default()),
+            Architecture::Riscv => CoreAccessOptions::Riscv(RiscvCoreAccessOptions::default()),
+            Architecture::Xtensa => CoreAccessOptions::Xtensa(XtensaCoreAccessOptions {}),
+        };
+
+        Self {
+            dump,
+            state: CoreState::new(0, options),
+        }
+    }
+
+    /// The core dump backing this core.
+    pub fn dump(&self) -> &CoreDump {
+        &self.dump
+    }
+
+    /// Returns a [`Core`] to inspect the core dump with.
+    pub fn core(&mut self) -> Core<'_> {
+        Core::new(
+            DumpCore {
+                dump: &mut self.dump,
+            },
+            &mut self.state,
+        )
+    }
+}
+
+/// The [`CoreInterface`] of an [`OfflineCore`].
+struct DumpCore<'a> {
+    dump: &'a mut CoreDump,
+}
+
+/// The error returned for operations which need a running target.
+fn offline() -> Error {
+    anyhow!("The operation is not supported by a core dump").into()
+}
+
+/// A finite list of all the errors that can occur when storing or loading a core dump.
+#[derive(Debug, thiserror::Error)]
+pub enum CoreDumpError {
+    /// The file is not a core dump.
+    #[error("The file is not a probe-rs core dump")]
+    InvalidFormat,
+    /// The core dump was stored with an unsupported version of the format.
+    #[error("The core dump format version {0} is not supported")]
+    UnsupportedVersion(u32),
+    /// Encoding or decoding the core dump has failed.
+    #[error("Could not encode or decode the core dump")]
+    Encoding(#[from] bincode::Error),
+    /// An IO error has occurred while reading or writing the file.
+    #[error("I/O error")]
+    IO(#[from] std::io::Error),
+}
+
+impl CoreDump {
+    /// Writes the core dump to the file at `path`.
+    pub fn store(&self, path: impl AsRef<Path>) -> Result<(), CoreDumpError> {
+        self.write_to(File::create(path)?)
+    }
+
+    /// Loads a core dump from the file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CoreDumpError> {
+        Self::read_from(File::open(path)?)
+    }
+
+    /// Writes the core dump to `writer`.
+    pub fn write_to(&self, mut writer: impl Write) -> Result<(), CoreDumpError> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        bincode::serialize_into(writer, self)?;
+
+        Ok(())
+    }
+
+    /// Reads a core dump from `reader`.
+    pub fn read_from(mut reader: impl Read) -> Result<Self, CoreDumpError> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(CoreDumpError::InvalidFormat);
+        }
+
+        let mut version = [0; 4];
+        reader.read_exact(&mut version)?;
+        let version = u32::from_le_bytes(version);
+        if version != VERSION {
+            return Err(CoreDumpError::UnsupportedVersion(version));
+        }
+
+        Ok(bincode::deserialize_from(reader)?)
+    }
+
+    /// Returns the captured value of the register `id`.
+    pub fn register(&self, id: impl Into<RegisterId>) -> Option<RegisterValue> {
+        self.registers.get(&id.into()).copied()
+    }
+
+    /// The address ranges of the captured memory.
+    pub fn memory_ranges(&self) -> impl Iterator<Item = Range<u64>> + '_ {
+        self.memory
+            .iter()
+            .map(|(address, data)| *address..*address + data.len() as u64)
+    }
+
+    /// The register file of the dumped core.
+    ///
+    /// The floating point registers are only part of the register file if they were captured
+    /// with the size the register file expects.
+    fn register_file(&self) -> &'static RegisterFile {
+        let candidates: &[&'static RegisterFile] = match self.core_type {
+            CoreType::Armv6m => &[&CORTEX_M_COMMON_REGS],
+            CoreType::Armv7m | CoreType::Armv7em | CoreType::Armv8m => {
+                &[&CORTEX_M_WITH_FP_REGS, &CORTEX_M_COMMON_REGS]
+            }
+            CoreType::Armv7a | CoreType::Armv7r => &[
+                &AARCH32_FP_32_REGS,
+                &AARCH32_FP_16_REGS,
+                &AARCH32_COMMON_REGS,
+            ],
+            CoreType::Armv8a if self.instruction_set == InstructionSet::A64 => {
+                &[&AARCH64_REGISTER_FILE]
+            }
+            CoreType::Armv8a => &[&AARCH32_FP_32_REGS],
+            CoreType::Riscv => &[&RISCV_WITH_DP_REGS, &RISCV_WITH_FP_REGS, &RISCV_REGISTERS],
+            CoreType::Xtensa => &[&XTENSA_REGISTERS],
+        };
+
+        let captured = |file: &RegisterFile| match file.fpu_registers() {
+            Some(mut registers) => registers.all(|description| {
+                self.register(description.id).map(value_size) == Some(description.size_in_bits())
+            }),
+            None => true,
+        };
+
+        candidates
+            .iter()
+            .copied()
+            .find(|file| captured(file))
+            .unwrap_or(candidates[candidates.len() - 1])
+    }
+
+    /// Copies the captured memory at `address` into `data`.
+    fn read_bytes(&self, address: u64, data: &mut [u8]) -> Result<(), Error> {
+        let end = address + data.len() as u64;
+
+        let (start, contents) = self
+            .memory
+            .iter()
+            .find(|(start, contents)| *start <= address && end <= *start + contents.len() as u64)
+            .ok_or_else(|| {
+                anyhow!(
+                    "The memory at {:#010x}..{:#010x} is not part of the core dump",
+                    address,
+                    end
+                )
+            })?;
+
+        let offset = (address - start) as usize;
+        data.copy_from_slice(&contents[offset..offset + data.len()]);
+
+        Ok(())
+    }
+}
+
+/// The size of a register value in bits.
+fn value_size(value: RegisterValue) -> usize {
+    match value {
+        RegisterValue::U32(_) => 32,
+        RegisterValue::U64(_) => 64,
+        RegisterValue::U128(_) => 128,
+    }
+}
+
+/// Reads `N` bytes at `address` from the core dump.
+fn read_array<const N: usize>(dump: &CoreDump, address: u64) -> Result<[u8; N], Error> {
+    let mut bytes = [0; N];
+    dump.read_bytes(address, &mut bytes)?;
+
+    Ok(bytes)
+}
+
+/// The error returned when writing to a core dump.
+fn read_only() -> Error {
+    anyhow!("A core dump can't be written").into()
+}
+
+impl MemoryInterface for CoreDump {
+    fn supports_native_64bit_access(&mut self) -> bool {
+        true
+    }
+
+    fn read_word_64(&mut self, address: u64) -> Result<u64, Error> {
+        Ok(u64::from_le_bytes(read_array(self, address)?))
+    }
+
+    fn read_word_32(&mut self, address: u64) -> Result<u32, Error> {
+        Ok(u32::from_le_bytes(read_array(self, address)?))
+    }
+
+    fn read_word_16(&mut self, address: u64) -> Result<u16, Error> {
+        Ok(u16::from_le_bytes(read_array(self, address)?))
+    }
+
+    fn read_word_8(&mut self, address: u64) -> Result<u8, Error> {
+        Ok(read_array::<1>(self, address)?[0])
+    }
+
+    fn read_64(&mut self, address: u64, data: &mut [u64]) -> Result<(), Error> {
+        for (i, word) in data.iter_mut().enumerate() {
+            *word = self.read_word_64(address + i as u64 * 8)?;
+        }
+
+        Ok(())
+    }
+
+    fn read_32(&mut self, address: u64, data: &mut [u32]) -> Result<(), Error> {
+        for (i, word) in data.iter_mut().enumerate() {
+            *word = self.read_word_32(address + i as u64 * 4)?;
+        }
+
+        Ok(())
+    }
+
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), Error> {
+        for (i, word) in data.iter_mut().enumerate() {
+            *word = self.read_word_16(address + i as u64 * 2)?;
+        }
+
+        Ok(())
+    }
+
+    fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error> {
+        self.read_bytes(address, data)
+    }
+
+    fn read(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error> {
+        self.read_bytes(address, data)
+    }
+
+    fn write_word_64(&mut self, _address: u64, _data: u64) -> Result<(), Error> {
+        Err(read_only())
+    }
+
+    fn write_word_32(&mut self, _address: u64, _data: u32) -> Result<(), Error> {
+        Err(read_only())
+    }
+
+    fn write_word_16(&mut self, _address: u64, _data: u16) -> Result<(), Error> {
+        Err(read_only())
+    }
+
+    fn write_word_8(&mut self, _address: u64, _data: u8) -> Result<(), Error> {
+        Err(read_only())
+    }
+
+    fn write_64(&mut self, _address: u64, _data: &[u64]) -> Result<(), Error> {
+        Err(read_only())
+    }
+
+    fn write_32(&mut self, _address: u64, _data: &[u32]) -> Result<(), Error> {
+        Err(read_only())
+    }
+
+    fn write_16(&mut self, _address: u64, _data: &[u16]) -> Result<(), Error> {
+        Err(read_only())
+    }
+
+    fn write_8(&mut self, _address: u64, _data: &[u8]) -> Result<(), Error> {
+        Err(read_only())
+    }
+
+    fn write(&mut self, _address: u64, _data: &[u8]) -> Result<(), Error> {
+        Err(read_only())
+    }
+
+    fn supports_8bit_transfers(&self) -> Result<bool, Error> {
+        Ok(true)
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl CoreInterface for DumpCore<'_> {
+    fn wait_for_core_halted(&mut self, _timeout: Duration) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn core_halted(&mut self) -> Result<bool, Error> {
+        Ok(true)
+    }
+
+    fn status(&mut self) -> Result<CoreStatus, Error> {
+        Ok(CoreStatus::Halted(HaltReason::Unknown))
+    }
+
+    fn halt(&mut self, _timeout: Duration) -> Result<CoreInformation, Error> {
+        let pc = self.read_core_reg(self.registers().program_counter().id)?;
+
+        Ok(CoreInformation { pc: pc.try_into()? })
+    }
+
+    fn run(&mut self) -> Result<(), Error> {
+        Err(offline())
+    }
+
+    fn reset(&mut self, _reset_type: ResetType) -> Result<(), Error> {
+        Err(offline())
+    }
+
+    fn reset_and_halt(
+        &mut self,
+        _reset_type: ResetType,
+        _timeout: Duration,
+    ) -> Result<CoreInformation, Error> {
+        Err(offline())
+    }
+
+    fn step(&mut self) -> Result<CoreInformation, Error> {
+        Err(offline())
+    }
+
+    fn read_core_reg(&mut self, address: RegisterId) -> Result<RegisterValue, Error> {
+        self.dump
+            .register(address)
+            .ok_or_else(|| anyhow!("Register {:?} is not part of the core dump", address).into())
+    }
+
+    fn write_core_reg(&mut self, _address: RegisterId, _value: RegisterValue) -> Result<(), Error> {
+        Err(read_only())
+    }
+
+    fn available_breakpoint_units(&mut self) -> Result<u32, Error> {
+        Ok(0)
+    }
+
+    fn hw_breakpoints(&mut self) -> Result<Vec<Option<u64>>, Error> {
+        Ok(Vec::new())
+    }
+
+    fn enable_breakpoints(&mut self, _state: bool) -> Result<(), Error> {
+        Err(offline())
+    }
+
+    fn set_hw_breakpoint(&mut self, _unit_index: usize, _addr: u64) -> Result<(), Error> {
+        Err(offline())
+    }
+
+    fn clear_hw_breakpoint(&mut self, _unit_index: usize) -> Result<(), Error> {
+        Err(offline())
+    }
+
+    fn registers(&self) -> &'static RegisterFile {
+        self.dump.register_file()
+    }
+
+    fn hw_breakpoints_enabled(&self) -> bool {
+        false
+    }
+
+    fn architecture(&self) -> Architecture {
+        self.dump.core_type.architecture()
+    }
+
+    fn core_type(&self) -> CoreType {
+        self.dump.core_type
+    }
+
+    fn instruction_set(&mut self) -> Result<InstructionSet, Error> {
+        Ok(self.dump.instruction_set)
+    }
+
+    fn fpu_support(&mut self) -> Result<bool, Error> {
+        Ok(self.registers().fpu_registers().is_some())
+    }
+}
+
+impl MemoryInterface for DumpCore<'_> {
+    fn supports_native_64bit_access(&mut self) -> bool {
+        self.dump.supports_native_64bit_access()
+    }
+
+    fn read_word_64(&mut self, address: u64) -> Result<u64, Error> {
+        self.dump.read_word_64(address)
+    }
+
+    fn read_word_32(&mut self, address: u64) -> Result<u32, Error> {
+        self.dump.read_word_32(address)
+    }
+
+    fn read_word_16(&mut self, address: u64) -> Result<u16, Error> {
+        self.dump.read_word_16(address)
+    }
+
+    fn read_word_8(&mut self, address: u64) -> Result<u8, Error> {
+        self.dump.read_word_8(address)
+    }
+
+    fn read_64(&mut self, address: u64, data: &mut [u64]) -> Result<(), Error> {
+        self.dump.read_64(address, data)
+    }
+
+    fn read_32(&mut self, address: u64, data: &mut [u32]) -> Result<(), Error> {
+        self.dump.read_32(address, data)
+    }
+
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), Error> {
+        self.dump.read_16(address, data)
+    }
+
+    fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error> {
+        self.dump.read_8(address, data)
+    }
+
+    fn read(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error> {
+        self.dump.read(address, data)
+    }
+
+    fn write_word_64(&mut self, address: u64, data: u64) -> Result<(), Error> {
+        self.dump.write_word_64(address, data)
+    }
+
+    fn write_word_32(&mut self, address: u64, data: u32) -> Result<(), Error> {
+        self.dump.write_word_32(address, data)
+    }
+
+    fn write_word_16(&mut self, address: u64, data: u16) -> Result<(), Error> {
+        self.dump.write_word_16(address, data)
+    }
+
+    fn write_word_8(&mut self, address: u64, data: u8) -> Result<(), Error> {
+        self.dump.write_word_8(address, data)
+    }
+
+    fn write_64(&mut self, address: u64, data: &[u64]) -> Result<(), Error> {
+        self.dump.write_64(address, data)
+    }
+
+    fn write_32(&mut self, address: u64, data: &[u32]) -> Result<(), Error> {
+        self.dump.write_32(address, data)
+    }
+
+    fn write_16(&mut self, address: u64, data: &[u16]) -> Result<(), Error> {
+        self.dump.write_16(address, data)
+    }
+
+    fn write_8(&mut self, address: u64, data: &[u8]) -> Result<(), Error> {
+        self.dump.write_8(address, data)
+    }
+
+    fn write(&mut self, address: u64, data: &[u8]) -> Result<(), Error> {
+        self.dump.write(address, data)
+    }
+
+    fn supports_8bit_transfers(&self) -> Result<bool, Error> {
+        self.dump.supports_8bit_transfers()
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.dump.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use super::{CoreDump, CoreDumpError, OfflineCore};
+    use crate::{
+        core::{RegisterId, RegisterValue},
+        CoreStatus, CoreType, HaltReason, InstructionSet, MemoryInterface,
+    };
+
+    fn dump() -> CoreDump {
+        let mut registers = BTreeMap::new();
+        registers.insert(RegisterId(15), RegisterValue::U32(0x0800_0100));
+        registers.insert(RegisterId(0x50), RegisterValue::U64(0x1234_5678_9abc_def0));
+
+        CoreDump {
+            core_type: CoreType::Armv7em,
+            instruction_set: InstructionSet::Thumb2,
+            registers,
+            memory: vec![(0x2000_0000, (0..16).collect())],
+        }
+    }
+
+    #[test]
+    fn store_and_load() {
+        let dump = dump();
+
+        let mut stored = Vec::new();
+        dump.write_to(&mut stored).unwrap();
+
+        assert_eq!(&stored[..4], b"PRCD");
+        assert_eq!(CoreDump::read_from(&stored[..]).unwrap(), dump);
+    }
+
+    #[test]
+    fn load_invalid_file() {
+        assert!(matches!(
+            CoreDump::read_from(&b"\x7fELF\x01\x00\x00\x00"[..]),
+            Err(CoreDumpError::InvalidFormat)
+        ));
+        assert!(matches!(
+            CoreDump::read_from(&b"PRCD\x02\x00\x00\x00"[..]),
+            Err(CoreDumpError::UnsupportedVersion(2))
+        ));
+    }
+
+    #[test]
+    fn read_captured_memory() {
+        let mut dump = dump();
+
+        assert_eq!(
+            dump.register(RegisterId(15)),
+            Some(RegisterValue::U32(0x0800_0100))
+        );
+        assert_eq!(dump.read_word_32(0x2000_0004).unwrap(), 0x0706_0504);
+
+        let mut data = [0; 3];
+        dump.read(0x2000_000d, &mut data).unwrap();
+        assert_eq!(data, [13, 14, 15]);
+
+        assert!(dump.read_word_32(0x2000_000e).is_err());
+        assert!(dump.write_word_8(0x2000_0000, 0).is_err());
+    }
+
+    #[test]
+    fn offline_core() {
+        let mut offline = OfflineCore::new(dump());
+        let mut core = offline.core();
+
+        assert_eq!(
+            core.status().unwrap(),
+            CoreStatus::Halted(HaltReason::Unknown)
+        );
+        // No floating point registers were captured.
+        assert!(core.registers().fpu_registers().is_none());
+
+        let pc: u32 = core
+            .read_core_reg(core.registers().program_counter())
+            .unwrap();
+        assert_eq!(pc, 0x0800_0100);
+        assert_eq!(core.read_word_32(0x2000_0008).unwrap(), 0x0b0a_0908);
+
+        assert!(core
+            .read_core_reg::<u32>(core.registers().stack_pointer())
+            .is_err());
+        assert!(core.run().is_err());
+        assert!(core.write_word_32(0x2000_0000, 0).is_err());
+    }
+
+    #[test]
+    fn read_partial_memory() {
+        let mut dump = dump();
+        dump.memory.push((0x2000_0018, (24..32).collect()));
+
+        let mut data = [0xff; 0x1c];
+        let faults = dump.read_partial(0x2000_0002, &mut data).unwrap();
+
+        assert_eq!(faults, vec![0x2000_0010..0x2000_0018]);
+        assert_eq!(&data[..14], &(2..16).collect::<Vec<u8>>()[..]);
+        assert_eq!(&data[14..22], &[0; 8]);
+        assert_eq!(&data[22..], &(24..30).collect::<Vec<u8>>()[..]);
+    }
+
+    #[test]
+    fn dump_leaves_out_faulted_memory() {
+        let mut offline = OfflineCore::new(dump());
+        let range = 0x2000_0008..0x2000_0020;
+        let dump = offline.core().dump(&[range]).unwrap();
+
+        assert_eq!(dump.memory, vec![(0x2000_0008, (8..16).collect())]);
+    }
+}
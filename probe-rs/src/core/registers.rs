@@ -2,6 +2,7 @@
 
 use crate::Error;
 use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
 use std::{cmp::Ordering, convert::Infallible};
 
 /// The type of data stored in a register
@@ -65,7 +66,7 @@ impl From<&RegisterDescription> for RegisterId {
 }
 
 /// The location of a CPU \register. This is not an actual memory address, but a core specific location that represents a specific core register.
-#[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Ord, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Ord, Eq, Hash, Serialize, Deserialize)]
 pub struct RegisterId(pub u16);
 
 impl From<RegisterId> for u32 {
@@ -92,7 +93,7 @@ pub(crate) enum RegisterKind {
 /// Creating a new `RegisterValue` should be done using From or Into.
 /// Converting a value back to a primitive type can be done with either
 /// a match arm or TryInto
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum RegisterValue {
     /// 32-bit unsigned integer
     U32(u32),
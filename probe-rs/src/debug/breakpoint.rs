@@ -0,0 +1,169 @@
+use super::{DebugError, DebugInfo, VerifiedBreakpoint};
+use crate::core::Core;
+use std::path::PathBuf;
+
+/// A breakpoint which is requested in terms of the program source, instead of an address.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SourceBreakpoint {
+    /// Halt when the function with the given name is called.
+    ///
+    /// See [`DebugInfo::get_function_breakpoint_locations`] for the supported names.
+    Function(String),
+    /// Halt at a line, and optionally a column, in a source file.
+    Location {
+        /// The path of the source file.
+        path: PathBuf,
+        /// The line in the source file.
+        line: u64,
+        /// The column in the source file.
+        column: Option<u64>,
+    },
+}
+
+impl SourceBreakpoint {
+    /// Resolve the breakpoint to all the addresses where it has to be set.
+    ///
+    /// The result is empty if the program has no code for the breakpoint.
+    pub fn resolve(&self, debug_info: &DebugInfo) -> Result<Vec<VerifiedBreakpoint>, DebugError> {
+        match self {
+            SourceBreakpoint::Function(function_name) => {
+                debug_info.get_function_breakpoint_locations(function_name)
+            }
+            SourceBreakpoint::Location { path, line, column } => {
+                debug_info.get_breakpoint_locations(path, *line, *column)
+            }
+        }
+    }
+}
+
+/// A set of [`SourceBreakpoint`]s, and the hardware breakpoints which are set on a core for them.
+///
+/// The breakpoints are tied to the program they were resolved with. After a new program is flashed,
+/// [`SourceBreakpoints::resolve_all`] moves the hardware breakpoints to the locations in the new program.
+#[derive(Debug, Default)]
+pub struct SourceBreakpoints {
+    breakpoints: Vec<(SourceBreakpoint, Vec<VerifiedBreakpoint>)>,
+}
+
+impl SourceBreakpoints {
+    /// Create an empty set of breakpoints.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolve the `breakpoint`, and set a hardware breakpoint at each of its locations.
+    ///
+    /// Returns the resolved locations. A breakpoint without any locations is kept,
+    /// and resolved again by [`SourceBreakpoints::resolve_all`].
+    /// If the breakpoint was set before, it is replaced.
+    pub fn set(
+        &mut self,
+        core: &mut Core,
+        debug_info: &DebugInfo,
+        breakpoint: SourceBreakpoint,
+    ) -> Result<Vec<VerifiedBreakpoint>, DebugError> {
+        self.clear(core, &breakpoint)?;
+
+        let locations = breakpoint.resolve(debug_info)?;
+        for location in &locations {
+            core.set_hw_breakpoint(location.address)?;
+        }
+
+        self.breakpoints.push((breakpoint, locations.clone()));
+
+        Ok(locations)
+    }
+
+    /// Clear the hardware breakpoints of `breakpoint`, and remove it from the set.
+    ///
+    /// Locations which are shared with another breakpoint in the set stay active.
+    pub fn clear(
+        &mut self,
+        core: &mut Core,
+        breakpoint: &SourceBreakpoint,
+    ) -> Result<(), DebugError> {
+        let Some(position) = self
+            .breakpoints
+            .iter()
+            .position(|(existing, _)| existing == breakpoint)
+        else {
+            return Ok(());
+        };
+
+        let (_, locations) = self.breakpoints.remove(position);
+        for location in locations {
+            if !self.is_active(location.address) {
+                core.clear_hw_breakpoint(location.address)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Clear the hardware breakpoints of all breakpoints, and remove them from the set.
+    pub fn clear_all(&mut self, core: &mut Core) -> Result<(), DebugError> {
+        while let Some((breakpoint, _)) = self.breakpoints.last().cloned() {
+            self.clear(core, &breakpoint)?;
+        }
+
+        Ok(())
+    }
+
+    /// Resolve all breakpoints again using `debug_info`, and move the hardware breakpoints to the new locations.
+    ///
+    /// This has to be called after a new program was flashed, with the debug information of the new program.
+    pub fn resolve_all(
+        &mut self,
+        core: &mut Core,
+        debug_info: &DebugInfo,
+    ) -> Result<(), DebugError> {
+        for (_, locations) in &mut self.breakpoints {
+            for location in locations.drain(..) {
+                // The breakpoint may have been cleared already, if the location was shared.
+                if let Err(error) = core.clear_hw_breakpoint(location.address) {
+                    tracing::debug!(
+                        "Breakpoint at {:#010x} was already cleared: {}",
+                        location.address,
+                        error
+                    );
+                }
+            }
+        }
+
+        for (breakpoint, locations) in &mut self.breakpoints {
+            *locations = breakpoint.resolve(debug_info)?;
+            for location in locations.iter() {
+                core.set_hw_breakpoint(location.address)?;
+            }
+            tracing::debug!(
+                "Resolved breakpoint {:?} to {} location(s)",
+                breakpoint,
+                locations.len()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// The locations of `breakpoint`, if it is part of the set.
+    pub fn locations(&self, breakpoint: &SourceBreakpoint) -> Option<&[VerifiedBreakpoint]> {
+        self.breakpoints
+            .iter()
+            .find(|(existing, _)| existing == breakpoint)
+            .map(|(_, locations)| locations.as_slice())
+    }
+
+    /// Iterate over all breakpoints in the set, and their locations.
+    pub fn iter(&self) -> impl Iterator<Item = (&SourceBreakpoint, &[VerifiedBreakpoint])> {
+        self.breakpoints
+            .iter()
+            .map(|(breakpoint, locations)| (breakpoint, locations.as_slice()))
+    }
+
+    /// Check if any breakpoint in the set has a location at `address`.
+    fn is_active(&self, address: u64) -> bool {
+        self.breakpoints
+            .iter()
+            .any(|(_, locations)| locations.iter().any(|location| location.address == address))
+    }
+}
@@ -0,0 +1,294 @@
+use super::{DebugError, DebugInfo, Expression, StackFrame};
+use crate::{core::Core, CoreStatus, FaultStatus, HaltReason};
+use std::str::FromStr;
+
+/// The symbol of the Rust panic handler, which is called with the `PanicInfo` on every panic.
+const PANIC_HANDLER: &str = "rust_begin_unwind";
+
+/// The symbols of the HardFault handler and its trampoline, as defined by `cortex-m-rt`.
+const FAULT_HANDLERS: &[&str] = &["HardFault", "HardFault_", "HardFaultTrampoline"];
+
+/// The maximum number of message pieces which are read from the `fmt::Arguments` of a panic.
+const MAX_MESSAGE_PIECES: u64 = 16;
+
+/// The location and message of a Rust panic.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PanicReport {
+    /// The panic message.
+    ///
+    /// The values of formatting arguments can't be formatted without running code on the target,
+    /// so they are replaced with `{}`. For `panic!("index {} out of range", i)`, this is
+    /// `index {} out of range`.
+    pub message: Option<String>,
+    /// The source file where the panic occurred.
+    pub file: Option<String>,
+    /// The line in the source file.
+    pub line: Option<u64>,
+    /// The column in the source file.
+    pub column: Option<u64>,
+}
+
+impl std::fmt::Display for PanicReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Panicked")?;
+
+        if let Some(file) = &self.file {
+            write!(f, " at {file}")?;
+
+            if let Some(line) = self.line {
+                write!(f, ":{line}")?;
+
+                if let Some(column) = self.column {
+                    write!(f, ":{column}")?;
+                }
+            }
+        }
+
+        if let Some(message) = &self.message {
+            write!(f, ": {message}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A HardFault, with the location of the faulting instruction and the decoded fault status registers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FaultReport {
+    /// The address of the instruction which caused the fault, if the stack could be unwound past the fault handler.
+    pub pc: Option<u64>,
+    /// The decoded fault status registers. These are empty on cores without fault status registers, e.g. ARMv6-M.
+    pub status: FaultStatus,
+}
+
+impl std::fmt::Display for FaultReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "HardFault")?;
+
+        if let Some(pc) = self.pc {
+            write!(f, " at {pc:#010x}")?;
+        }
+
+        if self.status.is_faulted() {
+            write!(f, "\n{}", self.status)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The cause of a crash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CrashCause {
+    /// The program panicked.
+    Panic(PanicReport),
+    /// The core took a HardFault.
+    HardFault(FaultReport),
+}
+
+impl std::fmt::Display for CrashCause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CrashCause::Panic(report) => report.fmt(f),
+            CrashCause::HardFault(report) => report.fmt(f),
+        }
+    }
+}
+
+/// A report of a panic or HardFault of the program running on a halted core.
+#[derive(Debug)]
+pub struct CrashReport {
+    /// What caused the crash.
+    pub cause: CrashCause,
+    /// The stack at the time the core was halted, with the innermost frame first.
+    pub backtrace: Vec<StackFrame>,
+}
+
+impl CrashReport {
+    /// Check if the halted `core` has crashed, and create a report about it.
+    ///
+    /// A panic is detected if the panic handler (`rust_begin_unwind`) is on the stack.
+    /// A HardFault is detected if the core is halted in the HardFault handler, or if it was halted
+    /// because of a fault, e.g. by a vector catch.
+    ///
+    /// Returns `None` if the core is not halted because of a crash.
+    pub fn from_core(
+        core: &mut Core<'_>,
+        debug_info: &DebugInfo,
+    ) -> Result<Option<Self>, DebugError> {
+        let status = core.status()?;
+        if !status.is_halted() {
+            return Ok(None);
+        }
+
+        let program_counter: u64 = core.read_core_reg(core.registers().program_counter())?;
+        let mut backtrace = debug_info.unwind(core, program_counter)?;
+
+        let symbol_names = backtrace
+            .iter()
+            .map(|frame| {
+                let pc: u64 = frame.pc.try_into().ok()?;
+                debug_info
+                    .symbols()
+                    .symbol_at(pc)
+                    .map(|symbol| symbol.mangled_name.clone())
+            })
+            .collect::<Vec<_>>();
+
+        if let Some(index) = symbol_names
+            .iter()
+            .position(|name| name.as_deref() == Some(PANIC_HANDLER))
+        {
+            let report = panic_report(core, debug_info, &mut backtrace[index]);
+
+            return Ok(Some(CrashReport {
+                cause: CrashCause::Panic(report),
+                backtrace,
+            }));
+        }
+
+        let in_fault_handler = symbol_names
+            .iter()
+            .position(|name| matches!(name, Some(name) if FAULT_HANDLERS.contains(&name.as_str())));
+
+        if in_fault_handler.is_some() || matches!(status, CoreStatus::Halted(HaltReason::Fault(_)))
+        {
+            // The first frame after the fault handler is the one which caused the fault.
+            let pc = match in_fault_handler {
+                Some(handler) => symbol_names
+                    .iter()
+                    .enumerate()
+                    .skip(handler)
+                    .find(|(_, name)| !matches!(name, Some(name) if FAULT_HANDLERS.contains(&name.as_str())))
+                    .and_then(|(index, _)| backtrace[index].pc.try_into().ok()),
+                None => Some(program_counter),
+            };
+
+            // ARMv6-M and non-ARM cores don't have fault status registers.
+            let status = core.fault_status().unwrap_or_else(|error| {
+                tracing::debug!("Unable to read the fault status: {}", error);
+                FaultStatus::default()
+            });
+
+            return Ok(Some(CrashReport {
+                cause: CrashCause::HardFault(FaultReport { pc, status }),
+                backtrace,
+            }));
+        }
+
+        Ok(None)
+    }
+}
+
+impl std::fmt::Display for CrashReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.cause)?;
+
+        for (index, frame) in self.backtrace.iter().enumerate() {
+            write!(f, "{index:>4}: {} @ {}", frame.function_name, frame.pc)?;
+
+            if let Some(location) = &frame.source_location {
+                if let (Some(file), Some(line)) = (&location.file, location.line) {
+                    write!(f, " ({file}:{line})")?;
+                }
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Read the panic location and message from the `info: &PanicInfo` argument of the panic handler.
+fn panic_report(
+    core: &mut Core<'_>,
+    debug_info: &DebugInfo,
+    frame: &mut StackFrame,
+) -> PanicReport {
+    let mut evaluate = |expression: &str| -> Option<String> {
+        let value = Expression::from_str(expression)
+            .and_then(|expression| expression.evaluate(core, debug_info, frame))
+            .map_err(|error| tracing::debug!("Unable to evaluate '{}': {}", expression, error))
+            .ok()?;
+
+        Some(value.value).filter(|value| !value.is_empty() && !value.starts_with("Error"))
+    };
+
+    let file = evaluate("info.location.file");
+    let line = evaluate("info.location.line").and_then(|line| line.parse().ok());
+    let column = evaluate("info.location.col").and_then(|column| column.parse().ok());
+
+    // The message is an `Option<&fmt::Arguments>` in older versions of `core`.
+    let message = ["info.message.__0.pieces", "info.message.pieces"]
+        .iter()
+        .find_map(|pieces| {
+            let pieces = (0..MAX_MESSAGE_PIECES)
+                .map_while(|index| evaluate(&format!("{pieces}[{index}]")))
+                .collect::<Vec<_>>();
+
+            (!pieces.is_empty()).then(|| format_message(&pieces))
+        });
+
+    PanicReport {
+        message,
+        file,
+        line,
+        column,
+    }
+}
+
+/// Combine the static pieces of a `fmt::Arguments`, with placeholders for the arguments between them.
+fn format_message(pieces: &[String]) -> String {
+    pieces.join("{}")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{format_message, FaultReport, PanicReport};
+    use crate::{Fault, FaultKind, FaultStatus};
+
+    #[test]
+    fn message_with_arguments() {
+        let pieces = ["index ".to_string(), " out of range".to_string()];
+
+        assert_eq!(format_message(&pieces), "index {} out of range");
+        assert_eq!(format_message(&pieces[..1]), "index ");
+    }
+
+    #[test]
+    fn display_panic() {
+        let report = PanicReport {
+            message: Some("explicit panic".to_string()),
+            file: Some("src/main.rs".to_string()),
+            line: Some(12),
+            column: Some(5),
+        };
+
+        assert_eq!(
+            report.to_string(),
+            "Panicked at src/main.rs:12:5: explicit panic"
+        );
+        assert_eq!(PanicReport::default().to_string(), "Panicked");
+    }
+
+    #[test]
+    fn display_fault() {
+        let report = FaultReport {
+            pc: Some(0x0800_0400),
+            status: FaultStatus {
+                faults: vec![Fault {
+                    kind: FaultKind::UsageFault,
+                    cause: "Division by zero",
+                    address: None,
+                }],
+                escalated: true,
+            },
+        };
+
+        assert_eq!(
+            report.to_string(),
+            "HardFault at 0x08000400\nUsageFault: Division by zero\nThe fault was escalated to a HardFault"
+        );
+    }
+}
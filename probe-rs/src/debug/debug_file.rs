@@ -0,0 +1,245 @@
+use super::{debug_info::DwarfReader, DebugError};
+use gimli::Reader;
+use object::read::{CompressionFormat, Object, ObjectSection};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    io::Read,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+/// The directory where distributions install external debug files.
+const GLOBAL_DEBUG_DIRECTORY: &str = "/usr/lib/debug";
+
+/// Get the (uncompressed) data of a section.
+///
+/// Sections compressed with `SHF_COMPRESSED` (zlib or zstd), as well as sections using the legacy
+/// GNU `.zdebug_*` format, are decompressed. A missing section results in empty data.
+pub(crate) fn section_data<'data>(
+    object: &object::File<'data>,
+    name: &str,
+) -> Result<Cow<'data, [u8]>, DebugError> {
+    let section = object.section_by_name(name).or_else(|| {
+        name.strip_prefix(".debug_")
+            .and_then(|suffix| object.section_by_name(&format!(".zdebug_{suffix}")))
+    });
+
+    let Some(section) = section else {
+        return Ok(Cow::Borrowed(&[][..]));
+    };
+
+    let compressed = section.compressed_data()?;
+
+    match compressed.format {
+        CompressionFormat::None => Ok(Cow::Borrowed(compressed.data)),
+        CompressionFormat::Zlib => {
+            let mut data = Vec::with_capacity(compressed.uncompressed_size as usize);
+            flate2::read::ZlibDecoder::new(compressed.data).read_to_end(&mut data)?;
+            Ok(Cow::Owned(data))
+        }
+        CompressionFormat::Zstandard => Ok(Cow::Owned(zstd::stream::decode_all(compressed.data)?)),
+        other => Err(DebugError::Other(anyhow::anyhow!(
+            "Section {} uses an unsupported compression format: {:?}",
+            name,
+            other
+        ))),
+    }
+}
+
+/// Load the DWARF sections of an object file.
+///
+/// If `dwo` is set, the sections of a split DWARF object (`.debug_info.dwo` etc.) are loaded.
+pub(crate) fn load_dwarf(
+    object: &object::File<'_>,
+    dwo: bool,
+) -> Result<gimli::Dwarf<DwarfReader>, DebugError> {
+    gimli::Dwarf::load(|id| load_section(object, id, dwo))
+}
+
+fn load_section(
+    object: &object::File<'_>,
+    id: gimli::SectionId,
+    dwo: bool,
+) -> Result<DwarfReader, DebugError> {
+    let name = if dwo { id.dwo_name() } else { Some(id.name()) };
+
+    let data = match name {
+        Some(name) => section_data(object, name)?,
+        None => Cow::Borrowed(&[][..]),
+    };
+
+    Ok(gimli::read::EndianRcSlice::new(
+        Rc::from(&*data),
+        gimli::LittleEndian,
+    ))
+}
+
+/// Find the external debug file referenced by the `.gnu_debuglink` section of `object`.
+///
+/// The same locations as GDB are searched: the directory of the object file, its `.debug`
+/// subdirectory, and the global debug directory. Only files with a matching CRC are accepted.
+pub(crate) fn find_debuglink(object: &object::File<'_>, path: &Path) -> Option<PathBuf> {
+    let (file_name, crc) = object.gnu_debuglink().ok().flatten()?;
+    let file_name = Path::new(std::str::from_utf8(file_name).ok()?);
+
+    let directory = path.parent().unwrap_or_else(|| Path::new(""));
+    let absolute_directory = directory
+        .canonicalize()
+        .unwrap_or_else(|_| directory.to_path_buf());
+
+    let candidates = [
+        directory.join(file_name),
+        directory.join(".debug").join(file_name),
+        Path::new(GLOBAL_DEBUG_DIRECTORY)
+            .join(
+                absolute_directory
+                    .strip_prefix("/")
+                    .unwrap_or(&absolute_directory),
+            )
+            .join(file_name),
+    ];
+
+    candidates.into_iter().find(|candidate| {
+        // The debug link can point to the file itself, if the debug information was not stripped.
+        if candidate.canonicalize().ok() == path.canonicalize().ok() {
+            return false;
+        }
+
+        match std::fs::read(candidate) {
+            Ok(data) => {
+                let mut hasher = flate2::Crc::new();
+                hasher.update(&data);
+
+                if hasher.sum() == crc {
+                    true
+                } else {
+                    tracing::warn!(
+                        "Ignoring debug file {}, its CRC does not match the debug link.",
+                        candidate.display()
+                    );
+                    false
+                }
+            }
+            Err(_) => false,
+        }
+    })
+}
+
+/// Load the split DWARF units for all skeleton units in `dwarf`.
+///
+/// A DWARF package (`<path>.dwp`) is preferred. Otherwise, the `.dwo` file named by each skeleton
+/// unit is searched in its compilation directory, and in the directory of the object file.
+///
+/// The returned map is indexed by the offset of the skeleton unit.
+pub(crate) fn load_split_units(
+    dwarf: &gimli::Dwarf<DwarfReader>,
+    path: Option<&Path>,
+) -> Result<HashMap<gimli::DebugInfoOffset, gimli::Dwarf<DwarfReader>>, DebugError> {
+    let mut split_units = HashMap::new();
+
+    let mut package = None;
+
+    if let Some(package_path) = path.map(dwp_path) {
+        if let Ok(data) = std::fs::read(&package_path) {
+            let object = object::File::parse(&*data)?;
+            let empty = gimli::read::EndianRcSlice::new(Rc::from(&[][..]), gimli::LittleEndian);
+
+            package = Some(gimli::DwarfPackage::load(
+                |id| load_section(&object, id, true),
+                empty,
+            )?);
+        }
+    }
+
+    let mut units = dwarf.units();
+
+    while let Some(header) = units.next()? {
+        let Some(offset) = header.offset().as_debug_info_offset() else {
+            continue;
+        };
+        let unit = dwarf.unit(header)?;
+        let Some(dwo_id) = unit.dwo_id else {
+            continue;
+        };
+
+        if let Some(package) = &package {
+            if let Some(split_dwarf) = package.find_cu(dwo_id, dwarf)? {
+                split_units.insert(offset, split_dwarf);
+                continue;
+            }
+        }
+
+        match load_dwo_file(dwarf, &unit, path)? {
+            Some(split_dwarf) => {
+                split_units.insert(offset, split_dwarf);
+            }
+            None => tracing::warn!(
+                "Unable to find the split DWARF object for unit {:?} ({:#x}).",
+                offset,
+                dwo_id.0
+            ),
+        }
+    }
+
+    Ok(split_units)
+}
+
+/// The conventional path of the DWARF package for an object file, `<path>.dwp`.
+fn dwp_path(path: &Path) -> PathBuf {
+    let mut file_name = path.as_os_str().to_owned();
+    file_name.push(".dwp");
+    PathBuf::from(file_name)
+}
+
+fn load_dwo_file(
+    dwarf: &gimli::Dwarf<DwarfReader>,
+    unit: &gimli::Unit<DwarfReader>,
+    path: Option<&Path>,
+) -> Result<Option<gimli::Dwarf<DwarfReader>>, DebugError> {
+    let Some(dwo_name) = unit.dwo_name()? else {
+        return Ok(None);
+    };
+    let dwo_name = dwarf.attr_string(unit, dwo_name)?;
+    let dwo_name = Path::new(std::str::from_utf8(&dwo_name)?);
+
+    let mut candidates = Vec::new();
+
+    if let Some(comp_dir) = &unit.comp_dir {
+        candidates.push(Path::new(std::str::from_utf8(&comp_dir.to_slice()?)?).join(dwo_name));
+    }
+
+    // The build directory might not exist on this machine, so also look next to the object file.
+    if let Some(directory) = path.and_then(Path::parent) {
+        candidates.push(directory.join(dwo_name));
+
+        if let Some(file_name) = dwo_name.file_name() {
+            candidates.push(directory.join(file_name));
+        }
+    }
+
+    for candidate in candidates {
+        let Ok(data) = std::fs::read(&candidate) else {
+            continue;
+        };
+
+        let object = object::File::parse(&*data)?;
+        let mut split_dwarf = load_dwarf(&object, true)?;
+        split_dwarf.make_dwo(dwarf);
+
+        // Verify that the split unit belongs to the skeleton unit.
+        let mut split_units = split_dwarf.units();
+        if let Some(header) = split_units.next()? {
+            if split_dwarf.unit(header)?.dwo_id == unit.dwo_id {
+                return Ok(Some(split_dwarf));
+            }
+        }
+
+        tracing::warn!(
+            "Ignoring split DWARF object {}, its id does not match the skeleton unit.",
+            candidate.display()
+        );
+    }
+
+    Ok(None)
+}
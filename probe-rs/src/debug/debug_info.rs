@@ -1,24 +1,28 @@
 use super::{
-    function_die::FunctionDie, get_sequential_key, unit_info::UnitInfo, unit_info::UnitIter,
-    variable::*, DebugError, DebugRegisters, SourceLocation, StackFrame, VariableCache,
+    debug_file, function_die::FunctionDie, get_sequential_key, unit_info::UnitInfo,
+    unit_info::UnitIter, variable::*, DebugError, DebugRegisters, SourceLocation, StackFrame,
+    StaticValue, StaticVariable, Symbols, VariableCache,
 };
 use crate::{
     core::Core,
     core::RegisterValue,
-    debug::{registers, source_statement::SourceStatements},
+    debug::{
+        registers,
+        source_statement::{SourceStatement, SourceStatements},
+    },
     MemoryInterface,
 };
 use ::gimli::{FileEntry, LineProgramHeader, UnwindContext};
-use gimli::{BaseAddresses, ColumnType, DebugFrame, UnwindSection};
+use gimli::{BaseAddresses, ColumnType, DebugFrame, EhFrame, UnwindSection};
 use object::read::{Object, ObjectSection};
 use probe_rs_target::InstructionSet;
 use registers::RegisterGroup;
 use std::{
-    borrow,
     cmp::Ordering,
+    collections::HashMap,
     convert::TryInto,
     num::NonZeroU64,
-    ops::ControlFlow,
+    ops::{ControlFlow, Range},
     path::{Path, PathBuf},
     rc::Rc,
     str::from_utf8,
@@ -42,33 +46,82 @@ pub struct VerifiedBreakpoint {
     pub source_location: SourceLocation,
 }
 
+/// One level of the source level call chain at an address.
+///
+/// When a function has been inlined, a single address corresponds to several source level
+/// functions. See [`DebugInfo::get_source_frames`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceFrame {
+    /// The name of the function, if it is known.
+    pub function_name: Option<String>,
+    /// For the innermost frame, this is the source location of the address itself.
+    /// For all outer frames, it is the call site of the function which was inlined into this one.
+    pub source_location: Option<SourceLocation>,
+    /// Indicates that this function was inlined into the next (outer) frame.
+    pub is_inlined: bool,
+}
+
 /// Debug information which is parsed from DWARF debugging information.
 pub struct DebugInfo {
     pub(crate) dwarf: gimli::Dwarf<DwarfReader>,
+    /// The split DWARF units from `.dwo` files or a `.dwp` package, indexed by the offset of their skeleton unit.
+    pub(crate) split_units: HashMap<gimli::DebugInfoOffset, gimli::Dwarf<DwarfReader>>,
     pub(crate) frame_section: gimli::DebugFrame<DwarfReader>,
-    pub(crate) locations_section: gimli::LocationLists<DwarfReader>,
-    pub(crate) address_section: gimli::DebugAddr<DwarfReader>,
+    /// The `.eh_frame` section, which is used for unwinding if `.debug_frame` has no entry for an address.
+    pub(crate) eh_frame_section: gimli::EhFrame<DwarfReader>,
+    /// The addresses required to parse pointers in the `.eh_frame` section.
+    pub(crate) eh_frame_bases: BaseAddresses,
     pub(crate) debug_line_section: gimli::DebugLine<DwarfReader>,
+    /// The symbol table, for lookups which don't need DWARF debug information.
+    pub(crate) symbols: Symbols,
 }
 
 impl DebugInfo {
     /// Read debug info directly from a ELF file.
+    ///
+    /// In addition to [`DebugInfo::from_raw`], this also loads the debug information from an
+    /// external debug file referenced by a `.gnu_debuglink` section, and from split DWARF
+    /// objects (`.dwo` files, or a `.dwp` package next to the ELF file).
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<DebugInfo, DebugError> {
+        let path = path.as_ref();
         let data = std::fs::read(path)?;
 
-        DebugInfo::from_raw(&data)
+        let object = object::File::parse(&*data)?;
+
+        match debug_file::find_debuglink(&object, path) {
+            Some(debug_file_path) => {
+                tracing::debug!("Using debug file {}", debug_file_path.display());
+
+                let debug_data = std::fs::read(debug_file_path)?;
+                let debug_object = object::File::parse(&*debug_data)?;
+
+                DebugInfo::load(&object, &debug_object, Some(path))
+            }
+            None => DebugInfo::load(&object, &object, Some(path)),
+        }
     }
 
     /// Parse debug information directly from a buffer containing an ELF file.
+    ///
+    /// Compressed debug sections are supported. External debug files can only be loaded
+    /// with [`DebugInfo::from_file`].
     pub fn from_raw(data: &[u8]) -> Result<Self, DebugError> {
         let object = object::File::parse(data)?;
 
+        DebugInfo::load(&object, &object, None)
+    }
+
+    /// Load the debug information for `object`, with the DWARF sections taken from `debug_object`.
+    ///
+    /// These are the same, unless the debug information has been moved to a separate file.
+    fn load(
+        object: &object::File<'_>,
+        debug_object: &object::File<'_>,
+        path: Option<&Path>,
+    ) -> Result<Self, DebugError> {
         // Load a section and return as `Cow<[u8]>`.
-        let load_section = |id: gimli::SectionId| -> Result<DwarfReader, gimli::Error> {
-            let data = object
-                .section_by_name(id.name())
-                .and_then(|section| section.uncompressed_data().ok())
-                .unwrap_or_else(|| borrow::Cow::Borrowed(&[][..]));
+        let load_section = |id: gimli::SectionId| -> Result<DwarfReader, DebugError> {
+            let data = debug_file::section_data(debug_object, id.name())?;
 
             Ok(gimli::read::EndianRcSlice::new(
                 Rc::from(&*data),
@@ -77,25 +130,55 @@ impl DebugInfo {
         };
 
         // Load all of the sections.
-        let dwarf_cow = gimli::Dwarf::load(&load_section)?;
+        let dwarf_cow = debug_file::load_dwarf(debug_object, false)?;
+        let split_units = debug_file::load_split_units(&dwarf_cow, path)?;
 
         use gimli::Section;
         let frame_section = gimli::DebugFrame::load(load_section)?;
-        let address_section = gimli::DebugAddr::load(load_section)?;
-        let debug_loc = gimli::DebugLoc::load(load_section)?;
-        let debug_loc_lists = gimli::DebugLocLists::load(load_section)?;
-        let locations_section = gimli::LocationLists::new(debug_loc, debug_loc_lists);
+        // The `.eh_frame` section is loaded at runtime, so it is never moved to a debug file.
+        let eh_frame_section = gimli::EhFrame::load(|id| {
+            let data = debug_file::section_data(object, id.name())?;
+
+            Ok::<_, DebugError>(gimli::read::EndianRcSlice::new(
+                Rc::from(&*data),
+                gimli::LittleEndian,
+            ))
+        })?;
+        let section_address = |name: &str| {
+            object
+                .section_by_name(name)
+                .map_or(0, |section| section.address())
+        };
+        let eh_frame_bases = BaseAddresses::default()
+            .set_eh_frame(section_address(".eh_frame"))
+            .set_text(section_address(".text"));
         let debug_line_section = gimli::DebugLine::load(load_section)?;
 
+        // A stripped file might only have a symbol table in the debug file.
+        let symbol_object = if object.symbols().next().is_some() {
+            object
+        } else {
+            debug_object
+        };
+
+        let symbols = Symbols::from_object(symbol_object);
+
         Ok(DebugInfo {
             dwarf: dwarf_cow,
+            split_units,
             frame_section,
-            locations_section,
-            address_section,
+            eh_frame_section,
+            eh_frame_bases,
             debug_line_section,
+            symbols,
         })
     }
 
+    /// The symbol table of the ELF file.
+    pub fn symbols(&self) -> &Symbols {
+        &self.symbols
+    }
+
     /// Get the name of the function at the given address.
     ///
     /// If no function is found, `None` will be returend.
@@ -127,6 +210,45 @@ impl DebugInfo {
         Ok(None)
     }
 
+    /// Get the source level call chain for a given address, taking inlined functions into account.
+    ///
+    /// The innermost function comes first. Its source location is the location of `address`
+    /// itself, while each of the following frames points at the call site of the inlined
+    /// function before it. For an address which is not part of an inlined function, the
+    /// result contains a single frame.
+    ///
+    /// If no function is found at the address, an empty list is returned.
+    pub fn get_source_frames(&self, address: u64) -> Result<Vec<SourceFrame>, DebugError> {
+        let mut units = self.get_units();
+
+        while let Some(unit_info) = self.get_next_unit_info(&mut units) {
+            let functions = unit_info.get_function_dies(address, None, true)?;
+
+            if functions.is_empty() {
+                continue;
+            }
+
+            let mut frames = Vec::with_capacity(functions.len());
+            let mut source_location = self.get_source_location(address);
+
+            // The functions are ordered from the outermost to the innermost one.
+            for function_die in functions.iter().rev() {
+                frames.push(SourceFrame {
+                    function_name: function_die.function_name(),
+                    source_location,
+                    is_inlined: function_die.is_inline(),
+                });
+
+                // The location in the caller is the call site of the inlined function.
+                source_location = function_die.inline_call_location();
+            }
+
+            return Ok(frames);
+        }
+
+        Ok(vec![])
+    }
+
     /// Try get the [`SourceLocation`] for a given address.
     pub fn get_source_location(&self, address: u64) -> Option<SourceLocation> {
         let mut units = self.dwarf.units();
@@ -271,15 +393,52 @@ impl DebugInfo {
     pub(crate) fn get_next_unit_info(&self, units: &mut UnitIter) -> Option<UnitInfo> {
         while let Ok(Some(header)) = units.next() {
             if let Ok(unit) = self.dwarf.unit(header) {
-                return Some(UnitInfo {
-                    debug_info: self,
-                    unit,
-                });
+                return Some(self.unit_info(unit));
             };
         }
         None
     }
 
+    /// Get the [`UnitInfo`] for the unit at the given offset in the `.debug_info` section.
+    pub(crate) fn get_unit_info_at(
+        &self,
+        offset: gimli::DebugInfoOffset,
+    ) -> Result<UnitInfo<'_>, DebugError> {
+        let header = self.dwarf.debug_info.header_from_offset(offset)?;
+
+        Ok(self.unit_info(gimli::Unit::new(&self.dwarf, header)?))
+    }
+
+    /// Create the [`UnitInfo`] for a unit. For a skeleton unit, this resolves the split unit with the actual debug information.
+    fn unit_info(&self, unit: gimli::Unit<GimliReader>) -> UnitInfo<'_> {
+        let split_dwarf = unit
+            .header
+            .offset()
+            .as_debug_info_offset()
+            .and_then(|offset| self.split_units.get(&offset));
+
+        if let Some(split_dwarf) = split_dwarf {
+            match split_unit(split_dwarf, &unit) {
+                Ok(split_unit) => {
+                    return UnitInfo {
+                        debug_info: self,
+                        dwarf: split_dwarf,
+                        unit: split_unit,
+                        skeleton: Some(unit),
+                    }
+                }
+                Err(error) => tracing::warn!("Unable to load split DWARF unit: {:?}", error),
+            }
+        }
+
+        UnitInfo {
+            debug_info: self,
+            dwarf: &self.dwarf,
+            unit,
+            skeleton: None,
+        }
+    }
+
     /// We do not actually resolve the children of `[VariableName::StaticScope]` automatically, and only create the necessary header in the `VariableCache`.
     /// This allows us to resolve the `[VariableName::StaticScope]` on demand/lazily, when a user requests it from the debug client.
     /// This saves a lot of overhead when a user only wants to see the `[VariableName::LocalScope]` or `[VariableName::Registers]` while stepping through code (the most common use cases)
@@ -295,10 +454,8 @@ impl DebugInfo {
         // Navigate the current unit from the header down.
         if let Ok(mut header_tree) = unit_info.unit.header.entries_tree(abbrevs, None) {
             let unit_node = header_tree.root()?;
-            let mut static_root_variable = Variable::new(
-                unit_info.unit.header.offset().as_debug_info_offset(),
-                Some(unit_node.entry().offset()),
-            );
+            let mut static_root_variable =
+                Variable::new(unit_info.header_offset(), Some(unit_node.entry().offset()));
             static_root_variable.variable_node_type = VariableNodeType::DirectLookup;
             static_root_variable.name = VariableName::StaticScopeRoot;
             static_variable_cache.cache_variable(None, static_root_variable, core)?;
@@ -323,7 +480,7 @@ impl DebugInfo {
         let function_node = tree.root()?;
 
         let mut function_root_variable = Variable::new(
-            unit_info.unit.header.offset().as_debug_info_offset(),
+            unit_info.header_offset(),
             Some(function_node.entry().offset()),
         );
         function_root_variable.variable_node_type = VariableNodeType::DirectLookup;
@@ -351,12 +508,7 @@ impl DebugInfo {
                 // Only attempt this part if we have not yet resolved the referenced children.
                 if !cache.has_children(parent_variable)? {
                     if let Some(header_offset) = parent_variable.unit_header_offset {
-                        let unit_header =
-                            self.dwarf.debug_info.header_from_offset(header_offset)?;
-                        let unit_info = UnitInfo {
-                            debug_info: self,
-                            unit: gimli::Unit::new(&self.dwarf, unit_header)?,
-                        };
+                        let unit_info = self.get_unit_info_at(header_offset)?;
                         // Reference to a type, or an node.entry() to another type or a type modifier which will point to another type.
                         let mut type_tree = unit_info
                             .unit
@@ -366,7 +518,7 @@ impl DebugInfo {
                         let mut referenced_variable = cache.cache_variable(
                             Some(parent_variable.variable_key),
                             Variable::new(
-                                unit_info.unit.header.offset().as_debug_info_offset(),
+                                unit_info.header_offset(),
                                 Some(referenced_node.entry().offset()),
                             ),
                             core,
@@ -406,12 +558,7 @@ impl DebugInfo {
                 // Only attempt this if the children are not already resolved.
                 if !cache.has_children(parent_variable)? {
                     if let Some(header_offset) = parent_variable.unit_header_offset {
-                        let unit_header =
-                            self.dwarf.debug_info.header_from_offset(header_offset)?;
-                        let unit_info = UnitInfo {
-                            debug_info: self,
-                            unit: gimli::Unit::new(&self.dwarf, unit_header)?,
-                        };
+                        let unit_info = self.get_unit_info_at(header_offset)?;
                         // Find the parent node
                         let mut type_tree = unit_info
                             .unit
@@ -447,12 +594,7 @@ impl DebugInfo {
                 // Only attempt this if the children are not already resolved.
                 if !cache.has_children(parent_variable)? {
                     if let Some(header_offset) = parent_variable.unit_header_offset {
-                        let unit_header =
-                            self.dwarf.debug_info.header_from_offset(header_offset)?;
-                        let unit_info = UnitInfo {
-                            debug_info: self,
-                            unit: gimli::Unit::new(&self.dwarf, unit_header)?,
-                        };
+                        let unit_info = self.get_unit_info_at(header_offset)?;
                         // Find the parent node
                         let mut type_tree = unit_info.unit.header.entries_tree(
                             &unit_info.unit.abbreviations,
@@ -533,7 +675,14 @@ impl DebugInfo {
 
                 let next_function = &functions[index + 1];
 
-                assert!(next_function.is_inline());
+                if !next_function.is_inline() {
+                    tracing::warn!(
+                        "UNWIND: Expected function {:?} to be inlined into {}, skipping it.",
+                        next_function.function_name(),
+                        function_name
+                    );
+                    continue;
+                }
 
                 // Calculate the call site for this function, so that we can use it later to create an additional 'callee' `StackFrame` from that PC.
                 let address_size = unit_info.unit.header.address_size() as u64;
@@ -693,9 +842,11 @@ impl DebugInfo {
     /// The unwind loop will continue until we meet one of the following conditions:
     /// - We can no longer unwind a valid PC value to be used for the next frame.
     /// - We encounter a LR register value of 0x0 or 0xFFFFFFFF(Arm 'Reset' value for that register).
-    /// - TODO: Catch the situation where the PC value indicates a hard-fault or other non-recoverable exception
     /// - We can not intelligently calculate a valid LR register value from the other registers, or the gimli::RegisterRule result is a value of 0x0. Note: [DWARF](https://dwarfstd.org) 6.4.4 - CIE defines the return register address used in the `gimli::RegisterRule` tables for unwind operations. Theoretically, if we encounter a function that has `Undefined` `gimli::RegisterRule` for the return register address, it means we have reached the bottom of the stack OR the function is a 'no return' type of function. I have found actual examples (e.g. local functions) where we get `Undefined` for register rule when we cannot apply this logic. Example 1: local functions in main.rs will have LR rule as `Undefined`. Example 2: main()-> ! that is called from a trampoline will have a valid LR rule.
     /// - Similarly, certain error conditions encountered in `StackFrameIterator` will also break out of the unwind loop.
+    ///
+    /// The unwind rules are taken from the `.debug_frame` section, or from the `.eh_frame` section if `.debug_frame` has no entry for an address.
+    /// On Cortex-M cores, unwinding continues through exception handlers: when the unwound program counter is an EXC_RETURN value, the registers of the interrupted code are restored from the exception stack frame.
     /// Note: In addition to populating the `StackFrame`s, this function will also populate the `DebugInfo::VariableCache` with `Variable`s for available Registers as well as static and function variables.
     /// TODO: Separate logic for stackframe creation and cache population
     pub fn unwind(&self, core: &mut Core, address: u64) -> Result<Vec<StackFrame>, crate::Error> {
@@ -726,6 +877,18 @@ impl DebugInfo {
                 frame_pc,
             );
 
+            // On Cortex-M cores, the LR contains an EXC_RETURN value in an exception handler.
+            // The registers of the interrupted code are restored from the exception stack frame.
+            if core.core_type().is_cortex_m() && frame_pc & EXC_RETURN_MASK == EXC_RETURN_MASK {
+                if let Err(error) =
+                    unwind_exception_frame(&mut unwind_registers, frame_pc as u32 | 1, core)
+                {
+                    tracing::error!("UNWIND: Unable to unwind the exception frame: {}", error);
+                    break;
+                }
+                continue 'unwind;
+            }
+
             //
             // PART 1-a: Prepare the `StackFrame` that holds the current frame information.
             let return_frame = match self.get_stackframe_info(core, frame_pc, &unwind_registers) {
@@ -782,7 +945,13 @@ impl DebugInfo {
                 return_frame.source_location
             );
             // PART 2-a: get the `gimli::FrameDescriptorEntry` for this address and then the unwind info associated with this row.
-            match get_unwind_info(&mut unwind_context, &self.frame_section, frame_pc) {
+            match get_unwind_info(
+                &mut unwind_context,
+                &self.frame_section,
+                &self.eh_frame_section,
+                &self.eh_frame_bases,
+                frame_pc,
+            ) {
                 Ok(unwind_info) => {
                     // Because we will be updating the `unwind_registers` with previous frame unwind info, we need to keep a copy of the current frame's registers that can be used to resolve [DWARF](https://dwarfstd.org) expressions.
                     let callee_frame_registers = unwind_registers.clone();
@@ -816,7 +985,11 @@ impl DebugInfo {
                                 }
                             }
                         }
-                        gimli::CfaRule::Expression(_) => unimplemented!(),
+                        gimli::CfaRule::Expression(_) => {
+                            tracing::error!("UNWIND: CFA expressions are not supported");
+                            stack_frames.push(return_frame);
+                            break;
+                        }
                     };
 
                     // PART 2-c: Unwind registers for the "previous/calling" frame.
@@ -894,14 +1067,41 @@ impl DebugInfo {
 
     /// Find the program counter where a breakpoint should be set,
     /// given a source file, a line and optionally a column.
+    ///
+    /// If the line has code in multiple places, e.g. because it belongs to a generic or inlined function,
+    /// the first location is returned. Use [`DebugInfo::get_breakpoint_locations`] to find all of them.
     pub fn get_breakpoint_location(
         &self,
         path: &Path,
         line: u64,
         column: Option<u64>,
     ) -> Result<VerifiedBreakpoint, DebugError> {
+        self.get_breakpoint_locations(path, line, column)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                DebugError::Other(anyhow::anyhow!(
+                    "No valid breakpoint information found for file: {:?}, line: {:?}, column: {:?}",
+                    path,
+                    line,
+                    column
+                ))
+            })
+    }
+
+    /// Find all the program counters where a breakpoint should be set,
+    /// given a source file, a line and optionally a column.
+    ///
+    /// Every instance of the line is returned, i.e. one location for each instantiation of a generic function,
+    /// and for each function the line was inlined into. If the line has no code, the result is empty.
+    pub fn get_breakpoint_locations(
+        &self,
+        path: &Path,
+        line: u64,
+        column: Option<u64>,
+    ) -> Result<Vec<VerifiedBreakpoint>, DebugError> {
         tracing::debug!(
-            "Looking for breakpoint location for {}:{}:{}",
+            "Looking for breakpoint locations for {}:{}:{}",
             path.display(),
             line,
             column
@@ -909,164 +1109,418 @@ impl DebugInfo {
                 .unwrap_or_else(|| "-".to_owned())
         );
 
+        let mut breakpoints: Vec<VerifiedBreakpoint> = Vec::new();
         let mut unit_iter = self.dwarf.units();
 
         while let Some(unit_header) = self.get_next_unit_info(&mut unit_iter) {
             let unit = &unit_header.unit;
 
-            if let Some(ref line_program) = unit.line_program {
-                let header = line_program.header();
+            let Some(ref line_program) = unit.line_program else {
+                continue;
+            };
+            let header = line_program.header();
 
-                for file_name in header.file_names() {
-                    let combined_path = self.get_path(unit, header, file_name);
-                    if combined_path
-                        .map(|p| canonical_path_eq(path, &p))
-                        .unwrap_or(false)
+            if !header.file_names().iter().any(|file_name| {
+                self.get_path(unit, header, file_name)
+                    .map(|p| canonical_path_eq(path, &p))
+                    .unwrap_or(false)
+            }) {
+                continue;
+            }
+
+            // Each sequence (usually a single function) contributes at most one location for the line.
+            let mut resolved_sequence: Option<Range<u64>> = None;
+            let mut rows = line_program.clone().rows();
+
+            while let Some((header, row)) = rows.next_row()? {
+                if resolved_sequence
+                    .as_ref()
+                    .map(|sequence| sequence.contains(&row.address()))
+                    .unwrap_or(false)
+                {
+                    continue;
+                }
+
+                let row_path = row
+                    .file(header)
+                    .and_then(|file_entry| self.get_path(unit, header, file_entry));
+
+                if row_path
+                    .map(|p| !canonical_path_eq(path, &p))
+                    .unwrap_or(true)
+                {
+                    continue;
+                }
+
+                let Some(cur_line) = row.line() else {
+                    continue;
+                };
+                if cur_line.get() != line {
+                    continue;
+                }
+
+                // The first match of the file and row will be used to build the SourceStatements, and then:
+                // 1. If there is an exact column match, we will use the low_pc of the statement at that column and line.
+                // 2. If there is no exact column match, we use the first available statement in the line.
+                let source_statements =
+                    match SourceStatements::new(self, &unit_header, row.address()) {
+                        Ok(source_statements) => source_statements.statements,
+                        Err(DebugError::NoValidHaltLocation { .. }) => continue,
+                        Err(other_error) => return Err(other_error),
+                    };
+
+                let source_statement = source_statements
+                    .iter()
+                    .find(|statement| {
+                        statement.line == Some(cur_line)
+                            && column
+                                .and_then(NonZeroU64::new)
+                                .map(ColumnType::Column)
+                                .map_or(false, |col| col == statement.column)
+                    })
+                    .or_else(|| {
+                        source_statements
+                            .iter()
+                            .find(|statement| statement.line == Some(cur_line))
+                    });
+
+                if let Some(source_statement) = source_statement {
+                    resolved_sequence = Some(row.address()..source_statement.sequence_range.end);
+
+                    if let Some(breakpoint) =
+                        self.verified_breakpoint(&unit_header, source_statement)
                     {
-                        let mut rows = line_program.clone().rows();
+                        if !breakpoints
+                            .iter()
+                            .any(|existing| existing.address == breakpoint.address)
+                        {
+                            breakpoints.push(breakpoint);
+                        }
+                    }
+                }
+            }
+        }
 
-                        while let Some((header, row)) = rows.next_row()? {
-                            let row_path = row
-                                .file(header)
-                                .and_then(|file_entry| self.get_path(unit, header, file_entry));
+        Ok(breakpoints)
+    }
 
-                            if row_path
-                                .map(|p| !canonical_path_eq(path, &p))
-                                .unwrap_or(true)
-                            {
-                                continue;
-                            }
+    /// Find all the program counters where a breakpoint should be set, to halt when the function `function_name` is called.
+    ///
+    /// The name can be the plain function name (e.g. `blink`), or include (parts of) the module path (e.g. `leds::blink`).
+    /// Generic parameters are ignored, unless they are part of `function_name`.
+    /// A location is returned for each instantiation of the function, and for each function it was inlined into.
+    /// The breakpoint is placed at the first statement after the function prologue.
+    pub fn get_function_breakpoint_locations(
+        &self,
+        function_name: &str,
+    ) -> Result<Vec<VerifiedBreakpoint>, DebugError> {
+        tracing::debug!(
+            "Looking for breakpoint locations for function {}",
+            function_name
+        );
 
-                            if let Some(cur_line) = row.line() {
-                                if cur_line.get() == line {
-                                    // The first match of the file and row will be used to build the SourceStatements, and then:
-                                    // 1. If there is an exact column match, we will use the low_pc of the statement at that column and line.
-                                    // 2. If there is no exact column match, we use the first available statement in the line.
-                                    let source_statements =
-                                        SourceStatements::new(self, &unit_header, row.address())?
-                                            .statements;
-                                    if let Some((halt_address, Some(halt_location))) =
-                                        source_statements
-                                            .iter()
-                                            .find(|statement| {
-                                                statement.line == Some(cur_line)
-                                                    && column
-                                                        .and_then(NonZeroU64::new)
-                                                        .map(ColumnType::Column)
-                                                        .map_or(false, |col| {
-                                                            col == statement.column
-                                                        })
-                                            })
-                                            .map(|source_statement| {
-                                                (
-                                                    source_statement.low_pc(),
-                                                    line_program
-                                                        .header()
-                                                        .file(source_statement.file_index)
-                                                        .and_then(|file_entry| {
-                                                            self.find_file_and_directory(
-                                                                &unit_header.unit,
-                                                                line_program.header(),
-                                                                file_entry,
-                                                            )
-                                                            .map(|(file, directory)| {
-                                                                SourceLocation {
-                                                                    line: source_statement
-                                                                        .line
-                                                                        .map(
-                                                                        std::num::NonZeroU64::get,
-                                                                    ),
-                                                                    column: Some(
-                                                                        source_statement
-                                                                            .column
-                                                                            .into(),
-                                                                    ),
-                                                                    file,
-                                                                    directory,
-                                                                    low_pc: Some(
-                                                                        source_statement.low_pc()
-                                                                            as u32,
-                                                                    ),
-                                                                    high_pc: Some(
-                                                                        source_statement
-                                                                            .instruction_range
-                                                                            .end
-                                                                            as u32,
-                                                                    ),
-                                                                }
-                                                            })
-                                                        }),
-                                                )
-                                            })
-                                    {
-                                        return Ok(VerifiedBreakpoint {
-                                            address: halt_address,
-                                            source_location: halt_location,
-                                        });
-                                    } else if let Some((halt_address, Some(halt_location))) =
-                                        source_statements
-                                            .iter()
-                                            .find(|statement| statement.line == Some(cur_line))
-                                            .map(|source_statement| {
-                                                (
-                                                    source_statement.low_pc(),
-                                                    line_program
-                                                        .header()
-                                                        .file(source_statement.file_index)
-                                                        .and_then(|file_entry| {
-                                                            self.find_file_and_directory(
-                                                                &unit_header.unit,
-                                                                line_program.header(),
-                                                                file_entry,
-                                                            )
-                                                            .map(|(file, directory)| {
-                                                                SourceLocation {
-                                                                    line: source_statement
-                                                                        .line
-                                                                        .map(
-                                                                        std::num::NonZeroU64::get,
-                                                                    ),
-                                                                    column: Some(
-                                                                        source_statement
-                                                                            .column
-                                                                            .into(),
-                                                                    ),
-                                                                    file,
-                                                                    directory,
-                                                                    low_pc: Some(
-                                                                        source_statement.low_pc()
-                                                                            as u32,
-                                                                    ),
-                                                                    high_pc: Some(
-                                                                        source_statement
-                                                                            .instruction_range
-                                                                            .end
-                                                                            as u32,
-                                                                    ),
-                                                                }
-                                                            })
-                                                        }),
-                                                )
-                                            })
-                                    {
-                                        return Ok(VerifiedBreakpoint {
-                                            address: halt_address,
-                                            source_location: halt_location,
-                                        });
-                                    }
-                                }
+        let mut breakpoints: Vec<VerifiedBreakpoint> = Vec::new();
+        let mut units = self.get_units();
+
+        while let Some(unit_info) = self.get_next_unit_info(&mut units) {
+            let unit = &unit_info.unit;
+            let function_names = self.qualified_names(&unit_info, gimli::DW_TAG_subprogram)?;
+
+            let mut entries = unit.entries();
+            while let Some((_, entry)) = entries.next_dfs()? {
+                if !matches!(
+                    entry.tag(),
+                    gimli::DW_TAG_subprogram | gimli::DW_TAG_inlined_subroutine
+                ) {
+                    continue;
+                }
+
+                let Some(name) = function_names.get(&definition_offset(unit, entry)) else {
+                    continue;
+                };
+                if !qualified_name_matches(name, function_name) {
+                    continue;
+                }
+
+                // Abstract instances and declarations have no code.
+                let entry_address = match entry.attr_value(gimli::DW_AT_entry_pc)? {
+                    Some(gimli::AttributeValue::Addr(address)) => Some(address),
+                    _ => {
+                        let mut ranges = unit_info.dwarf.die_ranges(unit, entry)?;
+                        let mut entry_address: Option<u64> = None;
+                        while let Some(range) = ranges.next()? {
+                            if range.begin < range.end
+                                && !matches!(entry_address, Some(address) if address <= range.begin)
+                            {
+                                entry_address = Some(range.begin);
                             }
                         }
+                        entry_address
                     }
+                };
+                // The linker sets the address of functions which were removed as unused to zero.
+                let Some(entry_address) = entry_address.filter(|address| *address != 0) else {
+                    continue;
+                };
+
+                // Skip the prologue, by using the first valid halt location of the function.
+                let Ok((Some(address), source_location)) = super::SteppingMode::BreakPoint
+                    .get_halt_location(None, self, entry_address, None)
+                else {
+                    tracing::debug!(
+                        "No valid halt location for function {} at {:#010x}",
+                        name,
+                        entry_address
+                    );
+                    continue;
+                };
+
+                let Some(source_location) =
+                    source_location.or_else(|| self.get_source_location(address))
+                else {
+                    continue;
+                };
+
+                if !breakpoints
+                    .iter()
+                    .any(|existing| existing.address == address)
+                {
+                    breakpoints.push(VerifiedBreakpoint {
+                        address,
+                        source_location,
+                    });
                 }
             }
         }
-        Err(DebugError::Other(anyhow::anyhow!(
-            "No valid breakpoint information found for file: {:?}, line: {:?}, column: {:?}",
-            path,
-            line,
-            column
-        )))
+
+        Ok(breakpoints)
+    }
+
+    /// The fully qualified names (including the enclosing namespaces) of all named DIEs with the `tag` in the `unit`,
+    /// indexed by the offset of their DIE.
+    fn qualified_names(
+        &self,
+        unit_info: &UnitInfo,
+        tag: gimli::DwTag,
+    ) -> Result<HashMap<gimli::UnitOffset, String>, DebugError> {
+        let unit = &unit_info.unit;
+        let mut qualified_names = HashMap::new();
+        // The enclosing namespaces, and the depth of their DIE.
+        let mut namespaces: Vec<(isize, String)> = Vec::new();
+        let mut depth = 0;
+
+        let mut entries = unit.entries();
+        while let Some((delta_depth, entry)) = entries.next_dfs()? {
+            depth += delta_depth;
+            while matches!(namespaces.last(), Some((namespace_depth, _)) if *namespace_depth >= depth)
+            {
+                namespaces.pop();
+            }
+
+            let Some(name) = entry
+                .attr_value(gimli::DW_AT_name)?
+                .and_then(|name| unit_info.dwarf.attr_string(unit, name).ok())
+                .map(|name| String::from_utf8_lossy(&name).to_string())
+            else {
+                continue;
+            };
+
+            match entry.tag() {
+                gimli::DW_TAG_namespace
+                | gimli::DW_TAG_structure_type
+                | gimli::DW_TAG_enumeration_type
+                | gimli::DW_TAG_union_type => namespaces.push((depth, name)),
+                entry_tag if entry_tag == tag => {
+                    let mut qualified_name = String::new();
+                    for (_, namespace) in &namespaces {
+                        qualified_name.push_str(namespace);
+                        qualified_name.push_str("::");
+                    }
+                    qualified_name.push_str(&name);
+                    qualified_names.insert(entry.offset(), qualified_name);
+                }
+                _ => {}
+            }
+        }
+
+        Ok(qualified_names)
+    }
+
+    /// Find the static variable `name`, to read or write it with [`StaticVariable::read`] and [`StaticVariable::write`].
+    ///
+    /// The name can include (the end of) the module path, e.g. `app::COUNTER`, and has to include it if the name is ambiguous.
+    /// The variable is resolved with the DWARF debug information. If it is not found there, the symbol table is used,
+    /// which only provides the address and the size, e.g. for `#[no_mangle]` statics without debug information.
+    pub fn get_static(&self, name: &str) -> Result<StaticVariable, DebugError> {
+        let mut found: Vec<StaticVariable> = Vec::new();
+        let mut units = self.get_units();
+
+        while let Some(unit_info) = self.get_next_unit_info(&mut units) {
+            let unit = &unit_info.unit;
+            let variable_names = self.qualified_names(&unit_info, gimli::DW_TAG_variable)?;
+
+            for (offset, qualified_name) in variable_names {
+                if !qualified_name_matches(&qualified_name, name) {
+                    continue;
+                }
+                let entry = unit.entry(offset)?;
+                let Some(address) = self.static_address(&unit_info, &entry)? else {
+                    // Local variables, or statics which were removed as unused.
+                    continue;
+                };
+
+                let (type_name, size, is_base_type) = self.static_type(&unit_info, &entry)?;
+                if !found.iter().any(|existing| existing.address == address) {
+                    found.push(StaticVariable {
+                        name: qualified_name,
+                        address,
+                        size,
+                        type_name,
+                        is_base_type,
+                    });
+                }
+            }
+        }
+
+        if found.is_empty() {
+            if let Some(symbol) = self.symbols.iter().find(|symbol| {
+                !symbol.is_function && (symbol.name == name || symbol.mangled_name == name)
+            }) {
+                found.push(StaticVariable {
+                    name: name.to_string(),
+                    address: symbol.address,
+                    size: Some(symbol.size).filter(|size| *size != 0),
+                    type_name: None,
+                    is_base_type: false,
+                });
+            }
+        }
+
+        match found.len() {
+            0 => Err(DebugError::Other(anyhow::anyhow!(
+                "No static variable named '{}' was found",
+                name
+            ))),
+            1 => Ok(found.remove(0)),
+            _ => Err(DebugError::Other(anyhow::anyhow!(
+                "The name '{}' is ambiguous, use one of: {}",
+                name,
+                found
+                    .iter()
+                    .map(|variable| variable.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))),
+        }
+    }
+
+    /// Read the value of the static variable `name`. See [`DebugInfo::get_static`] for the supported names.
+    pub fn read_static<T: StaticValue>(
+        &self,
+        core: &mut Core<'_>,
+        name: &str,
+    ) -> Result<T, DebugError> {
+        self.get_static(name)?.read(core)
+    }
+
+    /// Write a new `value` to the static variable `name`. See [`DebugInfo::get_static`] for the supported names.
+    pub fn write_static<T: StaticValue>(
+        &self,
+        core: &mut Core<'_>,
+        name: &str,
+        value: T,
+    ) -> Result<(), DebugError> {
+        self.get_static(name)?.write(core, value)
+    }
+
+    /// The name, the size, and if it is a primitive type, of the type of the variable `entry`.
+    ///
+    /// Type modifiers and typedefs are skipped, so a `static mut COUNTER: u32` has the type `u32`.
+    fn static_type(
+        &self,
+        unit_info: &UnitInfo,
+        entry: &gimli::DebuggingInformationEntry<GimliReader>,
+    ) -> Result<(Option<String>, Option<u64>, bool), DebugError> {
+        let unit = &unit_info.unit;
+        let mut type_offset = match entry.attr_value(gimli::DW_AT_type)? {
+            Some(gimli::AttributeValue::UnitRef(offset)) => offset,
+            _ => return Ok((None, None, false)),
+        };
+
+        // Limit the number of modifiers which are followed, in case of malformed debug information.
+        for _ in 0..8 {
+            let type_entry = unit.entry(type_offset)?;
+            match type_entry.tag() {
+                gimli::DW_TAG_const_type | gimli::DW_TAG_volatile_type | gimli::DW_TAG_typedef => {
+                    match type_entry.attr_value(gimli::DW_AT_type)? {
+                        Some(gimli::AttributeValue::UnitRef(offset)) => type_offset = offset,
+                        _ => break,
+                    }
+                }
+                tag => {
+                    let type_name = type_entry
+                        .attr_value(gimli::DW_AT_name)?
+                        .and_then(|name| unit_info.dwarf.attr_string(unit, name).ok())
+                        .map(|name| String::from_utf8_lossy(&name).to_string());
+                    let size = type_entry
+                        .attr_value(gimli::DW_AT_byte_size)?
+                        .and_then(|size| size.udata_value());
+                    return Ok((type_name, size, tag == gimli::DW_TAG_base_type));
+                }
+            }
+        }
+
+        Ok((None, None, false))
+    }
+
+    /// The address of a static variable, if its location is a fixed address.
+    fn static_address(
+        &self,
+        unit_info: &UnitInfo,
+        entry: &gimli::DebuggingInformationEntry<GimliReader>,
+    ) -> Result<Option<u64>, DebugError> {
+        let unit = &unit_info.unit;
+        let Some(gimli::AttributeValue::Exprloc(expression)) =
+            entry.attr_value(gimli::DW_AT_location)?
+        else {
+            return Ok(None);
+        };
+
+        let mut operations = expression.operations(unit.encoding());
+        let address = match operations.next()? {
+            Some(gimli::Operation::Address { address }) => address,
+            Some(gimli::Operation::AddressIndex { index }) => {
+                unit_info.dwarf.address(unit, index)?
+            }
+            _ => return Ok(None),
+        };
+
+        // The linker sets the address of statics which were removed as unused to zero.
+        Ok(Some(address).filter(|address| *address != 0))
+    }
+
+    /// Build the [`VerifiedBreakpoint`] for a source statement in the `unit_info`.
+    fn verified_breakpoint(
+        &self,
+        unit_info: &UnitInfo,
+        source_statement: &SourceStatement,
+    ) -> Option<VerifiedBreakpoint> {
+        let line_program = unit_info.unit.line_program.as_ref()?;
+        let file_entry = line_program.header().file(source_statement.file_index)?;
+        let (file, directory) =
+            self.find_file_and_directory(&unit_info.unit, line_program.header(), file_entry)?;
+
+        Some(VerifiedBreakpoint {
+            address: source_statement.low_pc(),
+            source_location: SourceLocation {
+                line: source_statement.line.map(std::num::NonZeroU64::get),
+                column: Some(source_statement.column.into()),
+                file,
+                directory,
+                low_pc: Some(source_statement.low_pc() as u32),
+                high_pc: Some(source_statement.instruction_range.end as u32),
+            },
+        })
     }
 
     /// Get the path for an entry in a line program header, using the compilation unit's directory and file entries.
@@ -1125,6 +1579,85 @@ impl DebugInfo {
     }
 }
 
+/// The offset of the DIE which defines the name of the function `entry`.
+///
+/// Inlined functions and out-of-line instances refer to their abstract instance with `DW_AT_abstract_origin`,
+/// and the definition of a function can refer to its declaration with `DW_AT_specification`.
+/// Load the split unit belonging to the `skeleton` unit from `split_dwarf`.
+fn split_unit(
+    split_dwarf: &gimli::Dwarf<DwarfReader>,
+    skeleton: &gimli::Unit<GimliReader>,
+) -> Result<gimli::Unit<GimliReader>, DebugError> {
+    let header = split_dwarf.units().next()?.ok_or_else(|| {
+        DebugError::Other(anyhow::anyhow!("The split DWARF object contains no units"))
+    })?;
+
+    let mut unit = gimli::Unit::new(split_dwarf, header)?;
+    unit.copy_relocated_attributes(skeleton);
+
+    // The line table with the addresses is only part of the skeleton unit.
+    if unit.line_program.is_none() {
+        unit.line_program = skeleton.line_program.clone();
+    }
+
+    Ok(unit)
+}
+
+fn definition_offset(
+    unit: &gimli::Unit<GimliReader>,
+    entry: &gimli::DebuggingInformationEntry<GimliReader>,
+) -> gimli::UnitOffset {
+    let mut offset = entry.offset();
+    // Limit the number of references which are followed, in case of malformed debug information.
+    for _ in 0..4 {
+        let Ok(current) = unit.entry(offset) else {
+            break;
+        };
+        match current
+            .attr_value(gimli::DW_AT_abstract_origin)
+            .ok()
+            .flatten()
+            .or_else(|| {
+                current
+                    .attr_value(gimli::DW_AT_specification)
+                    .ok()
+                    .flatten()
+            }) {
+            Some(gimli::AttributeValue::UnitRef(origin)) => offset = origin,
+            _ => break,
+        }
+    }
+    offset
+}
+
+/// Check if the qualified name `name` of a function or variable matches the requested name `function_name`.
+///
+/// The requested name has to match the end of the qualified name, at a path separator.
+/// Generic parameters are ignored, unless the requested name contains them.
+fn qualified_name_matches(name: &str, function_name: &str) -> bool {
+    let name = if function_name.contains('<') {
+        name.to_string()
+    } else {
+        let mut depth = 0;
+        name.chars()
+            .filter(|c| match c {
+                '<' => {
+                    depth += 1;
+                    false
+                }
+                '>' => {
+                    depth -= 1;
+                    false
+                }
+                _ => depth == 0,
+            })
+            .collect()
+    };
+
+    name == function_name
+        || matches!(name.strip_suffix(function_name), Some(prefix) if prefix.ends_with("::"))
+}
+
 /// Uses the [std::fs::canonicalize] function to canonicalize both paths before applying the [std::path::PathBuf::eq]
 /// to test if the secondary path is equal or a suffix of the primary path.
 /// If for some reason (e.g., the paths don't exist) the canonicalization fails, the original equality check is used.
@@ -1153,41 +1686,61 @@ pub(crate) fn canonical_path_eq(primary_path: &Path, secondary_path: &Path) -> b
 }
 
 /// Get a handle to the [`gimli::UnwindTableRow`] for this call frame, so that we can reference it to unwind register values.
+///
+/// The frame description is looked up in the `.debug_frame` section first, and in the `.eh_frame` section
+/// if `.debug_frame` has no entry for the program counter.
 fn get_unwind_info<'a>(
     unwind_context: &'a mut Box<UnwindContext<DwarfReader>>,
     frame_section: &'a DebugFrame<DwarfReader>,
+    eh_frame_section: &'a EhFrame<DwarfReader>,
+    eh_frame_bases: &BaseAddresses,
     frame_program_counter: u64,
 ) -> Result<&'a gimli::UnwindTableRow<DwarfReader, gimli::StoreOnHeap>, DebugError> {
     let unwind_bases = BaseAddresses::default();
-    let frame_descriptor_entry = match frame_section.fde_for_address(
+
+    let unwind_info = match frame_section.fde_for_address(
         &unwind_bases,
         frame_program_counter,
         gimli::DebugFrame::cie_from_offset,
     ) {
-        Ok(frame_descriptor_entry) => frame_descriptor_entry,
-        Err(error) => {
-            return Err(DebugError::Other(anyhow::anyhow!(
-                "UNWIND: Error reading FrameDescriptorEntry at PC={} : {}",
-                frame_program_counter,
-                error
-            )));
-        }
-    };
-
-    frame_descriptor_entry
-        .unwind_info_for_address(
+        Ok(frame_descriptor_entry) => frame_descriptor_entry.unwind_info_for_address(
             frame_section,
             &unwind_bases,
             unwind_context,
             frame_program_counter,
-        )
-        .map_err(|error| {
-            DebugError::Other(anyhow::anyhow!(
-                "UNWIND: Error reading FrameDescriptorEntry at PC={} : {}",
+        ),
+        Err(debug_frame_error) => {
+            let frame_descriptor_entry = eh_frame_section
+                .fde_for_address(
+                    eh_frame_bases,
+                    frame_program_counter,
+                    gimli::EhFrame::cie_from_offset,
+                )
+                .map_err(|error| {
+                    DebugError::Other(anyhow::anyhow!(
+                        "UNWIND: Error reading FrameDescriptorEntry at PC={} : {} (.eh_frame: {})",
+                        frame_program_counter,
+                        debug_frame_error,
+                        error
+                    ))
+                })?;
+
+            frame_descriptor_entry.unwind_info_for_address(
+                eh_frame_section,
+                eh_frame_bases,
+                unwind_context,
                 frame_program_counter,
-                error
-            ))
-        })
+            )
+        }
+    };
+
+    unwind_info.map_err(|error| {
+        DebugError::Other(anyhow::anyhow!(
+            "UNWIND: Error reading FrameDescriptorEntry at PC={} : {}",
+            frame_program_counter,
+            error
+        ))
+    })
 }
 
 /// A per_register unwind, applying register rules and updating the [`registers::DebugRegister`] value as appropriate, before returning control to the calling function.
@@ -1330,8 +1883,32 @@ fn unwind_register(
                 return ControlFlow::Break(());
             }
         }
-        //TODO: Implement the remainder of these `RegisterRule`s
-        _ => unimplemented!(),
+        ValOffset(address_offset) => {
+            // "The previous value of this register is the value CFA+N where CFA is the current CFA value and N is a signed offset"
+            register_rule_string = format!("CFA {register_rule:?}");
+            unwind_cfa.map(|unwind_cfa| {
+                let value = add_to_address(unwind_cfa, address_offset);
+                if debug_register.is_u32() {
+                    RegisterValue::U32(value as u32)
+                } else {
+                    RegisterValue::U64(value)
+                }
+            })
+        }
+        Register(register) => {
+            // "The previous value of this register is stored in another register numbered R"
+            callee_frame_registers
+                .get_register_by_dwarf_id(register.0)
+                .and_then(|register| register.value)
+        }
+        _ => {
+            tracing::error!(
+                "UNWIND: The register rule {:?} for register {} is not supported",
+                register_rule,
+                debug_register.name
+            );
+            return ControlFlow::Break(());
+        }
     };
     debug_register.value = new_value;
 
@@ -1348,6 +1925,85 @@ fn unwind_register(
     ControlFlow::Continue(())
 }
 
+/// EXC_RETURN values, which are loaded into the LR on exception entry on Cortex-M cores, start with `0xFF`.
+const EXC_RETURN_MASK: u64 = 0xFF00_0000;
+
+/// The size of the exception stack frame which was stacked on exception entry on a Cortex-M core.
+///
+/// The basic frame contains R0-R3, R12, LR, PC and xPSR. If EXC_RETURN bit 4 is cleared, the frame
+/// is extended with S0-S15, FPSCR and a reserved word. Bit 9 of the stacked xPSR indicates that
+/// a padding word was inserted to align the stack to 8 bytes.
+fn exception_frame_size(exc_return: u32, stacked_xpsr: u32) -> u64 {
+    let mut size = if exc_return & (1 << 4) == 0 {
+        0x68
+    } else {
+        0x20
+    };
+
+    if stacked_xpsr & (1 << 9) != 0 {
+        size += 4;
+    }
+
+    size
+}
+
+/// Restores the registers of the code which was interrupted by an exception on a Cortex-M core.
+///
+/// `unwind_registers` are the registers of the exception handler's caller, which is the exception
+/// entry, so the stack pointer points to the exception stack frame, unless EXC_RETURN bit 2
+/// indicates that the process stack was used.
+fn unwind_exception_frame(
+    unwind_registers: &mut DebugRegisters,
+    exc_return: u32,
+    core: &mut Core,
+) -> Result<(), crate::Error> {
+    let frame_address: u32 = if exc_return & (1 << 2) != 0 {
+        let psp = core
+            .registers()
+            .psp()
+            .ok_or_else(|| anyhow::anyhow!("The core has no process stack pointer"))?;
+        core.read_core_reg(psp)?
+    } else {
+        unwind_registers
+            .get_stack_pointer()
+            .and_then(|sp| sp.value)
+            .ok_or_else(|| anyhow::anyhow!("The stack pointer of the exception frame is unknown"))?
+            .try_into()?
+    };
+
+    let mut frame = [0u32; 8];
+    core.read_32(frame_address as u64, &mut frame)?;
+    let [r0, r1, r2, r3, r12, lr, pc, xpsr] = frame;
+
+    tracing::trace!(
+        "UNWIND: Exception frame at {:#010x}, EXC_RETURN={:#010x}",
+        frame_address,
+        exc_return
+    );
+
+    let sp = frame_address as u64 + exception_frame_size(exc_return, xpsr);
+
+    for debug_register in unwind_registers.0.iter_mut() {
+        let value = match debug_register.dwarf_id {
+            Some(0) => Some(r0),
+            Some(1) => Some(r1),
+            Some(2) => Some(r2),
+            Some(3) => Some(r3),
+            Some(12) => Some(r12),
+            Some(13) => Some(sp as u32),
+            Some(14) => Some(lr),
+            Some(15) => Some(pc),
+            _ => None,
+        };
+
+        if let Some(value) = value {
+            debug_register.value = Some(RegisterValue::U32(value));
+        }
+    }
+
+    Ok(())
+}
+
 /// Helper function to handle adding a signed offset to a u64 address.
 /// The result wraps, which matches previous behavior of using i64 operations and
 /// casting to u32
@@ -1358,3 +2014,18 @@ fn add_to_address(address: u64, offset: i64) -> u64 {
         address.wrapping_sub(offset.unsigned_abs())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::exception_frame_size;
+
+    #[test]
+    fn exception_frame_sizes() {
+        // Basic frame, returning to thread mode using the process stack.
+        assert_eq!(exception_frame_size(0xFFFF_FFFD, 0x0100_0000), 0x20);
+        // Basic frame with alignment padding.
+        assert_eq!(exception_frame_size(0xFFFF_FFF9, 0x0100_0200), 0x24);
+        // Extended frame with the floating point context.
+        assert_eq!(exception_frame_size(0xFFFF_FFE9, 0x0100_0000), 0x68);
+    }
+}
@@ -6,7 +6,7 @@ use super::{
 use crate::{
     architecture::{arm::ArmError, riscv::communication_interface::RiscvError},
     core::Core,
-    CoreStatus, HaltReason,
+    CoreStatus, HaltReason, InstructionSet,
 };
 use std::{ops::RangeInclusive, time::Duration};
 
@@ -53,7 +53,7 @@ impl SteppingMode {
             }
         };
         let origin_program_counter = program_counter;
-        let mut return_address = core.read_core_reg(core.registers().return_address())?;
+        let mut return_address = caller_address(core, debug_info, program_counter)?;
 
         // Sometimes the target program_counter is at a location where the debug_info program row data does not contain valid statements for halt points.
         // When DebugError::NoValidHaltLocation happens, we will step to the next instruction and try again(until we can reasonably expect to have passed out of an epilogue), before giving up.
@@ -98,7 +98,7 @@ impl SteppingMode {
                             message
                         );
                         program_counter = core.step()?.pc;
-                        return_address = core.read_core_reg(core.registers().return_address())?;
+                        return_address = caller_address(core, debug_info, program_counter)?;
                         continue;
                     }
                     other_error => {
@@ -286,7 +286,7 @@ impl SteppingMode {
                     if let Some(core) = core {
                         let inclusive_range = current_source_statement.instruction_range.start
                             ..=current_source_statement.instruction_range.end;
-                        let (core_status, new_pc) = step_to_address(inclusive_range, core)?;
+                        let (core_status, new_pc) = step_to_address(inclusive_range.clone(), core)?;
                         if new_pc == current_source_statement.instruction_range.end {
                             // We have halted at the address after the current statement, so we can conclude there was no branching calls in this sequence.
                             tracing::debug!("Stepping into next statement, but no branching calls found. Stepped to next available statement.");
@@ -303,6 +303,25 @@ impl SteppingMode {
                                 "Stepping into next statement at address: {:#010x}.",
                                 new_pc
                             );
+
+                            // Compiler generated glue (e.g. `compiler_builtins` or outlined helpers) has no line information, and is not worth stepping into.
+                            // Return to the calling statement, and continue to the next statement from there.
+                            if let Err(DebugError::NoValidHaltLocation { .. }) =
+                                SteppingMode::BreakPoint
+                                    .get_halt_location(None, debug_info, new_pc, None)
+                            {
+                                let return_address = return_address_register(core)?;
+                                if inclusive_range.contains(&return_address) {
+                                    tracing::debug!(
+                                        "No line information at {:#010x}, returning to the calling statement at {:#010x}.",
+                                        new_pc,
+                                        return_address
+                                    );
+                                    let (_, new_pc) = run_to_return_address(return_address, core)?;
+                                    return SteppingMode::BreakPoint
+                                        .get_halt_location(None, debug_info, new_pc, None);
+                                }
+                            }
                         }
 
                         return SteppingMode::BreakPoint
@@ -442,6 +461,63 @@ fn run_to_address(
     })
 }
 
+/// Run the target until it returns to `return_address`, in the calling function.
+/// Unlike [`run_to_address`], the return address may be lower than the current program counter, so a breakpoint is required.
+fn run_to_return_address(
+    return_address: u64,
+    core: &mut Core,
+) -> Result<(CoreStatus, u64), DebugError> {
+    core.set_hw_breakpoint(return_address)?;
+    core.run()?;
+    let halted = core.wait_for_core_halted(Duration::from_millis(1000));
+    if halted.is_err() {
+        core.halt(Duration::from_millis(500))?;
+    }
+    core.clear_hw_breakpoint(return_address)?;
+    halted?;
+    Ok((
+        core.status()?,
+        core.read_core_reg(core.registers().program_counter())?,
+    ))
+}
+
+/// The address in the calling function, where execution resumes when the current function returns.
+///
+/// The return address register is only reliable until the current function makes calls of its own, so the
+/// stack is unwound to find the calling frame. If the unwind is not successful, the return address register is used.
+fn caller_address(
+    core: &mut Core,
+    debug_info: &DebugInfo,
+    program_counter: u64,
+) -> Result<u64, DebugError> {
+    let unwound_address = debug_info
+        .unwind(core, program_counter)
+        .ok()
+        .and_then(|stack_frames| {
+            // Inlined frames share the return address of the function they are inlined into.
+            stack_frames
+                .iter()
+                .skip_while(|stack_frame| stack_frame.is_inlined)
+                .nth(1)
+                .and_then(|stack_frame| stack_frame.pc.try_into().ok())
+        });
+
+    match unwound_address {
+        Some(address) => Ok(address),
+        None => return_address_register(core),
+    }
+}
+
+/// Read the return address register, without the Thumb bit.
+fn return_address_register(core: &mut Core) -> Result<u64, DebugError> {
+    let return_address: u64 = core.read_core_reg(core.registers().return_address())?;
+    if matches!(core.instruction_set(), Ok(InstructionSet::Thumb2)) {
+        Ok(return_address & !0b1)
+    } else {
+        Ok(return_address)
+    }
+}
+
 /// In some cases, we need to single-step the core, until ONE of the following conditions are met:
 /// - We reach the `target_address_range.end()` (inclusive)
 /// - We reach an address that is not in the sequential range of `target_address_range` (inclusive), i.e. we stepped to some kind of branch instruction.
@@ -483,7 +559,7 @@ fn get_compile_unit_info(
 ) -> Result<super::unit_info::UnitInfo, DebugError> {
     let mut units = debug_info.get_units();
     while let Some(header) = debug_info.get_next_unit_info(&mut units) {
-        match debug_info.dwarf.unit_ranges(&header.unit) {
+        match header.unit_ranges() {
             Ok(mut ranges) => {
                 while let Ok(Some(range)) = ranges.next() {
                     if (range.begin <= program_counter) && (range.end > program_counter) {
@@ -499,3 +575,58 @@ fn get_compile_unit_info(
         pc_at_error: program_counter,
     })
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::{return_address_register, run_to_return_address};
+    use crate::{
+        core::test::{core_state, MockCore},
+        Core, CoreStatus,
+    };
+
+    #[test]
+    fn return_address_without_thumb_bit() {
+        let mut state = core_state();
+        let mut core = Core::new(
+            MockCore {
+                lr: 0x0000_0805,
+                ..MockCore::new()
+            },
+            &mut state,
+        );
+
+        assert_eq!(return_address_register(&mut core).unwrap(), 0x0000_0804);
+    }
+
+    #[test]
+    fn run_to_lower_return_address() {
+        let mut state = core_state();
+        let mut core = Core::new(
+            MockCore {
+                pc: 0xc,
+                reaches_breakpoint: true,
+                ..MockCore::new()
+            },
+            &mut state,
+        );
+
+        let (status, pc) = run_to_return_address(0x4, &mut core).unwrap();
+
+        assert!(matches!(status, CoreStatus::Halted(_)));
+        assert_eq!(pc, 0x4);
+        // The only breakpoint unit is available again.
+        core.set_hw_breakpoint(0x8).unwrap();
+    }
+
+    #[test]
+    fn run_to_return_address_timeout() {
+        let mut state = core_state();
+        let mut core = Core::new(MockCore::new(), &mut state);
+
+        assert!(run_to_return_address(0x4, &mut core).is_err());
+
+        assert!(core.core_halted().unwrap());
+        core.set_hw_breakpoint(0x8).unwrap();
+    }
+}
@@ -0,0 +1,568 @@
+use super::{
+    DebugError, DebugInfo, StackFrame, Variable, VariableCache, VariableLocation, VariableName,
+    VariableType,
+};
+use crate::{core::Core, MemoryInterface};
+use std::{iter::Peekable, str::Chars, str::FromStr};
+
+/// An expression over the variables of a stack frame, e.g. `device.uart.tx_buf[3].len`.
+///
+/// The supported operations are:
+/// - Local and static variables, optionally qualified with their module path, e.g. `app::COUNTER`.
+/// - Member access of structs (`device.uart`) and tuples (`pair.0`). Pointers and references are dereferenced automatically.
+/// - Indexing of arrays and slices, e.g. `buffer[3]`.
+/// - Explicit dereferencing of pointers and references, e.g. `*ptr`.
+/// - Casts of a value in memory to a primitive type, e.g. `status as u8`. The memory of the value is re-interpreted, not converted.
+///
+/// Parentheses can be used for grouping, e.g. `(*ptr).len`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expression {
+    /// A local or static variable, with the path of its namespaces.
+    Variable(Vec<String>),
+    /// A member of a struct, or a field of a tuple.
+    Member(Box<Expression>, String),
+    /// An element of an array or a slice.
+    Index(Box<Expression>, u64),
+    /// The value a pointer or reference points to.
+    Deref(Box<Expression>),
+    /// The memory of a value, interpreted as a primitive type.
+    Cast(Box<Expression>, String),
+}
+
+/// The value of an evaluated [`Expression`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpressionValue {
+    /// The name of the type of the value.
+    pub type_name: String,
+    /// The formatted value.
+    pub value: String,
+    /// The location of the value.
+    pub memory_location: VariableLocation,
+    /// The key of the [`Variable`] in the local or static [`VariableCache`] of the stack frame.
+    /// This is `None` for the result of a cast, which is not a variable.
+    pub variable_key: Option<i64>,
+}
+
+impl FromStr for Expression {
+    type Err = DebugError;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            chars: expression.chars().peekable(),
+        };
+        let parsed = parser.expression()?;
+        parser.skip_whitespace();
+        match parser.chars.next() {
+            None => Ok(parsed),
+            Some(unexpected) => Err(expression_error(format!(
+                "Unexpected '{unexpected}' in expression '{expression}'"
+            ))),
+        }
+    }
+}
+
+impl Expression {
+    /// Evaluate the expression, using the variables of the `stack_frame` and the memory of the `core`.
+    ///
+    /// Local variables take precedence over static variables with the same name.
+    /// Variables which are needed for the evaluation are cached in the stack frame, like the
+    /// variables which are expanded on request by a debugger.
+    pub fn evaluate(
+        &self,
+        core: &mut Core<'_>,
+        debug_info: &DebugInfo,
+        stack_frame: &mut StackFrame,
+    ) -> Result<ExpressionValue, DebugError> {
+        let mut evaluator = Evaluator {
+            core,
+            debug_info,
+            stack_frame,
+        };
+
+        match evaluator.evaluate(self)? {
+            Evaluated::Variable(scope, variable) => {
+                let cache = scope.cache(evaluator.stack_frame)?;
+                Ok(ExpressionValue {
+                    type_name: variable.type_name.to_string(),
+                    value: variable.get_value(cache),
+                    memory_location: variable.memory_location,
+                    variable_key: Some(variable.variable_key),
+                })
+            }
+            Evaluated::Value(value) => Ok(value),
+        }
+    }
+}
+
+fn expression_error(message: String) -> DebugError {
+    DebugError::Other(anyhow::anyhow!(message))
+}
+
+/// A recursive descent parser for [`Expression`]s.
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl Parser<'_> {
+    /// `expression := unary ("as" type)*`
+    fn expression(&mut self) -> Result<Expression, DebugError> {
+        let mut expression = self.unary()?;
+        loop {
+            self.skip_whitespace();
+            if !self.peek_keyword("as") {
+                return Ok(expression);
+            }
+            self.identifier()?;
+            expression = Expression::Cast(Box::new(expression), self.identifier()?);
+        }
+    }
+
+    /// `unary := "*" unary | postfix`
+    fn unary(&mut self) -> Result<Expression, DebugError> {
+        self.skip_whitespace();
+        if self.chars.next_if_eq(&'*').is_some() {
+            Ok(Expression::Deref(Box::new(self.unary()?)))
+        } else {
+            self.postfix()
+        }
+    }
+
+    /// `postfix := primary ("." member | "[" index "]")*`
+    fn postfix(&mut self) -> Result<Expression, DebugError> {
+        let mut expression = self.primary()?;
+        loop {
+            self.skip_whitespace();
+            if self.chars.next_if_eq(&'.').is_some() {
+                expression = Expression::Member(Box::new(expression), self.identifier()?);
+            } else if self.chars.next_if_eq(&'[').is_some() {
+                let index = self.number()?;
+                self.expect(']')?;
+                expression = Expression::Index(Box::new(expression), index);
+            } else {
+                return Ok(expression);
+            }
+        }
+    }
+
+    /// `primary := "(" expression ")" | identifier ("::" identifier)*`
+    fn primary(&mut self) -> Result<Expression, DebugError> {
+        self.skip_whitespace();
+        if self.chars.next_if_eq(&'(').is_some() {
+            let expression = self.expression()?;
+            self.expect(')')?;
+            return Ok(expression);
+        }
+
+        let mut path = vec![self.identifier()?];
+        while self.chars.peek() == Some(&':') {
+            self.expect(':')?;
+            self.expect(':')?;
+            path.push(self.identifier()?);
+        }
+        Ok(Expression::Variable(path))
+    }
+
+    /// An identifier, or the number of a tuple field.
+    fn identifier(&mut self) -> Result<String, DebugError> {
+        self.skip_whitespace();
+        let mut identifier = String::new();
+        while let Some(c) = self
+            .chars
+            .next_if(|c| c.is_alphanumeric() || *c == '_' || *c == '$')
+        {
+            identifier.push(c);
+        }
+
+        if identifier.is_empty() {
+            Err(self.unexpected("an identifier"))
+        } else {
+            Ok(identifier)
+        }
+    }
+
+    /// A decimal, or a hexadecimal number with a `0x` prefix.
+    fn number(&mut self) -> Result<u64, DebugError> {
+        let digits = self.identifier()?;
+        match digits.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16),
+            None => digits.parse(),
+        }
+        .map_err(|_| expression_error(format!("Expected a number, found '{digits}'")))
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), DebugError> {
+        self.skip_whitespace();
+        match self.chars.next_if_eq(&expected) {
+            Some(_) => Ok(()),
+            None => Err(self.unexpected(&format!("'{expected}'"))),
+        }
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        let mut chars = self.chars.clone();
+        keyword.chars().all(|c| chars.next() == Some(c))
+            && !matches!(chars.next(), Some(c) if c.is_alphanumeric() || c == '_')
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    fn unexpected(&mut self, expected: &str) -> DebugError {
+        match self.chars.peek() {
+            Some(found) => expression_error(format!("Expected {expected}, found '{found}'")),
+            None => expression_error(format!(
+                "Expected {expected}, found the end of the expression"
+            )),
+        }
+    }
+}
+
+/// The variable cache of a stack frame, which contains a variable.
+#[derive(Debug, Clone, Copy)]
+enum Scope {
+    Local,
+    Static,
+}
+
+impl Scope {
+    fn cache(self, stack_frame: &mut StackFrame) -> Result<&mut VariableCache, DebugError> {
+        match self {
+            Scope::Local => stack_frame.local_variables.as_mut(),
+            Scope::Static => stack_frame.static_variables.as_mut(),
+        }
+        .ok_or_else(|| expression_error(format!("No {self:?} variables are available")))
+    }
+}
+
+/// The intermediate result of an evaluation.
+enum Evaluated {
+    Variable(Scope, Box<Variable>),
+    Value(ExpressionValue),
+}
+
+struct Evaluator<'a, 'probe> {
+    core: &'a mut Core<'probe>,
+    debug_info: &'a DebugInfo,
+    stack_frame: &'a mut StackFrame,
+}
+
+impl Evaluator<'_, '_> {
+    fn evaluate(&mut self, expression: &Expression) -> Result<Evaluated, DebugError> {
+        match expression {
+            Expression::Variable(path) => self.variable(path),
+            Expression::Member(base, member) => {
+                let (scope, variable) = self.dereferenced(base)?;
+                // Tuple fields are named `__0`, `__1`, ...
+                let child = self
+                    .child(scope, &variable, member)?
+                    .or(self.child(scope, &variable, &format!("__{member}"))?)
+                    .ok_or_else(|| {
+                        expression_error(format!(
+                            "'{}' of type '{}' has no member '{member}'",
+                            variable.name, variable.type_name
+                        ))
+                    })?;
+                Ok(Evaluated::Variable(scope, Box::new(child)))
+            }
+            Expression::Index(base, index) => {
+                let (scope, variable) = self.dereferenced(base)?;
+                let element = self
+                    .child(scope, &variable, &format!("__{index}"))?
+                    .ok_or_else(|| {
+                        expression_error(format!(
+                            "Index {index} is out of bounds for '{}' of type '{}'",
+                            variable.name, variable.type_name
+                        ))
+                    })?;
+                Ok(Evaluated::Variable(scope, Box::new(element)))
+            }
+            Expression::Deref(base) => {
+                let (scope, variable) = self.variable_of(base)?;
+                let pointee = self.deref(scope, &variable)?;
+                Ok(Evaluated::Variable(scope, Box::new(pointee)))
+            }
+            Expression::Cast(base, type_name) => {
+                let (_, variable) = self.variable_of(base)?;
+                let address = variable.memory_location.memory_address().map_err(|_| {
+                    expression_error(format!(
+                        "'{}' can not be cast, because it is not in memory",
+                        variable.name
+                    ))
+                })?;
+                let size = primitive_size(type_name).ok_or_else(|| {
+                    expression_error(format!("Casts to '{type_name}' are not supported"))
+                })?;
+
+                let mut bytes = vec![0; size];
+                self.core.read_8(address, &mut bytes)?;
+
+                Ok(Evaluated::Value(ExpressionValue {
+                    type_name: type_name.clone(),
+                    value: format_primitive(type_name, &bytes).unwrap_or_default(),
+                    memory_location: VariableLocation::Address(address),
+                    variable_key: None,
+                }))
+            }
+        }
+    }
+
+    /// Evaluate an expression, which has to result in a variable.
+    fn variable_of(&mut self, expression: &Expression) -> Result<(Scope, Variable), DebugError> {
+        match self.evaluate(expression)? {
+            Evaluated::Variable(scope, variable) => Ok((scope, *variable)),
+            Evaluated::Value(value) => Err(expression_error(format!(
+                "The value of type '{}' is not a variable",
+                value.type_name
+            ))),
+        }
+    }
+
+    /// Evaluate an expression, and follow pointers and references until a variable which is not a pointer is reached.
+    fn dereferenced(&mut self, expression: &Expression) -> Result<(Scope, Variable), DebugError> {
+        let (scope, mut variable) = self.variable_of(expression)?;
+        while matches!(variable.type_name, VariableType::Pointer(_)) {
+            variable = self.deref(scope, &variable)?;
+        }
+        Ok((scope, variable))
+    }
+
+    fn deref(&mut self, scope: Scope, variable: &Variable) -> Result<Variable, DebugError> {
+        if !matches!(variable.type_name, VariableType::Pointer(_)) {
+            return Err(expression_error(format!(
+                "'{}' of type '{}' can not be dereferenced",
+                variable.name, variable.type_name
+            )));
+        }
+
+        self.children(scope, variable)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                expression_error(format!(
+                    "Unable to dereference '{}' of type '{}'",
+                    variable.name, variable.type_name
+                ))
+            })
+    }
+
+    fn child(
+        &mut self,
+        scope: Scope,
+        variable: &Variable,
+        name: &str,
+    ) -> Result<Option<Variable>, DebugError> {
+        let name = VariableName::Named(name.to_string());
+        Ok(self
+            .children(scope, variable)?
+            .into_iter()
+            .find(|child| child.name == name))
+    }
+
+    /// The children of `variable`, which are cached first if their processing was deferred.
+    fn children(&mut self, scope: Scope, variable: &Variable) -> Result<Vec<Variable>, DebugError> {
+        let registers = &self.stack_frame.registers;
+        let frame_base = self.stack_frame.frame_base;
+        let cache = match scope {
+            Scope::Local => self.stack_frame.local_variables.as_mut(),
+            Scope::Static => self.stack_frame.static_variables.as_mut(),
+        }
+        .ok_or_else(|| expression_error(format!("No {scope:?} variables are available")))?;
+
+        if variable.variable_node_type.is_deferred() && !cache.has_children(variable)? {
+            let mut variable = variable.clone();
+            self.debug_info.cache_deferred_variables(
+                cache,
+                self.core,
+                &mut variable,
+                registers,
+                frame_base,
+            )?;
+        }
+
+        Ok(cache.get_children(Some(variable.variable_key))?)
+    }
+
+    /// Find the variable with the `path`, in the local variables first, and then in the static variables.
+    fn variable(&mut self, path: &[String]) -> Result<Evaluated, DebugError> {
+        let Some((name, namespace)) = path.split_last() else {
+            return Err(expression_error("Empty variable name".to_string()));
+        };
+
+        if namespace.is_empty() {
+            if let Some(root) = self.root(Scope::Local)? {
+                if let Some(local) = self.child(Scope::Local, &root, name)? {
+                    return Ok(Evaluated::Variable(Scope::Local, Box::new(local)));
+                }
+            }
+        }
+
+        if let Some(root) = self.root(Scope::Static)? {
+            let namespace = namespace.join("::");
+            if let Some(global) = self.find_static(&root, &namespace, name)? {
+                return Ok(Evaluated::Variable(Scope::Static, Box::new(global)));
+            }
+        }
+
+        Err(expression_error(format!(
+            "No variable named '{}' was found",
+            path.join("::")
+        )))
+    }
+
+    /// The root variable of the cache for `scope`, if the stack frame has the cache.
+    fn root(&mut self, scope: Scope) -> Result<Option<Variable>, DebugError> {
+        match scope.cache(self.stack_frame) {
+            Ok(cache) => Ok(cache.get_children(None)?.into_iter().next()),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Search the namespaces below `parent` for a static variable `name`.
+    /// The full path of the namespace of the variable has to end with `namespace`.
+    fn find_static(
+        &mut self,
+        parent: &Variable,
+        namespace: &str,
+        name: &str,
+    ) -> Result<Option<Variable>, DebugError> {
+        let in_namespace = namespace.is_empty()
+            || match &parent.name {
+                VariableName::Namespace(parent_namespace) => {
+                    parent_namespace == namespace
+                        || matches!(parent_namespace.strip_suffix(namespace), Some(prefix) if prefix.ends_with("::"))
+                }
+                _ => false,
+            };
+
+        let children = self.children(Scope::Static, parent)?;
+
+        if in_namespace {
+            let name = VariableName::Named(name.to_string());
+            if let Some(variable) = children.iter().find(|child| child.name == name) {
+                return Ok(Some(variable.clone()));
+            }
+        }
+
+        for child in children {
+            if child.type_name == VariableType::Namespace {
+                if let Some(variable) = self.find_static(&child, namespace, name)? {
+                    return Ok(Some(variable));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// The size in bytes of a primitive type, which can be the target of a cast.
+fn primitive_size(type_name: &str) -> Option<usize> {
+    Some(match type_name {
+        "u8" | "i8" | "bool" => 1,
+        "u16" | "i16" => 2,
+        "u32" | "i32" | "f32" | "char" => 4,
+        "u64" | "i64" | "f64" => 8,
+        "u128" | "i128" => 16,
+        _ => return None,
+    })
+}
+
+/// Format the little endian `bytes` as a value of the primitive type `type_name`.
+fn format_primitive(type_name: &str, bytes: &[u8]) -> Option<String> {
+    let mut buffer = [0u8; 16];
+    buffer.get_mut(..bytes.len())?.copy_from_slice(bytes);
+    let unsigned = u128::from_le_bytes(buffer);
+
+    Some(match type_name {
+        "bool" => (unsigned != 0).to_string(),
+        "u8" | "u16" | "u32" | "u64" | "u128" => unsigned.to_string(),
+        "i8" => (unsigned as i8).to_string(),
+        "i16" => (unsigned as i16).to_string(),
+        "i32" => (unsigned as i32).to_string(),
+        "i64" => (unsigned as i64).to_string(),
+        "i128" => (unsigned as i128).to_string(),
+        "f32" => f32::from_bits(unsigned as u32).to_string(),
+        "f64" => f64::from_bits(unsigned as u64).to_string(),
+        "char" => char::from_u32(unsigned as u32)
+            .map(|c| format!("{c:?}"))
+            .unwrap_or_else(|| format!("<invalid char {unsigned:#x}>")),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::{format_primitive, Expression};
+
+    fn variable(name: &str) -> Box<Expression> {
+        Box::new(Expression::Variable(vec![name.to_string()]))
+    }
+
+    #[test]
+    fn parse_member_and_index() {
+        let expression: Expression = "device.uart.tx_buf[3].len".parse().unwrap();
+
+        assert_eq!(
+            expression,
+            Expression::Member(
+                Box::new(Expression::Index(
+                    Box::new(Expression::Member(
+                        Box::new(Expression::Member(variable("device"), "uart".to_string())),
+                        "tx_buf".to_string()
+                    )),
+                    3
+                )),
+                "len".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn parse_deref_cast_and_path() {
+        let expression: Expression = "(*app::STATE).0 as u8".parse().unwrap();
+
+        assert_eq!(
+            expression,
+            Expression::Cast(
+                Box::new(Expression::Member(
+                    Box::new(Expression::Deref(Box::new(Expression::Variable(vec![
+                        "app".to_string(),
+                        "STATE".to_string()
+                    ])))),
+                    "0".to_string()
+                )),
+                "u8".to_string()
+            )
+        );
+
+        assert_eq!(
+            "buffer[0x10]".parse::<Expression>().unwrap(),
+            Expression::Index(variable("buffer"), 16)
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        for invalid in ["", "a.", "a[", "a[b]", "a b", "(a", "a as", "a:b"] {
+            assert!(
+                invalid.parse::<Expression>().is_err(),
+                "'{invalid}' should not parse"
+            );
+        }
+    }
+
+    #[test]
+    fn format_primitives() {
+        assert_eq!(format_primitive("i8", &[0xFF]).unwrap(), "-1");
+        assert_eq!(format_primitive("u16", &[0x34, 0x12]).unwrap(), "4660");
+        assert_eq!(format_primitive("bool", &[0x01]).unwrap(), "true");
+        assert_eq!(
+            format_primitive("f32", &1.5f32.to_le_bytes()).unwrap(),
+            "1.5"
+        );
+        assert_eq!(format_primitive("char", &[0x41, 0, 0, 0]).unwrap(), "'A'");
+        assert_eq!(format_primitive("usize", &[0; 4]), None);
+    }
+}
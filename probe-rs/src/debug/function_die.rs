@@ -78,8 +78,14 @@ impl<'debugunit, 'abbrev, 'unit: 'debugunit, 'unit_info, 'debug_info>
     pub(crate) fn function_name(&self) -> Option<String> {
         if let Some(fn_name_attr) = self.get_attribute(gimli::DW_AT_name) {
             match fn_name_attr.value() {
-                gimli::AttributeValue::DebugStrRef(fn_name_ref) => {
-                    match self.unit_info.debug_info.dwarf.string(fn_name_ref) {
+                value @ (gimli::AttributeValue::DebugStrRef(_)
+                | gimli::AttributeValue::DebugStrOffsetsIndex(_)
+                | gimli::AttributeValue::String(_)) => {
+                    match self
+                        .unit_info
+                        .dwarf
+                        .attr_string(&self.unit_info.unit, value)
+                    {
                         Ok(fn_name_raw) => Some(String::from_utf8_lossy(&fn_name_raw).to_string()),
                         Err(error) => {
                             tracing::debug!("No value for DW_AT_name: {:?}: error", error);
@@ -8,10 +8,18 @@
 // Bad things happen to the VSCode debug extenison and debug_adapter if we panic at the wrong time.
 #![warn(clippy::unwrap_used, clippy::panic, clippy::expect_used)]
 
+/// Breakpoints at functions and source locations, which are resolved using the debug information.
+pub mod breakpoint;
+/// Reports about panics and HardFaults of the program running on a core.
+pub mod crash_report;
+/// Loading of debug information from compressed sections, external debug files and split DWARF.
+pub(crate) mod debug_file;
 /// Debug information which is parsed from DWARF debugging information.
 pub mod debug_info;
 /// Stepping through a program during debug, at various granularities.
 pub mod debug_step;
+/// Evaluation of expressions over the variables of a stack frame.
+pub mod expression;
 /// References to the DIE (debug information entry) of functions.
 pub mod function_die;
 /// Target Register definitions.
@@ -20,6 +28,10 @@ pub mod registers;
 pub(crate) mod source_statement;
 /// The stack frame information used while unwinding the stack from a specific program counter.
 pub mod stack_frame;
+/// Access to static variables by name.
+pub mod static_variable;
+/// Symbol lookup and demangling, using only the symbol table.
+pub mod symbols;
 /// Information about a Unit in the debug information.
 pub mod unit_info;
 /// Variable information used during debug.
@@ -28,8 +40,17 @@ pub mod variable;
 pub mod variable_cache;
 
 pub use self::{
-    debug_info::*, debug_step::SteppingMode, registers::*, stack_frame::StackFrame, variable::*,
-    variable_cache::VariableCache,
+    breakpoint::{SourceBreakpoint, SourceBreakpoints},
+    crash_report::{CrashCause, CrashReport, FaultReport, PanicReport},
+    debug_info::*,
+    debug_step::SteppingMode,
+    expression::{Expression, ExpressionValue},
+    registers::*,
+    stack_frame::StackFrame,
+    static_variable::{StaticValue, StaticVariable},
+    symbols::{demangle, Symbol, Symbols},
+    variable::*,
+    variable_cache::{VariableCache, VariableValueTree},
 };
 use crate::{core::Core, MemoryInterface};
 use gimli::DebuggingInformationEntry;
@@ -217,12 +238,15 @@ fn extract_line(attribute_value: gimli::AttributeValue<GimliReader>) -> Option<u
 }
 
 fn extract_name(
-    debug_info: &DebugInfo,
+    unit_info: &unit_info::UnitInfo,
     attribute_value: gimli::AttributeValue<GimliReader>,
 ) -> String {
     match attribute_value {
-        gimli::AttributeValue::DebugStrRef(name_ref) => {
-            if let Ok(name_raw) = debug_info.dwarf.string(name_ref) {
+        gimli::AttributeValue::DebugStrRef(_) | gimli::AttributeValue::DebugStrOffsetsIndex(_) => {
+            if let Ok(name_raw) = unit_info
+                .dwarf
+                .attr_string(&unit_info.unit, attribute_value)
+            {
                 String::from_utf8_lossy(&name_raw).to_string()
             } else {
                 "Invalid DW_AT_name value".to_string()
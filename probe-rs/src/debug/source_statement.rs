@@ -101,7 +101,8 @@ impl SourceStatements {
                         && (row.line() == source_row.line || row.line().is_none())
                         && row.column() == source_row.column)
                 {
-                    if source_row.low_pc() >= program_counter {
+                    // Rows without a line number are compiler generated glue (line 0), and are not valid halt locations.
+                    if source_row.low_pc() >= program_counter && source_row.line.is_some() {
                         // We need to close off the "current" source statement and add it to the list.
                         source_row.sequence_range = program_counter..active_sequence.end;
                         source_statements.add(source_row.clone());
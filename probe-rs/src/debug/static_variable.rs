@@ -0,0 +1,166 @@
+use super::DebugError;
+use crate::{core::Core, MemoryInterface};
+
+/// A static variable of the program, which was found by its name with [`DebugInfo::get_static`](super::DebugInfo::get_static).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaticVariable {
+    /// The name of the variable, including its module path, e.g. `app::COUNTER`.
+    pub name: String,
+    /// The address of the variable in target memory.
+    pub address: u64,
+    /// The size of the variable in bytes, if it is known.
+    pub size: Option<u64>,
+    /// The name of the type of the variable, e.g. `u32`.
+    /// This is `None` if the variable was only found in the symbol table.
+    pub type_name: Option<String>,
+    /// Indicates if the type is a primitive type, e.g. `u32`, and not a type which wraps it, e.g. `AtomicU32`.
+    pub is_base_type: bool,
+}
+
+impl StaticVariable {
+    /// Read the value of the variable from the target.
+    ///
+    /// The type `T` has to match the type of a primitive variable, otherwise the sizes have to match.
+    pub fn read<T: StaticValue>(&self, core: &mut Core<'_>) -> Result<T, DebugError> {
+        self.check_type::<T>()?;
+
+        let mut bytes = vec![0; T::SIZE];
+        core.read(self.address, &mut bytes)?;
+
+        Ok(T::from_le_bytes(&bytes))
+    }
+
+    /// Write a new `value` of the variable to the target.
+    ///
+    /// The type `T` has to match the type of a primitive variable, otherwise the sizes have to match.
+    pub fn write<T: StaticValue>(&self, core: &mut Core<'_>, value: T) -> Result<(), DebugError> {
+        self.check_type::<T>()?;
+
+        core.write_8(self.address, &value.to_le_bytes())?;
+
+        Ok(())
+    }
+
+    /// Read the raw memory of the variable from the target.
+    pub fn read_bytes(&self, core: &mut Core<'_>) -> Result<Vec<u8>, DebugError> {
+        let size = self.size.ok_or_else(|| {
+            DebugError::Other(anyhow::anyhow!(
+                "The size of the static variable '{}' is unknown",
+                self.name
+            ))
+        })?;
+
+        let mut bytes = vec![0; size as usize];
+        core.read(self.address, &mut bytes)?;
+
+        Ok(bytes)
+    }
+
+    /// Check that a value of type `T` can be stored in the variable.
+    fn check_type<T: StaticValue>(&self) -> Result<(), DebugError> {
+        if let (true, Some(type_name)) = (self.is_base_type, &self.type_name) {
+            if type_name != T::TYPE_NAME {
+                return Err(DebugError::Other(anyhow::anyhow!(
+                    "The static variable '{}' has type '{}', not '{}'",
+                    self.name,
+                    type_name,
+                    T::TYPE_NAME
+                )));
+            }
+        }
+
+        match self.size {
+            Some(size) if size != T::SIZE as u64 => Err(DebugError::Other(anyhow::anyhow!(
+                "The static variable '{}' has a size of {} bytes, but '{}' has a size of {} bytes",
+                self.name,
+                size,
+                T::TYPE_NAME,
+                T::SIZE
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A primitive type, which can be read from and written to a [`StaticVariable`].
+pub trait StaticValue: Sized {
+    /// The name of the type in the debug information.
+    const TYPE_NAME: &'static str;
+    /// The size of the type in bytes.
+    const SIZE: usize;
+
+    /// Create a value from its little endian representation, which has a length of [`StaticValue::SIZE`].
+    fn from_le_bytes(bytes: &[u8]) -> Self;
+
+    /// The little endian representation of the value.
+    fn to_le_bytes(&self) -> Vec<u8>;
+}
+
+macro_rules! static_value {
+    ($($ty:ty),*) => {
+        $(
+            impl StaticValue for $ty {
+                const TYPE_NAME: &'static str = stringify!($ty);
+                const SIZE: usize = std::mem::size_of::<$ty>();
+
+                fn from_le_bytes(bytes: &[u8]) -> Self {
+                    let mut buffer = [0; std::mem::size_of::<$ty>()];
+                    buffer.copy_from_slice(bytes);
+                    <$ty>::from_le_bytes(buffer)
+                }
+
+                fn to_le_bytes(&self) -> Vec<u8> {
+                    <$ty>::to_le_bytes(*self).to_vec()
+                }
+            }
+        )*
+    };
+}
+
+static_value!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+impl StaticValue for bool {
+    const TYPE_NAME: &'static str = "bool";
+    const SIZE: usize = 1;
+
+    fn from_le_bytes(bytes: &[u8]) -> Self {
+        bytes[0] != 0
+    }
+
+    fn to_le_bytes(&self) -> Vec<u8> {
+        vec![*self as u8]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::StaticVariable;
+
+    fn variable(type_name: &str, size: u64, is_base_type: bool) -> StaticVariable {
+        StaticVariable {
+            name: "app::COUNTER".to_string(),
+            address: 0x2000_0000,
+            size: Some(size),
+            type_name: Some(type_name.to_string()),
+            is_base_type,
+        }
+    }
+
+    #[test]
+    fn primitive_types_must_match() {
+        let counter = variable("u32", 4, true);
+
+        assert!(counter.check_type::<u32>().is_ok());
+        assert!(counter.check_type::<i32>().is_err());
+        assert!(counter.check_type::<u16>().is_err());
+    }
+
+    #[test]
+    fn wrapped_types_must_have_the_same_size() {
+        let counter = variable("AtomicU32", 4, false);
+
+        assert!(counter.check_type::<u32>().is_ok());
+        assert!(counter.check_type::<f32>().is_ok());
+        assert!(counter.check_type::<u64>().is_err());
+    }
+}
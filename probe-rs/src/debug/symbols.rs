@@ -0,0 +1,200 @@
+use super::DebugError;
+use object::read::{Object, ObjectSymbol};
+use std::path::Path;
+
+/// A function or data symbol from the symbol table of an ELF file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    /// The demangled name of the symbol, e.g. `cortex_m::asm::delay`.
+    ///
+    /// For Rust symbols, the hash suffix is not included.
+    pub name: String,
+    /// The name of the symbol as it appears in the symbol table.
+    pub mangled_name: String,
+    /// The address of the symbol. For Thumb functions, this is the address of the first instruction, without the Thumb bit.
+    pub address: u64,
+    /// The size of the symbol in bytes. This is zero if the size is unknown.
+    pub size: u64,
+    /// Indicates that the symbol is a function.
+    pub is_function: bool,
+}
+
+impl Symbol {
+    /// Check if the `address` is part of this symbol.
+    ///
+    /// A symbol without size only contains its start address.
+    pub fn contains(&self, address: u64) -> bool {
+        if self.size == 0 {
+            address == self.address
+        } else {
+            self.address <= address && address - self.address < self.size
+        }
+    }
+}
+
+/// Lookup of symbols by address and name, based only on the symbol table of an ELF file.
+///
+/// This does not require DWARF debug information, so it can be used for lightweight tools
+/// such as profilers, or to decorate addresses in log output.
+#[derive(Debug, Clone, Default)]
+pub struct Symbols {
+    /// All symbols, sorted by their address and size.
+    symbols: Vec<Symbol>,
+}
+
+impl Symbols {
+    /// Read the symbols from an ELF file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, DebugError> {
+        let data = std::fs::read(path)?;
+
+        Symbols::from_raw(&data)
+    }
+
+    /// Read the symbols from a buffer containing an ELF file.
+    pub fn from_raw(data: &[u8]) -> Result<Self, DebugError> {
+        let object = object::File::parse(data)?;
+
+        Ok(Symbols::from_object(&object))
+    }
+
+    pub(crate) fn from_object(object: &object::File<'_>) -> Self {
+        // On ARM, bit 0 of the address of a function indicates that it uses the Thumb instruction set.
+        let address_mask = if object.architecture() == object::Architecture::Arm {
+            !1
+        } else {
+            !0
+        };
+
+        let mut symbols: Vec<Symbol> = object
+            .symbols()
+            .filter(|symbol| symbol.is_definition())
+            .filter(|symbol| {
+                matches!(
+                    symbol.kind(),
+                    object::SymbolKind::Text | object::SymbolKind::Data
+                )
+            })
+            .filter_map(|symbol| {
+                let mangled_name = symbol.name().ok()?;
+
+                // Skip the ARM mapping symbols (`$t`, `$d`, ...), which only mark the type of the following code.
+                if mangled_name.is_empty() || mangled_name.starts_with('$') {
+                    return None;
+                }
+
+                let is_function = symbol.kind() == object::SymbolKind::Text;
+
+                Some(Symbol {
+                    name: demangle(mangled_name),
+                    mangled_name: mangled_name.to_string(),
+                    address: if is_function {
+                        symbol.address() & address_mask
+                    } else {
+                        symbol.address()
+                    },
+                    size: symbol.size(),
+                    is_function,
+                })
+            })
+            .collect();
+
+        symbols.sort_by_key(|symbol| (symbol.address, symbol.size));
+
+        Symbols { symbols }
+    }
+
+    /// Get the symbol which contains the `address`.
+    ///
+    /// If multiple symbols contain the address, e.g. aliases of the same function,
+    /// the one with the highest start address is returned, and of those the largest one.
+    pub fn symbol_at(&self, address: u64) -> Option<&Symbol> {
+        let end = self
+            .symbols
+            .partition_point(|symbol| symbol.address <= address);
+
+        self.symbols[..end]
+            .iter()
+            .rev()
+            .find(|symbol| symbol.contains(address))
+    }
+
+    /// Get the address of the symbol with the given name.
+    ///
+    /// The name can either be the demangled name, e.g. `cortex_m::asm::delay`, or the mangled name from the symbol table.
+    pub fn address_of(&self, name: &str) -> Option<u64> {
+        self.symbols
+            .iter()
+            .find(|symbol| symbol.name == name || symbol.mangled_name == name)
+            .map(|symbol| symbol.address)
+    }
+
+    /// Iterate over all symbols, sorted by their address.
+    pub fn iter(&self) -> impl Iterator<Item = &Symbol> {
+        self.symbols.iter()
+    }
+
+    /// The number of symbols.
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    /// Returns `true` if there are no symbols.
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+}
+
+/// Demangle a Rust (legacy or v0 mangling) or C++ symbol name.
+///
+/// The hash suffix of Rust symbols is removed. Names which are not mangled are returned unchanged.
+pub fn demangle(name: &str) -> String {
+    if let Ok(demangled) = rustc_demangle::try_demangle(name) {
+        return format!("{demangled:#}");
+    }
+
+    if name.starts_with("_Z") {
+        if let Ok(demangled) = cpp_demangle::Symbol::new(name)
+            .map_err(|_| std::fmt::Error)
+            .and_then(|symbol| symbol.demangle(&Default::default()))
+        {
+            return demangled;
+        }
+    }
+
+    name.to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::demangle;
+
+    #[test]
+    fn demangle_rust_legacy() {
+        assert_eq!(
+            demangle("_ZN22probe_rs_debugger_test3U3217h888b9e0a719234ecE"),
+            "probe_rs_debugger_test::U32"
+        );
+    }
+
+    #[test]
+    fn demangle_rust_v0() {
+        assert_eq!(
+            demangle("_RNvNtCs1234_7mycrate3foo3bar"),
+            "mycrate::foo::bar"
+        );
+    }
+
+    #[test]
+    fn demangle_cpp() {
+        assert_eq!(
+            demangle("_ZN9namespace5Class6methodEi"),
+            "namespace::Class::method(int)"
+        );
+    }
+
+    #[test]
+    fn plain_names_are_unchanged() {
+        assert_eq!(demangle("Reset"), "Reset");
+        assert_eq!(demangle("_Zinvalid"), "_Zinvalid");
+    }
+}
@@ -19,10 +19,33 @@ pub(crate) enum ExpressionResult {
 /// A struct containing information about a single compilation unit.
 pub(crate) struct UnitInfo<'debuginfo> {
     pub(crate) debug_info: &'debuginfo DebugInfo,
+    /// The DWARF sections which contain this unit. For split units, these are the sections of the `.dwo` file or `.dwp` package.
+    pub(crate) dwarf: &'debuginfo gimli::Dwarf<DwarfReader>,
     pub(crate) unit: gimli::Unit<GimliReader, usize>,
+    /// For split units, the skeleton unit in the main file, which contains the address ranges.
+    pub(crate) skeleton: Option<gimli::Unit<GimliReader, usize>>,
 }
 
 impl<'debuginfo> UnitInfo<'debuginfo> {
+    /// The offset of this unit in the `.debug_info` section of the main file, which can be used
+    /// with [`DebugInfo::get_unit_info_at`]. For split units, this is the offset of the skeleton unit.
+    pub(crate) fn header_offset(&self) -> Option<gimli::DebugInfoOffset> {
+        self.skeleton
+            .as_ref()
+            .unwrap_or(&self.unit)
+            .header
+            .offset()
+            .as_debug_info_offset()
+    }
+
+    /// The address ranges of this unit.
+    pub(crate) fn unit_ranges(&self) -> Result<gimli::RangeIter<GimliReader>, DebugError> {
+        Ok(match &self.skeleton {
+            Some(skeleton) => self.debug_info.dwarf.unit_ranges(skeleton)?,
+            None => self.dwarf.unit_ranges(&self.unit)?,
+        })
+    }
+
     /// Retrieve the value of the DW_AT_language attribute of the compilation unit.
     /// This is used to influence logic for special cases related to the way the debug_info is generated.
     /// In the unlikely event that we are unable to retrieve the language, we assume Rust,
@@ -57,7 +80,7 @@ impl<'debuginfo> UnitInfo<'debuginfo> {
         let mut entries_cursor = self.unit.entries();
         while let Ok(Some((_depth, current))) = entries_cursor.next_dfs() {
             if current.tag() == gimli::DW_TAG_subprogram {
-                let mut ranges = self.debug_info.dwarf.die_ranges(&self.unit, current)?;
+                let mut ranges = self.dwarf.die_ranges(&self.unit, current)?;
 
                 while let Ok(Some(ranges)) = ranges.next() {
                     if ranges.begin <= address && address < ranges.end {
@@ -151,7 +174,7 @@ impl<'debuginfo> UnitInfo<'debuginfo> {
                 }
 
                 if current.tag() == gimli::DW_TAG_inlined_subroutine {
-                    let mut ranges = self.debug_info.dwarf.die_ranges(&self.unit, current)?;
+                    let mut ranges = self.dwarf.die_ranges(&self.unit, current)?;
 
                     while let Ok(Some(ranges)) = ranges.next() {
                         if ranges.begin <= address && address < ranges.end {
@@ -262,7 +285,7 @@ impl<'debuginfo> UnitInfo<'debuginfo> {
             .transpose()?
             .flatten()
         {
-            child_variable.name = VariableName::Named(extract_name(self.debug_info, name));
+            child_variable.name = VariableName::Named(extract_name(self, name));
         }
 
         if let Some(attributes_entry) = attributes_entry {
@@ -430,7 +453,7 @@ impl<'debuginfo> UnitInfo<'debuginfo> {
                             let mut discriminant_variable = cache.cache_variable(
                                 Some(parent_variable.variable_key),
                                 Variable::new(
-                                    self.unit.header.offset().as_debug_info_offset(),
+                                    self.header_offset(),
                                     Some(discriminant_node.entry().offset()),
                                 ),
                                 core,
@@ -448,9 +471,7 @@ impl<'debuginfo> UnitInfo<'debuginfo> {
                                 parent_variable.role = VariantRole::VariantPart(u64::MAX);
                             } else {
                                 parent_variable.role = VariantRole::VariantPart(
-                                    discriminant_variable
-                                        .get_value(cache)
-                                        .parse()
+                                    read_discriminant(&discriminant_variable, core, cache)
                                         .unwrap_or(u64::MAX),
                                 );
                             }
@@ -562,12 +583,12 @@ impl<'debuginfo> UnitInfo<'debuginfo> {
                     gimli::DW_TAG_namespace => {
                         // Use these parents to extract `statics`.
                         let mut namespace_variable = Variable::new(
-                            self.unit.header.offset().as_debug_info_offset(),
+                            self.header_offset(),
                             Some(child_node.entry().offset()),
                         );
 
                         namespace_variable.name = if let Ok(Some(attr)) = child_node.entry().attr(gimli::DW_AT_name) {
-                            VariableName::Namespace(extract_name(self.debug_info, attr.value()))
+                            VariableName::Namespace(extract_name(self, attr.value()))
                         } else { VariableName::AnonymousNamespace };
                         namespace_variable.type_name = VariableType::Namespace;
                         namespace_variable.memory_location = VariableLocation::Unavailable;
@@ -579,20 +600,20 @@ impl<'debuginfo> UnitInfo<'debuginfo> {
                                 gimli::DW_TAG_variable => {
                                     // We only want the TOP level variables of the namespace (statics).
                                     let static_child_variable = cache.cache_variable(Some(namespace_variable.variable_key), Variable::new(
-                                        self.unit.header.offset().as_debug_info_offset(),
+                                        self.header_offset(),
                                         Some(namespace_child_node.entry().offset()),), core)?;
                                     self.process_tree_node_attributes(&mut namespace_child_node, &mut namespace_variable, static_child_variable, core, stack_frame_registers, frame_base, cache)?;
                                 }
                                 gimli::DW_TAG_namespace => {
                                     // Recurse for additional namespace variables.
                                     let mut namespace_child_variable = Variable::new(
-                                        self.unit.header.offset().as_debug_info_offset(),
+                                        self.header_offset(),
                                         Some(namespace_child_node.entry().offset()),);
                                     namespace_child_variable.name = if let Ok(Some(attr)) = namespace_child_node.entry().attr(gimli::DW_AT_name) {
 
                                         match &namespace_variable.name {
                                             VariableName::Namespace(name) => {
-                                            VariableName::Namespace(format!("{}::{}", name, extract_name(self.debug_info, attr.value())))
+                                            VariableName::Namespace(format!("{}::{}", name, extract_name(self, attr.value())))
                                             }
                                             other => return Err(DebugError::UnwindIncompleteResults {message: format!("Unable to construct namespace variable, unexpected parent name: {other:?}")})
                                         }
@@ -621,7 +642,7 @@ impl<'debuginfo> UnitInfo<'debuginfo> {
                     gimli::DW_TAG_enumerator         // Possible values for enumerators, used by extract_type() when processing DW_TAG_enumeration_type.
                     => {
                         let mut child_variable = cache.cache_variable(Some(parent_variable.variable_key), Variable::new(
-                        self.unit.header.offset().as_debug_info_offset(),
+                        self.header_offset(),
                         Some(child_node.entry().offset()),
                     ), core)?;
                         child_variable = self.process_tree_node_attributes(&mut child_node, &mut parent_variable, child_variable, core, stack_frame_registers, frame_base, cache,)?;
@@ -646,7 +667,7 @@ impl<'debuginfo> UnitInfo<'debuginfo> {
                         // TODO: Handle Level 3 nodes that belong to a DW_AT_discr_list, instead of having a discreet DW_AT_discr_value 
                         let mut child_variable = cache.cache_variable(
                             Some(parent_variable.variable_key),
-                            Variable::new(self.unit.header.offset().as_debug_info_offset(),Some(child_node.entry().offset())),
+                            Variable::new(self.header_offset(),Some(child_node.entry().offset())),
                             core
                         )?;
                         // To determine the discriminant, we use the following rules:
@@ -666,7 +687,7 @@ impl<'debuginfo> UnitInfo<'debuginfo> {
                         if !cache.has_children(&parent_variable)? {
                             let mut child_variable = cache.cache_variable(
                                 Some(parent_variable.variable_key),
-                                Variable::new(self.unit.header.offset().as_debug_info_offset(), Some(child_node.entry().offset())),
+                                Variable::new(self.header_offset(), Some(child_node.entry().offset())),
                                 core
                             )?;
                             self.extract_variant_discriminant(&child_node, &mut child_variable)?;
@@ -695,7 +716,7 @@ impl<'debuginfo> UnitInfo<'debuginfo> {
                         // This tag is a child node fore parent types such as (array, vector, etc.).
                         // Recursively process each node, but pass the parent_variable so that new children are caught despite missing these tags.
                         let mut range_variable = cache.cache_variable(Some(parent_variable.variable_key),Variable::new(
-                        self.unit.header.offset().as_debug_info_offset(),
+                        self.header_offset(),
                         Some(child_node.entry().offset()),
                     ), core)?;
                         range_variable = self.process_tree_node_attributes(&mut child_node, &mut parent_variable, range_variable, core, stack_frame_registers, frame_base, cache)?;
@@ -740,10 +761,9 @@ impl<'debuginfo> UnitInfo<'debuginfo> {
                                 = child_node.entry().attr(gimli::DW_AT_ranges) {
                                     match ranges.value() {
                                         gimli::AttributeValue::RangeListsRef(raw_range_lists_offset) => {
-                                            let range_lists_offset = self.debug_info.dwarf.ranges_offset_from_raw(&self.unit, raw_range_lists_offset);
+                                            let range_lists_offset = self.dwarf.ranges_offset_from_raw(&self.unit, raw_range_lists_offset);
 
                                             if let Ok(mut ranges) = self
-                                                .debug_info
                                                 .dwarf
                                                 .ranges(&self.unit, range_lists_offset) {
                                                     while let Ok(Some(ranges)) = ranges.next() {
@@ -813,12 +833,16 @@ impl<'debuginfo> UnitInfo<'debuginfo> {
                 Ok(optional_discr_value_attr) => {
                     match optional_discr_value_attr {
                         Some(discr_attr) => {
-                            match discr_attr.value() {
-                                gimli::AttributeValue::Data1(const_value) => {
-                                    VariantRole::Variant(const_value as u64)
-                                }
-                                other_attribute_value => {
-                                    variable.set_value(VariableValue::Error(format!("Unimplemented: Attribute Value for DW_AT_discr_value: {:.100}", format!("{other_attribute_value:?}"))));
+                            let discr_value = discr_attr.value();
+                            // Niche values are encoded with the size of the niche field,
+                            // e.g. `Data8` for a null pointer niche.
+                            match discr_value
+                                .udata_value()
+                                .or_else(|| discr_value.sdata_value().map(|value| value as u64))
+                            {
+                                Some(const_value) => VariantRole::Variant(const_value),
+                                None => {
+                                    variable.set_value(VariableValue::Error(format!("Unimplemented: Attribute Value for DW_AT_discr_value: {:.100}", format!("{discr_value:?}"))));
                                     VariantRole::Variant(u64::MAX)
                                 }
                             }
@@ -858,7 +882,7 @@ impl<'debuginfo> UnitInfo<'debuginfo> {
     ) -> Result<Variable, DebugError> {
         let type_name = match node.entry().attr(gimli::DW_AT_name) {
             Ok(optional_name_attr) => {
-                optional_name_attr.map(|name_attr| extract_name(self.debug_info, name_attr.value()))
+                optional_name_attr.map(|name_attr| extract_name(self, name_attr.value()))
             }
             Err(error) => {
                 let message = format!("Error: evaluating type name: {error:?} ");
@@ -980,8 +1004,10 @@ impl<'debuginfo> UnitInfo<'debuginfo> {
                             // The default behaviour is to defer the processing of child types.
                             child_variable.variable_node_type =
                                 VariableNodeType::TypeOffset(node.entry().offset());
+                            let slice = is_slice(name);
                             // In some cases, it really simplifies the UX if we can auto resolve the children and derive a value that is visible at first glance to the user.
                             if name.starts_with("&str")
+                                || slice
                                 || name.starts_with("Option")
                                 || name.starts_with("Some")
                                 || name.starts_with("Result")
@@ -1000,6 +1026,16 @@ impl<'debuginfo> UnitInfo<'debuginfo> {
                                     cache,
                                 )?;
                                 child_variable.variable_node_type = temp_node_type;
+
+                                if slice {
+                                    self.expand_slice(
+                                        cache,
+                                        &mut child_variable,
+                                        core,
+                                        stack_frame_registers,
+                                        frame_base,
+                                    )?;
+                                }
                             }
                         }
                     } else {
@@ -1033,10 +1069,12 @@ impl<'debuginfo> UnitInfo<'debuginfo> {
                             cache.get_children(Some(child_variable.variable_key))?;
 
                         if let VariableLocation::Address(address) = child_variable.memory_location {
-                            // NOTE: hard-coding value of variable.byte_size to 1 ... replace with code if necessary.
-                            let mut buff = [0u8; 1];
-                            core.read(address, &mut buff)?;
-                            let this_enum_const_value = u8::from_le_bytes(buff).to_string();
+                            let this_enum_const_value = read_unsigned(
+                                core,
+                                address,
+                                child_variable.byte_size.unwrap_or(1),
+                            )?
+                            .to_string();
                             let enumumerator_value =
                                 match enumerator_values.into_iter().find(|enumerator_variable| {
                                     enumerator_variable.get_value(cache) == this_enum_const_value
@@ -1092,10 +1130,7 @@ impl<'debuginfo> UnitInfo<'debuginfo> {
                                             let mut subrange_variable = cache.cache_variable(
                                                 Some(child_variable.variable_key),
                                                 Variable::new(
-                                                    self.unit
-                                                        .header
-                                                        .offset()
-                                                        .as_debug_info_offset(),
+                                                    self.header_offset(),
                                                     Some(node.entry().offset()),
                                                 ),
                                                 core,
@@ -1231,12 +1266,9 @@ impl<'debuginfo> UnitInfo<'debuginfo> {
                                             .attr(gimli::DW_AT_name)
                                         {
                                             Ok(optional_name_attr) => match optional_name_attr {
-                                                Some(name_attr) => {
-                                                    VariableType::Other(extract_name(
-                                                        self.debug_info,
-                                                        name_attr.value(),
-                                                    ))
-                                                }
+                                                Some(name_attr) => VariableType::Other(
+                                                    extract_name(self, name_attr.value()),
+                                                ),
                                                 None => VariableType::Unknown,
                                             },
                                             Err(error) => VariableType::Other(format!(
@@ -1313,10 +1345,7 @@ impl<'debuginfo> UnitInfo<'debuginfo> {
         if let Ok(array_member_type_node) = array_member_type_tree.root() {
             let mut array_member_variable = cache.cache_variable(
                 Some(child_variable.variable_key),
-                Variable::new(
-                    self.unit.header.offset().as_debug_info_offset(),
-                    Some(unit_ref),
-                ),
+                Variable::new(self.header_offset(), Some(unit_ref)),
                 core,
             )?;
             array_member_variable.member_index = Some(array_member_index);
@@ -1372,6 +1401,93 @@ impl<'debuginfo> UnitInfo<'debuginfo> {
         Ok(())
     }
 
+    /// Replace the `data_ptr` and `length` members of a slice (`&[T]`), which is a fat pointer,
+    /// with the elements of the slice, so the slice can be inspected like an array.
+    ///
+    /// At most [`MAX_SLICE_ELEMENTS`] elements are expanded, to work around buggy lengths.
+    fn expand_slice(
+        &self,
+        cache: &mut VariableCache,
+        slice_variable: &mut Variable,
+        core: &mut Core,
+        stack_frame_registers: &DebugRegisters,
+        frame_base: Option<u64>,
+    ) -> Result<(), DebugError> {
+        let members = cache.get_children(Some(slice_variable.variable_key))?;
+        let member = |name: &str| {
+            members
+                .iter()
+                .find(|member| member.name == VariableName::Named(name.to_string()))
+        };
+
+        let (Some(data_ptr), Some(length)) = (member("data_ptr"), member("length")) else {
+            return Ok(());
+        };
+        let Ok(length) = length.get_value(cache).parse::<i64>() else {
+            return Ok(());
+        };
+        let (VariableLocation::Address(data_ptr_location), Some(element_type)) = (
+            &data_ptr.memory_location,
+            data_ptr
+                .variable_unit_offset
+                .map(|offset| self.pointee_type(offset))
+                .transpose()?
+                .flatten(),
+        ) else {
+            return Ok(());
+        };
+
+        if length == 0 {
+            return Ok(());
+        }
+
+        if length > MAX_SLICE_ELEMENTS {
+            tracing::warn!(
+                "Very long slice ({} elements), truncating to {} elements.",
+                length,
+                MAX_SLICE_ELEMENTS
+            );
+        }
+
+        let data_address = core.read_word_32(*data_ptr_location)? as u64;
+
+        cache.remove_cache_entry_children(slice_variable.variable_key)?;
+        slice_variable.memory_location = VariableLocation::Address(data_address);
+        slice_variable.byte_size = None;
+        slice_variable.range_lower_bound = 0;
+        slice_variable.range_upper_bound = length.min(MAX_SLICE_ELEMENTS);
+
+        for index in 0..slice_variable.range_upper_bound {
+            self.expand_array_member(
+                element_type,
+                cache,
+                slice_variable,
+                core,
+                index,
+                stack_frame_registers,
+                frame_base,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// The type pointed to by the pointer type of the member at `member_offset`.
+    fn pointee_type(&self, member_offset: UnitOffset) -> Result<Option<UnitOffset>, DebugError> {
+        let type_of = |offset: UnitOffset| -> Result<Option<UnitOffset>, DebugError> {
+            let entry = self.unit.entry(offset)?;
+            Ok(match entry.attr_value(gimli::DW_AT_type)? {
+                Some(gimli::AttributeValue::UnitRef(unit_ref)) => Some(unit_ref),
+                _ => None,
+            })
+        };
+
+        match type_of(member_offset)? {
+            Some(pointer_type) => type_of(pointer_type),
+            None => Ok(None),
+        }
+    }
+
     /// Process a memory location for a variable, by first evaluating the `byte_size`, and then calling the `self.extract_location`.
     pub(crate) fn process_memory_location(
         &self,
@@ -1505,13 +1621,8 @@ impl<'debuginfo> UnitInfo<'debuginfo> {
                         }
                     },
                     gimli::AttributeValue::LocationListsRef(location_list_offset) => {
-                        match self.debug_info.locations_section.locations(
-                            location_list_offset,
-                            self.unit.header.encoding(),
-                            self.unit.low_pc,
-                            &self.debug_info.address_section,
-                            self.unit.addr_base,
-                        ) {
+                        // This uses the location lists of the `.dwo` file for split units.
+                        match self.dwarf.locations(&self.unit, location_list_offset) {
                             Ok(mut locations) => {
                                 if let Some(program_counter) = stack_frame_registers
                                     .get_program_counter()
@@ -1821,6 +1932,11 @@ impl<'debuginfo> UnitInfo<'debuginfo> {
 
                     evaluation.resume_with_register(gimli::Value::Generic(raw_value.try_into()?))?
                 }
+                RequiresIndexedAddress { index, relocate: _ } => {
+                    // The address is stored in the `.debug_addr` section, which is used by split DWARF.
+                    let address = self.dwarf.address(&self.unit, index)?;
+                    evaluation.resume_with_indexed_address(address)?
+                }
                 RequiresRelocatedAddress(address_index) => {
                     // The address_index as an offset from 0, so just pass it into the next step.
                     evaluation.resume_with_relocated_address(address_index)?
@@ -1942,3 +2058,59 @@ impl<'debuginfo> UnitInfo<'debuginfo> {
         Ok(false)
     }
 }
+
+/// The maximum number of elements of a slice which are expanded.
+const MAX_SLICE_ELEMENTS: i64 = 200;
+
+/// Returns `true` if `type_name` is the name of a slice, e.g. `&[u8]` or `&mut [u8]`.
+fn is_slice(type_name: &str) -> bool {
+    type_name.starts_with("&[") || type_name.starts_with("&mut [")
+}
+
+/// Reads an unsigned little endian integer of `byte_size` bytes at `address`.
+fn read_unsigned(core: &mut Core<'_>, address: u64, byte_size: u64) -> Result<u64, DebugError> {
+    let mut buff = [0u8; 8];
+    let size = match byte_size {
+        1 | 2 | 4 | 8 => byte_size as usize,
+        other => {
+            return Err(DebugError::Other(anyhow::anyhow!(
+                "Unsupported integer size of {} bytes",
+                other
+            )))
+        }
+    };
+    core.read(address, &mut buff[..size])?;
+
+    Ok(u64::from_le_bytes(buff))
+}
+
+/// Reads the value of the discriminant of a variant part.
+///
+/// Rust uses invalid values of another field as the discriminant of an enum, e.g. a null pointer or
+/// an out of range `bool` or `char`, which is called niche optimization. These values can't be
+/// parsed from the formatted value of the field, so the raw value is read from memory.
+fn read_discriminant(
+    discriminant: &Variable,
+    core: &mut Core<'_>,
+    cache: &VariableCache,
+) -> Option<u64> {
+    match (&discriminant.memory_location, discriminant.byte_size) {
+        (VariableLocation::Address(address), Some(byte_size)) => {
+            read_unsigned(core, *address, byte_size).ok()
+        }
+        _ => discriminant.get_value(cache).parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::is_slice;
+
+    #[test]
+    fn slice_type_names() {
+        assert!(is_slice("&[u8]"));
+        assert!(is_slice("&mut [core::option::Option<u32>]"));
+        assert!(!is_slice("&str"));
+        assert!(!is_slice("[u8; 4]"));
+    }
+}
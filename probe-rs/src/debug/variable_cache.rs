@@ -3,6 +3,20 @@ use crate::Error;
 use anyhow::anyhow;
 use std::collections::HashMap;
 
+/// A variable with its value, and the values of its children, e.g. the fields of a struct,
+/// the elements of an array or slice, or the fields of the active variant of an enum.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariableValueTree {
+    /// The name of the variable.
+    pub name: String,
+    /// The name of the type of the variable.
+    pub type_name: String,
+    /// The value of the variable. For structured types, this is derived from the values of the children.
+    pub value: String,
+    /// The children of the variable, in the order of their declaration.
+    pub children: Vec<VariableValueTree>,
+}
+
 /// VariableCache stores available `Variable`s, and provides methods to create and navigate the parent-child relationships of the Variables.
 #[derive(Debug)]
 pub struct VariableCache {
@@ -247,6 +261,25 @@ impl VariableCache {
         }
         Ok(())
     }
+    /// Builds the tree of values of `variable` and its children in the cache.
+    ///
+    /// Children whose processing is deferred (see [`VariableNodeType::is_deferred`]) are only
+    /// part of the tree after they were cached with [`DebugInfo::cache_deferred_variables`].
+    pub fn value_tree(&self, variable: &Variable) -> Result<VariableValueTree, Error> {
+        let children = self
+            .get_children(Some(variable.variable_key))?
+            .iter()
+            .map(|child| self.value_tree(child))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(VariableValueTree {
+            name: variable.name.to_string(),
+            type_name: variable.type_name.to_string(),
+            value: variable.get_value(self),
+            children,
+        })
+    }
+
     /// Removing an entry from the `VariableCache` will recursively remove all its children
     pub fn remove_cache_entry(&mut self, variable_key: i64) -> Result<(), Error> {
         self.remove_cache_entry_children(variable_key)?;
@@ -256,3 +289,44 @@ impl VariableCache {
         Ok(())
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::{Variable, VariableCache, VariableName, VariableType, VariableValue};
+
+    fn insert(
+        cache: &mut VariableCache,
+        key: i64,
+        parent_key: Option<i64>,
+        name: &str,
+        value: &str,
+    ) {
+        let mut variable = Variable::new(None, None);
+        variable.variable_key = key;
+        variable.parent_key = parent_key;
+        variable.name = VariableName::Named(name.to_string());
+        variable.type_name = VariableType::Base("u32".to_string());
+        variable.set_value(VariableValue::Valid(value.to_string()));
+        cache.variable_hash_map.insert(key, variable);
+    }
+
+    #[test]
+    fn value_tree_keeps_declaration_order() {
+        let mut cache = VariableCache::new();
+        insert(&mut cache, 1, None, "point", "");
+        insert(&mut cache, 3, Some(1), "y", "2");
+        insert(&mut cache, 2, Some(1), "x", "1");
+
+        let root = cache.get_variable_by_key(1).unwrap();
+        let tree = cache.value_tree(&root).unwrap();
+
+        assert_eq!(tree.name, "point");
+        let children = tree
+            .children
+            .iter()
+            .map(|child| (child.name.as_str(), child.value.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(children, vec![("x", "1"), ("y", "2")]);
+    }
+}
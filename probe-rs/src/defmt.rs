@@ -0,0 +1,187 @@
+//! Host side decoding of [defmt](https://defmt.ferrous-systems.com) log frames.
+//!
+//! defmt only transfers the index of a log message and the values of its arguments. The format
+//! strings are stored in the `.defmt` section of the ELF file, which is needed to decode the
+//! frames.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use probe_rs::defmt::DefmtDecoder;
+//!
+//! let elf = std::fs::read("path/to/firmware.elf")?;
+//! let decoder = DefmtDecoder::from_elf(&elf)?.expect("The firmware doesn't use defmt");
+//! let mut stream = decoder.stream();
+//!
+//! // Pass the data received from the target, e.g. from the `defmt` RTT channel.
+//! # let data: [u8; 0] = [];
+//! stream.received(&data);
+//!
+//! while let Some(frame) = stream.next_frame()? {
+//!     println!("{frame}");
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use defmt_decoder::{DecodeError, Locations, StreamDecoder, Table};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Decoder for defmt log frames, created from the `.defmt` table of an ELF file.
+#[derive(Debug)]
+pub struct DefmtDecoder {
+    table: Table,
+    locations: Option<Locations>,
+}
+
+impl DefmtDecoder {
+    /// Reads the defmt table and the locations of the log statements from an ELF file.
+    ///
+    /// Returns `None` if the ELF file doesn't use defmt. If the ELF file doesn't contain
+    /// (complete) debug information, the frames are decoded without location.
+    pub fn from_elf(elf: &[u8]) -> Result<Option<Self>, Error> {
+        let Some(table) = Table::parse(elf).map_err(Error::Table)? else {
+            return Ok(None);
+        };
+
+        let locations = match table.get_locations(elf) {
+            Ok(locations)
+                if table
+                    .indices()
+                    .all(|index| locations.contains_key(&(index as u64))) =>
+            {
+                Some(locations)
+            }
+            Ok(_) => {
+                tracing::warn!("Location info for defmt is incomplete, compile your program with `debug = 2` to enable it.");
+                None
+            }
+            Err(error) => {
+                tracing::warn!("Unable to read the location info for defmt: {}", error);
+                None
+            }
+        };
+
+        Ok(Some(DefmtDecoder { table, locations }))
+    }
+
+    /// Creates a decoder for a stream of bytes, e.g. from an RTT channel or ITM stimulus port.
+    ///
+    /// Use a separate stream for every source of data.
+    pub fn stream(&self) -> DefmtStream<'_> {
+        DefmtStream {
+            decoder: self,
+            stream: self.table.new_stream_decoder(),
+        }
+    }
+}
+
+/// A stream of defmt log frames, created by [`DefmtDecoder::stream`].
+pub struct DefmtStream<'a> {
+    decoder: &'a DefmtDecoder,
+    stream: Box<dyn StreamDecoder + 'a>,
+}
+
+impl std::fmt::Debug for DefmtStream<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DefmtStream").finish_non_exhaustive()
+    }
+}
+
+impl DefmtStream<'_> {
+    /// Adds data received from the target to the stream.
+    pub fn received(&mut self, data: &[u8]) {
+        self.stream.received(data);
+    }
+
+    /// Decodes the next frame from the received data.
+    ///
+    /// Returns `None` if more data is needed to decode the next frame. If the data is malformed,
+    /// but the encoding allows to recover from it, the malformed frame is skipped.
+    pub fn next_frame(&mut self) -> Result<Option<LogFrame>, Error> {
+        let can_recover = self.decoder.table.encoding().can_recover();
+
+        loop {
+            match self.stream.decode() {
+                Ok(frame) => {
+                    let location = self
+                        .decoder
+                        .locations
+                        .as_ref()
+                        .and_then(|locations| locations.get(&frame.index()))
+                        .map(|location| LogLocation {
+                            file: location.file.clone(),
+                            line: location.line,
+                            module: location.module.clone(),
+                        });
+
+                    return Ok(Some(LogFrame {
+                        level: frame
+                            .level()
+                            .and_then(|level| tracing::Level::from_str(level.as_str()).ok()),
+                        timestamp: frame
+                            .display_timestamp()
+                            .map(|timestamp| timestamp.to_string()),
+                        message: frame.display_message().to_string(),
+                        location,
+                    }));
+                }
+                Err(DecodeError::UnexpectedEof) => return Ok(None),
+                Err(DecodeError::Malformed) if can_recover => {
+                    tracing::warn!("Skipping a malformed defmt frame.");
+                }
+                Err(DecodeError::Malformed) => return Err(Error::Malformed),
+            }
+        }
+    }
+}
+
+/// A decoded defmt log frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFrame {
+    /// The log level, `None` for frames of `defmt::println!`.
+    pub level: Option<tracing::Level>,
+    /// The formatted timestamp, if the firmware defines one with `defmt::timestamp!`.
+    pub timestamp: Option<String>,
+    /// The formatted log message.
+    pub message: String,
+    /// The location of the log statement in the source code.
+    pub location: Option<LogLocation>,
+}
+
+impl std::fmt::Display for LogFrame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(timestamp) = &self.timestamp {
+            write!(f, "{timestamp} ")?;
+        }
+
+        if let Some(level) = self.level {
+            write!(f, "{level} ")?;
+        }
+
+        write!(f, "{}", self.message)
+    }
+}
+
+/// The location of a log statement in the source code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogLocation {
+    /// The source file.
+    pub file: PathBuf,
+    /// The line in the source file.
+    pub line: u64,
+    /// The module path of the log statement.
+    pub module: String,
+}
+
+/// Error type for defmt decoding.
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    /// The defmt table in the ELF file is invalid, or uses an unsupported defmt version.
+    #[error("Unable to read the defmt table: {0}")]
+    Table(anyhow::Error),
+
+    /// The received data is malformed, and the encoding doesn't allow to recover from it.
+    #[error("Unrecoverable error while decoding defmt data, some data may have been lost.")]
+    Malformed,
+}
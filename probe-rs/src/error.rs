@@ -2,6 +2,7 @@
 
 use crate::architecture::arm::ArmError;
 use crate::architecture::riscv::communication_interface::RiscvError;
+use crate::architecture::xtensa::communication_interface::XtensaError;
 use crate::config::RegistryError;
 use crate::DebugProbeError;
 
@@ -17,12 +18,21 @@ pub enum Error {
     /// A RISCV specific error occurred.
     #[error("A RISCV specific error occurred.")]
     Riscv(#[source] RiscvError),
+    /// An Xtensa specific error occurred.
+    #[error("An Xtensa specific error occurred.")]
+    Xtensa(#[source] XtensaError),
     /// The probe could not be opened.
     #[error("Probe could not be opened: {0}")]
     UnableToOpenProbe(&'static str),
     /// The core with given ID does not exist.
     #[error("Core {0} does not exist")]
     CoreNotFound(usize),
+    /// The core with the given name does not exist on the target.
+    #[error("Core '{0}' does not exist")]
+    CoreNameNotFound(String),
+    /// The core is the redundant core of a lockstep pair, and can not be accessed.
+    #[error("Core {0} runs in lockstep with another core and can not be accessed directly")]
+    CoreInLockstep(usize),
     /// The given chip does not exist.
     #[error("Unable to load specification for chip")]
     ChipNotFound(#[from] RegistryError),
@@ -44,6 +54,17 @@ pub enum Error {
     #[error("A timeout occurred.")]
     Timeout,
 
+    /// A memory access was refused, because the access attributes of the memory region don't allow it.
+    #[error("{} {size} bytes at {address:#010x} is not allowed by the memory map", if *.write { "Writing" } else { "Reading" })]
+    MemoryAccessDenied {
+        /// The start address of the access.
+        address: u64,
+        /// The size of the access in bytes.
+        size: u64,
+        /// True for a write access, false for a read access.
+        write: bool,
+    },
+
     /// Unaligned memory access
     #[error("Alignment error")]
     MemoryNotAligned {
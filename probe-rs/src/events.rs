@@ -0,0 +1,351 @@
+//! Structured events for front-ends.
+//!
+//! Besides the human-readable log output, probe-rs emits [`Event`]s for operations like
+//! attaching and flashing, for retried DAP transfers and for warnings of the debug probes.
+//! Events are passed to the [`EventSink`] registered with [`set_event_sink`], so GUIs
+//! and CI systems can follow what happens without parsing the log.
+//!
+//! The [`JsonSink`] writes every event as a JSON object on a line of its own:
+//!
+//! ```no_run
+//! use probe_rs::events::{set_event_sink, JsonSink};
+//!
+//! set_event_sink(JsonSink::new(std::io::stderr()));
+//! ```
+//!
+//! ```text
+//! {"timestamp":1697371200.123,"event":"operation_started","operation":"erase"}
+//! {"timestamp":1697371200.456,"event":"operation_progress","operation":"erase","completed_bytes":4096,"total_bytes":8192,"elapsed_ms":333}
+//! ```
+//!
+//! The sink is global, because events are emitted by the probe drivers as well, which
+//! don't know about the session they are used by.
+
+use std::io::Write;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use once_cell::sync::Lazy;
+
+use crate::flashing::ProgressEvent;
+
+static SINK: Lazy<RwLock<Option<Arc<dyn EventSink>>>> = Lazy::new(|| RwLock::new(None));
+
+/// An event emitted by probe-rs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Event {
+    /// An operation has started.
+    OperationStarted {
+        /// The operation.
+        operation: Operation,
+    },
+    /// An operation has made progress.
+    OperationProgress {
+        /// The operation.
+        operation: Operation,
+        /// The number of bytes which were processed so far.
+        completed_bytes: u64,
+        /// The total number of bytes of the operation.
+        total_bytes: u64,
+        /// The time since the start of the operation, in milliseconds.
+        elapsed_ms: u64,
+    },
+    /// An operation has finished.
+    OperationFinished {
+        /// The operation.
+        operation: Operation,
+        /// Whether the operation was successful.
+        success: bool,
+        /// A description of the error, if it is known.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    /// A DAP transfer was retried, e.g. because the target responded with WAIT.
+    DapRetry {
+        /// The name of the probe.
+        probe: String,
+        /// The reason for the retry.
+        reason: String,
+        /// The number of the retry, starting at 1.
+        attempt: usize,
+    },
+    /// The debug probe reported a problem, which doesn't stop the current operation.
+    ProbeWarning {
+        /// The name of the probe.
+        probe: String,
+        /// The warning.
+        message: String,
+    },
+}
+
+/// The operations reported by [`Event::OperationStarted`] and the related events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Operation {
+    /// Attaching to the target.
+    Attach,
+    /// Determining the contents of the flash pages.
+    Fill,
+    /// Erasing the flash.
+    Erase,
+    /// Programming the flash.
+    Program,
+    /// Verifying the programmed data.
+    Verify,
+}
+
+/// A receiver of [`Event`]s, see [`set_event_sink`].
+///
+/// Events can be emitted from any thread, so the sink should return quickly
+/// and not block on other events.
+pub trait EventSink: Send + Sync {
+    /// Handles an event.
+    fn event(&self, event: &Event);
+}
+
+impl<F: Fn(&Event) + Send + Sync> EventSink for F {
+    fn event(&self, event: &Event) {
+        self(event)
+    }
+}
+
+/// An [`EventSink`], which writes every event as a line of JSON, with the
+/// time of the event in seconds since the Unix epoch as `timestamp`.
+///
+/// Errors writing the events are ignored.
+pub struct JsonSink<W: Write + Send> {
+    writer: Mutex<W>,
+}
+
+impl<W: Write + Send> JsonSink<W> {
+    /// Creates a sink writing to `writer`.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: Mutex::new(writer),
+        }
+    }
+
+    /// Returns the writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// An [`Event`] with the time it was emitted, as written by the [`JsonSink`].
+#[derive(Serialize)]
+struct TimestampedEvent<'a> {
+    timestamp: f64,
+    #[serde(flatten)]
+    event: &'a Event,
+}
+
+impl<W: Write + Send> EventSink for JsonSink<W> {
+    fn event(&self, event: &Event) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+
+        let Ok(mut line) = serde_json::to_vec(&TimestampedEvent { timestamp, event }) else {
+            return;
+        };
+        line.push(b'\n');
+
+        let mut writer = self
+            .writer
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let _ = writer.write_all(&line).and_then(|_| writer.flush());
+    }
+}
+
+/// Sets the sink receiving all events, replacing the previous one.
+pub fn set_event_sink(sink: impl EventSink + 'static) {
+    *SINK.write().unwrap() = Some(Arc::new(sink));
+}
+
+/// Removes the event sink, so events are discarded.
+pub fn clear_event_sink() {
+    *SINK.write().unwrap() = None;
+}
+
+/// Passes `event` to the event sink, if there is one.
+pub(crate) fn emit(event: impl FnOnce() -> Event) {
+    // The sink is cloned, so it can emit events itself without a deadlock.
+    let Some(sink) = SINK.read().unwrap().clone() else {
+        return;
+    };
+
+    sink.event(&event());
+}
+
+/// Emits an [`Event::DapRetry`].
+pub(crate) fn dap_retry(probe: &str, reason: &str, attempt: usize) {
+    emit(|| Event::DapRetry {
+        probe: probe.to_owned(),
+        reason: reason.to_owned(),
+        attempt,
+    });
+}
+
+/// Emits an [`Event::ProbeWarning`].
+pub(crate) fn probe_warning(probe: &str, message: impl Into<String>) {
+    emit(|| Event::ProbeWarning {
+        probe: probe.to_owned(),
+        message: message.into(),
+    });
+}
+
+/// Emits the start of `operation`, runs it, and emits its end.
+pub(crate) fn operation<T, E: std::fmt::Display>(
+    operation: Operation,
+    function: impl FnOnce() -> Result<T, E>,
+) -> Result<T, E> {
+    emit(|| Event::OperationStarted { operation });
+
+    let result = function();
+
+    emit(|| Event::OperationFinished {
+        operation,
+        success: result.is_ok(),
+        error: result.as_ref().err().map(|error| error.to_string()),
+    });
+
+    result
+}
+
+impl Event {
+    /// Converts a flashing progress event to the matching event, if there is one.
+    pub(crate) fn from_progress(event: &ProgressEvent) -> Option<Self> {
+        let started = |operation| Event::OperationStarted { operation };
+        let finished = |operation, success| Event::OperationFinished {
+            operation,
+            success,
+            error: None,
+        };
+        let progress =
+            |operation, completed_bytes, total_bytes, elapsed: Duration| Event::OperationProgress {
+                operation,
+                completed_bytes,
+                total_bytes,
+                elapsed_ms: elapsed.as_millis() as u64,
+            };
+
+        Some(match event {
+            ProgressEvent::StartedFilling => started(Operation::Fill),
+            ProgressEvent::StartedErasing => started(Operation::Erase),
+            ProgressEvent::StartedProgramming => started(Operation::Program),
+            ProgressEvent::StartedVerifying => started(Operation::Verify),
+            ProgressEvent::PageFilled { progress: p, .. } => {
+                progress(Operation::Fill, p.completed_bytes, p.total_bytes, p.elapsed)
+            }
+            ProgressEvent::SectorErased { progress: p, .. } => progress(
+                Operation::Erase,
+                p.completed_bytes,
+                p.total_bytes,
+                p.elapsed,
+            ),
+            ProgressEvent::PageProgrammed { progress: p, .. } => progress(
+                Operation::Program,
+                p.completed_bytes,
+                p.total_bytes,
+                p.elapsed,
+            ),
+            ProgressEvent::DataVerified { progress: p, .. } => progress(
+                Operation::Verify,
+                p.completed_bytes,
+                p.total_bytes,
+                p.elapsed,
+            ),
+            ProgressEvent::FinishedFilling => finished(Operation::Fill, true),
+            ProgressEvent::FinishedErasing => finished(Operation::Erase, true),
+            ProgressEvent::FinishedProgramming => finished(Operation::Program, true),
+            ProgressEvent::FinishedVerifying => finished(Operation::Verify, true),
+            ProgressEvent::FailedFilling => finished(Operation::Fill, false),
+            ProgressEvent::FailedErasing => finished(Operation::Erase, false),
+            ProgressEvent::FailedProgramming => finished(Operation::Program, false),
+            ProgressEvent::FailedVerifying => finished(Operation::Verify, false),
+            ProgressEvent::Initialized { .. } | ProgressEvent::DiagnosticMessage { .. } => {
+                return None
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::flashing::PhaseProgress;
+
+    #[test]
+    fn json_lines() {
+        let sink = JsonSink::new(Vec::new());
+
+        sink.event(&Event::OperationStarted {
+            operation: Operation::Erase,
+        });
+        sink.event(&Event::DapRetry {
+            probe: "J-Link".to_owned(),
+            reason: "WAIT response".to_owned(),
+            attempt: 2,
+        });
+
+        let output = String::from_utf8(sink.into_inner()).unwrap();
+        let lines = output
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0]["timestamp"].as_f64().unwrap() > 0.0);
+        assert_eq!(lines[0]["event"], "operation_started");
+        assert_eq!(lines[0]["operation"], "erase");
+        assert_eq!(lines[1]["event"], "dap_retry");
+        assert_eq!(lines[1]["probe"], "J-Link");
+        assert_eq!(lines[1]["attempt"], 2);
+    }
+
+    #[test]
+    fn progress_events() {
+        let progress = PhaseProgress {
+            completed: 1,
+            total: 4,
+            completed_bytes: 0x400,
+            total_bytes: 0x1000,
+            elapsed: Duration::from_millis(20),
+        };
+
+        assert_eq!(
+            Event::from_progress(&ProgressEvent::PageProgrammed {
+                size: 0x400,
+                time: Duration::from_millis(20),
+                progress,
+            }),
+            Some(Event::OperationProgress {
+                operation: Operation::Program,
+                completed_bytes: 0x400,
+                total_bytes: 0x1000,
+                elapsed_ms: 20,
+            })
+        );
+        assert_eq!(
+            Event::from_progress(&ProgressEvent::FailedVerifying),
+            Some(Event::OperationFinished {
+                operation: Operation::Verify,
+                success: false,
+                error: None,
+            })
+        );
+        assert_eq!(
+            Event::from_progress(&ProgressEvent::DiagnosticMessage {
+                message: "message".to_owned()
+            }),
+            None
+        );
+    }
+}
@@ -0,0 +1,364 @@
+//! Checks of memory ranges, which are executed by the target itself.
+//!
+//! Reading back large ranges through the debug probe is slow, so a small routine is
+//! loaded into the RAM of the target, which computes a CRC32 of a range, or compares two
+//! ranges, and only returns the result. The borrowed RAM and the registers used by the
+//! routine are restored afterwards.
+
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+use probe_rs_target::{InstructionSet, MemoryRange, MemoryRegion};
+
+use super::FlashError;
+use crate::core::RegisterValue;
+use crate::{Core, MemoryInterface, Session};
+
+/// A routine which is executed on the target.
+struct Stub {
+    /// The position independent machine code of the routine.
+    code: &'static [u8],
+    /// The platform registers which are modified by the routine. The first ones are
+    /// the arguments, the result is returned in the first one.
+    registers: &'static [usize],
+}
+
+/// Computes the CRC32 of `r1` bytes at `r0`, and returns it in `r0`.
+///
+/// Only uses ARMv6-M instructions, so it can be executed by all Cortex-M cores.
+const THUMB_CRC32: Stub = Stub {
+    code: &[
+        0x00, 0x22, // movs r2, #0
+        0xd2, 0x43, // mvns r2, r2
+        0x07, 0x4b, // ldr r3, [pc, #28]
+        0x00, 0x29, // loop: cmp r1, #0
+        0x0a, 0xd0, // beq done
+        0x04, 0x78, // ldrb r4, [r0]
+        0x01, 0x30, // adds r0, #1
+        0x62, 0x40, // eors r2, r4
+        0x08, 0x25, // movs r5, #8
+        0x52, 0x08, // bit: lsrs r2, r2, #1
+        0x00, 0xd3, // bcc next
+        0x5a, 0x40, // eors r2, r3
+        0x01, 0x3d, // next: subs r5, #1
+        0xfa, 0xd1, // bne bit
+        0x01, 0x39, // subs r1, #1
+        0xf2, 0xe7, // b loop
+        0xd0, 0x43, // done: mvns r0, r2
+        0x00, 0xbe, // bkpt #0
+        0x20, 0x83, 0xb8, 0xed, // .word 0xedb88320
+    ],
+    registers: &[0, 1, 2, 3, 4, 5],
+};
+
+/// Compares `r2` bytes at `r0` and `r1`, and returns the number of equal bytes
+/// before the first difference in `r0`.
+const THUMB_COMPARE: Stub = Stub {
+    code: &[
+        0x00, 0x23, // movs r3, #0
+        0x93, 0x42, // loop: cmp r3, r2
+        0x05, 0xd0, // beq done
+        0xc4, 0x5c, // ldrb r4, [r0, r3]
+        0xcd, 0x5c, // ldrb r5, [r1, r3]
+        0xac, 0x42, // cmp r4, r5
+        0x01, 0xd1, // bne done
+        0x01, 0x33, // adds r3, #1
+        0xf7, 0xe7, // b loop
+        0x18, 0x00, // done: movs r0, r3
+        0x00, 0xbe, // bkpt #0
+    ],
+    registers: &[0, 1, 2, 3, 4, 5],
+};
+
+/// Computes the CRC32 of `a1` bytes at `a0`, and returns it in `a0`.
+///
+/// Only uses RV32I instructions.
+const RISCV_CRC32: Stub = Stub {
+    code: &[
+        0x13, 0x06, 0xf0, 0xff, // li a2, -1
+        0xb7, 0x86, 0xb8, 0xed, // lui a3, 0xedb88
+        0x93, 0x86, 0x06, 0x32, // addi a3, a3, 0x320
+        0x63, 0x8a, 0x05, 0x02, // loop: beqz a1, done
+        0x03, 0x47, 0x05, 0x00, // lbu a4, 0(a0)
+        0x13, 0x05, 0x15, 0x00, // addi a0, a0, 1
+        0x33, 0x46, 0xe6, 0x00, // xor a2, a2, a4
+        0x93, 0x07, 0x80, 0x00, // li a5, 8
+        0x13, 0x78, 0x16, 0x00, // bit: andi a6, a2, 1
+        0x13, 0x56, 0x16, 0x00, // srli a2, a2, 1
+        0x63, 0x04, 0x08, 0x00, // beqz a6, next
+        0x33, 0x46, 0xd6, 0x00, // xor a2, a2, a3
+        0x93, 0x87, 0xf7, 0xff, // next: addi a5, a5, -1
+        0xe3, 0x96, 0x07, 0xfe, // bnez a5, bit
+        0x93, 0x85, 0xf5, 0xff, // addi a1, a1, -1
+        0x6f, 0xf0, 0x1f, 0xfd, // j loop
+        0x13, 0x45, 0xf6, 0xff, // done: not a0, a2
+        0x73, 0x00, 0x10, 0x00, // ebreak
+    ],
+    registers: &[10, 11, 12, 13, 14, 15, 16],
+};
+
+/// Compares `a2` bytes at `a0` and `a1`, and returns the number of equal bytes
+/// before the first difference in `a0`.
+const RISCV_COMPARE: Stub = Stub {
+    code: &[
+        0x93, 0x06, 0x00, 0x00, // li a3, 0
+        0x63, 0x80, 0xc6, 0x02, // loop: beq a3, a2, done
+        0x33, 0x07, 0xd5, 0x00, // add a4, a0, a3
+        0x03, 0x47, 0x07, 0x00, // lbu a4, 0(a4)
+        0xb3, 0x87, 0xd5, 0x00, // add a5, a1, a3
+        0x83, 0xc7, 0x07, 0x00, // lbu a5, 0(a5)
+        0x63, 0x16, 0xf7, 0x00, // bne a4, a5, done
+        0x93, 0x86, 0x16, 0x00, // addi a3, a3, 1
+        0x6f, 0xf0, 0x5f, 0xfe, // j loop
+        0x13, 0x85, 0x06, 0x00, // done: mv a0, a3
+        0x73, 0x00, 0x10, 0x00, // ebreak
+    ],
+    registers: &[10, 11, 12, 13, 14, 15],
+};
+
+/// Computes the CRC32 of the memory in `range` with the core `core_index`.
+///
+/// The CRC32 is the one used by Ethernet and zlib, so it can be compared to
+/// the result of e.g. [`crc32fast::hash`] for the expected contents.
+pub fn target_crc32(
+    session: &mut Session,
+    core_index: usize,
+    range: Range<u64>,
+) -> Result<u32, FlashError> {
+    let len = range.end.saturating_sub(range.start);
+
+    run_stub(
+        session,
+        core_index,
+        |instruction_set| match instruction_set {
+            InstructionSet::Thumb2 => Some(&THUMB_CRC32),
+            InstructionSet::RV32 | InstructionSet::RV32C => Some(&RISCV_CRC32),
+            _ => None,
+        },
+        std::slice::from_ref(&range),
+        &[into_reg(range.start)?, into_reg(len)?],
+        len,
+    )
+}
+
+/// Compares `len` bytes at `first` and `second` with the core `core_index`.
+///
+/// Returns the offset of the first byte which differs, or `None` if both ranges are equal.
+pub fn target_compare(
+    session: &mut Session,
+    core_index: usize,
+    first: u64,
+    second: u64,
+    len: u64,
+) -> Result<Option<u64>, FlashError> {
+    let equal = run_stub(
+        session,
+        core_index,
+        |instruction_set| match instruction_set {
+            InstructionSet::Thumb2 => Some(&THUMB_COMPARE),
+            InstructionSet::RV32 | InstructionSet::RV32C => Some(&RISCV_COMPARE),
+            _ => None,
+        },
+        &[first..first + len, second..second + len],
+        &[into_reg(first)?, into_reg(second)?, into_reg(len)?],
+        len,
+    )?;
+
+    Ok((u64::from(equal) < len).then_some(u64::from(equal)))
+}
+
+/// Loads the stub for the instruction set of the core into RAM, where it doesn't
+/// overlap any of the `used` ranges, and runs it with the `arguments`.
+///
+/// The RAM and the registers used by the stub are restored afterwards, and the core
+/// is resumed if it was running before.
+fn run_stub(
+    session: &mut Session,
+    core_index: usize,
+    select_stub: impl Fn(InstructionSet) -> Option<&'static Stub>,
+    used: &[Range<u64>],
+    arguments: &[u32],
+    len: u64,
+) -> Result<u32, FlashError> {
+    let target = session.target();
+    let core_name = target
+        .cores
+        .get(core_index)
+        .ok_or(crate::Error::CoreNotFound(core_index))?
+        .name
+        .clone();
+    let memory_map = target.memory_map.clone();
+
+    let mut core = session.core(core_index)?;
+
+    let instruction_set = core.instruction_set()?;
+    let stub = select_stub(instruction_set).ok_or(FlashError::StubNotSupported(instruction_set))?;
+
+    let address = find_placement(&memory_map, &core_name, stub.code.len() as u64, used)
+        .ok_or(FlashError::NoRamForStub { core_index })?;
+
+    let was_running = !core.core_halted()?;
+    if was_running {
+        core.halt(Duration::from_millis(100))?;
+    }
+
+    let mut saved_memory = vec![0; stub.code.len()];
+    core.read(address, &mut saved_memory)?;
+
+    let regs = core.registers();
+    let saved_registers = stub
+        .registers
+        .iter()
+        .map(|&index| regs.platform_register(index).id)
+        .chain(Some(regs.program_counter().id))
+        .chain(regs.psr().map(|psr| psr.id))
+        .map(|id| Ok((id, core.read_core_reg::<RegisterValue>(id)?)))
+        .collect::<Result<Vec<_>, crate::Error>>()?;
+
+    // Restore the target even if the stub fails, but report the first error.
+    let result = execute(&mut core, stub, address, arguments, len);
+
+    let restored = saved_registers
+        .into_iter()
+        .try_for_each(|(id, value)| core.write_core_reg(id, value))
+        .and_then(|_| core.write_8(address, &saved_memory))
+        .and_then(|_| if was_running { core.run() } else { Ok(()) });
+
+    let value = result?;
+    restored?;
+
+    Ok(value)
+}
+
+/// Writes the stub to `address`, and runs it until it halts again.
+fn execute(
+    core: &mut Core,
+    stub: &Stub,
+    address: u64,
+    arguments: &[u32],
+    len: u64,
+) -> Result<u32, FlashError> {
+    core.write_8(address, stub.code)?;
+
+    let regs = core.registers();
+    for (&index, &argument) in stub.registers.iter().zip(arguments) {
+        core.write_core_reg(regs.platform_register(index).id, argument)?;
+    }
+    core.write_core_reg(regs.program_counter().id, into_reg(address)?)?;
+
+    // Ensure RISC-V `ebreak` instruction enters debug mode.
+    core.debug_on_sw_breakpoint(true)?;
+    core.run()?;
+
+    // Every byte takes a few dozen cycles, so even slow cores check 50 kB per second.
+    let timeout = Duration::from_secs(1 + len / 50_000);
+    let start = Instant::now();
+    while !core.core_halted()? {
+        if start.elapsed() > timeout {
+            if let Err(error) = core.halt(Duration::from_millis(100)) {
+                tracing::warn!("Failed to halt the core after the check timed out: {error}");
+            }
+
+            return Err(FlashError::RoutineTimeout {
+                name: "memory check",
+                timeout,
+            });
+        }
+
+        std::thread::sleep(Duration::from_millis(1));
+    }
+
+    Ok(core.read_core_reg(regs.result_register(0).id)?)
+}
+
+/// Finds a word aligned place for `len` bytes in a RAM region of the core `core_name`,
+/// which doesn't overlap any of the `used` ranges.
+fn find_placement(
+    memory_map: &[MemoryRegion],
+    core_name: &str,
+    len: u64,
+    used: &[Range<u64>],
+) -> Option<u64> {
+    memory_map
+        .iter()
+        .filter_map(|region| match region {
+            MemoryRegion::Ram(region)
+                if region.cores.iter().any(|core| core == core_name)
+                    && region.access.map(|a| a.read && a.write).unwrap_or(true) =>
+            {
+                Some(&region.range)
+            }
+            _ => None,
+        })
+        .flat_map(|range| {
+            // Try both ends of the region, as the used ranges are often at its start.
+            let start = range.start.checked_add(3)? & !0x3;
+            let end = range.end.checked_sub(len)? & !0x3;
+
+            Some([start, end])
+        })
+        .flatten()
+        .find(|&address| {
+            let placement = address..address + len;
+
+            memory_map.iter().any(|region| match region {
+                MemoryRegion::Ram(region) => region.range.contains_range(&placement),
+                _ => false,
+            }) && !used.iter().any(|range| range.intersects_range(&placement))
+        })
+}
+
+/// Converts a value to a 32-bit register value.
+fn into_reg(value: u64) -> Result<u32, FlashError> {
+    value
+        .try_into()
+        .map_err(|_| FlashError::RegisterValueNotSupported(value))
+}
+
+#[cfg(test)]
+mod test {
+    use probe_rs_target::{MemoryRegion, NvmRegion, RamRegion};
+
+    use super::find_placement;
+
+    fn memory_map() -> Vec<MemoryRegion> {
+        vec![
+            MemoryRegion::Nvm(NvmRegion {
+                name: None,
+                range: 0x0800_0000..0x0810_0000,
+                is_boot_memory: true,
+                cores: vec!["main".to_owned()],
+                min_write_size: None,
+                no_overwrite: false,
+                access: None,
+            }),
+            MemoryRegion::Ram(RamRegion {
+                name: None,
+                range: 0x2000_0000..0x2000_1000,
+                is_boot_memory: false,
+                cores: vec!["main".to_owned()],
+                access: None,
+            }),
+        ]
+    }
+
+    #[test]
+    fn stub_placement() {
+        let memory_map = memory_map();
+        let flash = 0x0800_0000..0x0810_0000;
+        let start_of_ram = 0x2000_0000..0x2000_0800;
+        let ram = 0x2000_0000..0x2000_1000;
+
+        assert_eq!(
+            find_placement(&memory_map, "main", 0x40, &[flash]),
+            Some(0x2000_0000)
+        );
+        // The stub doesn't overwrite the checked RAM.
+        assert_eq!(
+            find_placement(&memory_map, "main", 0x40, &[start_of_ram]),
+            Some(0x2000_0fc0)
+        );
+        assert_eq!(find_placement(&memory_map, "main", 0x40, &[ram]), None);
+        assert_eq!(find_placement(&memory_map, "other", 0x40, &[]), None);
+    }
+}
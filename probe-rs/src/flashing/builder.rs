@@ -135,6 +135,100 @@ impl FlashLayout {
     pub fn visualize(&self) -> FlashVisualizer {
         FlashVisualizer::new(self)
     }
+
+    /// Removes the sectors for which `unchanged` returns true, together with their pages and fills.
+    ///
+    /// A sector is kept if it overlaps a page which also overlaps another kept sector,
+    /// because the page can only be programmed if all of its sectors are erased.
+    /// Returns the number of removed sectors.
+    pub(super) fn skip_sectors(&mut self, unchanged: impl Fn(&FlashSector) -> bool) -> usize {
+        let mut keep = self
+            .sectors
+            .iter()
+            .map(|sector| !unchanged(sector))
+            .collect::<Vec<_>>();
+
+        let page_range = |page: &FlashPage| page.address..page.address + page.size() as u64;
+        let sector_range = |sector: &FlashSector| sector.address..sector.address + sector.size;
+
+        // Keeping a sector can require keeping its neighbours if pages span multiple sectors.
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for page in &self.pages {
+                let range = page_range(page);
+                let overlapping = self
+                    .sectors
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, sector)| sector_range(sector).intersects_range(&range))
+                    .map(|(index, _)| index)
+                    .collect::<Vec<_>>();
+
+                if overlapping.iter().any(|&index| keep[index])
+                    && !overlapping.iter().all(|&index| keep[index])
+                {
+                    for index in overlapping {
+                        keep[index] = true;
+                    }
+                    changed = true;
+                }
+            }
+        }
+
+        let mut kept_sectors = Vec::new();
+        for (sector, keep) in self.sectors.drain(..).zip(&keep) {
+            if *keep {
+                kept_sectors.push(sector);
+            }
+        }
+        let skipped = keep.len() - kept_sectors.len();
+        self.sectors = kept_sectors;
+
+        // Remove the pages of the removed sectors.
+        let sectors = self.sectors.clone();
+        self.retain_pages(|page| {
+            let range = page_range(page);
+            sectors
+                .iter()
+                .any(|sector| sector_range(sector).intersects_range(&range))
+        });
+
+        skipped
+    }
+
+    /// Removes the pages which only contain the erased byte value, so they are not programmed.
+    ///
+    /// Returns the number of removed pages.
+    pub(super) fn remove_erased_pages(&mut self, erased_byte_value: u8) -> usize {
+        let count = self.pages.len();
+        self.retain_pages(|page| page.data().iter().any(|&byte| byte != erased_byte_value));
+
+        count - self.pages.len()
+    }
+
+    /// Removes the pages for which `keep` returns false, and updates the page indices of the fills.
+    fn retain_pages(&mut self, keep: impl Fn(&FlashPage) -> bool) {
+        let mut page_indices = Vec::with_capacity(self.pages.len());
+        let mut kept_pages = Vec::new();
+        for page in self.pages.drain(..) {
+            if keep(&page) {
+                page_indices.push(Some(kept_pages.len()));
+                kept_pages.push(page);
+            } else {
+                page_indices.push(None);
+            }
+        }
+        self.pages = kept_pages;
+
+        self.fills = self
+            .fills
+            .drain(..)
+            .filter_map(|fill| {
+                page_indices[fill.page_index].map(|page_index| FlashFill { page_index, ..fill })
+            })
+            .collect();
+    }
 }
 
 /// A block of data that is to be written to flash.
@@ -157,7 +251,7 @@ impl FlashDataBlockSpan {
 }
 
 /// A helper structure to build a flash layout from a set of data blocks.
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub(super) struct FlashBuilder {
     pub(super) data: BTreeMap<u64, Vec<u8>>,
 }
@@ -258,6 +352,67 @@ impl FlashBuilder {
             })
     }
 
+    /// Moves the data in the flash banks `a` and `b` to the other bank, which have the same size.
+    ///
+    /// This is used to program the data to the physical banks, when the banks are swapped in the memory map.
+    pub(super) fn swap_banks(&self, a: &Range<u64>, b: &Range<u64>) -> Result<Self, FlashError> {
+        let mut swapped = Self::new();
+
+        let mut boundaries = [a.start, a.end, b.start, b.end];
+        boundaries.sort_unstable();
+
+        for (&address, data) in &self.data {
+            let end = address + data.len() as u64;
+
+            // Split the data at the bank boundaries, so each part is moved as a whole.
+            let mut start = address;
+            for boundary in boundaries.iter().copied().chain(Some(end)) {
+                if boundary <= start || boundary > end {
+                    continue;
+                }
+
+                let part = &data[(start - address) as usize..(boundary - address) as usize];
+                let target = if a.contains(&start) {
+                    start - a.start + b.start
+                } else if b.contains(&start) {
+                    start - b.start + a.start
+                } else {
+                    start
+                };
+                swapped.add_data(target, part)?;
+
+                start = boundary;
+            }
+        }
+
+        Ok(swapped)
+    }
+
+    /// The contents of the flash in `range` after programming it.
+    ///
+    /// `current` are the contents of the flash before programming. Bytes without data keep their
+    /// current value if `restore_unwritten_bytes` is set, otherwise they are erased.
+    pub(super) fn expected_contents(
+        &self,
+        range: &Range<u64>,
+        current: &[u8],
+        restore_unwritten_bytes: bool,
+        erased_byte_value: u8,
+    ) -> Vec<u8> {
+        let mut contents = if restore_unwritten_bytes {
+            current.to_vec()
+        } else {
+            vec![erased_byte_value; current.len()]
+        };
+
+        for (address, data) in self.data_in_range(range) {
+            let offset = (address - range.start) as usize;
+            contents[offset..offset + data.len()].copy_from_slice(data);
+        }
+
+        contents
+    }
+
     /// Layouts the contents of a flash memory according to the contents of the flash loader.
     pub(super) fn build_sectors_and_pages(
         &self,
@@ -270,6 +425,24 @@ impl FlashBuilder {
         let mut fills: Vec<FlashFill> = Vec::new();
         let mut data_blocks: Vec<FlashDataBlockSpan> = Vec::new();
 
+        // Pages are written if there is data in any of their write units,
+        // which can span multiple pages.
+        let page_size = flash_algorithm.flash_properties.page_size;
+        let write_size = region.min_write_size.unwrap_or(1);
+        let pages_in_write_unit = write_size / page_size.max(1);
+        if !write_size.is_power_of_two()
+            || (page_size & (write_size - 1) != 0 && pages_in_write_unit * page_size != write_size)
+        {
+            return Err(FlashError::InvalidWriteSize {
+                page_size,
+                write_size,
+            });
+        }
+        let write_range = |range: Range<u64>| {
+            let mask = write_size as u64 - 1;
+            range.start & !mask..(range.end + mask) & !mask
+        };
+
         for info in flash_algorithm.iter_sectors() {
             let range = info.base_address..info.base_address + info.size;
 
@@ -279,7 +452,7 @@ impl FlashBuilder {
             }
 
             let page = flash_algorithm.page_info(info.base_address).unwrap();
-            let page_range = page.base_address..page.base_address + page.size as u64;
+            let page_range = write_range(page.base_address..page.base_address + page.size as u64);
             let sector_has_data = self.has_data_in_range(&range);
             let page_has_data = self.has_data_in_range(&page_range);
 
@@ -306,7 +479,7 @@ impl FlashBuilder {
             let sector = flash_algorithm.sector_info(info.base_address).unwrap();
             let sector_range = sector.base_address..sector.base_address + sector.size;
             let sector_has_data = self.has_data_in_range(&sector_range);
-            let page_has_data = self.has_data_in_range(&range);
+            let page_has_data = self.has_data_in_range(&write_range(range.clone()));
 
             // If include_empty_pages, include the page if there's data in is sector, even if there's no data in the page.
             if !page_has_data && (!include_empty_pages || !sector_has_data) {
@@ -393,6 +566,9 @@ mod tests {
             is_boot_memory: true,
             range: 0..1 << 16,
             cores: vec!["main".into()],
+            min_write_size: None,
+            no_overwrite: false,
+            access: None,
         };
 
         (region, flash_algorithm)
@@ -421,6 +597,9 @@ mod tests {
             is_boot_memory: true,
             range: 0..1 << 16,
             cores: vec!["main".into()],
+            min_write_size: None,
+            no_overwrite: false,
+            access: None,
         };
 
         (region, flash_algorithm)
@@ -1139,4 +1318,174 @@ mod tests {
             }
         )
     }
+
+    #[test]
+    fn skip_unchanged_sectors() {
+        let (region, flash_algorithm) = assemble_demo_flash1();
+        let mut flash_builder = FlashBuilder::new();
+        flash_builder.add_data(0, &[42; 5024]).unwrap();
+        let mut flash_layout = flash_builder
+            .build_sectors_and_pages(&region, &flash_algorithm, true)
+            .unwrap();
+
+        let skipped = flash_layout.skip_sectors(|sector| sector.address() == 0);
+
+        assert_eq!(skipped, 1);
+        assert_eq!(
+            flash_layout.sectors(),
+            [FlashSector {
+                address: 0x1000,
+                size: 0x1000,
+            }]
+        );
+        assert_eq!(
+            flash_layout
+                .pages()
+                .iter()
+                .map(|page| page.address())
+                .collect::<Vec<_>>(),
+            [0x1000, 0x1400, 0x1800, 0x1C00]
+        );
+        assert_eq!(
+            flash_layout.fills(),
+            [
+                FlashFill {
+                    address: 0x13A0,
+                    size: 0x60,
+                    page_index: 0,
+                },
+                FlashFill {
+                    address: 0x1400,
+                    size: 0x400,
+                    page_index: 1,
+                },
+                FlashFill {
+                    address: 0x1800,
+                    size: 0x400,
+                    page_index: 2,
+                },
+                FlashFill {
+                    address: 0x1C00,
+                    size: 0x400,
+                    page_index: 3,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn skip_unchanged_sectors_of_shared_page() {
+        let (region, flash_algorithm) = assemble_demo_flash2();
+        let mut flash_builder = FlashBuilder::new();
+        flash_builder.add_data(0, &[42; 1024]).unwrap();
+        let mut flash_layout = flash_builder
+            .build_sectors_and_pages(&region, &flash_algorithm, false)
+            .unwrap();
+
+        // All sectors of the page have to be kept, if one of them changed.
+        let skipped = flash_layout.skip_sectors(|sector| sector.address() != 0);
+        assert_eq!(skipped, 0);
+        assert_eq!(flash_layout.sectors().len(), 8);
+        assert_eq!(flash_layout.pages().len(), 1);
+
+        let skipped = flash_layout.skip_sectors(|_| true);
+        assert_eq!(skipped, 8);
+        assert!(flash_layout.sectors().is_empty());
+        assert!(flash_layout.pages().is_empty());
+    }
+
+    #[test]
+    fn expected_contents_of_sector() {
+        let mut flash_builder = FlashBuilder::new();
+        flash_builder.add_data(2, &[1, 2]).unwrap();
+
+        let current = [7; 6];
+        assert_eq!(
+            flash_builder.expected_contents(&(0..6), &current, true, 0xFF),
+            [7, 7, 1, 2, 7, 7]
+        );
+        assert_eq!(
+            flash_builder.expected_contents(&(0..6), &current, false, 0xFF),
+            [0xFF, 0xFF, 1, 2, 0xFF, 0xFF]
+        );
+    }
+
+    #[test]
+    fn swap_banks() {
+        let mut flash_builder = FlashBuilder::new();
+        flash_builder.add_data(0x0FF0, &[1; 0x20]).unwrap();
+        flash_builder.add_data(0x3000, &[2; 0x10]).unwrap();
+
+        let swapped = flash_builder
+            .swap_banks(&(0x1000..0x2000), &(0x2000..0x3000))
+            .unwrap();
+
+        assert_eq!(
+            swapped.data_in_range(&(0..0x4000)).collect::<Vec<_>>(),
+            [
+                (0x0FF0, &[1; 0x10][..]),
+                (0x2000, &[1; 0x10][..]),
+                (0x3000, &[2; 0x10][..]),
+            ]
+        );
+    }
+
+    #[test]
+    fn write_unit_spans_two_pages() {
+        let (mut region, flash_algorithm) = assemble_demo_flash1();
+        region.min_write_size = Some(2048);
+
+        let mut flash_builder = FlashBuilder::new();
+        flash_builder.add_data(0x10, &[42; 16]).unwrap();
+        let flash_layout = flash_builder
+            .build_sectors_and_pages(&region, &flash_algorithm, false)
+            .unwrap();
+
+        assert_eq!(
+            flash_layout
+                .pages()
+                .iter()
+                .map(|page| page.address())
+                .collect::<Vec<_>>(),
+            [0x000, 0x400]
+        );
+        assert!(flash_layout.pages()[1]
+            .data()
+            .iter()
+            .all(|&byte| byte == 255));
+    }
+
+    #[test]
+    fn invalid_write_size() {
+        let (mut region, flash_algorithm) = assemble_demo_flash1();
+        region.min_write_size = Some(24);
+
+        let mut flash_builder = FlashBuilder::new();
+        flash_builder.add_data(0, &[42; 16]).unwrap();
+
+        assert!(matches!(
+            flash_builder.build_sectors_and_pages(&region, &flash_algorithm, false),
+            Err(FlashError::InvalidWriteSize {
+                page_size: 1024,
+                write_size: 24
+            })
+        ));
+    }
+
+    #[test]
+    fn remove_erased_pages() {
+        let (region, flash_algorithm) = assemble_demo_flash1();
+
+        let mut flash_builder = FlashBuilder::new();
+        flash_builder.add_data(0, &[255; 1024]).unwrap();
+        flash_builder.add_data(1024, &[42; 16]).unwrap();
+        let mut flash_layout = flash_builder
+            .build_sectors_and_pages(&region, &flash_algorithm, false)
+            .unwrap();
+
+        assert_eq!(flash_layout.remove_erased_pages(255), 1);
+        assert_eq!(flash_layout.pages().len(), 1);
+        assert_eq!(flash_layout.pages()[0].address(), 1024);
+        assert_eq!(flash_layout.fills()[0].page_index(), 0);
+    }
 }
@@ -4,10 +4,10 @@ use object::{
 };
 use probe_rs_target::MemoryRange;
 
-use std::{fs::File, path::Path, str::FromStr};
+use std::{fs::File, ops::Range, path::Path, str::FromStr, time::Duration};
 
 use super::*;
-use crate::session::Session;
+use crate::{session::Session, Core};
 
 /// Extended options for flashing a binary file.
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
@@ -29,6 +29,34 @@ pub enum Format {
     Hex,
     /// Marks a file in the [ELF](https://en.wikipedia.org/wiki/Executable_and_Linkable_Format) format.
     Elf,
+    /// Marks a file in the [Motorola S-record](https://en.wikipedia.org/wiki/SREC_(file_format)) format.
+    Srec,
+    /// Marks a file in the [UF2](https://github.com/microsoft/uf2) format.
+    ///
+    /// Blocks which are marked as not belonging to the main flash are ignored.
+    Uf2,
+}
+
+impl Format {
+    /// Detects the format of a file from its `contents`.
+    ///
+    /// Returns `None` if the format can't be detected, which is usually the case for binary files.
+    pub fn detect(contents: &[u8]) -> Option<Format> {
+        if contents.starts_with(b"\x7FELF") {
+            Some(Format::Elf)
+        } else if contents.len() >= UF2_BLOCK_SIZE
+            && read_u32(contents, 0) == UF2_MAGIC_START0
+            && read_u32(contents, 4) == UF2_MAGIC_START1
+        {
+            Some(Format::Uf2)
+        } else if contents.starts_with(b":") {
+            Some(Format::Hex)
+        } else if matches!(contents, [b'S', digit, ..] if digit.is_ascii_digit()) {
+            Some(Format::Srec)
+        } else {
+            None
+        }
+    }
 }
 
 impl FromStr for Format {
@@ -42,6 +70,8 @@ impl FromStr for Format {
             })),
             "hex" | "ihex" | "intelhex" => Ok(Format::Hex),
             "elf" => Ok(Format::Elf),
+            "srec" | "s19" | "s28" | "s37" | "mot" => Ok(Format::Srec),
+            "uf2" => Ok(Format::Uf2),
             _ => Err(format!("Format '{s}' is unknown.")),
         }
     }
@@ -61,6 +91,22 @@ pub enum FileDownloadError {
     /// Reading and decoding the IHEX file has failed due to the given error.
     #[error("Could not read ihex format")]
     IhexRead(#[from] ihex::ReaderError),
+    /// The given line of the S-record file is invalid.
+    #[error("Could not read S-record file, line {line}: {reason}")]
+    SrecRead {
+        /// The number of the invalid line, starting at 1.
+        line: usize,
+        /// Why the line is invalid.
+        reason: &'static str,
+    },
+    /// The given block of the UF2 file is invalid.
+    #[error("Could not read UF2 file, block {block}: {reason}")]
+    Uf2Read {
+        /// The index of the invalid block in the file.
+        block: usize,
+        /// Why the block is invalid.
+        reason: &'static str,
+    },
     /// An IO error has occurred while reading the firmware file.
     #[error("I/O error")]
     IO(#[from] std::io::Error),
@@ -75,6 +121,14 @@ pub enum FileDownloadError {
     /// This is most likely because of a bad linker script.
     #[error("No loadable ELF sections were found.")]
     NoLoadableSegments,
+    /// The given address range of the file is not located in RAM, so it can't be run from RAM.
+    #[error("The data at {start:#010x}..{end:#010x} is not located in RAM.")]
+    NotInRam {
+        /// The start of the address range.
+        start: u64,
+        /// The end of the address range.
+        end: u64,
+    },
 }
 
 /// Options for downloading a file onto a target chip.
@@ -111,10 +165,52 @@ pub struct DownloadOptions {
     /// If the chip was pre-erased with external erasers, this flag can set to true to skip erasing
     /// It may be useful for mass production.
     pub skip_erase: bool,
+    /// Compare the contents of the flash with the data to be written, and only erase and program
+    /// the sectors which have changed. This has no effect if a full chip erase is done.
+    pub skip_unchanged: bool,
     /// After flashing, read back all the flashed data to verify it has been written correctly.
     pub verify: bool,
     /// Disable double buffering when loading flash.
     pub disable_double_buffering: bool,
+    /// What to do with the cores used for flashing, once the flash is programmed.
+    pub after_flash: AfterFlash,
+    /// Address ranges of the flash which keep their contents, e.g. calibration data or settings.
+    ///
+    /// The ranges are read before anything is erased, and programmed again afterwards.
+    /// This also works with a full chip erase. The ranges must not overlap the data to be flashed.
+    pub preserve_ranges: Vec<Range<u64>>,
+}
+
+/// The state in which the cores used for flashing are left after flashing.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum AfterFlash {
+    /// Leave the core halted, without resetting it.
+    ///
+    /// The core is still in the state the flash algorithm left it in,
+    /// so the caller usually has to reset it before running the flashed program.
+    #[default]
+    Halt,
+    /// Reset the core, and halt it at the reset vector.
+    ///
+    /// This keeps the flashed program from running, e.g. so breakpoints can be set first.
+    ResetAndHalt,
+    /// Reset the core, and let it run the flashed program.
+    ResetAndRun,
+}
+
+impl AfterFlash {
+    /// Applies the policy to `core`.
+    pub(super) fn apply(self, core: &mut Core) -> Result<(), crate::Error> {
+        match self {
+            AfterFlash::Halt => {}
+            AfterFlash::ResetAndHalt => {
+                core.reset_and_halt(Duration::from_millis(500))?;
+            }
+            AfterFlash::ResetAndRun => core.reset()?,
+        }
+
+        Ok(())
+    }
 }
 
 impl DownloadOptions {
@@ -148,18 +244,28 @@ pub fn download_file_with_options<P: AsRef<Path>>(
     format: Format,
     options: DownloadOptions,
 ) -> Result<(), FileDownloadError> {
-    let mut file = match File::open(path.as_ref()) {
-        Ok(file) => file,
-        Err(e) => return Err(FileDownloadError::IO(e)),
-    };
+    download_files_with_options(session, [(path, format)], options)
+}
 
+/// Downloads several `files`, each with its format, to the flash of the target given in `session`.
+///
+/// All files are programmed in a single pass, so sectors which contain data of multiple files
+/// are only erased once. The contents of the files must not overlap.
+///
+/// This can be used to flash e.g. a bootloader, an application and its settings at once.
+pub fn download_files_with_options<P: AsRef<Path>>(
+    session: &mut Session,
+    files: impl IntoIterator<Item = (P, Format)>,
+    options: DownloadOptions,
+) -> Result<(), FileDownloadError> {
     let mut loader = session.target().flash_loader();
 
-    match format {
-        Format::Bin(options) => loader.load_bin_data(&mut file, options),
-        Format::Elf => loader.load_elf_data(&mut file),
-        Format::Hex => loader.load_hex_data(&mut file),
-    }?;
+    for (path, format) in files {
+        let mut file = File::open(path.as_ref())?;
+
+        tracing::debug!("Loading {} as {:?}", path.as_ref().display(), format);
+        loader.load_image(&mut file, format)?;
+    }
 
     loader
         .commit(session, options)
@@ -282,11 +388,295 @@ pub(super) fn extract_from_elf<'data>(
     Ok(extracted_sections)
 }
 
+/// Parses the S-records in `contents`, and returns the data records as pairs of address and bytes.
+pub(super) fn parse_srec(contents: &str) -> Result<Vec<(u64, Vec<u8>)>, FileDownloadError> {
+    let mut data = Vec::new();
+
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let error = |reason| FileDownloadError::SrecRead {
+            line: index + 1,
+            reason,
+        };
+
+        let record_type = match line.as_bytes() {
+            [b'S', record_type, ..] => *record_type,
+            _ => return Err(error("Record doesn't start with 'S'")),
+        };
+
+        // All bytes after the record type, starting with the byte count.
+        let bytes = (2..line.len())
+            .step_by(2)
+            .map(|i| {
+                line.get(i..i + 2)
+                    .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+            })
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(|| error("Invalid hex digits"))?;
+
+        let Some((&count, record)) = bytes.split_first() else {
+            return Err(error("Missing byte count"));
+        };
+        if record.len() != count as usize {
+            return Err(error("Byte count doesn't match the record length"));
+        }
+
+        let checksum = bytes.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+        if checksum != 0xFF {
+            return Err(error("Invalid checksum"));
+        }
+
+        let address_size = match record_type {
+            // Header, record count and start address records don't contain data.
+            b'0' | b'5' | b'6' | b'7' | b'8' | b'9' => continue,
+            b'1' => 2,
+            b'2' => 3,
+            b'3' => 4,
+            _ => return Err(error("Unknown record type")),
+        };
+
+        // The last byte of the record is the checksum.
+        if record.len() < address_size + 1 {
+            return Err(error("Record is too short"));
+        }
+
+        let address = record[..address_size]
+            .iter()
+            .fold(0u64, |address, byte| address << 8 | *byte as u64);
+
+        data.push((address, record[address_size..record.len() - 1].to_vec()));
+    }
+
+    Ok(data)
+}
+
+/// Size of a UF2 block, including header and padding.
+const UF2_BLOCK_SIZE: usize = 512;
+/// Offset of the data in a UF2 block.
+const UF2_DATA_OFFSET: usize = 32;
+/// Maximum payload of a UF2 block.
+const UF2_MAX_PAYLOAD: usize = 476;
+
+const UF2_MAGIC_START0: u32 = 0x0A32_4655;
+const UF2_MAGIC_START1: u32 = 0x9E5D_5157;
+const UF2_MAGIC_END: u32 = 0x0AB1_6F30;
+
+/// The block doesn't belong to the main flash, e.g. it contains comments.
+const UF2_FLAG_NOT_MAIN_FLASH: u32 = 0x0000_0001;
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+/// Parses the UF2 blocks in `contents`, and returns the payloads as pairs of address and bytes.
+pub(super) fn parse_uf2(contents: &[u8]) -> Result<Vec<(u64, Vec<u8>)>, FileDownloadError> {
+    if contents.len() & (UF2_BLOCK_SIZE - 1) != 0 {
+        return Err(FileDownloadError::Uf2Read {
+            block: contents.len() / UF2_BLOCK_SIZE,
+            reason: "Incomplete block",
+        });
+    }
+
+    let mut data = Vec::new();
+
+    for (index, block) in contents.chunks_exact(UF2_BLOCK_SIZE).enumerate() {
+        let error = |reason| FileDownloadError::Uf2Read {
+            block: index,
+            reason,
+        };
+
+        if read_u32(block, 0) != UF2_MAGIC_START0
+            || read_u32(block, 4) != UF2_MAGIC_START1
+            || read_u32(block, UF2_BLOCK_SIZE - 4) != UF2_MAGIC_END
+        {
+            return Err(error("Invalid magic number"));
+        }
+
+        let flags = read_u32(block, 8);
+        if flags & UF2_FLAG_NOT_MAIN_FLASH != 0 {
+            tracing::debug!(
+                "Skipping UF2 block {}, which is not for the main flash",
+                index
+            );
+            continue;
+        }
+
+        let address = read_u32(block, 12) as u64;
+        let size = read_u32(block, 16) as usize;
+        if size > UF2_MAX_PAYLOAD {
+            return Err(error("Payload size is too large"));
+        }
+
+        data.push((
+            address,
+            block[UF2_DATA_OFFSET..UF2_DATA_OFFSET + size].to_vec(),
+        ));
+    }
+
+    Ok(data)
+}
+
 #[cfg(test)]
 mod tests {
+    use std::io::Cursor;
     use std::str::FromStr;
 
-    use super::{BinOptions, Format};
+    use probe_rs_target::{MemoryRegion, NvmRegion, TargetDescriptionSource};
+
+    use crate::flashing::{FlashError, FlashLoader};
+
+    use super::{
+        parse_srec, parse_uf2, BinOptions, FileDownloadError, Format, UF2_BLOCK_SIZE,
+        UF2_FLAG_NOT_MAIN_FLASH, UF2_MAGIC_END, UF2_MAGIC_START0, UF2_MAGIC_START1,
+    };
+
+    fn uf2_block(flags: u32, address: u32, payload: &[u8]) -> Vec<u8> {
+        let mut block = vec![0; UF2_BLOCK_SIZE];
+        block[0..4].copy_from_slice(&UF2_MAGIC_START0.to_le_bytes());
+        block[4..8].copy_from_slice(&UF2_MAGIC_START1.to_le_bytes());
+        block[8..12].copy_from_slice(&flags.to_le_bytes());
+        block[12..16].copy_from_slice(&address.to_le_bytes());
+        block[16..20].copy_from_slice(&(payload.len() as u32).to_le_bytes());
+        block[32..32 + payload.len()].copy_from_slice(payload);
+        block[508..512].copy_from_slice(&UF2_MAGIC_END.to_le_bytes());
+        block
+    }
+
+    #[test]
+    fn parse_srec_records() {
+        let contents = "S00F000068656C6C6F202020202000003C\n\
+                        S11F00007C0802A6900100049421FFF07C6C1B787C8C23783C6000003863000026\n\
+                        S2080800007B0A0A0A56\n\
+                        S3090800100001020304D4\n\
+                        S5030003F9\n\
+                        S9030000FC\n";
+
+        let data = parse_srec(contents).unwrap();
+
+        assert_eq!(data.len(), 3);
+        assert_eq!(data[0].0, 0x0000);
+        assert_eq!(data[0].1.len(), 0x1C);
+        assert_eq!(data[1], (0x08_0000, vec![0x7B, 0x0A, 0x0A, 0x0A]));
+        assert_eq!(data[2], (0x0800_1000, vec![0x01, 0x02, 0x03, 0x04]));
+    }
+
+    #[test]
+    fn parse_srec_invalid_checksum() {
+        let result = parse_srec("S00F000068656C6C6F202020202000003C\nS3090800100001020304D5\n");
+
+        assert!(matches!(
+            result,
+            Err(FileDownloadError::SrecRead {
+                line: 2,
+                reason: "Invalid checksum"
+            })
+        ));
+    }
+
+    #[test]
+    fn parse_uf2_blocks() {
+        let mut contents = uf2_block(0, 0x1000_0000, &[1, 2, 3, 4]);
+        contents.extend(uf2_block(UF2_FLAG_NOT_MAIN_FLASH, 0, b"comment"));
+        contents.extend(uf2_block(0, 0x1000_0100, &[5; 256]));
+
+        let data = parse_uf2(&contents).unwrap();
+
+        assert_eq!(
+            data,
+            vec![(0x1000_0000, vec![1, 2, 3, 4]), (0x1000_0100, vec![5; 256])]
+        );
+    }
+
+    #[test]
+    fn parse_uf2_incomplete_block() {
+        let contents = uf2_block(0, 0x1000_0000, &[1, 2, 3, 4]);
+
+        assert!(matches!(
+            parse_uf2(&contents[..500]),
+            Err(FileDownloadError::Uf2Read { block: 0, .. })
+        ));
+    }
+
+    fn flash_loader() -> FlashLoader {
+        FlashLoader::new(
+            vec![MemoryRegion::Nvm(NvmRegion {
+                name: None,
+                range: 0x0800_0000..0x0801_0000,
+                is_boot_memory: true,
+                cores: vec!["main".to_owned()],
+                min_write_size: None,
+                no_overwrite: false,
+                access: None,
+            })],
+            TargetDescriptionSource::BuiltIn,
+        )
+    }
+
+    #[test]
+    fn load_multiple_images() {
+        let mut loader = flash_loader();
+
+        let bootloader = BinOptions {
+            base_address: Some(0x0800_0000),
+            skip: 0,
+        };
+        loader
+            .load_image(&mut Cursor::new(vec![1; 0x100]), Format::Bin(bootloader))
+            .unwrap();
+
+        let application = b":020000040800F2\n:0410000001020304E2\n:00000001FF\n";
+        loader
+            .load_image(&mut Cursor::new(application), Format::Hex)
+            .unwrap();
+
+        let data = loader.data().collect::<Vec<_>>();
+        assert_eq!(
+            data,
+            vec![
+                (0x0800_0000, &[1; 0x100][..]),
+                (0x0800_1000, &[1, 2, 3, 4][..])
+            ]
+        );
+    }
+
+    #[test]
+    fn load_overlapping_images() {
+        let mut loader = flash_loader();
+
+        let options = BinOptions {
+            base_address: Some(0x0800_0000),
+            skip: 0,
+        };
+        loader
+            .load_image(
+                &mut Cursor::new(vec![1; 0x100]),
+                Format::Bin(options.clone()),
+            )
+            .unwrap();
+
+        let result = loader.load_image(&mut Cursor::new(vec![2; 0x10]), Format::Bin(options));
+
+        assert!(matches!(
+            result,
+            Err(FileDownloadError::Flash(FlashError::DataOverlaps { .. }))
+        ));
+    }
+
+    #[test]
+    fn detect_format() {
+        assert_eq!(Format::detect(b"\x7FELF\x01\x01"), Some(Format::Elf));
+        assert_eq!(Format::detect(b":020000040800F2\n"), Some(Format::Hex));
+        assert_eq!(Format::detect(b"S00F0000"), Some(Format::Srec));
+        assert_eq!(
+            Format::detect(&uf2_block(0, 0x1000_0000, &[1])),
+            Some(Format::Uf2)
+        );
+        assert_eq!(Format::detect(&[0x00, 0x20, 0x00, 0x20]), None);
+    }
 
     #[test]
     fn parse_format() {
@@ -329,6 +719,9 @@ mod tests {
         );
         assert_eq!(Format::from_str("Elf"), Ok(Format::Elf));
         assert_eq!(Format::from_str("elf"), Ok(Format::Elf));
+        assert_eq!(Format::from_str("srec"), Ok(Format::Srec));
+        assert_eq!(Format::from_str("S19"), Ok(Format::Srec));
+        assert_eq!(Format::from_str("uf2"), Ok(Format::Uf2));
         assert_eq!(
             Format::from_str("elfbin"),
             Err("Format 'elfbin' is unknown.".to_string())
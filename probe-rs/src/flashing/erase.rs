@@ -1,19 +1,17 @@
 use std::collections::HashMap;
+use std::ops::Range;
 
-use probe_rs_target::{MemoryRange, MemoryRegion, NvmRegion};
+use probe_rs_target::{MemoryRange, MemoryRegion, NvmRegion, SectorInfo};
 
-use crate::flashing::{flasher::Flasher, FlashError, FlashLoader};
+use crate::flashing::{flasher::Flasher, FlashAlgorithm, FlashError, FlashLoader};
 use crate::Session;
 
 use super::FlashProgress;
 
-/// Mass-erase all nonvolatile memory.
-///
-/// The optional progress will only be used to emit RTT messages.
-/// No actual indication for the state of the erase all operation will be given.
-pub fn erase_all(session: &mut Session, progress: Option<FlashProgress>) -> Result<(), FlashError> {
-    tracing::debug!("Erasing all...");
-
+/// Groups the NVM regions of the target by the flash algorithm and the core used to access them.
+fn nvm_regions_by_algorithm(
+    session: &Session,
+) -> Result<HashMap<(String, String), Vec<NvmRegion>>, FlashError> {
     let mut algos: HashMap<(String, String), Vec<NvmRegion>> = HashMap::new();
     tracing::debug!("Regions:");
     for region in &session.target().memory_map {
@@ -42,15 +40,76 @@ pub fn erase_all(session: &mut Session, progress: Option<FlashProgress>) -> Resu
         }
     }
 
-    for ((algo_name, core_name), regions) in algos {
-        tracing::debug!("Erasing with algorithm: {}", algo_name);
+    Ok(algos)
+}
 
-        // This can't fail, algo_name comes from the target.
-        let algo = session.target().flash_algorithm_by_name(&algo_name);
-        let algo = algo.unwrap().clone();
+/// Creates a flasher for the flash algorithm `algo_name`, running on the core `core_name`.
+fn flasher_for<'session>(
+    session: &'session mut Session,
+    algo_name: &str,
+    core_name: &str,
+    progress: Option<FlashProgress>,
+) -> Result<Flasher<'session>, FlashError> {
+    tracing::debug!("Erasing with algorithm: {}", algo_name);
+
+    // This can't fail, algo_name comes from the target.
+    let algo = session.target().flash_algorithm_by_name(algo_name);
+    let algo = algo.unwrap().clone();
+
+    let core_index = session.target().core_index_by_name(core_name).unwrap();
+    Flasher::new(session, core_index, &algo, progress)
+}
+
+/// The sectors of `algo`, which are located in one of the `regions`.
+fn sectors_in_regions<'a>(
+    algo: &'a FlashAlgorithm,
+    regions: &'a [NvmRegion],
+) -> impl Iterator<Item = SectorInfo> + 'a {
+    algo.iter_sectors().filter(|info| {
+        let range = info.base_address..info.base_address + info.size;
+        regions.iter().any(|r| r.range.contains_range(&range))
+    })
+}
+
+/// The sectors of `algo` in `regions` which overlap `range`.
+///
+/// Returns an error if `range` does not start and end at sector boundaries.
+fn sectors_in_range(
+    algo: &FlashAlgorithm,
+    regions: &[NvmRegion],
+    range: &Range<u64>,
+) -> Result<Vec<SectorInfo>, FlashError> {
+    let sectors = sectors_in_regions(algo, regions)
+        .filter(|info| {
+            let sector = info.base_address..info.base_address + info.size;
+            sector.intersects_range(range)
+        })
+        .collect::<Vec<_>>();
+
+    for info in &sectors {
+        let sector = info.base_address..info.base_address + info.size;
+        if !range.contains_range(&sector) {
+            return Err(FlashError::InvalidEraseRange {
+                start: range.start,
+                end: range.end,
+            });
+        }
+    }
+
+    Ok(sectors)
+}
+
+/// Mass-erase all nonvolatile memory.
+///
+/// The optional progress reports the start and the end of the erase procedure.
+/// If the chip has to be erased sector by sector, the erased sectors are reported as well.
+pub fn erase_all(session: &mut Session, progress: Option<FlashProgress>) -> Result<(), FlashError> {
+    tracing::debug!("Erasing all...");
+
+    let algos = nvm_regions_by_algorithm(session)?;
 
-        let core_index = session.target().core_index_by_name(&core_name).unwrap();
-        let mut flasher = Flasher::new(session, core_index, &algo, progress.clone())?;
+    for ((algo_name, core_name), regions) in algos {
+        let mut flasher = flasher_for(session, &algo_name, &core_name, progress.clone())?;
 
         if flasher.is_chip_erase_supported() {
             tracing::debug!("     -- chip erase supported, doing it.");
@@ -59,29 +118,10 @@ pub fn erase_all(session: &mut Session, progress: Option<FlashProgress>) -> Resu
             tracing::debug!("     -- chip erase not supported, erasing by sector.");
 
             // loop over all sectors erasing them individually instead.
+            let sectors =
+                sectors_in_regions(flasher.flash_algorithm(), &regions).collect::<Vec<_>>();
 
-            let sectors = flasher
-                .flash_algorithm()
-                .iter_sectors()
-                .filter(|info| {
-                    let range = info.base_address..info.base_address + info.size;
-                    regions.iter().any(|r| r.range.contains_range(&range))
-                })
-                .collect::<Vec<_>>();
-
-            flasher.run_erase(|active| {
-                for info in sectors {
-                    tracing::debug!(
-                        "    sector: {:08x}-{:08x} ({} bytes)",
-                        info.base_address,
-                        info.base_address + info.size,
-                        info.size
-                    );
-
-                    active.erase_sector(info.base_address)?;
-                }
-                Ok(())
-            })?;
+            flasher.erase_sectors(&sectors)?;
         }
     }
 
@@ -100,43 +140,10 @@ pub fn erase_sectors(
         start_sector + sectors
     );
 
-    let mut algos: HashMap<(String, String), Vec<NvmRegion>> = HashMap::new();
-    tracing::debug!("Regions:");
-    for region in &session.target().memory_map {
-        if let MemoryRegion::Nvm(region) = region {
-            tracing::debug!(
-                "    region: {:08x}-{:08x} ({} bytes)",
-                region.range.start,
-                region.range.end,
-                region.range.end - region.range.start
-            );
-
-            let algo = FlashLoader::get_flash_algorithm_for_region(region, session.target())?;
-
-            // Get the first core that can access the region
-            let core_name = region
-                .cores
-                .first()
-                .ok_or_else(|| FlashError::NoNvmCoreAccess(region.clone()))?;
-
-            let entry = algos
-                .entry((algo.name.clone(), core_name.clone()))
-                .or_default();
-            entry.push(region.clone());
-
-            tracing::debug!("     -- using algorithm: {}", algo.name);
-        }
-    }
+    let algos = nvm_regions_by_algorithm(session)?;
 
     for ((algo_name, core_name), regions) in algos {
-        tracing::debug!("Erasing with algorithm: {}", algo_name);
-
-        // This can't fail, algo_name comes from the target.
-        let algo = session.target().flash_algorithm_by_name(&algo_name);
-        let algo = algo.unwrap().clone();
-
-        let core_index = session.target().core_index_by_name(&core_name).unwrap();
-        let mut flasher = Flasher::new(session, core_index, &algo, progress.clone())?;
+        let mut flasher = flasher_for(session, &algo_name, &core_name, progress.clone())?;
 
         let sectors = flasher
             .flash_algorithm()
@@ -149,20 +156,148 @@ pub fn erase_sectors(
             })
             .collect::<Vec<_>>();
 
-        flasher.run_erase(|active| {
-            for info in sectors {
-                tracing::debug!(
-                    "    sector: {:08x}-{:08x} ({} bytes)",
-                    info.base_address,
-                    info.base_address + info.size,
-                    info.size
-                );
-
-                active.erase_sector(info.base_address)?;
-            }
-            Ok(())
-        })?;
+        flasher.erase_sectors(&sectors)?;
     }
 
     Ok(())
 }
+
+/// Erases all flash sectors in the address `range`, without programming anything.
+///
+/// The range has to start and end at sector boundaries, so no data outside of the range is erased.
+/// The range can span multiple sectors of different sizes, and multiple flash regions.
+/// If the range contains addresses which are not part of any flash sector,
+/// [`FlashError::InvalidEraseRange`] is returned and nothing is erased.
+pub fn erase_range(
+    session: &mut Session,
+    progress: Option<FlashProgress>,
+    range: Range<u64>,
+) -> Result<(), FlashError> {
+    tracing::debug!("Erasing range {:#010x}..{:#010x}", range.start, range.end);
+
+    let algos = nvm_regions_by_algorithm(session)?;
+
+    // Determine the sectors of all algorithms first, so nothing is erased if the range is invalid.
+    let mut erases = Vec::new();
+    let mut erased_size = 0;
+    for ((algo_name, core_name), regions) in algos {
+        // This can't fail, algo_name comes from the target.
+        let raw = session
+            .target()
+            .flash_algorithm_by_name(&algo_name)
+            .unwrap();
+
+        // Only the flash properties are needed to determine the sectors,
+        // the algorithm is assembled when the flasher is created.
+        let algo = FlashAlgorithm {
+            flash_properties: raw.flash_properties.clone(),
+            ..Default::default()
+        };
+
+        let sectors = sectors_in_range(&algo, &regions, &range)?;
+        if !sectors.is_empty() {
+            erased_size += sectors.iter().map(|info| info.size).sum::<u64>();
+            erases.push((algo_name, core_name, sectors));
+        }
+    }
+
+    // Every address of the range has to be part of a sector.
+    if erased_size != range.end.saturating_sub(range.start) {
+        return Err(FlashError::InvalidEraseRange {
+            start: range.start,
+            end: range.end,
+        });
+    }
+
+    for (algo_name, core_name, sectors) in erases {
+        let mut flasher = flasher_for(session, &algo_name, &core_name, progress.clone())?;
+
+        flasher.erase_sectors(&sectors)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use probe_rs_target::{FlashProperties, NvmRegion, SectorDescription, SectorInfo};
+
+    use super::sectors_in_range;
+    use crate::flashing::{FlashAlgorithm, FlashError};
+
+    /// A flash with four 16 KiB sectors, followed by a 64 KiB and a 128 KiB sector.
+    fn uneven_flash() -> (FlashAlgorithm, Vec<NvmRegion>) {
+        let algo = FlashAlgorithm {
+            flash_properties: FlashProperties {
+                address_range: 0x0800_0000..0x0804_0000,
+                page_size: 0x400,
+                erased_byte_value: 0xFF,
+                program_page_timeout: 10,
+                erase_sector_timeout: 100,
+                sectors: vec![
+                    SectorDescription {
+                        size: 0x4000,
+                        address: 0x0,
+                    },
+                    SectorDescription {
+                        size: 0x1_0000,
+                        address: 0x1_0000,
+                    },
+                    SectorDescription {
+                        size: 0x2_0000,
+                        address: 0x2_0000,
+                    },
+                ],
+            },
+            ..Default::default()
+        };
+
+        let region = NvmRegion {
+            name: None,
+            range: 0x0800_0000..0x0804_0000,
+            is_boot_memory: true,
+            cores: vec!["main".to_owned()],
+            min_write_size: None,
+            no_overwrite: false,
+            access: None,
+        };
+
+        (algo, vec![region])
+    }
+
+    #[test]
+    fn erase_range_of_uneven_sectors() {
+        let (algo, regions) = uneven_flash();
+
+        let sectors = sectors_in_range(&algo, &regions, &(0x0800_C000..0x0802_0000)).unwrap();
+
+        assert_eq!(
+            sectors,
+            vec![
+                SectorInfo {
+                    base_address: 0x0800_C000,
+                    size: 0x4000
+                },
+                SectorInfo {
+                    base_address: 0x0801_0000,
+                    size: 0x1_0000
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn erase_range_not_on_sector_boundary() {
+        let (algo, regions) = uneven_flash();
+
+        let result = sectors_in_range(&algo, &regions, &(0x0801_0000..0x0801_8000));
+
+        assert!(matches!(
+            result,
+            Err(FlashError::InvalidEraseRange {
+                start: 0x0801_0000,
+                end: 0x0801_8000
+            })
+        ));
+    }
+}
@@ -1,6 +1,8 @@
 use crate::config::{NvmRegion, RamRegion, TargetDescriptionSource};
 use crate::error;
+use crate::InstructionSet;
 use std::ops::Range;
+use std::time::Duration;
 
 /// Describes any error that happened during the or in preparation for the flashing procedure.
 #[derive(thiserror::Error, Debug)]
@@ -58,6 +60,14 @@ pub enum FlashError {
         /// The error code the called routine returned.
         error_code: u32,
     },
+    /// The given routine did not finish in time, and was stopped.
+    #[error("The execution of '{name}' did not finish within {timeout:?}. This might indicate a problem with the flash algorithm, or a timeout which is too short.")]
+    RoutineTimeout {
+        /// The name of the routine that was called.
+        name: &'static str,
+        /// The time after which the routine was stopped.
+        timeout: Duration,
+    },
     /// The given address was not contained in the given NVM region.
     #[error("{address:#010x} is not contained in {region:?}")]
     AddressNotInRegion {
@@ -114,6 +124,15 @@ pub enum FlashError {
     /// Flash content verification failed.
     #[error("Flash content verification failed.")]
     Verify,
+    /// The flash contents read back from the target differ from the expected contents.
+    #[error("The flash contents at {address:#010x} differ from the expected contents.")]
+    UnexpectedContents {
+        /// The address of the first byte which differs.
+        address: u64,
+    },
+    /// The target has no flash algorithm with the given name.
+    #[error("The target has no flash algorithm named '{0}'.")]
+    UnknownFlashAlgorithm(String),
     // TODO: 1 Add source of target definition
     // TOOD: 2 Do this at target load time.
     /// The given chip has no RAM defined.
@@ -145,10 +164,39 @@ pub enum FlashError {
     /// No core can access this NVM region.
     #[error("No core can access the NVM region {0:?}.")]
     NoNvmCoreAccess(NvmRegion),
+    /// The range to erase does not start and end at sector boundaries, or is not fully covered by flash sectors.
+    #[error("The range {start:#010x}..{end:#010x} can not be erased, it does not start and end at flash sector boundaries.")]
+    InvalidEraseRange {
+        /// The start address of the range.
+        start: u64,
+        /// The end address of the range.
+        end: u64,
+    },
+    /// Restoring unwritten bytes reads the flash through the memory map, which is not possible
+    /// while the flash banks are swapped.
+    #[error("Restoring unwritten bytes is not supported while the flash banks are swapped.")]
+    RestoreUnwrittenWithSwappedBanks,
+    /// The minimum write size of the NVM region doesn't fit to the page size of the flash algorithm.
+    #[error("The minimum write size {write_size} of the flash region is not a power of two, or doesn't fit to the page size {page_size}.")]
+    InvalidWriteSize {
+        /// The page size of the flash algorithm.
+        page_size: u32,
+        /// The minimum write size of the NVM region.
+        write_size: u32,
+    },
     /// No core can access this RAM region.
     #[error("No core can access the ram region {0:?}.")]
     NoRamCoreAccess(RamRegion),
     /// The register value supplied for this flash algorithm is out of the supported range.
     #[error("The register value {0:08X?} is out of the supported range.")]
     RegisterValueNotSupported(u64),
+    /// There is no routine to check memory on the target for this instruction set.
+    #[error("Checking memory on the target is not supported for the instruction set {0:?}.")]
+    StubNotSupported(InstructionSet),
+    /// No RAM of the core has room for the routine to check memory on the target.
+    #[error("No RAM of core {core_index} has room for the routine to check memory on the target.")]
+    NoRamForStub {
+        /// The index of the core.
+        core_index: usize,
+    },
 }
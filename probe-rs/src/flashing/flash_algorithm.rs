@@ -2,7 +2,10 @@ use probe_rs_target::{FlashProperties, PageInfo, RamRegion, RawFlashAlgorithm, S
 
 use super::FlashError;
 use crate::core::Architecture;
-use crate::{architecture::riscv, Target};
+use crate::{
+    architecture::{riscv, xtensa},
+    Target,
+};
 use std::convert::TryInto;
 
 /// A flash algorithm, which has been assembled for a specific
@@ -31,6 +34,8 @@ pub struct FlashAlgorithm {
     pub pc_erase_sector: u64,
     /// Address of the `EraseAll()` entry point. Optional.
     pub pc_erase_all: Option<u64>,
+    /// Address of the `Verify()` entry point. Optional.
+    pub pc_verify: Option<u64>,
     /// Initial value of the R9 register for calling flash algo entry points, which
     /// determines where the position-independent data resides.
     pub static_base: u64,
@@ -165,6 +170,9 @@ impl FlashAlgorithm {
     // Header for RISCV Flash Algorithms
     const RISCV_FLASH_BLOB_HEADER: [u32; 2] = [riscv::assembly::EBREAK, riscv::assembly::EBREAK];
 
+    // Header for Xtensa Flash Algorithms
+    const XTENSA_FLASH_BLOB_HEADER: [u32; 1] = [xtensa::arch::instruction::BREAK_1_15];
+
     const ARM_FLASH_BLOB_HEADER: [u32; 8] = [
         0xE00A_BE00,
         0x062D_780D,
@@ -180,6 +188,7 @@ impl FlashAlgorithm {
         match architecture {
             Architecture::Arm => &Self::ARM_FLASH_BLOB_HEADER,
             Architecture::Riscv => &Self::RISCV_FLASH_BLOB_HEADER,
+            Architecture::Xtensa => &Self::XTENSA_FLASH_BLOB_HEADER,
         }
     }
 
@@ -299,6 +308,7 @@ impl FlashAlgorithm {
             pc_program_page: code_start + raw.pc_program_page,
             pc_erase_sector: code_start + raw.pc_erase_sector,
             pc_erase_all: raw.pc_erase_all.map(|v| code_start + v),
+            pc_verify: raw.pc_verify.map(|v| code_start + v),
             static_base: code_start + raw.data_section_offset,
             begin_stack: addr_stack,
             begin_data: page_buffers[0],
@@ -311,9 +321,9 @@ impl FlashAlgorithm {
 
 #[cfg(test)]
 mod test {
-    use probe_rs_target::{FlashProperties, SectorDescription, SectorInfo};
+    use probe_rs_target::{FlashProperties, MemoryRegion, SectorDescription, SectorInfo};
 
-    use crate::flashing::FlashAlgorithm;
+    use crate::{config::get_target_by_name, flashing::FlashAlgorithm};
 
     #[test]
     fn flash_sector_single_size() {
@@ -481,4 +491,27 @@ mod test {
         ];
         assert_eq!(&got, expected);
     }
+
+    #[test]
+    fn verify_entry_point_is_relocated() {
+        let target = get_target_by_name("nrf51822_xxAC").unwrap();
+        let ram = target
+            .memory_map
+            .iter()
+            .find_map(|region| match region {
+                MemoryRegion::Ram(ram) => Some(ram),
+                _ => None,
+            })
+            .unwrap();
+
+        let mut raw = target.flash_algorithms[0].clone();
+        raw.pc_verify = None;
+        let algorithm = FlashAlgorithm::assemble_from_raw(&raw, ram, &target).unwrap();
+        assert_eq!(algorithm.pc_verify, None);
+
+        // Entry points are relative to the start of the code.
+        raw.pc_verify = Some(raw.pc_program_page + 0x40);
+        let algorithm = FlashAlgorithm::assemble_from_raw(&raw, ram, &target).unwrap();
+        assert_eq!(algorithm.pc_verify, Some(algorithm.pc_program_page + 0x40));
+    }
 }
@@ -1,6 +1,7 @@
-use probe_rs_target::{MemoryRegion, RawFlashAlgorithm};
+use probe_rs_target::{MemoryRange, MemoryRegion, RawFlashAlgorithm, SectorInfo};
 use tracing::Level;
 
+use super::progress::PhaseTracker;
 use super::{
     FlashAlgorithm, FlashBuilder, FlashError, FlashFill, FlashLayout, FlashPage, FlashProgress,
 };
@@ -221,6 +222,44 @@ impl<'session> Flasher<'session> {
         result
     }
 
+    /// Erases the given sectors, and reports the progress.
+    pub(super) fn erase_sectors(&mut self, sectors: &[SectorInfo]) -> Result<(), FlashError> {
+        self.progress.started_erasing();
+
+        let mut tracker =
+            PhaseTracker::new(sectors.len(), sectors.iter().map(|info| info.size).sum());
+        let mut t = std::time::Instant::now();
+        let result = self.run_erase(|active| {
+            for info in sectors {
+                tracing::debug!(
+                    "    sector: {:08x}-{:08x} ({} bytes)",
+                    info.base_address,
+                    info.base_address + info.size,
+                    info.size
+                );
+
+                active.erase_sector(info.base_address)?;
+
+                let progress = tracker.advance(info.size);
+                active
+                    .progress
+                    .sector_erased(info.size, t.elapsed(), progress);
+
+                t = std::time::Instant::now();
+            }
+            Ok(())
+        });
+
+        if result.is_ok() {
+            tracker.log_summary("Erased");
+            self.progress.finished_erasing();
+        } else {
+            self.progress.failed_erasing();
+        }
+
+        result
+    }
+
     pub(super) fn run_erase<T, F>(&mut self, f: F) -> Result<T, FlashError>
     where
         F: FnOnce(&mut ActiveFlasher<'_, Erase>) -> Result<T, FlashError> + Sized,
@@ -258,11 +297,24 @@ impl<'session> Flasher<'session> {
         self.session.has_sequence_erase_all() || self.flash_algorithm().pc_erase_all.is_some()
     }
 
+    /// Whether the flash algorithm can verify the flash contents itself.
+    pub(super) fn verify_supported(&self) -> bool {
+        self.flash_algorithm.pc_verify.is_some()
+    }
+
     /// Program the contents of given `FlashBuilder` to the flash.
     ///
     /// If `restore_unwritten_bytes` is `true`, all bytes of a sector,
     /// that are not to be written during flashing will be read from the flash first
     /// and written again once the sector is erased.
+    ///
+    /// If `skip_unchanged` is `true`, sectors which already contain the expected
+    /// contents are neither erased nor programmed.
+    ///
+    /// If `verify` is `true` and the flash algorithm has a `Verify()` routine, the
+    /// programmed pages are verified with it afterwards. Returns whether the pages
+    /// were verified.
+    #[allow(clippy::too_many_arguments)]
     pub(super) fn program(
         &mut self,
         region: &NvmRegion,
@@ -270,7 +322,9 @@ impl<'session> Flasher<'session> {
         restore_unwritten_bytes: bool,
         enable_double_buffering: bool,
         skip_erasing: bool,
-    ) -> Result<(), FlashError> {
+        skip_unchanged: bool,
+        verify: bool,
+    ) -> Result<bool, FlashError> {
         tracing::debug!("Starting program procedure.");
         // Convert the list of flash operations into flash sectors and pages.
         let mut flash_layout = flash_builder.build_sectors_and_pages(
@@ -278,6 +332,19 @@ impl<'session> Flasher<'session> {
             &self.flash_algorithm,
             restore_unwritten_bytes,
         )?;
+
+        if skip_unchanged {
+            self.skip_unchanged_sectors(&mut flash_layout, flash_builder, restore_unwritten_bytes)?;
+        }
+
+        // Writing the erased value would make the words unwritable until the next erase.
+        // The pages are still erased, unless the unwritten bytes are restored.
+        if region.no_overwrite && !restore_unwritten_bytes {
+            let removed = flash_layout
+                .remove_erased_pages(self.flash_algorithm.flash_properties.erased_byte_value);
+            tracing::debug!("Skipping {} erased pages", removed);
+        }
+
         self.progress.initialized(flash_layout.clone());
 
         tracing::debug!("Double Buffering enabled: {:?}", enable_double_buffering);
@@ -291,19 +358,24 @@ impl<'session> Flasher<'session> {
 
         if restore_unwritten_bytes {
             let fills = flash_layout.fills().to_vec();
+            let mut tracker =
+                PhaseTracker::new(fills.len(), fills.iter().map(|fill| fill.size()).sum());
             for fill in fills {
                 let t = std::time::Instant::now();
                 let page = &mut flash_layout.pages_mut()[fill.page_index()];
                 let result = self.fill_page(page, &fill);
 
                 // If we encounter an error, catch it, gracefully report the failure and return the error.
-                if result.is_err() {
+                if let Err(error) = result {
                     self.progress.failed_filling();
-                    return result;
+                    return Err(error);
                 } else {
-                    self.progress.page_filled(fill.size(), t.elapsed());
+                    let progress = tracker.advance(fill.size());
+                    self.progress
+                        .page_filled(fill.size(), t.elapsed(), progress);
                 }
             }
+            tracker.log_summary("Filled");
         }
 
         // We successfully finished filling.
@@ -322,6 +394,108 @@ impl<'session> Flasher<'session> {
             self.program_simple(&flash_layout)?;
         };
 
+        if verify && self.verify_supported() {
+            self.verify_pages(&flash_layout)?;
+
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    /// Reads `len` bytes of flash at `address`, and compares them with the contents
+    /// `flash_builder` is expected to leave in the flash.
+    fn check_contents(
+        &mut self,
+        address: u64,
+        len: u64,
+        flash_builder: &FlashBuilder,
+    ) -> Result<(), FlashError> {
+        let erased_byte_value = self.flash_algorithm.flash_properties.erased_byte_value;
+
+        let mut current = vec![0; len as usize];
+        self.run_verify(|active| {
+            active
+                .core
+                .read(address, &mut current)
+                .map_err(FlashError::Core)
+        })?;
+
+        let expected = flash_builder.expected_contents(
+            &(address..address + len),
+            &current,
+            false,
+            erased_byte_value,
+        );
+
+        match first_mismatch(&expected, &current) {
+            Some(offset) => Err(FlashError::UnexpectedContents {
+                address: address + offset as u64,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Removes the sectors from `flash_layout` which already contain the data to be programmed.
+    fn skip_unchanged_sectors(
+        &mut self,
+        flash_layout: &mut FlashLayout,
+        flash_builder: &FlashBuilder,
+        restore_unwritten_bytes: bool,
+    ) -> Result<(), FlashError> {
+        let erased_byte_value = self.flash_algorithm.flash_properties.erased_byte_value;
+        let sectors = flash_layout.sectors().to_vec();
+
+        let unchanged = self.run_verify(|active| {
+            let mut unchanged = Vec::new();
+            for sector in &sectors {
+                let range = sector.address()..sector.address() + sector.size();
+                let mut current = vec![0; sector.size() as usize];
+                active
+                    .core
+                    .read(sector.address(), &mut current)
+                    .map_err(FlashError::Core)?;
+
+                let expected = flash_builder.expected_contents(
+                    &range,
+                    &current,
+                    restore_unwritten_bytes,
+                    erased_byte_value,
+                );
+                if expected == current {
+                    unchanged.push(sector.address());
+                }
+            }
+            Ok(unchanged)
+        })?;
+
+        let skipped = flash_layout.skip_sectors(|sector| unchanged.contains(&sector.address()));
+        tracing::info!(
+            "Skipping {} of {} unchanged sectors",
+            skipped,
+            sectors.len()
+        );
+
+        Ok(())
+    }
+
+    /// Verifies the pages given in `flash_layout`, using the `Verify()` routine of the flash algorithm.
+    ///
+    /// Only the page contents are transferred to the target, which is faster than
+    /// reading back the flash contents.
+    fn verify_pages(&mut self, flash_layout: &FlashLayout) -> Result<(), FlashError> {
+        let mut tracker = page_tracker(flash_layout);
+
+        self.run_verify(|active| {
+            for page in flash_layout.pages() {
+                active.verify_page(page.address(), page.data())?;
+                tracker.advance(page.size() as u64);
+            }
+            Ok(())
+        })?;
+
+        tracker.log_summary("Verified");
+
         Ok(())
     }
 
@@ -349,6 +523,7 @@ impl<'session> Flasher<'session> {
     fn program_simple(&mut self, flash_layout: &FlashLayout) -> Result<(), FlashError> {
         self.progress.started_programming();
 
+        let mut tracker = page_tracker(flash_layout);
         let mut t = std::time::Instant::now();
         let result = self.run_program(|active| {
             for page in flash_layout.pages() {
@@ -358,7 +533,10 @@ impl<'session> Flasher<'session> {
                         page_address: page.address(),
                         source: Box::new(error),
                     })?;
-                active.progress.page_programmed(page.size(), t.elapsed());
+                let progress = tracker.advance(page.size() as u64);
+                active
+                    .progress
+                    .page_programmed(page.size(), t.elapsed(), progress);
 
                 t = std::time::Instant::now();
             }
@@ -366,6 +544,7 @@ impl<'session> Flasher<'session> {
         });
 
         if result.is_ok() {
+            tracker.log_summary("Programmed");
             self.progress.finished_programming();
         } else {
             self.progress.failed_programming();
@@ -378,6 +557,10 @@ impl<'session> Flasher<'session> {
     fn sector_erase(&mut self, flash_layout: &FlashLayout) -> Result<(), FlashError> {
         self.progress.started_erasing();
 
+        let mut tracker = PhaseTracker::new(
+            flash_layout.sectors().len(),
+            flash_layout.sectors().iter().map(|s| s.size()).sum(),
+        );
         let mut t = std::time::Instant::now();
         let result = self.run_erase(|active| {
             for sector in flash_layout.sectors() {
@@ -387,7 +570,10 @@ impl<'session> Flasher<'session> {
                         sector_address: sector.address(),
                         source: Box::new(e),
                     })?;
-                active.progress.sector_erased(sector.size(), t.elapsed());
+                let progress = tracker.advance(sector.size());
+                active
+                    .progress
+                    .sector_erased(sector.size(), t.elapsed(), progress);
 
                 t = std::time::Instant::now();
             }
@@ -395,6 +581,7 @@ impl<'session> Flasher<'session> {
         });
 
         if result.is_ok() {
+            tracker.log_summary("Erased");
             self.progress.finished_erasing();
         } else {
             self.progress.failed_erasing();
@@ -416,36 +603,30 @@ impl<'session> Flasher<'session> {
         let mut current_buf = 0;
         self.progress.started_programming();
 
+        let mut tracker = page_tracker(flash_layout);
         let mut t = std::time::Instant::now();
         let result = self.run_program(|active| {
-            let mut last_page_address = 0;
+            // The page which is currently programmed by the flash algorithm.
+            let mut in_flight: Option<&FlashPage> = None;
+
             for page in flash_layout.pages() {
-                // At the start of each loop cycle load the next page buffer into RAM.
+                // Transfer the next page into RAM, while the previous page is programmed.
                 active.load_page_buffer(page.address(), page.data(), current_buf)?;
 
-                // Then wait for the active RAM -> Flash copy process to finish.
-                // Also check if it finished properly. If it didn't, return an error.
-                let result =
-                    active
-                        .wait_for_completion(Duration::from_secs(2))
-                        .map_err(|error| FlashError::PageWrite {
-                            page_address: last_page_address,
-                            source: Box::new(error),
-                        })?;
-
-                last_page_address = page.address();
-                active.progress.page_programmed(page.size(), t.elapsed());
+                // Then wait for the previous page to be programmed, before the algorithm is started again.
+                if let Some(previous) = in_flight.take() {
+                    active.wait_for_page(previous.address())?;
 
-                t = std::time::Instant::now();
-                if result != 0 {
-                    return Err(FlashError::RoutineCallFailed {
-                        name: "program_page",
-                        error_code: result,
-                    });
+                    let progress = tracker.advance(previous.size() as u64);
+                    active
+                        .progress
+                        .page_programmed(previous.size(), t.elapsed(), progress);
+                    t = std::time::Instant::now();
                 }
 
                 // Start the next copy process.
                 active.start_program_page_with_buffer(page.address(), current_buf)?;
+                in_flight = Some(page);
 
                 // Swap the buffers
                 if current_buf == 1 {
@@ -455,24 +636,20 @@ impl<'session> Flasher<'session> {
                 }
             }
 
-            let result = active
-                .wait_for_completion(Duration::from_secs(2))
-                .map_err(|error| FlashError::PageWrite {
-                    page_address: last_page_address,
-                    source: Box::new(error),
-                })?;
+            if let Some(previous) = in_flight {
+                active.wait_for_page(previous.address())?;
 
-            if result != 0 {
-                Err(FlashError::RoutineCallFailed {
-                    name: "wait_for_completion",
-                    error_code: result,
-                })
-            } else {
-                Ok(0)
+                let progress = tracker.advance(previous.size() as u64);
+                active
+                    .progress
+                    .page_programmed(previous.size(), t.elapsed(), progress);
             }
+
+            Ok(())
         });
 
         if result.is_ok() {
+            tracker.log_summary("Programmed");
             self.progress.finished_programming();
         } else {
             self.progress.failed_programming();
@@ -484,6 +661,234 @@ impl<'session> Flasher<'session> {
     }
 }
 
+/// The outcome of a single check done by [`test_algorithm`].
+#[derive(Debug)]
+pub struct AlgorithmTestCase {
+    /// A description of the check.
+    pub name: String,
+    /// How long the check took.
+    pub duration: Duration,
+    /// The reason why the check failed, or `None` if it passed.
+    pub error: Option<FlashError>,
+}
+
+impl AlgorithmTestCase {
+    /// Whether the check passed.
+    pub fn passed(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// The result of testing a flash algorithm with [`test_algorithm`].
+#[derive(Debug)]
+pub struct AlgorithmTestReport {
+    /// The name of the tested flash algorithm.
+    pub algorithm: String,
+    /// All checks which were run, in order.
+    pub cases: Vec<AlgorithmTestCase>,
+}
+
+impl AlgorithmTestReport {
+    /// Whether all checks passed.
+    pub fn passed(&self) -> bool {
+        self.cases.iter().all(AlgorithmTestCase::passed)
+    }
+
+    /// Runs `check`, and records its outcome as `name`.
+    fn run<'session>(
+        &mut self,
+        flasher: &mut Flasher<'session>,
+        name: String,
+        check: impl FnOnce(&mut Flasher<'session>) -> Result<(), FlashError>,
+    ) {
+        tracing::info!("Testing flash algorithm: {}", name);
+
+        let t = Instant::now();
+        let error = check(flasher).err();
+        if let Some(error) = &error {
+            tracing::warn!("{} failed: {}", name, error);
+        }
+
+        self.cases.push(AlgorithmTestCase {
+            name,
+            duration: t.elapsed(),
+            error,
+        });
+    }
+}
+
+/// Tests the flash algorithm `algorithm_name` of the target in `session`.
+///
+/// The `Init()`, `UnInit()`, `EraseSector()`, `ProgramPage()` and, if present, `Verify()` routines
+/// are run against the first and the last sector of the first flash region covered by the algorithm.
+/// The sectors are erased, programmed with a generated pattern, and programmed with data which
+/// neither starts nor ends on a page boundary. After each step, the flash contents are read back
+/// and compared with the expected contents.
+///
+/// A failing check doesn't stop the test, all results are collected in the returned report.
+/// An error is only returned if the algorithm can't be loaded at all.
+///
+/// **This is destructive**: the tested sectors are erased afterwards, and their previous contents are lost.
+pub fn test_algorithm(
+    session: &mut Session,
+    algorithm_name: &str,
+    progress: Option<FlashProgress>,
+) -> Result<AlgorithmTestReport, FlashError> {
+    let target = session.target();
+    let raw_algorithm = target
+        .flash_algorithm_by_name(algorithm_name)
+        .ok_or_else(|| FlashError::UnknownFlashAlgorithm(algorithm_name.to_string()))?
+        .clone();
+
+    let address_range = &raw_algorithm.flash_properties.address_range;
+    let region = target
+        .memory_map
+        .iter()
+        .find_map(|region| match region {
+            MemoryRegion::Nvm(region) if address_range.contains_range(&region.range) => {
+                Some(region.clone())
+            }
+            _ => None,
+        })
+        .ok_or_else(|| FlashError::NoSuitableNvm {
+            start: address_range.start,
+            end: address_range.end,
+            description_source: target.source().clone(),
+        })?;
+
+    let core_name = region
+        .cores
+        .first()
+        .ok_or_else(|| FlashError::NoNvmCoreAccess(region.clone()))?;
+    // The core names of the memory map are validated when the target is loaded.
+    let core_index = target.core_index_by_name(core_name).unwrap();
+
+    let mut flasher = Flasher::new(session, core_index, &raw_algorithm, progress)?;
+
+    let mut report = AlgorithmTestReport {
+        algorithm: raw_algorithm.name.clone(),
+        cases: Vec::new(),
+    };
+
+    report.run(&mut flasher, "Init and uninit".to_string(), |flasher| {
+        flasher.run_erase(|_| Ok(()))
+    });
+
+    let page_size = flasher.flash_algorithm.flash_properties.page_size as u64;
+    let sectors = boundary_sectors(flasher.flash_algorithm.iter_sectors().filter(|info| {
+        region
+            .range
+            .contains_range(&(info.base_address..info.base_address + info.size))
+    }));
+
+    for sector in sectors {
+        let address = sector.base_address;
+
+        report.run(
+            &mut flasher,
+            format!("Erase sector {address:#010x}"),
+            |flasher| {
+                flasher.erase_sectors(&[sector])?;
+                flasher.check_contents(address, sector.size, &FlashBuilder::new())
+            },
+        );
+
+        report.run(
+            &mut flasher,
+            format!("Program pattern into sector {address:#010x}"),
+            |flasher| {
+                let mut builder = FlashBuilder::new();
+                builder.add_data(address, &test_pattern(address, sector.size))?;
+
+                flasher.program(&region, &builder, false, true, false, false, true)?;
+                flasher.check_contents(address, sector.size, &builder)
+            },
+        );
+
+        report.run(
+            &mut flasher,
+            format!("Program unaligned data into sector {address:#010x}"),
+            |flasher| {
+                // Start in the middle of a word, and end in the middle of the next page.
+                let start = address + 3;
+                let len = (page_size + 5).min(sector.size - 3);
+
+                let mut builder = FlashBuilder::new();
+                builder.add_data(start, &test_pattern(start, len))?;
+
+                flasher.program(&region, &builder, false, true, false, false, true)?;
+                flasher.check_contents(address, sector.size, &builder)
+            },
+        );
+
+        if flasher.verify_supported() {
+            report.run(
+                &mut flasher,
+                format!("Detect mismatch with verify in sector {address:#010x}"),
+                |flasher| {
+                    // The page still contains the unaligned data of the previous check.
+                    let data = vec![
+                        !flasher.flash_algorithm.flash_properties.erased_byte_value;
+                        page_size as usize
+                    ];
+
+                    match flasher.run_verify(|active| active.verify_page(address, &data)) {
+                        Err(FlashError::Verify) => Ok(()),
+                        Err(error) => Err(error),
+                        Ok(()) => Err(FlashError::UnexpectedContents { address }),
+                    }
+                },
+            );
+        }
+
+        report.run(
+            &mut flasher,
+            format!("Erase sector {address:#010x} after the test"),
+            |flasher| {
+                flasher.erase_sectors(&[sector])?;
+                flasher.check_contents(address, sector.size, &FlashBuilder::new())
+            },
+        );
+    }
+
+    Ok(report)
+}
+
+/// The first and the last of the given `sectors`.
+fn boundary_sectors(mut sectors: impl Iterator<Item = SectorInfo>) -> Vec<SectorInfo> {
+    let Some(first) = sectors.next() else {
+        return Vec::new();
+    };
+
+    match sectors.last() {
+        Some(last) => vec![first, last],
+        None => vec![first],
+    }
+}
+
+/// A pattern for `len` bytes at `address`, where each byte depends on its address.
+fn test_pattern(address: u64, len: u64) -> Vec<u8> {
+    (address..address + len)
+        .map(|address| (address ^ (address >> 8) ^ 0xA5) as u8)
+        .collect()
+}
+
+/// The offset of the first byte which differs between `expected` and `actual`.
+fn first_mismatch(expected: &[u8], actual: &[u8]) -> Option<usize> {
+    expected
+        .iter()
+        .zip(actual)
+        .position(|(expected, actual)| expected != actual)
+}
+
+/// Tracks the programming progress of the pages in `flash_layout`.
+fn page_tracker(flash_layout: &FlashLayout) -> PhaseTracker {
+    PhaseTracker::new(
+        flash_layout.pages().len(),
+        flash_layout.pages().iter().map(|p| p.size() as u64).sum(),
+    )
+}
+
 struct Registers {
     pc: u32,
     r0: Option<u32>,
@@ -532,6 +937,7 @@ impl<'probe, O: Operation> ActiveFlasher<'probe, O> {
         if let Some(pc_init) = algo.pc_init {
             let result = self
                 .call_function_and_wait(
+                    "init",
                     &Registers {
                         pc: into_reg(pc_init)?,
                         r0: Some(into_reg(address)?),
@@ -566,6 +972,7 @@ impl<'probe, O: Operation> ActiveFlasher<'probe, O> {
         if let Some(pc_uninit) = algo.pc_uninit {
             let result = self
                 .call_function_and_wait(
+                    "uninit",
                     &Registers {
                         pc: into_reg(pc_uninit)?,
                         r0: Some(O::operation()),
@@ -590,12 +997,13 @@ impl<'probe, O: Operation> ActiveFlasher<'probe, O> {
 
     fn call_function_and_wait(
         &mut self,
+        name: &'static str,
         registers: &Registers,
         init: bool,
         duration: Duration,
     ) -> Result<u32, FlashError> {
         self.call_function(registers, init)?;
-        self.wait_for_completion(duration)
+        self.wait_for_completion(name, duration)
     }
 
     fn call_function(&mut self, registers: &Registers, init: bool) -> Result<(), FlashError> {
@@ -689,8 +1097,16 @@ impl<'probe, O: Operation> ActiveFlasher<'probe, O> {
         Ok(())
     }
 
+    /// Waits until the routine `name` has returned, and returns its result.
+    ///
+    /// If the routine doesn't return within `timeout`, the core is halted and
+    /// [`FlashError::RoutineTimeout`] is returned.
     #[tracing::instrument(skip(self))]
-    pub(super) fn wait_for_completion(&mut self, timeout: Duration) -> Result<u32, FlashError> {
+    pub(super) fn wait_for_completion(
+        &mut self,
+        name: &'static str,
+        timeout: Duration,
+    ) -> Result<u32, FlashError> {
         tracing::debug!("Waiting for routine call completion.");
         let regs = self.core.registers();
 
@@ -716,7 +1132,12 @@ impl<'probe, O: Operation> ActiveFlasher<'probe, O> {
         }
 
         if timeout_ocurred {
-            return Err(FlashError::Core(crate::Error::Timeout));
+            // Stop the routine, so it doesn't keep running while the error is handled.
+            if let Err(error) = self.core.halt(Duration::from_millis(100)) {
+                tracing::warn!("Failed to halt the core after '{name}' timed out: {error}");
+            }
+
+            return Err(FlashError::RoutineTimeout { name, timeout });
         }
 
         let r: u32 = self.core.read_core_reg(regs.result_register(0).id)?;
@@ -753,6 +1174,7 @@ impl<'probe> ActiveFlasher<'probe, Erase> {
         if let Some(pc_erase_all) = algo.pc_erase_all {
             let result = flasher
                 .call_function_and_wait(
+                    "chip_erase",
                     &Registers {
                         pc: into_reg(pc_erase_all)?,
                         r0: None,
@@ -788,6 +1210,7 @@ impl<'probe> ActiveFlasher<'probe, Erase> {
 
         let result = self
             .call_function_and_wait(
+                "erase_sector",
                 &Registers {
                     pc: into_reg(self.flash_algorithm.pc_erase_sector)?,
                     r0: Some(into_reg(address)?),
@@ -811,9 +1234,12 @@ impl<'probe> ActiveFlasher<'probe, Erase> {
         );
 
         if result != 0 {
-            Err(FlashError::RoutineCallFailed {
-                name: "erase_sector",
-                error_code: result,
+            Err(FlashError::EraseFailed {
+                sector_address: address,
+                source: Box::new(FlashError::RoutineCallFailed {
+                    name: "erase_sector",
+                    error_code: result,
+                }),
             })
         } else {
             Ok(())
@@ -821,6 +1247,46 @@ impl<'probe> ActiveFlasher<'probe, Erase> {
     }
 }
 
+impl<'p> ActiveFlasher<'p, Verify> {
+    /// Compares the flash contents at `address` with `bytes`, using the `Verify()` routine.
+    pub(super) fn verify_page(&mut self, address: u64, bytes: &[u8]) -> Result<(), FlashError> {
+        let Some(pc_verify) = self.flash_algorithm.pc_verify else {
+            return Err(FlashError::Verify);
+        };
+
+        // Transfer the bytes to RAM.
+        self.core
+            .write_8(self.flash_algorithm.begin_data, bytes)
+            .map_err(FlashError::Core)?;
+
+        let end_address = into_reg(address + bytes.len() as u64)?;
+
+        // `Verify()` returns the end address if the contents match,
+        // and the address of the first mismatch otherwise.
+        let result = self.call_function_and_wait(
+            "verify",
+            &Registers {
+                pc: into_reg(pc_verify)?,
+                r0: Some(into_reg(address)?),
+                r1: Some(bytes.len() as u32),
+                r2: Some(into_reg(self.flash_algorithm.begin_data)?),
+                r3: None,
+            },
+            false,
+            Duration::from_millis(
+                self.flash_algorithm.flash_properties.program_page_timeout as u64,
+            ),
+        )?;
+
+        if result != end_address {
+            tracing::warn!("Flash contents differ at address {:#010x}", result);
+            return Err(FlashError::Verify);
+        }
+
+        Ok(())
+    }
+}
+
 impl<'p> ActiveFlasher<'p, Program> {
     pub(super) fn program_page(&mut self, address: u64, bytes: &[u8]) -> Result<(), FlashError> {
         let t1 = std::time::Instant::now();
@@ -838,6 +1304,7 @@ impl<'p> ActiveFlasher<'p, Program> {
 
         let result = self
             .call_function_and_wait(
+                "program_page",
                 &Registers {
                     pc: into_reg(self.flash_algorithm.pc_program_page)?,
                     r0: Some(into_reg(address)?),
@@ -900,6 +1367,33 @@ impl<'p> ActiveFlasher<'p, Program> {
         Ok(())
     }
 
+    /// Waits for the programming of the page at `address` to finish, which was started
+    /// with [`ActiveFlasher::start_program_page_with_buffer`].
+    pub(super) fn wait_for_page(&mut self, address: u64) -> Result<(), FlashError> {
+        let timeout = Duration::from_millis(
+            self.flash_algorithm.flash_properties.program_page_timeout as u64,
+        );
+
+        let result = self
+            .wait_for_completion("program_page", timeout)
+            .map_err(|error| FlashError::PageWrite {
+                page_address: address,
+                source: Box::new(error),
+            })?;
+
+        if result != 0 {
+            return Err(FlashError::PageWrite {
+                page_address: address,
+                source: Box::new(FlashError::RoutineCallFailed {
+                    name: "program_page",
+                    error_code: result,
+                }),
+            });
+        }
+
+        Ok(())
+    }
+
     pub(super) fn load_page_buffer(
         &mut self,
         _address: u64,
@@ -937,3 +1431,136 @@ impl<'p> ActiveFlasher<'p, Program> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use probe_rs_target::{FlashProperties, SectorInfo};
+
+    use super::{boundary_sectors, first_mismatch, test_pattern, ActiveFlasher, Program};
+    use crate::{
+        flashing::{FlashAlgorithm, FlashError, FlashProgress},
+        probe::fake_probe::FakeProbe,
+        Permissions, Session,
+    };
+
+    /// A session on a core, which is halted with `r0` set to `result` if `halted` is `true`.
+    fn session(halted: bool, result: u32) -> Session {
+        let mut scs = vec![0; 0x1000];
+        if halted {
+            // DHCSR: S_HALT and S_REGRDY
+            scs[0xdf0..0xdf4].copy_from_slice(&0x0003_0000u32.to_le_bytes());
+        }
+        // DCRDR
+        scs[0xdf8..0xdfc].copy_from_slice(&result.to_le_bytes());
+
+        let mut probe = FakeProbe::new();
+        probe.add_memory_region(0xe000_e000, scs);
+
+        probe
+            .into_probe()
+            .attach_non_intrusive("nrf51822_xxAC", Permissions::default())
+            .unwrap()
+    }
+
+    fn active_flasher(session: &mut Session) -> ActiveFlasher<'_, Program> {
+        ActiveFlasher {
+            core: session.core(0).unwrap(),
+            #[cfg(feature = "rtt")]
+            rtt: None,
+            #[cfg(feature = "rtt")]
+            memory_map: vec![],
+            progress: FlashProgress::new(|_| {}),
+            flash_algorithm: FlashAlgorithm {
+                flash_properties: FlashProperties {
+                    program_page_timeout: 10,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            _operation: core::marker::PhantomData,
+        }
+    }
+
+    #[test]
+    fn wait_for_programmed_page() {
+        let mut session = session(true, 0);
+
+        assert!(active_flasher(&mut session).wait_for_page(0x400).is_ok());
+    }
+
+    #[test]
+    fn failed_page_is_reported() {
+        let mut session = session(true, 1);
+        let mut flasher = active_flasher(&mut session);
+
+        match flasher.wait_for_page(0x400) {
+            Err(FlashError::PageWrite {
+                page_address: 0x400,
+                source,
+            }) => assert!(matches!(
+                source.downcast_ref::<FlashError>(),
+                Some(FlashError::RoutineCallFailed {
+                    name: "program_page",
+                    error_code: 1
+                })
+            )),
+            other => panic!("Unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn routine_timeout_is_reported() {
+        // The core never halts, so the routine doesn't finish.
+        let mut session = session(false, 0);
+        let mut flasher = active_flasher(&mut session);
+
+        match flasher.wait_for_page(0x400) {
+            Err(FlashError::PageWrite {
+                page_address: 0x400,
+                source,
+            }) => assert!(matches!(
+                source.downcast_ref::<FlashError>(),
+                Some(
+                FlashError::RoutineTimeout {
+                    name: "program_page",
+                    timeout
+                }) if *timeout == std::time::Duration::from_millis(10)
+            )),
+            other => panic!("Unexpected result: {other:?}"),
+        }
+    }
+
+    fn sector(base_address: u64) -> SectorInfo {
+        SectorInfo {
+            base_address,
+            size: 0x400,
+        }
+    }
+
+    #[test]
+    fn boundary_sectors_of_region() {
+        let sectors = (0..4).map(|i| sector(i * 0x400));
+        assert_eq!(boundary_sectors(sectors), vec![sector(0), sector(0xC00)]);
+
+        assert_eq!(
+            boundary_sectors(std::iter::once(sector(0x400))),
+            vec![sector(0x400)]
+        );
+        assert_eq!(boundary_sectors(std::iter::empty()), vec![]);
+    }
+
+    #[test]
+    fn test_pattern_depends_on_address() {
+        let pattern = test_pattern(0x0800_0000, 0x200);
+
+        // The pattern doesn't repeat within a page, so shifted data is detected.
+        assert_ne!(pattern[..0x100], pattern[0x100..]);
+        assert_eq!(test_pattern(0x0800_0100, 0x100), pattern[0x100..]);
+    }
+
+    #[test]
+    fn first_mismatching_byte() {
+        assert_eq!(first_mismatch(&[1, 2, 3], &[1, 2, 3]), None);
+        assert_eq!(first_mismatch(&[1, 2, 3], &[1, 0, 0]), Some(1));
+    }
+}
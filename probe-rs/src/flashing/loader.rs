@@ -7,13 +7,20 @@ use std::io::{Read, Seek, SeekFrom};
 use std::ops::Range;
 
 use super::builder::FlashBuilder;
+use super::download::{parse_srec, parse_uf2};
+use super::progress::PhaseTracker;
 use super::{
-    extract_from_elf, BinOptions, DownloadOptions, FileDownloadError, FlashError, Flasher,
+    extract_from_elf, target_crc32, AfterFlash, BinOptions, DownloadOptions, FileDownloadError,
+    FlashError, FlashProgress, Flasher, Format,
 };
 use crate::memory::MemoryInterface;
 use crate::session::Session;
 use crate::Target;
 
+/// The minimum length of a block of flash, which is verified by computing
+/// its CRC32 on the target, instead of reading it back.
+const ASSISTED_VERIFY_MIN_LEN: usize = 0x1000;
+
 /// `FlashLoader` is a struct which manages the flashing of any chunks of data onto any sections of flash.
 ///
 /// Use [add_data()](FlashLoader::add_data) to add a chunk of data.
@@ -90,6 +97,25 @@ impl FlashLoader {
         None
     }
 
+    /// Reads the image in `file` of the given `format`, and adds its data to the loader.
+    ///
+    /// This can be called multiple times to flash several images, e.g. a bootloader and an application,
+    /// with a single [`commit`](FlashLoader::commit). The images must not overlap, but they can share
+    /// flash sectors, which are then only erased once.
+    pub fn load_image<T: Read + Seek>(
+        &mut self,
+        file: &mut T,
+        format: Format,
+    ) -> Result<(), FileDownloadError> {
+        match format {
+            Format::Bin(options) => self.load_bin_data(file, options),
+            Format::Elf => self.load_elf_data(file),
+            Format::Hex => self.load_hex_data(file),
+            Format::Srec => self.load_srec_data(file),
+            Format::Uf2 => self.load_uf2_data(file),
+        }
+    }
+
     /// Reads the data from the binary file and adds it to the loader without splitting it into flash instructions yet.
     pub fn load_bin_data<T: Read + Seek>(
         &mut self,
@@ -146,6 +172,30 @@ impl FlashLoader {
         Ok(())
     }
 
+    /// Reads the data records of a Motorola S-record file and adds them as loadable data blocks to the loader.
+    /// This does not create and flash loader instructions yet.
+    pub fn load_srec_data<T: Read>(&mut self, file: &mut T) -> Result<(), FileDownloadError> {
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?;
+
+        for (address, data) in parse_srec(&contents)? {
+            self.add_data(address, &data)?;
+        }
+        Ok(())
+    }
+
+    /// Reads the blocks of a UF2 file and adds them as loadable data blocks to the loader.
+    /// This does not create and flash loader instructions yet.
+    pub fn load_uf2_data<T: Read>(&mut self, file: &mut T) -> Result<(), FileDownloadError> {
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents)?;
+
+        for (address, data) in parse_uf2(&contents)? {
+            self.add_data(address, &data)?;
+        }
+        Ok(())
+    }
+
     /// Prepares the data sections that have to be loaded into flash from an ELF file.
     /// This will validate the ELF file and transform all its data into sections but no flash loader commands yet.
     pub fn load_elf_data<T: Read>(&mut self, file: &mut T) -> Result<(), FileDownloadError> {
@@ -229,6 +279,44 @@ impl FlashLoader {
             tracing::warn!("Memory map of flash loader does not match memory map of target!");
         }
 
+        // The preserved ranges are programmed like any other data, after reading them from the flash.
+        let preserved_builder;
+        let builder = if options.preserve_ranges.is_empty() {
+            &self.builder
+        } else {
+            preserved_builder =
+                self.preserve_ranges(session, &options.preserve_ranges, options.do_chip_erase)?;
+            &preserved_builder
+        };
+
+        // If the flash banks are swapped, the data has to be programmed to the physical bank
+        // behind its address. Reading the flash while programming would read the other bank,
+        // so the flash is verified with a read back afterwards, and unchanged sectors aren't skipped.
+        let swapped_builder;
+        let mut skip_unchanged = options.skip_unchanged;
+        let mut verify_with_algorithm = options.verify;
+        let builder = match self.swapped_banks(session)? {
+            Some([bank_a, bank_b]) => {
+                tracing::info!(
+                    "Flash banks {:#010x}..{:#010x} and {:#010x}..{:#010x} are swapped",
+                    bank_a.start,
+                    bank_a.end,
+                    bank_b.start,
+                    bank_b.end
+                );
+
+                if options.keep_unwritten_bytes {
+                    return Err(FlashError::RestoreUnwrittenWithSwappedBanks);
+                }
+                skip_unchanged = false;
+                verify_with_algorithm = false;
+
+                swapped_builder = builder.swap_banks(&bank_a, &bank_b)?;
+                &swapped_builder
+            }
+            None => builder,
+        };
+
         let mut algos: HashMap<(String, String), Vec<NvmRegion>> = HashMap::new();
 
         // Commit NVM first
@@ -252,7 +340,7 @@ impl FlashLoader {
 
                 // If we have no data in this region, ignore it.
                 // This avoids uselessly initializing and deinitializing its flash algorithm.
-                if !self.builder.has_data_in_range(&region.range) {
+                if !builder.has_data_in_range(&region.range) {
                     tracing::debug!("     -- empty, ignoring!");
                     continue;
                 }
@@ -287,6 +375,12 @@ impl FlashLoader {
             return Ok(());
         }
 
+        // Regions which were already verified by the flash algorithm.
+        let mut verified_ranges = Vec::new();
+
+        // The cores which were used to write to the target, in the order of their first use.
+        let mut used_cores = Vec::new();
+
         // Iterate all flash algorithms we need to use.
         for ((algo_name, core_name), regions) in algos {
             tracing::debug!("Flashing ranges for algo: {}", algo_name);
@@ -301,6 +395,9 @@ impl FlashLoader {
                 .iter()
                 .position(|c| c.name == core_name)
                 .unwrap();
+            if !used_cores.contains(&core) {
+                used_cores.push(core);
+            }
             let mut flasher = Flasher::new(session, core, &algo, options.progress.clone())?;
 
             let mut do_chip_erase = options.do_chip_erase;
@@ -332,13 +429,19 @@ impl FlashLoader {
                 );
 
                 // Program the data.
-                flasher.program(
+                let verified = flasher.program(
                     &region,
-                    &self.builder,
+                    builder,
                     options.keep_unwritten_bytes,
                     do_use_double_buffering,
                     options.skip_erase || do_chip_erase,
+                    skip_unchanged && !do_chip_erase,
+                    verify_with_algorithm,
                 )?;
+
+                if verified {
+                    verified_ranges.push(region.range.clone());
+                }
             }
         }
 
@@ -369,6 +472,9 @@ impl FlashLoader {
                 let mut some = false;
                 for (address, data) in self.builder.data_in_range(&region.range) {
                     some = true;
+                    if !used_cores.contains(&region_core_index) {
+                        used_cores.push(region_core_index);
+                    }
                     tracing::debug!(
                         "     -- writing: {:08x}-{:08x} ({} bytes)",
                         address,
@@ -387,38 +493,232 @@ impl FlashLoader {
 
         if options.verify {
             tracing::debug!("Verifying!");
-            for (&address, data) in &self.builder.data {
-                tracing::debug!(
-                    "    data: {:08x}-{:08x} ({} bytes)",
+
+            // The progress is reported even without a handler, for the structured events.
+            let progress = options
+                .progress
+                .clone()
+                .unwrap_or_else(|| FlashProgress::new(|_| {}));
+
+            progress.started_verifying();
+
+            let result = self.verify(session, &verified_ranges, &progress);
+
+            if result.is_ok() {
+                progress.finished_verifying();
+            } else {
+                progress.failed_verifying();
+            }
+
+            result?;
+        }
+
+        if options.after_flash != AfterFlash::Halt {
+            tracing::debug!(
+                "Applying {:?} to cores {:?}",
+                options.after_flash,
+                used_cores
+            );
+
+            for core_index in used_cores {
+                let mut core = session.core(core_index).map_err(FlashError::Core)?;
+                options
+                    .after_flash
+                    .apply(&mut core)
+                    .map_err(FlashError::Core)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns a copy of the builder, which additionally contains the current flash contents of the `ranges`.
+    ///
+    /// Without a chip erase, only the parts of the ranges in regions which are programmed are preserved,
+    /// the other regions aren't erased at all.
+    fn preserve_ranges(
+        &self,
+        session: &mut Session,
+        ranges: &[Range<u64>],
+        do_chip_erase: bool,
+    ) -> Result<FlashBuilder, FlashError> {
+        let mut builder = self.builder.clone();
+
+        for range in ranges {
+            // The range can span multiple regions, each is read with its own core.
+            let mut address = range.start;
+            while address < range.end {
+                let region = match Self::get_region_for_address(&self.memory_map, address) {
+                    Some(MemoryRegion::Nvm(region)) => region,
+                    _ => {
+                        return Err(FlashError::NoSuitableNvm {
+                            start: range.start,
+                            end: range.end,
+                            description_source: self.source.clone(),
+                        })
+                    }
+                };
+
+                let end = region.range.end.min(range.end);
+                if !do_chip_erase && !self.builder.has_data_in_range(&region.range) {
+                    address = end;
+                    continue;
+                }
+
+                let core_name = region
+                    .cores
+                    .first()
+                    .ok_or_else(|| FlashError::NoNvmCoreAccess(region.clone()))?;
+                let core_index = session.target().core_index_by_name(core_name).unwrap();
+
+                tracing::info!(
+                    "Preserving flash contents at {:#010x}..{:#010x}",
                     address,
-                    address + data.len() as u64,
-                    data.len()
+                    end
                 );
 
+                let mut data = vec![0; (end - address) as usize];
+                session
+                    .core(core_index)
+                    .map_err(FlashError::Core)?
+                    .read(address, &mut data)
+                    .map_err(FlashError::Core)?;
+                builder.add_data(address, &data)?;
+
+                address = end;
+            }
+        }
+
+        Ok(builder)
+    }
+
+    /// Returns the address ranges of the flash banks, if the target has a dual-bank flash
+    /// and the banks are currently swapped.
+    fn swapped_banks(&self, session: &mut Session) -> Result<Option<[Range<u64>; 2]>, FlashError> {
+        let bank_swap = match &session.target().bank_swap {
+            Some(bank_swap) => bank_swap.clone(),
+            None => return Ok(None),
+        };
+
+        // This can't fail, the banks are validated with the target description.
+        let banks = bank_swap
+            .banks
+            .iter()
+            .map(|name| {
+                session
+                    .target()
+                    .memory_map
+                    .iter()
+                    .find_map(|region| match region {
+                        MemoryRegion::Nvm(region) if region.name.as_ref() == Some(name) => {
+                            Some(region.clone())
+                        }
+                        _ => None,
+                    })
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let core_name = banks[0]
+            .cores
+            .first()
+            .ok_or_else(|| FlashError::NoNvmCoreAccess(banks[0].clone()))?;
+        let core_index = session.target().core_index_by_name(core_name).unwrap();
+
+        let value = session
+            .core(core_index)
+            .map_err(FlashError::Core)?
+            .read_word_32(bank_swap.register)
+            .map_err(FlashError::Core)?;
+
+        if value & bank_swap.mask == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some([banks[0].range.clone(), banks[1].range.clone()]))
+    }
+
+    /// Reads back all the data, and compares it to the data which should have been written.
+    ///
+    /// Data in `verified_ranges` was already verified by the flash algorithm, and is skipped.
+    fn verify(
+        &self,
+        session: &mut Session,
+        verified_ranges: &[Range<u64>],
+        progress: &FlashProgress,
+    ) -> Result<(), FlashError> {
+        let mut tracker = PhaseTracker::new(
+            self.builder.data.len(),
+            self.builder
+                .data
+                .values()
+                .map(|data| data.len() as u64)
+                .sum(),
+        );
+
+        for (&address, data) in &self.builder.data {
+            let t = std::time::Instant::now();
+
+            tracing::debug!(
+                "    data: {:08x}-{:08x} ({} bytes)",
+                address,
+                address + data.len() as u64,
+                data.len()
+            );
+
+            let data_range = address..address + data.len() as u64;
+            if verified_ranges
+                .iter()
+                .any(|range| range.contains_range(&data_range))
+            {
+                tracing::debug!("     -- already verified by the flash algorithm");
+            } else {
                 let associated_region = session
                     .target()
                     .get_memory_region_by_address(address)
                     .unwrap();
-                let core_name = match associated_region {
-                    MemoryRegion::Ram(r) => &r.cores,
-                    MemoryRegion::Generic(r) => &r.cores,
-                    MemoryRegion::Nvm(r) => &r.cores,
-                }
-                .first()
-                .unwrap();
+                let is_nvm = matches!(associated_region, MemoryRegion::Nvm(_));
+                let core_name = associated_region.cores().first().unwrap();
                 let core_index = session.target().core_index_by_name(core_name).unwrap();
-                let mut core = session.core(core_index).map_err(FlashError::Core)?;
-
-                let mut written_data = vec![0; data.len()];
-                core.read(address, &mut written_data)
-                    .map_err(FlashError::Core)?;
 
-                if data != &written_data {
-                    return Err(FlashError::Verify);
+                // Large blocks of flash are checked by the target itself, which is faster
+                // than reading them back. If that's not possible, they are read back.
+                let crc = if is_nvm && data.len() >= ASSISTED_VERIFY_MIN_LEN {
+                    target_crc32(session, core_index, data_range)
+                        .map_err(|error| {
+                            tracing::debug!("Checking the CRC on the target failed: {error}")
+                        })
+                        .ok()
+                } else {
+                    None
+                };
+
+                match crc {
+                    Some(crc) => {
+                        if crc != crc32fast::hash(data) {
+                            return Err(FlashError::Verify);
+                        }
+                    }
+                    None => {
+                        let mut core = session.core(core_index).map_err(FlashError::Core)?;
+
+                        let mut written_data = vec![0; data.len()];
+                        core.read(address, &mut written_data)
+                            .map_err(FlashError::Core)?;
+
+                        if data != &written_data {
+                            return Err(FlashError::Verify);
+                        }
+                    }
                 }
             }
+
+            let block_progress = tracker.advance(data.len() as u64);
+            progress.data_verified(data.len() as u64, t.elapsed(), block_progress);
         }
 
+        tracker.log_summary("Verified");
+
         Ok(())
     }
 
@@ -475,3 +775,100 @@ impl FlashLoader {
             .map(|(address, data)| (*address, data.as_slice()))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        flashing::{AfterFlash, DownloadOptions, FlashError, FlashProgress},
+        probe::fake_probe::FakeProbe,
+        MemoryInterface, Permissions, Session,
+    };
+
+    use super::FlashLoader;
+
+    /// A session on a target, whose flash starts with `flash`.
+    fn session(flash: Vec<u8>) -> Session {
+        let mut probe = FakeProbe::new();
+        probe.add_memory_region(0x0, flash);
+        probe.add_memory_region(0x2000_0000, vec![0; 0x100]);
+        probe.add_memory_region(0xe000_e000, vec![0; 0x1000]);
+
+        probe
+            .into_probe()
+            .attach_non_intrusive("nrf51822_xxAC", Permissions::default())
+            .unwrap()
+    }
+
+    fn flash_loader(session: &Session, data: &[u8]) -> FlashLoader {
+        let target = session.target();
+        let mut loader = FlashLoader::new(target.memory_map.clone(), target.source().clone());
+        loader.add_data(0x0, data).unwrap();
+        loader
+    }
+
+    #[test]
+    fn verify_reads_back_data() {
+        let mut session = session(vec![1; 0x100]);
+        let progress = FlashProgress::new(|_| {});
+
+        let loader = flash_loader(&session, &[1; 0x100]);
+        assert!(loader.verify(&mut session, &[], &progress).is_ok());
+
+        let loader = flash_loader(&session, &[2; 0x100]);
+        assert!(matches!(
+            loader.verify(&mut session, &[], &progress),
+            Err(FlashError::Verify)
+        ));
+    }
+
+    #[test]
+    fn verify_skips_verified_ranges() {
+        let mut session = session(vec![1; 0x100]);
+        let progress = FlashProgress::new(|_| {});
+        let loader = flash_loader(&session, &[2; 0x100]);
+
+        // The data was already verified by the flash algorithm, so it isn't read back.
+        assert!(loader
+            .verify(&mut session, &[0x0..0x1000, 0x1000..0x2000], &progress)
+            .is_ok());
+
+        // Data which isn't covered by a single range is still read back.
+        assert!(matches!(
+            loader.verify(&mut session, &[0x0..0x80, 0x80..0x100], &progress),
+            Err(FlashError::Verify)
+        ));
+    }
+
+    /// Loads data into RAM with `after_flash`, and returns the value written to AIRCR.
+    fn load_to_ram(after_flash: AfterFlash) -> u32 {
+        let mut session = session(vec![0; 0x100]);
+
+        let target = session.target();
+        let mut loader = FlashLoader::new(target.memory_map.clone(), target.source().clone());
+        loader.add_data(0x2000_0000, &[1; 0x10]).unwrap();
+
+        let options = DownloadOptions {
+            after_flash,
+            ..Default::default()
+        };
+        loader.commit(&mut session, options).unwrap();
+
+        let mut core = session.core(0).unwrap();
+        let mut data = [0; 0x10];
+        core.read(0x2000_0000, &mut data).unwrap();
+        assert_eq!(data, [1; 0x10]);
+
+        core.read_word_32(0xe000_ed0c).unwrap()
+    }
+
+    #[test]
+    fn cores_are_left_halted_after_flashing() {
+        assert_eq!(load_to_ram(AfterFlash::Halt), 0);
+    }
+
+    #[test]
+    fn used_cores_are_reset_after_flashing() {
+        // AIRCR: VECTKEY and SYSRESETREQ
+        assert_eq!(load_to_ram(AfterFlash::ResetAndRun), 0x05fa_0004);
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! This modules provides a means to do flash unlocking, erasing and programming.
 //!
-//! It provides a convenient highlevel interface that can flash an ELF, IHEX or BIN file
+//! It provides a convenient highlevel interface that can flash an ELF, IHEX, S-record, UF2 or BIN file
 //! as well as a lower level block based interface.
 //!
 //!
@@ -43,6 +43,7 @@
 //!
 //!
 
+mod assisted;
 mod builder;
 mod download;
 mod erase;
@@ -50,16 +51,24 @@ mod error;
 mod flash_algorithm;
 mod flasher;
 mod loader;
+mod multi;
 mod progress;
+mod ram;
+mod upload;
 mod visualizer;
 
 use builder::*;
 use flasher::*;
 
+pub use assisted::{target_compare, target_crc32};
 pub use download::*;
 pub use erase::*;
 pub use error::*;
 pub use flash_algorithm::*;
+pub use flasher::{test_algorithm, AlgorithmTestCase, AlgorithmTestReport};
 pub use loader::*;
+pub use multi::*;
 pub use progress::*;
+pub use ram::*;
+pub use upload::*;
 pub use visualizer::*;
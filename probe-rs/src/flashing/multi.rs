@@ -0,0 +1,81 @@
+use std::{
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use super::{download_files_with_options, DownloadOptions, FileDownloadError, FlashProgress};
+use super::{Format, ProgressEvent};
+use crate::session::Session;
+
+/// A target which is flashed by [`flash_many`].
+pub struct FlashJob {
+    /// The session of the target, each target needs its own probe.
+    pub session: Session,
+    /// The files to flash, each with its format.
+    pub files: Vec<(PathBuf, Format)>,
+}
+
+/// The outcome of flashing a single target with [`flash_many`].
+pub struct FlashJobResult {
+    /// The session of the target, which can be used to continue working with the target.
+    pub session: Session,
+    /// Whether flashing the target was successful.
+    pub result: Result<(), FileDownloadError>,
+    /// How long flashing the target took.
+    pub duration: Duration,
+}
+
+/// Flashes multiple targets concurrently, each with its own session.
+///
+/// Each job is run in its own thread, using the [`DownloadOptions`] returned by `options`.
+/// The progress of all jobs is reported to `progress`, together with the index of the job
+/// the event belongs to. This replaces the progress reporter of the options.
+///
+/// The results are returned in the order of the `jobs`. A failing job doesn't affect the other jobs.
+pub fn flash_many(
+    jobs: Vec<FlashJob>,
+    options: impl Fn() -> DownloadOptions + Sync,
+    progress: impl Fn(usize, ProgressEvent) + Send + Sync + 'static,
+) -> Vec<FlashJobResult> {
+    let progress = Arc::new(progress);
+    let options = &options;
+
+    std::thread::scope(|scope| {
+        let handles = jobs
+            .into_iter()
+            .enumerate()
+            .map(|(index, job)| {
+                let progress = progress.clone();
+
+                scope.spawn(move || {
+                    let FlashJob { mut session, files } = job;
+
+                    let mut options = options();
+                    options.progress =
+                        Some(FlashProgress::new(move |event| progress(index, event)));
+
+                    let t = Instant::now();
+                    let result = download_files_with_options(&mut session, files, options);
+                    if let Err(error) = &result {
+                        tracing::warn!("Flashing target {} failed: {}", index, error);
+                    }
+
+                    FlashJobResult {
+                        session,
+                        result,
+                        duration: t.elapsed(),
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .map(|handle| match handle.join() {
+                Ok(result) => result,
+                Err(panic) => std::panic::resume_unwind(panic),
+            })
+            .collect()
+    })
+}
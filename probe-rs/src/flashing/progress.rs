@@ -1,5 +1,9 @@
 use super::FlashLayout;
-use std::{sync::Arc, time::Duration};
+use crate::events::{self, Event};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 /// A structure to manage the flashing procedure progress reporting.
 ///
@@ -29,6 +33,10 @@ impl FlashProgress {
 
     /// Emit a flashing progress event.
     fn emit(&self, event: ProgressEvent) {
+        if let Some(structured) = Event::from_progress(&event) {
+            events::emit(|| structured);
+        }
+
         (self.handler)(event);
     }
 
@@ -52,19 +60,45 @@ impl FlashProgress {
         self.emit(ProgressEvent::StartedProgramming);
     }
 
+    /// Signalize that the verifying procedure started.
+    pub(super) fn started_verifying(&self) {
+        self.emit(ProgressEvent::StartedVerifying);
+    }
+
     /// Signalize that the page programming procedure has made progress.
-    pub(super) fn page_programmed(&self, size: u32, time: Duration) {
-        self.emit(ProgressEvent::PageProgrammed { size, time });
+    pub(super) fn page_programmed(&self, size: u32, time: Duration, progress: PhaseProgress) {
+        self.emit(ProgressEvent::PageProgrammed {
+            size,
+            time,
+            progress,
+        });
     }
 
     /// Signalize that the sector erasing procedure has made progress.
-    pub(super) fn sector_erased(&self, size: u64, time: Duration) {
-        self.emit(ProgressEvent::SectorErased { size, time });
+    pub(super) fn sector_erased(&self, size: u64, time: Duration, progress: PhaseProgress) {
+        self.emit(ProgressEvent::SectorErased {
+            size,
+            time,
+            progress,
+        });
     }
 
     /// Signalize that the page filling procedure has made progress.
-    pub(super) fn page_filled(&self, size: u64, time: Duration) {
-        self.emit(ProgressEvent::PageFilled { size, time });
+    pub(super) fn page_filled(&self, size: u64, time: Duration, progress: PhaseProgress) {
+        self.emit(ProgressEvent::PageFilled {
+            size,
+            time,
+            progress,
+        });
+    }
+
+    /// Signalize that the verifying procedure has made progress.
+    pub(super) fn data_verified(&self, size: u64, time: Duration, progress: PhaseProgress) {
+        self.emit(ProgressEvent::DataVerified {
+            size,
+            time,
+            progress,
+        });
     }
 
     /// Signalize that the programming procedure failed.
@@ -97,6 +131,16 @@ impl FlashProgress {
         self.emit(ProgressEvent::FinishedFilling);
     }
 
+    /// Signalize that the verifying procedure failed.
+    pub(super) fn failed_verifying(&self) {
+        self.emit(ProgressEvent::FailedVerifying);
+    }
+
+    /// Signalize that the verifying procedure completed successfully.
+    pub(super) fn finished_verifying(&self) {
+        self.emit(ProgressEvent::FinishedVerifying);
+    }
+
     #[cfg(feature = "rtt")]
     pub(super) fn message(&self, message: String) {
         self.emit(ProgressEvent::DiagnosticMessage { message });
@@ -119,6 +163,12 @@ impl FlashProgress {
 /// * `PageProgrammed` for every page
 /// * `FinishedProgramming`
 ///
+/// If verification is enabled, it is followed by:
+///
+/// * `StartedVerifying`
+/// * `DataVerified` for every verified block of data
+/// * `FinishedVerifying`
+///
 /// If an erorr occurs in any stage, one of the `Failed*` event will be returned,
 /// and no further events will be returned.
 #[derive(Debug)]
@@ -139,6 +189,8 @@ pub enum ProgressEvent {
         size: u64,
         /// The time it took to fill this flash page.
         time: Duration,
+        /// The progress of the filling procedure, including this page.
+        progress: PhaseProgress,
     },
     /// Filling of the pages has failed.
     FailedFilling,
@@ -152,6 +204,8 @@ pub enum ProgressEvent {
         size: u64,
         /// The time it took to erase this sector.
         time: Duration,
+        /// The progress of the erasing procedure, including this sector.
+        progress: PhaseProgress,
     },
     /// Erasing of the flash has failed.
     FailedErasing,
@@ -165,14 +219,158 @@ pub enum ProgressEvent {
         size: u32,
         /// The time it took to program this page.
         time: Duration,
+        /// The progress of the programming procedure, including this page.
+        progress: PhaseProgress,
     },
     /// Programming of the flash failed.
     FailedProgramming,
     /// Programming of the flash has finished successfully.
     FinishedProgramming,
+    /// Verifying of the written data has started.
+    StartedVerifying,
+    /// A block of data has been read back and matches the written data.
+    DataVerified {
+        /// The size of the data block in bytes.
+        size: u64,
+        /// The time it took to verify this data block.
+        time: Duration,
+        /// The progress of the verifying procedure, including this data block.
+        progress: PhaseProgress,
+    },
+    /// Verifying of the written data failed.
+    FailedVerifying,
+    /// Verifying of the written data has finished successfully.
+    FinishedVerifying,
     /// a message was received from the algo.
     DiagnosticMessage {
         /// The message that was emitted.
         message: String,
     },
 }
+
+/// The progress within one phase of the flashing procedure, e.g. erasing sector N of M.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PhaseProgress {
+    /// The number of completed items (pages, sectors or data blocks) of this phase.
+    pub completed: usize,
+    /// The total number of items of this phase.
+    pub total: usize,
+    /// The number of bytes which were processed so far.
+    pub completed_bytes: u64,
+    /// The total number of bytes of this phase.
+    pub total_bytes: u64,
+    /// The time since the start of this phase.
+    pub elapsed: Duration,
+}
+
+impl PhaseProgress {
+    /// The estimated time until this phase is finished, based on the average
+    /// throughput so far.
+    ///
+    /// Returns `None` if nothing was processed yet.
+    pub fn eta(&self) -> Option<Duration> {
+        if self.completed_bytes == 0 {
+            return None;
+        }
+
+        let remaining_bytes = self.total_bytes.saturating_sub(self.completed_bytes);
+        let nanos =
+            self.elapsed.as_nanos() * remaining_bytes as u128 / self.completed_bytes as u128;
+
+        Some(Duration::from_nanos(nanos.try_into().unwrap_or(u64::MAX)))
+    }
+}
+
+/// Keeps track of the progress within one phase of the flashing procedure.
+pub(super) struct PhaseTracker {
+    started: Instant,
+    completed: usize,
+    total: usize,
+    completed_bytes: u64,
+    total_bytes: u64,
+}
+
+impl PhaseTracker {
+    /// Starts tracking a phase with `total` items, which have a size of `total_bytes` in total.
+    pub(super) fn new(total: usize, total_bytes: u64) -> Self {
+        Self {
+            started: Instant::now(),
+            completed: 0,
+            total,
+            completed_bytes: 0,
+            total_bytes,
+        }
+    }
+
+    /// Records the completion of an item with `size` bytes, and returns the new progress.
+    pub(super) fn advance(&mut self, size: u64) -> PhaseProgress {
+        self.completed += 1;
+        self.completed_bytes += size;
+
+        self.progress()
+    }
+
+    /// The current progress of the phase.
+    pub(super) fn progress(&self) -> PhaseProgress {
+        PhaseProgress {
+            completed: self.completed,
+            total: self.total,
+            completed_bytes: self.completed_bytes,
+            total_bytes: self.total_bytes,
+            elapsed: self.started.elapsed(),
+        }
+    }
+
+    /// Logs a summary of the phase, so it is visible where the time was spent.
+    pub(super) fn log_summary(&self, phase: &str) {
+        tracing::info!(
+            "{} {} of {} items ({} of {} bytes) in {:?}",
+            phase,
+            self.completed,
+            self.total,
+            self.completed_bytes,
+            self.total_bytes,
+            self.started.elapsed()
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PhaseProgress, PhaseTracker};
+    use std::time::Duration;
+
+    #[test]
+    fn phase_tracker_counts_items_and_bytes() {
+        let mut tracker = PhaseTracker::new(3, 3072);
+
+        tracker.advance(1024);
+        let progress = tracker.advance(1024);
+
+        assert_eq!(progress.completed, 2);
+        assert_eq!(progress.total, 3);
+        assert_eq!(progress.completed_bytes, 2048);
+        assert_eq!(progress.total_bytes, 3072);
+    }
+
+    #[test]
+    fn eta_from_throughput() {
+        let progress = PhaseProgress {
+            completed: 1,
+            total: 4,
+            completed_bytes: 1024,
+            total_bytes: 4096,
+            elapsed: Duration::from_millis(100),
+        };
+
+        assert_eq!(progress.eta(), Some(Duration::from_millis(300)));
+
+        let not_started = PhaseProgress {
+            completed: 0,
+            completed_bytes: 0,
+            ..progress
+        };
+
+        assert_eq!(not_started.eta(), None);
+    }
+}
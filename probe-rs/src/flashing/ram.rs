@@ -0,0 +1,184 @@
+use object::{Object, ObjectSection};
+use probe_rs_target::MemoryRegion;
+
+use std::{ops::Range, path::Path, time::Duration};
+
+use super::{extract_from_elf, FileDownloadError, FlashError};
+use crate::{session::Session, Core, MemoryInterface};
+
+/// Sections which contain the vector table of an ARM Cortex-M program.
+const VECTOR_TABLE_SECTIONS: [&str; 3] = [".vector_table", ".isr_vector", "RESET"];
+
+/// The Vector Table Offset Register of ARM Cortex-M cores.
+const VTOR: u64 = 0xE000_ED08;
+
+/// Loads the ELF file at `path` into the RAM of the target, and starts executing it on the core `core_index`.
+///
+/// All loadable segments of the file have to be located in RAM regions of the target,
+/// no flash algorithm is used. The core is reset and halted before the segments are written.
+///
+/// On ARM Cortex-M cores, the program is started like after a reset, if the file contains
+/// a vector table: VTOR is set to the vector table, and the stack pointer and the program counter
+/// are loaded from its first two entries. Otherwise, execution starts at the entry point of the file.
+pub fn run_from_ram<P: AsRef<Path>>(
+    session: &mut Session,
+    core_index: usize,
+    path: P,
+) -> Result<(), FileDownloadError> {
+    let elf_data = std::fs::read(path.as_ref())?;
+
+    let mut segments = Vec::new();
+    if extract_from_elf(&mut segments, &elf_data)? == 0 {
+        return Err(FileDownloadError::NoLoadableSegments);
+    }
+
+    for segment in &segments {
+        let start = segment.address as u64;
+        check_in_ram(
+            &session.target().memory_map,
+            start..start + segment.data.len() as u64,
+        )?;
+    }
+
+    let binary = object::File::parse(&elf_data[..])?;
+    let entry = binary.entry();
+    let vector_table = VECTOR_TABLE_SECTIONS
+        .iter()
+        .find_map(|name| binary.section_by_name(name))
+        .map(|section| section.address());
+
+    let mut core = session.core(core_index).map_err(FlashError::Core)?;
+    core.reset_and_halt(Duration::from_millis(500))
+        .map_err(FlashError::Core)?;
+
+    for segment in &segments {
+        tracing::debug!(
+            "Loading {:?} to {:#010x} ({} bytes)",
+            segment.section_names,
+            segment.address,
+            segment.data.len()
+        );
+        core.write_8(segment.address as u64, segment.data)
+            .map_err(FlashError::Core)?;
+    }
+
+    start_program(&mut core, vector_table, entry).map_err(FlashError::Core)?;
+
+    Ok(())
+}
+
+/// Sets up the registers of the halted `core` to start the program, and runs it.
+fn start_program(
+    core: &mut Core,
+    vector_table: Option<u64>,
+    entry: u64,
+) -> Result<(), crate::Error> {
+    let regs = core.registers();
+
+    let pc = match vector_table {
+        Some(vector_table) if core.core_type().is_cortex_m() => {
+            let mut vectors = [0; 2];
+            core.read_32(vector_table, &mut vectors)?;
+
+            tracing::info!(
+                "Starting from vector table at {:#010x}, SP = {:#010x}, PC = {:#010x}",
+                vector_table,
+                vectors[0],
+                vectors[1]
+            );
+
+            core.write_word_32(VTOR, vector_table as u32)?;
+            core.write_core_reg(regs.stack_pointer().id, vectors[0])?;
+
+            // The reset vector has the Thumb bit set, which must not be set in the PC.
+            vectors[1] & !1
+        }
+        _ => {
+            tracing::info!("Starting from entry point {:#010x}", entry);
+            entry as u32
+        }
+    };
+
+    core.write_core_reg(regs.program_counter().id, pc)?;
+    core.run()
+}
+
+/// Checks that `range` is completely covered by RAM regions of the `memory_map`.
+fn check_in_ram(memory_map: &[MemoryRegion], range: Range<u64>) -> Result<(), FileDownloadError> {
+    let mut address = range.start;
+    while address < range.end {
+        let region = memory_map.iter().find_map(|region| match region {
+            MemoryRegion::Ram(region) if region.range.contains(&address) => Some(&region.range),
+            _ => None,
+        });
+
+        match region {
+            Some(region) => address = region.end,
+            None => {
+                return Err(FileDownloadError::NotInRam {
+                    start: range.start,
+                    end: range.end,
+                })
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use probe_rs_target::{MemoryRegion, NvmRegion, RamRegion};
+
+    use super::check_in_ram;
+    use crate::flashing::FileDownloadError;
+
+    fn memory_map() -> Vec<MemoryRegion> {
+        vec![
+            MemoryRegion::Nvm(NvmRegion {
+                name: None,
+                range: 0x0800_0000..0x0810_0000,
+                is_boot_memory: true,
+                cores: vec!["main".to_owned()],
+                min_write_size: None,
+                no_overwrite: false,
+                access: None,
+            }),
+            MemoryRegion::Ram(RamRegion {
+                name: None,
+                range: 0x2000_0000..0x2002_0000,
+                is_boot_memory: false,
+                cores: vec!["main".to_owned()],
+                access: None,
+            }),
+            MemoryRegion::Ram(RamRegion {
+                name: None,
+                range: 0x2002_0000..0x2003_0000,
+                is_boot_memory: false,
+                cores: vec!["main".to_owned()],
+                access: None,
+            }),
+        ]
+    }
+
+    #[test]
+    fn segments_in_adjacent_ram_regions() {
+        assert!(check_in_ram(&memory_map(), 0x2000_0000..0x2000_0400).is_ok());
+        assert!(check_in_ram(&memory_map(), 0x2001_FF00..0x2002_0100).is_ok());
+    }
+
+    #[test]
+    fn segment_in_flash() {
+        assert!(matches!(
+            check_in_ram(&memory_map(), 0x0800_0000..0x0800_0400),
+            Err(FileDownloadError::NotInRam {
+                start: 0x0800_0000,
+                end: 0x0800_0400
+            })
+        ));
+        assert!(matches!(
+            check_in_ram(&memory_map(), 0x2002_FF00..0x2003_0100),
+            Err(FileDownloadError::NotInRam { .. })
+        ));
+    }
+}
@@ -0,0 +1,224 @@
+use ihex::Record;
+use probe_rs_target::{MemoryRange, MemoryRegion};
+
+use std::{fs::File, io::Write, ops::Range, path::Path, str::FromStr};
+
+use crate::{session::Session, MemoryInterface};
+
+/// The formats in which the memory contents can be written to a file by [`upload_file`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum UploadFormat {
+    /// The memory contents are written to the file 1:1.
+    Bin,
+    /// The memory contents are written in [Intel HEX](https://en.wikipedia.org/wiki/Intel_HEX) format,
+    /// which also contains the addresses of the data.
+    Hex,
+}
+
+impl FromStr for UploadFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &s.to_lowercase()[..] {
+            "bin" | "binary" => Ok(UploadFormat::Bin),
+            "hex" | "ihex" | "intelhex" => Ok(UploadFormat::Hex),
+            _ => Err(format!("Format '{s}' is unknown.")),
+        }
+    }
+}
+
+/// The memory which is read by [`upload_file`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum UploadRange {
+    /// An arbitrary address range.
+    Range(Range<u64>),
+    /// The whole NVM region with the given name, as given in the target description.
+    Region(String),
+}
+
+/// A finite list of all the errors that can occur when reading the target memory to a file.
+#[derive(Debug, thiserror::Error)]
+pub enum FileUploadError {
+    /// Reading the memory of the target has failed.
+    #[error("Error while reading the target memory")]
+    Read(#[from] crate::Error),
+    /// The target has no NVM region with the given name.
+    #[error("The target has no NVM region named '{0}'")]
+    UnknownRegion(String),
+    /// Encoding the memory contents as IHEX has failed due to the given error.
+    #[error("Could not write ihex format")]
+    IhexWrite(#[from] ihex::WriterError),
+    /// An IO error has occurred while writing the file.
+    #[error("I/O error")]
+    IO(#[from] std::io::Error),
+}
+
+/// Reads the memory given by `range` from the target in `session`.
+///
+/// The memory is read with the first core which can access it according to the memory map.
+pub fn read_memory(
+    session: &mut Session,
+    range: UploadRange,
+) -> Result<(u64, Vec<u8>), FileUploadError> {
+    let range = match range {
+        UploadRange::Range(range) => range,
+        UploadRange::Region(name) => session
+            .target()
+            .memory_map
+            .iter()
+            .find_map(|region| match region {
+                MemoryRegion::Nvm(region) if region.name.as_deref() == Some(&name) => {
+                    Some(region.range.clone())
+                }
+                _ => None,
+            })
+            .ok_or(FileUploadError::UnknownRegion(name))?,
+    };
+
+    let core_index = core_for_range(session, &range);
+
+    tracing::debug!(
+        "Reading {:#010x}..{:#010x} with core {}",
+        range.start,
+        range.end,
+        core_index
+    );
+
+    let mut data = vec![0; range.end.saturating_sub(range.start) as usize];
+    session.core(core_index)?.read(range.start, &mut data)?;
+
+    Ok((range.start, data))
+}
+
+/// Reads the memory given by `range` from the target in `session`, and writes it in the given `format` to the file at `path`.
+///
+/// This is the counterpart to [download_file](super::download_file), and can be used to make a backup of the flash contents.
+pub fn upload_file<P: AsRef<Path>>(
+    session: &mut Session,
+    path: P,
+    format: UploadFormat,
+    range: UploadRange,
+) -> Result<(), FileUploadError> {
+    let (address, data) = read_memory(session, range)?;
+
+    let mut file = File::create(path.as_ref())?;
+
+    match format {
+        UploadFormat::Bin => file.write_all(&data)?,
+        UploadFormat::Hex => {
+            let hex = ihex::create_object_file_representation(&hex_records(address, &data))?;
+            file.write_all(hex.as_bytes())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The index of the first core which can access `range`, or the first core of the target
+/// if the range is not part of the memory map.
+fn core_for_range(session: &Session, range: &Range<u64>) -> usize {
+    session
+        .target()
+        .memory_map
+        .iter()
+        .find_map(|region| {
+            if region.address_range().contains_range(range) {
+                region
+                    .cores()
+                    .first()
+                    .and_then(|name| session.target().core_index_by_name(name))
+            } else {
+                None
+            }
+        })
+        .unwrap_or(0)
+}
+
+/// Maximum number of data bytes in a single IHEX data record.
+const HEX_RECORD_SIZE: usize = 16;
+
+/// Converts the `data` at `address` into IHEX records.
+///
+/// An extended linear address record is emitted at the start, and whenever a 64 KiB boundary is crossed.
+fn hex_records(address: u64, data: &[u8]) -> Vec<Record> {
+    let mut records = Vec::new();
+    let mut upper = None;
+
+    let mut offset = 0;
+    while offset < data.len() {
+        let record_address = address + offset as u64;
+
+        let segment = (record_address >> 16) as u16;
+        if upper != Some(segment) {
+            records.push(Record::ExtendedLinearAddress(segment));
+            upper = Some(segment);
+        }
+
+        // Records must not cross a 64 KiB boundary.
+        let segment_end = ((record_address | 0xFFFF) + 1 - record_address) as usize;
+        let size = HEX_RECORD_SIZE.min(segment_end).min(data.len() - offset);
+
+        records.push(Record::Data {
+            offset: record_address as u16,
+            value: data[offset..offset + size].to_vec(),
+        });
+
+        offset += size;
+    }
+
+    records.push(Record::EndOfFile);
+
+    records
+}
+
+#[cfg(test)]
+mod test {
+    use ihex::Record;
+
+    use super::hex_records;
+
+    #[test]
+    fn hex_records_across_segment_boundary() {
+        let data = (0..24).collect::<Vec<u8>>();
+
+        assert_eq!(
+            hex_records(0x0800_FFF8, &data),
+            vec![
+                Record::ExtendedLinearAddress(0x0800),
+                Record::Data {
+                    offset: 0xFFF8,
+                    value: (0..8).collect(),
+                },
+                Record::ExtendedLinearAddress(0x0801),
+                Record::Data {
+                    offset: 0x0000,
+                    value: (8..24).collect(),
+                },
+                Record::EndOfFile,
+            ]
+        );
+    }
+
+    #[test]
+    fn hex_records_round_trip() {
+        let data = (0..=255).collect::<Vec<u8>>();
+
+        let hex =
+            ihex::create_object_file_representation(&hex_records(0x2000_0010, &data)).unwrap();
+
+        let mut read = Vec::new();
+        let mut upper = 0;
+        for record in ihex::Reader::new(&hex) {
+            match record.unwrap() {
+                Record::ExtendedLinearAddress(address) => upper = (address as u32) << 16,
+                Record::Data { offset, value } => {
+                    assert_eq!(upper + offset as u32, 0x2000_0010 + read.len() as u32);
+                    read.extend(value);
+                }
+                _ => {}
+            }
+        }
+
+        assert_eq!(read, data);
+    }
+}
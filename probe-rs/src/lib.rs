@@ -69,37 +69,56 @@ extern crate serde;
 
 /// All the interface bits for the different architectures.
 pub mod architecture;
+#[warn(missing_docs)]
+#[cfg(feature = "async")]
+pub mod asynch;
 pub mod config;
 
 #[warn(missing_docs)]
 mod core;
 pub mod debug;
+#[warn(missing_docs)]
+#[cfg(feature = "defmt")]
+pub mod defmt;
 mod error;
 #[warn(missing_docs)]
+pub mod events;
+#[warn(missing_docs)]
 pub mod flashing;
 #[warn(missing_docs)]
 mod memory;
 #[warn(missing_docs)]
+#[cfg(feature = "svd")]
+pub mod peripherals;
+#[warn(missing_docs)]
 mod probe;
 #[warn(missing_docs)]
 #[cfg(feature = "rtt")]
 pub mod rtt;
 #[warn(missing_docs)]
+pub mod semihosting;
+#[warn(missing_docs)]
 mod session;
+#[warn(missing_docs)]
+pub mod target_stream;
 
-pub use crate::config::{CoreType, InstructionSet, Target};
+pub use crate::config::{CoreType, InstructionSet, ResetType, Target};
 pub use crate::core::{
-    Architecture, BreakpointCause, Core, CoreInformation, CoreInterface, CoreState, CoreStatus,
-    HaltReason, MemoryMappedRegister, RegisterDescription, RegisterFile, RegisterId, RegisterValue,
-    SpecificCoreState,
+    Architecture, BreakpointCause, Core, CoreDump, CoreDumpError, CoreEvent, CoreInformation,
+    CoreInterface, CoreState, CoreStatus, CoreWatcher, CycleMeasurement, Fault, FaultKind,
+    FaultStatus, HaltReason, MemoryMappedRegister, OfflineCore, RegisterDescription, RegisterFile,
+    RegisterId, RegisterValue, SpecificCoreState, WatchpointCause, WatchpointKind,
 };
 pub use crate::error::Error;
 pub use crate::memory::MemoryInterface;
+pub use crate::probe::remote;
+pub use crate::probe::stlink::bridge as stlink_bridge;
+pub use crate::probe::{register_probe_driver, unregister_probe_driver, ProbeDriver};
 pub use crate::probe::{
     AttachMethod, DebugProbe, DebugProbeError, DebugProbeInfo, DebugProbeSelector, DebugProbeType,
-    Probe, ProbeCreationError, WireProtocol,
+    Probe, ProbeCreationError, ProbeEvent, ProbeWatcher, WireProtocol,
 };
 pub use crate::session::{Permissions, Session};
 
-// TODO: Hide behind feature
+#[cfg(feature = "test")]
 pub use crate::probe::fake_probe::FakeProbe;
@@ -1,4 +1,10 @@
+use std::ops::Range;
+
+use crate::architecture::arm::ArmError;
+use crate::architecture::riscv::communication_interface::RiscvError;
+use crate::architecture::xtensa::communication_interface::XtensaError;
 use crate::error::Error;
+use crate::DebugProbeError;
 
 use anyhow::{anyhow, Result};
 use scroll::Pread;
@@ -24,6 +30,12 @@ pub trait MemoryInterface {
     /// Returns [`Error::MemoryNotAligned`] if this does not hold true.
     fn read_word_32(&mut self, address: u64) -> Result<u32, Error>;
 
+    /// Read a 16bit word of at `address`.
+    ///
+    /// The address where the read should be performed at has to be half-word aligned.
+    /// Returns [`Error::MemoryNotAligned`] if this does not hold true.
+    fn read_word_16(&mut self, address: u64) -> Result<u16, Error>;
+
     /// Read an 8bit word of at `address`.
     fn read_word_8(&mut self, address: u64) -> Result<u8, Error>;
 
@@ -41,6 +53,13 @@ pub trait MemoryInterface {
     /// Returns [`Error::MemoryNotAligned`] if this does not hold true.
     fn read_32(&mut self, address: u64, data: &mut [u32]) -> Result<(), Error>;
 
+    /// Read a block of 16bit words at `address`.
+    ///
+    /// The number of words read is `data.len()`.
+    /// The address where the read should be performed at has to be half-word aligned.
+    /// Returns [`Error::MemoryNotAligned`] if this does not hold true.
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), Error>;
+
     /// Read a block of 8bit words at `address`.
     fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error>;
 
@@ -95,21 +114,58 @@ pub trait MemoryInterface {
     ///
     ///  Generally faster than `read_8`.
     fn read(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error> {
-        if self.supports_native_64bit_access() && address % 8 == 0 && data.len() % 8 == 0 {
-            // Avoid heap allocation and copy if we don't need it.
-            self.read_mem_64bit(address, data)?;
-        } else if address % 4 == 0 && data.len() % 4 == 0 {
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        if address % 4 == 0 && data.len() % 4 == 0 {
             // Avoid heap allocation and copy if we don't need it.
-            self.read_mem_32bit(address, data)?;
+            read_words(self, address, data)?;
         } else {
-            let start_extra_count = (address % 4) as usize;
-            let mut buffer = vec![0u8; (start_extra_count + data.len() + 3) / 4 * 4];
-            self.read_mem_32bit(address - start_extra_count as u64, &mut buffer)?;
-            data.copy_from_slice(&buffer[start_extra_count..start_extra_count + data.len()]);
+            // Read the surrounding words, so that no accesses smaller than a word are needed.
+            let start = address - address % 4;
+            let end = (address + data.len() as u64 + 3) & !0x3;
+
+            let mut buffer = vec![0u8; (end - start) as usize];
+            read_words(self, start, &mut buffer)?;
+
+            let offset = (address - start) as usize;
+            data.copy_from_slice(&buffer[offset..offset + data.len()]);
         }
         Ok(())
     }
 
+    /// Read data from `address`, like [`MemoryInterface::read`], but without failing if
+    /// parts of the range can't be read, e.g. because they are in an unpowered domain
+    /// or a secure region.
+    ///
+    /// Returns the sub-ranges which couldn't be read. Their bytes in `data` are set to zero.
+    /// The faulted sub-ranges are narrowed down to a granularity of 32-bit words, so reading
+    /// large ranges with many faults can be slow. Errors of the probe itself, e.g. a
+    /// disconnected probe, are still returned.
+    fn read_partial(&mut self, address: u64, data: &mut [u8]) -> Result<Vec<Range<u64>>, Error> {
+        let mut faults = Vec::new();
+
+        // Read in blocks first, so that a single fault doesn't require
+        // narrowing down the whole range.
+        let mut offset = 0;
+        while offset < data.len() {
+            let block_address = address + offset as u64;
+            let len = (PARTIAL_READ_BLOCK_SIZE - block_address % PARTIAL_READ_BLOCK_SIZE) as usize;
+            let len = len.min(data.len() - offset);
+
+            read_or_narrow_down(
+                self,
+                block_address,
+                &mut data[offset..offset + len],
+                &mut faults,
+            )?;
+            offset += len;
+        }
+
+        Ok(faults)
+    }
+
     /// Write a 64bit word at `address`.
     ///
     /// The address where the write should be performed at has to be word aligned.
@@ -122,6 +178,12 @@ pub trait MemoryInterface {
     /// Returns [`Error::MemoryNotAligned`] if this does not hold true.
     fn write_word_32(&mut self, address: u64, data: u32) -> Result<(), Error>;
 
+    /// Write a 16bit word at `address`.
+    ///
+    /// The address where the write should be performed at has to be half-word aligned.
+    /// Returns [`Error::MemoryNotAligned`] if this does not hold true.
+    fn write_word_16(&mut self, address: u64, data: u16) -> Result<(), Error>;
+
     /// Write an 8bit word at `address`.
     fn write_word_8(&mut self, address: u64, data: u8) -> Result<(), Error>;
 
@@ -139,6 +201,13 @@ pub trait MemoryInterface {
     /// Returns [`Error::MemoryNotAligned`] if this does not hold true.
     fn write_32(&mut self, address: u64, data: &[u32]) -> Result<(), Error>;
 
+    /// Write a block of 16bit words at `address`.
+    ///
+    /// The number of words written is `data.len()`.
+    /// The address where the write should be performed at has to be half-word aligned.
+    /// Returns [`Error::MemoryNotAligned`] if this does not hold true.
+    fn write_16(&mut self, address: u64, data: &[u16]) -> Result<(), Error>;
+
     /// Write a block of 8bit words at `address`.
     fn write_8(&mut self, address: u64, data: &[u8]) -> Result<(), Error>;
 
@@ -192,42 +261,44 @@ pub trait MemoryInterface {
     /// so should only be used if reading memory locations that don't have side
     /// effects. Generally faster than [`MemoryInterface::write_8`].
     ///
+    /// The block is split into chunks, which are each written with the largest
+    /// aligned access size, so an unaligned block starts and ends with byte or
+    /// half word accesses.
+    ///
     /// If the target does not support 8-bit aligned access, and `address` is not
     /// aligned on a 32-bit boundary, this function will return a [`Error::MemoryNotAligned`] error.
     fn write(&mut self, address: u64, data: &[u8]) -> Result<(), Error> {
-        let len = data.len();
-        let start_extra_count = 4 - (address % 4) as usize;
-        let end_extra_count = (len - start_extra_count) % 4;
-        let inbetween_count = len - start_extra_count - end_extra_count;
-        assert!(start_extra_count < 4);
-        assert!(end_extra_count < 4);
-        assert!(inbetween_count % 4 == 0);
-
-        // If we do not have 32 bit aligned access we first check that we can do 8 bit aligned access on this platform.
-        // If we cannot we throw an error.
-        // If we can we read the first n < 4 bytes up until the word aligned address that comes next.
-        if address % 4 != 0 || len % 4 != 0 {
-            // If we do not support 8 bit transfers we have to bail because we can only do 32 bit word aligned transers.
-            if !self.supports_8bit_transfers()? {
-                return Err(Error::MemoryNotAligned {
-                    address,
-                    alignment: 4,
-                });
-            }
-
-            // We first do an 8 bit write of the first < 4 bytes up until the 4 byte aligned boundary.
-            self.write_8(address, &data[..start_extra_count])?;
+        let max_size = if self.supports_native_64bit_access() {
+            8
+        } else {
+            4
+        };
+        let chunks = split_into_chunks(address, data.len(), max_size);
+
+        // Accesses smaller than a word are only possible if the target supports 8 bit transfers.
+        if chunks.iter().any(|chunk| chunk.size < 4) && !self.supports_8bit_transfers()? {
+            return Err(Error::MemoryNotAligned {
+                address,
+                alignment: 4,
+            });
         }
 
-        let mut buffer = vec![0u32; inbetween_count / 4];
-        for (bytes, value) in data.chunks_exact(4).zip(buffer.iter_mut()) {
-            *value = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
-        }
-        self.write_32(address, &buffer)?;
-
-        // We read the remaining bytes that we did not read yet which is always n < 4.
-        if end_extra_count > 0 {
-            self.write_8(address, &data[..start_extra_count])?;
+        for chunk in chunks {
+            let offset = (chunk.address - address) as usize;
+            let bytes = &data[offset..offset + chunk.len];
+
+            match chunk.size {
+                8 => self.write_mem_64bit(chunk.address, bytes)?,
+                4 => self.write_mem_32bit(chunk.address, bytes)?,
+                2 => {
+                    let half_words = bytes
+                        .chunks_exact(2)
+                        .map(|half_word| u16::from_le_bytes([half_word[0], half_word[1]]))
+                        .collect::<Vec<_>>();
+                    self.write_16(chunk.address, &half_words)?
+                }
+                _ => self.write_8(chunk.address, bytes)?,
+            }
         }
 
         Ok(())
@@ -261,6 +332,10 @@ where
         (*self).read_word_32(address)
     }
 
+    fn read_word_16(&mut self, address: u64) -> Result<u16, Error> {
+        (*self).read_word_16(address)
+    }
+
     fn read_word_8(&mut self, address: u64) -> Result<u8, Error> {
         (*self).read_word_8(address)
     }
@@ -273,6 +348,10 @@ where
         (*self).read_32(address, data)
     }
 
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), Error> {
+        (*self).read_16(address, data)
+    }
+
     fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error> {
         (*self).read_8(address, data)
     }
@@ -289,6 +368,10 @@ where
         (*self).write_word_32(address, data)
     }
 
+    fn write_word_16(&mut self, address: u64, data: u16) -> Result<(), Error> {
+        (*self).write_word_16(address, data)
+    }
+
     fn write_word_8(&mut self, address: u64, data: u8) -> Result<(), Error> {
         (*self).write_word_8(address, data)
     }
@@ -301,6 +384,10 @@ where
         (*self).write_32(address, data)
     }
 
+    fn write_16(&mut self, address: u64, data: &[u16]) -> Result<(), Error> {
+        (*self).write_16(address, data)
+    }
+
     fn write_8(&mut self, address: u64, data: &[u8]) -> Result<(), Error> {
         (*self).write_8(address, data)
     }
@@ -318,6 +405,142 @@ where
     }
 }
 
+/// A part of a block transfer, which is performed with accesses of `size` bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct AccessChunk {
+    /// The start address of the chunk, aligned to `size`.
+    pub address: u64,
+    /// The length of the chunk in bytes, a multiple of `size`.
+    pub len: usize,
+    /// The size of a single access in bytes.
+    pub size: usize,
+}
+
+/// Splits the block of `len` bytes at `address` into chunks, so that each
+/// chunk can be transferred with the largest aligned access size, up to `max_size`.
+///
+/// An unaligned block starts and ends with smaller accesses, e.g. a block from `0x1001`
+/// to `0x100b` is transferred with a byte access, a half word access, two word accesses,
+/// a half word access and a byte access.
+pub(crate) fn split_into_chunks(address: u64, len: usize, max_size: usize) -> Vec<AccessChunk> {
+    debug_assert!(max_size.is_power_of_two());
+
+    let mut chunks: Vec<AccessChunk> = Vec::new();
+    let mut offset = 0;
+
+    while offset < len {
+        let chunk_address = address + offset as u64;
+        let remaining = len - offset;
+
+        let mut size = max_size;
+        while size > 1 && (chunk_address % size as u64 != 0 || size > remaining) {
+            size /= 2;
+        }
+
+        // Only the largest access size is used for more than a single access,
+        // the smaller ones are only needed to reach an aligned address.
+        let chunk_len = if size == max_size {
+            remaining - remaining % size
+        } else {
+            size
+        };
+
+        match chunks.last_mut() {
+            Some(last) if last.size == size => last.len += chunk_len,
+            _ => chunks.push(AccessChunk {
+                address: chunk_address,
+                len: chunk_len,
+                size,
+            }),
+        }
+
+        offset += chunk_len;
+    }
+
+    chunks
+}
+
+/// The size of the blocks in which [`MemoryInterface::read_partial`] reads memory.
+const PARTIAL_READ_BLOCK_SIZE: u64 = 0x400;
+
+/// Reads the block at `address`. If this fails, both halves of the block are read
+/// separately, until the faulted words are found.
+fn read_or_narrow_down<M: MemoryInterface + ?Sized>(
+    memory: &mut M,
+    address: u64,
+    data: &mut [u8],
+    faults: &mut Vec<Range<u64>>,
+) -> Result<(), Error> {
+    let error = match memory.read(address, data) {
+        Ok(()) => return Ok(()),
+        Err(error) if is_probe_failure(&error) => return Err(error),
+        Err(error) => error,
+    };
+
+    // Split at a word boundary, so that the halves don't read the same words.
+    let middle = ((address + data.len() as u64 / 2) & !0x3).max(address - address % 4 + 4);
+    let end = address + data.len() as u64;
+
+    if middle >= end {
+        tracing::debug!("Reading {:#010x}..{:#010x} failed: {}", address, end, error);
+
+        data.fill(0);
+        match faults.last_mut() {
+            Some(last) if last.end == address => last.end = end,
+            _ => faults.push(address..end),
+        }
+
+        return Ok(());
+    }
+
+    let (first, second) = data.split_at_mut((middle - address) as usize);
+    read_or_narrow_down(memory, address, first, faults)?;
+    read_or_narrow_down(memory, middle, second, faults)
+}
+
+/// Returns true if the error is caused by the probe itself, and not by the memory access.
+fn is_probe_failure(error: &Error) -> bool {
+    let probe_error = match error {
+        Error::Probe(error)
+        | Error::Arm(ArmError::Probe(error))
+        | Error::Riscv(RiscvError::DebugProbe(error))
+        | Error::Xtensa(XtensaError::DebugProbe(error)) => error,
+        _ => return false,
+    };
+
+    matches!(
+        probe_error,
+        DebugProbeError::Usb(_)
+            | DebugProbeError::ProbeCouldNotBeCreated(_)
+            | DebugProbeError::NotAttached
+    )
+}
+
+/// Reads the word aligned block at `address`, with 64 bit accesses where possible.
+fn read_words<M: MemoryInterface + ?Sized>(
+    memory: &mut M,
+    address: u64,
+    data: &mut [u8],
+) -> Result<(), Error> {
+    let max_size = if memory.supports_native_64bit_access() {
+        8
+    } else {
+        4
+    };
+
+    for chunk in split_into_chunks(address, data.len(), max_size) {
+        let offset = (chunk.address - address) as usize;
+        let bytes = &mut data[offset..offset + chunk.len];
+
+        match chunk.size {
+            8 => memory.read_mem_64bit(chunk.address, bytes)?,
+            _ => memory.read_mem_32bit(chunk.address, bytes)?,
+        }
+    }
+
+    Ok(())
+}
+
 // Helper functions to validate address space constraints
 
 /// Validate that an input address is valid for 32-bit only systems
@@ -328,3 +551,222 @@ pub(crate) fn valid_32bit_address(address: u64) -> Result<u32, Error> {
 
     Ok(address)
 }
+
+#[cfg(test)]
+mod test {
+    use std::ops::Range;
+
+    use anyhow::anyhow;
+
+    use super::{split_into_chunks, AccessChunk, MemoryInterface};
+    use crate::{DebugProbeError, Error};
+
+    /// Memory, in which every word contains its own address, and reads of
+    /// the words in `faults` fail.
+    struct FaultingMemory {
+        faults: Vec<Range<u64>>,
+        probe_failure: bool,
+    }
+
+    impl FaultingMemory {
+        fn new(faults: Vec<Range<u64>>) -> Self {
+            Self {
+                faults,
+                probe_failure: false,
+            }
+        }
+
+        fn expected(address: u64, len: usize) -> Vec<u8> {
+            (address..address + len as u64)
+                .map(|address| (address & !0x3).to_le_bytes()[(address % 4) as usize])
+                .collect()
+        }
+    }
+
+    impl MemoryInterface for FaultingMemory {
+        fn supports_native_64bit_access(&mut self) -> bool {
+            false
+        }
+
+        fn read_word_64(&mut self, _address: u64) -> Result<u64, Error> {
+            unimplemented!()
+        }
+
+        fn read_word_32(&mut self, _address: u64) -> Result<u32, Error> {
+            unimplemented!()
+        }
+
+        fn read_word_16(&mut self, _address: u64) -> Result<u16, Error> {
+            unimplemented!()
+        }
+
+        fn read_word_8(&mut self, _address: u64) -> Result<u8, Error> {
+            unimplemented!()
+        }
+
+        fn read_64(&mut self, _address: u64, _data: &mut [u64]) -> Result<(), Error> {
+            unimplemented!()
+        }
+
+        fn read_32(&mut self, address: u64, data: &mut [u32]) -> Result<(), Error> {
+            for (i, word) in data.iter_mut().enumerate() {
+                let word_address = address + 4 * i as u64;
+
+                if self.faults.iter().any(|f| f.contains(&word_address)) {
+                    return Err(if self.probe_failure {
+                        Error::Probe(DebugProbeError::NotAttached)
+                    } else {
+                        Error::Other(anyhow!("Fault at {:#010x}", word_address))
+                    });
+                }
+
+                *word = word_address as u32;
+            }
+            Ok(())
+        }
+
+        fn read_16(&mut self, _address: u64, _data: &mut [u16]) -> Result<(), Error> {
+            unimplemented!()
+        }
+
+        fn read_8(&mut self, _address: u64, _data: &mut [u8]) -> Result<(), Error> {
+            unimplemented!()
+        }
+
+        fn write_word_64(&mut self, _address: u64, _data: u64) -> Result<(), Error> {
+            unimplemented!()
+        }
+
+        fn write_word_32(&mut self, _address: u64, _data: u32) -> Result<(), Error> {
+            unimplemented!()
+        }
+
+        fn write_word_16(&mut self, _address: u64, _data: u16) -> Result<(), Error> {
+            unimplemented!()
+        }
+
+        fn write_word_8(&mut self, _address: u64, _data: u8) -> Result<(), Error> {
+            unimplemented!()
+        }
+
+        fn write_64(&mut self, _address: u64, _data: &[u64]) -> Result<(), Error> {
+            unimplemented!()
+        }
+
+        fn write_32(&mut self, _address: u64, _data: &[u32]) -> Result<(), Error> {
+            unimplemented!()
+        }
+
+        fn write_16(&mut self, _address: u64, _data: &[u16]) -> Result<(), Error> {
+            unimplemented!()
+        }
+
+        fn write_8(&mut self, _address: u64, _data: &[u8]) -> Result<(), Error> {
+            unimplemented!()
+        }
+
+        fn supports_8bit_transfers(&self) -> Result<bool, Error> {
+            Ok(false)
+        }
+
+        fn flush(&mut self) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    fn chunk(address: u64, len: usize, size: usize) -> AccessChunk {
+        AccessChunk { address, len, size }
+    }
+
+    #[test]
+    fn split_aligned_block() {
+        assert_eq!(
+            split_into_chunks(0x1000, 0x100, 4),
+            vec![chunk(0x1000, 0x100, 4)]
+        );
+        assert_eq!(split_into_chunks(0x1000, 0, 4), vec![]);
+    }
+
+    #[test]
+    fn split_unaligned_block() {
+        assert_eq!(
+            split_into_chunks(0x1001, 10, 4),
+            vec![
+                chunk(0x1001, 1, 1),
+                chunk(0x1002, 2, 2),
+                chunk(0x1004, 4, 4),
+                chunk(0x1008, 2, 2),
+                chunk(0x100a, 1, 1),
+            ]
+        );
+        assert_eq!(split_into_chunks(0x1003, 1, 4), vec![chunk(0x1003, 1, 1)]);
+    }
+
+    #[test]
+    fn split_block_with_64bit_accesses() {
+        assert_eq!(
+            split_into_chunks(0x1004, 0x20, 8),
+            vec![
+                chunk(0x1004, 4, 4),
+                chunk(0x1008, 0x18, 8),
+                chunk(0x1020, 4, 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn read_partial_without_faults() {
+        let mut memory = FaultingMemory::new(vec![]);
+        let mut data = vec![0xff; 0x20];
+
+        assert_eq!(memory.read_partial(0x1000, &mut data).unwrap(), vec![]);
+        assert_eq!(data, FaultingMemory::expected(0x1000, 0x20));
+    }
+
+    #[test]
+    fn read_partial_unaligned_start() {
+        let mut memory = FaultingMemory::new(vec![0x1000..0x1004, 0x1008..0x100c]);
+        let mut data = vec![0xff; 12];
+
+        assert_eq!(
+            memory.read_partial(0x1002, &mut data).unwrap(),
+            vec![0x1002..0x1004, 0x1008..0x100c]
+        );
+
+        let mut expected = FaultingMemory::expected(0x1002, 12);
+        expected[0..2].fill(0);
+        expected[6..10].fill(0);
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn read_partial_merges_faults_across_blocks() {
+        let mut memory = FaultingMemory::new(vec![0x13f8..0x1408]);
+        let mut data = vec![0xff; 0x800];
+
+        assert_eq!(
+            memory.read_partial(0x1000, &mut data).unwrap(),
+            vec![0x13f8..0x1408]
+        );
+
+        let mut expected = FaultingMemory::expected(0x1000, 0x800);
+        expected[0x3f8..0x408].fill(0);
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn read_partial_returns_probe_failures() {
+        let mut memory = FaultingMemory {
+            faults: vec![0x1010..0x1014],
+            probe_failure: true,
+        };
+        let mut data = vec![0; 0x20];
+
+        assert!(matches!(
+            memory.read_partial(0x1000, &mut data),
+            Err(Error::Probe(DebugProbeError::NotAttached))
+        ));
+    }
+}
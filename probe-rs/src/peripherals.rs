@@ -0,0 +1,617 @@
+//! Access to the peripheral registers of a chip, described by a CMSIS-SVD file
+//!
+//! The SVD file of a chip describes its peripherals, the registers of the peripherals and the
+//! fields of the registers. Once the SVD file is loaded into a [`Session`], the registers can be
+//! accessed by name.
+//!
+//! Reading some registers has side effects, e.g. reading the data register of a UART removes the
+//! received byte from its FIFO. Whether such registers are read is decided by the [`ReadPolicy`].
+//!
+//! The SVD file of a chip is often contained in its CMSIS-Pack, and can be read from there
+//! with `config::cmsis_pack::read_svd`, if the `cmsis-pack` feature is enabled.
+//!
+//! # Examples
+//!
+//! ## Enable a UART
+//!
+//! ```no_run
+//! use probe_rs::{Permissions, Session};
+//!
+//! let mut session = Session::auto_attach("STM32F407VGTx", Permissions::default())?;
+//! session.load_svd("STM32F407.svd")?;
+//!
+//! let mut enable = session.peripheral("USART1")?.register("CR1")?.field("UE")?;
+//! if enable.read()? == 0 {
+//!     enable.write(1)?;
+//! }
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+use svd_parser::svd::{self, Access, ModifiedWriteValues};
+
+use crate::{MemoryInterface, Session};
+
+/// Error type for all errors which occur when loading an SVD file, or accessing
+/// the registers described by it.
+#[derive(Debug, thiserror::Error)]
+pub enum PeripheralError {
+    /// The SVD file could not be read.
+    #[error("Failed to read the SVD file")]
+    Io(#[from] std::io::Error),
+    /// The SVD file is invalid.
+    #[error("Failed to parse the SVD file: {0}")]
+    InvalidSvd(String),
+    /// No SVD file was loaded into the session.
+    #[error("No SVD file is loaded")]
+    NoSvd,
+    /// The SVD file contains no peripheral with this name.
+    #[error("The peripheral {0} does not exist")]
+    UnknownPeripheral(String),
+    /// The peripheral has no register with this name.
+    #[error("The register {register} does not exist in the peripheral {peripheral}")]
+    UnknownRegister {
+        /// The name of the peripheral.
+        peripheral: String,
+        /// The name of the register.
+        register: String,
+    },
+    /// The register has no field with this name.
+    #[error("The field {field} does not exist in the register {register}")]
+    UnknownField {
+        /// The name of the register.
+        register: String,
+        /// The name of the field.
+        field: String,
+    },
+    /// The register or field is write-only.
+    #[error("{0} cannot be read")]
+    NotReadable(String),
+    /// The register or field is read-only.
+    #[error("{0} cannot be written")]
+    NotWritable(String),
+    /// Reading the register has side effects, which the read policy doesn't allow.
+    #[error("Reading {0} has side effects")]
+    ReadSideEffects(String),
+    /// The value is too large for the register or field.
+    #[error("The value {value:#x} does not fit into {name}")]
+    ValueTooLarge {
+        /// The name of the register or field.
+        name: String,
+        /// The value which should have been written.
+        value: u64,
+    },
+    /// The register has a size which cannot be accessed.
+    #[error("The register {register} has an unsupported size of {size} bits")]
+    UnsupportedSize {
+        /// The name of the register.
+        register: String,
+        /// The size of the register, in bits.
+        size: u32,
+    },
+    /// An error occurred while accessing the target.
+    #[error("Error while accessing the register")]
+    Probe(#[from] crate::Error),
+}
+
+/// Decides whether registers are read, if reading them has side effects.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReadPolicy {
+    /// Registers with side effects on read are not read, the access fails with
+    /// [`PeripheralError::ReadSideEffects`] instead.
+    #[default]
+    AvoidSideEffects,
+    /// All readable registers are read.
+    AllowSideEffects,
+}
+
+/// The peripherals of a chip, as described by its SVD file.
+#[derive(Debug, Clone)]
+pub struct Peripherals {
+    peripherals: Vec<PeripheralDescription>,
+    read_policy: ReadPolicy,
+}
+
+impl Peripherals {
+    /// Parse the contents of an SVD file.
+    pub fn from_svd(svd: &str) -> Result<Self, PeripheralError> {
+        let config = svd_parser::Config::default()
+            .expand(true)
+            .expand_properties(true)
+            .ignore_enums(true);
+
+        let device = svd_parser::parse_with_config(svd, &config)
+            .map_err(|e| PeripheralError::InvalidSvd(format!("{e:#}")))?;
+
+        let peripherals = device
+            .peripherals
+            .iter()
+            .map(|peripheral| PeripheralDescription {
+                name: peripheral.name.clone(),
+                description: peripheral.description.clone(),
+                base_address: peripheral.base_address,
+                // The clusters are flattened when expanding the SVD file, the registers
+                // in clusters are named `<cluster>_<register>`.
+                registers: peripheral
+                    .registers()
+                    .map(|register| {
+                        RegisterDescription::new(
+                            register,
+                            peripheral.base_address + register.address_offset as u64,
+                        )
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Ok(Self {
+            peripherals,
+            read_policy: ReadPolicy::default(),
+        })
+    }
+
+    /// All peripherals of the chip.
+    pub fn peripherals(&self) -> &[PeripheralDescription] {
+        &self.peripherals
+    }
+
+    /// The policy for registers with side effects on read.
+    pub fn read_policy(&self) -> ReadPolicy {
+        self.read_policy
+    }
+
+    /// Set the policy for registers with side effects on read.
+    pub fn set_read_policy(&mut self, read_policy: ReadPolicy) {
+        self.read_policy = read_policy;
+    }
+
+    fn find_peripheral(&self, name: &str) -> Option<usize> {
+        self.peripherals
+            .iter()
+            .position(|peripheral| peripheral.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Description of a peripheral.
+#[derive(Debug, Clone)]
+pub struct PeripheralDescription {
+    /// The name of the peripheral.
+    pub name: String,
+    /// The description of the peripheral.
+    pub description: Option<String>,
+    /// The address of the peripheral.
+    pub base_address: u64,
+    /// The registers of the peripheral.
+    pub registers: Vec<RegisterDescription>,
+}
+
+/// Description of a peripheral register.
+#[derive(Debug, Clone)]
+pub struct RegisterDescription {
+    /// The name of the register.
+    pub name: String,
+    /// The description of the register.
+    pub description: Option<String>,
+    /// The address of the register.
+    pub address: u64,
+    /// The size of the register, in bits.
+    pub size: u32,
+    /// The value of the register after a reset.
+    pub reset_value: u64,
+    /// Whether the register can be read.
+    pub readable: bool,
+    /// Whether the register can be written.
+    pub writable: bool,
+    /// Whether reading the register, or any of its fields, has side effects.
+    pub read_side_effects: bool,
+    /// The fields of the register.
+    pub fields: Vec<FieldDescription>,
+    /// Bits which have to be cleared, or set, when writing back a value which was read, so that
+    /// fields with modifying writes like "write one to clear" are left unchanged.
+    neutral_clear: u64,
+    neutral_set: u64,
+}
+
+impl RegisterDescription {
+    fn new(register: &svd::Register, address: u64) -> Self {
+        let access = register.properties.access.unwrap_or(Access::ReadWrite);
+
+        let mut read_side_effects = register.read_action.is_some();
+        let mut neutral_clear = 0;
+        let mut neutral_set = 0;
+
+        let fields = register
+            .fields()
+            .map(|field| {
+                read_side_effects |= field.read_action.is_some();
+
+                let mask = field_mask(field.bit_range.width) << field.bit_range.offset;
+                match field
+                    .modified_write_values
+                    .or(register.modified_write_values)
+                {
+                    Some(
+                        ModifiedWriteValues::OneToClear
+                        | ModifiedWriteValues::OneToSet
+                        | ModifiedWriteValues::OneToToggle,
+                    ) => neutral_clear |= mask,
+                    Some(
+                        ModifiedWriteValues::ZeroToClear
+                        | ModifiedWriteValues::ZeroToSet
+                        | ModifiedWriteValues::ZeroToToggle,
+                    ) => neutral_set |= mask,
+                    _ => {}
+                }
+
+                let field_access = field.access.unwrap_or(access);
+
+                FieldDescription {
+                    name: field.name.clone(),
+                    description: field.description.clone(),
+                    bit_offset: field.bit_range.offset,
+                    bit_width: field.bit_range.width,
+                    readable: field_access.can_read(),
+                    writable: field_access.can_write(),
+                }
+            })
+            .collect();
+
+        Self {
+            name: register.name.clone(),
+            description: register.description.clone(),
+            address,
+            size: register.properties.size.unwrap_or(32),
+            reset_value: register.properties.reset_value.unwrap_or(0),
+            readable: access.can_read(),
+            writable: access.can_write(),
+            read_side_effects,
+            fields,
+            neutral_clear,
+            neutral_set,
+        }
+    }
+}
+
+/// Description of a field of a peripheral register.
+#[derive(Debug, Clone)]
+pub struct FieldDescription {
+    /// The name of the field.
+    pub name: String,
+    /// The description of the field.
+    pub description: Option<String>,
+    /// The position of the least significant bit of the field in the register.
+    pub bit_offset: u32,
+    /// The width of the field, in bits.
+    pub bit_width: u32,
+    /// Whether the field can be read.
+    pub readable: bool,
+    /// Whether the field can be written.
+    pub writable: bool,
+}
+
+fn field_mask(width: u32) -> u64 {
+    u64::MAX.checked_shr(64 - width).unwrap_or(0)
+}
+
+/// A peripheral, whose registers can be accessed.
+///
+/// Created with [`Session::peripheral`].
+pub struct Peripheral<'session> {
+    session: &'session mut Session,
+    core: usize,
+    peripheral: usize,
+}
+
+impl<'session> Peripheral<'session> {
+    pub(crate) fn new(session: &'session mut Session, name: &str) -> Result<Self, PeripheralError> {
+        let peripherals = session.peripherals().ok_or(PeripheralError::NoSvd)?;
+        let peripheral = peripherals
+            .find_peripheral(name)
+            .ok_or_else(|| PeripheralError::UnknownPeripheral(name.to_owned()))?;
+
+        Ok(Self {
+            session,
+            core: 0,
+            peripheral,
+        })
+    }
+
+    /// Access the peripheral through the core with the given index, instead of the first core.
+    pub fn on_core(self, core: usize) -> Self {
+        Self { core, ..self }
+    }
+
+    /// The description of the peripheral.
+    pub fn description(&self) -> &PeripheralDescription {
+        &loaded_peripherals(self.session).peripherals[self.peripheral]
+    }
+
+    /// Get the register with the given name.
+    pub fn register(self, name: &str) -> Result<Register<'session>, PeripheralError> {
+        let peripheral = self.description();
+        let register = peripheral
+            .registers
+            .iter()
+            .position(|register| register.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| PeripheralError::UnknownRegister {
+                peripheral: peripheral.name.clone(),
+                register: name.to_owned(),
+            })?;
+
+        Ok(Register {
+            session: self.session,
+            core: self.core,
+            peripheral: self.peripheral,
+            register,
+        })
+    }
+}
+
+/// A peripheral register.
+///
+/// Created with [`Peripheral::register`].
+pub struct Register<'session> {
+    session: &'session mut Session,
+    core: usize,
+    peripheral: usize,
+    register: usize,
+}
+
+impl<'session> Register<'session> {
+    /// The description of the register.
+    pub fn description(&self) -> &RegisterDescription {
+        &loaded_peripherals(self.session).peripherals[self.peripheral].registers[self.register]
+    }
+
+    /// Get the field with the given name.
+    pub fn field(self, name: &str) -> Result<Field<'session>, PeripheralError> {
+        let register = self.description();
+        let field = register
+            .fields
+            .iter()
+            .position(|field| field.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| PeripheralError::UnknownField {
+                register: register.name.clone(),
+                field: name.to_owned(),
+            })?;
+
+        Ok(Field {
+            register: self,
+            field,
+        })
+    }
+
+    /// Read the value of the register.
+    pub fn read(&mut self) -> Result<u64, PeripheralError> {
+        let register = self.description();
+        if !register.readable {
+            return Err(PeripheralError::NotReadable(register.name.clone()));
+        }
+
+        self.read_unchecked()
+    }
+
+    /// Write the value of the register.
+    pub fn write(&mut self, value: u64) -> Result<(), PeripheralError> {
+        let register = self.description();
+        if !register.writable {
+            return Err(PeripheralError::NotWritable(register.name.clone()));
+        }
+        if value & !field_mask(register.size) != 0 {
+            return Err(PeripheralError::ValueTooLarge {
+                name: register.name.clone(),
+                value,
+            });
+        }
+
+        self.write_unchecked(value)
+    }
+
+    /// Read the register, only checking the read policy.
+    fn read_unchecked(&mut self) -> Result<u64, PeripheralError> {
+        let register = self.description();
+        if register.read_side_effects
+            && loaded_peripherals(self.session).read_policy == ReadPolicy::AvoidSideEffects
+        {
+            return Err(PeripheralError::ReadSideEffects(register.name.clone()));
+        }
+
+        let (name, address, size) = (register.name.clone(), register.address, register.size);
+        let mut core = self.session.core(self.core)?;
+
+        Ok(match size {
+            8 => core.read_word_8(address)? as u64,
+            16 => core.read_word_16(address)? as u64,
+            32 => core.read_word_32(address)? as u64,
+            64 => core.read_word_64(address)?,
+            size => {
+                return Err(PeripheralError::UnsupportedSize {
+                    register: name,
+                    size,
+                })
+            }
+        })
+    }
+
+    fn write_unchecked(&mut self, value: u64) -> Result<(), PeripheralError> {
+        let register = self.description();
+        let (name, address, size) = (register.name.clone(), register.address, register.size);
+        let mut core = self.session.core(self.core)?;
+
+        match size {
+            8 => core.write_word_8(address, value as u8)?,
+            16 => core.write_word_16(address, value as u16)?,
+            32 => core.write_word_32(address, value as u32)?,
+            64 => core.write_word_64(address, value)?,
+            size => {
+                return Err(PeripheralError::UnsupportedSize {
+                    register: name,
+                    size,
+                })
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A field of a peripheral register.
+///
+/// Created with [`Register::field`].
+pub struct Field<'session> {
+    register: Register<'session>,
+    field: usize,
+}
+
+impl<'session> Field<'session> {
+    /// The description of the field.
+    pub fn description(&self) -> &FieldDescription {
+        &self.register.description().fields[self.field]
+    }
+
+    /// Read the value of the field.
+    pub fn read(&mut self) -> Result<u64, PeripheralError> {
+        let field = self.description();
+        if !field.readable {
+            return Err(PeripheralError::NotReadable(field.name.clone()));
+        }
+
+        let (offset, width) = (field.bit_offset, field.bit_width);
+        let value = self.register.read_unchecked()?;
+
+        Ok((value >> offset) & field_mask(width))
+    }
+
+    /// Write the value of the field.
+    ///
+    /// The other fields of the register are preserved, by reading the register first.
+    /// If the register cannot be read, the other fields are set to their reset value.
+    pub fn write(&mut self, value: u64) -> Result<(), PeripheralError> {
+        let field = self.description();
+        if !field.writable {
+            return Err(PeripheralError::NotWritable(field.name.clone()));
+        }
+        if value & !field_mask(field.bit_width) != 0 {
+            return Err(PeripheralError::ValueTooLarge {
+                name: field.name.clone(),
+                value,
+            });
+        }
+
+        let mask = field_mask(field.bit_width) << field.bit_offset;
+        let value = value << field.bit_offset;
+
+        let register = self.register.description();
+        let (neutral_clear, neutral_set) = (register.neutral_clear, register.neutral_set);
+        let current = if register.readable {
+            self.register.read_unchecked()?
+        } else {
+            register.reset_value
+        };
+
+        let current = (current & !neutral_clear) | neutral_set;
+
+        self.register.write_unchecked((current & !mask) | value)
+    }
+}
+
+/// The peripherals of a session, for which a [`Peripheral`] was created.
+fn loaded_peripherals(session: &Session) -> &Peripherals {
+    session
+        .peripherals()
+        .expect("A peripheral can only be created if an SVD file is loaded")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SVD: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<device>
+  <name>TEST</name>
+  <addressUnitBits>8</addressUnitBits>
+  <width>32</width>
+  <size>32</size>
+  <access>read-write</access>
+  <peripherals>
+    <peripheral>
+      <name>TIMER0</name>
+      <baseAddress>0x40008000</baseAddress>
+      <registers>
+        <register>
+          <name>INTEN</name>
+          <addressOffset>0x300</addressOffset>
+          <fields>
+            <field>
+              <name>COMPARE0</name>
+              <bitOffset>16</bitOffset>
+              <bitWidth>1</bitWidth>
+            </field>
+            <field>
+              <name>EVENT</name>
+              <bitOffset>0</bitOffset>
+              <bitWidth>1</bitWidth>
+              <modifiedWriteValues>oneToClear</modifiedWriteValues>
+            </field>
+          </fields>
+        </register>
+        <cluster>
+          <name>CH0</name>
+          <addressOffset>0x100</addressOffset>
+          <register>
+            <name>DATA</name>
+            <addressOffset>0x4</addressOffset>
+            <size>16</size>
+            <access>read-only</access>
+            <readAction>clear</readAction>
+          </register>
+        </cluster>
+      </registers>
+    </peripheral>
+    <peripheral derivedFrom="TIMER0">
+      <name>TIMER1</name>
+      <baseAddress>0x40009000</baseAddress>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
+
+    #[test]
+    fn parse_svd() {
+        let peripherals = Peripherals::from_svd(SVD).unwrap();
+        let timer = &peripherals.peripherals()[peripherals.find_peripheral("timer1").unwrap()];
+
+        assert_eq!(timer.base_address, 0x4000_9000);
+        assert_eq!(timer.registers.len(), 2);
+
+        let inten = &timer.registers[0];
+        assert_eq!(inten.address, 0x4000_9300);
+        assert_eq!(inten.size, 32);
+        assert!(!inten.read_side_effects);
+        assert_eq!(inten.fields[0].bit_offset, 16);
+        assert_eq!(inten.neutral_clear, 0x1);
+
+        let data = &timer.registers[1];
+        assert_eq!(data.name, "CH0_DATA");
+        assert_eq!(data.address, 0x4000_9104);
+        assert_eq!(data.size, 16);
+        assert!(data.readable);
+        assert!(!data.writable);
+        assert!(data.read_side_effects);
+    }
+
+    #[test]
+    fn invalid_svd() {
+        assert!(matches!(
+            Peripherals::from_svd("<device>"),
+            Err(PeripheralError::InvalidSvd(_))
+        ));
+    }
+
+    #[test]
+    fn masks() {
+        assert_eq!(field_mask(0), 0);
+        assert_eq!(field_mask(1), 0x1);
+        assert_eq!(field_mask(12), 0xFFF);
+        assert_eq!(field_mask(64), u64::MAX);
+    }
+}
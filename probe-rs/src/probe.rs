@@ -1,15 +1,26 @@
+#[cfg(any(feature = "ftdi", test))]
+mod cjtag;
 pub(crate) mod cmsisdap;
 pub(crate) mod espusbjtag;
+mod external;
+// Only used by the tests, unless the `test` feature exports the `FakeProbe`.
+#[cfg_attr(not(feature = "test"), allow(dead_code))]
 pub(crate) mod fake_probe;
 #[cfg(feature = "ftdi")]
 pub(crate) mod ftdi;
 pub(crate) mod jlink;
+pub mod remote;
 pub(crate) mod stlink;
+mod watcher;
 
 use self::espusbjtag::list_espjtag_devices;
 use crate::architecture::arm::ArmError;
 use crate::architecture::riscv::communication_interface::RiscvError;
+use crate::architecture::xtensa::communication_interface::{
+    XtensaCommunicationInterface, XtensaError,
+};
 use crate::error::Error;
+use crate::events::{self, Operation};
 use crate::Session;
 use crate::{
     architecture::arm::communication_interface::UninitializedArmProbe,
@@ -20,14 +31,16 @@ use crate::{
         arm::{
             communication_interface::DapProbe,
             sequences::{ArmDebugSequence, DefaultArmSequence},
-            PortType, SwoAccess,
+            PortType, RawDapAccess, SwoAccess,
         },
         riscv::communication_interface::RiscvCommunicationInterface,
     },
     Permissions,
 };
+pub use external::{register_probe_driver, unregister_probe_driver, ProbeDriver};
 use jlink::list_jlink_devices;
 use std::{convert::TryFrom, fmt};
+pub use watcher::{ProbeEvent, ProbeWatcher};
 
 /// Used to log warnings when the measured target voltage is
 /// lower than 1.4V, if at all measureable.
@@ -251,6 +264,8 @@ impl Probe {
 
         list.extend(list_espjtag_devices());
 
+        list.extend(external::list_external_probes());
+
         list
     }
 
@@ -280,15 +295,13 @@ impl Probe {
             Err(DebugProbeError::ProbeCouldNotBeCreated(ProbeCreationError::NotFound)) => {}
             Err(e) => return Err(e),
         };
-        match espusbjtag::EspUsbJtag::new_from_selector(selector) {
+        match espusbjtag::EspUsbJtag::new_from_selector(selector.clone()) {
             Ok(link) => return Ok(Probe::from_specific_probe(link)),
             Err(DebugProbeError::ProbeCouldNotBeCreated(ProbeCreationError::NotFound)) => {}
             Err(e) => return Err(e),
         };
 
-        Err(DebugProbeError::ProbeCouldNotBeCreated(
-            ProbeCreationError::NotFound,
-        ))
+        external::open_external_probe(&selector.into()).map(Probe::from_specific_probe)
     }
 
     /// Get the human readable name for the probe.
@@ -308,7 +321,51 @@ impl Probe {
     ) -> Result<Session, Error> {
         self.attached = true;
 
-        Session::new(self, target.into(), AttachMethod::Normal, permissions)
+        let target = target.into();
+        events::operation(Operation::Attach, || {
+            Session::new(self, target, AttachMethod::Normal, permissions)
+        })
+    }
+
+    /// Attach to the chip without disturbing the running firmware.
+    ///
+    /// Only the debug port is connected, the cores are neither halted nor reset, and the
+    /// debug registers of the cores are not written. This makes it possible to read the memory
+    /// of a device in the field, e.g. for RTT, without affecting it.
+    ///
+    /// Some operations of the session still change the state of the target, e.g. halting a core.
+    /// These have to be done explicitly. When the session is dropped, breakpoints and other
+    /// debug settings are not cleaned up, so it should only be used for inspecting the target.
+    ///
+    /// On Xtensa targets, the memory can only be accessed while a core is halted.
+    pub fn attach_non_intrusive(
+        mut self,
+        target: impl Into<TargetSelector>,
+        permissions: Permissions,
+    ) -> Result<Session, Error> {
+        self.attached = true;
+
+        let target = target.into();
+        events::operation(Operation::Attach, || {
+            Session::new(self, target, AttachMethod::NonIntrusive, permissions)
+        })
+    }
+
+    /// Recover a locked target by erasing it, and consume the probe.
+    ///
+    /// This uses a procedure which is specific to the target family, e.g. the ERASEALL register of
+    /// the nRF CTRL-AP or the mass erase of the Kinetis MDM-AP, and doesn't require access to the cores.
+    /// All data on the target is lost, so the `erase_all` permission is required.
+    ///
+    /// After the recovery, the target can be attached with a new probe.
+    pub fn recover(
+        mut self,
+        target: impl Into<TargetSelector>,
+        permissions: Permissions,
+    ) -> Result<(), Error> {
+        self.attached = true;
+
+        Session::recover(self, target.into(), permissions)
     }
 
     /// Attach to a target without knowing what target you have at hand.
@@ -347,14 +404,17 @@ impl Probe {
         permissions: Permissions,
     ) -> Result<Session, Error> {
         self.attached = true;
-        // The session will de-assert reset after connecting to the debug interface.
-        Session::new(self, target.into(), AttachMethod::UnderReset, permissions).map_err(|e| {
-            if matches!(e, Error::Arm(ArmError::Timeout) | Error::Riscv(RiscvError::Timeout)) {
-                Error::Other(
-                anyhow::anyhow!("Timeout while attaching to target under reset. This can happen if the target is not responding to the reset sequence. Ensure the chip's reset pin is connected, or try attaching without reset."))
-            } else {
-                e
-            }
+        let target = target.into();
+        events::operation(Operation::Attach, || {
+            // The session will de-assert reset after connecting to the debug interface.
+            Session::new(self, target, AttachMethod::UnderReset, permissions).map_err(|e| {
+                if matches!(e, Error::Arm(ArmError::Timeout) | Error::Riscv(RiscvError::Timeout)) {
+                    Error::Other(
+                    anyhow::anyhow!("Timeout while attaching to target under reset. This can happen if the target is not responding to the reset sequence. Ensure the chip's reset pin is connected, or try attaching without reset."))
+                } else {
+                    e
+                }
+            })
         })
     }
 
@@ -417,6 +477,17 @@ impl Probe {
         }
     }
 
+    /// Use compact JTAG (cJTAG, IEEE 1149.7) in the OScan1 format to connect to the target.
+    ///
+    /// This has to be configured before attaching to the target.
+    pub fn set_cjtag(&mut self, enabled: bool) -> Result<(), DebugProbeError> {
+        if !self.attached {
+            self.inner.set_cjtag(enabled)
+        } else {
+            Err(DebugProbeError::Attached)
+        }
+    }
+
     /// Get the currently used maximum speed for the debug protocol in kHz.
     ///
     /// Not all probes report which speed is used, meaning this value is not
@@ -471,6 +542,29 @@ impl Probe {
         }
     }
 
+    /// Check if the probe has an interface to
+    /// debug Xtensa chips.
+    pub fn has_xtensa_interface(&self) -> bool {
+        self.inner.has_xtensa_interface()
+    }
+
+    /// Try to get a [`XtensaCommunicationInterface`], which can
+    /// can be used to communicate with chips using the Xtensa
+    /// architecture.
+    ///
+    /// If an error occurs while trying to connect, the probe is returned.
+    pub fn try_into_xtensa_interface(
+        self,
+    ) -> Result<XtensaCommunicationInterface, (Self, XtensaError)> {
+        if !self.attached {
+            Err((self, DebugProbeError::NotAttached.into()))
+        } else {
+            self.inner
+                .try_get_xtensa_interface()
+                .map_err(|(probe, err)| (Probe::from_attached_probe(probe), err))
+        }
+    }
+
     /// Gets a SWO interface from the debug probe.
     ///
     /// This does not work on all probes.
@@ -498,6 +592,22 @@ impl Probe {
     pub fn get_target_voltage(&mut self) -> Result<Option<f32>, DebugProbeError> {
         self.inner.get_target_voltage()
     }
+
+    /// Set the state of debugger output pins directly.
+    ///
+    /// This can be used for custom unlock and recovery sequences, which require
+    /// direct control of the reset and debug pins. See [`DebugProbe::swj_pins`]
+    /// for the meaning of the arguments.
+    ///
+    /// This does not work on all probes.
+    pub fn swj_pins(
+        &mut self,
+        pin_out: u32,
+        pin_select: u32,
+        pin_wait: u32,
+    ) -> Result<u32, DebugProbeError> {
+        self.inner.swj_pins(pin_out, pin_select, pin_wait)
+    }
 }
 
 /// An abstraction over general debug probe functionality.
@@ -536,6 +646,21 @@ pub trait DebugProbe: Send + fmt::Debug {
     ///
     fn set_speed(&mut self, speed_khz: u32) -> Result<u32, DebugProbeError>;
 
+    /// Enable or disable compact JTAG (cJTAG, IEEE 1149.7) in the OScan1 format.
+    ///
+    /// When enabled, the target is brought online with the OScan1 activation
+    /// sequence during [`DebugProbe::attach`], and all JTAG scans are transferred
+    /// over the two cJTAG signals TCKC and TMSC.
+    ///
+    /// This requires a probe which can drive the JTAG pins directly.
+    fn set_cjtag(&mut self, enabled: bool) -> Result<(), DebugProbeError> {
+        if enabled {
+            Err(DebugProbeError::NotImplemented("cJTAG"))
+        } else {
+            Ok(())
+        }
+    }
+
     /// Attach to the chip.
     ///
     /// This should run all the necessary protocol init routines.
@@ -598,6 +723,22 @@ pub trait DebugProbe: Send + fmt::Debug {
         false
     }
 
+    /// Get the dedicated interface to debug Xtensa chips. Ensure that the
+    /// probe actually supports this by calling [DebugProbe::has_xtensa_interface] first.
+    fn try_get_xtensa_interface(
+        self: Box<Self>,
+    ) -> Result<XtensaCommunicationInterface, (Box<dyn DebugProbe>, XtensaError)> {
+        Err((
+            self.into_probe(),
+            DebugProbeError::InterfaceNotAvailable("Xtensa").into(),
+        ))
+    }
+
+    /// Check if the probe offers an interface to debug Xtensa chips.
+    fn has_xtensa_interface(&self) -> bool {
+        false
+    }
+
     /// Get a SWO interface from the debug probe.
     ///
     /// This is not available on all debug probes.
@@ -622,11 +763,48 @@ pub trait DebugProbe: Send + fmt::Debug {
         None
     }
 
+    /// Try getting low-level JTAG access for the given probe.
+    ///
+    /// This is not available on all probes.
+    fn try_as_jtag_probe(&mut self) -> Option<&mut dyn JTAGAccess> {
+        None
+    }
+
     /// Reads the target voltage in Volts, if possible. Returns `Ok(None)`
     /// if the probe doesn’t support reading the target voltage.
     fn get_target_voltage(&mut self) -> Result<Option<f32>, DebugProbeError> {
         Ok(None)
     }
+
+    /// Set the state of debugger output pins directly, similar to the
+    /// `DAP_SWJ_Pins` command of CMSIS-DAP.
+    ///
+    /// Only the pins selected in `pin_select` are changed. After setting the pins,
+    /// the probe waits up to `pin_wait` microseconds for the selected pins to settle.
+    /// The bits have the same meaning as in [`Pins`](crate::architecture::arm::Pins):
+    ///
+    /// Bit 0: SWCLK/TCK
+    /// Bit 1: SWDIO/TMS
+    /// Bit 2: TDI
+    /// Bit 3: TDO
+    /// Bit 5: nTRST
+    /// Bit 7: nRESET
+    ///
+    /// Returns the state of the pins after the wait, or `0xFFFF_FFFF` if the probe
+    /// can not read back the pin state.
+    ///
+    /// The default implementation uses the DAP interface of the probe, if available.
+    fn swj_pins(
+        &mut self,
+        pin_out: u32,
+        pin_select: u32,
+        pin_wait: u32,
+    ) -> Result<u32, DebugProbeError> {
+        match self.try_as_dap_probe() {
+            Some(dap) => RawDapAccess::swj_pins(dap, pin_out, pin_select, pin_wait),
+            None => Err(DebugProbeError::CommandNotSupportedByProbe("swj_pins")),
+        }
+    }
 }
 
 /// Denotes the type of a given [`DebugProbe`].
@@ -642,6 +820,8 @@ pub enum DebugProbeType {
     JLink,
     /// Built in RISC-V ESP JTAG debug probe
     EspJtag,
+    /// A probe handled by an external [`ProbeDriver`], identified by the name of the driver.
+    External(&'static str),
 }
 
 /// Gathers some information about a debug probe which was found during a scan.
@@ -744,6 +924,16 @@ pub struct DebugProbeSelector {
 impl TryFrom<&str> for DebugProbeSelector {
     type Error = DebugProbeSelectorParseError;
     fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::parse_usb_selector(value).or_else(|err| {
+            // Give external drivers a chance to parse their own selector formats.
+            external::parse_external_selector(value).ok_or(err)
+        })
+    }
+}
+
+impl DebugProbeSelector {
+    /// Parses a selector in the format `VID:PID:<Serial>`.
+    fn parse_usb_selector(value: &str) -> Result<Self, DebugProbeSelectorParseError> {
         let split = value.split(':').collect::<Vec<_>>();
         let mut selector = if split.len() > 1 {
             DebugProbeSelector {
@@ -912,4 +1102,8 @@ pub enum AttachMethod {
     ///
     /// This is required on targets that can remap SWD pins or disable the SWD interface in sleep.
     UnderReset,
+    /// Attach without halting, resetting or otherwise changing the state of the target.
+    ///
+    /// See [`Probe::attach_non_intrusive`] for details.
+    NonIntrusive,
 }
@@ -0,0 +1,203 @@
+//! Compact JTAG (cJTAG, IEEE 1149.7) in the OScan1 scan format.
+//!
+//! cJTAG only uses two signals, TCKC and the bidirectional TMSC. Before the target
+//! accepts OScan1 scans, it has to be brought online with an escape sequence and the
+//! online activation code. After that, each JTAG clock is transferred as three TCKC
+//! cycles, carrying the inverted TDI bit, the TMS bit and the TDO bit.
+//!
+//! This module only builds the pin sequences. Probes which can drive TCKC and TMSC
+//! directly implement [`CJtagPins`] to use it.
+
+use crate::DebugProbeError;
+
+/// The state of the cJTAG signals for one step of a bit-banged sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PinState {
+    /// Level of TCKC.
+    pub tckc: bool,
+    /// Level of TMSC, or `None` if the probe releases TMSC so the target can drive it.
+    pub tmsc: Option<bool>,
+}
+
+impl PinState {
+    const fn drive(tckc: bool, tmsc: bool) -> Self {
+        Self {
+            tckc,
+            tmsc: Some(tmsc),
+        }
+    }
+
+    const fn release(tckc: bool) -> Self {
+        Self { tckc, tmsc: None }
+    }
+}
+
+/// Probes which can drive the cJTAG signals directly.
+pub(crate) trait CJtagPins {
+    /// Applies the pin states in order, and returns the level of TMSC after each step.
+    fn set_pins(&mut self, states: &[PinState]) -> Result<Vec<bool>, DebugProbeError>;
+}
+
+/// Number of TMSC edges of the escape sequence which resets the cJTAG logic of the target.
+const ESCAPE_RESET: usize = 8;
+/// Number of TMSC edges of the escape sequence which starts the selection sequence.
+const ESCAPE_SELECTION: usize = 6;
+
+/// Online activation code (`1100`), extension code (`1000`) and check packet (`0000`),
+/// which switch the target to the OScan1 format. The bits are transmitted LSB first.
+const ONLINE_ACTIVATION: u16 = 0b0000_1000_1100;
+const ONLINE_ACTIVATION_BITS: usize = 12;
+
+/// Number of TCKC cycles which are needed to transfer one JTAG bit in OScan1.
+const OSCAN1_CYCLES_PER_BIT: usize = 3;
+
+/// An escape sequence with `edges` TMSC edges, while TCKC is high.
+fn escape(edges: usize) -> Vec<PinState> {
+    let mut states = vec![PinState::drive(false, true), PinState::drive(true, true)];
+
+    let mut tmsc = true;
+    for _ in 0..edges {
+        tmsc = !tmsc;
+        states.push(PinState::drive(true, tmsc));
+    }
+
+    states.push(PinState::drive(false, tmsc));
+
+    states
+}
+
+/// One TCKC cycle, with TMSC driven to `tmsc`. The target samples TMSC on the rising edge.
+fn drive_cycle(states: &mut Vec<PinState>, tmsc: bool) {
+    states.push(PinState::drive(false, tmsc));
+    states.push(PinState::drive(true, tmsc));
+}
+
+/// One TCKC cycle, with TMSC released. The target drives TMSC after the falling edge.
+fn release_cycle(states: &mut Vec<PinState>) {
+    states.push(PinState::release(false));
+    states.push(PinState::release(true));
+}
+
+/// The sequence which brings the target online, and selects the OScan1 format.
+fn activation_sequence() -> Vec<PinState> {
+    let mut states = escape(ESCAPE_RESET);
+    states.extend(escape(ESCAPE_SELECTION));
+
+    for bit in 0..ONLINE_ACTIVATION_BITS {
+        drive_cycle(&mut states, ONLINE_ACTIVATION & (1 << bit) != 0);
+    }
+
+    states
+}
+
+/// Converts a JTAG sequence into an OScan1 sequence.
+pub(crate) fn oscan1_sequence(tms: &[bool], tdi: &[bool]) -> Vec<PinState> {
+    assert_eq!(tms.len(), tdi.len());
+
+    let mut states = Vec::with_capacity(tms.len() * OSCAN1_CYCLES_PER_BIT * 2);
+
+    for (&tms, &tdi) in tms.iter().zip(tdi) {
+        drive_cycle(&mut states, !tdi);
+        drive_cycle(&mut states, tms);
+        release_cycle(&mut states);
+    }
+
+    states
+}
+
+/// Extracts the TDO bits from the TMSC samples of an OScan1 sequence.
+pub(crate) fn oscan1_tdo(samples: &[bool]) -> Vec<bool> {
+    samples
+        .chunks_exact(OSCAN1_CYCLES_PER_BIT * 2)
+        .map(|bit| bit[OSCAN1_CYCLES_PER_BIT * 2 - 1])
+        .collect()
+}
+
+/// Brings the target online in the OScan1 format.
+pub(crate) fn activate(pins: &mut impl CJtagPins) -> Result<(), DebugProbeError> {
+    tracing::debug!("Activating cJTAG OScan1 format");
+
+    pins.set_pins(&activation_sequence())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{activate, escape, oscan1_sequence, oscan1_tdo, CJtagPins, PinState};
+    use crate::DebugProbeError;
+
+    /// Records the pin states, TMSC is read back as low.
+    #[derive(Default)]
+    struct MockPins {
+        states: Vec<PinState>,
+    }
+
+    impl CJtagPins for MockPins {
+        fn set_pins(&mut self, states: &[PinState]) -> Result<Vec<bool>, DebugProbeError> {
+            self.states.extend_from_slice(states);
+
+            Ok(vec![false; states.len()])
+        }
+    }
+
+    /// Counts the TMSC edges while TCKC is high.
+    fn escape_edges(states: &[PinState]) -> usize {
+        states
+            .windows(2)
+            .filter(|w| w[0].tckc && w[1].tckc && w[0].tmsc != w[1].tmsc)
+            .count()
+    }
+
+    /// The TMSC levels at the rising edges of TCKC, outside of escape sequences.
+    fn sampled_bits(states: &[PinState]) -> Vec<Option<bool>> {
+        states
+            .windows(2)
+            .filter(|w| !w[0].tckc && w[1].tckc)
+            .map(|w| w[1].tmsc)
+            .collect()
+    }
+
+    #[test]
+    fn escape_sequences() {
+        assert_eq!(escape_edges(&escape(8)), 8);
+        assert_eq!(escape_edges(&escape(6)), 6);
+
+        // TCKC is low at the end of an escape sequence.
+        assert!(!escape(6).last().unwrap().tckc);
+    }
+
+    #[test]
+    fn online_activation() {
+        let mut pins = MockPins::default();
+        activate(&mut pins).unwrap();
+
+        assert_eq!(escape_edges(&pins.states), 8 + 6);
+
+        let bits = sampled_bits(&pins.states);
+
+        // The first rising edges belong to the two escape sequences.
+        let activation: Vec<_> = bits[2..].iter().map(|b| b.unwrap() as u8).collect();
+        assert_eq!(activation, [0, 0, 1, 1, 0, 0, 0, 1, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn oscan1_bits() {
+        let sequence = oscan1_sequence(&[true, false], &[false, true]);
+
+        assert_eq!(
+            sampled_bits(&sequence),
+            [Some(true), Some(true), None, Some(false), Some(false), None]
+        );
+    }
+
+    #[test]
+    fn oscan1_tdo_bits() {
+        let samples = [
+            false, false, false, false, false, true, //
+            true, true, true, true, true, false,
+        ];
+
+        assert_eq!(oscan1_tdo(&samples), [true, false]);
+    }
+}
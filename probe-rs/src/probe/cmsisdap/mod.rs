@@ -10,6 +10,7 @@ use crate::{
         ArmCommunicationInterface, ArmError, DapError, DpAddress, Pins, PortType, RawDapAccess,
         Register, SwoAccess, SwoConfig, SwoMode,
     },
+    events,
     probe::{
         cmsisdap::commands::{
             general::info::{CapabilitiesCommand, PacketCountCommand, SWOTraceBufferSizeCommand},
@@ -300,6 +301,7 @@ impl CmsisDap {
                         }
 
                         tracing::trace!("draining {:?} and retries left {:?}", count, retry);
+                        events::dap_retry("CMSIS-DAP", "FAULT response", 5 - retry);
                         batch.drain(0..count);
                         continue;
                     }
@@ -870,6 +872,14 @@ impl SwoAccess for CmsisDap {
                 config.baud(),
                 baud
             );
+            events::probe_warning(
+                "CMSIS-DAP",
+                format!(
+                    "Target SWO baud rate not met: requested {}, got {}",
+                    config.baud(),
+                    baud
+                ),
+            );
         }
 
         self.start_swo_capture()?;
@@ -13,6 +13,7 @@ use crate::{
             SwoAccess,
         },
         riscv::communication_interface::{RiscvCommunicationInterface, RiscvError},
+        xtensa::communication_interface::{XtensaCommunicationInterface, XtensaError},
     },
     probe::jlink::bits_to_byte,
     DebugProbe, DebugProbeError, DebugProbeSelector, WireProtocol,
@@ -448,10 +449,28 @@ impl DebugProbe for EspUsbJtag {
         true
     }
 
+    fn try_get_xtensa_interface(
+        self: Box<Self>,
+    ) -> Result<XtensaCommunicationInterface, (Box<dyn DebugProbe>, XtensaError)> {
+        // The ESP32-S3 uses the same built-in USB JTAG interface.
+        match XtensaCommunicationInterface::new(self) {
+            Ok(interface) => Ok(interface),
+            Err((probe, err)) => Err((probe.into_probe(), err)),
+        }
+    }
+
+    fn has_xtensa_interface(&self) -> bool {
+        true
+    }
+
     fn into_probe(self: Box<Self>) -> Box<dyn DebugProbe> {
         self
     }
 
+    fn try_as_jtag_probe(&mut self) -> Option<&mut dyn JTAGAccess> {
+        Some(self)
+    }
+
     fn try_as_dap_probe(&mut self) -> Option<&mut dyn DapProbe> {
         // This is not a DAP capable probe.
         None
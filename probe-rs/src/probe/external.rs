@@ -0,0 +1,174 @@
+//! Registration of probe drivers which are not part of probe-rs.
+//!
+//! Crates which implement a driver for a vendor-specific debug probe can register
+//! a [`ProbeDriver`] using [`register_probe_driver`]. Registered drivers are used
+//! by [`Probe::list_all`](super::Probe::list_all) and [`Probe::open`](super::Probe::open),
+//! after all built-in drivers.
+
+use std::sync::{Arc, Mutex};
+
+use once_cell::sync::Lazy;
+
+use super::{DebugProbe, DebugProbeError, DebugProbeInfo, DebugProbeSelector, ProbeCreationError};
+
+type DriverList = Vec<Arc<dyn ProbeDriver>>;
+
+static DRIVERS: Lazy<Arc<Mutex<DriverList>>> = Lazy::new(|| Arc::new(Mutex::new(Vec::new())));
+
+/// A driver for a kind of debug probe, which can be registered at runtime.
+///
+/// Probes found by the driver should use [`DebugProbeType::External`](super::DebugProbeType::External)
+/// with the name of the driver as their probe type.
+pub trait ProbeDriver: Send + Sync {
+    /// The name of the driver.
+    fn name(&self) -> &'static str;
+
+    /// Returns all probes connected which can be used with this driver.
+    fn list_probes(&self) -> Vec<DebugProbeInfo>;
+
+    /// Opens the probe matching `selector`.
+    ///
+    /// Has to return [`ProbeCreationError::NotFound`] if no probe handled by this driver
+    /// matches the selector, so that the remaining drivers are tried.
+    fn open(&self, selector: &DebugProbeSelector) -> Result<Box<dyn DebugProbe>, DebugProbeError>;
+
+    /// Parses a driver specific selector string.
+    ///
+    /// This is only used if the string is not a valid `VID:PID:<Serial>` selector. The default
+    /// implementation does not accept any other selectors.
+    fn parse_selector(&self, _selector: &str) -> Option<DebugProbeSelector> {
+        None
+    }
+}
+
+/// Registers an additional probe driver.
+///
+/// Drivers with the same name as an already registered driver replace the existing driver.
+pub fn register_probe_driver(driver: impl ProbeDriver + 'static) {
+    let mut drivers = DRIVERS.lock().unwrap();
+
+    drivers.retain(|registered| registered.name() != driver.name());
+    drivers.push(Arc::new(driver));
+}
+
+/// Removes the driver with the given name, if it is registered.
+pub fn unregister_probe_driver(name: &str) {
+    DRIVERS
+        .lock()
+        .unwrap()
+        .retain(|registered| registered.name() != name);
+}
+
+/// Returns a snapshot of the registered drivers.
+///
+/// The lock is not held while the drivers are called, so drivers can
+/// use the registration functions themselves.
+fn drivers() -> DriverList {
+    DRIVERS.lock().unwrap().clone()
+}
+
+/// Lists the probes found by all registered drivers.
+pub(crate) fn list_external_probes() -> Vec<DebugProbeInfo> {
+    drivers()
+        .iter()
+        .flat_map(|driver| driver.list_probes())
+        .collect()
+}
+
+/// Opens the probe matching `selector` with the first registered driver which finds it.
+pub(crate) fn open_external_probe(
+    selector: &DebugProbeSelector,
+) -> Result<Box<dyn DebugProbe>, DebugProbeError> {
+    for driver in drivers() {
+        match driver.open(selector) {
+            Ok(probe) => return Ok(probe),
+            Err(DebugProbeError::ProbeCouldNotBeCreated(ProbeCreationError::NotFound)) => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    Err(DebugProbeError::ProbeCouldNotBeCreated(
+        ProbeCreationError::NotFound,
+    ))
+}
+
+/// Parses `selector` with the first registered driver which accepts it.
+pub(crate) fn parse_external_selector(selector: &str) -> Option<DebugProbeSelector> {
+    drivers()
+        .iter()
+        .find_map(|driver| driver.parse_selector(selector))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        list_external_probes, open_external_probe, register_probe_driver, unregister_probe_driver,
+        ProbeDriver,
+    };
+    use crate::{
+        probe::fake_probe::FakeProbe, DebugProbe, DebugProbeError, DebugProbeInfo,
+        DebugProbeSelector, DebugProbeType, ProbeCreationError,
+    };
+
+    const VID: u16 = 0xfeed;
+    const PID: u16 = 0xbeef;
+
+    struct TestDriver;
+
+    impl ProbeDriver for TestDriver {
+        fn name(&self) -> &'static str {
+            "test-driver"
+        }
+
+        fn list_probes(&self) -> Vec<DebugProbeInfo> {
+            vec![DebugProbeInfo::new(
+                "External test probe",
+                VID,
+                PID,
+                Some("1".to_string()),
+                DebugProbeType::External(self.name()),
+                None,
+            )]
+        }
+
+        fn open(
+            &self,
+            selector: &DebugProbeSelector,
+        ) -> Result<Box<dyn DebugProbe>, DebugProbeError> {
+            if selector.vendor_id == VID && selector.product_id == PID {
+                Ok(Box::new(FakeProbe::new()))
+            } else {
+                Err(ProbeCreationError::NotFound.into())
+            }
+        }
+
+        fn parse_selector(&self, selector: &str) -> Option<DebugProbeSelector> {
+            let serial = selector.strip_prefix("test-driver/")?;
+
+            Some(DebugProbeSelector {
+                vendor_id: VID,
+                product_id: PID,
+                serial_number: Some(serial.to_string()),
+            })
+        }
+    }
+
+    #[test]
+    fn registered_drivers_are_used() {
+        register_probe_driver(TestDriver);
+
+        assert!(list_external_probes()
+            .iter()
+            .any(|info| info.probe_type == DebugProbeType::External("test-driver")));
+
+        let selector: DebugProbeSelector = "test-driver/1".parse().unwrap();
+        assert_eq!(selector.serial_number.as_deref(), Some("1"));
+
+        let probe = open_external_probe(&selector).unwrap();
+        assert_eq!(probe.get_name(), "Mock probe for testing");
+
+        unregister_probe_driver("test-driver");
+
+        assert!("test-driver/1".parse::<DebugProbeSelector>().is_err());
+    }
+}
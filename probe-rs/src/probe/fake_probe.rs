@@ -4,7 +4,7 @@ use crate::{
     architecture::arm::{
         ap::{memory_ap::mock::MockMemoryAp, AccessPort, MemoryAp},
         communication_interface::{
-            ArmDebugState, Initialized, SwdSequence, Uninitialized, UninitializedArmProbe,
+            ArmDebugState, DapProbe, Initialized, SwdSequence, Uninitialized, UninitializedArmProbe,
         },
         dp::DebugPortError,
         memory::adi_v5_memory_interface::{ADIMemoryInterface, ArmProbe},
@@ -16,15 +16,51 @@ use crate::{
 };
 
 /// This is a mock probe which can be used for mocking things in tests or for dry runs.
+///
+/// The fake probe does not require any hardware. Applications built on probe-rs can use it
+/// to test their logic, by scripting the responses of the probe:
+///
+/// - Raw DAP register accesses are answered by the handlers set with
+///   [`FakeProbe::set_dap_register_read_handler`] and [`FakeProbe::set_dap_register_write_handler`].
+/// - Raw AP register accesses through the ARM interface are answered by the handlers set with
+///   [`FakeProbe::set_ap_register_read_handler`] and [`FakeProbe::set_ap_register_write_handler`].
+/// - Memory accesses through the ARM interface are served from a small memory filled with a test
+///   pattern at address 0, and from the regions added with [`FakeProbe::add_memory_region`].
+///
+/// This is only available with the `test` feature enabled.
+///
+/// ## Example
+///
+/// ```
+/// use probe_rs::{FakeProbe, MemoryInterface, Permissions};
+///
+/// let mut fake_probe = FakeProbe::new();
+/// fake_probe.add_memory_region(0x2000_0000, vec![0x12, 0x34, 0x56, 0x78]);
+///
+/// let mut session = fake_probe
+///     .into_probe()
+///     .attach("nrf51822_xxAC", Permissions::default())?;
+///
+/// let mut core = session.core(0)?;
+/// assert_eq!(core.read_word_32(0x2000_0000)?, 0x7856_3412);
+/// # Ok::<(), probe_rs::Error>(())
+/// ```
 #[allow(clippy::type_complexity)]
 pub struct FakeProbe {
     protocol: WireProtocol,
     speed: u32,
 
+    memory_regions: Vec<(u32, Vec<u8>)>,
+
     dap_register_read_handler: Option<Box<dyn Fn(PortType, u8) -> Result<u32, ArmError> + Send>>,
 
     dap_register_write_handler:
         Option<Box<dyn Fn(PortType, u8, u32) -> Result<(), ArmError> + Send>>,
+
+    ap_register_read_handler: Option<Box<dyn Fn(ApAddress, u8) -> Result<u32, ArmError> + Send>>,
+
+    ap_register_write_handler:
+        Option<Box<dyn Fn(ApAddress, u8, u32) -> Result<(), ArmError> + Send>>,
 }
 
 impl Debug for FakeProbe {
@@ -43,8 +79,13 @@ impl FakeProbe {
             protocol: WireProtocol::Swd,
             speed: 1000,
 
+            memory_regions: Vec::new(),
+
             dap_register_read_handler: None,
             dap_register_write_handler: None,
+
+            ap_register_read_handler: None,
+            ap_register_write_handler: None,
         }
     }
 
@@ -66,6 +107,32 @@ impl FakeProbe {
         self.dap_register_write_handler = Some(handler);
     }
 
+    /// This sets the read handler for AP register reads through the ARM interface.
+    /// Can be used to hook into the read.
+    pub fn set_ap_register_read_handler(
+        &mut self,
+        handler: Box<dyn Fn(ApAddress, u8) -> Result<u32, ArmError> + Send>,
+    ) {
+        self.ap_register_read_handler = Some(handler);
+    }
+
+    /// This sets the write handler for AP register writes through the ARM interface.
+    /// Can be used to hook into the write.
+    pub fn set_ap_register_write_handler(
+        &mut self,
+        handler: Box<dyn Fn(ApAddress, u8, u32) -> Result<(), ArmError> + Send>,
+    ) {
+        self.ap_register_write_handler = Some(handler);
+    }
+
+    /// Adds a region of memory starting at `address`, which can be read and written through the
+    /// memory interface of the fake probe.
+    ///
+    /// Regions added later take precedence over regions added earlier, if they overlap.
+    pub fn add_memory_region(&mut self, address: u32, data: Vec<u8>) {
+        self.memory_regions.insert(0, (address, data));
+    }
+
     /// Makes a generic probe out of the [`FakeProbe`]
     pub fn into_probe(self) -> Probe {
         Probe::from_specific_probe(Box::new(self))
@@ -128,11 +195,11 @@ impl DebugProbe for FakeProbe {
     }
 
     fn target_reset_assert(&mut self) -> Result<(), DebugProbeError> {
-        unimplemented!()
+        Ok(())
     }
 
     fn target_reset_deassert(&mut self) -> Result<(), DebugProbeError> {
-        unimplemented!()
+        Ok(())
     }
 
     fn into_probe(self: Box<Self>) -> Box<dyn DebugProbe> {
@@ -149,8 +216,14 @@ impl DebugProbe for FakeProbe {
     fn has_arm_interface(&self) -> bool {
         true
     }
+
+    fn try_as_dap_probe(&mut self) -> Option<&mut dyn DapProbe> {
+        Some(self)
+    }
 }
 
+impl DapProbe for FakeProbe {}
+
 impl RawDapAccess for FakeProbe {
     fn select_dp(&mut self, _dp: DpAddress) -> Result<(), ArmError> {
         Err(DebugPortError::Unsupported(
@@ -161,20 +234,25 @@ impl RawDapAccess for FakeProbe {
 
     /// Reads the DAP register on the specified port and address
     fn raw_read_register(&mut self, port: PortType, addr: u8) -> Result<u32, ArmError> {
-        let handler = self.dap_register_read_handler.as_ref().unwrap();
+        let handler = self.dap_register_read_handler.as_ref().ok_or(
+            DebugProbeError::CommandNotSupportedByProbe("raw_read_register"),
+        )?;
 
         handler(port, addr)
     }
 
     /// Writes a value to the DAP register on the specified port and address
     fn raw_write_register(&mut self, port: PortType, addr: u8, value: u32) -> Result<(), ArmError> {
-        let handler = self.dap_register_write_handler.as_ref().unwrap();
+        let handler = self.dap_register_write_handler.as_ref().ok_or(
+            DebugProbeError::CommandNotSupportedByProbe("raw_write_register"),
+        )?;
 
         handler(port, addr, value)
     }
 
     fn swj_sequence(&mut self, _bit_len: u8, _bits: u64) -> Result<(), DebugProbeError> {
-        todo!()
+        // There is no wire, so the sequence is simply dropped.
+        Ok(())
     }
 
     fn swj_pins(
@@ -183,7 +261,7 @@ impl RawDapAccess for FakeProbe {
         _pin_select: u32,
         _pin_wait: u32,
     ) -> Result<u32, DebugProbeError> {
-        todo!()
+        Err(DebugProbeError::CommandNotSupportedByProbe("swj_pins"))
     }
 
     fn into_probe(self: Box<Self>) -> Box<dyn DebugProbe> {
@@ -209,7 +287,8 @@ impl FakeArmInterface<Uninitialized> {
         let state = Uninitialized {
             use_overrun_detect: false,
         };
-        let memory_ap = MockMemoryAp::with_pattern();
+        let mut memory_ap = MockMemoryAp::with_pattern();
+        memory_ap.regions = probe.memory_regions.clone();
 
         Self {
             probe,
@@ -233,11 +312,10 @@ impl FakeArmInterface<Initialized> {
         interface: FakeArmInterface<Uninitialized>,
         sequence: Arc<dyn ArmDebugSequence>,
     ) -> Self {
-        let memory_ap = MockMemoryAp::with_pattern();
         FakeArmInterface::<Initialized> {
             probe: interface.probe,
             _state: Initialized::new(sequence, false),
-            memory_ap,
+            memory_ap: interface.memory_ap,
         }
     }
 }
@@ -255,7 +333,7 @@ impl<S: ArmDebugState> SwdSequence for FakeArmInterface<S> {
         pin_select: u32,
         pin_wait: u32,
     ) -> Result<u32, DebugProbeError> {
-        let value = self.probe.swj_pins(pin_out, pin_select, pin_wait)?;
+        let value = RawDapAccess::swj_pins(&mut *self.probe, pin_out, pin_select, pin_wait)?;
 
         Ok(value)
     }
@@ -288,7 +366,9 @@ impl ArmProbeInterface for FakeArmInterface<Initialized> {
     ) -> Result<Box<dyn ArmProbe + '_>, ArmError> {
         let ap_information = MemoryApInformation {
             address: access_port.ap_address(),
+            idr: Default::default(),
             supports_only_32bit_data_size: false,
+            supports_packed_transfers: false,
             debug_base_address: 0xf000_0000,
             supports_hnonsec: false,
             has_large_data_extension: false,
@@ -356,42 +436,58 @@ impl DapAccess for FakeArmInterface<Initialized> {
         todo!()
     }
 
-    fn read_raw_ap_register(&mut self, _ap: ApAddress, _address: u8) -> Result<u32, ArmError> {
-        todo!()
+    fn read_raw_ap_register(&mut self, ap: ApAddress, address: u8) -> Result<u32, ArmError> {
+        let handler = self.probe.ap_register_read_handler.as_ref().ok_or(
+            DebugProbeError::CommandNotSupportedByProbe("read_raw_ap_register"),
+        )?;
+
+        handler(ap, address)
     }
 
     fn read_raw_ap_register_repeated(
         &mut self,
-        _ap: ApAddress,
-        _address: u8,
-        _values: &mut [u32],
+        ap: ApAddress,
+        address: u8,
+        values: &mut [u32],
     ) -> Result<(), ArmError> {
-        todo!()
+        for value in values {
+            *value = self.read_raw_ap_register(ap, address)?;
+        }
+
+        Ok(())
     }
 
     fn write_raw_ap_register(
         &mut self,
-        _ap: ApAddress,
-        _address: u8,
-        _value: u32,
+        ap: ApAddress,
+        address: u8,
+        value: u32,
     ) -> Result<(), ArmError> {
-        todo!()
+        let handler = self.probe.ap_register_write_handler.as_ref().ok_or(
+            DebugProbeError::CommandNotSupportedByProbe("write_raw_ap_register"),
+        )?;
+
+        handler(ap, address, value)
     }
 
     fn write_raw_ap_register_repeated(
         &mut self,
-        _ap: ApAddress,
-        _address: u8,
-        _values: &[u32],
+        ap: ApAddress,
+        address: u8,
+        values: &[u32],
     ) -> Result<(), ArmError> {
-        todo!()
+        for value in values {
+            self.write_raw_ap_register(ap, address, *value)?;
+        }
+
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::FakeProbe;
-    use crate::Permissions;
+    use crate::{MemoryInterface, Permissions};
 
     #[test]
     fn create_session_with_fake_probe() {
@@ -403,4 +499,50 @@ mod test {
             .attach("nrf51822_xxAC", Permissions::default())
             .unwrap();
     }
+
+    #[test]
+    fn memory_regions_are_accessible() {
+        let mut fake_probe = FakeProbe::new();
+        fake_probe.add_memory_region(0x2000_0000, vec![0; 16]);
+
+        let mut session = fake_probe
+            .into_probe()
+            .attach("nrf51822_xxAC", Permissions::default())
+            .unwrap();
+
+        let mut core = session.core(0).unwrap();
+
+        core.write_word_32(0x2000_0004, 0xdead_beef).unwrap();
+        assert_eq!(core.read_word_32(0x2000_0004).unwrap(), 0xdead_beef);
+        assert_eq!(core.read_word_8(0x2000_0007).unwrap(), 0xde);
+
+        // Memory outside of the regions reads as the test pattern.
+        assert_eq!(core.read_word_8(0x10).unwrap(), 0x11);
+    }
+
+    #[test]
+    fn non_intrusive_attach_does_not_write_debug_registers() {
+        // The system control space, with a breakpoint recorded in DFSR.
+        let mut scs = vec![0; 0x1000];
+        scs[0xd30] = 0x2;
+
+        let mut fake_probe = FakeProbe::new();
+        fake_probe.add_memory_region(0xe000_e000, scs.clone());
+
+        let mut session = fake_probe
+            .into_probe()
+            .attach_non_intrusive("nrf51822_xxAC", Permissions::default())
+            .unwrap();
+
+        let mut core = session.core(0).unwrap();
+
+        let mut words = vec![0; scs.len() / 4];
+        core.read_32(0xe000_e000, &mut words).unwrap();
+        let content = words
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect::<Vec<_>>();
+
+        assert_eq!(content, scs);
+    }
 }
@@ -1,8 +1,12 @@
 use crate::architecture::riscv::communication_interface::RiscvError;
+use crate::architecture::xtensa::communication_interface::{
+    XtensaCommunicationInterface, XtensaError,
+};
 use crate::architecture::{
     arm::communication_interface::UninitializedArmProbe,
     riscv::communication_interface::RiscvCommunicationInterface,
 };
+use crate::probe::cjtag::{self, CJtagPins, PinState};
 use crate::probe::{JTAGAccess, ProbeCreationError};
 use crate::{
     DebugProbe, DebugProbeError, DebugProbeInfo, DebugProbeSelector, DebugProbeType, WireProtocol,
@@ -37,10 +41,19 @@ pub(super) struct ChainParams {
     irlen: usize,
 }
 
+/// Pins of the low byte of the MPSSE port.
+mod pins {
+    pub const TCK: u8 = 1 << 0;
+    pub const TDI: u8 = 1 << 1;
+    pub const TMS: u8 = 1 << 3;
+}
+
 #[derive(Debug)]
 pub struct JtagAdapter {
     device: ftdi::Device,
     chain_params: Option<ChainParams>,
+    /// Use cJTAG in the OScan1 format. TCK is used as TCKC, and TMS as TMSC.
+    oscan1: bool,
 }
 
 impl JtagAdapter {
@@ -52,6 +65,7 @@ impl JtagAdapter {
         Ok(Self {
             device,
             chain_params: None,
+            oscan1: false,
         })
     }
 
@@ -101,10 +115,78 @@ impl JtagAdapter {
         Ok(result)
     }
 
+    /// Drives the pins directly, and returns the level of TMS after each step.
+    fn bitbang(&mut self, states: &[PinState]) -> io::Result<Vec<bool>> {
+        // Limit the number of steps per USB transfer, so the replies arrive within
+        // the timeout of `read_response`.
+        const CHUNK_SIZE: usize = 256;
+
+        let mut samples = Vec::with_capacity(states.len());
+
+        for chunk in states.chunks(CHUNK_SIZE) {
+            let mut command = Vec::with_capacity(chunk.len() * 4 + 1);
+
+            for state in chunk {
+                let mut output = 0;
+                let mut direction = pins::TCK | pins::TDI;
+
+                if state.tckc {
+                    output |= pins::TCK;
+                }
+
+                // Release TMS so the target can drive TMSC.
+                if let Some(tmsc) = state.tmsc {
+                    direction |= pins::TMS;
+                    if tmsc {
+                        output |= pins::TMS;
+                    }
+                }
+
+                // Set the pins, then read them back.
+                command.extend_from_slice(&[0x80, output, direction, 0x81]);
+            }
+
+            // Send Immediate
+            command.push(0x87);
+
+            self.device.write_all(&command)?;
+
+            let reply = self.read_response(chunk.len())?;
+            samples.extend(reply.iter().map(|pins| pins & pins::TMS != 0));
+        }
+
+        Ok(samples)
+    }
+
+    /// Performs a JTAG sequence with cJTAG, and returns the TDO bits.
+    fn oscan1_io(&mut self, tms: &[bool], tdi: &[bool]) -> io::Result<Vec<bool>> {
+        let samples = self.bitbang(&cjtag::oscan1_sequence(tms, tdi))?;
+
+        Ok(cjtag::oscan1_tdo(&samples))
+    }
+
+    /// The data bits of an MPSSE shift, with TMS set for the last bit to leave the shift state.
+    fn oscan1_shift(&mut self, data: &[u8], bits: usize) -> io::Result<Vec<bool>> {
+        let tdi = &BitSlice::<u8, Lsb0>::from_slice(data)[..bits];
+        let tdi: Vec<bool> = tdi.iter().by_vals().collect();
+
+        let mut tms = vec![false; bits];
+        tms[bits - 1] = true;
+
+        self.oscan1_io(&tms, &tdi)
+    }
+
     fn shift_tms(&mut self, mut data: &[u8], mut bits: usize) -> io::Result<()> {
         assert!(bits > 0);
         assert!((bits + 7) / 8 <= data.len());
 
+        if self.oscan1 {
+            let tms = &BitSlice::<u8, Lsb0>::from_slice(data)[..bits];
+            let tms: Vec<bool> = tms.iter().by_vals().collect();
+
+            return self.oscan1_io(&tms, &vec![false; bits]).map(|_| ());
+        }
+
         let mut command = vec![];
 
         while bits > 0 {
@@ -124,6 +206,10 @@ impl JtagAdapter {
         assert!(bits > 0);
         assert!((bits + 7) / 8 <= data.len());
 
+        if self.oscan1 {
+            return self.oscan1_shift(data, bits).map(|_| ());
+        }
+
         let mut command = vec![];
 
         let full_bytes = (bits - 1) / 8;
@@ -159,6 +245,12 @@ impl JtagAdapter {
         assert!(bits > 0);
         assert!((bits + 7) / 8 <= data.len());
 
+        if self.oscan1 {
+            let tdo = self.oscan1_shift(data, bits)?;
+
+            return Ok(tdo.into_iter().collect::<BitVec<u8, Lsb0>>().into_vec());
+        }
+
         let mut command = vec![];
 
         let full_bytes = (bits - 1) / 8;
@@ -422,6 +514,13 @@ pub struct FtdiProbe {
     idle_cycles: u8,
 }
 
+impl CJtagPins for JtagAdapter {
+    fn set_pins(&mut self, states: &[PinState]) -> Result<Vec<bool>, DebugProbeError> {
+        self.bitbang(states)
+            .map_err(|e| DebugProbeError::ProbeSpecific(Box::new(e)))
+    }
+}
+
 impl DebugProbe for FtdiProbe {
     fn new_from_selector(
         selector: impl Into<DebugProbeSelector>,
@@ -468,6 +567,11 @@ impl DebugProbe for FtdiProbe {
         Ok(speed_khz)
     }
 
+    fn set_cjtag(&mut self, enabled: bool) -> Result<(), DebugProbeError> {
+        self.adapter.oscan1 = enabled;
+        Ok(())
+    }
+
     fn attach(&mut self) -> Result<(), DebugProbeError> {
         tracing::debug!("attaching...");
 
@@ -475,6 +579,10 @@ impl DebugProbe for FtdiProbe {
             .attach()
             .map_err(|e| DebugProbeError::ProbeSpecific(Box::new(e)))?;
 
+        if self.adapter.oscan1 {
+            cjtag::activate(&mut self.adapter)?;
+        }
+
         let taps = self
             .adapter
             .scan()
@@ -551,10 +659,27 @@ impl DebugProbe for FtdiProbe {
         true
     }
 
+    fn try_get_xtensa_interface(
+        self: Box<Self>,
+    ) -> Result<XtensaCommunicationInterface, (Box<dyn DebugProbe>, XtensaError)> {
+        match XtensaCommunicationInterface::new(self) {
+            Ok(interface) => Ok(interface),
+            Err((probe, err)) => Err((probe.into_probe(), err)),
+        }
+    }
+
+    fn has_xtensa_interface(&self) -> bool {
+        true
+    }
+
     fn into_probe(self: Box<Self>) -> Box<dyn DebugProbe> {
         self
     }
 
+    fn try_as_jtag_probe(&mut self) -> Option<&mut dyn JTAGAccess> {
+        Some(self)
+    }
+
     fn try_get_arm_interface<'probe>(
         self: Box<Self>,
     ) -> Result<Box<dyn UninitializedArmProbe + 'probe>, (Box<dyn DebugProbe>, DebugProbeError)>
@@ -623,6 +748,22 @@ impl JTAGAccess for FtdiProbe {
         let mut index_offset = 0;
         let mut results = Vec::<CommandResult>::new();
 
+        // The batched commands are encoded as MPSSE shifts, which can't be used with cJTAG.
+        if self.adapter.oscan1 {
+            for write in writes {
+                match self
+                    .write_register(write.address, &write.data, write.len)
+                    .map_err(crate::Error::Probe)
+                    .and_then(|response| (write.transform)(response))
+                {
+                    Ok(res) => results.push(res),
+                    Err(e) => return Err(BatchExecutionError::new(e, results.clone())),
+                }
+            }
+
+            return Ok(results);
+        }
+
         let chain_params = self.adapter.get_chain_params().map_err(|e| {
             BatchExecutionError::new(
                 crate::Error::Probe(DebugProbeError::ProbeSpecific(Box::new(e))),
@@ -1,10 +1,11 @@
-use std::{iter, time::Duration};
+use std::iter;
 
 use crate::{
     architecture::arm::{
         dp::{Abort, Ctrl, RdBuff, DPIDR},
-        ArmError, DapError, DpAddress, Pins, PortType, RawDapAccess, Register,
+        ArmError, DapError, DpAddress, PortType, RawDapAccess, Register,
     },
+    events,
     probe::JTAGAccess,
     DebugProbe, DebugProbeError,
 };
@@ -966,6 +967,7 @@ impl<Probe: DebugProbe + RawProtocolIo + JTAGAccess + 'static> RawDapAccess for
                         "DAP WAIT, (read), retries remaining {}.",
                         dap_wait_retries - retry
                     );
+                    events::dap_retry(self.get_name(), "WAIT response", retry + 1);
 
                     // Because we use overrun detection, we now have to clear the overrun error
                     let mut abort = Abort(0);
@@ -1160,6 +1162,7 @@ impl<Probe: DebugProbe + RawProtocolIo + JTAGAccess + 'static> RawDapAccess for
                         "DAP WAIT, (write), retries remaining {}.",
                         dap_wait_retries - retry
                     );
+                    events::dap_retry(self.get_name(), "WAIT response", retry + 1);
 
                     let mut abort = Abort(0);
 
@@ -1325,30 +1328,7 @@ impl<Probe: DebugProbe + RawProtocolIo + JTAGAccess + 'static> RawDapAccess for
         pin_select: u32,
         pin_wait: u32,
     ) -> Result<u32, DebugProbeError> {
-        let mut nreset = Pins(0);
-        nreset.set_nreset(true);
-        let nreset_mask = nreset.0 as u32;
-
-        // If only the reset pin is selected we perform the reset.
-        // If something else is selected return an error as this is not supported on J-Links.
-        if pin_select == nreset_mask {
-            if Pins(pin_out as u8).nreset() {
-                self.target_reset_deassert()?;
-            } else {
-                self.target_reset_assert()?;
-            }
-
-            // Normally this would be the timeout we pass to the probe to settle the pins.
-            // The J-Link is not capable of this, so we just wait for this time on the host
-            // and assume it has settled until then.
-            std::thread::sleep(Duration::from_micros(pin_wait as u64));
-
-            // We signal that we cannot read the pin state.
-            Ok(0xFFFF_FFFF)
-        } else {
-            // This is not supported for J-Links, unfortunately.
-            Err(DebugProbeError::CommandNotSupportedByProbe("swj_pins"))
-        }
+        DebugProbe::swj_pins(self, pin_out, pin_select, pin_wait)
     }
 
     fn into_probe(self: Box<Self>) -> Box<dyn DebugProbe> {
@@ -1720,7 +1700,7 @@ mod test {
         }
 
         fn get_name(&self) -> &str {
-            todo!()
+            "Mock J-Link"
         }
 
         fn speed_khz(&self) -> u32 {
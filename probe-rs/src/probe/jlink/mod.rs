@@ -6,8 +6,11 @@ use std::convert::{TryFrom, TryInto};
 use std::iter;
 use std::time::{Duration, Instant};
 
-use crate::architecture::arm::{ArmError, RawDapAccess};
+use crate::architecture::arm::{ArmError, Pins, RawDapAccess};
 use crate::architecture::riscv::communication_interface::RiscvError;
+use crate::architecture::xtensa::communication_interface::{
+    XtensaCommunicationInterface, XtensaError,
+};
 use crate::{
     architecture::{
         arm::{
@@ -16,6 +19,7 @@ use crate::{
         },
         riscv::communication_interface::RiscvCommunicationInterface,
     },
+    events,
     probe::{
         DebugProbe, DebugProbeError, DebugProbeInfo, DebugProbeType, JTAGAccess, WireProtocol,
     },
@@ -28,6 +32,9 @@ mod arm;
 
 const SWO_BUFFER_SIZE: u16 = 128;
 
+/// Pins which can be controlled using [`DebugProbe::swj_pins`]: SWDIO/TMS, TDI, nTRST and nRESET.
+const SWJ_PINS_SUPPORTED: u32 = 0b1010_0110;
+
 #[derive(Debug)]
 pub(crate) struct JLink {
     handle: JayLink,
@@ -349,7 +356,11 @@ impl DebugProbe for JLink {
                 super::ProbeCreationError::NotFound,
             ));
         } else if jlinks.len() > 1 {
-            tracing::warn!("More than one matching J-Link was found. Opening the first one.")
+            tracing::warn!("More than one matching J-Link was found. Opening the first one.");
+            events::probe_warning(
+                "J-Link",
+                "More than one matching J-Link was found. Opening the first one.",
+            );
         }
         let jlink_handle = jlinks.pop().unwrap()?;
 
@@ -472,6 +483,10 @@ impl DebugProbe for JLink {
 
         if actual_protocol != configured_protocol {
             tracing::warn!("Protocol {} is configured, but not supported by the probe. Using protocol {} instead", configured_protocol, actual_protocol);
+            events::probe_warning(
+                "J-Link",
+                format!("Protocol {configured_protocol} is not supported, using {actual_protocol}"),
+            );
         }
 
         tracing::debug!("Attaching with protocol '{}'", actual_protocol);
@@ -501,6 +516,10 @@ impl DebugProbe for JLink {
                 "J-Link: Target voltage (VTref) is {:2.2} V. Is your target device powered?",
                 target_voltage
             );
+            events::probe_warning(
+                "J-Link",
+                format!("Target voltage (VTref) is {target_voltage:2.2} V"),
+            );
         } else {
             tracing::info!("J-Link: Target voltage: {:2.2} V", target_voltage);
         }
@@ -565,6 +584,45 @@ impl DebugProbe for JLink {
         Ok(())
     }
 
+    fn swj_pins(
+        &mut self,
+        pin_out: u32,
+        pin_select: u32,
+        pin_wait: u32,
+    ) -> Result<u32, DebugProbeError> {
+        let select = Pins(pin_select as u8);
+        let out = Pins(pin_out as u8);
+
+        // The J-Link can not drive the clock pin directly, and TDO is an input.
+        if pin_select & !SWJ_PINS_SUPPORTED != 0 {
+            return Err(DebugProbeError::CommandNotSupportedByProbe("swj_pins"));
+        }
+
+        if select.swdio_tms() {
+            self.handle.set_tms(out.swdio_tms())?;
+        }
+
+        if select.tdi() {
+            self.handle.set_tdi(out.tdi())?;
+        }
+
+        if select.ntrst() {
+            self.handle.set_trst(out.ntrst())?;
+        }
+
+        if select.nreset() {
+            self.handle.set_reset(out.nreset())?;
+        }
+
+        // Normally this would be the timeout we pass to the probe to settle the pins.
+        // The J-Link is not capable of this, so we just wait for this time on the host
+        // and assume it has settled until then.
+        std::thread::sleep(Duration::from_micros(pin_wait as u64));
+
+        // We signal that we cannot read the pin state.
+        Ok(0xFFFF_FFFF)
+    }
+
     fn try_get_riscv_interface(
         self: Box<Self>,
     ) -> Result<RiscvCommunicationInterface, (Box<dyn DebugProbe>, RiscvError)> {
@@ -597,10 +655,34 @@ impl DebugProbe for JLink {
         self.supported_protocols.contains(&WireProtocol::Jtag)
     }
 
+    fn try_get_xtensa_interface(
+        self: Box<Self>,
+    ) -> Result<XtensaCommunicationInterface, (Box<dyn DebugProbe>, XtensaError)> {
+        if self.supported_protocols.contains(&WireProtocol::Jtag) {
+            match XtensaCommunicationInterface::new(self) {
+                Ok(interface) => Ok(interface),
+                Err((probe, err)) => Err((probe.into_probe(), err)),
+            }
+        } else {
+            Err((
+                RawDapAccess::into_probe(self),
+                DebugProbeError::InterfaceNotAvailable("JTAG").into(),
+            ))
+        }
+    }
+
+    fn has_xtensa_interface(&self) -> bool {
+        self.supported_protocols.contains(&WireProtocol::Jtag)
+    }
+
     fn into_probe(self: Box<Self>) -> Box<dyn DebugProbe> {
         self
     }
 
+    fn try_as_jtag_probe(&mut self) -> Option<&mut dyn JTAGAccess> {
+        Some(self)
+    }
+
     fn try_as_dap_probe(&mut self) -> Option<&mut dyn DapProbe> {
         Some(self)
     }
@@ -804,3 +886,20 @@ impl TryFrom<jaylink::Interface> for WireProtocol {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::SWJ_PINS_SUPPORTED;
+    use crate::architecture::arm::Pins;
+
+    #[test]
+    fn supported_swj_pins() {
+        let mut pins = Pins(0);
+        pins.set_swdio_tms(true);
+        pins.set_tdi(true);
+        pins.set_ntrst(true);
+        pins.set_nreset(true);
+
+        assert_eq!(pins.0 as u32, SWJ_PINS_SUPPORTED);
+    }
+}
@@ -0,0 +1,389 @@
+use std::net::{TcpStream, ToSocketAddrs};
+
+use super::{
+    protocol::{ProbeDescription, Request, Response, PROTOCOL_VERSION},
+    read_message, write_message, RemoteProbeError, MAX_BLOCK_WORDS,
+};
+use crate::{
+    architecture::{
+        arm::{
+            communication_interface::{DapProbe, UninitializedArmProbe},
+            ArmCommunicationInterface, ArmError, DpAddress, PortType, RawDapAccess,
+        },
+        riscv::communication_interface::{RiscvCommunicationInterface, RiscvError},
+        xtensa::communication_interface::{XtensaCommunicationInterface, XtensaError},
+    },
+    probe::JTAGAccess,
+    CoreStatus, DebugProbe, DebugProbeError, DebugProbeSelector, Probe, ProbeCreationError,
+    WireProtocol,
+};
+
+impl From<RemoteProbeError> for DebugProbeError {
+    fn from(error: RemoteProbeError) -> Self {
+        DebugProbeError::ProbeSpecific(Box::new(error))
+    }
+}
+
+/// A debug probe which is attached to another machine, and
+/// accessed through a [`RemoteProbeServer`](super::RemoteProbeServer).
+#[derive(Debug)]
+pub struct RemoteProbe {
+    stream: TcpStream,
+    description: ProbeDescription,
+}
+
+impl RemoteProbe {
+    /// Connects to the remote probe server at `address`, and returns the served probe.
+    pub fn connect(address: impl ToSocketAddrs) -> Result<Probe, DebugProbeError> {
+        Ok(Probe::new(Self::connect_raw(address)?))
+    }
+
+    /// Connects to the remote probe server at `address`.
+    pub(crate) fn connect_raw(address: impl ToSocketAddrs) -> Result<Self, DebugProbeError> {
+        let stream = TcpStream::connect(address).map_err(|e| {
+            DebugProbeError::ProbeCouldNotBeCreated(ProbeCreationError::ProbeSpecific(Box::new(
+                RemoteProbeError::Io(e),
+            )))
+        })?;
+
+        stream.set_nodelay(true).map_err(RemoteProbeError::Io)?;
+
+        let mut probe = RemoteProbe {
+            stream,
+            description: ProbeDescription {
+                version: PROTOCOL_VERSION,
+                name: String::new(),
+                speed_khz: 0,
+                protocol: None,
+                has_arm_interface: false,
+                has_riscv_interface: false,
+                has_xtensa_interface: false,
+                has_dap_access: false,
+                has_jtag_access: false,
+                jtag_idle_cycles: 0,
+            },
+        };
+
+        match probe.transact(Request::Hello {
+            version: PROTOCOL_VERSION,
+        })? {
+            Response::Hello(description) => probe.description = description,
+            _ => return Err(RemoteProbeError::UnexpectedResponse.into()),
+        }
+
+        tracing::info!(
+            "Connected to remote probe '{}'",
+            probe.description.name.as_str()
+        );
+
+        Ok(probe)
+    }
+
+    /// Sends a request to the server, and waits for the response.
+    ///
+    /// Errors reported by the server are converted into [`RemoteProbeError::Remote`].
+    fn transact(&mut self, request: Request) -> Result<Response, RemoteProbeError> {
+        write_message(&mut self.stream, &request)?;
+
+        match read_message(&mut self.stream)? {
+            Some(Response::Error(message)) => Err(RemoteProbeError::Remote(message)),
+            Some(response) => Ok(response),
+            None => Err(RemoteProbeError::Io(
+                std::io::ErrorKind::UnexpectedEof.into(),
+            )),
+        }
+    }
+
+    fn transact_ok(&mut self, request: Request) -> Result<(), RemoteProbeError> {
+        match self.transact(request)? {
+            Response::Ok => Ok(()),
+            _ => Err(RemoteProbeError::UnexpectedResponse),
+        }
+    }
+
+    fn transact_value(&mut self, request: Request) -> Result<u32, RemoteProbeError> {
+        match self.transact(request)? {
+            Response::Value(value) => Ok(value),
+            _ => Err(RemoteProbeError::UnexpectedResponse),
+        }
+    }
+
+    fn transact_bytes(&mut self, request: Request) -> Result<Vec<u8>, RemoteProbeError> {
+        match self.transact(request)? {
+            Response::Bytes(bytes) => Ok(bytes),
+            _ => Err(RemoteProbeError::UnexpectedResponse),
+        }
+    }
+}
+
+impl DebugProbe for RemoteProbe {
+    fn new_from_selector(
+        _selector: impl Into<DebugProbeSelector>,
+    ) -> Result<Box<Self>, DebugProbeError>
+    where
+        Self: Sized,
+    {
+        // Remote probes are not discovered via USB, use `RemoteProbe::connect` instead.
+        Err(DebugProbeError::ProbeCouldNotBeCreated(
+            ProbeCreationError::NotFound,
+        ))
+    }
+
+    fn get_name(&self) -> &str {
+        &self.description.name
+    }
+
+    fn speed_khz(&self) -> u32 {
+        self.description.speed_khz
+    }
+
+    fn set_speed(&mut self, speed_khz: u32) -> Result<u32, DebugProbeError> {
+        let actual_speed = self.transact_value(Request::SetSpeed(speed_khz))?;
+
+        self.description.speed_khz = actual_speed;
+
+        Ok(actual_speed)
+    }
+
+    fn attach(&mut self) -> Result<(), DebugProbeError> {
+        Ok(self.transact_ok(Request::Attach)?)
+    }
+
+    fn detach(&mut self) -> Result<(), crate::Error> {
+        self.transact_ok(Request::Detach)
+            .map_err(|e| crate::Error::Probe(e.into()))
+    }
+
+    fn target_reset(&mut self) -> Result<(), DebugProbeError> {
+        Ok(self.transact_ok(Request::TargetReset)?)
+    }
+
+    fn target_reset_assert(&mut self) -> Result<(), DebugProbeError> {
+        Ok(self.transact_ok(Request::TargetResetAssert)?)
+    }
+
+    fn target_reset_deassert(&mut self) -> Result<(), DebugProbeError> {
+        Ok(self.transact_ok(Request::TargetResetDeassert)?)
+    }
+
+    fn select_protocol(&mut self, protocol: WireProtocol) -> Result<(), DebugProbeError> {
+        match self.transact(Request::SelectProtocol(protocol))? {
+            Response::Protocol(protocol) => {
+                self.description.protocol = protocol;
+                Ok(())
+            }
+            _ => Err(RemoteProbeError::UnexpectedResponse.into()),
+        }
+    }
+
+    fn active_protocol(&self) -> Option<WireProtocol> {
+        self.description.protocol
+    }
+
+    fn has_arm_interface(&self) -> bool {
+        self.description.has_arm_interface && self.description.has_dap_access
+    }
+
+    fn try_get_arm_interface<'probe>(
+        self: Box<Self>,
+    ) -> Result<Box<dyn UninitializedArmProbe + 'probe>, (Box<dyn DebugProbe>, DebugProbeError)>
+    {
+        if self.has_arm_interface() {
+            Ok(Box::new(ArmCommunicationInterface::new(self, false)))
+        } else {
+            Err((self, DebugProbeError::InterfaceNotAvailable("ARM")))
+        }
+    }
+
+    fn has_riscv_interface(&self) -> bool {
+        self.description.has_riscv_interface && self.description.has_jtag_access
+    }
+
+    fn try_get_riscv_interface(
+        self: Box<Self>,
+    ) -> Result<RiscvCommunicationInterface, (Box<dyn DebugProbe>, RiscvError)> {
+        if self.has_riscv_interface() {
+            RiscvCommunicationInterface::new(self).map_err(|(probe, err)| (probe.into_probe(), err))
+        } else {
+            Err((self, DebugProbeError::InterfaceNotAvailable("RISCV").into()))
+        }
+    }
+
+    fn has_xtensa_interface(&self) -> bool {
+        self.description.has_xtensa_interface && self.description.has_jtag_access
+    }
+
+    fn try_get_xtensa_interface(
+        self: Box<Self>,
+    ) -> Result<XtensaCommunicationInterface, (Box<dyn DebugProbe>, XtensaError)> {
+        if self.has_xtensa_interface() {
+            XtensaCommunicationInterface::new(self)
+                .map_err(|(probe, err)| (probe.into_probe(), err))
+        } else {
+            Err((
+                self,
+                DebugProbeError::InterfaceNotAvailable("Xtensa").into(),
+            ))
+        }
+    }
+
+    fn into_probe(self: Box<Self>) -> Box<dyn DebugProbe> {
+        self
+    }
+
+    fn try_as_dap_probe(&mut self) -> Option<&mut dyn DapProbe> {
+        if self.description.has_dap_access {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn try_as_jtag_probe(&mut self) -> Option<&mut dyn JTAGAccess> {
+        if self.description.has_jtag_access {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn get_target_voltage(&mut self) -> Result<Option<f32>, DebugProbeError> {
+        match self.transact(Request::TargetVoltage)? {
+            Response::Voltage(voltage) => Ok(voltage),
+            _ => Err(RemoteProbeError::UnexpectedResponse.into()),
+        }
+    }
+}
+
+impl RawDapAccess for RemoteProbe {
+    fn select_dp(&mut self, dp: DpAddress) -> Result<(), ArmError> {
+        Ok(self
+            .transact_ok(Request::SelectDp(dp))
+            .map_err(DebugProbeError::from)?)
+    }
+
+    fn raw_read_register(&mut self, port: PortType, addr: u8) -> Result<u32, ArmError> {
+        Ok(self
+            .transact_value(Request::RawReadRegister { port, addr })
+            .map_err(DebugProbeError::from)?)
+    }
+
+    fn raw_read_block(
+        &mut self,
+        port: PortType,
+        addr: u8,
+        values: &mut [u32],
+    ) -> Result<(), ArmError> {
+        // Large reads are split up, so that each response fits into a message.
+        for chunk in values.chunks_mut(MAX_BLOCK_WORDS as usize) {
+            let request = Request::RawReadBlock {
+                port,
+                addr,
+                len: chunk.len() as u32,
+            };
+
+            match self.transact(request).map_err(DebugProbeError::from)? {
+                Response::Values(data) if data.len() == chunk.len() => {
+                    chunk.copy_from_slice(&data);
+                }
+                _ => return Err(DebugProbeError::from(RemoteProbeError::UnexpectedResponse).into()),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn raw_write_register(&mut self, port: PortType, addr: u8, value: u32) -> Result<(), ArmError> {
+        Ok(self
+            .transact_ok(Request::RawWriteRegister { port, addr, value })
+            .map_err(DebugProbeError::from)?)
+    }
+
+    fn raw_write_block(
+        &mut self,
+        port: PortType,
+        addr: u8,
+        values: &[u32],
+    ) -> Result<(), ArmError> {
+        // Large writes are split up, so that each request fits into a message.
+        for chunk in values.chunks(MAX_BLOCK_WORDS as usize) {
+            self.transact_ok(Request::RawWriteBlock {
+                port,
+                addr,
+                values: chunk.to_vec(),
+            })
+            .map_err(DebugProbeError::from)?;
+        }
+
+        Ok(())
+    }
+
+    fn raw_flush(&mut self) -> Result<(), ArmError> {
+        Ok(self
+            .transact_ok(Request::RawFlush)
+            .map_err(DebugProbeError::from)?)
+    }
+
+    fn swj_sequence(&mut self, bit_len: u8, bits: u64) -> Result<(), DebugProbeError> {
+        Ok(self.transact_ok(Request::SwjSequence { bit_len, bits })?)
+    }
+
+    fn swj_pins(
+        &mut self,
+        pin_out: u32,
+        pin_select: u32,
+        pin_wait: u32,
+    ) -> Result<u32, DebugProbeError> {
+        Ok(self.transact_value(Request::SwjPins {
+            pin_out,
+            pin_select,
+            pin_wait,
+        })?)
+    }
+
+    fn into_probe(self: Box<Self>) -> Box<dyn DebugProbe> {
+        self
+    }
+
+    fn core_status_notification(&mut self, state: CoreStatus) -> Result<(), DebugProbeError> {
+        Ok(self.transact_ok(Request::CoreStatusNotification(state))?)
+    }
+}
+
+impl DapProbe for RemoteProbe {}
+
+impl JTAGAccess for RemoteProbe {
+    fn read_register(&mut self, address: u32, len: u32) -> Result<Vec<u8>, DebugProbeError> {
+        Ok(self.transact_bytes(Request::JtagReadRegister { address, len })?)
+    }
+
+    fn set_idle_cycles(&mut self, idle_cycles: u8) {
+        match self.transact_ok(Request::JtagSetIdleCycles(idle_cycles)) {
+            Ok(()) => self.description.jtag_idle_cycles = idle_cycles,
+            Err(e) => tracing::warn!("Failed to set idle cycles on remote probe: {}", e),
+        }
+    }
+
+    fn get_idle_cycles(&self) -> u8 {
+        self.description.jtag_idle_cycles
+    }
+
+    fn set_ir_len(&mut self, len: u32) {
+        if let Err(e) = self.transact_ok(Request::JtagSetIrLen(len)) {
+            tracing::warn!("Failed to set IR length on remote probe: {}", e);
+        }
+    }
+
+    fn write_register(
+        &mut self,
+        address: u32,
+        data: &[u8],
+        len: u32,
+    ) -> Result<Vec<u8>, DebugProbeError> {
+        Ok(self.transact_bytes(Request::JtagWriteRegister {
+            address,
+            data: data.to_vec(),
+            len,
+        })?)
+    }
+}
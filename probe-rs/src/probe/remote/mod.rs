@@ -0,0 +1,293 @@
+//! Access to debug probes attached to another machine.
+//!
+//! A [`RemoteProbeServer`] takes ownership of a locally attached [`Probe`](crate::Probe)
+//! and serves the low-level probe operations over TCP. A [`RemoteProbe`] connects to such a server
+//! and implements [`DebugProbe`](crate::DebugProbe), so it can be used like any
+//! other probe, for example to attach to a target connected to a lab machine or a CI rig.
+//!
+//! The server forwards the [`DebugProbe`](crate::DebugProbe) operations as well as raw DAP
+//! ([`RawDapAccess`](crate::architecture::arm::RawDapAccess)) and JTAG
+//! register transactions. All higher level logic, such as
+//! debug sequences and flash algorithms, runs on the client.
+//!
+//! The operations of any probe can also be recorded to a file using [`record`], and
+//! replayed later without any hardware using [`replay`].
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use probe_rs::{Probe, Permissions};
+//! use probe_rs::remote::{RemoteProbe, RemoteProbeServer};
+//!
+//! // On the machine the probe is attached to:
+//! let probes = Probe::list_all();
+//! let probe = probes[0].open()?;
+//! let listener = std::net::TcpListener::bind("0.0.0.0:4242")?;
+//! std::thread::spawn(move || RemoteProbeServer::new(probe).serve(&listener));
+//!
+//! // On the developer machine:
+//! let probe = RemoteProbe::connect("lab-machine:4242")?;
+//! let session = probe.attach("nrf52840_xxAA", Permissions::default())?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+mod client;
+mod protocol;
+mod recording;
+mod server;
+
+pub use client::RemoteProbe;
+pub use recording::{record, replay};
+pub use server::RemoteProbeServer;
+
+use std::io::{Read, Write};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+/// The largest message which is accepted on the wire.
+///
+/// This protects both sides against allocating huge buffers when
+/// the connection is out of sync.
+const MAX_MESSAGE_SIZE: u32 = 16 * 1024 * 1024;
+
+/// The space reserved in a message for everything but the values of a block transfer,
+/// like the length prefix and the other fields of the request or response.
+const BLOCK_ENVELOPE_SIZE: u32 = 64;
+
+/// The largest number of words of a single block transfer, so that the request
+/// or response fits into a message.
+///
+/// Larger transfers are split up by the client.
+const MAX_BLOCK_WORDS: u32 = (MAX_MESSAGE_SIZE - BLOCK_ENVELOPE_SIZE) / 4;
+
+/// An error which occurred while communicating with a remote probe.
+#[derive(thiserror::Error, Debug)]
+pub enum RemoteProbeError {
+    /// Reading from or writing to the connection failed.
+    #[error("Failed to communicate with the remote probe server")]
+    Io(#[from] std::io::Error),
+    /// A message could not be encoded or decoded.
+    #[error("Failed to encode or decode a message")]
+    Encoding(#[from] bincode::Error),
+    /// A message exceeded the maximum message size.
+    #[error("Message of {0} bytes exceeds the maximum message size")]
+    MessageTooLarge(u32),
+    /// The server returned a response which does not match the request.
+    #[error("Received an unexpected response from the remote probe server")]
+    UnexpectedResponse,
+    /// The operation failed on the server.
+    #[error("The remote probe server reported an error: {0}")]
+    Remote(String),
+    /// The recorded operation failed.
+    #[error("The recorded operation failed: {0}")]
+    Recorded(String),
+    /// The data is not a valid transaction recording.
+    #[error("The data is not a valid probe transaction recording")]
+    InvalidRecording,
+    /// All recorded transactions were already replayed.
+    #[error("All recorded transactions have already been replayed")]
+    ReplayExhausted,
+    /// A request did not match the request in the recording.
+    #[error("Request {index} does not match the recording. Expected {expected}, got {actual}")]
+    ReplayMismatch {
+        /// The index of the transaction in the recording.
+        index: usize,
+        /// The recorded request.
+        expected: String,
+        /// The request which was made during replay.
+        actual: String,
+    },
+}
+
+/// Writes a single length-prefixed message to `writer`.
+fn write_message<W: Write, M: Serialize>(
+    writer: &mut W,
+    message: &M,
+) -> Result<(), RemoteProbeError> {
+    let payload = bincode::serialize(message)?;
+    let len = u32::try_from(payload.len()).unwrap_or(u32::MAX);
+
+    if len > MAX_MESSAGE_SIZE {
+        return Err(RemoteProbeError::MessageTooLarge(len));
+    }
+
+    writer.write_all(&len.to_le_bytes())?;
+    writer.write_all(&payload)?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Reads a single length-prefixed message from `reader`.
+///
+/// Returns `Ok(None)` if the connection was closed before a new message started.
+fn read_message<R: Read, M: DeserializeOwned>(
+    reader: &mut R,
+) -> Result<Option<M>, RemoteProbeError> {
+    let mut len = [0u8; 4];
+
+    match reader.read_exact(&mut len) {
+        Ok(()) => (),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+
+    let len = u32::from_le_bytes(len);
+
+    if len > MAX_MESSAGE_SIZE {
+        return Err(RemoteProbeError::MessageTooLarge(len));
+    }
+
+    let mut payload = vec![0u8; len as usize];
+    reader.read_exact(&mut payload)?;
+
+    Ok(Some(bincode::deserialize(&payload)?))
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        net::TcpListener,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    use super::protocol::{Request, Response};
+    use super::{RemoteProbe, RemoteProbeServer, MAX_BLOCK_WORDS, MAX_MESSAGE_SIZE};
+    use crate::{
+        architecture::arm::{PortType, RawDapAccess},
+        probe::fake_probe::FakeProbe,
+        DebugProbe, WireProtocol,
+    };
+
+    fn start_server(probe: FakeProbe) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        std::thread::spawn(move || {
+            let mut server = RemoteProbeServer::new(probe.into_probe());
+            let (stream, _) = listener.accept().unwrap();
+            server.handle_connection(stream).unwrap();
+        });
+
+        address
+    }
+
+    #[test]
+    fn remote_probe_forwards_probe_operations() {
+        let address = start_server(FakeProbe::new());
+
+        let mut probe = RemoteProbe::connect_raw(&address).unwrap();
+
+        assert_eq!(probe.get_name(), "Mock probe for testing");
+        assert!(probe.has_arm_interface());
+
+        assert_eq!(probe.set_speed(4000).unwrap(), 4000);
+        assert_eq!(probe.speed_khz(), 4000);
+
+        probe.select_protocol(WireProtocol::Jtag).unwrap();
+        assert_eq!(probe.active_protocol(), Some(WireProtocol::Jtag));
+    }
+
+    #[test]
+    fn remote_probe_forwards_dap_transactions() {
+        let mut fake_probe = FakeProbe::new();
+        fake_probe.set_dap_register_read_handler(Box::new(|port, addr| {
+            assert_eq!(port, PortType::AccessPort);
+            Ok(0x1234_0000 | addr as u32)
+        }));
+        fake_probe.set_dap_register_write_handler(Box::new(|_, _, _| Ok(())));
+
+        let address = start_server(fake_probe);
+
+        let mut probe = RemoteProbe::connect_raw(&address).unwrap();
+
+        assert_eq!(
+            probe.raw_read_register(PortType::AccessPort, 0xc).unwrap(),
+            0x1234_000c
+        );
+
+        let mut values = [0u32; 3];
+        probe
+            .raw_read_block(PortType::AccessPort, 0x4, &mut values)
+            .unwrap();
+        assert_eq!(values, [0x1234_0004; 3]);
+
+        probe
+            .raw_write_register(PortType::DebugPort, 0x8, 0xdead_beef)
+            .unwrap();
+    }
+
+    #[test]
+    fn remote_errors_are_reported() {
+        let address = start_server(FakeProbe::new());
+
+        let mut probe = RemoteProbe::connect_raw(&address).unwrap();
+
+        // The fake probe does not support resetting the target.
+        assert!(probe.target_reset().is_err());
+
+        // The connection is still usable after an error.
+        assert_eq!(probe.set_speed(100).unwrap(), 100);
+    }
+
+    #[test]
+    fn block_transfers_fit_into_a_message() {
+        let values = vec![u32::MAX; MAX_BLOCK_WORDS as usize];
+
+        let response = Response::Values(values.clone());
+        assert!(bincode::serialized_size(&response).unwrap() <= MAX_MESSAGE_SIZE as u64);
+
+        let request = Request::RawWriteBlock {
+            port: PortType::AccessPort,
+            addr: 0xc,
+            values,
+        };
+        assert!(bincode::serialized_size(&request).unwrap() <= MAX_MESSAGE_SIZE as u64);
+    }
+
+    #[test]
+    fn large_block_transfers_are_split_up() {
+        let written = Arc::new(AtomicUsize::new(0));
+
+        let mut fake_probe = FakeProbe::new();
+        fake_probe.set_dap_register_read_handler(Box::new(|_, addr| Ok(addr as u32)));
+        let counter = written.clone();
+        fake_probe.set_dap_register_write_handler(Box::new(move |_, _, _| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }));
+
+        let address = start_server(fake_probe);
+
+        let mut probe = RemoteProbe::connect_raw(&address).unwrap();
+
+        let len = MAX_BLOCK_WORDS as usize + 3;
+
+        let mut values = vec![0u32; len];
+        probe
+            .raw_read_block(PortType::AccessPort, 0xc, &mut values)
+            .unwrap();
+        assert!(values.iter().all(|&value| value == 0xc));
+
+        probe
+            .raw_write_block(PortType::AccessPort, 0xc, &values)
+            .unwrap();
+        assert_eq!(written.load(Ordering::Relaxed), len);
+    }
+
+    #[test]
+    fn oversized_reads_are_rejected() {
+        let mut server = RemoteProbeServer::new(FakeProbe::new().into_probe());
+
+        let response = server.handle_request(Request::RawReadBlock {
+            port: PortType::AccessPort,
+            addr: 0xc,
+            len: u32::MAX,
+        });
+
+        assert!(matches!(response, Response::Error(message) if message.contains("limited")));
+    }
+}
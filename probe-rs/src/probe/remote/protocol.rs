@@ -0,0 +1,136 @@
+//! Messages exchanged between a [`RemoteProbe`](super::RemoteProbe) and a
+//! [`RemoteProbeServer`](super::RemoteProbeServer).
+//!
+//! Every [`Request`] is answered by exactly one [`Response`].
+
+use crate::{
+    architecture::arm::{DpAddress, PortType},
+    CoreStatus, DebugProbe, WireProtocol,
+};
+
+/// Version of the wire protocol, exchanged in the initial handshake.
+pub(super) const PROTOCOL_VERSION: u32 = 2;
+
+/// A request sent from the client to the server.
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) enum Request {
+    /// Initial handshake, answered with [`Response::Hello`].
+    Hello {
+        version: u32,
+    },
+
+    // `DebugProbe` operations
+    SetSpeed(u32),
+    Attach,
+    Detach,
+    TargetReset,
+    TargetResetAssert,
+    TargetResetDeassert,
+    SelectProtocol(WireProtocol),
+    TargetVoltage,
+
+    // `RawDapAccess` operations
+    SelectDp(DpAddress),
+    RawReadRegister {
+        port: PortType,
+        addr: u8,
+    },
+    RawReadBlock {
+        port: PortType,
+        addr: u8,
+        len: u32,
+    },
+    RawWriteRegister {
+        port: PortType,
+        addr: u8,
+        value: u32,
+    },
+    RawWriteBlock {
+        port: PortType,
+        addr: u8,
+        values: Vec<u32>,
+    },
+    RawFlush,
+    SwjSequence {
+        bit_len: u8,
+        bits: u64,
+    },
+    SwjPins {
+        pin_out: u32,
+        pin_select: u32,
+        pin_wait: u32,
+    },
+    CoreStatusNotification(CoreStatus),
+
+    // `JTAGAccess` operations
+    JtagReadRegister {
+        address: u32,
+        len: u32,
+    },
+    JtagWriteRegister {
+        address: u32,
+        data: Vec<u8>,
+        len: u32,
+    },
+    JtagSetIdleCycles(u8),
+    JtagSetIrLen(u32),
+}
+
+/// A response sent from the server to the client.
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) enum Response {
+    /// Answer to [`Request::Hello`], describing the served probe.
+    Hello(ProbeDescription),
+    /// The request was executed, and there is no data to return.
+    Ok,
+    /// The request was executed, and returned a single value.
+    Value(u32),
+    /// The request was executed, and returned multiple values.
+    Values(Vec<u32>),
+    /// The request was executed, and returned raw bytes.
+    Bytes(Vec<u8>),
+    /// Response to [`Request::SelectProtocol`].
+    Protocol(Option<WireProtocol>),
+    /// Response to [`Request::TargetVoltage`].
+    Voltage(Option<f32>),
+    /// The request failed on the server.
+    Error(String),
+}
+
+/// Static information about the probe served by a server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct ProbeDescription {
+    pub version: u32,
+    pub name: String,
+    pub speed_khz: u32,
+    pub protocol: Option<WireProtocol>,
+    pub has_arm_interface: bool,
+    pub has_riscv_interface: bool,
+    pub has_xtensa_interface: bool,
+    pub has_dap_access: bool,
+    pub has_jtag_access: bool,
+    pub jtag_idle_cycles: u8,
+}
+
+impl ProbeDescription {
+    /// Describes the capabilities of `probe`.
+    pub fn of(probe: &mut dyn DebugProbe) -> Self {
+        let jtag_idle_cycles = probe
+            .try_as_jtag_probe()
+            .map(|jtag| jtag.get_idle_cycles())
+            .unwrap_or(0);
+
+        ProbeDescription {
+            version: PROTOCOL_VERSION,
+            name: probe.get_name().to_string(),
+            speed_khz: probe.speed_khz(),
+            protocol: probe.active_protocol(),
+            has_arm_interface: probe.has_arm_interface(),
+            has_riscv_interface: probe.has_riscv_interface(),
+            has_xtensa_interface: probe.has_xtensa_interface(),
+            has_dap_access: probe.try_as_dap_probe().is_some(),
+            has_jtag_access: probe.try_as_jtag_probe().is_some(),
+            jtag_idle_cycles,
+        }
+    }
+}
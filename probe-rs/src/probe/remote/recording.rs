@@ -0,0 +1,2095 @@
+//! Recording and replaying of probe transactions.
+//!
+//! A recording contains every operation executed on the probe, together with the result
+//! and a timestamp. The operations are recorded on the level of the driver interfaces:
+//!
+//! - the [`DebugProbe`] operations, like changing the speed or resetting the target,
+//! - the DAP register accesses and memory accesses of the [`ArmProbeInterface`], and
+//!   the memory interfaces it returns,
+//! - the raw DAP and JTAG transactions, which are used by reset sequences and for
+//!   RISC-V and Xtensa targets.
+//!
+//! Because memory accesses are recorded, this works for all probes, including probes like
+//! the ST-Link which implement the memory access in their firmware. SWO data is not recorded.
+//!
+//! Recordings can be attached to bug reports, and replayed without any hardware, for
+//! example in regression tests.
+
+use std::{
+    collections::VecDeque,
+    io::{Read, Write},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use super::{protocol::ProbeDescription, read_message, write_message, RemoteProbeError};
+use crate::{
+    architecture::{
+        arm::{
+            ap::{AccessPort, GenericAp, MemoryAp, IDR},
+            communication_interface::{DapProbe, Initialized, SwdSequence, UninitializedArmProbe},
+            memory::adi_v5_memory_interface::ArmProbe,
+            sequences::ArmDebugSequence,
+            ApAddress, ApInformation, ArmChipInfo, ArmCommunicationInterface, ArmError,
+            ArmProbeInterface, DapAccess, DpAddress, MemoryApInformation, PortType, RawDapAccess,
+            SwoAccess, SwoConfig,
+        },
+        riscv::communication_interface::{RiscvCommunicationInterface, RiscvError},
+        xtensa::communication_interface::{XtensaCommunicationInterface, XtensaError},
+    },
+    probe::JTAGAccess,
+    CoreStatus, DebugProbe, DebugProbeError, DebugProbeSelector, Error, Probe, ProbeCreationError,
+    WireProtocol,
+};
+
+/// Identifies a file as a probe-rs transaction recording.
+const RECORDING_MAGIC: [u8; 8] = *b"PRSREC\0\0";
+
+/// Version of the recording format.
+const RECORDING_VERSION: u32 = 1;
+
+/// The header at the start of each recording.
+#[derive(Debug, Serialize, Deserialize)]
+struct RecordingHeader {
+    magic: [u8; 8],
+    version: u32,
+    /// The recorded probe, at the start of the recording.
+    description: ProbeDescription,
+}
+
+/// A single recorded transaction.
+#[derive(Debug, Serialize, Deserialize)]
+struct RecordedTransaction {
+    /// Time since the start of the recording, when the transaction completed.
+    timestamp: Duration,
+    request: Request,
+    response: Response,
+}
+
+/// A recorded operation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+enum Request {
+    // `DebugProbe` operations
+    SetSpeed(u32),
+    Attach,
+    Detach,
+    TargetReset,
+    TargetResetAssert,
+    TargetResetDeassert,
+    SelectProtocol(WireProtocol),
+    TargetVoltage,
+    SwjSequence {
+        bit_len: u8,
+        bits: u64,
+    },
+    SwjPins {
+        pin_out: u32,
+        pin_select: u32,
+        pin_wait: u32,
+    },
+
+    // `RawDapAccess` operations
+    SelectDp(DpAddress),
+    RawReadRegister {
+        port: PortType,
+        addr: u8,
+    },
+    RawReadBlock {
+        port: PortType,
+        addr: u8,
+        len: usize,
+    },
+    RawWriteRegister {
+        port: PortType,
+        addr: u8,
+        value: u32,
+    },
+    RawWriteBlock {
+        port: PortType,
+        addr: u8,
+        values: Vec<u32>,
+    },
+    RawFlush,
+    CoreStatusNotification(CoreStatus),
+
+    // `JTAGAccess` operations
+    JtagReadRegister {
+        address: u32,
+        len: u32,
+    },
+    JtagWriteRegister {
+        address: u32,
+        data: Vec<u8>,
+        len: u32,
+    },
+    JtagSetIdleCycles(u8),
+    JtagSetIrLen(u32),
+
+    // `ArmProbeInterface` operations
+    ArmInterface,
+    InitializeArm,
+    ReadDpRegister {
+        dp: DpAddress,
+        addr: u8,
+    },
+    WriteDpRegister {
+        dp: DpAddress,
+        addr: u8,
+        value: u32,
+    },
+    ReadApRegister {
+        ap: ApAddress,
+        addr: u8,
+    },
+    ReadApRegisterRepeated {
+        ap: ApAddress,
+        addr: u8,
+        len: usize,
+    },
+    WriteApRegister {
+        ap: ApAddress,
+        addr: u8,
+        value: u32,
+    },
+    WriteApRegisterRepeated {
+        ap: ApAddress,
+        addr: u8,
+        values: Vec<u32>,
+    },
+    ApInformation(ApAddress),
+    NumAccessPorts(DpAddress),
+    ReadChipInfo(DpAddress),
+    MemoryInterface(ApAddress),
+
+    // `ArmProbe` operations, `width` is the size of a single access in bytes.
+    ReadMemory {
+        ap: ApAddress,
+        address: u64,
+        width: u8,
+        len: usize,
+    },
+    WriteMemory {
+        ap: ApAddress,
+        address: u64,
+        width: u8,
+        data: Vec<u64>,
+    },
+    FlushMemory(ApAddress),
+    Supports8BitTransfers(ApAddress),
+    SupportsNative64BitAccess(ApAddress),
+}
+
+/// The recorded result of an operation.
+#[derive(Debug, Serialize, Deserialize)]
+enum Response {
+    /// The operation succeeded, and returned no data.
+    Ok,
+    /// The operation returned a single value.
+    Value(u64),
+    /// The operation returned multiple values.
+    Values(Vec<u64>),
+    /// The operation returned raw bytes.
+    Bytes(Vec<u8>),
+    /// The operation returned a flag.
+    Bool(bool),
+    /// Response to [`Request::SelectProtocol`], with the active protocol afterwards.
+    Protocol(Option<WireProtocol>),
+    /// Response to [`Request::TargetVoltage`].
+    Voltage(Option<f32>),
+    /// Response to [`Request::ApInformation`].
+    ApInformation(RecordedApInformation),
+    /// Response to [`Request::ReadChipInfo`], with the JEP106 continuation and
+    /// identity code of the manufacturer, and the part number.
+    ChipInfo(Option<(u8, u8, u16)>),
+    /// The operation failed.
+    Error(String),
+}
+
+/// The recorded version of [`ApInformation`].
+#[derive(Debug, Serialize, Deserialize)]
+enum RecordedApInformation {
+    MemoryAp {
+        address: ApAddress,
+        idr: u32,
+        supports_only_32bit_data_size: bool,
+        supports_packed_transfers: bool,
+        debug_base_address: u64,
+        supports_hnonsec: bool,
+        has_large_address_extension: bool,
+        has_large_data_extension: bool,
+        device_enabled: bool,
+    },
+    Other {
+        address: ApAddress,
+        idr: u32,
+    },
+}
+
+impl From<&ApInformation> for RecordedApInformation {
+    fn from(information: &ApInformation) -> Self {
+        match information {
+            ApInformation::MemoryAp(info) => RecordedApInformation::MemoryAp {
+                address: info.address,
+                idr: info.idr.into(),
+                supports_only_32bit_data_size: info.supports_only_32bit_data_size,
+                supports_packed_transfers: info.supports_packed_transfers,
+                debug_base_address: info.debug_base_address,
+                supports_hnonsec: info.supports_hnonsec,
+                has_large_address_extension: info.has_large_address_extension,
+                has_large_data_extension: info.has_large_data_extension,
+                device_enabled: info.device_enabled,
+            },
+            ApInformation::Other { address, idr } => RecordedApInformation::Other {
+                address: *address,
+                idr: (*idr).into(),
+            },
+        }
+    }
+}
+
+impl TryFrom<RecordedApInformation> for ApInformation {
+    type Error = RemoteProbeError;
+
+    fn try_from(information: RecordedApInformation) -> Result<Self, Self::Error> {
+        let idr = |value| IDR::try_from(value).map_err(|_| RemoteProbeError::InvalidRecording);
+
+        Ok(match information {
+            RecordedApInformation::MemoryAp {
+                address,
+                idr: value,
+                supports_only_32bit_data_size,
+                supports_packed_transfers,
+                debug_base_address,
+                supports_hnonsec,
+                has_large_address_extension,
+                has_large_data_extension,
+                device_enabled,
+            } => ApInformation::MemoryAp(MemoryApInformation {
+                address,
+                idr: idr(value)?,
+                supports_only_32bit_data_size,
+                supports_packed_transfers,
+                debug_base_address,
+                supports_hnonsec,
+                has_large_address_extension,
+                has_large_data_extension,
+                device_enabled,
+            }),
+            RecordedApInformation::Other {
+                address,
+                idr: value,
+            } => ApInformation::Other {
+                address,
+                idr: idr(value)?,
+            },
+        })
+    }
+}
+
+/// Converts the values of a memory access for the recording.
+fn to_values<T: Copy + Into<u64>>(data: &[T]) -> Vec<u64> {
+    data.iter().map(|&value| value.into()).collect()
+}
+
+/// Writes the recorded transactions.
+struct Recorder {
+    writer: Box<dyn Write + Send>,
+    start: Instant,
+}
+
+impl std::fmt::Debug for Recorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Recorder")
+            .field("start", &self.start)
+            .finish()
+    }
+}
+
+impl Recorder {
+    /// Records the `result` of `request`. A successful result is recorded as the
+    /// response returned by `response`.
+    ///
+    /// Failing to write the recording does not affect the operation, it is only logged.
+    fn record<T, E: std::fmt::Display>(
+        &mut self,
+        request: Request,
+        result: &Result<T, E>,
+        response: impl FnOnce(&T) -> Response,
+    ) {
+        let response = match result {
+            Ok(value) => response(value),
+            Err(e) => Response::Error(e.to_string()),
+        };
+
+        let transaction = RecordedTransaction {
+            timestamp: self.start.elapsed(),
+            request,
+            response,
+        };
+
+        if let Err(e) = write_message(&mut self.writer, &transaction) {
+            tracing::warn!("Failed to record probe transaction: {}", e);
+        }
+    }
+}
+
+/// A probe which records all operations of the wrapped probe.
+#[derive(Debug)]
+struct RecordingProbe {
+    inner: Box<dyn DebugProbe>,
+    recorder: Recorder,
+    jtag_idle_cycles: u8,
+}
+
+impl RecordingProbe {
+    fn new(mut inner: Box<dyn DebugProbe>, recorder: Recorder) -> Self {
+        let jtag_idle_cycles = inner
+            .try_as_jtag_probe()
+            .map(|jtag| jtag.get_idle_cycles())
+            .unwrap_or(0);
+
+        Self {
+            inner,
+            recorder,
+            jtag_idle_cycles,
+        }
+    }
+
+    /// Wraps the probe returned by a closed interface again.
+    fn wrap(probe: Probe, recorder: Recorder) -> Probe {
+        Probe {
+            inner: Box::new(Self::new(probe.inner, recorder)),
+            attached: probe.attached,
+        }
+    }
+
+    fn dap(&mut self) -> Result<&mut dyn DapProbe, DebugProbeError> {
+        self.inner
+            .try_as_dap_probe()
+            .ok_or(DebugProbeError::InterfaceNotAvailable("DAP"))
+    }
+
+    fn jtag(&mut self) -> Result<&mut dyn JTAGAccess, DebugProbeError> {
+        self.inner
+            .try_as_jtag_probe()
+            .ok_or(DebugProbeError::InterfaceNotAvailable("JTAG"))
+    }
+}
+
+impl DebugProbe for RecordingProbe {
+    fn new_from_selector(
+        _selector: impl Into<DebugProbeSelector>,
+    ) -> Result<Box<Self>, DebugProbeError>
+    where
+        Self: Sized,
+    {
+        // Recording probes are created using `record`.
+        Err(DebugProbeError::ProbeCouldNotBeCreated(
+            ProbeCreationError::NotFound,
+        ))
+    }
+
+    fn get_name(&self) -> &str {
+        self.inner.get_name()
+    }
+
+    fn speed_khz(&self) -> u32 {
+        self.inner.speed_khz()
+    }
+
+    fn set_speed(&mut self, speed_khz: u32) -> Result<u32, DebugProbeError> {
+        let result = self.inner.set_speed(speed_khz);
+        self.recorder
+            .record(Request::SetSpeed(speed_khz), &result, |&speed| {
+                Response::Value(speed.into())
+            });
+        result
+    }
+
+    fn attach(&mut self) -> Result<(), DebugProbeError> {
+        let result = self.inner.attach();
+        self.recorder
+            .record(Request::Attach, &result, |_| Response::Ok);
+        result
+    }
+
+    fn detach(&mut self) -> Result<(), Error> {
+        let result = self.inner.detach();
+        self.recorder
+            .record(Request::Detach, &result, |_| Response::Ok);
+        result
+    }
+
+    fn target_reset(&mut self) -> Result<(), DebugProbeError> {
+        let result = self.inner.target_reset();
+        self.recorder
+            .record(Request::TargetReset, &result, |_| Response::Ok);
+        result
+    }
+
+    fn target_reset_assert(&mut self) -> Result<(), DebugProbeError> {
+        let result = self.inner.target_reset_assert();
+        self.recorder
+            .record(Request::TargetResetAssert, &result, |_| Response::Ok);
+        result
+    }
+
+    fn target_reset_deassert(&mut self) -> Result<(), DebugProbeError> {
+        let result = self.inner.target_reset_deassert();
+        self.recorder
+            .record(Request::TargetResetDeassert, &result, |_| Response::Ok);
+        result
+    }
+
+    fn select_protocol(&mut self, protocol: WireProtocol) -> Result<(), DebugProbeError> {
+        let result = self.inner.select_protocol(protocol);
+        let active_protocol = self.inner.active_protocol();
+        self.recorder
+            .record(Request::SelectProtocol(protocol), &result, |_| {
+                Response::Protocol(active_protocol)
+            });
+        result
+    }
+
+    fn active_protocol(&self) -> Option<WireProtocol> {
+        self.inner.active_protocol()
+    }
+
+    fn has_arm_interface(&self) -> bool {
+        self.inner.has_arm_interface()
+    }
+
+    fn try_get_arm_interface<'probe>(
+        self: Box<Self>,
+    ) -> Result<Box<dyn UninitializedArmProbe + 'probe>, (Box<dyn DebugProbe>, DebugProbeError)>
+    {
+        let RecordingProbe {
+            inner,
+            mut recorder,
+            ..
+        } = *self;
+
+        let result = inner.try_get_arm_interface();
+        recorder.record(
+            Request::ArmInterface,
+            &result.as_ref().map_err(|(_, e)| e),
+            |_| Response::Ok,
+        );
+
+        match result {
+            Ok(inner) => Ok(Box::new(RecordingUninitializedArmProbe { inner, recorder })),
+            Err((probe, e)) => Err((Box::new(RecordingProbe::new(probe, recorder)), e)),
+        }
+    }
+
+    fn has_riscv_interface(&self) -> bool {
+        self.inner.has_riscv_interface()
+    }
+
+    fn try_get_riscv_interface(
+        mut self: Box<Self>,
+    ) -> Result<RiscvCommunicationInterface, (Box<dyn DebugProbe>, RiscvError)> {
+        if self.has_riscv_interface() && self.jtag().is_ok() {
+            RiscvCommunicationInterface::new(self).map_err(|(probe, err)| (probe.into_probe(), err))
+        } else {
+            Err((self, DebugProbeError::InterfaceNotAvailable("RISCV").into()))
+        }
+    }
+
+    fn has_xtensa_interface(&self) -> bool {
+        self.inner.has_xtensa_interface()
+    }
+
+    fn try_get_xtensa_interface(
+        mut self: Box<Self>,
+    ) -> Result<XtensaCommunicationInterface, (Box<dyn DebugProbe>, XtensaError)> {
+        if self.has_xtensa_interface() && self.jtag().is_ok() {
+            XtensaCommunicationInterface::new(self)
+                .map_err(|(probe, err)| (probe.into_probe(), err))
+        } else {
+            Err((
+                self,
+                DebugProbeError::InterfaceNotAvailable("Xtensa").into(),
+            ))
+        }
+    }
+
+    fn get_swo_interface(&self) -> Option<&dyn SwoAccess> {
+        self.inner.get_swo_interface()
+    }
+
+    fn get_swo_interface_mut(&mut self) -> Option<&mut dyn SwoAccess> {
+        self.inner.get_swo_interface_mut()
+    }
+
+    fn into_probe(self: Box<Self>) -> Box<dyn DebugProbe> {
+        self
+    }
+
+    fn try_as_dap_probe(&mut self) -> Option<&mut dyn DapProbe> {
+        if self.dap().is_ok() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn try_as_jtag_probe(&mut self) -> Option<&mut dyn JTAGAccess> {
+        if self.jtag().is_ok() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn get_target_voltage(&mut self) -> Result<Option<f32>, DebugProbeError> {
+        let result = self.inner.get_target_voltage();
+        self.recorder
+            .record(Request::TargetVoltage, &result, |&voltage| {
+                Response::Voltage(voltage)
+            });
+        result
+    }
+
+    fn swj_pins(
+        &mut self,
+        pin_out: u32,
+        pin_select: u32,
+        pin_wait: u32,
+    ) -> Result<u32, DebugProbeError> {
+        let result = self.inner.swj_pins(pin_out, pin_select, pin_wait);
+        self.recorder.record(
+            Request::SwjPins {
+                pin_out,
+                pin_select,
+                pin_wait,
+            },
+            &result,
+            |&pins| Response::Value(pins.into()),
+        );
+        result
+    }
+}
+
+impl RawDapAccess for RecordingProbe {
+    fn select_dp(&mut self, dp: DpAddress) -> Result<(), ArmError> {
+        let result = self
+            .dap()
+            .map_err(ArmError::from)
+            .and_then(|dap| dap.select_dp(dp));
+        self.recorder
+            .record(Request::SelectDp(dp), &result, |_| Response::Ok);
+        result
+    }
+
+    fn raw_read_register(&mut self, port: PortType, addr: u8) -> Result<u32, ArmError> {
+        let result = self
+            .dap()
+            .map_err(ArmError::from)
+            .and_then(|dap| dap.raw_read_register(port, addr));
+        self.recorder
+            .record(Request::RawReadRegister { port, addr }, &result, |&value| {
+                Response::Value(value.into())
+            });
+        result
+    }
+
+    fn raw_read_block(
+        &mut self,
+        port: PortType,
+        addr: u8,
+        values: &mut [u32],
+    ) -> Result<(), ArmError> {
+        let result = self
+            .dap()
+            .map_err(ArmError::from)
+            .and_then(|dap| dap.raw_read_block(port, addr, values));
+        self.recorder.record(
+            Request::RawReadBlock {
+                port,
+                addr,
+                len: values.len(),
+            },
+            &result,
+            |_| Response::Values(to_values(values)),
+        );
+        result
+    }
+
+    fn raw_write_register(&mut self, port: PortType, addr: u8, value: u32) -> Result<(), ArmError> {
+        let result = self
+            .dap()
+            .map_err(ArmError::from)
+            .and_then(|dap| dap.raw_write_register(port, addr, value));
+        self.recorder.record(
+            Request::RawWriteRegister { port, addr, value },
+            &result,
+            |_| Response::Ok,
+        );
+        result
+    }
+
+    fn raw_write_block(
+        &mut self,
+        port: PortType,
+        addr: u8,
+        values: &[u32],
+    ) -> Result<(), ArmError> {
+        let result = self
+            .dap()
+            .map_err(ArmError::from)
+            .and_then(|dap| dap.raw_write_block(port, addr, values));
+        self.recorder.record(
+            Request::RawWriteBlock {
+                port,
+                addr,
+                values: values.to_vec(),
+            },
+            &result,
+            |_| Response::Ok,
+        );
+        result
+    }
+
+    fn raw_flush(&mut self) -> Result<(), ArmError> {
+        let result = self
+            .dap()
+            .map_err(ArmError::from)
+            .and_then(|dap| dap.raw_flush());
+        self.recorder
+            .record(Request::RawFlush, &result, |_| Response::Ok);
+        result
+    }
+
+    fn swj_sequence(&mut self, bit_len: u8, bits: u64) -> Result<(), DebugProbeError> {
+        let result = self
+            .dap()
+            .and_then(|dap| RawDapAccess::swj_sequence(dap, bit_len, bits));
+        self.recorder
+            .record(Request::SwjSequence { bit_len, bits }, &result, |_| {
+                Response::Ok
+            });
+        result
+    }
+
+    fn swj_pins(
+        &mut self,
+        pin_out: u32,
+        pin_select: u32,
+        pin_wait: u32,
+    ) -> Result<u32, DebugProbeError> {
+        DebugProbe::swj_pins(self, pin_out, pin_select, pin_wait)
+    }
+
+    fn into_probe(self: Box<Self>) -> Box<dyn DebugProbe> {
+        self
+    }
+
+    fn core_status_notification(&mut self, state: CoreStatus) -> Result<(), DebugProbeError> {
+        let result = self
+            .dap()
+            .and_then(|dap| dap.core_status_notification(state));
+        self.recorder
+            .record(Request::CoreStatusNotification(state), &result, |_| {
+                Response::Ok
+            });
+        result
+    }
+}
+
+impl DapProbe for RecordingProbe {}
+
+impl JTAGAccess for RecordingProbe {
+    fn read_register(&mut self, address: u32, len: u32) -> Result<Vec<u8>, DebugProbeError> {
+        let result = self
+            .jtag()
+            .and_then(|jtag| jtag.read_register(address, len));
+        self.recorder.record(
+            Request::JtagReadRegister { address, len },
+            &result,
+            |data| Response::Bytes(data.clone()),
+        );
+        result
+    }
+
+    fn set_idle_cycles(&mut self, idle_cycles: u8) {
+        let result = self.jtag().map(|jtag| jtag.set_idle_cycles(idle_cycles));
+        self.recorder
+            .record(Request::JtagSetIdleCycles(idle_cycles), &result, |_| {
+                Response::Ok
+            });
+
+        if result.is_ok() {
+            self.jtag_idle_cycles = idle_cycles;
+        }
+    }
+
+    fn get_idle_cycles(&self) -> u8 {
+        self.jtag_idle_cycles
+    }
+
+    fn set_ir_len(&mut self, len: u32) {
+        let result = self.jtag().map(|jtag| jtag.set_ir_len(len));
+        self.recorder
+            .record(Request::JtagSetIrLen(len), &result, |_| Response::Ok);
+    }
+
+    fn write_register(
+        &mut self,
+        address: u32,
+        data: &[u8],
+        len: u32,
+    ) -> Result<Vec<u8>, DebugProbeError> {
+        let result = self
+            .jtag()
+            .and_then(|jtag| jtag.write_register(address, data, len));
+        self.recorder.record(
+            Request::JtagWriteRegister {
+                address,
+                data: data.to_vec(),
+                len,
+            },
+            &result,
+            |data| Response::Bytes(data.clone()),
+        );
+        result
+    }
+}
+
+/// Records the operations of an ARM interface before it is initialized.
+#[derive(Debug)]
+struct RecordingUninitializedArmProbe<'probe> {
+    inner: Box<dyn UninitializedArmProbe + 'probe>,
+    recorder: Recorder,
+}
+
+impl UninitializedArmProbe for RecordingUninitializedArmProbe<'_> {
+    fn initialize(
+        self: Box<Self>,
+        sequence: Arc<dyn ArmDebugSequence>,
+    ) -> Result<Box<dyn ArmProbeInterface>, (Box<dyn UninitializedArmProbe>, Error)> {
+        let RecordingUninitializedArmProbe {
+            inner,
+            mut recorder,
+        } = *self;
+
+        let result = inner.initialize(sequence);
+        recorder.record(
+            Request::InitializeArm,
+            &result.as_ref().map_err(|(_, e)| e),
+            |_| Response::Ok,
+        );
+
+        match result {
+            Ok(inner) => Ok(Box::new(RecordingArmInterface { inner, recorder })),
+            Err((inner, e)) => Err((
+                Box::new(RecordingUninitializedArmProbe { inner, recorder }),
+                e,
+            )),
+        }
+    }
+
+    fn close(self: Box<Self>) -> Probe {
+        RecordingProbe::wrap(self.inner.close(), self.recorder)
+    }
+}
+
+impl SwdSequence for RecordingUninitializedArmProbe<'_> {
+    fn swj_sequence(&mut self, bit_len: u8, bits: u64) -> Result<(), DebugProbeError> {
+        let result = self.inner.swj_sequence(bit_len, bits);
+        self.recorder
+            .record(Request::SwjSequence { bit_len, bits }, &result, |_| {
+                Response::Ok
+            });
+        result
+    }
+
+    fn swj_pins(
+        &mut self,
+        pin_out: u32,
+        pin_select: u32,
+        pin_wait: u32,
+    ) -> Result<u32, DebugProbeError> {
+        let result = self.inner.swj_pins(pin_out, pin_select, pin_wait);
+        self.recorder.record(
+            Request::SwjPins {
+                pin_out,
+                pin_select,
+                pin_wait,
+            },
+            &result,
+            |&pins| Response::Value(pins.into()),
+        );
+        result
+    }
+}
+
+/// Records the operations of an initialized ARM interface.
+struct RecordingArmInterface {
+    inner: Box<dyn ArmProbeInterface>,
+    recorder: Recorder,
+}
+
+impl ArmProbeInterface for RecordingArmInterface {
+    fn memory_interface(
+        &mut self,
+        access_port: MemoryAp,
+    ) -> Result<Box<dyn ArmProbe + '_>, ArmError> {
+        let ap = access_port.ap_address();
+
+        let result = self.inner.memory_interface(access_port);
+        self.recorder
+            .record(Request::MemoryInterface(ap), &result, |_| Response::Ok);
+        let inner = result?;
+
+        // The support for 8 bit transfers is queried through a shared reference,
+        // so it can't be recorded later.
+        let supports_8bit_transfers = inner.supports_8bit_transfers();
+        self.recorder.record(
+            Request::Supports8BitTransfers(ap),
+            &supports_8bit_transfers,
+            |&supported| Response::Bool(supported),
+        );
+
+        Ok(Box::new(RecordingMemory {
+            inner,
+            recorder: &mut self.recorder,
+            ap,
+        }))
+    }
+
+    fn ap_information(&mut self, access_port: GenericAp) -> Result<&ApInformation, ArmError> {
+        let result = self.inner.ap_information(access_port);
+        self.recorder.record(
+            Request::ApInformation(access_port.ap_address()),
+            &result,
+            |&information| Response::ApInformation(information.into()),
+        );
+        result
+    }
+
+    fn num_access_ports(&mut self, dp: DpAddress) -> Result<usize, ArmError> {
+        let result = self.inner.num_access_ports(dp);
+        self.recorder
+            .record(Request::NumAccessPorts(dp), &result, |&count| {
+                Response::Value(count as u64)
+            });
+        result
+    }
+
+    fn read_chip_info_from_rom_table(
+        &mut self,
+        dp: DpAddress,
+    ) -> Result<Option<ArmChipInfo>, ArmError> {
+        let result = self.inner.read_chip_info_from_rom_table(dp);
+        self.recorder
+            .record(Request::ReadChipInfo(dp), &result, |info| {
+                Response::ChipInfo(
+                    info.as_ref()
+                        .map(|info| (info.manufacturer.cc, info.manufacturer.id, info.part)),
+                )
+            });
+        result
+    }
+
+    fn close(self: Box<Self>) -> Probe {
+        RecordingProbe::wrap(self.inner.close(), self.recorder)
+    }
+}
+
+impl DapAccess for RecordingArmInterface {
+    fn read_raw_dp_register(&mut self, dp: DpAddress, addr: u8) -> Result<u32, ArmError> {
+        let result = self.inner.read_raw_dp_register(dp, addr);
+        self.recorder
+            .record(Request::ReadDpRegister { dp, addr }, &result, |&value| {
+                Response::Value(value.into())
+            });
+        result
+    }
+
+    fn write_raw_dp_register(
+        &mut self,
+        dp: DpAddress,
+        addr: u8,
+        value: u32,
+    ) -> Result<(), ArmError> {
+        let result = self.inner.write_raw_dp_register(dp, addr, value);
+        self.recorder.record(
+            Request::WriteDpRegister { dp, addr, value },
+            &result,
+            |_| Response::Ok,
+        );
+        result
+    }
+
+    fn read_raw_ap_register(&mut self, ap: ApAddress, addr: u8) -> Result<u32, ArmError> {
+        let result = self.inner.read_raw_ap_register(ap, addr);
+        self.recorder
+            .record(Request::ReadApRegister { ap, addr }, &result, |&value| {
+                Response::Value(value.into())
+            });
+        result
+    }
+
+    fn read_raw_ap_register_repeated(
+        &mut self,
+        ap: ApAddress,
+        addr: u8,
+        values: &mut [u32],
+    ) -> Result<(), ArmError> {
+        let result = self.inner.read_raw_ap_register_repeated(ap, addr, values);
+        self.recorder.record(
+            Request::ReadApRegisterRepeated {
+                ap,
+                addr,
+                len: values.len(),
+            },
+            &result,
+            |_| Response::Values(to_values(values)),
+        );
+        result
+    }
+
+    fn write_raw_ap_register(
+        &mut self,
+        ap: ApAddress,
+        addr: u8,
+        value: u32,
+    ) -> Result<(), ArmError> {
+        let result = self.inner.write_raw_ap_register(ap, addr, value);
+        self.recorder.record(
+            Request::WriteApRegister { ap, addr, value },
+            &result,
+            |_| Response::Ok,
+        );
+        result
+    }
+
+    fn write_raw_ap_register_repeated(
+        &mut self,
+        ap: ApAddress,
+        addr: u8,
+        values: &[u32],
+    ) -> Result<(), ArmError> {
+        let result = self.inner.write_raw_ap_register_repeated(ap, addr, values);
+        self.recorder.record(
+            Request::WriteApRegisterRepeated {
+                ap,
+                addr,
+                values: values.to_vec(),
+            },
+            &result,
+            |_| Response::Ok,
+        );
+        result
+    }
+}
+
+impl SwdSequence for RecordingArmInterface {
+    fn swj_sequence(&mut self, bit_len: u8, bits: u64) -> Result<(), DebugProbeError> {
+        let result = self.inner.swj_sequence(bit_len, bits);
+        self.recorder
+            .record(Request::SwjSequence { bit_len, bits }, &result, |_| {
+                Response::Ok
+            });
+        result
+    }
+
+    fn swj_pins(
+        &mut self,
+        pin_out: u32,
+        pin_select: u32,
+        pin_wait: u32,
+    ) -> Result<u32, DebugProbeError> {
+        let result = self.inner.swj_pins(pin_out, pin_select, pin_wait);
+        self.recorder.record(
+            Request::SwjPins {
+                pin_out,
+                pin_select,
+                pin_wait,
+            },
+            &result,
+            |&pins| Response::Value(pins.into()),
+        );
+        result
+    }
+}
+
+// SWO data is not recorded.
+impl SwoAccess for RecordingArmInterface {
+    fn enable_swo(&mut self, config: &SwoConfig) -> Result<(), ArmError> {
+        self.inner.enable_swo(config)
+    }
+
+    fn disable_swo(&mut self) -> Result<(), ArmError> {
+        self.inner.disable_swo()
+    }
+
+    fn read_swo_timeout(&mut self, timeout: Duration) -> Result<Vec<u8>, ArmError> {
+        self.inner.read_swo_timeout(timeout)
+    }
+
+    fn swo_poll_interval_hint(&mut self, config: &SwoConfig) -> Option<Duration> {
+        self.inner.swo_poll_interval_hint(config)
+    }
+
+    fn swo_buffer_size(&mut self) -> Option<usize> {
+        self.inner.swo_buffer_size()
+    }
+}
+
+/// Records the operations of a memory interface.
+struct RecordingMemory<'a> {
+    inner: Box<dyn ArmProbe + 'a>,
+    recorder: &'a mut Recorder,
+    ap: ApAddress,
+}
+
+impl RecordingMemory<'_> {
+    fn record_read<T: Copy + Into<u64>>(
+        &mut self,
+        address: u64,
+        data: &[T],
+        result: &Result<(), ArmError>,
+    ) {
+        self.recorder.record(
+            Request::ReadMemory {
+                ap: self.ap,
+                address,
+                width: std::mem::size_of::<T>() as u8,
+                len: data.len(),
+            },
+            result,
+            |_| Response::Values(to_values(data)),
+        );
+    }
+
+    fn record_write<T: Copy + Into<u64>>(
+        &mut self,
+        address: u64,
+        data: &[T],
+        result: &Result<(), ArmError>,
+    ) {
+        self.recorder.record(
+            Request::WriteMemory {
+                ap: self.ap,
+                address,
+                width: std::mem::size_of::<T>() as u8,
+                data: to_values(data),
+            },
+            result,
+            |_| Response::Ok,
+        );
+    }
+}
+
+impl ArmProbe for RecordingMemory<'_> {
+    fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), ArmError> {
+        let result = self.inner.read_8(address, data);
+        self.record_read(address, data, &result);
+        result
+    }
+
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), ArmError> {
+        let result = self.inner.read_16(address, data);
+        self.record_read(address, data, &result);
+        result
+    }
+
+    fn read_32(&mut self, address: u64, data: &mut [u32]) -> Result<(), ArmError> {
+        let result = self.inner.read_32(address, data);
+        self.record_read(address, data, &result);
+        result
+    }
+
+    fn read_64(&mut self, address: u64, data: &mut [u64]) -> Result<(), ArmError> {
+        let result = self.inner.read_64(address, data);
+        self.record_read(address, data, &result);
+        result
+    }
+
+    fn write_8(&mut self, address: u64, data: &[u8]) -> Result<(), ArmError> {
+        let result = self.inner.write_8(address, data);
+        self.record_write(address, data, &result);
+        result
+    }
+
+    fn write_16(&mut self, address: u64, data: &[u16]) -> Result<(), ArmError> {
+        let result = self.inner.write_16(address, data);
+        self.record_write(address, data, &result);
+        result
+    }
+
+    fn write_32(&mut self, address: u64, data: &[u32]) -> Result<(), ArmError> {
+        let result = self.inner.write_32(address, data);
+        self.record_write(address, data, &result);
+        result
+    }
+
+    fn write_64(&mut self, address: u64, data: &[u64]) -> Result<(), ArmError> {
+        let result = self.inner.write_64(address, data);
+        self.record_write(address, data, &result);
+        result
+    }
+
+    fn flush(&mut self) -> Result<(), ArmError> {
+        let result = self.inner.flush();
+        self.recorder
+            .record(Request::FlushMemory(self.ap), &result, |_| Response::Ok);
+        result
+    }
+
+    fn supports_native_64bit_access(&mut self) -> bool {
+        let supported = self.inner.supports_native_64bit_access();
+        self.recorder.record(
+            Request::SupportsNative64BitAccess(self.ap),
+            &Ok::<_, ArmError>(supported),
+            |&supported| Response::Bool(supported),
+        );
+        supported
+    }
+
+    fn supports_8bit_transfers(&self) -> Result<bool, ArmError> {
+        // Recorded when the memory interface is created.
+        self.inner.supports_8bit_transfers()
+    }
+
+    fn ap(&mut self) -> MemoryAp {
+        self.inner.ap()
+    }
+
+    fn get_arm_communication_interface(
+        &mut self,
+    ) -> Result<&mut ArmCommunicationInterface<Initialized>, DebugProbeError> {
+        // Operations on the interface bypass the recording.
+        Err(DebugProbeError::NotImplemented(
+            "get_arm_communication_interface",
+        ))
+    }
+
+    fn update_core_status(&mut self, state: CoreStatus) {
+        self.inner.update_core_status(state)
+    }
+}
+
+impl SwdSequence for RecordingMemory<'_> {
+    fn swj_sequence(&mut self, bit_len: u8, bits: u64) -> Result<(), DebugProbeError> {
+        let result = self.inner.swj_sequence(bit_len, bits);
+        self.recorder
+            .record(Request::SwjSequence { bit_len, bits }, &result, |_| {
+                Response::Ok
+            });
+        result
+    }
+
+    fn swj_pins(
+        &mut self,
+        pin_out: u32,
+        pin_select: u32,
+        pin_wait: u32,
+    ) -> Result<u32, DebugProbeError> {
+        let result = self.inner.swj_pins(pin_out, pin_select, pin_wait);
+        self.recorder.record(
+            Request::SwjPins {
+                pin_out,
+                pin_select,
+                pin_wait,
+            },
+            &result,
+            |&pins| Response::Value(pins.into()),
+        );
+        result
+    }
+}
+
+/// Answers operations with the results from a recording.
+#[derive(Debug)]
+struct Replayer {
+    transactions: VecDeque<RecordedTransaction>,
+    index: usize,
+}
+
+impl Replayer {
+    /// Returns the recorded response to `request`.
+    ///
+    /// Recorded failures are converted into [`RemoteProbeError::Recorded`].
+    fn replay(&mut self, request: Request) -> Result<Response, RemoteProbeError> {
+        let index = self.index;
+
+        let Some(transaction) = self.transactions.pop_front() else {
+            return Err(RemoteProbeError::ReplayExhausted);
+        };
+
+        if transaction.request != request {
+            return Err(RemoteProbeError::ReplayMismatch {
+                index,
+                expected: format!("{:?}", transaction.request),
+                actual: format!("{request:?}"),
+            });
+        }
+
+        self.index += 1;
+
+        match transaction.response {
+            Response::Error(message) => Err(RemoteProbeError::Recorded(message)),
+            response => Ok(response),
+        }
+    }
+
+    fn replay_ok(&mut self, request: Request) -> Result<(), RemoteProbeError> {
+        match self.replay(request)? {
+            Response::Ok => Ok(()),
+            _ => Err(RemoteProbeError::UnexpectedResponse),
+        }
+    }
+
+    fn replay_value(&mut self, request: Request) -> Result<u64, RemoteProbeError> {
+        match self.replay(request)? {
+            Response::Value(value) => Ok(value),
+            _ => Err(RemoteProbeError::UnexpectedResponse),
+        }
+    }
+
+    fn replay_u32(&mut self, request: Request) -> Result<u32, RemoteProbeError> {
+        u32::try_from(self.replay_value(request)?).map_err(|_| RemoteProbeError::UnexpectedResponse)
+    }
+
+    fn replay_bool(&mut self, request: Request) -> Result<bool, RemoteProbeError> {
+        match self.replay(request)? {
+            Response::Bool(value) => Ok(value),
+            _ => Err(RemoteProbeError::UnexpectedResponse),
+        }
+    }
+
+    fn replay_bytes(&mut self, request: Request) -> Result<Vec<u8>, RemoteProbeError> {
+        match self.replay(request)? {
+            Response::Bytes(bytes) => Ok(bytes),
+            _ => Err(RemoteProbeError::UnexpectedResponse),
+        }
+    }
+
+    /// Replays a read, and copies the recorded values to `data`.
+    fn replay_values<T: TryFrom<u64>>(
+        &mut self,
+        request: Request,
+        data: &mut [T],
+    ) -> Result<(), RemoteProbeError> {
+        match self.replay(request)? {
+            Response::Values(values) if values.len() == data.len() => {
+                for (target, value) in data.iter_mut().zip(values) {
+                    *target =
+                        T::try_from(value).map_err(|_| RemoteProbeError::UnexpectedResponse)?;
+                }
+                Ok(())
+            }
+            _ => Err(RemoteProbeError::UnexpectedResponse),
+        }
+    }
+}
+
+/// Converts a replay error into an [`ArmError`].
+fn arm_error(error: RemoteProbeError) -> ArmError {
+    DebugProbeError::from(error).into()
+}
+
+/// A probe which replays a recording.
+#[derive(Debug)]
+struct ReplayProbe {
+    replayer: Replayer,
+    description: ProbeDescription,
+}
+
+impl DebugProbe for ReplayProbe {
+    fn new_from_selector(
+        _selector: impl Into<DebugProbeSelector>,
+    ) -> Result<Box<Self>, DebugProbeError>
+    where
+        Self: Sized,
+    {
+        // Replay probes are created using `replay`.
+        Err(DebugProbeError::ProbeCouldNotBeCreated(
+            ProbeCreationError::NotFound,
+        ))
+    }
+
+    fn get_name(&self) -> &str {
+        &self.description.name
+    }
+
+    fn speed_khz(&self) -> u32 {
+        self.description.speed_khz
+    }
+
+    fn set_speed(&mut self, speed_khz: u32) -> Result<u32, DebugProbeError> {
+        let actual_speed = self.replayer.replay_u32(Request::SetSpeed(speed_khz))?;
+
+        self.description.speed_khz = actual_speed;
+
+        Ok(actual_speed)
+    }
+
+    fn attach(&mut self) -> Result<(), DebugProbeError> {
+        Ok(self.replayer.replay_ok(Request::Attach)?)
+    }
+
+    fn detach(&mut self) -> Result<(), Error> {
+        self.replayer
+            .replay_ok(Request::Detach)
+            .map_err(|e| Error::Probe(e.into()))
+    }
+
+    fn target_reset(&mut self) -> Result<(), DebugProbeError> {
+        Ok(self.replayer.replay_ok(Request::TargetReset)?)
+    }
+
+    fn target_reset_assert(&mut self) -> Result<(), DebugProbeError> {
+        Ok(self.replayer.replay_ok(Request::TargetResetAssert)?)
+    }
+
+    fn target_reset_deassert(&mut self) -> Result<(), DebugProbeError> {
+        Ok(self.replayer.replay_ok(Request::TargetResetDeassert)?)
+    }
+
+    fn select_protocol(&mut self, protocol: WireProtocol) -> Result<(), DebugProbeError> {
+        match self.replayer.replay(Request::SelectProtocol(protocol))? {
+            Response::Protocol(protocol) => {
+                self.description.protocol = protocol;
+                Ok(())
+            }
+            _ => Err(RemoteProbeError::UnexpectedResponse.into()),
+        }
+    }
+
+    fn active_protocol(&self) -> Option<WireProtocol> {
+        self.description.protocol
+    }
+
+    fn has_arm_interface(&self) -> bool {
+        self.description.has_arm_interface
+    }
+
+    fn try_get_arm_interface<'probe>(
+        mut self: Box<Self>,
+    ) -> Result<Box<dyn UninitializedArmProbe + 'probe>, (Box<dyn DebugProbe>, DebugProbeError)>
+    {
+        match self.replayer.replay_ok(Request::ArmInterface) {
+            Ok(()) => Ok(Box::new(ReplayArmInterface {
+                probe: *self,
+                ap_information: None,
+            })),
+            Err(e) => Err((self, e.into())),
+        }
+    }
+
+    fn has_riscv_interface(&self) -> bool {
+        self.description.has_riscv_interface && self.description.has_jtag_access
+    }
+
+    fn try_get_riscv_interface(
+        self: Box<Self>,
+    ) -> Result<RiscvCommunicationInterface, (Box<dyn DebugProbe>, RiscvError)> {
+        if self.has_riscv_interface() {
+            RiscvCommunicationInterface::new(self).map_err(|(probe, err)| (probe.into_probe(), err))
+        } else {
+            Err((self, DebugProbeError::InterfaceNotAvailable("RISCV").into()))
+        }
+    }
+
+    fn has_xtensa_interface(&self) -> bool {
+        self.description.has_xtensa_interface && self.description.has_jtag_access
+    }
+
+    fn try_get_xtensa_interface(
+        self: Box<Self>,
+    ) -> Result<XtensaCommunicationInterface, (Box<dyn DebugProbe>, XtensaError)> {
+        if self.has_xtensa_interface() {
+            XtensaCommunicationInterface::new(self)
+                .map_err(|(probe, err)| (probe.into_probe(), err))
+        } else {
+            Err((
+                self,
+                DebugProbeError::InterfaceNotAvailable("Xtensa").into(),
+            ))
+        }
+    }
+
+    fn into_probe(self: Box<Self>) -> Box<dyn DebugProbe> {
+        self
+    }
+
+    fn try_as_dap_probe(&mut self) -> Option<&mut dyn DapProbe> {
+        if self.description.has_dap_access {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn try_as_jtag_probe(&mut self) -> Option<&mut dyn JTAGAccess> {
+        if self.description.has_jtag_access {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn get_target_voltage(&mut self) -> Result<Option<f32>, DebugProbeError> {
+        match self.replayer.replay(Request::TargetVoltage)? {
+            Response::Voltage(voltage) => Ok(voltage),
+            _ => Err(RemoteProbeError::UnexpectedResponse.into()),
+        }
+    }
+
+    fn swj_pins(
+        &mut self,
+        pin_out: u32,
+        pin_select: u32,
+        pin_wait: u32,
+    ) -> Result<u32, DebugProbeError> {
+        Ok(self.replayer.replay_u32(Request::SwjPins {
+            pin_out,
+            pin_select,
+            pin_wait,
+        })?)
+    }
+}
+
+impl RawDapAccess for ReplayProbe {
+    fn select_dp(&mut self, dp: DpAddress) -> Result<(), ArmError> {
+        self.replayer
+            .replay_ok(Request::SelectDp(dp))
+            .map_err(arm_error)
+    }
+
+    fn raw_read_register(&mut self, port: PortType, addr: u8) -> Result<u32, ArmError> {
+        self.replayer
+            .replay_u32(Request::RawReadRegister { port, addr })
+            .map_err(arm_error)
+    }
+
+    fn raw_read_block(
+        &mut self,
+        port: PortType,
+        addr: u8,
+        values: &mut [u32],
+    ) -> Result<(), ArmError> {
+        let request = Request::RawReadBlock {
+            port,
+            addr,
+            len: values.len(),
+        };
+
+        self.replayer
+            .replay_values(request, values)
+            .map_err(arm_error)
+    }
+
+    fn raw_write_register(&mut self, port: PortType, addr: u8, value: u32) -> Result<(), ArmError> {
+        self.replayer
+            .replay_ok(Request::RawWriteRegister { port, addr, value })
+            .map_err(arm_error)
+    }
+
+    fn raw_write_block(
+        &mut self,
+        port: PortType,
+        addr: u8,
+        values: &[u32],
+    ) -> Result<(), ArmError> {
+        self.replayer
+            .replay_ok(Request::RawWriteBlock {
+                port,
+                addr,
+                values: values.to_vec(),
+            })
+            .map_err(arm_error)
+    }
+
+    fn raw_flush(&mut self) -> Result<(), ArmError> {
+        self.replayer
+            .replay_ok(Request::RawFlush)
+            .map_err(arm_error)
+    }
+
+    fn swj_sequence(&mut self, bit_len: u8, bits: u64) -> Result<(), DebugProbeError> {
+        Ok(self
+            .replayer
+            .replay_ok(Request::SwjSequence { bit_len, bits })?)
+    }
+
+    fn swj_pins(
+        &mut self,
+        pin_out: u32,
+        pin_select: u32,
+        pin_wait: u32,
+    ) -> Result<u32, DebugProbeError> {
+        DebugProbe::swj_pins(self, pin_out, pin_select, pin_wait)
+    }
+
+    fn into_probe(self: Box<Self>) -> Box<dyn DebugProbe> {
+        self
+    }
+
+    fn core_status_notification(&mut self, state: CoreStatus) -> Result<(), DebugProbeError> {
+        Ok(self
+            .replayer
+            .replay_ok(Request::CoreStatusNotification(state))?)
+    }
+}
+
+impl DapProbe for ReplayProbe {}
+
+impl JTAGAccess for ReplayProbe {
+    fn read_register(&mut self, address: u32, len: u32) -> Result<Vec<u8>, DebugProbeError> {
+        Ok(self
+            .replayer
+            .replay_bytes(Request::JtagReadRegister { address, len })?)
+    }
+
+    fn set_idle_cycles(&mut self, idle_cycles: u8) {
+        match self
+            .replayer
+            .replay_ok(Request::JtagSetIdleCycles(idle_cycles))
+        {
+            Ok(()) => self.description.jtag_idle_cycles = idle_cycles,
+            Err(e) => tracing::warn!("Failed to replay setting the idle cycles: {}", e),
+        }
+    }
+
+    fn get_idle_cycles(&self) -> u8 {
+        self.description.jtag_idle_cycles
+    }
+
+    fn set_ir_len(&mut self, len: u32) {
+        if let Err(e) = self.replayer.replay_ok(Request::JtagSetIrLen(len)) {
+            tracing::warn!("Failed to replay setting the IR length: {}", e);
+        }
+    }
+
+    fn write_register(
+        &mut self,
+        address: u32,
+        data: &[u8],
+        len: u32,
+    ) -> Result<Vec<u8>, DebugProbeError> {
+        Ok(self.replayer.replay_bytes(Request::JtagWriteRegister {
+            address,
+            data: data.to_vec(),
+            len,
+        })?)
+    }
+}
+
+/// Replays the operations of an ARM interface.
+///
+/// The same type is used before and after the interface is initialized.
+#[derive(Debug)]
+struct ReplayArmInterface {
+    probe: ReplayProbe,
+    /// The last replayed AP information, which is returned by reference.
+    ap_information: Option<ApInformation>,
+}
+
+impl UninitializedArmProbe for ReplayArmInterface {
+    fn initialize(
+        mut self: Box<Self>,
+        _sequence: Arc<dyn ArmDebugSequence>,
+    ) -> Result<Box<dyn ArmProbeInterface>, (Box<dyn UninitializedArmProbe>, Error)> {
+        // The sequence was already run when the recording was made.
+        match self.probe.replayer.replay_ok(Request::InitializeArm) {
+            Ok(()) => Ok(self),
+            Err(e) => Err((self, Error::Probe(e.into()))),
+        }
+    }
+
+    fn close(self: Box<Self>) -> Probe {
+        Probe::from_attached_probe(Box::new(self.probe))
+    }
+}
+
+impl ArmProbeInterface for ReplayArmInterface {
+    fn memory_interface(
+        &mut self,
+        access_port: MemoryAp,
+    ) -> Result<Box<dyn ArmProbe + '_>, ArmError> {
+        let ap = access_port.ap_address();
+        let replayer = &mut self.probe.replayer;
+
+        replayer
+            .replay_ok(Request::MemoryInterface(ap))
+            .map_err(arm_error)?;
+
+        let supports_8bit_transfers = match replayer.replay_bool(Request::Supports8BitTransfers(ap))
+        {
+            Ok(supported) => Ok(supported),
+            Err(RemoteProbeError::Recorded(message)) => Err(message),
+            Err(e) => return Err(arm_error(e)),
+        };
+
+        Ok(Box::new(ReplayMemory {
+            replayer,
+            ap,
+            supports_8bit_transfers,
+        }))
+    }
+
+    fn ap_information(&mut self, access_port: GenericAp) -> Result<&ApInformation, ArmError> {
+        let information = match self
+            .probe
+            .replayer
+            .replay(Request::ApInformation(access_port.ap_address()))
+            .map_err(arm_error)?
+        {
+            Response::ApInformation(information) => {
+                ApInformation::try_from(information).map_err(arm_error)?
+            }
+            _ => return Err(arm_error(RemoteProbeError::UnexpectedResponse)),
+        };
+
+        Ok(self.ap_information.insert(information))
+    }
+
+    fn num_access_ports(&mut self, dp: DpAddress) -> Result<usize, ArmError> {
+        self.probe
+            .replayer
+            .replay_value(Request::NumAccessPorts(dp))
+            .map(|count| count as usize)
+            .map_err(arm_error)
+    }
+
+    fn read_chip_info_from_rom_table(
+        &mut self,
+        dp: DpAddress,
+    ) -> Result<Option<ArmChipInfo>, ArmError> {
+        match self
+            .probe
+            .replayer
+            .replay(Request::ReadChipInfo(dp))
+            .map_err(arm_error)?
+        {
+            Response::ChipInfo(info) => Ok(info.map(|(cc, id, part)| ArmChipInfo {
+                manufacturer: jep106::JEP106Code::new(cc, id),
+                part,
+            })),
+            _ => Err(arm_error(RemoteProbeError::UnexpectedResponse)),
+        }
+    }
+
+    fn close(self: Box<Self>) -> Probe {
+        Probe::from_attached_probe(Box::new(self.probe))
+    }
+}
+
+impl DapAccess for ReplayArmInterface {
+    fn read_raw_dp_register(&mut self, dp: DpAddress, addr: u8) -> Result<u32, ArmError> {
+        self.probe
+            .replayer
+            .replay_u32(Request::ReadDpRegister { dp, addr })
+            .map_err(arm_error)
+    }
+
+    fn write_raw_dp_register(
+        &mut self,
+        dp: DpAddress,
+        addr: u8,
+        value: u32,
+    ) -> Result<(), ArmError> {
+        self.probe
+            .replayer
+            .replay_ok(Request::WriteDpRegister { dp, addr, value })
+            .map_err(arm_error)
+    }
+
+    fn read_raw_ap_register(&mut self, ap: ApAddress, addr: u8) -> Result<u32, ArmError> {
+        self.probe
+            .replayer
+            .replay_u32(Request::ReadApRegister { ap, addr })
+            .map_err(arm_error)
+    }
+
+    fn read_raw_ap_register_repeated(
+        &mut self,
+        ap: ApAddress,
+        addr: u8,
+        values: &mut [u32],
+    ) -> Result<(), ArmError> {
+        let request = Request::ReadApRegisterRepeated {
+            ap,
+            addr,
+            len: values.len(),
+        };
+
+        self.probe
+            .replayer
+            .replay_values(request, values)
+            .map_err(arm_error)
+    }
+
+    fn write_raw_ap_register(
+        &mut self,
+        ap: ApAddress,
+        addr: u8,
+        value: u32,
+    ) -> Result<(), ArmError> {
+        self.probe
+            .replayer
+            .replay_ok(Request::WriteApRegister { ap, addr, value })
+            .map_err(arm_error)
+    }
+
+    fn write_raw_ap_register_repeated(
+        &mut self,
+        ap: ApAddress,
+        addr: u8,
+        values: &[u32],
+    ) -> Result<(), ArmError> {
+        self.probe
+            .replayer
+            .replay_ok(Request::WriteApRegisterRepeated {
+                ap,
+                addr,
+                values: values.to_vec(),
+            })
+            .map_err(arm_error)
+    }
+}
+
+impl SwdSequence for ReplayArmInterface {
+    fn swj_sequence(&mut self, bit_len: u8, bits: u64) -> Result<(), DebugProbeError> {
+        RawDapAccess::swj_sequence(&mut self.probe, bit_len, bits)
+    }
+
+    fn swj_pins(
+        &mut self,
+        pin_out: u32,
+        pin_select: u32,
+        pin_wait: u32,
+    ) -> Result<u32, DebugProbeError> {
+        DebugProbe::swj_pins(&mut self.probe, pin_out, pin_select, pin_wait)
+    }
+}
+
+impl SwoAccess for ReplayArmInterface {
+    fn enable_swo(&mut self, _config: &SwoConfig) -> Result<(), ArmError> {
+        Err(DebugProbeError::NotImplemented("SWO replay").into())
+    }
+
+    fn disable_swo(&mut self) -> Result<(), ArmError> {
+        Err(DebugProbeError::NotImplemented("SWO replay").into())
+    }
+
+    fn read_swo_timeout(&mut self, _timeout: Duration) -> Result<Vec<u8>, ArmError> {
+        Err(DebugProbeError::NotImplemented("SWO replay").into())
+    }
+}
+
+/// Replays the operations of a memory interface.
+struct ReplayMemory<'a> {
+    replayer: &'a mut Replayer,
+    ap: ApAddress,
+    supports_8bit_transfers: Result<bool, String>,
+}
+
+impl ReplayMemory<'_> {
+    fn replay_read<T: TryFrom<u64>>(
+        &mut self,
+        address: u64,
+        data: &mut [T],
+    ) -> Result<(), ArmError> {
+        let request = Request::ReadMemory {
+            ap: self.ap,
+            address,
+            width: std::mem::size_of::<T>() as u8,
+            len: data.len(),
+        };
+
+        self.replayer
+            .replay_values(request, data)
+            .map_err(arm_error)
+    }
+
+    fn replay_write<T: Copy + Into<u64>>(
+        &mut self,
+        address: u64,
+        data: &[T],
+    ) -> Result<(), ArmError> {
+        self.replayer
+            .replay_ok(Request::WriteMemory {
+                ap: self.ap,
+                address,
+                width: std::mem::size_of::<T>() as u8,
+                data: to_values(data),
+            })
+            .map_err(arm_error)
+    }
+}
+
+impl ArmProbe for ReplayMemory<'_> {
+    fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), ArmError> {
+        self.replay_read(address, data)
+    }
+
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), ArmError> {
+        self.replay_read(address, data)
+    }
+
+    fn read_32(&mut self, address: u64, data: &mut [u32]) -> Result<(), ArmError> {
+        self.replay_read(address, data)
+    }
+
+    fn read_64(&mut self, address: u64, data: &mut [u64]) -> Result<(), ArmError> {
+        self.replay_read(address, data)
+    }
+
+    fn write_8(&mut self, address: u64, data: &[u8]) -> Result<(), ArmError> {
+        self.replay_write(address, data)
+    }
+
+    fn write_16(&mut self, address: u64, data: &[u16]) -> Result<(), ArmError> {
+        self.replay_write(address, data)
+    }
+
+    fn write_32(&mut self, address: u64, data: &[u32]) -> Result<(), ArmError> {
+        self.replay_write(address, data)
+    }
+
+    fn write_64(&mut self, address: u64, data: &[u64]) -> Result<(), ArmError> {
+        self.replay_write(address, data)
+    }
+
+    fn flush(&mut self) -> Result<(), ArmError> {
+        self.replayer
+            .replay_ok(Request::FlushMemory(self.ap))
+            .map_err(arm_error)
+    }
+
+    fn supports_native_64bit_access(&mut self) -> bool {
+        match self
+            .replayer
+            .replay_bool(Request::SupportsNative64BitAccess(self.ap))
+        {
+            Ok(supported) => supported,
+            Err(e) => {
+                tracing::warn!("Failed to replay the support for 64 bit access: {}", e);
+                false
+            }
+        }
+    }
+
+    fn supports_8bit_transfers(&self) -> Result<bool, ArmError> {
+        self.supports_8bit_transfers
+            .clone()
+            .map_err(|message| arm_error(RemoteProbeError::Recorded(message)))
+    }
+
+    fn ap(&mut self) -> MemoryAp {
+        MemoryAp::new(self.ap)
+    }
+
+    fn get_arm_communication_interface(
+        &mut self,
+    ) -> Result<&mut ArmCommunicationInterface<Initialized>, DebugProbeError> {
+        Err(DebugProbeError::NotImplemented(
+            "get_arm_communication_interface",
+        ))
+    }
+}
+
+impl SwdSequence for ReplayMemory<'_> {
+    fn swj_sequence(&mut self, bit_len: u8, bits: u64) -> Result<(), DebugProbeError> {
+        Ok(self
+            .replayer
+            .replay_ok(Request::SwjSequence { bit_len, bits })?)
+    }
+
+    fn swj_pins(
+        &mut self,
+        pin_out: u32,
+        pin_select: u32,
+        pin_wait: u32,
+    ) -> Result<u32, DebugProbeError> {
+        Ok(self.replayer.replay_u32(Request::SwjPins {
+            pin_out,
+            pin_select,
+            pin_wait,
+        })?)
+    }
+}
+
+/// Wraps `probe`, so that all operations on the probe are recorded to `writer`.
+///
+/// The operations are recorded on the level of the probe driver, see the
+/// [module documentation](self) for details. This works for all probes, including probes
+/// which implement memory access in their firmware, such as the ST-Link.
+///
+/// The recording can be replayed using [`replay`].
+pub fn record(
+    mut probe: Probe,
+    writer: impl Write + Send + 'static,
+) -> Result<Probe, RemoteProbeError> {
+    let mut writer: Box<dyn Write + Send> = Box::new(writer);
+
+    write_message(
+        &mut writer,
+        &RecordingHeader {
+            magic: RECORDING_MAGIC,
+            version: RECORDING_VERSION,
+            description: ProbeDescription::of(&mut *probe.inner),
+        },
+    )?;
+
+    let recorder = Recorder {
+        writer,
+        start: Instant::now(),
+    };
+
+    Ok(RecordingProbe::wrap(probe, recorder))
+}
+
+/// Creates a probe which replays a recording created with [`record`].
+///
+/// The operations on the returned probe have to match the recorded operations exactly,
+/// otherwise a [`RemoteProbeError::ReplayMismatch`] error is returned.
+pub fn replay(mut reader: impl Read) -> Result<Probe, RemoteProbeError> {
+    let header: RecordingHeader =
+        read_message(&mut reader)?.ok_or(RemoteProbeError::InvalidRecording)?;
+
+    if header.magic != RECORDING_MAGIC || header.version != RECORDING_VERSION {
+        return Err(RemoteProbeError::InvalidRecording);
+    }
+
+    let mut transactions = VecDeque::new();
+
+    while let Some(transaction) = read_message(&mut reader)? {
+        transactions.push_back(transaction);
+    }
+
+    tracing::debug!("Replaying {} recorded transactions", transactions.len());
+
+    Ok(Probe::new(ReplayProbe {
+        replayer: Replayer {
+            transactions,
+            index: 0,
+        },
+        description: header.description,
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        io::Write,
+        sync::{Arc, Mutex},
+    };
+
+    use super::{record, replay};
+    use crate::{
+        architecture::arm::PortType,
+        probe::{fake_probe::FakeProbe, remote::RemoteProbeError},
+        MemoryInterface, Permissions, Probe,
+    };
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn read_register(probe: &mut Probe, addr: u8) -> u32 {
+        probe
+            .try_as_dap_probe()
+            .unwrap()
+            .raw_read_register(PortType::AccessPort, addr)
+            .unwrap()
+    }
+
+    fn recording() -> Vec<u8> {
+        let mut fake_probe = FakeProbe::new();
+        fake_probe.set_dap_register_read_handler(Box::new(|_, addr| Ok(0xcafe_0000 | addr as u32)));
+
+        let buffer = SharedBuffer::default();
+
+        let mut probe = record(fake_probe.into_probe(), buffer.clone()).unwrap();
+        probe.set_speed(2000).unwrap();
+        assert_eq!(read_register(&mut probe, 0x4), 0xcafe_0004);
+        assert_eq!(read_register(&mut probe, 0x8), 0xcafe_0008);
+
+        let recording = buffer.0.lock().unwrap().clone();
+        recording
+    }
+
+    /// Reads and writes memory in a session on `probe`.
+    fn access_memory(probe: Probe) {
+        let mut session = probe
+            .attach_non_intrusive("nrf51822_xxAC", Permissions::default())
+            .unwrap();
+        let mut core = session.core(0).unwrap();
+
+        assert_eq!(core.read_word_32(0x2000_0004).unwrap(), 0x0403_0201);
+
+        core.write_word_32(0x2000_0008, 0xdead_beef).unwrap();
+        let mut bytes = [0u8; 4];
+        core.read_8(0x2000_0008, &mut bytes).unwrap();
+        assert_eq!(bytes, [0xef, 0xbe, 0xad, 0xde]);
+    }
+
+    #[test]
+    fn replay_returns_recorded_responses() {
+        let recording = recording();
+
+        let mut probe = replay(recording.as_slice()).unwrap();
+
+        assert_eq!(probe.get_name(), "Mock probe for testing");
+        assert_eq!(probe.set_speed(2000).unwrap(), 2000);
+        assert_eq!(read_register(&mut probe, 0x4), 0xcafe_0004);
+        assert_eq!(read_register(&mut probe, 0x8), 0xcafe_0008);
+    }
+
+    #[test]
+    fn memory_accesses_are_recorded() {
+        // The fake probe implements the memory access without the DAP, like an ST-Link.
+        let mut fake_probe = FakeProbe::new();
+        fake_probe.add_memory_region(0x2000_0000, vec![0, 0, 0, 0, 1, 2, 3, 4, 0, 0, 0, 0]);
+        fake_probe.add_memory_region(0xe000_e000, vec![0; 0x1000]);
+
+        let buffer = SharedBuffer::default();
+        access_memory(record(fake_probe.into_probe(), buffer.clone()).unwrap());
+
+        let recording = buffer.0.lock().unwrap().clone();
+        access_memory(replay(recording.as_slice()).unwrap());
+    }
+
+    #[test]
+    fn replay_detects_diverging_requests() {
+        let recording = recording();
+
+        let mut probe = replay(recording.as_slice()).unwrap();
+
+        let error = probe.set_speed(4000).unwrap_err();
+        let crate::DebugProbeError::ProbeSpecific(source) = error else {
+            panic!("Unexpected error: {error:?}");
+        };
+
+        assert!(matches!(
+            source.downcast_ref::<RemoteProbeError>(),
+            Some(RemoteProbeError::ReplayMismatch { index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn replay_rejects_invalid_recordings() {
+        assert!(matches!(
+            replay(&[][..]),
+            Err(RemoteProbeError::InvalidRecording)
+        ));
+
+        assert!(replay(&[1u8, 2, 3, 4][..]).is_err());
+    }
+}
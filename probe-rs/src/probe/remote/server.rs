@@ -0,0 +1,238 @@
+use std::{
+    io::{Read, Write},
+    net::{TcpListener, TcpStream},
+};
+
+use super::{
+    protocol::{ProbeDescription, Request, Response, PROTOCOL_VERSION},
+    read_message, write_message, RemoteProbeError, MAX_BLOCK_WORDS, MAX_MESSAGE_SIZE,
+};
+use crate::{architecture::arm::RawDapAccess, DebugProbe, Probe};
+
+/// The largest length of a JTAG register read in bits, so that the response fits into a message.
+const MAX_JTAG_REGISTER_BITS: u32 = MAX_MESSAGE_SIZE;
+
+/// Serves a locally attached probe to [`RemoteProbe`](super::RemoteProbe) clients.
+///
+/// Only a single client is served at a time. When a client disconnects,
+/// the probe is detached and the next client can connect.
+#[derive(Debug)]
+pub struct RemoteProbeServer {
+    probe: Box<dyn DebugProbe>,
+}
+
+impl RemoteProbeServer {
+    /// Creates a new server for the given probe.
+    pub fn new(probe: Probe) -> Self {
+        Self { probe: probe.inner }
+    }
+
+    /// Accepts clients on `listener`, and serves them one after the other.
+    ///
+    /// This only returns if accepting a new connection fails. Errors
+    /// on a single connection are logged, and the server continues with
+    /// the next client.
+    pub fn serve(&mut self, listener: &TcpListener) -> Result<(), RemoteProbeError> {
+        loop {
+            let (stream, peer) = listener.accept()?;
+
+            tracing::info!("Remote probe client {} connected", peer);
+
+            if let Err(e) = self.handle_tcp_connection(stream) {
+                tracing::warn!("Connection to remote probe client {} failed: {}", peer, e);
+            }
+
+            tracing::info!("Remote probe client {} disconnected", peer);
+        }
+    }
+
+    fn handle_tcp_connection(&mut self, stream: TcpStream) -> Result<(), RemoteProbeError> {
+        // Transactions are small and latency sensitive.
+        stream.set_nodelay(true)?;
+
+        self.handle_connection(stream)
+    }
+
+    /// Serves a single client connection until it is closed by the client.
+    pub fn handle_connection<S: Read + Write>(
+        &mut self,
+        mut stream: S,
+    ) -> Result<(), RemoteProbeError> {
+        let result = self.handle_requests(&mut stream);
+
+        // Never leave the probe attached for the next client.
+        if let Err(e) = self.probe.detach() {
+            tracing::debug!("Failed to detach probe after client disconnected: {}", e);
+        }
+
+        result
+    }
+
+    fn handle_requests<S: Read + Write>(&mut self, stream: &mut S) -> Result<(), RemoteProbeError> {
+        while let Some(request) = read_message::<_, Request>(stream)? {
+            tracing::trace!("Remote request: {:?}", request);
+
+            let response = self.handle_request(request);
+
+            write_message(stream, &response)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the served probe.
+    pub fn into_probe(self) -> Probe {
+        Probe::from_specific_probe(self.probe)
+    }
+
+    pub(super) fn handle_request(&mut self, request: Request) -> Response {
+        let probe = &mut self.probe;
+
+        let result: Result<Response, String> = match request {
+            Request::Hello { version } => {
+                if version != PROTOCOL_VERSION {
+                    Err(format!(
+                        "Unsupported protocol version {version}, server uses version {PROTOCOL_VERSION}"
+                    ))
+                } else {
+                    Ok(Response::Hello(ProbeDescription::of(&mut *self.probe)))
+                }
+            }
+            Request::SetSpeed(speed_khz) => probe
+                .set_speed(speed_khz)
+                .map(Response::Value)
+                .map_err(|e| e.to_string()),
+            Request::Attach => probe
+                .attach()
+                .map(|_| Response::Ok)
+                .map_err(|e| e.to_string()),
+            Request::Detach => probe
+                .detach()
+                .map(|_| Response::Ok)
+                .map_err(|e| e.to_string()),
+            Request::TargetReset => probe
+                .target_reset()
+                .map(|_| Response::Ok)
+                .map_err(|e| e.to_string()),
+            Request::TargetResetAssert => probe
+                .target_reset_assert()
+                .map(|_| Response::Ok)
+                .map_err(|e| e.to_string()),
+            Request::TargetResetDeassert => probe
+                .target_reset_deassert()
+                .map(|_| Response::Ok)
+                .map_err(|e| e.to_string()),
+            Request::SelectProtocol(protocol) => probe
+                .select_protocol(protocol)
+                .map(|_| Response::Protocol(probe.active_protocol()))
+                .map_err(|e| e.to_string()),
+            Request::TargetVoltage => probe
+                .get_target_voltage()
+                .map(Response::Voltage)
+                .map_err(|e| e.to_string()),
+            request @ (Request::SelectDp(_)
+            | Request::RawReadRegister { .. }
+            | Request::RawReadBlock { .. }
+            | Request::RawWriteRegister { .. }
+            | Request::RawWriteBlock { .. }
+            | Request::RawFlush
+            | Request::SwjSequence { .. }
+            | Request::SwjPins { .. }
+            | Request::CoreStatusNotification(_)) => self.handle_dap_request(request),
+            request @ (Request::JtagReadRegister { .. }
+            | Request::JtagWriteRegister { .. }
+            | Request::JtagSetIdleCycles(_)
+            | Request::JtagSetIrLen(_)) => self.handle_jtag_request(request),
+        };
+
+        result.unwrap_or_else(Response::Error)
+    }
+
+    fn handle_dap_request(&mut self, request: Request) -> Result<Response, String> {
+        let Some(dap) = self.probe.try_as_dap_probe() else {
+            return Err("The served probe does not support DAP access".to_string());
+        };
+
+        let response = match request {
+            Request::SelectDp(dp) => dap.select_dp(dp).map(|_| Response::Ok),
+            Request::RawReadRegister { port, addr } => {
+                dap.raw_read_register(port, addr).map(Response::Value)
+            }
+            Request::RawReadBlock { port, addr, len } => {
+                // The length is checked before the buffer for the read is allocated.
+                if len > MAX_BLOCK_WORDS {
+                    return Err(format!(
+                        "Block reads are limited to {MAX_BLOCK_WORDS} words, {len} were requested"
+                    ));
+                }
+
+                let mut values = vec![0; len as usize];
+                dap.raw_read_block(port, addr, &mut values)
+                    .map(|_| Response::Values(values))
+            }
+            Request::RawWriteRegister { port, addr, value } => dap
+                .raw_write_register(port, addr, value)
+                .map(|_| Response::Ok),
+            Request::RawWriteBlock { port, addr, values } => dap
+                .raw_write_block(port, addr, &values)
+                .map(|_| Response::Ok),
+            Request::RawFlush => dap.raw_flush().map(|_| Response::Ok),
+            Request::SwjSequence { bit_len, bits } => {
+                return dap
+                    .swj_sequence(bit_len, bits)
+                    .map(|_| Response::Ok)
+                    .map_err(|e| e.to_string())
+            }
+            Request::SwjPins {
+                pin_out,
+                pin_select,
+                pin_wait,
+            } => {
+                return RawDapAccess::swj_pins(dap, pin_out, pin_select, pin_wait)
+                    .map(Response::Value)
+                    .map_err(|e| e.to_string())
+            }
+            Request::CoreStatusNotification(status) => {
+                return dap
+                    .core_status_notification(status)
+                    .map(|_| Response::Ok)
+                    .map_err(|e| e.to_string())
+            }
+            _ => unreachable!("Only DAP requests are handled here"),
+        };
+
+        response.map_err(|e| e.to_string())
+    }
+
+    fn handle_jtag_request(&mut self, request: Request) -> Result<Response, String> {
+        let Some(jtag) = self.probe.try_as_jtag_probe() else {
+            return Err("The served probe does not support JTAG access".to_string());
+        };
+
+        let response = match request {
+            Request::JtagReadRegister { address, len } => {
+                if len > MAX_JTAG_REGISTER_BITS {
+                    return Err(format!(
+                        "JTAG register reads are limited to {MAX_JTAG_REGISTER_BITS} bits, {len} were requested"
+                    ));
+                }
+
+                jtag.read_register(address, len).map(Response::Bytes)
+            }
+            Request::JtagWriteRegister { address, data, len } => jtag
+                .write_register(address, &data, len)
+                .map(Response::Bytes),
+            Request::JtagSetIdleCycles(idle_cycles) => {
+                jtag.set_idle_cycles(idle_cycles);
+                Ok(Response::Ok)
+            }
+            Request::JtagSetIrLen(len) => {
+                jtag.set_ir_len(len);
+                Ok(Response::Ok)
+            }
+            _ => unreachable!("Only JTAG requests are handled here"),
+        };
+
+        response.map_err(|e| e.to_string())
+    }
+}
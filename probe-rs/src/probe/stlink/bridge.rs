@@ -0,0 +1,771 @@
+//! The bridge interface of the ST-Link V3.
+//!
+//! Besides the debug interface, the ST-Link V3 has a bridge interface, which gives access
+//! to the SPI, I2C, CAN and GPIO pins of the probe. The bridge is a separate USB interface,
+//! so it can be used while a debug session is active.
+//!
+//! ```no_run
+//! use probe_rs::stlink_bridge::{GpioConfig, StLinkBridge};
+//!
+//! let probes = probe_rs::Probe::list_all();
+//! let mut bridge = StLinkBridge::open(probes[0].clone())?;
+//!
+//! // Drive GPIO 0 high.
+//! bridge.init_gpio(0b0001, GpioConfig::default())?;
+//! bridge.set_gpio(0b0001, 0b0001)?;
+//! # Ok::<(), probe_rs::DebugProbeError>(())
+//! ```
+
+use scroll::{Pread, Pwrite, LE};
+
+use super::constants::{commands, BridgeStatus};
+use super::usb_interface::{StLinkUsb, StLinkUsbDevice, TIMEOUT};
+use super::StlinkError;
+use crate::{DebugProbeError, DebugProbeSelector};
+
+/// The maximum number of bytes of a single SPI or I2C transfer.
+const MAX_TRANSFER_SIZE: usize = u16::MAX as usize;
+
+/// The size of a CAN message read from the bridge.
+const CAN_MESSAGE_SIZE: usize = 16;
+
+/// The peripherals of the bridge.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BridgeInterface {
+    /// The SPI interface.
+    Spi,
+    /// The I2C interface.
+    I2c,
+    /// The CAN interface.
+    Can,
+    /// The GPIO pins.
+    Gpio,
+}
+
+impl BridgeInterface {
+    fn com(self) -> u8 {
+        match self {
+            BridgeInterface::Spi => commands::BRIDGE_COM_SPI,
+            BridgeInterface::I2c => commands::BRIDGE_COM_I2C,
+            BridgeInterface::Can => commands::BRIDGE_COM_CAN,
+            BridgeInterface::Gpio => commands::BRIDGE_COM_GPIO,
+        }
+    }
+}
+
+/// The mode of a GPIO pin.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum GpioMode {
+    /// The pin is an input.
+    #[default]
+    Input,
+    /// The pin is an output.
+    Output,
+    /// The pin is an analog pin.
+    Analog,
+}
+
+/// The pull-up or pull-down resistor of a GPIO pin.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum GpioPull {
+    /// No pull resistor.
+    #[default]
+    None,
+    /// A pull-up resistor.
+    Up,
+    /// A pull-down resistor.
+    Down,
+}
+
+/// The configuration of GPIO pins.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct GpioConfig {
+    /// The mode of the pins.
+    pub mode: GpioMode,
+    /// The pull resistor of the pins.
+    pub pull: GpioPull,
+    /// Use open drain instead of push-pull outputs.
+    pub open_drain: bool,
+}
+
+/// The configuration of the I2C interface.
+///
+/// Only 7 bit addresses are supported.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct I2cConfig {
+    /// The value of the `I2C_TIMINGR` register of the bridge, which selects the bus speed.
+    ///
+    /// It depends on the input clock of the I2C interface, see [`StLinkBridge::clock`].
+    pub timing: u32,
+    /// Enable the analog noise filter.
+    pub analog_filter: bool,
+    /// The length of the digital noise filter, from 0 (disabled) to 15.
+    pub digital_filter: u8,
+}
+
+/// The configuration of the SPI interface.
+///
+/// The bridge is the SPI controller, transfers 8 bit words, and the chip
+/// select is driven by [`StLinkBridge::spi_chip_select`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct SpiConfig {
+    /// The clock is high when idle (CPOL).
+    pub clock_idle_high: bool,
+    /// Data is captured on the second clock edge (CPHA).
+    pub capture_on_second_edge: bool,
+    /// Transfer the least significant bit first.
+    pub lsb_first: bool,
+    /// The divider of the SPI clock, from the input clock of the SPI interface.
+    ///
+    /// Must be a power of two from 2 to 256.
+    pub prescaler: u16,
+}
+
+/// The operating mode of the CAN interface.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum CanMode {
+    /// Normal operation.
+    #[default]
+    Normal,
+    /// Transmitted messages are received, and not sent on the bus.
+    Loopback,
+    /// Messages are only received, nothing is sent on the bus.
+    Silent,
+    /// Combination of [`CanMode::Loopback`] and [`CanMode::Silent`].
+    SilentLoopback,
+}
+
+/// The configuration of the CAN interface.
+///
+/// The bit rate is the input clock of the CAN interface, see [`StLinkBridge::clock`],
+/// divided by `prescaler * (1 + bs1 + bs2)`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CanConfig {
+    /// The prescaler of the time quantum, from 1 to 1024.
+    pub prescaler: u16,
+    /// The resynchronization jump width in time quanta, from 1 to 4.
+    pub sjw: u8,
+    /// The length of bit segment 1 in time quanta, from 1 to 16.
+    pub bs1: u8,
+    /// The length of bit segment 2 in time quanta, from 1 to 8.
+    pub bs2: u8,
+    /// The operating mode.
+    pub mode: CanMode,
+}
+
+/// A CAN message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CanMessage {
+    /// The identifier of the message.
+    pub id: u32,
+    /// The identifier is an extended 29 bit identifier.
+    pub extended: bool,
+    /// The message is a remote transmission request.
+    pub remote: bool,
+    /// The data of the message, up to 8 bytes.
+    pub data: Vec<u8>,
+}
+
+/// The clocks of a bridge interface.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BridgeClock {
+    /// The input clock of the interface in kHz.
+    pub input_khz: u32,
+    /// The system clock of the probe in kHz.
+    pub system_khz: u32,
+}
+
+/// The bridge interface of an ST-Link V3.
+pub struct StLinkBridge {
+    device: Box<dyn StLinkUsb + Send>,
+}
+
+impl std::fmt::Debug for StLinkBridge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StLinkBridge")
+            .field("device", &self.device)
+            .finish()
+    }
+}
+
+impl StLinkBridge {
+    /// Opens the bridge interface of the ST-Link V3 matching the selector.
+    pub fn open(selector: impl Into<DebugProbeSelector>) -> Result<Self, DebugProbeError> {
+        let device = StLinkUsbDevice::new_bridge_from_selector(selector)?;
+
+        Ok(Self::new(Box::new(device)))
+    }
+
+    fn new(device: Box<dyn StLinkUsb + Send>) -> Self {
+        Self { device }
+    }
+
+    /// Returns the clocks of a bridge interface, which are needed to
+    /// calculate the configuration of the interface.
+    pub fn clock(&mut self, interface: BridgeInterface) -> Result<BridgeClock, DebugProbeError> {
+        let mut response = [0; 12];
+        self.command(
+            &[
+                commands::BRIDGE_COMMAND,
+                commands::BRIDGE_GET_CLOCK,
+                interface.com(),
+            ],
+            &[],
+            &mut response,
+        )?;
+
+        Ok(BridgeClock {
+            input_khz: response.pread_with(4, LE).unwrap(),
+            system_khz: response.pread_with(8, LE).unwrap(),
+        })
+    }
+
+    /// Closes a bridge interface, which releases its pins.
+    pub fn close(&mut self, interface: BridgeInterface) -> Result<(), DebugProbeError> {
+        self.command(
+            &[
+                commands::BRIDGE_COMMAND,
+                commands::BRIDGE_CLOSE,
+                interface.com(),
+            ],
+            &[],
+            &mut [0; 2],
+        )
+    }
+
+    /// Configures the GPIO pins selected by `mask`. Bit 0 of the mask selects GPIO 0,
+    /// up to bit 3 for GPIO 3.
+    pub fn init_gpio(&mut self, mask: u8, config: GpioConfig) -> Result<(), DebugProbeError> {
+        let mode = match config.mode {
+            GpioMode::Input => 0,
+            GpioMode::Output => 1,
+            GpioMode::Analog => 3,
+        };
+        let pull = match config.pull {
+            GpioPull::None => 0,
+            GpioPull::Up => 1,
+            GpioPull::Down => 2,
+        };
+
+        self.command(
+            &[
+                commands::BRIDGE_COMMAND,
+                commands::BRIDGE_INIT_GPIO,
+                mask,
+                mode,
+                pull,
+                config.open_drain as u8,
+            ],
+            &[],
+            &mut [0; 8],
+        )
+    }
+
+    /// Sets the outputs selected by `mask` to the corresponding bits of `values`.
+    pub fn set_gpio(&mut self, mask: u8, values: u8) -> Result<(), DebugProbeError> {
+        self.command(
+            &[
+                commands::BRIDGE_COMMAND,
+                commands::BRIDGE_SET_RESET_GPIO,
+                mask,
+                values,
+            ],
+            &[],
+            &mut [0; 8],
+        )
+    }
+
+    /// Reads the GPIO pins selected by `mask`.
+    pub fn read_gpio(&mut self, mask: u8) -> Result<u8, DebugProbeError> {
+        let mut response = [0; 8];
+        self.command(
+            &[commands::BRIDGE_COMMAND, commands::BRIDGE_READ_GPIO, mask],
+            &[],
+            &mut response,
+        )?;
+
+        Ok(response[2] & mask)
+    }
+
+    /// Configures the I2C interface.
+    pub fn init_i2c(&mut self, config: &I2cConfig) -> Result<(), DebugProbeError> {
+        let mut cmd = [0; 11];
+        cmd[0] = commands::BRIDGE_COMMAND;
+        cmd[1] = commands::BRIDGE_INIT_I2C;
+        cmd.pwrite_with(config.timing, 2, LE).unwrap();
+        // Bytes 6 and 7 are the own address, and byte 8 selects 7 bit addressing.
+        cmd[9] = config.analog_filter as u8;
+        cmd[10] = config.digital_filter.min(15);
+
+        self.command(&cmd, &[], &mut [0; 8])
+    }
+
+    /// Writes `data` to the I2C device with the 7 bit `address`.
+    pub fn i2c_write(&mut self, address: u8, data: &[u8]) -> Result<(), DebugProbeError> {
+        let cmd = transfer_command(commands::BRIDGE_WRITE_I2C, data.len(), Some(address))?;
+
+        self.device
+            .write(&cmd, data, &mut [], TIMEOUT)
+            .map_err(DebugProbeError::from)?;
+        self.transfer_status()
+    }
+
+    /// Reads `data` from the I2C device with the 7 bit `address`.
+    pub fn i2c_read(&mut self, address: u8, data: &mut [u8]) -> Result<(), DebugProbeError> {
+        let cmd = transfer_command(commands::BRIDGE_READ_I2C, data.len(), Some(address))?;
+
+        self.device
+            .write(&cmd, &[], data, TIMEOUT)
+            .map_err(DebugProbeError::from)?;
+        self.transfer_status()
+    }
+
+    /// Configures the SPI interface.
+    pub fn init_spi(&mut self, config: &SpiConfig) -> Result<(), DebugProbeError> {
+        let prescaler = match config.prescaler {
+            2 => 0,
+            4 => 1,
+            8 => 2,
+            16 => 3,
+            32 => 4,
+            64 => 5,
+            128 => 6,
+            256 => 7,
+            _ => return Err(StlinkError::BridgeCommandFailed(BridgeStatus::BadParameter).into()),
+        };
+
+        self.command(
+            &[
+                commands::BRIDGE_COMMAND,
+                commands::BRIDGE_INIT_SPI,
+                // Full duplex with two lines.
+                0,
+                // Controller mode.
+                1,
+                // 8 bit words.
+                0,
+                config.clock_idle_high as u8,
+                config.capture_on_second_edge as u8,
+                config.lsb_first as u8,
+                // Motorola frame format.
+                0,
+                // Software chip select.
+                0,
+                // No chip select pulse.
+                0,
+                prescaler,
+            ],
+            &[],
+            &mut [0; 8],
+        )
+    }
+
+    /// Drives the chip select of the SPI interface, which is active low.
+    pub fn spi_chip_select(&mut self, active: bool) -> Result<(), DebugProbeError> {
+        self.command(
+            &[
+                commands::BRIDGE_COMMAND,
+                commands::BRIDGE_CS_SPI,
+                !active as u8,
+            ],
+            &[],
+            &mut [0; 8],
+        )
+    }
+
+    /// Writes `data` on the SPI interface, ignoring the received data.
+    pub fn spi_write(&mut self, data: &[u8]) -> Result<(), DebugProbeError> {
+        let cmd = transfer_command(commands::BRIDGE_WRITE_SPI, data.len(), None)?;
+
+        self.device
+            .write(&cmd, data, &mut [], TIMEOUT)
+            .map_err(DebugProbeError::from)?;
+        self.transfer_status()
+    }
+
+    /// Reads `data` from the SPI interface.
+    pub fn spi_read(&mut self, data: &mut [u8]) -> Result<(), DebugProbeError> {
+        let cmd = transfer_command(commands::BRIDGE_READ_SPI, data.len(), None)?;
+
+        self.device
+            .write(&cmd, &[], data, TIMEOUT)
+            .map_err(DebugProbeError::from)?;
+        self.transfer_status()
+    }
+
+    /// Configures the CAN interface, and starts receiving all messages.
+    pub fn init_can(&mut self, config: &CanConfig) -> Result<(), DebugProbeError> {
+        let mode = match config.mode {
+            CanMode::Normal => 0,
+            CanMode::Loopback => 1,
+            CanMode::Silent => 2,
+            CanMode::SilentLoopback => 3,
+        };
+
+        let mut cmd = [0; 8];
+        cmd[0] = commands::BRIDGE_COMMAND;
+        cmd[1] = commands::BRIDGE_INIT_CAN;
+        cmd.pwrite_with(config.prescaler, 2, LE).unwrap();
+        cmd[4] = mode;
+        cmd[5] = config.sjw;
+        cmd[6] = config.bs1;
+        cmd[7] = config.bs2;
+        self.command(&cmd, &[], &mut [0; 8])?;
+
+        // Filter 0 accepts all messages: 32 bit mask mode with
+        // an empty mask, which stores messages in FIFO 0.
+        self.command(
+            &[
+                commands::BRIDGE_COMMAND,
+                commands::BRIDGE_INIT_FILTER_CAN,
+                // Filter number
+                0,
+                // Activate
+                1,
+                // Mask mode
+                0,
+                // 32 bit scale
+                1,
+                // FIFO 0
+                0,
+            ],
+            &[],
+            &mut [0; 8],
+        )?;
+
+        self.command(
+            &[
+                commands::BRIDGE_COMMAND,
+                commands::BRIDGE_START_MSG_RECEPTION_CAN,
+            ],
+            &[],
+            &mut [0; 8],
+        )
+    }
+
+    /// Stops receiving CAN messages.
+    pub fn stop_can(&mut self) -> Result<(), DebugProbeError> {
+        self.command(
+            &[
+                commands::BRIDGE_COMMAND,
+                commands::BRIDGE_STOP_MSG_RECEPTION_CAN,
+            ],
+            &[],
+            &mut [0; 8],
+        )
+    }
+
+    /// Sends a CAN message.
+    pub fn can_write(&mut self, message: &CanMessage) -> Result<(), DebugProbeError> {
+        if message.data.len() > 8 {
+            return Err(StlinkError::BridgeTransferTooLarge {
+                len: message.data.len(),
+                max: 8,
+            }
+            .into());
+        }
+
+        let mut cmd = [0; 9];
+        cmd[0] = commands::BRIDGE_COMMAND;
+        cmd[1] = commands::BRIDGE_WRITE_MSG_CAN;
+        cmd.pwrite_with(message.id, 2, LE).unwrap();
+        cmd[6] = message.extended as u8;
+        cmd[7] = message.remote as u8;
+        cmd[8] = message.data.len() as u8;
+
+        self.device
+            .write(&cmd, &message.data, &mut [], TIMEOUT)
+            .map_err(DebugProbeError::from)?;
+        self.transfer_status()
+    }
+
+    /// Reads the CAN messages which were received since the last call.
+    pub fn can_read(&mut self) -> Result<Vec<CanMessage>, DebugProbeError> {
+        let mut response = [0; 8];
+        self.command(
+            &[commands::BRIDGE_COMMAND, commands::BRIDGE_GET_NB_MSG_CAN],
+            &[],
+            &mut response,
+        )?;
+        let count: u16 = response.pread_with(2, LE).unwrap();
+
+        if count == 0 {
+            return Ok(vec![]);
+        }
+
+        let mut cmd = [0; 4];
+        cmd[0] = commands::BRIDGE_COMMAND;
+        cmd[1] = commands::BRIDGE_READ_MSG_CAN;
+        cmd.pwrite_with(count, 2, LE).unwrap();
+
+        let mut messages = vec![0; count as usize * CAN_MESSAGE_SIZE];
+        self.device
+            .write(&cmd, &[], &mut messages, TIMEOUT)
+            .map_err(DebugProbeError::from)?;
+        self.transfer_status()?;
+
+        Ok(messages
+            .chunks_exact(CAN_MESSAGE_SIZE)
+            .map(|message| {
+                let len = (message[6] as usize).min(8);
+
+                CanMessage {
+                    id: message.pread_with(0, LE).unwrap(),
+                    extended: message[4] != 0,
+                    remote: message[5] != 0,
+                    data: message[8..8 + len].to_vec(),
+                }
+            })
+            .collect())
+    }
+
+    /// Sends a command, and checks the status at the start of the response.
+    fn command(
+        &mut self,
+        cmd: &[u8],
+        write_data: &[u8],
+        response: &mut [u8],
+    ) -> Result<(), DebugProbeError> {
+        self.device.write(cmd, write_data, response, TIMEOUT)?;
+
+        check_status(response)
+    }
+
+    /// Reads the status of the last data transfer.
+    fn transfer_status(&mut self) -> Result<(), DebugProbeError> {
+        self.command(
+            &[commands::BRIDGE_COMMAND, commands::BRIDGE_GET_RWCMD_STATUS],
+            &[],
+            &mut [0; 8],
+        )
+    }
+}
+
+/// Builds the command of an SPI or I2C transfer of `len` bytes.
+fn transfer_command(
+    command: u8,
+    len: usize,
+    address: Option<u8>,
+) -> Result<Vec<u8>, DebugProbeError> {
+    if len > MAX_TRANSFER_SIZE {
+        return Err(StlinkError::BridgeTransferTooLarge {
+            len,
+            max: MAX_TRANSFER_SIZE,
+        }
+        .into());
+    }
+
+    let mut cmd = vec![commands::BRIDGE_COMMAND, command];
+    cmd.extend_from_slice(&(len as u16).to_le_bytes());
+    if let Some(address) = address {
+        cmd.extend_from_slice(&(address as u16).to_le_bytes());
+    }
+
+    Ok(cmd)
+}
+
+/// Checks the status in the first two bytes of a bridge response.
+fn check_status(response: &[u8]) -> Result<(), DebugProbeError> {
+    match BridgeStatus::from(u16::from_le_bytes([response[0], response[1]])) {
+        BridgeStatus::Ok => Ok(()),
+        status => {
+            tracing::warn!("Bridge command failed with status {:?}", status);
+            Err(StlinkError::BridgeCommandFailed(status).into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    /// The sent commands, with the data written with each of them.
+    type CommandLog = Vec<(Vec<u8>, Vec<u8>)>;
+    type Commands = Arc<Mutex<CommandLog>>;
+
+    /// A bridge which records the commands, and answers them from a queue of responses.
+    ///
+    /// Commands without a response phase don't consume a response, and an empty
+    /// queue answers with the OK status.
+    #[derive(Debug)]
+    struct MockBridge {
+        commands: Commands,
+        responses: VecDeque<Vec<u8>>,
+    }
+
+    impl StLinkUsb for MockBridge {
+        fn write(
+            &mut self,
+            cmd: &[u8],
+            write_data: &[u8],
+            read_data: &mut [u8],
+            _timeout: std::time::Duration,
+        ) -> Result<(), StlinkError> {
+            self.commands
+                .lock()
+                .unwrap()
+                .push((cmd.to_vec(), write_data.to_vec()));
+
+            if !read_data.is_empty() {
+                let response = self.responses.pop_front().unwrap_or_else(|| vec![0x80]);
+                read_data[..response.len()].copy_from_slice(&response);
+            }
+
+            Ok(())
+        }
+
+        fn reset(&mut self) -> Result<(), DebugProbeError> {
+            Ok(())
+        }
+
+        fn read_swo(
+            &mut self,
+            _read_data: &mut [u8],
+            _timeout: std::time::Duration,
+        ) -> Result<usize, DebugProbeError> {
+            Ok(0)
+        }
+    }
+
+    /// Runs `f` on a bridge with the given responses, and returns the sent commands.
+    fn run<T>(
+        responses: Vec<Vec<u8>>,
+        f: impl FnOnce(&mut StLinkBridge) -> Result<T, DebugProbeError>,
+    ) -> (Result<T, DebugProbeError>, CommandLog) {
+        let commands = Commands::default();
+        let mut bridge = StLinkBridge::new(Box::new(MockBridge {
+            commands: commands.clone(),
+            responses: responses.into(),
+        }));
+
+        let result = f(&mut bridge);
+
+        let commands = commands.lock().unwrap().clone();
+        (result, commands)
+    }
+
+    #[test]
+    fn gpio() {
+        let (result, commands) = run(vec![vec![0x80], vec![0x80, 0x00, 0b1010]], |bridge| {
+            bridge.set_gpio(0b0011, 0b0001)?;
+            bridge.read_gpio(0b1110)
+        });
+
+        assert_eq!(result.unwrap(), 0b1010);
+        assert_eq!(commands[0].0, vec![0xfc, 0x61, 0b0011, 0b0001]);
+        assert_eq!(commands[1].0, vec![0xfc, 0x62, 0b1110]);
+    }
+
+    #[test]
+    fn i2c_write() {
+        let (result, commands) = run(vec![], |bridge| bridge.i2c_write(0x50, &[1, 2, 3]));
+
+        result.unwrap();
+        assert_eq!(commands.len(), 2);
+        assert_eq!(
+            commands[0],
+            (vec![0xfc, 0x31, 3, 0, 0x50, 0], vec![1, 2, 3])
+        );
+        // The status of the transfer is read afterwards.
+        assert_eq!(commands[1].0, vec![0xfc, 0x02]);
+    }
+
+    #[test]
+    fn i2c_read_failure() {
+        // The data is read, but the transfer status reports an I2C error.
+        let (result, _) = run(vec![vec![0xaa, 0xbb], vec![0x03, 0x00]], |bridge| {
+            let mut data = [0; 2];
+            bridge.i2c_read(0x50, &mut data)
+        });
+
+        assert!(matches!(
+            result,
+            Err(DebugProbeError::ProbeSpecific(error))
+                if error.to_string().contains("I2cError")
+        ));
+    }
+
+    #[test]
+    fn oversized_transfer() {
+        let (result, commands) = run(vec![], |bridge| {
+            bridge.spi_write(&vec![0; MAX_TRANSFER_SIZE + 1])
+        });
+
+        assert!(result.is_err());
+        assert!(commands.is_empty());
+    }
+
+    #[test]
+    fn spi_init() {
+        let config = SpiConfig {
+            clock_idle_high: true,
+            capture_on_second_edge: true,
+            lsb_first: false,
+            prescaler: 16,
+        };
+        let (result, commands) = run(vec![], |bridge| bridge.init_spi(&config));
+
+        result.unwrap();
+        assert_eq!(
+            commands[0].0,
+            vec![0xfc, 0x20, 0, 1, 0, 1, 1, 0, 0, 0, 0, 3]
+        );
+
+        let (result, commands) = run(vec![], |bridge| {
+            bridge.init_spi(&SpiConfig {
+                prescaler: 3,
+                ..config
+            })
+        });
+        assert!(result.is_err());
+        assert!(commands.is_empty());
+    }
+
+    #[test]
+    fn can_read() {
+        let mut message = vec![0; CAN_MESSAGE_SIZE];
+        message[..4].copy_from_slice(&0x123u32.to_le_bytes());
+        message[6] = 2;
+        message[8] = 0xde;
+        message[9] = 0xad;
+
+        let (result, commands) = run(
+            vec![vec![0x80, 0x00, 1, 0], message, vec![0x80]],
+            |bridge| bridge.can_read(),
+        );
+
+        assert_eq!(
+            result.unwrap(),
+            vec![CanMessage {
+                id: 0x123,
+                extended: false,
+                remote: false,
+                data: vec![0xde, 0xad],
+            }]
+        );
+        assert_eq!(commands[1].0, vec![0xfc, 0x42, 1, 0]);
+    }
+
+    #[test]
+    fn can_write() {
+        let message = CanMessage {
+            id: 0x1abc_def0,
+            extended: true,
+            remote: false,
+            data: vec![1, 2],
+        };
+        let (result, commands) = run(vec![], |bridge| bridge.can_write(&message));
+
+        result.unwrap();
+        assert_eq!(
+            commands[0],
+            (
+                vec![0xfc, 0x41, 0xf0, 0xde, 0xbc, 0x1a, 1, 0, 2],
+                vec![1, 2]
+            )
+        );
+    }
+}
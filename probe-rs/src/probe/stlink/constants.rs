@@ -66,6 +66,35 @@ pub mod commands {
     // Parameters for SET_COM_FREQ and GET_COM_FREQ.
     pub const JTAG_STLINK_SWD_COM: u8 = 0x00;
     pub const JTAG_STLINK_JTAG_COM: u8 = 0x01;
+
+    // Commands of the bridge interface of the ST-Link V3.
+    pub const BRIDGE_COMMAND: u8 = 0xfc;
+    pub const BRIDGE_CLOSE: u8 = 0x01;
+    pub const BRIDGE_GET_RWCMD_STATUS: u8 = 0x02;
+    pub const BRIDGE_GET_CLOCK: u8 = 0x03;
+    pub const BRIDGE_INIT_SPI: u8 = 0x20;
+    pub const BRIDGE_WRITE_SPI: u8 = 0x21;
+    pub const BRIDGE_READ_SPI: u8 = 0x22;
+    pub const BRIDGE_CS_SPI: u8 = 0x23;
+    pub const BRIDGE_INIT_I2C: u8 = 0x30;
+    pub const BRIDGE_WRITE_I2C: u8 = 0x31;
+    pub const BRIDGE_READ_I2C: u8 = 0x32;
+    pub const BRIDGE_INIT_CAN: u8 = 0x40;
+    pub const BRIDGE_WRITE_MSG_CAN: u8 = 0x41;
+    pub const BRIDGE_READ_MSG_CAN: u8 = 0x42;
+    pub const BRIDGE_START_MSG_RECEPTION_CAN: u8 = 0x43;
+    pub const BRIDGE_STOP_MSG_RECEPTION_CAN: u8 = 0x44;
+    pub const BRIDGE_INIT_FILTER_CAN: u8 = 0x45;
+    pub const BRIDGE_GET_NB_MSG_CAN: u8 = 0x47;
+    pub const BRIDGE_INIT_GPIO: u8 = 0x60;
+    pub const BRIDGE_SET_RESET_GPIO: u8 = 0x61;
+    pub const BRIDGE_READ_GPIO: u8 = 0x62;
+
+    // Parameters for BRIDGE_GET_CLOCK and BRIDGE_CLOSE.
+    pub const BRIDGE_COM_SPI: u8 = 0x02;
+    pub const BRIDGE_COM_I2C: u8 = 0x03;
+    pub const BRIDGE_COM_CAN: u8 = 0x04;
+    pub const BRIDGE_COM_GPIO: u8 = 0x06;
 }
 
 /// STLink status codes and messages.
@@ -141,6 +170,43 @@ impl From<u8> for Status {
     }
 }
 
+/// Status codes of the bridge interface of the ST-Link V3.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BridgeStatus {
+    Ok,
+    SpiError,
+    I2cError,
+    CanError,
+    InitNotDone,
+    UnknownCommand,
+    BadParameter,
+    Timeout,
+    Aborted,
+    InternalError,
+    Busy,
+    Other(u16),
+}
+
+impl From<u16> for BridgeStatus {
+    fn from(value: u16) -> BridgeStatus {
+        use BridgeStatus::*;
+        match value {
+            0x80 => Ok,
+            0x02 => SpiError,
+            0x03 => I2cError,
+            0x04 => CanError,
+            0x07 => InitNotDone,
+            0x08 => UnknownCommand,
+            0x09 => BadParameter,
+            0x0A => Timeout,
+            0x0B => Aborted,
+            0x0C => InternalError,
+            0x0D => Busy,
+            v => Other(v),
+        }
+    }
+}
+
 /// Map from SWD frequency in Hertz to delay loop count.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum SwdFrequencyToDelayCount {
@@ -1,3 +1,4 @@
+pub mod bridge;
 pub mod constants;
 pub mod tools;
 mod usb_interface;
@@ -17,11 +18,13 @@ use crate::{
         ApAddress, ApInformation, ArmChipInfo, DapAccess, DpAddress, Pins, SwoAccess, SwoConfig,
         SwoMode,
     },
-    DebugProbeSelector, Error as ProbeRsError, Probe,
+    events, DebugProbeSelector, Error as ProbeRsError, Probe,
+};
+use constants::{
+    commands, BridgeStatus, JTagFrequencyToDivider, Mode, Status, SwdFrequencyToDelayCount,
 };
-use constants::{commands, JTagFrequencyToDivider, Mode, Status, SwdFrequencyToDelayCount};
 use scroll::{Pread, Pwrite, BE, LE};
-use std::{cmp::Ordering, convert::TryInto, sync::Arc, time::Duration};
+use std::{convert::TryInto, sync::Arc, time::Duration};
 use usb_interface::TIMEOUT;
 
 /// Maximum length of 32 bit reads in bytes.
@@ -37,6 +40,9 @@ const STLINK_MAX_WRITE_LEN: usize = 0xFFFC;
 
 const DP_PORT: u16 = 0xFFFF;
 
+/// Size of the SWO trace buffer in the ST-Link.
+const SWO_BUFFER_SIZE: u16 = 4096;
+
 #[derive(Debug)]
 pub(crate) struct StLink<D: StLinkUsb> {
     device: D,
@@ -46,7 +52,8 @@ pub(crate) struct StLink<D: StLinkUsb> {
     protocol: WireProtocol,
     swd_speed_khz: u32,
     jtag_speed_khz: u32,
-    swo_enabled: bool,
+    /// The SWO configuration, if SWO trace reception is enabled.
+    swo_config: Option<SwoConfig>,
 
     /// List of opened APs
     opened_aps: Vec<u8>,
@@ -65,7 +72,7 @@ impl DebugProbe for StLink<StLinkUsbDevice> {
             protocol: WireProtocol::Swd,
             swd_speed_khz: 1_800,
             jtag_speed_khz: 1_120,
-            swo_enabled: false,
+            swo_config: None,
 
             opened_aps: vec![],
         };
@@ -87,8 +94,8 @@ impl DebugProbe for StLink<StLinkUsbDevice> {
     }
 
     fn set_speed(&mut self, speed_khz: u32) -> Result<u32, DebugProbeError> {
-        match self.hw_version.cmp(&3) {
-            Ordering::Less => match self.protocol {
+        if self.hw_version < 3 {
+            match self.protocol {
                 WireProtocol::Swd => {
                     let actual_speed = SwdFrequencyToDelayCount::find_setting(speed_khz);
 
@@ -115,26 +122,23 @@ impl DebugProbe for StLink<StLinkUsbDevice> {
                         Err(DebugProbeError::UnsupportedSpeed(speed_khz))
                     }
                 }
-            },
-            Ordering::Equal => {
-                let (available, _) = self.get_communication_frequencies(self.protocol)?;
-
-                let actual_speed_khz = available
-                    .into_iter()
-                    .filter(|speed| *speed <= speed_khz)
-                    .max()
-                    .ok_or(DebugProbeError::UnsupportedSpeed(speed_khz))?;
+            }
+        } else {
+            // The ST-Link V3 reports the frequencies it supports, which go
+            // up to 24 MHz for SWD.
+            let (available, _) = self.get_communication_frequencies(self.protocol)?;
 
-                self.set_communication_frequency(self.protocol, actual_speed_khz)?;
+            let actual_speed_khz = select_communication_frequency(&available, speed_khz)
+                .ok_or(DebugProbeError::UnsupportedSpeed(speed_khz))?;
 
-                match self.protocol {
-                    WireProtocol::Swd => self.swd_speed_khz = actual_speed_khz,
-                    WireProtocol::Jtag => self.jtag_speed_khz = actual_speed_khz,
-                }
+            self.set_communication_frequency(self.protocol, actual_speed_khz)?;
 
-                Ok(actual_speed_khz)
+            match self.protocol {
+                WireProtocol::Swd => self.swd_speed_khz = actual_speed_khz,
+                WireProtocol::Jtag => self.jtag_speed_khz = actual_speed_khz,
             }
-            Ordering::Greater => unimplemented!(),
+
+            Ok(actual_speed_khz)
         }
     }
 
@@ -162,6 +166,10 @@ impl DebugProbe for StLink<StLinkUsbDevice> {
                 "Target voltage (VAPP) is {:2.2} V. Is your target device powered?",
                 target_voltage
             );
+            events::probe_warning(
+                "ST-Link",
+                format!("Target voltage (VAPP) is {target_voltage:2.2} V"),
+            );
         } else {
             tracing::info!("Target voltage (VAPP): {:2.2} V", target_voltage);
         }
@@ -195,7 +203,7 @@ impl DebugProbe for StLink<StLinkUsbDevice> {
 
     fn detach(&mut self) -> Result<(), crate::Error> {
         tracing::debug!("Detaching from STLink.");
-        if self.swo_enabled {
+        if self.swo_config.is_some() {
             self.disable_swo().map_err(crate::Error::Arm)?;
         }
         self.enter_idle()
@@ -307,7 +315,7 @@ impl DebugProbe for StLink<StLinkUsbDevice> {
 impl<D: StLinkUsb> Drop for StLink<D> {
     fn drop(&mut self) {
         // We ignore the error cases as we can't do much about it anyways.
-        if self.swo_enabled {
+        if self.swo_config.is_some() {
             let _ = self.disable_swo();
         }
         let _ = self.enter_idle();
@@ -371,6 +379,18 @@ impl<D: StLinkUsb> StLink<D> {
     /// FW versions where this is supported.
     const MIN_JTAG_VERSION_DP_BANK_SEL: u8 = 32;
 
+    /// Maximum number of bytes for a single 8-bit write.
+    ///
+    /// 8-bit writes are limited to a single USB packet. The ST-Link V3 uses
+    /// USB high speed, with 512 byte packets instead of 64 byte packets.
+    fn max_8bit_write_len(&self) -> usize {
+        if self.hw_version < 3 {
+            64
+        } else {
+            512
+        }
+    }
+
     /// Get the current mode of the ST-Link
     fn get_current_mode(&mut self) -> Result<Mode, StlinkError> {
         tracing::trace!("Getting current mode of device...");
@@ -398,7 +418,7 @@ impl<D: StLinkUsb> StLink<D> {
     /// If this is not supported, some DP registers cannot be accessed.
     fn supports_dp_bank_selection(&self) -> bool {
         (self.hw_version == 2 && self.jtag_version >= Self::MIN_JTAG_VERSION_DP_BANK_SEL)
-            || self.hw_version == 3
+            || self.hw_version >= 3
     }
 
     /// Commands the ST-Link to enter idle mode.
@@ -477,7 +497,7 @@ impl<D: StLinkUsb> StLink<D> {
         if self.jtag_version == 0 {
             Err(StlinkError::JTAGNotSupportedOnProbe.into())
         } else if (self.hw_version < 3 && self.jtag_version < Self::MIN_JTAG_VERSION)
-            || (self.hw_version == 3 && self.jtag_version < Self::MIN_JTAG_VERSION_V3)
+            || (self.hw_version >= 3 && self.jtag_version < Self::MIN_JTAG_VERSION_V3)
         {
             Err(DebugProbeError::ProbeFirmwareOutdated)
         } else {
@@ -506,7 +526,7 @@ impl<D: StLinkUsb> StLink<D> {
         let version = self.get_version()?;
         tracing::debug!("STLink version: {:?}", version);
 
-        if self.hw_version == 3 {
+        if self.hw_version >= 3 {
             let (_, current) = self.get_communication_frequencies(WireProtocol::Swd)?;
             self.swd_speed_khz = current;
 
@@ -557,13 +577,13 @@ impl<D: StLinkUsb> StLink<D> {
         Ok(())
     }
 
-    /// Sets the communication frequency (V3 only)
+    /// Sets the communication frequency (V3 and newer only)
     fn set_communication_frequency(
         &mut self,
         protocol: WireProtocol,
         frequency_khz: u32,
     ) -> Result<(), DebugProbeError> {
-        if self.hw_version != 3 {
+        if self.hw_version < 3 {
             return Err(DebugProbeError::CommandNotSupportedByProbe(
                 "set_communication_frequency",
             ));
@@ -583,12 +603,12 @@ impl<D: StLinkUsb> StLink<D> {
         Ok(())
     }
 
-    /// Returns the current and available communication frequencies (V3 only)
+    /// Returns the current and available communication frequencies (V3 and newer only)
     fn get_communication_frequencies(
         &mut self,
         protocol: WireProtocol,
     ) -> Result<(Vec<u32>, u32), DebugProbeError> {
-        if self.hw_version != 3 {
+        if self.hw_version < 3 {
             return Err(DebugProbeError::CommandNotSupportedByProbe(
                 "get_communication_frequencies",
             ));
@@ -710,9 +730,27 @@ impl<D: StLinkUsb> StLink<D> {
         }
     }
 
+    /// Maximum SWO baud rate supported by the ST-Link.
+    fn max_swo_baud(&self) -> u32 {
+        if self.hw_version < 3 {
+            2_000_000
+        } else {
+            24_000_000
+        }
+    }
+
     pub fn start_trace_reception(&mut self, config: &SwoConfig) -> Result<(), DebugProbeError> {
+        let max_baud = self.max_swo_baud();
+        if config.baud() > max_baud {
+            return Err(StlinkError::SwoBaudRateNotSupported {
+                baud: config.baud(),
+                max_baud,
+            }
+            .into());
+        }
+
         let mut buf = [0; 2];
-        let bufsize = 4096u16.to_le_bytes();
+        let bufsize = SWO_BUFFER_SIZE.to_le_bytes();
         let baud = config.baud().to_le_bytes();
         let mut command = vec![commands::JTAG_COMMAND, commands::SWO_START_TRACE_RECEPTION];
         command.extend_from_slice(&bufsize);
@@ -720,7 +758,7 @@ impl<D: StLinkUsb> StLink<D> {
 
         self.send_jtag_command(&command, &[], &mut buf, TIMEOUT)?;
 
-        self.swo_enabled = true;
+        self.swo_config = Some(*config);
 
         Ok(())
     }
@@ -735,7 +773,7 @@ impl<D: StLinkUsb> StLink<D> {
             TIMEOUT,
         )?;
 
-        self.swo_enabled = false;
+        self.swo_config = None;
 
         Ok(())
     }
@@ -759,7 +797,12 @@ impl<D: StLinkUsb> StLink<D> {
     fn read_swo_data(&mut self, timeout: Duration) -> Result<Vec<u8>, DebugProbeError> {
         // The byte count always needs to be polled first, otherwise
         // the ST-Link won't return any data.
-        let mut buf = vec![0; self.read_swo_available_byte_count()?];
+        let available = self.read_swo_available_byte_count()?;
+        if available == 0 {
+            return Ok(vec![]);
+        }
+
+        let mut buf = vec![0; available];
         let bytes_read = self.device.read_swo(&mut buf, timeout)?;
         buf.truncate(bytes_read);
         Ok(buf)
@@ -893,6 +936,57 @@ impl<D: StLinkUsb> StLink<D> {
         Ok(())
     }
 
+    /// Reads 16 bit wide values, which requires firmware version J26 or later.
+    fn read_mem_16bit(
+        &mut self,
+        address: u32,
+        data: &mut [u8],
+        apsel: u8,
+    ) -> Result<(), DebugProbeError> {
+        self.select_ap(apsel)?;
+
+        assert!(
+            data.len() <= STLINK_MAX_READ_LEN,
+            "Maximum read length for STLink is {STLINK_MAX_READ_LEN} bytes"
+        );
+
+        assert!(
+            data.len() & 0x1 == 0,
+            "Data length has to be a multiple of 2 for 16 bit reads"
+        );
+
+        if address & 0x1 != 0 {
+            return Err(DebugProbeError::from(StlinkError::UnalignedAddress));
+        }
+
+        let data_length = data.len().to_le_bytes();
+
+        let addbytes = address.to_le_bytes();
+
+        retry_on_wait(|| {
+            self.device.write(
+                &[
+                    commands::JTAG_COMMAND,
+                    commands::JTAG_READMEM_16BIT,
+                    addbytes[0],
+                    addbytes[1],
+                    addbytes[2],
+                    addbytes[3],
+                    data_length[0],
+                    data_length[1],
+                    apsel,
+                ],
+                &[],
+                data,
+                TIMEOUT,
+            )?;
+
+            self.get_last_rw_status()
+        })?;
+
+        Ok(())
+    }
+
     fn read_mem_8bit(
         &mut self,
         address: u32,
@@ -1010,6 +1104,58 @@ impl<D: StLinkUsb> StLink<D> {
         Ok(())
     }
 
+    /// Writes 16 bit wide values, which requires firmware version J26 or later.
+    fn write_mem_16bit(
+        &mut self,
+        address: u32,
+        data: &[u8],
+        apsel: u8,
+    ) -> Result<(), DebugProbeError> {
+        self.select_ap(apsel)?;
+
+        tracing::trace!("write_mem_16bit");
+        let length = data.len();
+
+        assert!(
+            length <= STLINK_MAX_WRITE_LEN,
+            "Maximum write length for STLink is {STLINK_MAX_WRITE_LEN} bytes"
+        );
+
+        assert!(
+            data.len() & 0x1 == 0,
+            "Data length has to be a multiple of 2 for 16 bit writes"
+        );
+
+        if address & 0x1 != 0 {
+            return Err(DebugProbeError::from(StlinkError::UnalignedAddress));
+        }
+
+        let addbytes = address.to_le_bytes();
+        let lenbytes = length.to_le_bytes();
+        retry_on_wait(|| {
+            self.device.write(
+                &[
+                    commands::JTAG_COMMAND,
+                    commands::JTAG_WRITEMEM_16BIT,
+                    addbytes[0],
+                    addbytes[1],
+                    addbytes[2],
+                    addbytes[3],
+                    lenbytes[0],
+                    lenbytes[1],
+                    apsel,
+                ],
+                data,
+                &mut [],
+                TIMEOUT,
+            )?;
+
+            self.get_last_rw_status()
+        })?;
+
+        Ok(())
+    }
+
     fn write_mem_8bit(
         &mut self,
         address: u32,
@@ -1021,17 +1167,12 @@ impl<D: StLinkUsb> StLink<D> {
         tracing::trace!("write_mem_8bit");
         let byte_length = data.len();
 
-        if self.hw_version < 3 {
-            assert!(
-                byte_length <= 64,
-                "8-Bit writes are limited to 64 bytes on ST-Link v2"
-            );
-        } else {
-            assert!(
-                byte_length <= 512,
-                "8-Bit writes are limited to 512 bytes on ST-Link v3"
-            );
-        }
+        assert!(
+            byte_length <= self.max_8bit_write_len(),
+            "8-Bit writes are limited to {} bytes on ST-Link v{}",
+            self.max_8bit_write_len(),
+            self.hw_version
+        );
 
         let addbytes = address.to_le_bytes();
         let lenbytes = byte_length.to_le_bytes();
@@ -1117,9 +1258,34 @@ impl<D: StLinkUsb> SwoAccess for StLink<D> {
         Ok(())
     }
 
+    fn swo_buffer_size(&mut self) -> Option<usize> {
+        Some(SWO_BUFFER_SIZE.into())
+    }
+
     fn read_swo_timeout(&mut self, timeout: Duration) -> Result<Vec<u8>, ArmError> {
-        let data = self.read_swo_data(timeout)?;
-        Ok(data)
+        let Some(config) = self.swo_config else {
+            return Err(DebugProbeError::ProbeSpecific(StlinkError::SwoNotEnabled.into()).into());
+        };
+
+        let end = std::time::Instant::now() + timeout;
+
+        let poll_interval = self
+            .swo_poll_interval_hint(&config)
+            .unwrap_or(Duration::from_millis(1));
+
+        // The ST-Link buffers the trace data, so we poll it until
+        // some data arrives or the timeout expires.
+        loop {
+            let remaining = end.saturating_duration_since(std::time::Instant::now());
+            let bytes = self.read_swo_data(remaining.max(Duration::from_millis(1)))?;
+
+            let now = std::time::Instant::now();
+            if !bytes.is_empty() || now + poll_interval >= end {
+                return Ok(bytes);
+            }
+
+            std::thread::sleep(poll_interval);
+        }
     }
 }
 
@@ -1138,12 +1304,22 @@ pub(crate) enum StlinkError {
     NotEnoughBytesWritten { is: usize, should: usize },
     #[error("Usb endpoint not found.")]
     EndpointNotFound,
+    #[error("The probe has no bridge interface. Only the ST-Link V3 has a bridge.")]
+    BridgeNotFound,
+    #[error("Bridge command failed with status {0:?}")]
+    BridgeCommandFailed(BridgeStatus),
+    #[error("Bridge transfers are limited to {max} bytes, {len} were requested")]
+    BridgeTransferTooLarge { len: usize, max: usize },
     #[error("Command failed with status {0:?}")]
     CommandFailed(Status),
     #[error("JTAG not supported on Probe")]
     JTAGNotSupportedOnProbe,
     #[error("Manchester-coded SWO mode not supported")]
     ManchesterSwoNotSupported,
+    #[error("SWO baud rate {baud} is not supported, the maximum is {max_baud}")]
+    SwoBaudRateNotSupported { baud: u32, max_baud: u32 },
+    #[error("SWO trace reception is not enabled")]
+    SwoNotEnabled,
     #[error("Multidrop SWD not supported")]
     MultidropNotSupported,
     #[error("Unaligned")]
@@ -1350,7 +1526,9 @@ impl ArmProbeInterface for StlinkArmDebug {
 
                 let component = Component::try_parse(&mut *memory, baseaddr)?;
 
-                if let Component::Class1RomTable(component_id, _) = component {
+                if let Component::Class1RomTable(component_id, _)
+                | Component::Class9RomTable(component_id, _) = component
+                {
                     if let Some(jep106) = component_id.peripheral_id().jep106() {
                         return Ok(Some(ArmChipInfo {
                             manufacturer: jep106,
@@ -1402,6 +1580,10 @@ impl SwoAccess for StlinkArmDebug {
         self.probe.disable_swo()
     }
 
+    fn swo_buffer_size(&mut self) -> Option<usize> {
+        self.probe.swo_buffer_size()
+    }
+
     fn read_swo_timeout(&mut self, timeout: Duration) -> Result<Vec<u8>, ArmError> {
         self.probe.read_swo_timeout(timeout)
     }
@@ -1472,6 +1654,27 @@ impl ArmProbe for StLinkMemoryInterface<'_> {
         Ok(())
     }
 
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), ArmError> {
+        let address = valid_32bit_arm_address(address)?;
+
+        // Read needs to be chunked into chunks with appropiate max length (see STLINK_MAX_READ_LEN).
+        for (index, chunk) in data.chunks_mut(STLINK_MAX_READ_LEN / 2).enumerate() {
+            let mut buff = vec![0u8; 2 * chunk.len()];
+
+            self.probe.probe.read_mem_16bit(
+                address + (index * STLINK_MAX_READ_LEN) as u32,
+                &mut buff,
+                self.current_ap.ap_address().ap,
+            )?;
+
+            for (index, half_word) in buff.chunks_exact(2).enumerate() {
+                chunk[index] = u16::from_le_bytes([half_word[0], half_word[1]]);
+            }
+        }
+
+        Ok(())
+    }
+
     fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), ArmError> {
         let address = valid_32bit_arm_address(address)?;
 
@@ -1545,19 +1748,31 @@ impl ArmProbe for StLinkMemoryInterface<'_> {
         Ok(())
     }
 
+    fn write_16(&mut self, address: u64, data: &[u16]) -> Result<(), ArmError> {
+        let address = valid_32bit_arm_address(address)?;
+
+        let tx_buffer: Vec<u8> = data.iter().flat_map(|value| value.to_le_bytes()).collect();
+
+        for (index, chunk) in tx_buffer.chunks(STLINK_MAX_WRITE_LEN).enumerate() {
+            self.probe.probe.write_mem_16bit(
+                address + (index * STLINK_MAX_WRITE_LEN) as u32,
+                chunk,
+                self.current_ap.ap_address().ap,
+            )?;
+        }
+
+        Ok(())
+    }
+
     fn write_8(&mut self, address: u64, data: &[u8]) -> Result<(), ArmError> {
         let address = valid_32bit_arm_address(address)?;
 
         // The underlying STLink command is limited to a single USB frame at a time
         // so we must manually chunk it into multiple command if it exceeds
         // that size.
-        let chunk_size = if self.probe.probe.hw_version < 3 {
-            64
-        } else {
-            512
-        };
+        let chunk_size = self.probe.probe.max_8bit_write_len();
 
-        // If we write less than 64 bytes, just write it directly
+        // If we write less than a single chunk, just write it directly
         if data.len() < chunk_size {
             tracing::trace!("write_8: small - direct 8 bit write to {:08x}", address);
             self.probe
@@ -1669,6 +1884,7 @@ fn retry_on_wait<R>(mut f: impl FnMut() -> Result<R, StlinkError>) -> Result<R,
             Err(e) => {
                 if is_wait_error(&e) {
                     tracing::warn!("got SwdDpWait/SwdApWait, retrying.");
+                    events::dap_retry("ST-Link", "WAIT response", attempt as usize + 1);
                     last_err = Some(e);
                 } else {
                     return Err(e);
@@ -1681,11 +1897,26 @@ fn retry_on_wait<R>(mut f: impl FnMut() -> Result<R, StlinkError>) -> Result<R,
     }
 
     tracing::warn!("too many retries, giving up");
+    events::probe_warning(
+        "ST-Link",
+        "Too many retries after WAIT responses, giving up",
+    );
 
     // Return the last error (will be SwdDpWait or SwdApWait)
     Err(last_err.unwrap())
 }
 
+/// Selects the highest frequency in `available` which does not exceed `requested_khz`.
+///
+/// Returns `None` if all available frequencies are higher than the requested one.
+fn select_communication_frequency(available: &[u32], requested_khz: u32) -> Option<u32> {
+    available
+        .iter()
+        .copied()
+        .filter(|speed| *speed <= requested_khz)
+        .max()
+}
+
 #[cfg(test)]
 mod test {
 
@@ -1693,7 +1924,7 @@ mod test {
 
     use scroll::Pwrite;
 
-    #[derive(Debug)]
+    #[derive(Debug, Default)]
     struct MockUsb {
         hw_version: u8,
         jtag_version: u8,
@@ -1701,8 +1932,17 @@ mod test {
 
         target_voltage_a0: f32,
         _target_voltage_a1: f32,
+
+        /// Current communication frequency, only used for V3.
+        com_freq_khz: u32,
+
+        /// SWO data which has not been read yet.
+        swo_data: Vec<u8>,
     }
 
+    /// SWD frequencies reported by a ST-Link V3.
+    const V3_SWD_FREQUENCIES_KHZ: [u32; 7] = [24_000, 8_000, 3_300, 1_000, 200, 50, 5];
+
     impl MockUsb {
         fn build(self) -> StLink<MockUsb> {
             StLink {
@@ -1713,7 +1953,7 @@ mod test {
                 jtag_version: 0,
                 swd_speed_khz: 0,
                 jtag_speed_khz: 0,
-                swo_enabled: false,
+                swo_config: None,
                 opened_aps: vec![],
             }
         }
@@ -1751,6 +1991,39 @@ mod test {
                     read_data.pwrite(self.target_voltage_a0, 4).unwrap();
                     Ok(())
                 }
+                commands::GET_VERSION_EXT => {
+                    read_data[0] = self.hw_version;
+                    read_data[1] = self.swim_version;
+                    read_data[2] = self.jtag_version;
+
+                    Ok(())
+                }
+                commands::JTAG_COMMAND if cmd[1] == commands::GET_COM_FREQ => {
+                    read_data[0] = 0x80;
+                    read_data.pwrite_with(self.com_freq_khz, 4, LE).unwrap();
+                    read_data
+                        .pwrite_with(V3_SWD_FREQUENCIES_KHZ.len() as u32, 8, LE)
+                        .unwrap();
+
+                    for (i, frequency) in V3_SWD_FREQUENCIES_KHZ.iter().enumerate() {
+                        read_data.pwrite_with(*frequency, 12 + 4 * i, LE).unwrap();
+                    }
+
+                    Ok(())
+                }
+                commands::JTAG_COMMAND if cmd[1] == commands::SWO_GET_TRACE_NEW_RECORD_NB => {
+                    read_data
+                        .pwrite_with(self.swo_data.len() as u16, 0, LE)
+                        .unwrap();
+
+                    Ok(())
+                }
+                commands::JTAG_COMMAND if cmd[1] == commands::SET_COM_FREQ => {
+                    read_data[0] = 0x80;
+                    self.com_freq_khz = cmd.pread_with(4, LE).unwrap();
+
+                    Ok(())
+                }
                 commands::JTAG_COMMAND => {
                     // Return a status of OK for JTAG commands
                     read_data[0] = 0x80;
@@ -1766,10 +2039,13 @@ mod test {
 
         fn read_swo(
             &mut self,
-            _read_data: &mut [u8],
+            read_data: &mut [u8],
             _timeout: std::time::Duration,
         ) -> Result<usize, DebugProbeError> {
-            unimplemented!("Not implemented for MockUSB")
+            let len = read_data.len().min(self.swo_data.len());
+            read_data[..len].copy_from_slice(&self.swo_data[..len]);
+            self.swo_data.drain(..len);
+            Ok(len)
         }
     }
 
@@ -1784,6 +2060,7 @@ mod test {
 
             target_voltage_a0: 1.0,
             _target_voltage_a1: 2.0,
+            ..Default::default()
         };
 
         let mut probe = usb_mock.build();
@@ -1807,6 +2084,7 @@ mod test {
             swim_version: 0,
             target_voltage_a0: 1.0,
             _target_voltage_a1: 2.0,
+            ..Default::default()
         };
 
         let mut probe = usb_mock.build();
@@ -1832,6 +2110,7 @@ mod test {
             swim_version: 0,
             target_voltage_a0: 1.0,
             _target_voltage_a1: 2.0,
+            ..Default::default()
         };
 
         let mut probe = usb_mock.build();
@@ -1846,6 +2125,84 @@ mod test {
             .expect("Selecting AP other than AP 0 should work");
     }
 
+    #[test]
+    fn v3_communication_frequencies() {
+        let usb_mock = MockUsb {
+            hw_version: 3,
+            jtag_version: 7,
+            swim_version: 0,
+            target_voltage_a0: 1.0,
+            _target_voltage_a1: 2.0,
+            com_freq_khz: 8_000,
+            ..Default::default()
+        };
+
+        let mut probe = usb_mock.build();
+
+        probe.init().expect("Init function failed");
+        assert_eq!(probe.swd_speed_khz, 8_000);
+
+        let (available, current) = probe
+            .get_communication_frequencies(WireProtocol::Swd)
+            .unwrap();
+        assert_eq!(available, V3_SWD_FREQUENCIES_KHZ);
+        assert_eq!(current, 8_000);
+
+        probe
+            .set_communication_frequency(WireProtocol::Swd, 24_000)
+            .unwrap();
+        assert_eq!(probe.device.com_freq_khz, 24_000);
+    }
+
+    #[test]
+    fn swo_reception() {
+        let usb_mock = MockUsb {
+            hw_version: 2,
+            jtag_version: 30,
+            swo_data: vec![1, 2, 3],
+            ..Default::default()
+        };
+
+        let mut probe = usb_mock.build();
+
+        probe.init().expect("Init function failed");
+
+        // Reading without enabling SWO fails.
+        assert!(probe.read_swo().is_err());
+
+        // Baud rates above the maximum of the ST-Link V2 are rejected.
+        assert!(probe
+            .enable_swo(&SwoConfig::new(0).set_baud(4_000_000))
+            .is_err());
+
+        probe
+            .enable_swo(&SwoConfig::new(0).set_baud(1_000_000))
+            .unwrap();
+        assert_eq!(probe.swo_buffer_size(), Some(SWO_BUFFER_SIZE.into()));
+
+        assert_eq!(probe.read_swo().unwrap(), vec![1, 2, 3]);
+        assert!(probe.read_swo().unwrap().is_empty());
+
+        probe.disable_swo().unwrap();
+        assert!(probe.swo_config.is_none());
+    }
+
+    #[test]
+    fn select_frequency() {
+        assert_eq!(
+            select_communication_frequency(&V3_SWD_FREQUENCIES_KHZ, 24_000),
+            Some(24_000)
+        );
+        assert_eq!(
+            select_communication_frequency(&V3_SWD_FREQUENCIES_KHZ, 23_999),
+            Some(8_000)
+        );
+        assert_eq!(
+            select_communication_frequency(&V3_SWD_FREQUENCIES_KHZ, 4),
+            None
+        );
+    }
+
     #[test]
     fn test_is_wait_error() {
         assert!(!is_wait_error(&StlinkError::BanksNotAllowedOnDPRegister));
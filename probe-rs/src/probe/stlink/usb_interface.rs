@@ -32,6 +32,7 @@ pub static USB_PID_EP_MAP: Lazy<HashMap<u16, StLinkInfo>> = Lazy::new(|| {
     m.insert(0x374f, StLinkInfo::new("V3", 0x374f, 0x01, 0x81, 0x82)); // Bridge
     m.insert(0x3753, StLinkInfo::new("V3", 0x3753, 0x01, 0x81, 0x82)); // 2VCP
     m.insert(0x3754, StLinkInfo::new("V3", 0x3754, 0x01, 0x81, 0x82)); // Without mass storage
+    m.insert(0x3757, StLinkInfo::new("V3PWR", 0x3757, 0x01, 0x81, 0x82));
     m
 });
 
@@ -61,10 +62,40 @@ impl StLinkInfo {
             ep_swo,
         }
     }
+
+    /// Checks if an interface with the given endpoint addresses is the debug interface.
+    fn has_debug_endpoints(&self, endpoints: &[u8]) -> bool {
+        [self.ep_out, self.ep_in, self.ep_swo]
+            .iter()
+            .all(|endpoint| endpoints.contains(endpoint))
+    }
+
+    /// Returns the OUT and IN endpoint of the bridge interface of an ST-Link V3,
+    /// if the interface with the given class and endpoint addresses is the bridge.
+    ///
+    /// The bridge is the vendor specific interface besides the debug interface.
+    fn bridge_endpoints(&self, class_code: u8, endpoints: &[u8]) -> Option<(u8, u8)> {
+        if !self.version_name.starts_with("V3")
+            || class_code != VENDOR_SPECIFIC_CLASS
+            || self.has_debug_endpoints(endpoints)
+        {
+            return None;
+        }
+
+        let ep_out = endpoints.iter().find(|&&ep| ep & 0x80 == 0)?;
+        let ep_in = endpoints.iter().find(|&&ep| ep & 0x80 != 0)?;
+
+        Some((*ep_out, *ep_in))
+    }
 }
 
+/// The USB class code of vendor specific interfaces.
+const VENDOR_SPECIFIC_CLASS: u8 = 0xff;
+
 pub(crate) struct StLinkUsbDevice {
     device_handle: DeviceHandle<rusb::Context>,
+    /// Number of the claimed debug interface.
+    interface: u8,
     pub(crate) info: StLinkInfo,
 }
 
@@ -72,6 +103,7 @@ impl std::fmt::Debug for StLinkUsbDevice {
     fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         fmt.debug_struct("StLinkUsbDevice")
             .field("device_handle", &"DeviceHandle<rusb::Context>")
+            .field("interface", &self.interface)
             .field("info", &self.info)
             .finish()
     }
@@ -102,8 +134,18 @@ impl StLinkUsbDevice {
     pub fn new_from_selector(
         selector: impl Into<DebugProbeSelector>,
     ) -> Result<Self, ProbeCreationError> {
-        let selector = selector.into();
+        Self::open(selector.into(), false)
+    }
+
+    /// Opens the bridge interface of an ST-Link V3, which provides access to the
+    /// SPI, I2C, CAN and GPIO interfaces of the probe.
+    pub fn new_bridge_from_selector(
+        selector: impl Into<DebugProbeSelector>,
+    ) -> Result<Self, ProbeCreationError> {
+        Self::open(selector.into(), true)
+    }
 
+    fn open(selector: DebugProbeSelector, bridge: bool) -> Result<Self, ProbeCreationError> {
         let context = Context::new()?;
 
         tracing::debug!("Acquired libusb context.");
@@ -148,44 +190,61 @@ impl StLinkUsbDevice {
 
         tracing::debug!("Device descriptor: {:?}", &descriptor);
 
-        let info = USB_PID_EP_MAP[&descriptor.product_id()].clone();
-
-        device_handle.claim_interface(0)?;
-
-        tracing::debug!("Claimed interface 0 of USB device.");
+        let mut info = USB_PID_EP_MAP[&descriptor.product_id()].clone();
+
+        // Depending on the firmware, the ST-Link exposes additional interfaces besides the
+        // debug interface, e.g. for mass storage, virtual COM ports, or the bridge on the
+        // ST-Link V3. Search for the interface with the debug endpoints instead of relying
+        // on the interface order.
+        let mut debug_interface = None;
+        let mut bridge_interface = None;
+
+        for interface in config.interfaces() {
+            for descriptor in interface.descriptors() {
+                let endpoints = descriptor
+                    .endpoint_descriptors()
+                    .map(|endpoint| endpoint.address())
+                    .collect::<Vec<_>>();
+
+                tracing::debug!(
+                    "Interface {} (class {:#04x}) has endpoints {:x?}",
+                    descriptor.interface_number(),
+                    descriptor.class_code(),
+                    endpoints
+                );
 
-        let mut endpoint_out = false;
-        let mut endpoint_in = false;
-        let mut endpoint_swo = false;
+                if debug_interface.is_none() && info.has_debug_endpoints(&endpoints) {
+                    debug_interface = Some(descriptor.interface_number());
+                }
 
-        if let Some(interface) = config.interfaces().next() {
-            if let Some(descriptor) = interface.descriptors().next() {
-                for endpoint in descriptor.endpoint_descriptors() {
-                    if endpoint.address() == info.ep_out {
-                        endpoint_out = true;
-                    } else if endpoint.address() == info.ep_in {
-                        endpoint_in = true;
-                    } else if endpoint.address() == info.ep_swo {
-                        endpoint_swo = true;
+                if bridge_interface.is_none() {
+                    if let Some(endpoints) =
+                        info.bridge_endpoints(descriptor.class_code(), &endpoints)
+                    {
+                        bridge_interface = Some((descriptor.interface_number(), endpoints));
                     }
                 }
             }
         }
 
-        if !endpoint_out {
-            return Err(StlinkError::EndpointNotFound.into());
-        }
+        let interface = if bridge {
+            let (interface, (ep_out, ep_in)) =
+                bridge_interface.ok_or(StlinkError::BridgeNotFound)?;
 
-        if !endpoint_in {
-            return Err(StlinkError::EndpointNotFound.into());
-        }
+            // The bridge interface has no SWO endpoint.
+            info = StLinkInfo::new(info.version_name, info.usb_pid, ep_out, ep_in, 0);
+            interface
+        } else {
+            debug_interface.ok_or(StlinkError::EndpointNotFound)?
+        };
 
-        if !endpoint_swo {
-            return Err(StlinkError::EndpointNotFound.into());
-        }
+        device_handle.claim_interface(interface)?;
+
+        tracing::debug!("Claimed interface {} of USB device.", interface);
 
         let usb_stlink = Self {
             device_handle,
+            interface,
             info,
         };
 
@@ -197,7 +256,7 @@ impl StLinkUsbDevice {
     /// Closes the USB interface gracefully.
     /// Internal helper.
     fn close(&mut self) -> Result<(), Error> {
-        self.device_handle.release_interface(0)
+        self.device_handle.release_interface(self.interface)
     }
 }
 
@@ -323,3 +382,33 @@ impl Drop for StLinkUsbDevice {
         let _ = self.close();
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::USB_PID_EP_MAP;
+
+    #[test]
+    fn debug_interface_detection() {
+        let v2 = &USB_PID_EP_MAP[&0x3748];
+        assert!(v2.has_debug_endpoints(&[0x02, 0x81, 0x83]));
+        assert!(!v2.has_debug_endpoints(&[0x01, 0x81, 0x82]));
+
+        // The bridge interface of the V3 does not have the debug endpoints.
+        let v3 = &USB_PID_EP_MAP[&0x374f];
+        assert!(v3.has_debug_endpoints(&[0x01, 0x81, 0x82]));
+        assert!(!v3.has_debug_endpoints(&[0x05, 0x86]));
+    }
+
+    #[test]
+    fn bridge_interface_detection() {
+        let v3 = &USB_PID_EP_MAP[&0x374f];
+        assert_eq!(v3.bridge_endpoints(0xff, &[0x86, 0x05]), Some((0x05, 0x86)));
+        // The debug interface and other classes are not the bridge.
+        assert_eq!(v3.bridge_endpoints(0xff, &[0x01, 0x81, 0x82]), None);
+        assert_eq!(v3.bridge_endpoints(0x08, &[0x83, 0x03]), None);
+
+        // Only the V3 has a bridge.
+        let v2 = &USB_PID_EP_MAP[&0x374b];
+        assert_eq!(v2.bridge_endpoints(0xff, &[0x86, 0x05]), None);
+    }
+}
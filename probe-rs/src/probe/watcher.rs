@@ -0,0 +1,218 @@
+//! Watching for debug probes being connected and disconnected.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use super::{DebugProbeInfo, Probe};
+
+/// The default interval in which the connected probes are scanned.
+const DEFAULT_SCAN_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A change in the set of connected debug probes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProbeEvent {
+    /// A probe was connected.
+    ProbeAttached(DebugProbeInfo),
+    /// A probe was disconnected.
+    ProbeDetached(DebugProbeInfo),
+}
+
+/// Watches for debug probes being connected or disconnected.
+///
+/// The watcher scans for probes on a background thread, and reports each change
+/// as a [`ProbeEvent`]. When the watcher is created, a [`ProbeEvent::ProbeAttached`]
+/// event is reported for every probe which is already connected.
+///
+/// The background thread is stopped when the watcher is dropped.
+///
+/// ## Example
+///
+/// ```no_run
+/// use probe_rs::{ProbeEvent, ProbeWatcher};
+///
+/// let watcher = ProbeWatcher::new();
+///
+/// for event in watcher.iter() {
+///     match event {
+///         ProbeEvent::ProbeAttached(info) => println!("Connected: {info:?}"),
+///         ProbeEvent::ProbeDetached(info) => println!("Disconnected: {info:?}"),
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ProbeWatcher {
+    events: Receiver<ProbeEvent>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ProbeWatcher {
+    /// Starts watching for probes, using the default scan interval.
+    pub fn new() -> Self {
+        Self::with_interval(DEFAULT_SCAN_INTERVAL)
+    }
+
+    /// Starts watching for probes, scanning for probes every `interval`.
+    pub fn with_interval(interval: Duration) -> Self {
+        Self::with_scanner(interval, Probe::list_all)
+    }
+
+    fn with_scanner(
+        interval: Duration,
+        scan: impl Fn() -> Vec<DebugProbeInfo> + Send + 'static,
+    ) -> Self {
+        let (sender, events) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread = {
+            let stop = stop.clone();
+
+            std::thread::Builder::new()
+                .name("probe-watcher".to_string())
+                .spawn(move || {
+                    let mut known_probes = Vec::new();
+
+                    while !stop.load(Ordering::Relaxed) {
+                        let probes = scan();
+
+                        for event in probe_changes(&known_probes, &probes) {
+                            if sender.send(event).is_err() {
+                                // The watcher was dropped.
+                                return;
+                            }
+                        }
+
+                        known_probes = probes;
+
+                        std::thread::sleep(interval);
+                    }
+                })
+                .expect("Failed to spawn probe watcher thread")
+        };
+
+        Self {
+            events,
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    /// Returns the next event, if one is available, without blocking.
+    pub fn try_next_event(&self) -> Option<ProbeEvent> {
+        self.events.try_recv().ok()
+    }
+
+    /// Waits up to `timeout` for the next event.
+    pub fn next_event_timeout(&self, timeout: Duration) -> Option<ProbeEvent> {
+        self.events.recv_timeout(timeout).ok()
+    }
+
+    /// Returns an iterator which blocks waiting for events.
+    pub fn iter(&self) -> impl Iterator<Item = ProbeEvent> + '_ {
+        self.events.iter()
+    }
+}
+
+impl Default for ProbeWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for ProbeWatcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Determines the events between two scans of the connected probes.
+fn probe_changes(old: &[DebugProbeInfo], new: &[DebugProbeInfo]) -> Vec<ProbeEvent> {
+    let detached = old
+        .iter()
+        .filter(|probe| !new.contains(probe))
+        .cloned()
+        .map(ProbeEvent::ProbeDetached);
+
+    let attached = new
+        .iter()
+        .filter(|probe| !old.contains(probe))
+        .cloned()
+        .map(ProbeEvent::ProbeAttached);
+
+    detached.chain(attached).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use super::{probe_changes, ProbeEvent, ProbeWatcher};
+    use crate::{DebugProbeInfo, DebugProbeType};
+
+    fn probe(serial: &str) -> DebugProbeInfo {
+        DebugProbeInfo::new(
+            "Test probe",
+            0x1234,
+            0x5678,
+            Some(serial.to_string()),
+            DebugProbeType::CmsisDap,
+            None,
+        )
+    }
+
+    #[test]
+    fn changes_between_scans() {
+        let old = vec![probe("A"), probe("B")];
+        let new = vec![probe("B"), probe("C")];
+
+        assert_eq!(
+            probe_changes(&old, &new),
+            vec![
+                ProbeEvent::ProbeDetached(probe("A")),
+                ProbeEvent::ProbeAttached(probe("C")),
+            ]
+        );
+
+        assert!(probe_changes(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn watcher_reports_events() {
+        let probes = Arc::new(Mutex::new(vec![probe("A")]));
+
+        let watcher = {
+            let probes = probes.clone();
+            ProbeWatcher::with_scanner(Duration::from_millis(1), move || {
+                probes.lock().unwrap().clone()
+            })
+        };
+
+        let timeout = Duration::from_secs(5);
+
+        assert_eq!(
+            watcher.next_event_timeout(timeout),
+            Some(ProbeEvent::ProbeAttached(probe("A")))
+        );
+
+        probes.lock().unwrap().clear();
+
+        assert_eq!(
+            watcher.next_event_timeout(timeout),
+            Some(ProbeEvent::ProbeDetached(probe("A")))
+        );
+    }
+}
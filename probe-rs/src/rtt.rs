@@ -13,7 +13,7 @@
 //! ```no_run
 //! use std::sync::{Arc, Mutex};
 //! use probe_rs::{Probe, Permissions};
-//! use probe_rs::rtt::Rtt;
+//! use probe_rs::rtt::{Rtt, ScanRegion};
 //!
 //! // First obtain a probe-rs session (see probe-rs documentation for details)
 //! let probe = Probe::list_all()[0].open()?;
@@ -22,8 +22,10 @@
 //! // Select a core.
 //! let mut core = session.core(0)?;
 //!
-//! // Attach to RTT
-//! let mut rtt = Rtt::attach(&mut core, &memory_map)?;
+//! // Attach to RTT, using the address of the control block from the ELF file if available
+//! let elf = std::fs::read("path/to/firmware.elf")?;
+//! let scan_region = ScanRegion::from_elf(&elf)?;
+//! let mut rtt = Rtt::attach_region(&mut core, &memory_map, &scan_region)?;
 //!
 //! // Read from a channel
 //! if let Some(input) = rtt.up_channels().take(0) {
@@ -47,7 +49,11 @@ pub use channel::*;
 pub mod channels;
 pub use channels::Channels;
 
+pub mod routing;
+pub use routing::{RttRouter, Sink, Source};
+
 use crate::{config::MemoryRegion, Core, MemoryInterface};
+use object::read::{Object, ObjectSymbol};
 use scroll::{Pread, LE};
 use std::borrow::Cow;
 use std::collections::BTreeMap;
@@ -86,6 +92,8 @@ pub struct Rtt {
     ptr: u32,
     up_channels: Channels<UpChannel>,
     down_channels: Channels<DownChannel>,
+    /// The state of the control block when it was last checked with [`Rtt::reattach_if_reset`].
+    state: ControlBlockState,
 }
 
 // Rtt must follow this data layout when reading/writing memory in order to be compatible with the
@@ -199,10 +207,15 @@ impl Rtt {
             }
         }
 
+        let Some(state) = ControlBlockState::parse(&mem[..cb_len]) else {
+            return Err(Error::ControlBlockNotFound);
+        };
+
         Ok(Some(Rtt {
             ptr,
             up_channels: Channels(up_channels),
             down_channels: Channels(down_channels),
+            state,
         }))
     }
 
@@ -289,6 +302,103 @@ impl Rtt {
         Ok(instances.remove(0))
     }
 
+    /// Reads all available data from the up channels which were not taken from this instance.
+    ///
+    /// Compared to calling [`UpChannel::read`] for every channel, this reads the descriptors of
+    /// all channels with a single block transfer, and only accesses the buffers of channels which
+    /// contain data. This significantly reduces the number of memory accesses per poll, which is
+    /// important for the throughput on slow probes.
+    ///
+    /// Returns the data of the channels which contained any, indexed by the channel number.
+    pub fn poll_up_channels(&mut self, core: &mut Core) -> Result<BTreeMap<usize, Vec<u8>>, Error> {
+        let mut data = BTreeMap::new();
+
+        let (Some(first), Some(last)) = (
+            self.up_channels.0.values().next(),
+            self.up_channels.0.values().last(),
+        ) else {
+            return Ok(data);
+        };
+
+        first.0.validate_core_id(core)?;
+
+        let start = first.0.ptr();
+        let mut descriptors = vec![0; (last.0.ptr() - start) as usize + Channel::SIZE];
+        core.read(start.into(), &mut descriptors)?;
+
+        for (number, channel) in &self.up_channels.0 {
+            let offset = (channel.0.ptr() - start) as usize;
+
+            if let Some(buf) = channel.read_with_descriptor(core, &descriptors[offset..])? {
+                data.insert(*number, buf);
+            }
+        }
+
+        Ok(data)
+    }
+
+    /// Checks if the target was reset since RTT was attached, and attaches again if it was.
+    ///
+    /// A reset is detected if the control block was cleared or moved, or if the target
+    /// initialized it with a different channel configuration. Call this periodically, e.g.
+    /// every time the channels are polled, to follow resets caused by a watchdog or by the
+    /// firmware itself. If the control block was not initialized again yet, this returns
+    /// [`Error::ControlBlockNotFound`], and can be retried later.
+    ///
+    /// After reattaching, all channels are replaced, so channels which were taken from this
+    /// instance before have to be taken again.
+    ///
+    /// A reset which initializes the control block with exactly the same configuration can't be
+    /// told apart from regular operation. The channels stay valid in this case, but use
+    /// [`Rtt::reattach`] after resetting the target from the host to be informed about lost data.
+    pub fn reattach_if_reset(
+        &mut self,
+        core: &mut Core,
+        memory_map: &[MemoryRegion],
+        region: &ScanRegion,
+    ) -> Result<Option<RttReset>, Error> {
+        let mut mem = vec![0; self.state.len()];
+        core.read(self.ptr.into(), &mut mem)?;
+
+        match ControlBlockState::parse(&mem) {
+            Some(state) if state.has_same_layout(&self.state) => {
+                self.state = state;
+                Ok(None)
+            }
+            _ => {
+                tracing::info!(
+                    "RTT control block at {:#010x} changed, the target was reset.",
+                    self.ptr
+                );
+
+                self.reattach(core, memory_map, region).map(Some)
+            }
+        }
+    }
+
+    /// Attaches to the control block again, e.g. after the target was reset by the host.
+    ///
+    /// All channels are replaced, so channels which were taken from this instance before have to
+    /// be taken again. The returned [`RttReset`] lists the up channels which may have lost data.
+    pub fn reattach(
+        &mut self,
+        core: &mut Core,
+        memory_map: &[MemoryRegion],
+        region: &ScanRegion,
+    ) -> Result<RttReset, Error> {
+        let rtt = Rtt::attach_region(core, memory_map, region)?;
+
+        let reset = RttReset {
+            previous_ptr: self.ptr,
+            ptr: rtt.ptr,
+            unread_up_channels: self.state.unread_up_channels(),
+        };
+
+        *self = rtt;
+
+        Ok(reset)
+    }
+
     /// Returns the memory address of the control block in target memory.
     pub fn ptr(&self) -> u32 {
         self.ptr
@@ -305,6 +415,85 @@ impl Rtt {
     }
 }
 
+/// Information about a reattachment to the control block after a target reset.
+///
+/// This is returned by [`Rtt::reattach_if_reset`] and [`Rtt::reattach`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RttReset {
+    /// The memory address of the control block before the reset.
+    pub previous_ptr: u32,
+    /// The memory address of the control block after the reset.
+    pub ptr: u32,
+    /// The numbers of the up channels which contained data that was not read yet when the control
+    /// block was last checked. This data, and anything written by the target after the last
+    /// check, is lost.
+    pub unread_up_channels: Vec<usize>,
+}
+
+/// A snapshot of the control block and its channel descriptors in target memory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ControlBlockState {
+    max_up_channels: usize,
+    max_down_channels: usize,
+    /// The name, buffer pointer, size, write and read offsets of every channel, up channels first.
+    channels: Vec<[u32; 5]>,
+}
+
+impl ControlBlockState {
+    /// Parses a control block, returns `None` if it doesn't start with the RTT ID or is incomplete.
+    fn parse(mem: &[u8]) -> Option<Self> {
+        if mem.get(Rtt::O_ID..Rtt::O_ID + Rtt::RTT_ID.len())? != Rtt::RTT_ID {
+            return None;
+        }
+
+        let max_up_channels = mem.pread_with::<u32>(Rtt::O_MAX_UP_CHANNELS, LE).ok()? as usize;
+        let max_down_channels = mem.pread_with::<u32>(Rtt::O_MAX_DOWN_CHANNELS, LE).ok()? as usize;
+
+        let channels = (0..max_up_channels + max_down_channels)
+            .map(|i| {
+                let offset = Rtt::O_CHANNEL_ARRAYS + i * Channel::SIZE;
+                let mut fields = [0; 5];
+                for (field, value) in fields.iter_mut().enumerate() {
+                    *value = mem.pread_with(offset + field * 4, LE).ok()?;
+                }
+                Some(fields)
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(ControlBlockState {
+            max_up_channels,
+            max_down_channels,
+            channels,
+        })
+    }
+
+    /// The size of the control block in bytes.
+    fn len(&self) -> usize {
+        Rtt::O_CHANNEL_ARRAYS + self.channels.len() * Channel::SIZE
+    }
+
+    /// Checks if both states have the same channel configuration, ignoring the read and write offsets.
+    fn has_same_layout(&self, other: &ControlBlockState) -> bool {
+        self.max_up_channels == other.max_up_channels
+            && self.max_down_channels == other.max_down_channels
+            && self
+                .channels
+                .iter()
+                .zip(&other.channels)
+                .all(|(a, b)| a[..3] == b[..3])
+    }
+
+    /// The numbers of the initialized up channels whose buffers contain data that was not read yet.
+    fn unread_up_channels(&self) -> Vec<usize> {
+        self.channels[..self.max_up_channels]
+            .iter()
+            .enumerate()
+            .filter(|(_, [_, buffer_ptr, _, write, read])| *buffer_ptr != 0 && write != read)
+            .map(|(number, _)| number)
+            .collect()
+    }
+}
+
 /// Used to specify which memory regions to scan for the RTT control block.
 #[derive(Clone, Debug, Default)]
 pub enum ScanRegion {
@@ -323,6 +512,43 @@ pub enum ScanRegion {
     Exact(u32),
 }
 
+impl ScanRegion {
+    /// Uses the address of the `_SEGGER_RTT` symbol in the ELF file to locate the control block.
+    ///
+    /// If the ELF file doesn't contain the symbol, all RAM regions are scanned.
+    pub fn from_elf(elf: &[u8]) -> Result<Self, Error> {
+        Ok(match find_rtt_control_block_in_raw_file(elf)? {
+            Some(address) => ScanRegion::Exact(address),
+            None => ScanRegion::Ram,
+        })
+    }
+}
+
+/// The name of the symbol of the RTT control block.
+const CONTROL_BLOCK_SYMBOL: &str = "_SEGGER_RTT";
+
+/// Get the address of the RTT control block from the symbol table of an ELF file.
+///
+/// Returns `None` if the ELF file doesn't contain the `_SEGGER_RTT` symbol,
+/// e.g. because the firmware doesn't use RTT.
+pub fn find_rtt_control_block_in_raw_file(elf: &[u8]) -> Result<Option<u32>, Error> {
+    let object = object::File::parse(elf)?;
+
+    let Some(symbol) = object
+        .symbols()
+        .find(|symbol| symbol.name() == Ok(CONTROL_BLOCK_SYMBOL))
+    else {
+        return Ok(None);
+    };
+
+    symbol.address().try_into().map(Some).map_err(|_| {
+        Error::ControlBlockCorrupted(format!(
+            "The address of the control block {:#x} is out of range",
+            symbol.address()
+        ))
+    })
+}
+
 /// Error type for RTT operations.
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -355,4 +581,184 @@ pub enum Error {
     /// Wraps errors propagated up from reading memory on the target.
     #[error("Unexpected error while reading {0} from target memory. Please report this as a bug.")]
     MemoryRead(String),
+
+    /// The target didn't read the data of a down channel in time.
+    #[error("Timeout while writing to down channel {channel}, only {written} bytes were written.")]
+    WriteTimeout {
+        /// The number of the down channel.
+        channel: usize,
+        /// The number of bytes which were written before the timeout.
+        written: usize,
+    },
+
+    /// Reading from a source or writing to a sink of an [`RttRouter`] failed.
+    #[error("Error routing RTT data: {0}")]
+    Routing(#[from] std::io::Error),
+
+    /// The ELF file used to locate the control block could not be parsed.
+    #[error("Unable to parse the ELF file: {0}")]
+    ElfParse(#[from] object::read::Error),
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use super::{Channel, ControlBlockState, Rtt, ScanRegion};
+    use crate::{probe::fake_probe::FakeProbe, MemoryInterface, Permissions, Session};
+
+    /// A control block with one up and one down channel.
+    fn control_block(up: [u32; 5], down: [u32; 5]) -> Vec<u8> {
+        let mut mem = Rtt::RTT_ID.to_vec();
+        mem.extend_from_slice(&1u32.to_le_bytes());
+        mem.extend_from_slice(&1u32.to_le_bytes());
+
+        for fields in [up, down] {
+            for field in fields {
+                mem.extend_from_slice(&field.to_le_bytes());
+            }
+            // flags
+            mem.extend_from_slice(&0u32.to_le_bytes());
+        }
+
+        mem
+    }
+
+    #[test]
+    fn parse_control_block() {
+        let mem = control_block(
+            [0x100, 0x2000_0000, 1024, 10, 4],
+            [0, 0x2000_0400, 16, 0, 0],
+        );
+        let state = ControlBlockState::parse(&mem).unwrap();
+
+        assert_eq!(state.len(), mem.len());
+        assert_eq!(state.unread_up_channels(), vec![0]);
+
+        assert!(ControlBlockState::parse(&mem[..mem.len() - 8]).is_none());
+        assert!(ControlBlockState::parse(&[0; 72]).is_none());
+    }
+
+    #[test]
+    fn offsets_do_not_change_layout() {
+        let before = control_block(
+            [0x100, 0x2000_0000, 1024, 10, 4],
+            [0, 0x2000_0400, 16, 0, 0],
+        );
+        let after = control_block(
+            [0x100, 0x2000_0000, 1024, 12, 12],
+            [0, 0x2000_0400, 16, 3, 1],
+        );
+        let moved = control_block([0x100, 0x2000_0100, 1024, 0, 0], [0, 0x2000_0400, 16, 0, 0]);
+
+        let before = ControlBlockState::parse(&before).unwrap();
+        let after = ControlBlockState::parse(&after).unwrap();
+        let moved = ControlBlockState::parse(&moved).unwrap();
+
+        assert!(before.has_same_layout(&after));
+        assert!(after.unread_up_channels().is_empty());
+        assert!(!before.has_same_layout(&moved));
+    }
+
+    /// A session with a control block at 0x2000_0000, which has two up channels with 16 byte
+    /// buffers. The first one contains `abcd`, the data of the second one wraps around the end
+    /// of its buffer.
+    fn session() -> Session {
+        let mut control_block = Rtt::RTT_ID.to_vec();
+        control_block.extend_from_slice(&2u32.to_le_bytes());
+        control_block.extend_from_slice(&0u32.to_le_bytes());
+
+        for (buffer, write, read) in [(0x2000_0100u32, 4u32, 0u32), (0x2000_0200, 4, 12)] {
+            for field in [0, buffer, 16, write, read, 0] {
+                control_block.extend_from_slice(&field.to_le_bytes());
+            }
+        }
+
+        let mut ram = vec![0; 0x400];
+        ram[..control_block.len()].copy_from_slice(&control_block);
+        ram[0x100..0x104].copy_from_slice(b"abcd");
+        ram[0x20c..0x210].copy_from_slice(b"efgh");
+        ram[0x200..0x204].copy_from_slice(b"ijkl");
+
+        let mut probe = FakeProbe::new();
+        probe.add_memory_region(0x2000_0000, ram);
+        probe.add_memory_region(0xe000_e000, vec![0; 0x1000]);
+
+        probe
+            .into_probe()
+            .attach_non_intrusive("nrf51822_xxAC", Permissions::default())
+            .unwrap()
+    }
+
+    /// The address of the descriptor of up channel `number`.
+    fn descriptor_address(number: u64) -> u64 {
+        0x2000_0000 + Rtt::O_CHANNEL_ARRAYS as u64 + number * Channel::SIZE as u64
+    }
+
+    #[test]
+    fn poll_up_channels() {
+        let mut session = session();
+        let memory_map = session.target().memory_map.clone();
+        let mut core = session.core(0).unwrap();
+        let mut rtt =
+            Rtt::attach_region(&mut core, &memory_map, &ScanRegion::Exact(0x2000_0000)).unwrap();
+
+        let data = rtt.poll_up_channels(&mut core).unwrap();
+        assert_eq!(
+            data,
+            BTreeMap::from([(0, b"abcd".to_vec()), (1, b"efghijkl".to_vec())])
+        );
+
+        // The read offsets are advanced, so the data is only returned once.
+        assert_eq!(core.read_word_32(descriptor_address(0) + 16).unwrap(), 4);
+        assert_eq!(core.read_word_32(descriptor_address(1) + 16).unwrap(), 4);
+        assert!(rtt.poll_up_channels(&mut core).unwrap().is_empty());
+    }
+
+    #[test]
+    fn poll_skips_taken_up_channels() {
+        let mut session = session();
+        let memory_map = session.target().memory_map.clone();
+        let mut core = session.core(0).unwrap();
+        let mut rtt =
+            Rtt::attach_region(&mut core, &memory_map, &ScanRegion::Exact(0x2000_0000)).unwrap();
+
+        let channel = rtt.up_channels().take(0).unwrap();
+
+        let data = rtt.poll_up_channels(&mut core).unwrap();
+        assert_eq!(data, BTreeMap::from([(1, b"efghijkl".to_vec())]));
+
+        let mut buf = [0; 16];
+        assert_eq!(channel.read(&mut core, &mut buf).unwrap(), 4);
+        assert_eq!(&buf[..4], b"abcd");
+    }
+
+    #[test]
+    fn read_with_descriptor_wraps_around() {
+        let mut session = session();
+        let memory_map = session.target().memory_map.clone();
+        let mut core = session.core(0).unwrap();
+        let mut rtt =
+            Rtt::attach_region(&mut core, &memory_map, &ScanRegion::Exact(0x2000_0000)).unwrap();
+        let channel = rtt.up_channels().take(1).unwrap();
+
+        let mut descriptor = [0; Channel::SIZE];
+        core.read(descriptor_address(1), &mut descriptor).unwrap();
+
+        assert_eq!(
+            channel
+                .read_with_descriptor(&mut core, &descriptor)
+                .unwrap(),
+            Some(b"efghijkl".to_vec())
+        );
+
+        // All data was read, so the updated descriptor shows an empty channel.
+        core.read(descriptor_address(1), &mut descriptor).unwrap();
+        assert_eq!(
+            channel
+                .read_with_descriptor(&mut core, &descriptor)
+                .unwrap(),
+            None
+        );
+    }
 }
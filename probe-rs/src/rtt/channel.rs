@@ -2,6 +2,7 @@ use crate::rtt::Error;
 use crate::{config::MemoryRegion, Core, MemoryInterface};
 use scroll::{Pread, LE};
 use std::cmp::min;
+use std::time::{Duration, Instant};
 
 /// Trait for channel information shared between up and down channels.
 pub trait RttChannel {
@@ -98,6 +99,11 @@ impl Channel {
         }
     }
 
+    /// The address of the channel descriptor in target memory.
+    pub(crate) fn ptr(&self) -> u32 {
+        self.ptr
+    }
+
     pub fn name(&self) -> Option<&str> {
         self.name.as_ref().map(|s| s.as_ref())
     }
@@ -114,6 +120,26 @@ impl Channel {
         let write: u32 = block[0];
         let read: u32 = block[1];
 
+        self.validate_pointers(write, read, dir)?;
+
+        Ok((write, read))
+    }
+
+    /// Reads the write and read offsets from a copy of the channel descriptor which was read in advance.
+    fn pointers_from(&self, descriptor: &[u8], dir: &'static str) -> Result<(u32, u32), Error> {
+        let (Ok(write), Ok(read)) = (
+            descriptor.pread_with::<u32>(Self::O_WRITE, LE),
+            descriptor.pread_with::<u32>(Self::O_READ, LE),
+        ) else {
+            return Err(Error::MemoryRead("RTT channel pointers".to_string()));
+        };
+
+        self.validate_pointers(write, read, dir)?;
+
+        Ok((write, read))
+    }
+
+    fn validate_pointers(&self, write: u32, read: u32, dir: &'static str) -> Result<(), Error> {
         let validate = |which, value| {
             if value >= self.size {
                 Err(Error::ControlBlockCorrupted(format!(
@@ -131,9 +157,7 @@ impl Channel {
         };
 
         validate("write", write)?;
-        validate("read", read)?;
-
-        Ok((write, read))
+        validate("read", read)
     }
 }
 
@@ -189,10 +213,22 @@ impl UpChannel {
         Ok(())
     }
 
-    fn read_core(&self, core: &mut Core, mut buf: &mut [u8]) -> Result<(u32, usize), Error> {
+    fn read_core(&self, core: &mut Core, buf: &mut [u8]) -> Result<(u32, usize), Error> {
         self.0.validate_core_id(core)?;
-        let (write, mut read) = self.0.read_pointers(core, "up")?;
+        let (write, read) = self.0.read_pointers(core, "up")?;
+
+        self.read_buffer(core, write, read, buf)
+    }
 
+    /// Reads data between the `read` and `write` offsets from the buffer, and returns the new
+    /// read offset and the number of bytes read.
+    fn read_buffer(
+        &self,
+        core: &mut Core,
+        write: u32,
+        mut read: u32,
+        mut buf: &mut [u8],
+    ) -> Result<(u32, usize), Error> {
         let mut total = 0;
 
         // Read while buffer contains data and output buffer has space (maximum of two iterations)
@@ -235,6 +271,36 @@ impl UpChannel {
         Ok(total)
     }
 
+    /// Reads all available data from the channel, using the write and read offsets from
+    /// `descriptor`, a copy of the channel descriptor which was read from target memory in advance.
+    ///
+    /// Returns `None` without accessing the target if the channel is empty.
+    pub(crate) fn read_with_descriptor(
+        &self,
+        core: &mut Core,
+        descriptor: &[u8],
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let (write, read) = self.0.pointers_from(descriptor, "up")?;
+
+        let available = if write >= read {
+            write - read
+        } else {
+            self.0.size - read + write
+        } as usize;
+
+        if available == 0 {
+            return Ok(None);
+        }
+
+        let mut buf = vec![0; available];
+        let (read, total) = self.read_buffer(core, write, read, &mut buf)?;
+        buf.truncate(total);
+
+        core.write_word_32((self.0.ptr + Channel::O_READ as u32).into(), read)?;
+
+        Ok(Some(buf))
+    }
+
     /// Peeks at the current data in the channel buffer, copies data into the specified buffer and
     /// returns how many bytes were read.
     ///
@@ -331,6 +397,59 @@ impl DownChannel {
         Ok(total)
     }
 
+    /// Writes all of `buf` into the channel buffer, waiting for the target to read the data if
+    /// the buffer is full, and returns the number of bytes written.
+    ///
+    /// If the target doesn't make enough space within `timeout`, [`Error::WriteTimeout`] is
+    /// returned, containing the number of bytes which were written so far.
+    pub fn write_all(
+        &self,
+        core: &mut Core,
+        mut buf: &[u8],
+        timeout: Duration,
+    ) -> Result<usize, Error> {
+        let start = Instant::now();
+        let mut total = 0;
+
+        while !buf.is_empty() {
+            let count = self.write(core, buf)?;
+
+            total += count;
+            buf = &buf[count..];
+
+            if count == 0 {
+                if start.elapsed() >= timeout {
+                    return Err(Error::WriteTimeout {
+                        channel: self.number(),
+                        written: total,
+                    });
+                }
+
+                std::thread::sleep(Duration::from_millis(1));
+            }
+        }
+
+        Ok(total)
+    }
+
+    /// Returns the number of bytes which can be written into the channel buffer without blocking.
+    pub fn available_space(&self, core: &mut Core) -> Result<usize, Error> {
+        self.0.validate_core_id(core)?;
+        let (write, read) = self.0.read_pointers(core, "down")?;
+
+        Ok(self.free_space(write, read))
+    }
+
+    /// Calculates the total amount of space available for writing, which is always one byte less
+    /// than the buffer size, so that a full buffer can be told apart from an empty one.
+    fn free_space(&self, write: u32, read: u32) -> usize {
+        (if read > write {
+            read - write - 1
+        } else {
+            self.0.size - write + read - 1
+        }) as usize
+    }
+
     /// Calculates amount of contiguous space available for writing
     fn writable_contiguous(&self, write: u32, read: u32) -> usize {
         (if read > write {
@@ -411,3 +530,38 @@ pub enum ChannelMode {
     /// is not read by the host.
     BlockIfFull = 2,
 }
+
+#[cfg(test)]
+mod test {
+    use super::{Channel, DownChannel};
+
+    fn down_channel(size: u32) -> DownChannel {
+        DownChannel(Channel {
+            number: 0,
+            core_id: 0,
+            ptr: 0,
+            name: None,
+            buffer_ptr: 0,
+            size,
+        })
+    }
+
+    #[test]
+    fn free_space() {
+        let channel = down_channel(8);
+
+        // Empty buffer, one byte is always kept free.
+        assert_eq!(channel.free_space(0, 0), 7);
+        assert_eq!(channel.free_space(5, 5), 7);
+        // Full buffer.
+        assert_eq!(channel.free_space(4, 5), 0);
+        assert_eq!(channel.free_space(7, 0), 0);
+        // Wrapped around.
+        assert_eq!(channel.free_space(2, 6), 3);
+        assert_eq!(channel.free_space(6, 2), 3);
+
+        // The free space is the sum of the contiguous parts.
+        assert_eq!(channel.writable_contiguous(6, 2), 2);
+        assert_eq!(channel.writable_contiguous(0, 2), 1);
+    }
+}
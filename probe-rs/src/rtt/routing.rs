@@ -0,0 +1,315 @@
+//! Routing of RTT channel data to files, sockets and callbacks.
+//!
+//! An [`RttRouter`] connects each up channel to a [`Sink`], and each down channel to a
+//! [`Source`]. Calling [`RttRouter::poll`] from the polling loop of the session transfers the
+//! available data in both directions.
+
+use super::{DownChannel, Error, UpChannel};
+use crate::Core;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::Path;
+
+/// A function which is called with the data of an up channel, see [`Sink::Callback`].
+pub type SinkCallback = Box<dyn FnMut(&[u8]) + Send>;
+
+/// A function which returns the data for a down channel, see [`Source::Callback`].
+pub type SourceCallback = Box<dyn FnMut() -> Vec<u8> + Send>;
+
+/// The destination for the data read from an up channel.
+pub enum Sink {
+    /// Writes the data to a file or any other writer.
+    Writer(Box<dyn Write + Send>),
+    /// Sends the data to all clients connected to a TCP socket.
+    Tcp(TcpEndpoint),
+    /// Calls a function with the data.
+    Callback(SinkCallback),
+}
+
+impl Sink {
+    /// Creates a sink which writes the data to a file. An existing file is truncated.
+    pub fn file(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Sink::Writer(Box::new(File::create(path)?)))
+    }
+
+    /// Creates a sink which sends the data to all clients connected to a TCP socket listening on `addr`.
+    pub fn tcp(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Sink::Tcp(TcpEndpoint::bind(addr)?))
+    }
+
+    /// Creates a sink which calls `callback` with the data.
+    pub fn callback(callback: impl FnMut(&[u8]) + Send + 'static) -> Self {
+        Sink::Callback(Box::new(callback))
+    }
+
+    /// Writes data to the sink, e.g. data which is not read from an RTT channel.
+    pub fn write(&mut self, data: &[u8]) -> io::Result<()> {
+        match self {
+            Sink::Writer(writer) => {
+                writer.write_all(data)?;
+                writer.flush()
+            }
+            Sink::Tcp(endpoint) => {
+                endpoint.accept()?;
+                endpoint.send(data);
+                Ok(())
+            }
+            Sink::Callback(callback) => {
+                callback(data);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for Sink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Sink::Writer(_) => write!(f, "Writer"),
+            Sink::Tcp(endpoint) => f.debug_tuple("Tcp").field(endpoint).finish(),
+            Sink::Callback(_) => write!(f, "Callback"),
+        }
+    }
+}
+
+/// The source of the data written to a down channel.
+pub enum Source {
+    /// Reads the data from a file or any other reader.
+    ///
+    /// The reader is polled from the polling loop, so it must not block, e.g. a file or a
+    /// non-blocking socket.
+    Reader(Box<dyn Read + Send>),
+    /// Receives the data from all clients connected to a TCP socket.
+    Tcp(TcpEndpoint),
+    /// Calls a function to get the data. The function returns an empty vector if there is none.
+    Callback(SourceCallback),
+}
+
+impl Source {
+    /// Creates a source which reads the data from a file.
+    pub fn file(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Source::Reader(Box::new(File::open(path)?)))
+    }
+
+    /// Creates a source which receives the data from all clients connected to a TCP socket listening on `addr`.
+    pub fn tcp(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Source::Tcp(TcpEndpoint::bind(addr)?))
+    }
+
+    /// Creates a source which calls `callback` to get the data.
+    pub fn callback(callback: impl FnMut() -> Vec<u8> + Send + 'static) -> Self {
+        Source::Callback(Box::new(callback))
+    }
+
+    /// Appends the available data to `buf`.
+    fn read(&mut self, buf: &mut Vec<u8>) -> io::Result<()> {
+        match self {
+            Source::Reader(reader) => {
+                let mut chunk = [0; 1024];
+                match reader.read(&mut chunk) {
+                    Ok(count) => buf.extend_from_slice(&chunk[..count]),
+                    Err(error) if error.kind() == io::ErrorKind::WouldBlock => {}
+                    Err(error) => return Err(error),
+                }
+            }
+            Source::Tcp(endpoint) => {
+                endpoint.accept()?;
+                endpoint.receive(buf);
+            }
+            Source::Callback(callback) => buf.append(&mut callback()),
+        }
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Source::Reader(_) => write!(f, "Reader"),
+            Source::Tcp(endpoint) => f.debug_tuple("Tcp").field(endpoint).finish(),
+            Source::Callback(_) => write!(f, "Callback"),
+        }
+    }
+}
+
+/// A non-blocking TCP socket, which accepts any number of clients.
+#[derive(Debug)]
+pub struct TcpEndpoint {
+    listener: TcpListener,
+    clients: Vec<TcpStream>,
+}
+
+impl TcpEndpoint {
+    /// Creates a socket listening on `addr`.
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+
+        Ok(TcpEndpoint {
+            listener,
+            clients: Vec::new(),
+        })
+    }
+
+    /// The address the socket is listening on.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accepts all pending connections.
+    fn accept(&mut self) -> io::Result<()> {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, addr)) => {
+                    tracing::debug!("Accepted RTT connection from {}", addr);
+                    stream.set_nonblocking(true)?;
+                    self.clients.push(stream);
+                }
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    /// Sends the data to all clients. Clients which can't receive it are disconnected.
+    fn send(&mut self, data: &[u8]) {
+        self.clients
+            .retain_mut(|client| match client.write_all(data) {
+                Ok(()) => true,
+                Err(error) => {
+                    tracing::debug!("Disconnecting RTT client: {}", error);
+                    false
+                }
+            });
+    }
+
+    /// Appends the data received from all clients to `buf`. Disconnected clients are removed.
+    fn receive(&mut self, buf: &mut Vec<u8>) {
+        self.clients.retain_mut(|client| {
+            let mut chunk = [0; 1024];
+
+            loop {
+                match client.read(&mut chunk) {
+                    Ok(0) => return false,
+                    Ok(count) => buf.extend_from_slice(&chunk[..count]),
+                    Err(error) if error.kind() == io::ErrorKind::WouldBlock => return true,
+                    Err(error) => {
+                        tracing::debug!("Disconnecting RTT client: {}", error);
+                        return false;
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// A down channel with its source, and the data which didn't fit into the channel buffer yet.
+#[derive(Debug)]
+struct DownRoute {
+    channel: DownChannel,
+    source: Source,
+    pending: Vec<u8>,
+}
+
+/// Transfers data between RTT channels and their sinks and sources.
+#[derive(Debug, Default)]
+pub struct RttRouter {
+    up: BTreeMap<usize, (UpChannel, Sink)>,
+    down: BTreeMap<usize, DownRoute>,
+}
+
+impl RttRouter {
+    /// Creates a router without any channels.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Routes the data of an up channel to `sink`. An existing route of the channel is replaced.
+    pub fn route_up(&mut self, channel: UpChannel, sink: Sink) {
+        self.up.insert(channel.number(), (channel, sink));
+    }
+
+    /// Routes the data from `source` to a down channel. An existing route of the channel is replaced.
+    pub fn route_down(&mut self, channel: DownChannel, source: Source) {
+        self.down.insert(
+            channel.number(),
+            DownRoute {
+                channel,
+                source,
+                pending: Vec::new(),
+            },
+        );
+    }
+
+    /// Transfers the available data of all channels, and returns the number of bytes transferred.
+    ///
+    /// This doesn't block. Call it periodically, e.g. from the polling loop of the session.
+    /// Data which doesn't fit into the buffer of a down channel is kept, and written by the
+    /// next call.
+    pub fn poll(&mut self, core: &mut Core) -> Result<usize, Error> {
+        let mut total = 0;
+
+        for (channel, sink) in self.up.values_mut() {
+            let mut buf = vec![0; channel.buffer_size()];
+            let count = channel.read(core, &mut buf)?;
+
+            if count > 0 {
+                sink.write(&buf[..count])?;
+                total += count;
+            }
+        }
+
+        for route in self.down.values_mut() {
+            route.source.read(&mut route.pending)?;
+
+            if !route.pending.is_empty() {
+                let count = route.channel.write(core, &route.pending)?;
+                route.pending.drain(..count);
+                total += count;
+            }
+        }
+
+        Ok(total)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::TcpEndpoint;
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+
+    #[test]
+    fn tcp_endpoint_sends_and_receives() {
+        let mut endpoint = TcpEndpoint::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(endpoint.local_addr().unwrap()).unwrap();
+
+        // The connection is accepted asynchronously.
+        while endpoint.clients.is_empty() {
+            endpoint.accept().unwrap();
+        }
+
+        endpoint.send(b"from target");
+
+        let mut received = [0; 11];
+        client.read_exact(&mut received).unwrap();
+        assert_eq!(&received, b"from target");
+
+        client.write_all(b"to target").unwrap();
+
+        let mut buf = Vec::new();
+        while buf.len() < 9 {
+            endpoint.receive(&mut buf);
+        }
+        assert_eq!(buf, b"to target");
+
+        drop(client);
+
+        while !endpoint.clients.is_empty() {
+            endpoint.receive(&mut buf);
+        }
+    }
+}
@@ -0,0 +1,699 @@
+//! Host side implementation of ARM and RISC-V semihosting.
+//!
+//! Semihosting lets a target use the console and the file system of the host. The target
+//! executes a breakpoint instruction, with the operation number in the first argument register
+//! and a pointer to the parameters in the second one. The core halts, the debugger performs the
+//! operation, writes the result to the first argument register, and resumes the core after the
+//! breakpoint instruction.
+//!
+//! On ARM, the breakpoint instruction is `BKPT 0xAB`, and the arguments are passed in `r0` and `r1`.
+//! On RISC-V, the `ebreak` instruction is surrounded by `slli x0, x0, 0x1f` and `srai x0, x0, 7`,
+//! and the arguments are passed in `a0` and `a1`. The operations are the same for both architectures.
+//! RISC-V cores only halt on `ebreak` if [`Core::debug_on_sw_breakpoint`] is enabled.
+//!
+//! File access is restricted to a sandbox directory, see [`SemihostingOptions::root`].
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use probe_rs::semihosting::{Semihosting, SemihostingOptions, SemihostingOutcome};
+//! # use probe_rs::{Permissions, Probe};
+//!
+//! # let probe = Probe::list_all()[0].open()?;
+//! # let mut session = probe.attach("somechip", Permissions::default())?;
+//! let mut semihosting = Semihosting::new(SemihostingOptions::default());
+//! let mut core = session.core(0)?;
+//!
+//! core.run()?;
+//! let exit_code = loop {
+//!     core.wait_for_core_halted(Duration::from_secs(60))?;
+//!
+//!     match semihosting.handle(&mut core)? {
+//!         Some(SemihostingOutcome::Resumed) => continue,
+//!         Some(SemihostingOutcome::Exit(code)) => break code,
+//!         // The core halted for another reason.
+//!         None => break -1,
+//!     }
+//! };
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::{File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Component, Path, PathBuf},
+    time::{Instant, SystemTime},
+};
+
+use crate::{Architecture, BreakpointCause, Core, CoreStatus, Error, HaltReason, MemoryInterface};
+
+const SYS_OPEN: u32 = 0x01;
+const SYS_CLOSE: u32 = 0x02;
+const SYS_WRITEC: u32 = 0x03;
+const SYS_WRITE0: u32 = 0x04;
+const SYS_WRITE: u32 = 0x05;
+const SYS_READ: u32 = 0x06;
+const SYS_READC: u32 = 0x07;
+const SYS_ISERROR: u32 = 0x08;
+const SYS_ISTTY: u32 = 0x09;
+const SYS_SEEK: u32 = 0x0A;
+const SYS_FLEN: u32 = 0x0C;
+const SYS_REMOVE: u32 = 0x0E;
+const SYS_CLOCK: u32 = 0x10;
+const SYS_TIME: u32 = 0x11;
+const SYS_ERRNO: u32 = 0x13;
+const SYS_GET_CMDLINE: u32 = 0x15;
+const SYS_EXIT: u32 = 0x18;
+const SYS_EXIT_EXTENDED: u32 = 0x20;
+
+/// The reason code of `SYS_EXIT` for a successful exit of the application.
+const ADP_STOPPED_APPLICATION_EXIT: u32 = 0x20026;
+
+/// The special file name, which opens the console of the host.
+const CONSOLE_FILE_NAME: &[u8] = b":tt";
+
+/// The result of a failed operation.
+const FAILURE: u32 = u32::MAX;
+
+/// The size of the `BKPT` instruction, which is skipped when an ARM core is resumed.
+const BKPT_SIZE: u64 = 2;
+/// The size of the `ebreak` instruction, which is skipped when a RISC-V core is resumed.
+const EBREAK_SIZE: u64 = 4;
+
+/// Error numbers reported by `SYS_ERRNO`, if the host error has no OS error number.
+const EIO: i32 = 5;
+const EBADF: i32 = 9;
+const EACCES: i32 = 13;
+
+/// Options for the semihosting implementation of the host.
+#[derive(Debug, Clone, Default)]
+pub struct SemihostingOptions {
+    /// The directory in which the target can open and remove files.
+    ///
+    /// File names from the target are resolved relative to this directory. Absolute paths and
+    /// paths which leave the directory are rejected. If this is `None`, the target can only use
+    /// the console.
+    pub root: Option<PathBuf>,
+    /// The command line which is returned to the target by `SYS_GET_CMDLINE`.
+    pub command_line: String,
+    /// Collect the console output of the target instead of writing it to the stdout and stderr of
+    /// the host, see [`Semihosting::take_console_output`].
+    ///
+    /// Console input is then only read from the data passed to [`Semihosting::push_console_input`].
+    /// If there is none, reads from the console return end of file instead of blocking.
+    pub capture_console: bool,
+}
+
+/// The console output of the target, collected if [`SemihostingOptions::capture_console`] is set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConsoleOutput {
+    /// The data written to stdout, including `SYS_WRITEC` and `SYS_WRITE0`.
+    pub stdout: Vec<u8>,
+    /// The data written to stderr.
+    pub stderr: Vec<u8>,
+}
+
+/// The result of handling a halt with [`Semihosting::handle`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SemihostingOutcome {
+    /// The semihosting operation was performed, and the core was resumed.
+    Resumed,
+    /// The target requested to exit with the given exit code. The core stays halted.
+    ///
+    /// The exit code is `0` if the application exited successfully, and the code reported by
+    /// the target or `1` otherwise.
+    Exit(i32),
+}
+
+/// A file handle, which was opened by the target.
+#[derive(Debug)]
+enum Handle {
+    Stdin,
+    Stdout,
+    Stderr,
+    File(File),
+}
+
+/// The result of an operation, which is reported to the target.
+enum Action {
+    Return(u32),
+    Exit(i32),
+}
+
+/// The host side state of semihosting, e.g. the files opened by the target.
+///
+/// Use one instance for the whole run of the target, so file handles stay valid across operations.
+#[derive(Debug)]
+pub struct Semihosting {
+    options: SemihostingOptions,
+    handles: HashMap<u32, Handle>,
+    next_handle: u32,
+    errno: i32,
+    start: Instant,
+    console_output: ConsoleOutput,
+    console_input: VecDeque<u8>,
+}
+
+impl Semihosting {
+    /// Creates the semihosting state, with no files opened.
+    pub fn new(options: SemihostingOptions) -> Self {
+        Self {
+            options,
+            handles: HashMap::new(),
+            // Handle 0 is not used, some targets treat it as invalid.
+            next_handle: 1,
+            errno: 0,
+            start: Instant::now(),
+            console_output: ConsoleOutput::default(),
+            console_input: VecDeque::new(),
+        }
+    }
+
+    /// Returns the console output collected since the last call, if
+    /// [`SemihostingOptions::capture_console`] is set.
+    pub fn take_console_output(&mut self) -> ConsoleOutput {
+        std::mem::take(&mut self.console_output)
+    }
+
+    /// Adds data to the console input of the target, if [`SemihostingOptions::capture_console`] is set.
+    pub fn push_console_input(&mut self, data: &[u8]) {
+        self.console_input.extend(data);
+    }
+
+    /// Handles a halt of `core`.
+    ///
+    /// If the core is halted on a semihosting breakpoint, the requested operation is performed,
+    /// and the core is resumed unless the target requested to exit. `None` is returned if the
+    /// core is not halted on a semihosting breakpoint, and the core is not touched in that case.
+    pub fn handle(&mut self, core: &mut Core) -> Result<Option<SemihostingOutcome>, Error> {
+        if core.status()?
+            != CoreStatus::Halted(HaltReason::Breakpoint(BreakpointCause::Semihosting))
+        {
+            return Ok(None);
+        }
+
+        let registers = core.registers();
+        let operation: u32 = core.read_core_reg(registers.argument_register(0))?;
+        let parameter: u32 = core.read_core_reg(registers.argument_register(1))?;
+
+        tracing::debug!(
+            "Semihosting operation {:#04x}, parameter {:#010x}",
+            operation,
+            parameter
+        );
+
+        match self.perform(core, operation, parameter)? {
+            Action::Return(value) => {
+                core.write_core_reg(registers.argument_register(0).id, value)?;
+
+                let breakpoint_size = match core.architecture() {
+                    Architecture::Riscv => EBREAK_SIZE,
+                    _ => BKPT_SIZE,
+                };
+
+                let pc: u64 = core.read_core_reg(registers.program_counter())?;
+                core.write_core_reg(registers.program_counter().id, pc + breakpoint_size)?;
+
+                core.run()?;
+
+                Ok(Some(SemihostingOutcome::Resumed))
+            }
+            Action::Exit(code) => {
+                tracing::info!("Target exited with code {}", code);
+
+                Ok(Some(SemihostingOutcome::Exit(code)))
+            }
+        }
+    }
+
+    fn perform(
+        &mut self,
+        memory: &mut dyn MemoryInterface,
+        operation: u32,
+        parameter: u32,
+    ) -> Result<Action, Error> {
+        let parameter = parameter as u64;
+
+        let result = match operation {
+            SYS_OPEN => {
+                let [name, mode, length] = read_parameters(memory, parameter)?;
+                let name = read_bytes(memory, name, length)?;
+
+                self.open(&name, mode)
+            }
+            SYS_CLOSE => {
+                let [handle] = read_parameters(memory, parameter)?;
+
+                match self.handles.remove(&handle) {
+                    Some(_) => 0,
+                    None => self.fail(EBADF),
+                }
+            }
+            SYS_WRITEC => {
+                let character = memory.read_word_8(parameter)?;
+                self.write_console(&[character]);
+
+                0
+            }
+            SYS_WRITE0 => {
+                let string = read_string(memory, parameter)?;
+                self.write_console(&string);
+
+                0
+            }
+            SYS_WRITE => {
+                let [handle, buffer, length] = read_parameters(memory, parameter)?;
+                let data = read_bytes(memory, buffer, length)?;
+
+                match self.write(handle, &data) {
+                    Ok(()) => 0,
+                    Err(errno) => {
+                        self.errno = errno;
+                        length
+                    }
+                }
+            }
+            SYS_READ => {
+                let [handle, buffer, length] = read_parameters(memory, parameter)?;
+
+                match self.read(handle, length as usize) {
+                    Ok(data) => {
+                        memory.write_8(buffer as u64, &data)?;
+                        length - data.len() as u32
+                    }
+                    Err(errno) => {
+                        self.errno = errno;
+                        length
+                    }
+                }
+            }
+            SYS_READC => {
+                if self.options.capture_console {
+                    match self.console_input.pop_front() {
+                        Some(character) => character as u32,
+                        None => FAILURE,
+                    }
+                } else {
+                    let mut character = [0];
+                    match std::io::stdin().read_exact(&mut character) {
+                        Ok(()) => character[0] as u32,
+                        Err(error) => self.fail(errno(&error)),
+                    }
+                }
+            }
+            SYS_ISERROR => {
+                let [status] = read_parameters(memory, parameter)?;
+
+                ((status as i32) < 0) as u32
+            }
+            SYS_ISTTY => {
+                let [handle] = read_parameters(memory, parameter)?;
+
+                match self.handles.get(&handle) {
+                    Some(Handle::File(_)) => 0,
+                    Some(_) => 1,
+                    None => self.fail(EBADF),
+                }
+            }
+            SYS_SEEK => {
+                let [handle, position] = read_parameters(memory, parameter)?;
+
+                match self.handles.get_mut(&handle) {
+                    Some(Handle::File(file)) => match file.seek(SeekFrom::Start(position as u64)) {
+                        Ok(_) => 0,
+                        Err(error) => self.fail(errno(&error)),
+                    },
+                    _ => self.fail(EBADF),
+                }
+            }
+            SYS_FLEN => {
+                let [handle] = read_parameters(memory, parameter)?;
+
+                match self.handles.get(&handle) {
+                    Some(Handle::File(file)) => match file.metadata() {
+                        Ok(metadata) => metadata.len() as u32,
+                        Err(error) => self.fail(errno(&error)),
+                    },
+                    _ => self.fail(EBADF),
+                }
+            }
+            SYS_REMOVE => {
+                let [name, length] = read_parameters(memory, parameter)?;
+                let name = read_bytes(memory, name, length)?;
+
+                match self.resolve(&name) {
+                    Ok(path) => match std::fs::remove_file(path) {
+                        Ok(()) => 0,
+                        Err(error) => self.fail(errno(&error)),
+                    },
+                    Err(errno) => self.fail(errno),
+                }
+            }
+            SYS_CLOCK => (self.start.elapsed().as_millis() / 10) as u32,
+            SYS_TIME => SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map(|time| time.as_secs() as u32)
+                .unwrap_or(0),
+            SYS_ERRNO => self.errno as u32,
+            SYS_GET_CMDLINE => {
+                let [buffer, length] = read_parameters(memory, parameter)?;
+
+                let mut command_line = self.options.command_line.as_bytes().to_vec();
+                if command_line.len() < length as usize {
+                    memory.write_word_32(parameter + 4, command_line.len() as u32)?;
+                    command_line.push(0);
+                    memory.write_8(buffer as u64, &command_line)?;
+
+                    0
+                } else {
+                    FAILURE
+                }
+            }
+            SYS_EXIT => return Ok(Action::Exit(exit_code(parameter as u32, None))),
+            SYS_EXIT_EXTENDED => {
+                let [reason, code] = read_parameters(memory, parameter)?;
+
+                return Ok(Action::Exit(exit_code(reason, Some(code))));
+            }
+            _ => {
+                tracing::warn!("Unsupported semihosting operation {:#04x}", operation);
+
+                FAILURE
+            }
+        };
+
+        Ok(Action::Return(result))
+    }
+
+    /// Records the error number for `SYS_ERRNO`, and returns the failure result.
+    fn fail(&mut self, errno: i32) -> u32 {
+        self.errno = errno;
+
+        FAILURE
+    }
+
+    fn open(&mut self, name: &[u8], mode: u32) -> u32 {
+        let handle = if name == CONSOLE_FILE_NAME {
+            match mode {
+                0..=3 => Handle::Stdin,
+                4..=7 => Handle::Stdout,
+                _ => Handle::Stderr,
+            }
+        } else {
+            let Some(options) = open_options(mode) else {
+                return self.fail(EACCES);
+            };
+
+            let file = self
+                .resolve(name)
+                .and_then(|path| options.open(path).map_err(|error| errno(&error)));
+
+            match file {
+                Ok(file) => Handle::File(file),
+                Err(errno) => return self.fail(errno),
+            }
+        };
+
+        let number = self.next_handle;
+        self.next_handle += 1;
+        self.handles.insert(number, handle);
+
+        number
+    }
+
+    /// Resolves a file name of the target in the sandbox directory.
+    fn resolve(&self, name: &[u8]) -> Result<PathBuf, i32> {
+        let root = self.options.root.as_deref().ok_or(EACCES)?;
+        let name = std::str::from_utf8(name).map_err(|_| EACCES)?;
+
+        sandboxed_path(root, name).ok_or_else(|| {
+            tracing::warn!("Target tried to access '{}' outside of the sandbox", name);
+            EACCES
+        })
+    }
+
+    fn write(&mut self, handle: u32, data: &[u8]) -> Result<(), i32> {
+        let capture = self.options.capture_console;
+
+        let result = match self.handles.get_mut(&handle) {
+            Some(Handle::Stdout) if capture => {
+                self.console_output.stdout.extend_from_slice(data);
+                Ok(())
+            }
+            Some(Handle::Stderr) if capture => {
+                self.console_output.stderr.extend_from_slice(data);
+                Ok(())
+            }
+            Some(Handle::Stdout) => std::io::stdout().write_all(data),
+            Some(Handle::Stderr) => std::io::stderr().write_all(data),
+            Some(Handle::File(file)) => file.write_all(data),
+            Some(Handle::Stdin) | None => return Err(EBADF),
+        };
+
+        result.map_err(|error| errno(&error))
+    }
+
+    fn read(&mut self, handle: u32, length: usize) -> Result<Vec<u8>, i32> {
+        let mut data = vec![0; length];
+
+        let count = match self.handles.get_mut(&handle) {
+            Some(Handle::Stdin) if self.options.capture_console => {
+                let count = length.min(self.console_input.len());
+                data.truncate(count);
+                data.iter_mut()
+                    .zip(self.console_input.drain(..count))
+                    .for_each(|(byte, input)| *byte = input);
+                Ok(count)
+            }
+            // A console read returns after the first line, so don't wait for more data.
+            Some(Handle::Stdin) => std::io::stdin().read(&mut data),
+            Some(Handle::File(file)) => read_up_to(file, &mut data),
+            Some(Handle::Stdout) | Some(Handle::Stderr) | None => return Err(EBADF),
+        }
+        .map_err(|error| errno(&error))?;
+
+        data.truncate(count);
+
+        Ok(data)
+    }
+
+    fn write_console(&mut self, data: &[u8]) {
+        if self.options.capture_console {
+            self.console_output.stdout.extend_from_slice(data);
+            return;
+        }
+
+        let mut stdout = std::io::stdout();
+        if let Err(error) = stdout.write_all(data).and_then(|()| stdout.flush()) {
+            self.errno = errno(&error);
+        }
+    }
+}
+
+/// Reads `N` words of the parameter block at `address`.
+fn read_parameters<const N: usize>(
+    memory: &mut dyn MemoryInterface,
+    address: u64,
+) -> Result<[u32; N], Error> {
+    let mut parameters = [0; N];
+    memory.read_32(address, &mut parameters)?;
+
+    Ok(parameters)
+}
+
+fn read_bytes(
+    memory: &mut dyn MemoryInterface,
+    address: u32,
+    length: u32,
+) -> Result<Vec<u8>, Error> {
+    let mut data = vec![0; length as usize];
+    memory.read_8(address as u64, &mut data)?;
+
+    Ok(data)
+}
+
+/// Reads a null terminated string at `address`.
+fn read_string(memory: &mut dyn MemoryInterface, mut address: u64) -> Result<Vec<u8>, Error> {
+    /// Strings are read in chunks, which don't cross the chunk size boundary,
+    /// so no memory after the end of the string is read unless it is in the same chunk.
+    const CHUNK_SIZE: u64 = 64;
+
+    let mut string = Vec::new();
+    loop {
+        let mut chunk = vec![0; (CHUNK_SIZE - address % CHUNK_SIZE) as usize];
+        memory.read_8(address, &mut chunk)?;
+
+        if let Some(end) = chunk.iter().position(|&byte| byte == 0) {
+            string.extend_from_slice(&chunk[..end]);
+            return Ok(string);
+        }
+
+        string.extend_from_slice(&chunk);
+        address += chunk.len() as u64;
+    }
+}
+
+/// Fills `data` from `file`, until the end of the file is reached.
+fn read_up_to(file: &mut File, data: &mut [u8]) -> std::io::Result<usize> {
+    let mut count = 0;
+    while count < data.len() {
+        match file.read(&mut data[count..])? {
+            0 => break,
+            read => count += read,
+        }
+    }
+
+    Ok(count)
+}
+
+/// The host options for an `fopen` mode of `SYS_OPEN`.
+///
+/// The modes are `r`, `rb`, `r+`, `r+b`, `w`, `wb`, `w+`, `w+b`, `a`, `ab`, `a+` and `a+b`.
+fn open_options(mode: u32) -> Option<OpenOptions> {
+    let mut options = OpenOptions::new();
+    let update = mode & 0b10 != 0;
+
+    match mode >> 2 {
+        0 => options.read(true).write(update),
+        1 => options.write(true).read(update).create(true).truncate(true),
+        2 => options.append(true).read(update).create(true),
+        _ => return None,
+    };
+
+    Some(options)
+}
+
+/// Resolves `name` relative to `root`, if it doesn't leave `root`.
+fn sandboxed_path(root: &Path, name: &str) -> Option<PathBuf> {
+    let name = Path::new(name);
+
+    let inside = name
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+
+    inside.then(|| root.join(name))
+}
+
+/// The exit code for the `reason` of `SYS_EXIT`, and the `code` of `SYS_EXIT_EXTENDED`.
+fn exit_code(reason: u32, code: Option<u32>) -> i32 {
+    match (reason, code) {
+        (ADP_STOPPED_APPLICATION_EXIT, Some(code)) => code as i32,
+        (ADP_STOPPED_APPLICATION_EXIT, None) => 0,
+        _ => 1,
+    }
+}
+
+/// The error number of a host error, which is reported to the target.
+fn errno(error: &std::io::Error) -> i32 {
+    error.raw_os_error().unwrap_or(EIO)
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::{Path, PathBuf};
+
+    use super::{
+        exit_code, sandboxed_path, ConsoleOutput, Semihosting, SemihostingOptions,
+        ADP_STOPPED_APPLICATION_EXIT,
+    };
+
+    #[test]
+    fn sandbox_paths() {
+        let root = Path::new("/tmp/sandbox");
+
+        assert_eq!(
+            sandboxed_path(root, "out/result.txt"),
+            Some(PathBuf::from("/tmp/sandbox/out/result.txt"))
+        );
+        assert_eq!(
+            sandboxed_path(root, "./log"),
+            Some(PathBuf::from("/tmp/sandbox/./log"))
+        );
+        assert_eq!(sandboxed_path(root, "../secret"), None);
+        assert_eq!(sandboxed_path(root, "out/../../secret"), None);
+        assert_eq!(sandboxed_path(root, "/etc/passwd"), None);
+    }
+
+    #[test]
+    fn exit_codes() {
+        assert_eq!(exit_code(ADP_STOPPED_APPLICATION_EXIT, None), 0);
+        assert_eq!(exit_code(ADP_STOPPED_APPLICATION_EXIT, Some(3)), 3);
+        // ADP_Stopped_RunTimeErrorUnknown
+        assert_eq!(exit_code(0x20023, None), 1);
+        assert_eq!(exit_code(0x20023, Some(0)), 1);
+    }
+
+    #[test]
+    fn files_in_sandbox() {
+        let root =
+            std::env::temp_dir().join(format!("probe-rs-semihosting-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+
+        let mut semihosting = Semihosting::new(SemihostingOptions {
+            root: Some(root.clone()),
+            ..Default::default()
+        });
+
+        // "w"
+        let handle = semihosting.open(b"test.txt", 4);
+        assert_ne!(handle, u32::MAX);
+        semihosting.write(handle, b"semihosting").unwrap();
+        semihosting.handles.remove(&handle);
+
+        // "r"
+        let handle = semihosting.open(b"test.txt", 0);
+        assert_eq!(semihosting.read(handle, 64).unwrap(), b"semihosting");
+        assert!(semihosting.write(handle, b"read only").is_err());
+
+        // Files outside of the sandbox can't be opened.
+        assert_eq!(semihosting.open(b"../test.txt", 0), u32::MAX);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn captured_console() {
+        let mut semihosting = Semihosting::new(SemihostingOptions {
+            capture_console: true,
+            ..Default::default()
+        });
+
+        let stdout = semihosting.open(b":tt", 4);
+        let stderr = semihosting.open(b":tt", 8);
+        let stdin = semihosting.open(b":tt", 0);
+
+        semihosting.write(stdout, b"Hello").unwrap();
+        semihosting.write_console(b"!");
+        semihosting.write(stderr, b"Error").unwrap();
+
+        assert_eq!(
+            semihosting.take_console_output(),
+            ConsoleOutput {
+                stdout: b"Hello!".to_vec(),
+                stderr: b"Error".to_vec(),
+            }
+        );
+        assert_eq!(semihosting.take_console_output(), ConsoleOutput::default());
+
+        // Reading without input returns end of file instead of blocking.
+        assert_eq!(semihosting.read(stdin, 16).unwrap(), b"");
+
+        semihosting.push_console_input(b"input");
+        assert_eq!(semihosting.read(stdin, 3).unwrap(), b"inp");
+        assert_eq!(semihosting.read(stdin, 16).unwrap(), b"ut");
+    }
+
+    #[test]
+    fn no_files_without_sandbox() {
+        let mut semihosting = Semihosting::new(SemihostingOptions::default());
+
+        assert_eq!(semihosting.open(b"test.txt", 4), u32::MAX);
+
+        // The console is always available.
+        let stdout = semihosting.open(b":tt", 4);
+        assert_ne!(stdout, u32::MAX);
+        assert_ne!(semihosting.open(b":tt", 0), stdout);
+    }
+}
@@ -1,23 +1,31 @@
-use probe_rs_target::CoreAccessOptions;
+use probe_rs_target::{CoreAccessOptions, MemoryAccess, MemoryRange, RamRegion};
 
 use crate::architecture::arm::component::get_arm_components;
 use crate::architecture::arm::sequences::{ArmDebugSequence, DefaultArmSequence};
 use crate::architecture::arm::{ApAddress, ArmError, DpAddress};
 use crate::architecture::riscv::communication_interface::RiscvError;
-use crate::config::{ChipInfo, RegistryError, Target, TargetSelector};
-use crate::core::{Architecture, CoreState, SpecificCoreState};
+use crate::architecture::xtensa::communication_interface::{
+    XtensaCommunicationInterface, XtensaError,
+};
+use crate::config::{ChipInfo, MemoryRegion, RegistryError, Target, TargetSelector};
+use crate::core::{Architecture, CoreEvent, CoreState, SpecificCoreState};
+#[cfg(feature = "svd")]
+use crate::peripherals::{Peripheral, PeripheralError, Peripherals};
+use crate::probe::fake_probe::FakeProbe;
 use crate::{
     architecture::{
         arm::{
             ap::MemoryAp, communication_interface::ArmProbeInterface, component::TraceSink,
-            memory::CoresightComponent, SwoReader,
+            memory::CoresightComponent, swo::PcSamplingInterval, SwoReader,
         },
         riscv::communication_interface::RiscvCommunicationInterface,
     },
     config::DebugSequence,
 };
-use crate::{AttachMethod, Core, CoreType, Error, FakeProbe, Probe};
+use crate::{AttachMethod, Core, CoreType, Error, Probe};
 use std::ops::DerefMut;
+#[cfg(feature = "svd")]
+use std::path::Path;
 use std::{fmt, sync::Arc, time::Duration};
 
 /// The `Session` struct represents an active debug session.
@@ -26,7 +34,7 @@ use std::{fmt, sync::Arc, time::Duration};
 /// The session can be created by calling the [Session::auto_attach()] function,
 /// which tries to automatically select a probe, and then connect to the target.
 ///
-/// For more control, the [Probe::attach()] and [Probe::attach_under_reset()]
+/// For more control, the [Probe::attach()], [Probe::attach_under_reset()] and [Probe::attach_non_intrusive()]
 /// methods can be used to open a `Session` from a specific [Probe].
 ///
 /// # Usage
@@ -44,11 +52,15 @@ pub struct Session {
     interface: ArchitectureInterface,
     cores: Vec<(SpecificCoreState, CoreState)>,
     configured_trace_sink: Option<TraceSink>,
+    attach_method: AttachMethod,
+    #[cfg(feature = "svd")]
+    peripherals: Option<Peripherals>,
 }
 
 enum ArchitectureInterface {
     Arm(Box<dyn ArmProbeInterface + 'static>),
     Riscv(Box<RiscvCommunicationInterface>),
+    Xtensa(Box<XtensaCommunicationInterface>),
 }
 
 impl fmt::Debug for ArchitectureInterface {
@@ -59,6 +71,10 @@ impl fmt::Debug for ArchitectureInterface {
                 .debug_tuple("ArchitectureInterface::Riscv")
                 .field(iface)
                 .finish(),
+            ArchitectureInterface::Xtensa(iface) => f
+                .debug_tuple("ArchitectureInterface::Xtensa")
+                .field(iface)
+                .finish(),
         }
     }
 }
@@ -68,6 +84,7 @@ impl From<ArchitectureInterface> for Architecture {
         match value {
             ArchitectureInterface::Arm(_) => Architecture::Arm,
             ArchitectureInterface::Riscv(_) => Architecture::Riscv,
+            ArchitectureInterface::Xtensa(_) => Architecture::Xtensa,
         }
     }
 }
@@ -87,11 +104,17 @@ impl ArchitectureInterface {
                     .ok_or_else(|| Error::CoreNotFound(core_state.id()))?;
                 let arm_core_access_options = match &config.core_access_options {
                     probe_rs_target::CoreAccessOptions::Arm(opt) => opt,
-                    probe_rs_target::CoreAccessOptions::Riscv(_) => {
+                    probe_rs_target::CoreAccessOptions::Riscv(_)
+                    | probe_rs_target::CoreAccessOptions::Xtensa(_) => {
                         unreachable!("This should never happen. Please file a bug if it does.")
                     }
                 };
 
+                // Only the lead core of a lockstep pair is debugged.
+                if arm_core_access_options.lockstep_redundant {
+                    return Err(Error::CoreInLockstep(core_state.id()));
+                }
+
                 let dp = match arm_core_access_options.psel {
                     0 => DpAddress::Default,
                     x => DpAddress::Multidrop(x),
@@ -105,7 +128,26 @@ impl ArchitectureInterface {
 
                 core.attach_arm(core_state, memory, target)
             }
-            ArchitectureInterface::Riscv(state) => core.attach_riscv(core_state, state),
+            ArchitectureInterface::Riscv(state) => {
+                let config = target
+                    .cores
+                    .get(core_state.id())
+                    .ok_or_else(|| Error::CoreNotFound(core_state.id()))?;
+                let hart = match &config.core_access_options {
+                    probe_rs_target::CoreAccessOptions::Riscv(opt) => {
+                        opt.hart_id.unwrap_or(core_state.id() as u32)
+                    }
+                    probe_rs_target::CoreAccessOptions::Arm(_)
+                    | probe_rs_target::CoreAccessOptions::Xtensa(_) => {
+                        unreachable!("This should never happen. Please file a bug if it does.")
+                    }
+                };
+
+                state.select_hart(hart).map_err(Error::Riscv)?;
+
+                core.attach_riscv(core_state, state)
+            }
+            ArchitectureInterface::Xtensa(state) => core.attach_xtensa(core_state, state),
         }
     }
 }
@@ -125,10 +167,19 @@ impl Session {
             .iter()
             .enumerate()
             .map(|(id, core)| {
-                (
-                    SpecificCoreState::from_core_type(core.core_type),
-                    Core::create_state(id, core.core_access_options.clone()),
-                )
+                let mut state = Core::create_state(id, core.core_access_options.clone());
+                state.default_reset_type = target.default_reset_type;
+                state.restricted_regions = target
+                    .memory_map
+                    .iter()
+                    .filter(|region| region.cores().contains(&core.name))
+                    .filter(|region| region.access() != MemoryAccess::default())
+                    .map(|region| (region.address_range(), region.access()))
+                    .collect();
+                state.memory_aliases = core.memory_aliases.clone();
+                state.non_intrusive = attach_method == AttachMethod::NonIntrusive;
+
+                (SpecificCoreState::from_core_type(core.core_type), state)
             })
             .collect();
 
@@ -137,7 +188,8 @@ impl Session {
                 let config = target.cores[0].clone();
                 let arm_core_access_options = match config.core_access_options {
                     probe_rs_target::CoreAccessOptions::Arm(opt) => opt,
-                    probe_rs_target::CoreAccessOptions::Riscv(_) => {
+                    probe_rs_target::CoreAccessOptions::Riscv(_)
+                    | probe_rs_target::CoreAccessOptions::Xtensa(_) => {
                         unreachable!("This should never happen. Please file a bug if it does.")
                     }
                 };
@@ -152,7 +204,7 @@ impl Session {
 
                 let sequence_handle = match &target.debug_sequence {
                     DebugSequence::Arm(sequence) => sequence.clone(),
-                    DebugSequence::Riscv(_) => {
+                    DebugSequence::Riscv(_) | DebugSequence::Xtensa(_) => {
                         panic!("Mismatch between architecture and sequence type!")
                     }
                 };
@@ -200,20 +252,27 @@ impl Session {
                     Err(e) => return Err(Error::Arm(e)),
                 }
 
-                {
+                // The debug registers of the cores are left alone when attaching non-intrusively.
+                if attach_method != AttachMethod::NonIntrusive {
                     // For each core, setup debugging
                     for (id, core) in target.cores.iter().enumerate() {
                         let core_span = tracing::debug_span!("debug_setup", core_id = id).entered();
 
                         let arm_core_access_options = match &core.core_access_options {
                             probe_rs_target::CoreAccessOptions::Arm(opt) => opt,
-                            probe_rs_target::CoreAccessOptions::Riscv(_) => {
+                            probe_rs_target::CoreAccessOptions::Riscv(_)
+                            | probe_rs_target::CoreAccessOptions::Xtensa(_) => {
                                 unreachable!(
                                     "This should never happen. Please file a bug if it does."
                                 )
                             }
                         };
 
+                        if arm_core_access_options.lockstep_redundant {
+                            tracing::debug!("Skipping core {}, it runs in lockstep", id);
+                            continue;
+                        }
+
                         let mem_ap = MemoryAp::new(ApAddress {
                             dp: match arm_core_access_options.psel {
                                 0 => DpAddress::Default,
@@ -228,7 +287,7 @@ impl Session {
                         sequence_handle.debug_core_start(
                             &mut *interface,
                             mem_ap,
-                            config.core_type,
+                            core.core_type,
                             arm_core_access_options.debug_base,
                             arm_core_access_options.cti_base,
                         )?;
@@ -274,6 +333,9 @@ impl Session {
                         interface: ArchitectureInterface::Arm(interface),
                         cores,
                         configured_trace_sink: None,
+                        attach_method,
+                        #[cfg(feature = "svd")]
+                        peripherals: None,
                     };
 
                     {
@@ -305,6 +367,9 @@ impl Session {
                         interface: ArchitectureInterface::Arm(interface),
                         cores,
                         configured_trace_sink: None,
+                        attach_method,
+                        #[cfg(feature = "svd")]
+                        peripherals: None,
                     }
                 }
             }
@@ -313,7 +378,7 @@ impl Session {
 
                 let sequence_handle = match &target.debug_sequence {
                     DebugSequence::Riscv(sequence) => sequence.clone(),
-                    DebugSequence::Arm(_) => {
+                    _ => {
                         panic!("Mismatch between architecture and sequence type!")
                     }
                 };
@@ -329,26 +394,128 @@ impl Session {
                     interface: ArchitectureInterface::Riscv(Box::new(interface)),
                     cores,
                     configured_trace_sink: None,
+                    attach_method,
+                    #[cfg(feature = "svd")]
+                    peripherals: None,
                 };
 
-                {
-                    // Todo: Add multicore support. How to deal with any cores that are not active and won't respond?
-                    let mut core = session.core(0)?;
+                if attach_method != AttachMethod::NonIntrusive {
+                    {
+                        // Todo: Add multicore support. How to deal with any cores that are not active and won't respond?
+                        let mut core = session.core(0)?;
+
+                        core.halt(Duration::from_millis(100))?;
+                    }
 
-                    core.halt(Duration::from_millis(100))?;
+                    sequence_handle.on_connect(session.get_riscv_interface()?)?;
                 }
 
-                sequence_handle.on_connect(session.get_riscv_interface()?)?;
+                session
+            }
+            Architecture::Xtensa => {
+                let sequence_handle = match &target.debug_sequence {
+                    DebugSequence::Xtensa(sequence) => sequence.clone(),
+                    _ => {
+                        panic!("Mismatch between architecture and sequence type!")
+                    }
+                };
+
+                probe.inner_attach()?;
+
+                let interface = probe
+                    .try_into_xtensa_interface()
+                    .map_err(|(_probe, err)| err)?;
+
+                let mut session = Session {
+                    target,
+                    interface: ArchitectureInterface::Xtensa(Box::new(interface)),
+                    cores,
+                    configured_trace_sink: None,
+                    attach_method,
+                    #[cfg(feature = "svd")]
+                    peripherals: None,
+                };
+
+                if attach_method != AttachMethod::NonIntrusive {
+                    {
+                        // Memory can only be accessed while the core is halted.
+                        let mut core = session.core(0)?;
+
+                        core.halt(Duration::from_millis(100))?;
+                    }
+
+                    sequence_handle.on_connect(session.get_xtensa_interface()?)?;
+                }
 
                 session
             }
         };
 
-        session.clear_all_hw_breakpoints()?;
+        if attach_method != AttachMethod::NonIntrusive {
+            session.clear_all_hw_breakpoints()?;
+            session.add_tcm_regions();
+        }
 
         Ok(session)
     }
 
+    /// Recover a locked target, by running the recovery sequence of the target.
+    ///
+    /// Unlike [`Session::new`], the cores are not attached, so this also works for devices
+    /// which can't be attached because they are locked.
+    pub(crate) fn recover(
+        probe: Probe,
+        target: TargetSelector,
+        permissions: Permissions,
+    ) -> Result<(), Error> {
+        permissions
+            .erase_all()
+            .map_err(|MissingPermissions(desc)| Error::MissingPermissions(desc))?;
+
+        let (mut probe, target) = get_target_from_selector(target, AttachMethod::Normal, probe)?;
+
+        let sequence_handle = match &target.debug_sequence {
+            DebugSequence::Arm(sequence) => sequence.clone(),
+            DebugSequence::Riscv(_) | DebugSequence::Xtensa(_) => {
+                return Err(Error::Probe(crate::DebugProbeError::NotImplemented(
+                    "Device recovery is only available for ARM targets",
+                )))
+            }
+        };
+
+        let arm_core_access_options = match &target.cores[0].core_access_options {
+            probe_rs_target::CoreAccessOptions::Arm(opt) => opt,
+            probe_rs_target::CoreAccessOptions::Riscv(_)
+            | probe_rs_target::CoreAccessOptions::Xtensa(_) => {
+                unreachable!("This should never happen. Please file a bug if it does.")
+            }
+        };
+
+        let default_memory_ap = MemoryAp::new(ApAddress {
+            dp: match arm_core_access_options.psel {
+                0 => DpAddress::Default,
+                x => DpAddress::Multidrop(x),
+            },
+            ap: arm_core_access_options.ap,
+        });
+
+        probe.inner_attach()?;
+
+        let interface = probe.try_into_arm_interface().map_err(|(_, err)| err)?;
+
+        let mut interface = interface
+            .initialize(sequence_handle.clone())
+            .map_err(|(_interface, e)| e)?;
+
+        tracing::info!("Recovering {}", target.name);
+
+        match sequence_handle.debug_device_recover(&mut *interface, default_memory_ap) {
+            // The target has to be attached again after the recovery anyways.
+            Ok(()) | Err(ArmError::ReAttachRequired) => Ok(()),
+            Err(e) => Err(Error::Arm(e)),
+        }
+    }
+
     /// Automatically creates a session with the first connected probe found.
     #[tracing::instrument(skip(target))]
     pub fn auto_attach(
@@ -377,6 +544,44 @@ impl Session {
             .collect()
     }
 
+    /// Attaches to the core with the given name, as given in the target description.
+    ///
+    /// See [`Session::core`] for details.
+    pub fn core_by_name(&mut self, name: &str) -> Result<Core<'_>, Error> {
+        let core_index = self
+            .target
+            .core_index_by_name(name)
+            .ok_or_else(|| Error::CoreNameNotFound(name.to_string()))?;
+
+        self.core(core_index)
+    }
+
+    /// Returns the memory regions which can be accessed by the core with the given number.
+    pub fn core_memory_map(&self, core_index: usize) -> Result<Vec<MemoryRegion>, Error> {
+        let core = self
+            .target
+            .cores
+            .get(core_index)
+            .ok_or(Error::CoreNotFound(core_index))?;
+
+        Ok(self.target.memory_map_for_core(&core.name))
+    }
+
+    /// Returns the state changes of all cores since the last call, with the index of the core.
+    ///
+    /// See [`Core::poll_events`] for details, and [`CoreWatcher`](crate::CoreWatcher)
+    /// to poll the cores on a background thread.
+    pub fn poll_core_events(&mut self) -> Result<Vec<(usize, CoreEvent)>, Error> {
+        let mut events = Vec::new();
+
+        for core_index in 0..self.cores.len() {
+            let core_events = self.core(core_index)?.poll_events()?;
+            events.extend(core_events.into_iter().map(|event| (core_index, event)));
+        }
+
+        Ok(events)
+    }
+
     /// Attaches to the core with the given number.
     ///
     /// ## Usage
@@ -401,6 +606,21 @@ impl Session {
         self.interface.attach(core, core_state, &self.target)
     }
 
+    /// Sets the clock frequency of the core with index `core_index` in Hz.
+    ///
+    /// The frequency is used to estimate the wall-clock time of cycle measurements,
+    /// see [`Core::measure_cycles`]. If it isn't set, the TPIU clock of the SWO configuration
+    /// passed to [`Session::setup_tracing`] is used, which usually is the core clock.
+    pub fn set_core_clock(&mut self, core_index: usize, frequency: u32) -> Result<(), Error> {
+        let (_, core_state) = self
+            .cores
+            .get_mut(core_index)
+            .ok_or(Error::CoreNotFound(core_index))?;
+        core_state.clock_frequency = Some(frequency);
+
+        Ok(())
+    }
+
     /// Read available trace data from the specified data sink.
     ///
     /// This method is only supported for ARM-based targets, and will
@@ -462,6 +682,15 @@ impl Session {
         Ok(interface)
     }
 
+    fn get_xtensa_interface(&mut self) -> Result<&mut XtensaCommunicationInterface, XtensaError> {
+        let interface = match &mut self.interface {
+            ArchitectureInterface::Xtensa(interface) => interface,
+            _ => return Err(XtensaError::NoXtensaTarget),
+        };
+
+        Ok(interface)
+    }
+
     #[tracing::instrument(skip_all)]
     fn reattach_arm_interface(
         interface: &mut Box<dyn ArmProbeInterface>,
@@ -501,7 +730,7 @@ impl Session {
     pub fn has_sequence_erase_all(&self) -> bool {
         match &self.target.debug_sequence {
             DebugSequence::Arm(seq) => seq.debug_erase_sequence().is_some(),
-            DebugSequence::Riscv(_) => false,
+            DebugSequence::Riscv(_) | DebugSequence::Xtensa(_) => false,
         }
     }
 
@@ -518,7 +747,7 @@ impl Session {
             ArchitectureInterface::Arm(interface) => {
                 let debug_sequence = match &self.target.debug_sequence {
                     DebugSequence::Arm(seq) => seq.clone(),
-                    DebugSequence::Riscv(_) => {
+                    DebugSequence::Riscv(_) | DebugSequence::Xtensa(_) => {
                         unreachable!("This should never happen. Please file a bug if it does.")
                     }
                 };
@@ -537,7 +766,8 @@ impl Session {
                                 let config = self.target.cores[i].clone();
                                 let arm_core_access_options = match config.core_access_options {
                                     probe_rs_target::CoreAccessOptions::Arm(opt) => opt,
-                                    probe_rs_target::CoreAccessOptions::Riscv(_) => {
+                                    probe_rs_target::CoreAccessOptions::Riscv(_)
+                                    | probe_rs_target::CoreAccessOptions::Xtensa(_) => {
                                         unreachable!(
                                     "This should never happen. Please file a bug if it does."
                                 )
@@ -572,9 +802,11 @@ impl Session {
                     )))
                 }
             }
-            ArchitectureInterface::Riscv(_) => Err(Error::Probe(
-                crate::DebugProbeError::NotImplemented("Debug Erase Sequence"),
-            )),
+            ArchitectureInterface::Riscv(_) | ArchitectureInterface::Xtensa(_) => {
+                Err(Error::Probe(crate::DebugProbeError::NotImplemented(
+                    "Debug Erase Sequence",
+                )))
+            }
         }
     }
 
@@ -591,11 +823,86 @@ impl Session {
         get_arm_components(interface, dp)
     }
 
+    /// Discovers all CoreSight components of the target.
+    ///
+    /// The ROM tables behind all memory APs of the debug ports used by the target are
+    /// parsed recursively, and all found components, including the ROM tables themselves,
+    /// are returned as a flat list. Use [`CoresightComponent::peripheral_type`] to find out
+    /// what kind of component was found.
+    pub fn discover_coresight_components(&mut self) -> Result<Vec<CoresightComponent>, ArmError> {
+        let mut dps = vec![];
+
+        for core in &self.target.cores {
+            if let CoreAccessOptions::Arm(options) = &core.core_access_options {
+                let dp = match options.psel {
+                    0 => DpAddress::Default,
+                    x => DpAddress::Multidrop(x),
+                };
+
+                if !dps.contains(&dp) {
+                    dps.push(dp);
+                }
+            }
+        }
+
+        let interface = self.get_arm_interface()?;
+
+        let mut components = vec![];
+
+        for dp in dps {
+            for root in get_arm_components(interface, dp)? {
+                components.extend(root.iter().cloned());
+            }
+        }
+
+        Ok(components)
+    }
+
     /// Get the target description of the connected target.
     pub fn target(&self) -> &Target {
         &self.target
     }
 
+    /// Load the SVD file describing the peripherals of the target.
+    ///
+    /// This replaces any previously loaded SVD file.
+    #[cfg(feature = "svd")]
+    pub fn load_svd(&mut self, path: impl AsRef<Path>) -> Result<(), PeripheralError> {
+        let svd = std::fs::read_to_string(path)?;
+        self.set_peripherals(Peripherals::from_svd(&svd)?);
+
+        Ok(())
+    }
+
+    /// Set the description of the peripherals of the target, e.g. from an SVD file which
+    /// was read from a CMSIS-Pack.
+    #[cfg(feature = "svd")]
+    pub fn set_peripherals(&mut self, peripherals: Peripherals) {
+        self.peripherals = Some(peripherals);
+    }
+
+    /// The description of the peripherals of the target, if an SVD file was loaded.
+    #[cfg(feature = "svd")]
+    pub fn peripherals(&self) -> Option<&Peripherals> {
+        self.peripherals.as_ref()
+    }
+
+    /// The description of the peripherals of the target, if an SVD file was loaded.
+    ///
+    /// Can be used to change the [`ReadPolicy`](crate::peripherals::ReadPolicy).
+    #[cfg(feature = "svd")]
+    pub fn peripherals_mut(&mut self) -> Option<&mut Peripherals> {
+        self.peripherals.as_mut()
+    }
+
+    /// Access the registers of the peripheral with the given name.
+    ///
+    /// Requires that an SVD file was loaded with [`Session::load_svd`] or [`Session::set_peripherals`].
+    #[cfg(feature = "svd")]
+    pub fn peripheral(&mut self, name: &str) -> Result<Peripheral<'_>, PeripheralError> {
+        Peripheral::new(self, name)
+    }
+
     /// Configure the target and probe for serial wire view (SWV) tracing.
     pub fn setup_tracing(
         &mut self,
@@ -610,7 +917,7 @@ impl Session {
 
         let sequence_handle = match &self.target.debug_sequence {
             DebugSequence::Arm(sequence) => sequence.clone(),
-            DebugSequence::Riscv(_) => {
+            DebugSequence::Riscv(_) | DebugSequence::Xtensa(_) => {
                 panic!("Mismatch between architecture and sequence type!")
             }
         };
@@ -620,21 +927,30 @@ impl Session {
 
         // Configure SWO on the probe when the trace sink is configured for a serial output. Note
         // that on some architectures, the TPIU is configured to drive SWO.
-        match destination {
+        let tpiu_clk = match destination {
             TraceSink::Swo(ref config) => {
                 interface.enable_swo(config)?;
+                Some(config.tpiu_clk())
             }
             TraceSink::Tpiu(ref config) => {
                 interface.enable_swo(config)?;
+                Some(config.tpiu_clk())
             }
-            TraceSink::TraceMemory => {}
-        }
+            TraceSink::TraceMemory => None,
+        };
 
         sequence_handle.trace_start(interface, &components, &destination)?;
         crate::architecture::arm::component::setup_tracing(interface, &components, &destination)?;
 
         self.configured_trace_sink.replace(destination);
 
+        // The TPIU is usually clocked by the core clock.
+        if let Some((_, core_state)) = self.cores.get_mut(core_index) {
+            if core_state.clock_frequency.is_none() {
+                core_state.clock_frequency = tpiu_clk;
+            }
+        }
+
         Ok(())
     }
 
@@ -656,6 +972,65 @@ impl Session {
         )
     }
 
+    /// Enable periodic PC sampling, which is sent over SWV.
+    ///
+    /// Tracing has to be configured with [`Session::setup_tracing`] first. The samples can be
+    /// decoded from the trace data with a [`PcSampleDecoder`](crate::architecture::arm::swo::PcSampleDecoder).
+    pub fn enable_pc_sampling(&mut self, interval: PcSamplingInterval) -> Result<(), ArmError> {
+        let components = self.get_arm_components(DpAddress::Default)?;
+        let interface = self.get_arm_interface()?;
+        crate::architecture::arm::component::enable_pc_sampling(interface, &components, interval)
+    }
+
+    /// Disable periodic PC sampling.
+    pub fn disable_pc_sampling(&mut self) -> Result<(), ArmError> {
+        let components = self.get_arm_components(DpAddress::Default)?;
+        let interface = self.get_arm_interface()?;
+        crate::architecture::arm::component::disable_pc_sampling(interface, &components)
+    }
+
+    /// Configure the ETM (or PTM) of the target to trace all executed instructions.
+    ///
+    /// The trace is emitted with the trace source ID `trace_id`, which must not collide with the
+    /// ID used by the ITM (13). Tracing has to be configured with [`Session::setup_tracing`] first.
+    pub fn enable_etm_trace(&mut self, trace_id: u8) -> Result<(), ArmError> {
+        let components = self.get_arm_components(DpAddress::Default)?;
+        let interface = self.get_arm_interface()?;
+        crate::architecture::arm::component::enable_etm_trace(interface, &components, trace_id)
+    }
+
+    /// Stop the instruction trace of the ETM.
+    pub fn disable_etm_trace(&mut self) -> Result<(), ArmError> {
+        let components = self.get_arm_components(DpAddress::Default)?;
+        let interface = self.get_arm_interface()?;
+        crate::architecture::arm::component::disable_etm_trace(interface, &components)
+    }
+
+    /// Read the raw, undecoded ETM trace stream.
+    ///
+    /// When tracing into trace memory, only the data of the trace source `trace_id` is returned.
+    /// When tracing over SWO, the data is returned as received from the probe, which requires
+    /// the TPIU formatter to be enabled to separate ETM and ITM data.
+    pub fn read_etm_trace(&mut self, trace_id: u8) -> Result<Vec<u8>, ArmError> {
+        let sink = self
+            .configured_trace_sink
+            .as_ref()
+            .ok_or(ArmError::TracingUnconfigured)?;
+
+        match sink {
+            TraceSink::TraceMemory => {
+                let components = self.get_arm_components(DpAddress::Default)?;
+                let interface = self.get_arm_interface()?;
+                crate::architecture::arm::component::read_etm_trace_memory(
+                    interface,
+                    &components,
+                    trace_id,
+                )
+            }
+            TraceSink::Swo(_) | TraceSink::Tpiu(_) => self.read_trace_data(),
+        }
+    }
+
     /// Stop tracing from a given SWV unit
     pub fn remove_swv_data_trace(&mut self, unit: usize) -> Result<(), ArmError> {
         let components = self.get_arm_components(DpAddress::Default)?;
@@ -668,6 +1043,7 @@ impl Session {
         match self.interface {
             ArchitectureInterface::Arm(_) => Architecture::Arm,
             ArchitectureInterface::Riscv(_) => Architecture::Riscv,
+            ArchitectureInterface::Xtensa(_) => Architecture::Xtensa,
         }
     }
 
@@ -678,6 +1054,72 @@ impl Session {
                 .and_then(|mut core| core.clear_all_hw_breakpoints())
         })
     }
+
+    /// Adds the enabled TCMs of the ARMv7-R cores to the memory map.
+    ///
+    /// TCMs which overlap a memory region of the target description are not added,
+    /// and errors reading the TCMs are only logged.
+    fn add_tcm_regions(&mut self) {
+        let cores = self
+            .target
+            .cores
+            .iter()
+            .enumerate()
+            .filter(|(_, core)| {
+                core.core_type == CoreType::Armv7r
+                    && !matches!(
+                        &core.core_access_options,
+                        CoreAccessOptions::Arm(options) if options.lockstep_redundant
+                    )
+            })
+            .map(|(index, core)| (index, core.name.clone()))
+            .collect::<Vec<_>>();
+
+        for (index, name) in cores {
+            let regions = match self.read_tcm_regions(index) {
+                Ok(regions) => regions,
+                Err(error) => {
+                    tracing::warn!("Could not read the TCMs of core {}: {}", name, error);
+                    continue;
+                }
+            };
+
+            for mut region in regions {
+                let overlaps = self
+                    .target
+                    .memory_map
+                    .iter()
+                    .any(|existing| existing.address_range().intersects_range(&region.range));
+
+                if overlaps {
+                    tracing::debug!("{:?} of core {} is already in the memory map", region, name);
+                    continue;
+                }
+
+                tracing::debug!("Adding {:?} of core {} to the memory map", region, name);
+                region.cores = vec![name.clone()];
+                self.target.memory_map.push(MemoryRegion::Ram(region));
+            }
+        }
+    }
+
+    /// Reads the TCMs of a core, halting it while the TCM registers are read.
+    fn read_tcm_regions(&mut self, core_index: usize) -> Result<Vec<RamRegion>, Error> {
+        let mut core = self.core(core_index)?;
+
+        let was_running = !core.core_halted()?;
+        if was_running {
+            core.halt(Duration::from_millis(100))?;
+        }
+
+        let regions = core.tcm_regions();
+
+        if was_running {
+            core.run()?;
+        }
+
+        regions
+    }
 }
 
 // This test ensures that [Session] is fully [Send] + [Sync].
@@ -687,6 +1129,11 @@ static_assertions::assert_impl_all!(Session: Send);
 impl Drop for Session {
     #[tracing::instrument(name = "session_drop", skip(self))]
     fn drop(&mut self) {
+        // A non-intrusive session leaves the target as it is.
+        if self.attach_method == AttachMethod::NonIntrusive {
+            return;
+        }
+
         if let Err(err) = { 0..self.cores.len() }.try_for_each(|i| {
             self.core(i)
                 .and_then(|mut core| core.clear_all_hw_breakpoints())
@@ -694,6 +1141,13 @@ impl Drop for Session {
             tracing::warn!("Could not clear all hardware breakpoints: {:?}", err);
         }
 
+        if let Err(err) = { 0..self.cores.len() }.try_for_each(|i| {
+            self.core(i)
+                .and_then(|mut core| core.clear_all_hw_watchpoints())
+        }) {
+            tracing::warn!("Could not clear all hardware watchpoints: {:?}", err);
+        }
+
         if let Err(err) = { 0..self.cores.len() }.try_for_each(|i| {
             self.core(i)
                 .and_then(|mut core| core.debug_on_sw_breakpoint(false))
@@ -729,7 +1183,7 @@ impl Drop for Session {
 
                 let core_information = match &self.target.cores[i].core_access_options {
                     CoreAccessOptions::Arm(arm) => arm,
-                    CoreAccessOptions::Riscv(_) => unreachable!(),
+                    CoreAccessOptions::Riscv(_) | CoreAccessOptions::Xtensa(_) => unreachable!(),
                 };
 
                 let ap = core_information.ap;
@@ -772,7 +1226,7 @@ fn get_target_from_selector(
         TargetSelector::Unspecified(name) => crate::config::get_target_by_name(name)?,
         TargetSelector::Specified(target) => target,
         TargetSelector::Auto => {
-            let mut found_chip = None;
+            let mut found_chips = Vec::new();
 
             // At this point we do not know what the target is, so we cannot use the chip specific reset sequence.
             // Thus, we try just using a normal reset for target detection if we want to do so under reset.
@@ -799,7 +1253,19 @@ fn get_target_from_selector(
                                 None
                             });
 
-                        found_chip = found_arm_chip.map(ChipInfo::from);
+                        let manufacturer = found_arm_chip.as_ref().map(|info| info.manufacturer);
+                        found_chips.extend(found_arm_chip.map(ChipInfo::from));
+
+                        // The part number in the ROM table is often shared by a whole family,
+                        // so the vendor specific ID registers are used to distinguish the chips.
+                        match interface.memory_interface(MemoryAp::new(ApAddress { dp, ap: 0 })) {
+                            Ok(mut memory) => found_chips.extend(
+                                ChipInfo::read_from_vendor_registers(memory.as_mut(), manufacturer),
+                            ),
+                            Err(e) => {
+                                tracing::debug!("Unable to read the vendor ID registers: {}", e)
+                            }
+                        }
 
                         probe = interface.close();
                     }
@@ -812,13 +1278,17 @@ fn get_target_from_selector(
                 tracing::debug!("No ARM interface was present. Skipping Riscv autodetect.");
             }
 
-            if found_chip.is_none() && probe.has_riscv_interface() {
+            if found_chips.is_empty() && probe.has_riscv_interface() {
                 match probe.try_into_riscv_interface() {
                     Ok(mut interface) => {
                         let idcode = interface.read_idcode();
 
                         tracing::debug!("ID Code read over JTAG: {:x?}", idcode);
 
+                        if let Ok(idcode) = idcode {
+                            found_chips.extend(ChipInfo::from_jtag_idcode(idcode));
+                        }
+
                         probe = interface.close();
                     }
                     Err((returned_probe, err)) => {
@@ -830,14 +1300,36 @@ fn get_target_from_selector(
                 tracing::debug!("No RISCV interface was present. Skipping Riscv autodetect.");
             }
 
+            if found_chips.is_empty() && probe.has_xtensa_interface() {
+                match probe.try_into_xtensa_interface() {
+                    Ok(mut interface) => {
+                        let idcode = interface.read_idcode();
+
+                        tracing::debug!("ID Code read over JTAG: {:x?}", idcode);
+
+                        if let Ok(idcode) = idcode {
+                            found_chips.extend(ChipInfo::from_jtag_idcode(idcode));
+                        }
+
+                        probe = interface.close();
+                    }
+                    Err((returned_probe, err)) => {
+                        tracing::debug!("Error during autodetection of Xtensa chips: {}", err);
+                        probe = returned_probe;
+                    }
+                }
+            } else {
+                tracing::debug!("No Xtensa interface was present. Skipping Xtensa autodetect.");
+            }
+
             // Now we can deassert reset in case we asserted it before. This is always okay.
             probe.target_reset_deassert()?;
 
-            if let Some(chip) = found_chip {
-                crate::config::get_target_by_chip_info(chip)?
-            } else {
+            if found_chips.is_empty() {
                 return Err(Error::ChipNotFound(RegistryError::ChipAutodetectFailed));
             }
+
+            crate::config::get_target_by_chip_info(&found_chips)?
         }
     };
 
@@ -0,0 +1,240 @@
+//! A unified view of the console of the target, independent of the transport it uses.
+//!
+//! Firmware usually prints via RTT, ITM stimulus ports or semihosting. A [`TargetStream`] is
+//! created for the transport used by the firmware, and then provides its stdout, stderr and
+//! stdin the same way for all of them.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use probe_rs::semihosting::SemihostingOptions;
+//! use probe_rs::target_stream::TargetStream;
+//! # use probe_rs::{Permissions, Probe};
+//! use std::io::Write;
+//!
+//! # let probe = Probe::list_all()[0].open()?;
+//! # let mut session = probe.attach("somechip", Permissions::default())?;
+//! let mut stream = TargetStream::semihosting(0, SemihostingOptions::default());
+//!
+//! loop {
+//!     let output = stream.poll(&mut session)?;
+//!     std::io::stdout().write_all(&output.stdout)?;
+//!     std::io::stderr().write_all(&output.stderr)?;
+//!
+//!     if output.exit_code.is_some() {
+//!         break;
+//!     }
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+#[cfg(feature = "rtt")]
+use crate::rtt::{DownChannel, Rtt, UpChannel};
+use crate::{
+    architecture::arm::swo::{ItmDecoder, ItmPacket},
+    semihosting::{Semihosting, SemihostingOptions, SemihostingOutcome},
+    Error, Session,
+};
+
+/// The output of the target since the last poll.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TargetOutput {
+    /// The data the target wrote to stdout.
+    pub stdout: Vec<u8>,
+    /// The data the target wrote to stderr.
+    pub stderr: Vec<u8>,
+    /// The exit code, if the target requested to exit. This is only supported by semihosting.
+    pub exit_code: Option<i32>,
+}
+
+/// The transport used by the firmware, with its state.
+#[derive(Debug)]
+enum Transport {
+    #[cfg(feature = "rtt")]
+    Rtt {
+        core_index: usize,
+        stdout: Option<UpChannel>,
+        stderr: Option<UpChannel>,
+        stdin: Option<DownChannel>,
+    },
+    Itm {
+        decoder: ItmDecoder,
+        stdout_port: u8,
+        stderr_port: Option<u8>,
+    },
+    Semihosting {
+        core_index: usize,
+        semihosting: Box<Semihosting>,
+    },
+}
+
+/// The stdout, stderr and stdin of the target, over RTT, ITM or semihosting.
+#[derive(Debug)]
+pub struct TargetStream {
+    transport: Transport,
+    /// Input which was not sent to the target yet.
+    pending_input: Vec<u8>,
+}
+
+impl TargetStream {
+    /// Uses RTT channels of the core with index `core_index`.
+    ///
+    /// Up channel 0 is used for stdout, and down channel 0 for stdin. An up channel named
+    /// `stderr` is used for stderr. The channels are taken from `rtt`.
+    #[cfg(feature = "rtt")]
+    pub fn rtt(core_index: usize, rtt: &mut Rtt) -> Self {
+        let stderr = rtt
+            .up_channels()
+            .iter()
+            .find(|channel| channel.name() == Some("stderr"))
+            .map(|channel| channel.number());
+
+        Self::new(Transport::Rtt {
+            core_index,
+            stdout: rtt.up_channels().take(0),
+            stderr: stderr.and_then(|number| rtt.up_channels().take(number)),
+            stdin: rtt.down_channels().take(0),
+        })
+    }
+
+    /// Uses the ITM stimulus ports, which are read from the SWO trace data.
+    ///
+    /// The trace has to be set up with [`Session::setup_tracing`] before. ITM doesn't support input.
+    pub fn itm(stdout_port: u8, stderr_port: Option<u8>) -> Self {
+        Self::new(Transport::Itm {
+            decoder: ItmDecoder::new(),
+            stdout_port,
+            stderr_port,
+        })
+    }
+
+    /// Uses semihosting on the core with index `core_index`.
+    ///
+    /// The core is resumed after each semihosting operation when polling. The console of the
+    /// target is always captured, see [`SemihostingOptions::capture_console`].
+    pub fn semihosting(core_index: usize, options: SemihostingOptions) -> Self {
+        let semihosting = Semihosting::new(SemihostingOptions {
+            capture_console: true,
+            ..options
+        });
+
+        Self::new(Transport::Semihosting {
+            core_index,
+            semihosting: Box::new(semihosting),
+        })
+    }
+
+    fn new(transport: Transport) -> Self {
+        Self {
+            transport,
+            pending_input: Vec::new(),
+        }
+    }
+
+    /// Checks if the transport supports input to the target.
+    pub fn supports_input(&self) -> bool {
+        match &self.transport {
+            #[cfg(feature = "rtt")]
+            Transport::Rtt { stdin, .. } => stdin.is_some(),
+            Transport::Itm { .. } => false,
+            Transport::Semihosting { .. } => true,
+        }
+    }
+
+    /// Queues data for the stdin of the target, which is sent by the following polls.
+    ///
+    /// The data is discarded if the transport doesn't support input, see [`TargetStream::supports_input`].
+    pub fn write_stdin(&mut self, data: &[u8]) {
+        if self.supports_input() {
+            self.pending_input.extend_from_slice(data);
+        } else {
+            tracing::warn!(
+                "The target doesn't support input, discarding {} bytes.",
+                data.len()
+            );
+        }
+    }
+
+    /// Transfers the available data, and returns the output of the target since the last poll.
+    ///
+    /// This doesn't block. Call it periodically while the target is running.
+    pub fn poll(&mut self, session: &mut Session) -> Result<TargetOutput, Error> {
+        let mut output = TargetOutput::default();
+
+        match &mut self.transport {
+            #[cfg(feature = "rtt")]
+            Transport::Rtt {
+                core_index,
+                stdout,
+                stderr,
+                stdin,
+            } => {
+                let mut core = session.core(*core_index)?;
+
+                for (channel, output) in
+                    [(stdout, &mut output.stdout), (stderr, &mut output.stderr)]
+                {
+                    if let Some(channel) = channel {
+                        let mut buf = vec![0; channel.buffer_size()];
+                        let count = channel
+                            .read(&mut core, &mut buf)
+                            .map_err(anyhow::Error::from)?;
+                        output.extend_from_slice(&buf[..count]);
+                    }
+                }
+
+                if let Some(stdin) = stdin {
+                    if !self.pending_input.is_empty() {
+                        let count = stdin
+                            .write(&mut core, &self.pending_input)
+                            .map_err(anyhow::Error::from)?;
+                        self.pending_input.drain(..count);
+                    }
+                }
+            }
+            Transport::Itm {
+                decoder,
+                stdout_port,
+                stderr_port,
+            } => {
+                let data = session.read_trace_data()?;
+
+                for packet in decoder.decode(&data) {
+                    match packet {
+                        ItmPacket::Instrumentation { port, payload } if port == *stdout_port => {
+                            output.stdout.extend_from_slice(&payload)
+                        }
+                        ItmPacket::Instrumentation { port, payload }
+                            if Some(port) == *stderr_port =>
+                        {
+                            output.stderr.extend_from_slice(&payload)
+                        }
+                        ItmPacket::Overflow => {
+                            tracing::warn!("ITM overflow, target output was lost")
+                        }
+                        _ => (),
+                    }
+                }
+            }
+            Transport::Semihosting {
+                core_index,
+                semihosting,
+            } => {
+                let mut core = session.core(*core_index)?;
+
+                semihosting.push_console_input(&self.pending_input);
+                self.pending_input.clear();
+
+                if let Some(SemihostingOutcome::Exit(code)) = semihosting.handle(&mut core)? {
+                    output.exit_code = Some(code);
+                }
+
+                let console = semihosting.take_console_output();
+                output.stdout = console.stdout;
+                output.stderr = console.stderr;
+            }
+        }
+
+        Ok(output)
+    }
+}
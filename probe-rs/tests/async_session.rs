@@ -0,0 +1,81 @@
+use futures::executor::block_on;
+use probe_rs::asynch::AsyncSession;
+use probe_rs::{FakeProbe, MemoryInterface, Permissions};
+
+fn session() -> AsyncSession {
+    block_on(AsyncSession::open(|| {
+        let mut fake_probe = FakeProbe::new();
+        fake_probe.add_memory_region(0x2000_0000, vec![0; 16]);
+
+        fake_probe
+            .into_probe()
+            .attach("nrf51822_xxAC", Permissions::default())
+    }))
+    .unwrap()
+}
+
+#[test]
+fn memory_access() {
+    let session = session();
+
+    block_on(async {
+        session
+            .write(0, 0x2000_0001, vec![1, 2, 3, 4, 5])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            session.read(0, 0x2000_0000, 8).await.unwrap(),
+            vec![0, 1, 2, 3, 4, 5, 0, 0]
+        );
+
+        let word = session
+            .with_core(0, |core| core.read_word_32(0x2000_0004))
+            .await
+            .unwrap();
+        assert_eq!(word, 0x0000_0504);
+    });
+}
+
+#[test]
+fn requests_are_executed_in_order() {
+    let session = session();
+
+    let writes = (0..4u8).map(|i| session.write(0, 0x2000_0000 + u64::from(i), vec![i]));
+    block_on(futures::future::try_join_all(writes)).unwrap();
+
+    assert_eq!(
+        block_on(session.read(0, 0x2000_0000, 4)).unwrap(),
+        vec![0, 1, 2, 3]
+    );
+}
+
+#[test]
+fn open_fails() {
+    let result = block_on(AsyncSession::open(|| {
+        FakeProbe::new()
+            .into_probe()
+            .attach("unknown-target", Permissions::default())
+    }));
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn back_to_session() {
+    let session = session();
+    block_on(session.write(0, 0x2000_0000, vec![0xab])).unwrap();
+
+    let mut session = block_on(session.into_session());
+    assert_eq!(
+        session.core(0).unwrap().read_word_8(0x2000_0000).unwrap(),
+        0xab
+    );
+
+    // The session can be moved to a new thread again.
+    let session = AsyncSession::new(session);
+    assert_eq!(
+        block_on(session.read(0, 0x2000_0000, 1)).unwrap(),
+        vec![0xab]
+    );
+}
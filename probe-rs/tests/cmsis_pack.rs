@@ -0,0 +1,55 @@
+use std::path::{Path, PathBuf};
+
+use probe_rs::config::{cmsis_pack, get_target_by_name, MemoryRegion, TargetDescriptionSource};
+
+fn nrf_pack() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../target-gen/tests/test_data/NordicSemiconductor.nRF_DeviceFamilyPack.8.32.1.pack")
+}
+
+#[test]
+fn read_nrf_pack() {
+    let mut families = vec![];
+    cmsis_pack::read_pack_file(&nrf_pack(), &mut families).unwrap();
+
+    let family = families
+        .iter()
+        .find(|family| family.name == "nRF52 Series")
+        .expect("The nRF52 family is missing in the pack");
+
+    assert!(family.generated_from_pack);
+    assert_eq!(family.pack_file_release.as_deref(), Some("8.32.1"));
+    family.validate().unwrap();
+
+    let chip = family
+        .variants
+        .iter()
+        .find(|chip| chip.name == "nRF52840_xxAA")
+        .expect("The nRF52840 is missing in the pack");
+
+    assert_eq!(chip.cores.len(), 1);
+    assert!(chip.flash_algorithms.contains(&"nrf52xxx".to_owned()));
+    assert!(chip.memory_map.iter().any(|region| matches!(
+        region,
+        MemoryRegion::Nvm(region) if region.range == (0..0x10_0000)
+    )));
+
+    let algorithm = family
+        .flash_algorithms
+        .iter()
+        .find(|algorithm| algorithm.name == "nrf52xxx")
+        .unwrap();
+
+    assert!(!algorithm.instructions.is_empty());
+    assert_eq!(algorithm.flash_properties.page_size, 0x1000);
+}
+
+#[test]
+fn add_nrf_pack_to_registry() {
+    cmsis_pack::add_targets_from_pack(nrf_pack()).unwrap();
+
+    let target = get_target_by_name("nRF9160_xxAA").unwrap();
+
+    assert_eq!(*target.source(), TargetDescriptionSource::External);
+    assert!(!target.flash_algorithms.is_empty());
+}
@@ -0,0 +1,25 @@
+#!/bin/sh
+# Builds the binaries used by `tests/debug_info_formats.rs` from `main.c`.
+set -e
+
+CFLAGS="-g -O1 -fdebug-prefix-map=$(pwd)=/build"
+
+# Split DWARF, with the `.dwo` file next to the binary.
+gcc $CFLAGS -gsplit-dwarf -c main.c -o main.o
+gcc main.o -o split-dwarf
+
+# Split DWARF, combined into a DWARF package.
+gcc main.o -o split-dwarf-package
+llvm-dwp main.dwo -o split-dwarf-package.dwp
+rm main.o
+
+# Compressed debug sections.
+gcc $CFLAGS main.c -o uncompressed
+objcopy --compress-debug-sections=zlib-gabi uncompressed compressed-zlib
+objcopy --compress-debug-sections=zstd uncompressed compressed-zstd
+objcopy --compress-debug-sections=zlib-gnu uncompressed compressed-zlib-gnu
+
+# Debug information in an external file, referenced by `.gnu_debuglink`.
+objcopy --only-keep-debug uncompressed debuglink.debug
+objcopy --strip-debug --add-gnu-debuglink=debuglink.debug uncompressed debuglink
+rm uncompressed
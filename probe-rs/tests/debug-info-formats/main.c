@@ -0,0 +1,18 @@
+static volatile int COUNTER = 0;
+
+static inline __attribute__((always_inline)) void increment(int amount)
+{
+    COUNTER += amount;
+}
+
+__attribute__((noinline)) int add(int a, int b)
+{
+    return a + b;
+}
+
+int main(void)
+{
+    int sum = add(1, 2);
+    increment(sum);
+    return COUNTER;
+}
@@ -0,0 +1,73 @@
+//! Loading debug information which is compressed, split into `.dwo` files, or stored in a separate file.
+//!
+//! The binaries are built from `tests/debug-info-formats/main.c` with `tests/debug-info-formats/build.sh`.
+
+use probe_rs::debug::{debug_info::DebugInfo, DebugError};
+
+type TestResult = Result<(), DebugError>;
+
+const ADD: u64 = 0x1129;
+const COUNTER: u64 = 0x4014;
+
+fn assert_debug_info(file: &str) -> TestResult {
+    let di = DebugInfo::from_file(format!("tests/debug-info-formats/{file}"))?;
+
+    assert_eq!(di.function_name(ADD, false)?.as_deref(), Some("add"));
+
+    let location = di.get_source_location(ADD + 3).unwrap();
+    assert_eq!(location.file.as_deref(), Some("main.c"));
+    assert_eq!(location.line, Some(11));
+
+    // The call to `increment` is inlined into `main`.
+    let frames = di.get_source_frames(0x1144)?;
+    let names = frames
+        .iter()
+        .map(|frame| frame.function_name.as_deref())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec![Some("increment"), Some("main")]);
+    assert_eq!(frames[1].source_location.as_ref().unwrap().line, Some(16));
+
+    assert_eq!(di.get_static("COUNTER")?.address, COUNTER);
+
+    Ok(())
+}
+
+#[test]
+fn split_dwarf() -> TestResult {
+    assert_debug_info("split-dwarf")
+}
+
+#[test]
+fn split_dwarf_package() -> TestResult {
+    assert_debug_info("split-dwarf-package")
+}
+
+#[test]
+fn compressed_zlib() -> TestResult {
+    assert_debug_info("compressed-zlib")
+}
+
+#[test]
+fn compressed_zlib_gnu() -> TestResult {
+    assert_debug_info("compressed-zlib-gnu")
+}
+
+#[test]
+fn compressed_zstd() -> TestResult {
+    assert_debug_info("compressed-zstd")
+}
+
+#[test]
+fn gnu_debuglink() -> TestResult {
+    assert_debug_info("debuglink")
+}
+
+#[test]
+fn compressed_from_raw() -> TestResult {
+    let data = std::fs::read("tests/debug-info-formats/compressed-zlib")?;
+    let di = DebugInfo::from_raw(&data)?;
+
+    assert_eq!(di.function_name(ADD, false)?.as_deref(), Some("add"));
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use probe_rs::defmt::{DefmtDecoder, LogFrame};
+
+fn decoder() -> DefmtDecoder {
+    let elf = std::fs::read("tests/defmt/defmt.o").unwrap();
+
+    DefmtDecoder::from_elf(&elf).unwrap().unwrap()
+}
+
+#[test]
+fn decode_frames() {
+    let decoder = decoder();
+    let mut stream = decoder.stream();
+
+    // Index, timestamp and arguments of `defmt::info!("Hello, {=u8}!", 42)`.
+    stream.received(&[1, 0, 0x40, 0x42, 0x0f, 0x00, 42]);
+    // `defmt::error!("Something went wrong")`, split over two reads.
+    stream.received(&[2, 0, 0x80]);
+
+    assert_eq!(
+        stream.next_frame().unwrap(),
+        Some(LogFrame {
+            level: Some(tracing::Level::INFO),
+            timestamp: Some("1.000000".to_string()),
+            message: "Hello, 42!".to_string(),
+            location: None,
+        })
+    );
+    assert_eq!(stream.next_frame().unwrap(), None);
+
+    stream.received(&[0x84, 0x1e, 0x00]);
+
+    let frame = stream.next_frame().unwrap().unwrap();
+    assert_eq!(frame.to_string(), "2.000000 ERROR Something went wrong");
+
+    stream.received(&[3, 0, 0, 0, 0, 0]);
+
+    let frame = stream.next_frame().unwrap().unwrap();
+    assert_eq!(frame.level, None);
+    assert_eq!(frame.to_string(), "0.000000 No level");
+}
+
+#[test]
+fn elf_without_defmt() {
+    let elf = std::fs::read("tests/probe-rs-debugger-test").unwrap();
+
+    assert!(DefmtDecoder::from_elf(&elf).unwrap().is_none());
+}
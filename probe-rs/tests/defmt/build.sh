@@ -0,0 +1,5 @@
+#!/bin/sh
+# Builds the object file used by `tests/defmt.rs` from `defmt.s`.
+set -e
+
+as defmt.s -o defmt.o
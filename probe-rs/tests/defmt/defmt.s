@@ -0,0 +1,18 @@
+# A minimal defmt table, as it would be emitted by the `defmt` macros.
+#
+# The address of each symbol in the `.defmt` section is the index of the log message,
+# and its name describes the message as JSON.
+    .section .defmt,"",@progbits
+    .globl "_defmt_version_ = 4"
+    .set "_defmt_version_ = 4", 1
+    .globl "_defmt_encoding_ = raw"
+    .set "_defmt_encoding_ = raw", 1
+    .byte 0
+"{\"package\":\"test\",\"tag\":\"defmt_info\",\"data\":\"Hello, {=u8}!\",\"disambiguator\":\"1\",\"crate_name\":\"test\"}":
+    .byte 0
+"{\"package\":\"test\",\"tag\":\"defmt_error\",\"data\":\"Something went wrong\",\"disambiguator\":\"2\",\"crate_name\":\"test\"}":
+    .byte 0
+"{\"package\":\"test\",\"tag\":\"defmt_println\",\"data\":\"No level\",\"disambiguator\":\"3\",\"crate_name\":\"test\"}":
+    .byte 0
+"{\"package\":\"test\",\"tag\":\"defmt_timestamp\",\"data\":\"{=u32:us}\",\"disambiguator\":\"4\",\"crate_name\":\"test\"}":
+    .byte 0
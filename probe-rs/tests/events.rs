@@ -0,0 +1,47 @@
+use std::sync::{Arc, Mutex};
+
+use probe_rs::events::{clear_event_sink, set_event_sink, Event, Operation};
+use probe_rs::{FakeProbe, Permissions};
+
+#[test]
+fn attach_events() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+
+    let sink_events = events.clone();
+    set_event_sink(move |event: &Event| sink_events.lock().unwrap().push(event.clone()));
+
+    FakeProbe::new()
+        .into_probe()
+        .attach("nrf51822_xxAC", Permissions::default())
+        .unwrap();
+
+    assert!(FakeProbe::new()
+        .into_probe()
+        .attach("unknown-chip", Permissions::default())
+        .is_err());
+
+    clear_event_sink();
+
+    let events = events.lock().unwrap();
+    assert_eq!(
+        events[..2],
+        [
+            Event::OperationStarted {
+                operation: Operation::Attach
+            },
+            Event::OperationFinished {
+                operation: Operation::Attach,
+                success: true,
+                error: None
+            },
+        ]
+    );
+    assert!(matches!(
+        &events[3],
+        Event::OperationFinished {
+            operation: Operation::Attach,
+            success: false,
+            error: Some(_),
+        }
+    ));
+}
@@ -1,4 +1,12 @@
-use probe_rs::{flashing::DownloadOptions, FakeProbe, Permissions, Probe};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use probe_rs::{
+    flashing::{flash_many, BinOptions, DownloadOptions, FlashError, FlashJob, Format},
+    FakeProbe, Permissions, Probe,
+};
 
 /// A chip where the flash algorithm's range is greater than the NVM range.
 #[test]
@@ -47,3 +55,78 @@ fn flash_dry_run_mimxrt1010() {
         .commit(&mut session, flash_options)
         .expect("Failed to flash in dry run mode.");
 }
+
+/// Multiple targets flashed concurrently, each with its own probe.
+#[test]
+fn flash_many_dry_run() {
+    let path = std::env::temp_dir().join("probe-rs-flash-many-dry-run.bin");
+    std::fs::write(&path, [0x1, 0x2, 0x3, 0x4]).unwrap();
+
+    let jobs = (0..2)
+        .map(|_| {
+            let probe = Probe::from_specific_probe(Box::new(FakeProbe::new()));
+            let session = probe
+                .attach("stm32wb55ccux", Permissions::default())
+                .expect("Failed to attach with 'fake' probe.");
+
+            FlashJob {
+                session,
+                files: vec![(
+                    path.clone(),
+                    Format::Bin(BinOptions {
+                        base_address: Some(0x8000000),
+                        skip: 0,
+                    }),
+                )],
+            }
+        })
+        .collect();
+
+    let events = Arc::new(AtomicUsize::new(0));
+    let counter = events.clone();
+
+    let results = flash_many(
+        jobs,
+        || {
+            let mut options = DownloadOptions::new();
+            options.dry_run = true;
+            options
+        },
+        move |_, _| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        },
+    );
+
+    assert_eq!(results.len(), 2);
+    for result in &results {
+        assert!(result.result.is_ok());
+    }
+
+    // A dry run reports the failure of each phase, for each target.
+    assert_eq!(events.load(Ordering::Relaxed), 6);
+}
+
+/// Preserved ranges must be located in the flash.
+#[test]
+fn flash_dry_run_preserve_ram() {
+    let probe = Probe::from_specific_probe(Box::new(FakeProbe::new()));
+
+    let mut session = probe
+        .attach("stm32wb55ccux", Permissions::default())
+        .expect("Failed to attach with 'fake' probe.");
+
+    let mut flasher = session.target().flash_loader();
+
+    flasher
+        .add_data(0x8000000, &[0x1, 0x2, 0x3, 0x4])
+        .expect("Failed to add flash");
+
+    let mut flash_options = DownloadOptions::new();
+
+    flash_options.dry_run = true;
+    flash_options.preserve_ranges.push(0x2000_0000..0x2000_0100);
+
+    let result = flasher.commit(&mut session, flash_options);
+
+    assert!(matches!(result, Err(FlashError::NoSuitableNvm { .. })));
+}
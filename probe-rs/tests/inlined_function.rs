@@ -80,3 +80,56 @@ fn function_name_of_non_inlined_function() -> TestResult {
 
     Ok(())
 }
+
+#[test]
+fn breakpoint_locations_of_inlined_function() -> TestResult {
+    let di = DebugInfo::from_file("tests/inlined-function").unwrap();
+
+    let locations = di.get_function_breakpoint_locations("blink_on")?;
+
+    assert_eq!(
+        locations
+            .iter()
+            .map(|location| location.address)
+            .collect::<Vec<_>>(),
+        vec![0x15e]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn source_frames_of_inlined_function() -> TestResult {
+    let di = DebugInfo::from_file("tests/inlined-function").unwrap();
+
+    let frames = di.get_source_frames(0x15e)?;
+
+    let names = frames
+        .iter()
+        .map(|frame| frame.function_name.as_deref())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec![Some("blink_on"), Some("__cortex_m_rt_main")]);
+
+    assert!(frames[0].is_inlined);
+    assert!(!frames[1].is_inlined);
+
+    // The caller frame points at the call site of the inlined function.
+    let call_site = frames[1].source_location.as_ref().unwrap();
+    assert_eq!(call_site.file.as_deref(), Some("main.rs"));
+    assert!(call_site.line.is_some());
+
+    Ok(())
+}
+
+#[test]
+fn source_frames_of_non_inlined_function() -> TestResult {
+    let di = DebugInfo::from_file("tests/inlined-function").unwrap();
+
+    let frames = di.get_source_frames(0xf4)?;
+
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].function_name.as_deref(), Some("blink_off"));
+    assert!(!frames[0].is_inlined);
+
+    Ok(())
+}
@@ -0,0 +1,176 @@
+use std::path::Path;
+
+use probe_rs::config::cmsis_pack;
+use probe_rs::peripherals::{PeripheralError, Peripherals, ReadPolicy};
+use probe_rs::{FakeProbe, MemoryInterface, Permissions, Session};
+
+const SVD: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<device>
+  <name>TEST</name>
+  <addressUnitBits>8</addressUnitBits>
+  <width>32</width>
+  <size>32</size>
+  <access>read-write</access>
+  <peripherals>
+    <peripheral>
+      <name>UART0</name>
+      <baseAddress>0x40002000</baseAddress>
+      <registers>
+        <register>
+          <name>STATUS</name>
+          <addressOffset>0x0</addressOffset>
+          <fields>
+            <field>
+              <name>ERROR</name>
+              <bitOffset>0</bitOffset>
+              <bitWidth>1</bitWidth>
+              <modifiedWriteValues>oneToClear</modifiedWriteValues>
+            </field>
+            <field>
+              <name>ENABLE</name>
+              <bitOffset>4</bitOffset>
+              <bitWidth>4</bitWidth>
+            </field>
+          </fields>
+        </register>
+        <register>
+          <name>RXD</name>
+          <addressOffset>0x4</addressOffset>
+          <size>8</size>
+          <access>read-only</access>
+          <readAction>modify</readAction>
+        </register>
+        <register>
+          <name>TXD</name>
+          <addressOffset>0x8</addressOffset>
+          <access>write-only</access>
+        </register>
+      </registers>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
+
+fn session() -> Session {
+    let mut fake_probe = FakeProbe::new();
+    fake_probe.add_memory_region(0x4000_2000, vec![0; 16]);
+
+    let mut session = fake_probe
+        .into_probe()
+        .attach("nrf51822_xxAC", Permissions::default())
+        .unwrap();
+    session.set_peripherals(Peripherals::from_svd(SVD).unwrap());
+
+    session
+}
+
+#[test]
+fn register_access() {
+    let mut session = session();
+
+    let mut status = session
+        .peripheral("UART0")
+        .unwrap()
+        .register("STATUS")
+        .unwrap();
+    status.write(0x31).unwrap();
+    assert_eq!(status.read().unwrap(), 0x31);
+
+    assert!(matches!(
+        session
+            .peripheral("UART0")
+            .unwrap()
+            .register("TXD")
+            .unwrap()
+            .read(),
+        Err(PeripheralError::NotReadable(_))
+    ));
+    assert!(matches!(
+        session
+            .peripheral("UART0")
+            .unwrap()
+            .register("RXD")
+            .unwrap()
+            .write(1),
+        Err(PeripheralError::NotWritable(_))
+    ));
+    assert!(matches!(
+        session.peripheral("UART1"),
+        Err(PeripheralError::UnknownPeripheral(_))
+    ));
+}
+
+#[test]
+fn field_access() {
+    let mut session = session();
+    session
+        .core(0)
+        .unwrap()
+        .write_word_32(0x4000_2000, 0x1)
+        .unwrap();
+
+    let mut enable = session
+        .peripheral("UART0")
+        .unwrap()
+        .register("STATUS")
+        .unwrap()
+        .field("ENABLE")
+        .unwrap();
+    enable.write(0x8).unwrap();
+    assert_eq!(enable.read().unwrap(), 0x8);
+    assert!(matches!(
+        enable.write(0x10),
+        Err(PeripheralError::ValueTooLarge { .. })
+    ));
+
+    // The "write one to clear" field is not cleared by writing another field.
+    assert_eq!(
+        session.core(0).unwrap().read_word_32(0x4000_2000).unwrap(),
+        0x80
+    );
+}
+
+#[test]
+fn read_policy() {
+    let mut session = session();
+
+    assert!(matches!(
+        session
+            .peripheral("UART0")
+            .unwrap()
+            .register("RXD")
+            .unwrap()
+            .read(),
+        Err(PeripheralError::ReadSideEffects(_))
+    ));
+
+    session
+        .peripherals_mut()
+        .unwrap()
+        .set_read_policy(ReadPolicy::AllowSideEffects);
+
+    assert_eq!(
+        session
+            .peripheral("UART0")
+            .unwrap()
+            .register("RXD")
+            .unwrap()
+            .read()
+            .unwrap(),
+        0
+    );
+}
+
+#[test]
+fn svd_from_pack() {
+    let pack = std::fs::File::open(Path::new(env!("CARGO_MANIFEST_DIR")).join(
+        "../target-gen/tests/test_data/NordicSemiconductor.nRF_DeviceFamilyPack.8.32.1.pack",
+    ))
+    .unwrap();
+
+    let svd = cmsis_pack::read_svd(pack, "nRF52840_xxAA")
+        .unwrap()
+        .expect("The pack contains no SVD file for the nRF52840");
+
+    assert!(svd.contains("<name>nrf52840</name>"));
+}
@@ -0,0 +1,31 @@
+use probe_rs::rtt::{find_rtt_control_block_in_raw_file, ScanRegion};
+
+#[test]
+fn control_block_from_elf() {
+    let elf = std::fs::read("tests/probe-rs-debugger-test").unwrap();
+
+    assert_eq!(
+        find_rtt_control_block_in_raw_file(&elf).unwrap(),
+        Some(0x2000004c)
+    );
+    assert!(matches!(
+        ScanRegion::from_elf(&elf).unwrap(),
+        ScanRegion::Exact(0x2000004c)
+    ));
+}
+
+#[test]
+fn elf_without_control_block() {
+    let elf = std::fs::read("tests/inlined-function").unwrap();
+
+    assert_eq!(find_rtt_control_block_in_raw_file(&elf).unwrap(), None);
+    assert!(matches!(
+        ScanRegion::from_elf(&elf).unwrap(),
+        ScanRegion::Ram
+    ));
+}
+
+#[test]
+fn invalid_elf() {
+    assert!(find_rtt_control_block_in_raw_file(b"not an ELF file").is_err());
+}
@@ -105,3 +105,45 @@ fn find_non_existing_unit_by_path() {
         .get_breakpoint_location(unit_path, 14, None)
         .is_err());
 }
+
+#[test]
+fn breakpoint_locations_for_line() {
+    let di = DebugInfo::from_file("tests/probe-rs-debugger-test").unwrap();
+
+    let path = Path::new("/Users/jacknoppe/dev/probe-rs-debugger-test/src/main.rs");
+
+    let locations = di.get_breakpoint_locations(path, 240, None).unwrap();
+
+    assert_eq!(
+        locations
+            .iter()
+            .map(|location| location.address)
+            .collect::<Vec<_>>(),
+        vec![0x80006EA]
+    );
+}
+
+#[test]
+fn breakpoint_locations_for_function() {
+    let di = DebugInfo::from_file("tests/probe-rs-debugger-test").unwrap();
+
+    // The plain name and the qualified name resolve to the same location, after the prologue.
+    for name in ["delay", "cortex_m::asm::delay"] {
+        let locations = di.get_function_breakpoint_locations(name).unwrap();
+
+        assert_eq!(
+            locations
+                .iter()
+                .map(|location| (location.address, location.source_location.line))
+                .collect::<Vec<_>>(),
+            vec![(0x8000478, Some(28))],
+            "Unexpected locations for function {name}"
+        );
+    }
+
+    // Only complete path segments are matched.
+    assert!(di
+        .get_function_breakpoint_locations("asm::dela")
+        .unwrap()
+        .is_empty());
+}
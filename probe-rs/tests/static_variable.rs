@@ -0,0 +1,34 @@
+use probe_rs::debug::debug_info::DebugInfo;
+
+#[test]
+fn static_variable_by_name() {
+    let di = DebugInfo::from_file("tests/probe-rs-debugger-test").unwrap();
+
+    for name in ["U32", "probe_rs_debugger_test::U32"] {
+        let variable = di.get_static(name).unwrap();
+
+        assert_eq!(variable.name, "probe_rs_debugger_test::U32");
+        assert_eq!(variable.address, 0x2000_0020);
+        assert_eq!(variable.size, Some(4));
+        assert_eq!(variable.type_name.as_deref(), Some("u32"));
+        assert!(variable.is_base_type);
+    }
+}
+
+#[test]
+fn static_variable_in_function() {
+    let di = DebugInfo::from_file("tests/probe-rs-debugger-test").unwrap();
+
+    let variable = di.get_static("LOCAL_STATIC").unwrap();
+
+    assert_eq!(variable.address, 0x2000_0040);
+    assert_eq!(variable.size, Some(8));
+}
+
+#[test]
+fn ambiguous_static_variable() {
+    let di = DebugInfo::from_file("tests/probe-rs-debugger-test").unwrap();
+
+    assert!(di.get_static("_RTT_CHANNEL_BUFFER").is_err());
+    assert!(di.get_static("NON_EXISTING").is_err());
+}
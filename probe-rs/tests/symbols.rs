@@ -0,0 +1,50 @@
+use probe_rs::debug::{symbols::Symbols, DebugError};
+
+type TestResult = Result<(), DebugError>;
+
+#[test]
+fn symbol_at_address() -> TestResult {
+    let symbols = Symbols::from_file("tests/probe-rs-debugger-test")?;
+
+    // The Thumb bit is not part of the address.
+    let reset = symbols.symbol_at(0x8000400).unwrap();
+    assert_eq!(reset.name, "Reset");
+    assert_eq!(reset.address, 0x8000400);
+
+    let reset = symbols.symbol_at(0x8000410).unwrap();
+    assert_eq!(reset.name, "Reset");
+
+    // Of the aliases at the same address, the one with a size is preferred.
+    let handler = symbols.symbol_at(0x8001420).unwrap();
+    assert_eq!(handler.name, "DefaultHandler_");
+
+    let variable = symbols.symbol_at(0x20000022).unwrap();
+    assert_eq!(variable.name, "probe_rs_debugger_test::U32");
+    assert_eq!(
+        variable.mangled_name,
+        "_ZN22probe_rs_debugger_test3U3217h888b9e0a719234ecE"
+    );
+    assert!(!variable.is_function);
+
+    assert!(symbols.symbol_at(0x10).is_none());
+
+    Ok(())
+}
+
+#[test]
+fn address_of_symbol() -> TestResult {
+    let symbols = Symbols::from_file("tests/probe-rs-debugger-test")?;
+
+    assert_eq!(symbols.address_of("Reset"), Some(0x8000400));
+    assert_eq!(
+        symbols.address_of("probe_rs_debugger_test::U32"),
+        Some(0x20000020)
+    );
+    assert_eq!(
+        symbols.address_of("_ZN22probe_rs_debugger_test3U3217h888b9e0a719234ecE"),
+        Some(0x20000020)
+    );
+    assert_eq!(symbols.address_of("does_not_exist"), None);
+
+    Ok(())
+}
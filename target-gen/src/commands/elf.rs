@@ -1,8 +1,9 @@
 use anyhow::{bail, Context, Result};
+use probe_rs::config::cmsis_pack::extract_flash_algo;
 use probe_rs::CoreType;
 use probe_rs_target::{
     ArmCoreAccessOptions, Chip, ChipFamily, Core, CoreAccessOptions, MemoryRegion, NvmRegion,
-    RamRegion, TargetDescriptionSource::BuiltIn,
+    RamRegion, ResetType, TargetDescriptionSource::BuiltIn,
 };
 use std::{
     fs::{File, OpenOptions},
@@ -10,8 +11,6 @@ use std::{
     path::Path,
 };
 
-use crate::parser::extract_flash_algo;
-
 /// Prepare a target config based on an ELF file containing a flash algorithm.
 pub fn cmd_elf(
     file: &Path,
@@ -84,7 +83,10 @@ pub fn cmd_elf(
                         psel: 0,
                         debug_base: None,
                         cti_base: None,
+                        lockstep_redundant: false,
+                        cache_maintenance: false,
                     }),
+                    memory_aliases: vec![],
                 }],
                 part: None,
                 name: "<chip name>".to_owned(),
@@ -94,17 +96,25 @@ pub fn cmd_elf(
                         range: 0..0x2000,
                         cores: vec!["main".to_owned()],
                         name: None,
+                        min_write_size: None,
+                        no_overwrite: false,
+                        access: None,
                     }),
                     MemoryRegion::Ram(RamRegion {
                         is_boot_memory: true,
                         range: 0x1_0000..0x2_0000,
                         cores: vec!["main".to_owned()],
                         name: None,
+                        access: None,
                     }),
                 ],
                 flash_algorithms: vec![algorithm_name],
+                sequences: vec![],
+                bank_swap: None,
+                default_reset_type: ResetType::default(),
             }],
             flash_algorithms: vec![algorithm],
+            debug_sequence: None,
             source: BuiltIn,
         };
 
@@ -4,9 +4,9 @@ use std::path::Path;
 use std::rc::Rc;
 use std::time::Instant;
 
-use anyhow::Result;
+use anyhow::{ensure, Result};
 use colored::Colorize;
-use probe_rs::flashing::erase_all;
+use probe_rs::flashing::{erase_all, test_algorithm};
 use probe_rs::MemoryInterface;
 use probe_rs::{
     flashing::{erase_sectors, DownloadOptions, FlashLoader, FlashProgress},
@@ -154,13 +154,31 @@ pub fn cmd_test(
     let mut loader = session.target().flash_loader();
     let data = (0..data_size).map(|n| (n % 256) as u8).collect::<Vec<_>>();
     loader.add_data(flash_properties.address_range.start + 1, &data)?;
-    run_flash_download(&mut session, loader, progress, false)?;
+    run_flash_download(&mut session, loader, progress.clone(), false)?;
     let mut readback = vec![0; data_size as usize];
     session
         .core(0)?
         .read_8(flash_properties.address_range.start + 1, &mut readback)?;
     assert_eq!(readback, data);
 
+    println!("{test}: Testing the first and the last sector of the flash ...");
+    let report = test_algorithm(&mut session, ALGORITHM_NAME, Some(progress))?;
+    for case in &report.cases {
+        match &case.error {
+            None => println!(
+                "    {} {} ({:?})",
+                "Passed".green(),
+                case.name,
+                case.duration
+            ),
+            Some(error) => println!("    {} {}: {error}", "Failed".red(), case.name),
+        }
+    }
+    ensure!(
+        report.passed(),
+        "The flash algorithm failed some of the tests"
+    );
+
     Ok(())
 }
 
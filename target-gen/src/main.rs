@@ -1,13 +1,10 @@
-pub mod algorithm_binary;
 pub mod commands;
 pub mod fetch;
-pub mod flash_device;
 pub mod generate;
-pub mod parser;
 
 use anyhow::{ensure, Context, Result};
 use clap::Parser;
-use probe_rs::config::ChipFamily;
+use probe_rs::config::{validate_target, ChipFamily, Severity};
 use std::{
     env::current_dir,
     fs::create_dir,
@@ -99,6 +96,12 @@ enum TargetGen {
         /// The path to the ELF.
         target_artifact: PathBuf,
     },
+    /// Checks a target description for mistakes, like overlapping memory regions
+    /// or flash algorithms which don't fit into RAM.
+    Validate {
+        /// The path of the YAML target description.
+        target: PathBuf,
+    },
 }
 
 fn main() -> Result<()> {
@@ -140,6 +143,7 @@ fn main() -> Result<()> {
             template_path.as_path(),
             definition_export_path.as_path(),
         )?,
+        TargetGen::Validate { target } => cmd_validate(&target)?,
     }
 
     println!("Finished in {:?}", t.elapsed());
@@ -147,6 +151,31 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// Handle the validate subcommand. Prints all problems of the target description,
+/// and fails if any of them is an error.
+fn cmd_validate(target: &Path) -> Result<()> {
+    let yaml = std::fs::read_to_string(target)
+        .context(format!("Failed to read file '{}'.", target.display()))?;
+
+    let diagnostics = validate_target(&yaml);
+
+    for diagnostic in &diagnostics {
+        println!("{diagnostic}");
+    }
+
+    ensure!(
+        diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.severity != Severity::Error),
+        "The target description '{}' is invalid.",
+        target.display()
+    );
+
+    println!("The target description '{}' is valid.", target.display());
+
+    Ok(())
+}
+
 /// Handle the pack subcommand. `input` is either the path
 /// to a CMSIS-Pack file, or a directory containing at least one .pdsc file.
 ///